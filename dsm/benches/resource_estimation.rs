@@ -0,0 +1,148 @@
+// DSM Resource Estimation Calibration Benchmark
+//
+// This benchmark sets the measured execution time of representative
+// operations against the `cpu_units` estimated for them, to calibrate the
+// per-operation cost table and `NS_PER_CPU_UNIT`:
+// 1. Operations of growing size - measures the hashing cost of the payload
+// 2. Balance operations on growing states - measures the balance scan cost
+// 3. Nested dependency wrappers - measures the cost of wrapped operations
+//
+// Each case is registered under its estimated `cpu_units`, and the measured
+// nanoseconds per estimated unit are printed before the Criterion runs.
+
+use std::time::Instant;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsm::core::resources::{estimate_resources, NS_PER_CPU_UNIT};
+use dsm::core::state_machine::{generate_transition_entropy, transition};
+use dsm::types::operations::Operation;
+use dsm::types::state_types::{DeviceInfo, State};
+use dsm::types::token_types::Balance;
+
+/// Iterations timed for each printed calibration ratio
+const CALIBRATION_ITERATIONS: u32 = 200;
+
+/// Create a genesis state holding `balances` token balances
+fn create_benchmark_state(balances: usize) -> State {
+    let device_info = DeviceInfo::new("benchmark_device", vec![1, 2, 3, 4]);
+    let mut state = State::new_genesis(vec![5, 6, 7, 8], device_info);
+    for i in 0..balances {
+        state
+            .token_balances
+            .insert(format!("benchmark_device.TOKEN{}", i), Balance::new(1_000));
+    }
+    state.hash = state.compute_hash().unwrap();
+    state
+}
+
+fn generic(data_len: usize) -> Operation {
+    Operation::Generic {
+        operation_type: "benchmark_op".to_string(),
+        data: vec![7; data_len],
+        message: "calibration".to_string(),
+    }
+}
+
+fn mint() -> Operation {
+    Operation::Mint {
+        amount: Balance::new(10),
+        token_id: "TOKEN0".to_string(),
+        authorized_by: "benchmark_issuer".to_string(),
+        proof_of_authorization: vec![1, 2, 3],
+        message: "calibration".to_string(),
+    }
+}
+
+fn nested(depth: usize) -> Operation {
+    (0..depth).fold(generic(64), |inner, level| Operation::WithDependencies {
+        dependencies: vec![[level as u8; 32]],
+        inner: Box::new(inner),
+    })
+}
+
+/// Representative cases: label, starting state and operation
+fn calibration_cases() -> Vec<(String, State, Operation)> {
+    let mut cases = Vec::new();
+    for data_len in [64, 4 * 1024, 64 * 1024] {
+        cases.push((
+            format!("generic_{}b", data_len),
+            create_benchmark_state(0),
+            generic(data_len),
+        ));
+    }
+    for balances in [1, 100, 1_000] {
+        cases.push((
+            format!("mint_{}_balances", balances),
+            create_benchmark_state(balances),
+            mint(),
+        ));
+    }
+    for depth in [1, 4, 16] {
+        cases.push((
+            format!("nested_{}", depth),
+            create_benchmark_state(0),
+            nested(depth),
+        ));
+    }
+    cases
+}
+
+/// Execute one transition of `op` on top of `state`
+fn execute(state: &State, op: &Operation) -> State {
+    let entropy = generate_transition_entropy(state, op).unwrap();
+    transition::apply_transition(state, op, &entropy).unwrap()
+}
+
+/// Print the measured nanoseconds per estimated CPU unit of each case
+fn print_calibration(cases: &[(String, State, Operation)]) {
+    println!("Resource estimation calibration (assumed {NS_PER_CPU_UNIT} ns/unit)");
+    for (label, state, op) in cases {
+        let estimate = estimate_resources(op, state);
+        let start = Instant::now();
+        for _ in 0..CALIBRATION_ITERATIONS {
+            execute(state, op);
+        }
+        let measured_ns = start.elapsed().as_nanos() / u128::from(CALIBRATION_ITERATIONS);
+        println!(
+            "  {label}: {} cpu units, {measured_ns} ns measured, {:.2} ns/unit",
+            estimate.cpu_units,
+            measured_ns as f64 / estimate.cpu_units as f64
+        );
+    }
+}
+
+/// Benchmark representative operations against their estimated cost
+fn resource_estimation_benchmark(c: &mut Criterion) {
+    // Initialize DSM subsystems
+    dsm::initialize();
+
+    let cases = calibration_cases();
+    print_calibration(&cases);
+
+    let mut group = c.benchmark_group("Resource Estimation");
+    for (label, state, op) in &cases {
+        let cpu_units = estimate_resources(op, state).cpu_units;
+        group.bench_with_input(
+            BenchmarkId::new(label.as_str(), cpu_units),
+            &(state, op),
+            |b, (state, op)| b.iter(|| execute(state, op)),
+        );
+    }
+
+    // Estimation itself runs before every transition and must stay cheap
+    let (_, state, op) = &cases[cases.len() - 1];
+    group.bench_function("estimate_nested_16", |b| {
+        b.iter(|| estimate_resources(op, state))
+    });
+
+    group.finish();
+}
+
+// Register benchmark group with appropriate sampling parameters
+criterion_group!(
+    name = resource_estimation_benchmarks;
+    config = Criterion::default().sample_size(20);
+    targets = resource_estimation_benchmark
+);
+
+criterion_main!(resource_estimation_benchmarks);
@@ -0,0 +1,387 @@
+//! Ethereum Bridge Adapter
+//!
+//! Bridges DSM tokens to an Ethereum bridge contract. Relayers subscribe to
+//! new locks and submit [`EthereumBridgeAdapter::encode_lock_proof`] as the
+//! calldata of the contract's `submitLock` function:
+//!
+//! ```solidity
+//! function submitLock(
+//!     bytes32 lockId,
+//!     string tokenId,
+//!     uint256 amount,
+//!     address recipient,
+//!     bytes32 stateHash,
+//!     bytes32 merkleRoot,
+//!     bytes32[] proof,
+//!     uint256 leafIndex
+//! ) external;
+//! ```
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use sha3::{Digest, Keccak256};
+use tokio::sync::broadcast;
+
+use super::{BridgeAdapter, BridgeLock};
+use crate::core::identity::Identity;
+use crate::types::error::DsmError;
+use crate::types::state_types::State;
+use crate::types::token_types::Balance;
+
+/// Name of the Ethereum chain in bridge locks
+pub const ETHEREUM_CHAIN: &str = "ethereum";
+
+/// Signature of the bridge contract function that accepts locks
+const SUBMIT_LOCK_SIGNATURE: &str =
+    "submitLock(bytes32,string,uint256,address,bytes32,bytes32,bytes32[],uint256)";
+
+/// Locks buffered for each relayer subscribed to new locks
+const LOCK_CHANNEL_CAPACITY: usize = 100;
+
+/// A value in the ABI encoding of a contract call
+enum AbiValue<'a> {
+    Bytes32([u8; 32]),
+    Uint(u64),
+    Address([u8; 20]),
+    String(&'a str),
+    Bytes32Array(&'a [[u8; 32]]),
+}
+
+/// Bridge adapter for an Ethereum bridge contract
+pub struct EthereumBridgeAdapter {
+    /// SPHINCS+ public key with which the bridge signs releases
+    bridge_public_key: Vec<u8>,
+
+    /// IDs of the locks already released
+    released: Mutex<HashSet<String>>,
+
+    /// Live feed of new locks for relayers
+    lock_tx: broadcast::Sender<BridgeLock>,
+}
+
+impl EthereumBridgeAdapter {
+    /// Create an adapter for a bridge that signs releases with `bridge_public_key`
+    pub fn new(bridge_public_key: Vec<u8>) -> Self {
+        Self {
+            bridge_public_key,
+            released: Mutex::new(HashSet::new()),
+            lock_tx: broadcast::channel(LOCK_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Subscribe to locks as they are created
+    pub fn subscribe_locks(&self) -> broadcast::Receiver<BridgeLock> {
+        self.lock_tx.subscribe()
+    }
+
+    /// Calldata submitting a lock to the bridge contract's `submitLock`
+    pub fn encode_lock_proof(lock: &BridgeLock) -> Result<Vec<u8>, DsmError> {
+        let lock_id: [u8; 32] = hex::decode(&lock.lock_id)
+            .ok()
+            .and_then(|id| id.try_into().ok())
+            .ok_or_else(|| {
+                DsmError::invalid_parameter(format!("Invalid bridge lock ID {}", lock.lock_id))
+            })?;
+        let state_hash: [u8; 32] =
+            lock.lock_state.hash.clone().try_into().map_err(|_| {
+                DsmError::invalid_parameter("Bridge lock state hash is not 32 bytes")
+            })?;
+
+        let mut calldata = Keccak256::digest(SUBMIT_LOCK_SIGNATURE.as_bytes())[..4].to_vec();
+        calldata.extend(abi_encode(&[
+            AbiValue::Bytes32(lock_id),
+            AbiValue::String(&lock.token_id),
+            AbiValue::Uint(lock.amount),
+            AbiValue::Address(parse_address(&lock.destination_address)?),
+            AbiValue::Bytes32(state_hash),
+            AbiValue::Bytes32(lock.merkle_root),
+            AbiValue::Bytes32Array(&lock.merkle_proof),
+            AbiValue::Uint(lock.leaf_index as u64),
+        ]));
+
+        Ok(calldata)
+    }
+}
+
+#[async_trait]
+impl BridgeAdapter for EthereumBridgeAdapter {
+    async fn lock_for_bridge(
+        &self,
+        token_id: &str,
+        amount: Balance,
+        destination_chain: &str,
+        destination_address: &str,
+        identity: &Identity,
+        state: &State,
+    ) -> Result<BridgeLock, DsmError> {
+        if destination_chain != ETHEREUM_CHAIN {
+            return Err(DsmError::invalid_parameter(format!(
+                "Ethereum bridge cannot send tokens to {}",
+                destination_chain
+            )));
+        }
+        parse_address(destination_address)?;
+
+        let lock = BridgeLock::create(
+            token_id,
+            &amount,
+            destination_chain,
+            destination_address,
+            identity,
+            state,
+        )?;
+
+        // Sending only fails when no relayer is subscribed
+        let _ = self.lock_tx.send(lock.clone());
+
+        Ok(lock)
+    }
+
+    async fn release_from_bridge(
+        &self,
+        lock_proof: BridgeLock,
+        bridge_signature: &[u8],
+    ) -> Result<State, DsmError> {
+        if lock_proof.destination_chain != ETHEREUM_CHAIN {
+            return Err(DsmError::invalid_parameter(format!(
+                "Lock {} was not bridged to Ethereum",
+                lock_proof.lock_id
+            )));
+        }
+
+        let mut released = self.released.lock().map_err(|_| {
+            DsmError::internal(
+                "Released bridge locks poisoned",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+        if released.contains(&lock_proof.lock_id) {
+            return Err(DsmError::validation(
+                format!("Lock {} was already released", lock_proof.lock_id),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let state = lock_proof.release(&self.bridge_public_key, bridge_signature)?;
+        released.insert(lock_proof.lock_id);
+
+        Ok(state)
+    }
+}
+
+/// Parse a `0x`-prefixed Ethereum address
+fn parse_address(address: &str) -> Result<[u8; 20], DsmError> {
+    address
+        .strip_prefix("0x")
+        .and_then(|hex_address| hex::decode(hex_address).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| DsmError::invalid_parameter(format!("Invalid Ethereum address {}", address)))
+}
+
+/// A `uint256` word holding `value`
+fn uint_word(value: u64) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI-encode the arguments of a contract call
+///
+/// Static values are encoded in place; dynamic ones are appended after the
+/// static part and referenced by their offset.
+fn abi_encode(values: &[AbiValue]) -> Vec<u8> {
+    let head_len = values.len() * 32;
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+
+    for value in values {
+        match value {
+            AbiValue::Bytes32(bytes) => head.extend_from_slice(bytes),
+            AbiValue::Uint(value) => head.extend_from_slice(&uint_word(*value)),
+            AbiValue::Address(address) => {
+                head.extend_from_slice(&[0; 12]);
+                head.extend_from_slice(address);
+            }
+            AbiValue::String(string) => {
+                head.extend_from_slice(&uint_word((head_len + tail.len()) as u64));
+                tail.extend_from_slice(&uint_word(string.len() as u64));
+                tail.extend_from_slice(string.as_bytes());
+                tail.resize(tail.len() + (32 - string.len() % 32) % 32, 0);
+            }
+            AbiValue::Bytes32Array(items) => {
+                head.extend_from_slice(&uint_word((head_len + tail.len()) as u64));
+                tail.extend_from_slice(&uint_word(items.len() as u64));
+                for item in items.iter() {
+                    tail.extend_from_slice(item);
+                }
+            }
+        }
+    }
+
+    head.extend(tail);
+    head
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::sphincs;
+    use crate::test_support::genesis;
+    use crate::types::state_types::DeviceInfo;
+
+    const RECIPIENT: &str = "0x52908400098527886e0f7030069857d2e4169ee7";
+
+    fn identity() -> Identity {
+        Identity {
+            name: "alice".to_string(),
+            master_genesis: genesis(vec![7; 32]),
+            devices: Vec::new(),
+            invalidated: false,
+        }
+    }
+
+    fn funded_state() -> State {
+        let mut state = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("alice", vec![0; 32]));
+        state
+            .token_balances
+            .insert("ROOT".to_string(), Balance::from_state(500, vec![0; 32]));
+        state.hash = state.compute_hash().unwrap();
+        state
+    }
+
+    /// Bridge contract stand-in: an adapter and the key the bridge signs with
+    fn bridge() -> (EthereumBridgeAdapter, Vec<u8>) {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        (EthereumBridgeAdapter::new(public_key), secret_key)
+    }
+
+    fn balance(state: &State) -> u64 {
+        state.token_balances["ROOT"].value()
+    }
+
+    async fn try_lock(
+        adapter: &EthereumBridgeAdapter,
+        chain: &str,
+        address: &str,
+        amount: u64,
+    ) -> Result<BridgeLock, DsmError> {
+        adapter
+            .lock_for_bridge(
+                "ROOT",
+                Balance::from_state(amount, vec![0; 32]),
+                chain,
+                address,
+                &identity(),
+                &funded_state(),
+            )
+            .await
+    }
+
+    async fn lock(adapter: &EthereumBridgeAdapter, amount: u64) -> BridgeLock {
+        try_lock(adapter, ETHEREUM_CHAIN, RECIPIENT, amount)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_lock_release_round_trip_preserves_amount() {
+        let (adapter, bridge_key) = bridge();
+        let mut relayer = adapter.subscribe_locks();
+
+        let lock = lock(&adapter, 200).await;
+        assert_eq!(balance(&lock.lock_state), 300);
+        assert_eq!(lock.amount, 200);
+        assert!(lock.verify().unwrap());
+
+        // The relayer sees the lock, which survives serialization
+        let relayed = relayer.recv().await.unwrap();
+        let relayed: BridgeLock =
+            bincode::deserialize(&bincode::serialize(&relayed).unwrap()).unwrap();
+        assert_eq!(relayed.lock_id, lock.lock_id);
+
+        let signature = sphincs::sphincs_sign(&bridge_key, &relayed.release_message()).unwrap();
+        let released = adapter
+            .release_from_bridge(relayed, &signature)
+            .await
+            .unwrap();
+        assert_eq!(balance(&released), 500);
+        assert_eq!(released.state_number, funded_state().state_number + 2);
+        assert_eq!(released.prev_state_hash, lock.lock_state.hash);
+    }
+
+    #[tokio::test]
+    async fn test_release_requires_bridge_signature_once() {
+        let (adapter, bridge_key) = bridge();
+        let (_, other_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let lock = lock(&adapter, 200).await;
+
+        let forged = sphincs::sphincs_sign(&other_key, &lock.release_message()).unwrap();
+        assert!(adapter
+            .release_from_bridge(lock.clone(), &forged)
+            .await
+            .is_err());
+
+        let signature = sphincs::sphincs_sign(&bridge_key, &lock.release_message()).unwrap();
+        adapter
+            .release_from_bridge(lock.clone(), &signature)
+            .await
+            .unwrap();
+        assert!(adapter.release_from_bridge(lock, &signature).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_lock_rejected() {
+        let (adapter, bridge_key) = bridge();
+        let mut lock = lock(&adapter, 200).await;
+        lock.amount = 400;
+
+        assert!(!lock.verify().unwrap());
+        let signature = sphincs::sphincs_sign(&bridge_key, &lock.release_message()).unwrap();
+        assert!(adapter.release_from_bridge(lock, &signature).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lock_rejects_bad_destination_and_overdraft() {
+        let (adapter, _) = bridge();
+
+        assert!(try_lock(&adapter, "solana", RECIPIENT, 100).await.is_err());
+        assert!(try_lock(&adapter, ETHEREUM_CHAIN, "0x1234", 100)
+            .await
+            .is_err());
+        assert!(try_lock(&adapter, ETHEREUM_CHAIN, RECIPIENT, 501)
+            .await
+            .is_err());
+        assert!(try_lock(&adapter, ETHEREUM_CHAIN, RECIPIENT, 500)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lock_proof_abi_encoding() {
+        let (adapter, _) = bridge();
+        let lock = lock(&adapter, 200).await;
+        let calldata = EthereumBridgeAdapter::encode_lock_proof(&lock).unwrap();
+
+        let selector = Keccak256::digest(SUBMIT_LOCK_SIGNATURE.as_bytes());
+        assert_eq!(calldata[..4], selector[..4]);
+
+        let words: Vec<&[u8]> = calldata[4..].chunks(32).collect();
+        assert_eq!(hex::encode(words[0]), lock.lock_id);
+        assert_eq!(words[1], uint_word(8 * 32)); // String offset after the head
+        assert_eq!(words[2], uint_word(200));
+        assert_eq!(hex::encode(&words[3][12..]), &RECIPIENT[2..]);
+        assert_eq!(words[4], lock.lock_state.hash.as_slice());
+        assert_eq!(words[5], lock.merkle_root);
+        assert_eq!(words[6], uint_word(10 * 32)); // Proof offset after the string
+        assert_eq!(words[7], uint_word(0));
+
+        // "ROOT" as length and padded bytes, then the one-element proof
+        assert_eq!(words[8], uint_word(4));
+        assert_eq!(&words[9][..4], b"ROOT");
+        assert_eq!(words[10], uint_word(1));
+        assert_eq!(words[11], lock.merkle_proof[0]);
+        assert_eq!(words.len(), 12);
+    }
+}
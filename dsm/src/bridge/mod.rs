@@ -0,0 +1,309 @@
+//! Cross-Chain Asset Bridge
+//!
+//! Moves DSM tokens to and from external chains. Locking tokens for a bridge
+//! is a state transition that removes them from the owner's balance and
+//! records where they are going in a [`BridgeLock`]. Relayers watch for new
+//! locks and submit them to the bridge contract on the destination chain,
+//! which mints the wrapped tokens there. Tokens come back once the bridge
+//! signs the release of their lock, which credits them in a new state.
+//!
+//! Each destination chain has a [`BridgeAdapter`] that formats locks for its
+//! bridge contract and verifies the bridge's release signatures.
+
+pub mod ethereum;
+
+pub use ethereum::EthereumBridgeAdapter;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::core::identity::Identity;
+use crate::core::state_machine::generate_transition_entropy;
+use crate::core::state_machine::transition::{create_next_state, VerificationType};
+use crate::crypto::sphincs;
+use crate::merkle::tree::MerkleTree;
+use crate::types::error::DsmError;
+use crate::types::operations::{Operation, TransactionMode};
+use crate::types::state_types::State;
+use crate::types::token_types::Balance;
+
+/// Domain separator of lock leaves
+const BRIDGE_LOCK_DOMAIN: &[u8] = b"DSM_BRIDGE_LOCK";
+
+/// Domain separator of the messages bridges sign to release a lock
+const BRIDGE_RELEASE_DOMAIN: &[u8] = b"DSM_BRIDGE_RELEASE";
+
+/// Moves tokens between DSM and an external chain
+#[async_trait]
+pub trait BridgeAdapter: Send + Sync {
+    /// Lock tokens for transfer to `destination_address` on `destination_chain`
+    ///
+    /// The returned lock holds the state following `state`, in which `amount`
+    /// is removed from the token balance.
+    async fn lock_for_bridge(
+        &self,
+        token_id: &str,
+        amount: Balance,
+        destination_chain: &str,
+        destination_address: &str,
+        identity: &Identity,
+        state: &State,
+    ) -> Result<BridgeLock, DsmError>;
+
+    /// Return locked tokens once the bridge has signed their release
+    ///
+    /// Returns the state following the lock state, in which the locked amount
+    /// is credited back.
+    async fn release_from_bridge(
+        &self,
+        lock_proof: BridgeLock,
+        bridge_signature: &[u8],
+    ) -> Result<State, DsmError>;
+}
+
+/// Tokens locked on DSM for transfer to an external chain
+///
+/// The lock is a leaf of a merkle tree whose other leaf is the hash of the
+/// state that locked the tokens, so the proof binds the transfer to that
+/// state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeLock {
+    /// Hex-encoded hash of the lock, its leaf in the merkle tree
+    pub lock_id: String,
+
+    /// Token that was locked
+    pub token_id: String,
+
+    /// Amount that was locked
+    pub amount: u64,
+
+    /// Chain the tokens are moving to
+    pub destination_chain: String,
+
+    /// Recipient on the destination chain
+    pub destination_address: String,
+
+    /// ID of the identity that locked the tokens
+    pub owner_id: String,
+
+    /// State in which the tokens were locked
+    pub lock_state: State,
+
+    /// Root of the merkle tree over the lock and the lock state hash
+    pub merkle_root: [u8; 32],
+
+    /// Sibling hashes from the lock leaf to the root
+    pub merkle_proof: Vec<[u8; 32]>,
+
+    /// Index of the lock leaf in the tree
+    pub leaf_index: usize,
+}
+
+impl BridgeLock {
+    /// Lock tokens of `state` in a new state and prove the lock
+    pub fn create(
+        token_id: &str,
+        amount: &Balance,
+        destination_chain: &str,
+        destination_address: &str,
+        identity: &Identity,
+        state: &State,
+    ) -> Result<Self, DsmError> {
+        if identity.invalidated {
+            return Err(DsmError::validation(
+                "Invalidated identities cannot lock tokens",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let amount = amount.value();
+        let available = state
+            .token_balances
+            .get(token_id)
+            .map_or(0, Balance::available);
+        if amount == 0 || amount > available {
+            return Err(DsmError::validation(
+                format!(
+                    "Cannot lock {} {} for bridging with {} available",
+                    amount, token_id, available
+                ),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let mut lock = Self {
+            lock_id: String::new(),
+            token_id: token_id.to_string(),
+            amount,
+            destination_chain: destination_chain.to_string(),
+            destination_address: destination_address.to_string(),
+            owner_id: identity.id(),
+            lock_state: State::default(),
+            merkle_root: [0; 32],
+            merkle_proof: Vec::new(),
+            leaf_index: 0,
+        };
+
+        let operation = Operation::LockToken {
+            token_id: lock.token_id.clone(),
+            amount: lock.signed_amount()?,
+            purpose: lock.purpose(),
+            mode: TransactionMode::Unilateral,
+        };
+        lock.lock_state = next_state(state, operation, token_id, |balance| {
+            balance.update_sub(amount)
+        })?;
+
+        let leaf = lock.leaf();
+        let tree = lock.tree(leaf);
+        lock.lock_id = hex::encode(leaf);
+        lock.merkle_root = tree.root_hash().ok_or_else(|| {
+            DsmError::internal(
+                "Bridge lock tree has no root",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+        lock.merkle_proof = tree.generate_proof(0).path;
+
+        Ok(lock)
+    }
+
+    /// Hash of the lock, the leaf proven by the merkle proof
+    pub fn leaf(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(BRIDGE_LOCK_DOMAIN);
+        for field in [
+            &self.token_id,
+            &self.destination_chain,
+            &self.destination_address,
+            &self.owner_id,
+        ] {
+            hasher.update(&(field.len() as u64).to_le_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hasher.update(&self.amount.to_le_bytes());
+        hasher.update(&self.lock_state.state_number.to_le_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Check the lock ID, the lock state and the merkle proof
+    pub fn verify(&self) -> Result<bool, DsmError> {
+        let leaf = self.leaf();
+        if self.lock_id != hex::encode(leaf) {
+            return Ok(false);
+        }
+
+        // The state must hash to the leaf committed in the tree
+        if self.lock_state.compute_hash()? != self.lock_state.hash {
+            return Ok(false);
+        }
+
+        let locks_tokens = match &self.lock_state.operation {
+            Operation::LockToken {
+                token_id,
+                amount,
+                purpose,
+                ..
+            } => {
+                *token_id == self.token_id
+                    && u64::try_from(*amount).ok() == Some(self.amount)
+                    && *purpose == self.purpose()
+            }
+            _ => false,
+        };
+
+        Ok(locks_tokens
+            && self.tree(leaf).root_hash() == Some(self.merkle_root)
+            && MerkleTree::verify_proof(
+                &self.merkle_root,
+                &leaf,
+                &self.merkle_proof,
+                self.leaf_index,
+            ))
+    }
+
+    /// Message a bridge signs to release the lock
+    pub fn release_message(&self) -> Vec<u8> {
+        let mut message = BRIDGE_RELEASE_DOMAIN.to_vec();
+        message.extend_from_slice(&self.leaf());
+        message.extend_from_slice(&self.merkle_root);
+        message
+    }
+
+    /// Credit the locked tokens back once the bridge has signed the release
+    pub fn release(
+        &self,
+        bridge_public_key: &[u8],
+        bridge_signature: &[u8],
+    ) -> Result<State, DsmError> {
+        if !self.verify()? {
+            return Err(DsmError::verification(format!(
+                "Invalid proof for bridge lock {}",
+                self.lock_id
+            )));
+        }
+
+        if !sphincs::sphincs_verify(bridge_public_key, &self.release_message(), bridge_signature)? {
+            return Err(DsmError::verification(format!(
+                "Invalid bridge signature releasing lock {}",
+                self.lock_id
+            )));
+        }
+
+        let operation = Operation::UnlockToken {
+            token_id: self.token_id.clone(),
+            amount: self.signed_amount()?,
+            purpose: self.purpose(),
+            mode: TransactionMode::Unilateral,
+        };
+        next_state(&self.lock_state, operation, &self.token_id, |balance| {
+            balance.update_add(self.amount);
+            Ok(())
+        })
+    }
+
+    /// Purpose recorded in the lock and unlock operations
+    fn purpose(&self) -> String {
+        format!(
+            "bridge:{}:{}",
+            self.destination_chain, self.destination_address
+        )
+    }
+
+    /// Amount as recorded in token lock operations
+    fn signed_amount(&self) -> Result<i64, DsmError> {
+        i64::try_from(self.amount).map_err(|_| {
+            DsmError::invalid_parameter(format!("Bridge amount {} is too large", self.amount))
+        })
+    }
+
+    fn tree(&self, leaf: [u8; 32]) -> MerkleTree {
+        MerkleTree::new(vec![leaf.to_vec(), self.lock_state.hash.clone()])
+    }
+}
+
+/// Apply a token lock operation to `state`, updating the balance of `token_id`
+fn next_state(
+    state: &State,
+    operation: Operation,
+    token_id: &str,
+    update: impl FnOnce(&mut Balance) -> Result<(), DsmError>,
+) -> Result<State, DsmError> {
+    let entropy = generate_transition_entropy(state, &operation)?;
+    let mut next = create_next_state(
+        state,
+        operation,
+        &entropy,
+        &VerificationType::Standard,
+        false,
+    )?;
+
+    let balance = next
+        .token_balances
+        .entry(token_id.to_string())
+        .or_insert_with(|| Balance::from_state(0, state.hash.clone()));
+    update(balance)?;
+    next.hash = next.compute_hash()?;
+
+    Ok(next)
+}
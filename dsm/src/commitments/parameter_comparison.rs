@@ -349,6 +349,38 @@ pub fn extract_operation_parameters(
             params.insert("mode".to_string(), bincode::serialize(mode).unwrap());
             Ok(params)
         }
+        Operation::WithDependencies {
+            dependencies,
+            inner,
+        } => {
+            // Commitments bind to the wrapped operation plus its declared prerequisites
+            let mut params = extract_operation_parameters(inner)?;
+            params.insert("dependencies".to_string(), dependencies.concat());
+            Ok(params)
+        }
+        Operation::CommittedTransfer {
+            recipient,
+            from_commitment,
+            to_commitment,
+            proof,
+        } => {
+            let mut params = HashMap::new();
+            params.insert("operation_type".to_string(), b"committed_transfer".to_vec());
+            params.insert("recipient".to_string(), recipient.as_bytes().to_vec());
+            params.insert("token_id".to_string(), proof.token_id.as_bytes().to_vec());
+            params.insert("from_commitment".to_string(), from_commitment.to_bytes());
+            params.insert("to_commitment".to_string(), to_commitment.to_bytes());
+            Ok(params)
+        }
+        Operation::RevealBalance {
+            token_id, amount, ..
+        } => {
+            let mut params = HashMap::new();
+            params.insert("operation_type".to_string(), b"reveal_balance".to_vec());
+            params.insert("token_id".to_string(), token_id.as_bytes().to_vec());
+            params.insert("amount".to_string(), amount.to_le_bytes().to_vec());
+            Ok(params)
+        }
     }
 }
 
@@ -710,7 +710,7 @@ pub fn publish_state(&self, state: &State) -> Result<(), DsmError> {
                     // For synchronous operations, handle based on state type
                     if entry_type == DirectoryEntryType::Genesis {
                         // Use tokio runtime to execute async code in sync context with proper error propagation
-                        let rt = tokio::runtime::Runtime::new().map_err(|e| {
+                        let rt = crate::utils::blocking_runtime().map_err(|e| {
                             DsmError::generic(
                                 "Failed to create tokio runtime for sync publication",
                                 Some(e),
@@ -757,7 +757,7 @@ pub fn retrieve_state(&self, state_id: &str) -> Result<State, DsmError> {
         // If not found locally and we have a remote client, try to fetch from remote
         if let Some(client) = &self.client {
             // Use tokio runtime to execute async code in sync context with proper error handling
-            let rt = tokio::runtime::Runtime::new().map_err(|e| {
+            let rt = crate::utils::blocking_runtime().map_err(|e| {
                 DsmError::generic(
                     "Failed to create tokio runtime for state retrieval",
                     Some(e),
@@ -976,7 +976,7 @@ pub fn publish_invalidation(
                 }
                 ConsistencyMode::WriteSync | ConsistencyMode::WriteRemoteFirst => {
                     // Use tokio runtime to execute async code in sync context
-                    let rt = tokio::runtime::Runtime::new().map_err(|e| {
+                    let rt = crate::utils::blocking_runtime().map_err(|e| {
                         DsmError::generic(
                             "Failed to create tokio runtime for invalidation publication",
                             Some(e),
@@ -1038,7 +1038,7 @@ pub async fn verify_genesis_state(&self, state: &State) -> Result<bool, DsmError
         // If not found locally and we have a remote client, try to verify against remote directory
         if let Some(client) = &self.client {
             // Use tokio runtime to execute async code in sync context
-            let rt = tokio::runtime::Runtime::new().map_err(|e| {
+            let rt = crate::utils::blocking_runtime().map_err(|e| {
                 DsmError::generic(
                     "Failed to create tokio runtime for genesis verification",
                     Some(e),
@@ -1120,7 +1120,7 @@ pub fn check_invalidation(
         // If not found locally and we have a remote client, try to fetch from remote
         if let Some(client) = &self.client {
             // Use tokio runtime to execute async code in sync context
-            let rt = tokio::runtime::Runtime::new().map_err(|e| {
+            let rt = crate::utils::blocking_runtime().map_err(|e| {
                 DsmError::generic(
                     "Failed to create tokio runtime for invalidation check",
                     Some(e),
@@ -8,7 +8,11 @@
 //! * Directory services for node discovery
 //! * Near Field Communication (NFC) support
 //! * Storage node communication and caching
+//! * Offline queuing of operations for replay to storage nodes
+//! * Per-endpoint rate limiting of requests to storage nodes
+//! * Write permits for storage nodes that restrict writes per identity
 //! * Certificate management for secure connections
+//! * Post-quantum hybrid TLS for storage node servers (with the `pq-tls` feature)
 //!
 //! The communication module supports multiple transport types including TLS over TCP,
 //! secure UDP with DTLS, and Bluetooth (when the feature is enabled). It provides
@@ -19,10 +23,15 @@
 pub mod init;
 pub mod manager;
 pub mod nfc;
+pub mod offline_queue;
 pub mod p2p;
 pub mod protocol;
+pub mod rate_limiter;
 pub mod storage_cache;
+#[cfg(all(feature = "pq-tls", not(target_arch = "wasm32")))]
+pub mod tls;
 pub mod transport;
+pub mod write_permit;
 
 use crate::types::error::DsmError;
 use async_trait::async_trait;
@@ -104,9 +113,11 @@ pub trait NetworkInterface: Send + Sync {
     async fn receive_data(&self, peer_id: &str) -> Result<Option<Bytes>, DsmError>;
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 use rcgen::{Certificate, CertificateParams, DistinguishedName, DnType, IsCa};
 
 /// Generate a self-signed certificate for TLS communication
+#[cfg(not(target_arch = "wasm32"))]
 pub fn generate_self_signed_cert() -> Result<Certificate, rcgen::Error> {
     // Create certificate parameters
     let mut params = CertificateParams::new(vec!["dsm.local".to_string()]);
@@ -145,6 +156,7 @@ pub fn generate_self_signed_cert() -> Result<Certificate, rcgen::Error> {
 
 /// Generate a CA certificate for signing other certificates
 /// Generate a CA certificate for signing other certificates
+#[cfg(not(target_arch = "wasm32"))]
 pub fn generate_ca_cert() -> Result<Certificate, rcgen::Error> {
     let mut params = CertificateParams::new(vec!["DSM Root CA".to_string()]);
     let mut dn = DistinguishedName::new();
@@ -167,6 +179,16 @@ pub fn generate_ca_cert() -> Result<Certificate, rcgen::Error> {
 
 // Re-export key types
 pub use self::manager::{ConnectionManager, NetworkManager};
+pub use self::offline_queue::{
+    DrainResult, FailedOperation, OfflineQueue, OperationReplayer, QueuedOperation,
+};
 pub use self::protocol::{Message, Protocol, Session};
-pub use self::storage_cache::StorageCache;
+pub use self::rate_limiter::{RateLimiter, TokenBucket, TokenBucketConfig};
+pub use self::storage_cache::{
+    CacheLookup, CachePeer, Freshness, PrewarmReport, ScopedStorageCache, StatePurgeStats,
+    StorageCache, VaultOutcome, VaultPrunePolicy, VaultPruneStats, VaultTombstone,
+};
 pub use self::transport::{Transport, TransportConnection, TransportListener};
+pub use self::write_permit::{
+    request_write_permission, WritePermitChallenge, WritePermitRequest, WritePermitToken,
+};
@@ -0,0 +1,398 @@
+//! Offline Operation Queue
+//!
+//! Operations executed while the storage node cannot be reached are queued
+//! together with the state they produced, and replayed in order once the node
+//! is reachable again. The storage cache only lives in memory, so a queue that
+//! must survive restarts is opened on a journal file, which is rewritten on
+//! every change to the queue.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::types::error::DsmError;
+use crate::types::operations::Operation;
+use crate::types::state_types::State;
+
+/// An operation waiting to be replayed to the storage node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedOperation {
+    /// The executed operation
+    pub operation: Operation,
+
+    /// State the operation produced
+    pub state: State,
+
+    /// Seconds since the Unix epoch at which the operation was queued
+    pub queued_at: u64,
+}
+
+impl QueuedOperation {
+    /// Wrap an operation and the state it produced, timestamped now
+    pub fn new(operation: Operation, state: State) -> Self {
+        Self {
+            operation,
+            state,
+            queued_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+/// A queued operation that was rejected when replayed
+#[derive(Debug, Clone)]
+pub struct FailedOperation {
+    /// The rejected operation, which is no longer queued
+    pub operation: QueuedOperation,
+
+    /// Why the operation was rejected
+    pub reason: String,
+}
+
+/// Outcome of draining an [`OfflineQueue`]
+#[derive(Debug, Clone, Default)]
+pub struct DrainResult {
+    /// Number of operations replayed successfully
+    pub replayed: usize,
+
+    /// Rejected operations, in queue order
+    pub failed: Vec<FailedOperation>,
+
+    /// Number of operations left queued because the destination became unreachable
+    pub remaining: usize,
+}
+
+impl DrainResult {
+    /// Whether every queued operation was replayed
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.remaining == 0
+    }
+}
+
+/// Destination to which queued operations are replayed
+#[async_trait]
+pub trait OperationReplayer: Send + Sync {
+    /// Replay a queued operation
+    ///
+    /// A `DsmError::Network` error means the destination cannot be reached and
+    /// leaves the operation queued. Any other error rejects the operation.
+    async fn replay(&self, operation: &QueuedOperation) -> Result<(), DsmError>;
+}
+
+/// Operations waiting for the storage node, in execution order
+#[derive(Debug, Default)]
+pub struct OfflineQueue {
+    /// Queued operations, oldest first
+    entries: Mutex<VecDeque<QueuedOperation>>,
+
+    /// File the queue is journaled to, if it survives restarts
+    journal: Option<PathBuf>,
+
+    /// Held while draining, so that no operation is replayed twice
+    drain_lock: tokio::sync::Mutex<()>,
+}
+
+impl OfflineQueue {
+    /// Create a queue that only lives in memory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a queue journaled to `path`, restoring the operations queued there
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DsmError> {
+        let path = path.as_ref().to_path_buf();
+
+        let entries = if path.exists() {
+            let data = std::fs::read(&path).map_err(|e| {
+                DsmError::storage(
+                    format!("Failed to read offline queue {}", path.display()),
+                    Some(e),
+                )
+            })?;
+            bincode::deserialize(&data).map_err(|e| {
+                DsmError::serialization(
+                    format!("Failed to decode offline queue {}", path.display()),
+                    Some(e),
+                )
+            })?
+        } else {
+            VecDeque::new()
+        };
+        debug!(
+            "Opened offline queue {} with {} operations",
+            path.display(),
+            entries.len()
+        );
+
+        Ok(Self {
+            entries: Mutex::new(entries),
+            journal: Some(path),
+            drain_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Append an operation to the queue
+    pub fn enqueue(&self, op: QueuedOperation) -> Result<(), DsmError> {
+        let mut entries = self.entries.lock();
+        entries.push_back(op);
+
+        if let Err(e) = self.persist(&entries) {
+            entries.pop_back();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Number of queued operations
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether no operations are queued
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Copy of the queued operations, oldest first
+    pub fn pending(&self) -> Vec<QueuedOperation> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    /// Replay queued operations to `client` in the order they were queued
+    ///
+    /// Replayed and rejected operations leave the queue. Draining stops at the
+    /// first operation the client cannot deliver because it is unreachable;
+    /// that operation and the ones after it stay queued for the next drain.
+    pub async fn drain_queue<R>(&self, client: &R) -> Result<DrainResult, DsmError>
+    where
+        R: OperationReplayer + ?Sized,
+    {
+        let _draining = self.drain_lock.lock().await;
+        let mut result = DrainResult::default();
+
+        loop {
+            let Some(op) = self.entries.lock().front().cloned() else {
+                break;
+            };
+
+            match client.replay(&op).await {
+                Ok(()) => result.replayed += 1,
+                Err(e @ DsmError::Network { .. }) => {
+                    result.remaining = self.len();
+                    debug!(
+                        "Storage node unreachable; {} operations stay queued: {}",
+                        result.remaining, e
+                    );
+                    break;
+                }
+                Err(e) => {
+                    warn!(
+                        "Queued operation for state {} was rejected: {}",
+                        op.state.state_number, e
+                    );
+                    result.failed.push(FailedOperation {
+                        operation: op,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+
+            // Only the drain removes entries, so the front is still `op`
+            let mut entries = self.entries.lock();
+            entries.pop_front();
+            self.persist(&entries)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Rewrite the journal, if any, with `entries`
+    fn persist(&self, entries: &VecDeque<QueuedOperation>) -> Result<(), DsmError> {
+        let Some(path) = &self.journal else {
+            return Ok(());
+        };
+
+        let data = bincode::serialize(entries)
+            .map_err(|e| DsmError::serialization("Failed to encode offline queue", Some(e)))?;
+
+        // Write a temporary file first so a crash never leaves a truncated journal
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, data)
+            .and_then(|()| std::fs::rename(&temp_path, path))
+            .map_err(|e| {
+                DsmError::storage(
+                    format!("Failed to write offline queue {}", path.display()),
+                    Some(e),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state_machine::StateMachine;
+    use crate::types::state_types::DeviceInfo;
+
+    fn genesis() -> State {
+        let device_info = DeviceInfo::new("queue_device", vec![5, 6, 7, 8]);
+        let mut state = State::new_genesis(vec![1, 2, 3, 4], device_info);
+        state.hash = state.hash().unwrap();
+        state
+    }
+
+    /// Execute `count` operations on a fresh chain, queuing each one
+    fn queue_operations(queue: &OfflineQueue, count: usize) -> Vec<State> {
+        let mut machine = StateMachine::new();
+        machine.set_state(genesis());
+
+        (0..count)
+            .map(|i| {
+                let operation = Operation::Generic {
+                    operation_type: format!("offline_{}", i),
+                    data: vec![i as u8],
+                    message: format!("Offline operation {}", i),
+                };
+                let state = machine.execute_transition(operation.clone()).unwrap();
+                queue
+                    .enqueue(QueuedOperation::new(operation, state.clone()))
+                    .unwrap();
+                state
+            })
+            .collect()
+    }
+
+    /// Re-executes replayed operations on its own chain
+    struct ChainReplayer {
+        machine: Mutex<StateMachine>,
+        reachable_for: Mutex<usize>,
+        reject_state: Option<u64>,
+    }
+
+    impl ChainReplayer {
+        fn new() -> Self {
+            let mut machine = StateMachine::new();
+            machine.set_state(genesis());
+            Self {
+                machine: Mutex::new(machine),
+                reachable_for: Mutex::new(usize::MAX),
+                reject_state: None,
+            }
+        }
+
+        fn current_state(&self) -> State {
+            self.machine.lock().current_state().cloned().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl OperationReplayer for ChainReplayer {
+        async fn replay(&self, op: &QueuedOperation) -> Result<(), DsmError> {
+            let mut reachable_for = self.reachable_for.lock();
+            if *reachable_for == 0 {
+                return Err(DsmError::network(
+                    "Storage node unreachable",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            *reachable_for -= 1;
+
+            if self.reject_state == Some(op.state.state_number) {
+                return Err(DsmError::validation(
+                    "State rejected",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+
+            let state = self
+                .machine
+                .lock()
+                .execute_transition(op.operation.clone())?;
+            if state.hash != op.state.hash {
+                return Err(DsmError::verification("Replayed state diverged"));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drain_replays_in_order() {
+        let queue = OfflineQueue::new();
+        let states = queue_operations(&queue, 10);
+        assert_eq!(queue.len(), 10);
+
+        let replayer = ChainReplayer::new();
+        let result = queue.drain_queue(&replayer).await.unwrap();
+
+        assert_eq!(result.replayed, 10);
+        assert!(result.is_complete());
+        assert!(queue.is_empty());
+        assert_eq!(replayer.current_state().hash, states.last().unwrap().hash);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_client_keeps_remaining_operations() {
+        let queue = OfflineQueue::new();
+        let states = queue_operations(&queue, 10);
+
+        let replayer = ChainReplayer::new();
+        *replayer.reachable_for.lock() = 4;
+        let result = queue.drain_queue(&replayer).await.unwrap();
+        assert_eq!((result.replayed, result.remaining), (4, 6));
+        assert_eq!(queue.pending()[0].state.state_number, 5);
+
+        *replayer.reachable_for.lock() = usize::MAX;
+        let result = queue.drain_queue(&replayer).await.unwrap();
+        assert_eq!((result.replayed, result.remaining), (6, 0));
+        assert_eq!(replayer.current_state().hash, states.last().unwrap().hash);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_operations_are_reported() {
+        let queue = OfflineQueue::new();
+        queue_operations(&queue, 3);
+
+        let replayer = ChainReplayer {
+            reject_state: Some(3),
+            ..ChainReplayer::new()
+        };
+        let result = queue.drain_queue(&replayer).await.unwrap();
+
+        assert_eq!(result.replayed, 2);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].operation.state.state_number, 3);
+        assert!(result.failed[0].reason.contains("State rejected"));
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_journal_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("offline_queue.bin");
+
+        let states = {
+            let queue = OfflineQueue::open(&path).unwrap();
+            queue_operations(&queue, 10)
+        };
+
+        let queue = OfflineQueue::open(&path).unwrap();
+        let restored: Vec<u64> = queue
+            .pending()
+            .iter()
+            .map(|op| op.state.state_number)
+            .collect();
+        assert_eq!(restored, (1..=10).collect::<Vec<_>>());
+
+        let replayer = ChainReplayer::new();
+        assert_eq!(queue.drain_queue(&replayer).await.unwrap().replayed, 10);
+        assert_eq!(replayer.current_state().hash, states.last().unwrap().hash);
+        assert!(OfflineQueue::open(&path).unwrap().is_empty());
+    }
+}
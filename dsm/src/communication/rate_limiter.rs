@@ -0,0 +1,237 @@
+//! Client-Side Rate Limiting
+//!
+//! Keeps a client from overwhelming a storage node with requests. Each
+//! endpoint path prefix can have its own token bucket; requests to endpoints
+//! without one draw from a default bucket. A request takes one token, and an
+//! empty bucket rejects the request with the time until its next token.
+
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+// Tokio's clock can be paused in tests but panics in the browser
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::types::error::DsmError;
+
+/// Size and refill rate of a token bucket
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucketConfig {
+    /// Most tokens the bucket holds, i.e. the largest burst of requests
+    pub capacity: usize,
+
+    /// Tokens added per second
+    pub refill_rate: f64,
+}
+
+/// Token bucket limiting the rate of requests
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    /// Most tokens the bucket holds
+    capacity: usize,
+
+    /// Tokens added per second
+    refill_rate: f64,
+
+    /// Tokens available as of `last_refill`
+    current_tokens: f64,
+
+    /// When the bucket was last refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(config: TokenBucketConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            refill_rate: config.refill_rate,
+            current_tokens: config.capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token, or report how long until one is available
+    pub fn try_acquire(&mut self) -> Result<(), DsmError> {
+        self.refill();
+
+        if self.current_tokens >= 1.0 {
+            self.current_tokens -= 1.0;
+            return Ok(());
+        }
+
+        // A bucket that never refills or can never hold a token stays empty
+        let retry_after_ms = if self.refill_rate > 0.0 && self.capacity > 0 {
+            ((1.0 - self.current_tokens) / self.refill_rate * 1000.0).ceil() as u64
+        } else {
+            u64::MAX
+        };
+        Err(DsmError::RateLimited { retry_after_ms })
+    }
+
+    /// Tokens currently available, including any refilled since the last request
+    pub fn available(&mut self) -> f64 {
+        self.refill();
+        self.current_tokens
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+
+        if self.refill_rate > 0.0 {
+            self.current_tokens =
+                (self.current_tokens + elapsed * self.refill_rate).min(self.capacity as f64);
+        }
+    }
+}
+
+/// Rate limiter with token buckets per endpoint path prefix
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Bucket for requests to endpoints without their own
+    default_bucket: Mutex<TokenBucket>,
+
+    /// Buckets by endpoint path prefix, longest prefix first
+    endpoint_buckets: Vec<(String, Mutex<TokenBucket>)>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter with one bucket for all endpoints
+    pub fn new(config: TokenBucketConfig) -> Self {
+        Self {
+            default_bucket: Mutex::new(TokenBucket::new(config)),
+            endpoint_buckets: Vec::new(),
+        }
+    }
+
+    /// Give endpoints under each path prefix (e.g. `"/inbox"`) their own bucket
+    ///
+    /// An endpoint matching several prefixes uses the bucket of the longest.
+    pub fn with_endpoint_limits(mut self, limits: HashMap<&str, TokenBucketConfig>) -> Self {
+        self.endpoint_buckets.extend(
+            limits
+                .into_iter()
+                .map(|(prefix, config)| (prefix.to_string(), Mutex::new(TokenBucket::new(config)))),
+        );
+        self.endpoint_buckets
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        self
+    }
+
+    /// Take a token from the default bucket
+    ///
+    /// Returns `DsmError::RateLimited` with the time until the next token
+    /// when the bucket is empty.
+    pub fn acquire(&self) -> Result<(), DsmError> {
+        self.default_bucket.lock().try_acquire()
+    }
+
+    /// Take a token from the bucket of the endpoint at `path`
+    ///
+    /// Returns `DsmError::RateLimited` with the time until the next token
+    /// when the bucket is empty.
+    pub fn acquire_for(&self, path: &str) -> Result<(), DsmError> {
+        match self
+            .endpoint_buckets
+            .iter()
+            .find(|(prefix, _)| matches_prefix(prefix, path))
+        {
+            Some((_, bucket)) => bucket.lock().try_acquire(),
+            None => self.acquire(),
+        }
+    }
+}
+
+/// Whether `path` is the endpoint `prefix` or lies below it
+fn matches_prefix(prefix: &str, path: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn config(capacity: usize, refill_rate: f64) -> TokenBucketConfig {
+        TokenBucketConfig {
+            capacity,
+            refill_rate,
+        }
+    }
+
+    fn retry_after(result: Result<(), DsmError>) -> u64 {
+        match result {
+            Err(DsmError::RateLimited { retry_after_ms }) => retry_after_ms,
+            other => panic!("expected a rate limit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bucket_saturates_at_capacity() {
+        tokio::time::pause();
+        let limiter = RateLimiter::new(config(5, 2.0));
+
+        for _ in 0..5 {
+            limiter.acquire().unwrap();
+        }
+
+        // Two tokens per second: the next one is half a second away
+        assert_eq!(retry_after(limiter.acquire()), 500);
+        for _ in 0..4 {
+            assert!(limiter.acquire().is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tokens_refill_over_time() {
+        tokio::time::pause();
+        let limiter = RateLimiter::new(config(5, 1.0));
+        for _ in 0..5 {
+            limiter.acquire().unwrap();
+        }
+        assert_eq!(retry_after(limiter.acquire()), 1000);
+
+        tokio::time::advance(Duration::from_millis(250)).await;
+        assert_eq!(retry_after(limiter.acquire()), 750);
+
+        tokio::time::advance(Duration::from_millis(750)).await;
+        limiter.acquire().unwrap();
+        assert!(limiter.acquire().is_err());
+
+        // Refilling stops at the capacity
+        tokio::time::advance(Duration::from_secs(60)).await;
+        for _ in 0..5 {
+            limiter.acquire().unwrap();
+        }
+        assert!(limiter.acquire().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_use_their_own_buckets() {
+        tokio::time::pause();
+        let limiter = RateLimiter::new(config(1, 1.0)).with_endpoint_limits(HashMap::from([
+            ("/inbox", config(2, 1.0)),
+            ("/vault", config(1, 0.5)),
+            ("/vault/creator", config(3, 1.0)),
+        ]));
+
+        limiter.acquire_for("/inbox/abc").unwrap();
+        limiter.acquire_for("/inbox").unwrap();
+        assert!(limiter.acquire_for("/inbox/def").is_err());
+
+        // The longest matching prefix wins
+        limiter.acquire_for("/vault/v1").unwrap();
+        assert_eq!(retry_after(limiter.acquire_for("/vault/v2")), 2000);
+        limiter.acquire_for("/vault/creator/c1").unwrap();
+
+        // Other endpoints, including ones that only share a name prefix, use the default
+        limiter.acquire_for("/inboxes").unwrap();
+        assert!(limiter.acquire_for("/data/x").is_err());
+    }
+}
@@ -1,21 +1,27 @@
 //! Storage Cache Module
 //!
 //! This module provides caching functionality for DSM storage nodes,
-//! enabling clients to cache genesis states, tokens, checkpoints, and
-//! other critical data for offline operations.
+//! enabling clients to cache genesis states, tokens, checkpoints, state
+//! history and other critical data for offline operations.
 
 use crate::core::identity::GenesisState;
 use crate::recovery::invalidation::InvalidationMarker;
 use crate::types::error::DsmError;
 use crate::types::state_types::State;
 use crate::types::token_types::Token;
+use crate::vault::{LimboVault, VaultState};
 
-use std::collections::HashMap;
-// use std::sync::Arc - Removed unused import
+use std::collections::{HashMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use async_trait::async_trait;
 use blake3;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
+use tracing::{debug, warn};
 
 /// Cache entry with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +43,9 @@ struct CacheEntry<T> {
 
     /// Whether this item has been cryptographically verified
     verified: bool,
+
+    /// Serialized size of the item in bytes, measured at insert time
+    size_bytes: usize,
 }
 
 impl<T: Serialize + Clone> CacheEntry<T> {
@@ -57,6 +66,7 @@ fn new(data: T, ttl: u64, verified: bool) -> Result<Self, DsmError> {
             ttl,
             hash,
             verified,
+            size_bytes: serialized.len(),
         })
     }
 
@@ -90,6 +100,516 @@ fn verify_integrity(&self) -> Result<bool, DsmError> {
     }
 }
 
+/// How current a cached value is
+///
+/// Variants are ordered from least to most current, so a lookup satisfies a
+/// requirement when `lookup.freshness >= required`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Freshness {
+    /// Past its TTL; usable while offline but due for revalidation
+    Stale,
+    /// Within its TTL
+    Fresh,
+}
+
+/// A cached value tagged with its freshness
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheLookup<T> {
+    /// The cached value
+    pub value: T,
+
+    /// Whether the value is still within its TTL
+    pub freshness: Freshness,
+}
+
+impl<T> CacheLookup<T> {
+    /// Whether the value is within its TTL
+    pub fn is_fresh(&self) -> bool {
+        self.freshness == Freshness::Fresh
+    }
+
+    /// Whether the value is past its TTL
+    pub fn is_stale(&self) -> bool {
+        self.freshness == Freshness::Stale
+    }
+
+    /// Take the value, discarding its freshness
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Fail unless the value is at least as fresh as required
+    fn require(self, required: Freshness, description: &str) -> Result<Self, DsmError> {
+        if self.freshness < required {
+            return Err(DsmError::storage(
+                format!("Cached {} is stale", description),
+                None::<std::convert::Infallible>,
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// Category of cached data, used for byte budgeting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CacheCategory {
+    /// Genesis states
+    Genesis,
+    /// Tokens
+    Token,
+    /// Checkpoint states
+    Checkpoint,
+    /// Invalidation markers
+    Invalidation,
+    /// Limbo vaults
+    Vault,
+    /// States from an identity's history
+    State,
+}
+
+impl CacheCategory {
+    /// Name of the category as reported in cache statistics
+    pub fn name(&self) -> &'static str {
+        match self {
+            CacheCategory::Genesis => "genesis",
+            CacheCategory::Token => "token",
+            CacheCategory::Checkpoint => "checkpoint",
+            CacheCategory::Invalidation => "invalidation",
+            CacheCategory::Vault => "vault",
+            CacheCategory::State => "state",
+        }
+    }
+}
+
+impl std::fmt::Display for CacheCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Byte budgets enforced by the storage cache
+///
+/// Sizes are measured as the serialized size of each entry at insert time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// Maximum number of bytes across all categories (None = unlimited)
+    pub max_total_bytes: Option<usize>,
+
+    /// Optional per-category byte budgets
+    pub category_budgets: HashMap<CacheCategory, usize>,
+}
+
+impl CacheLimits {
+    /// Limits with no byte budgets at all
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Set the global byte budget
+    pub fn with_total_bytes(mut self, max_total_bytes: usize) -> Self {
+        self.max_total_bytes = Some(max_total_bytes);
+        self
+    }
+
+    /// Set the byte budget for a single category
+    pub fn with_category_budget(mut self, category: CacheCategory, max_bytes: usize) -> Self {
+        self.category_budgets.insert(category, max_bytes);
+        self
+    }
+
+    /// Reject entries that could never fit within the configured budgets
+    fn check_entry_size(&self, category: CacheCategory, size: usize) -> Result<(), DsmError> {
+        if let Some(budget) = self.category_budgets.get(&category) {
+            if size > *budget {
+                return Err(DsmError::storage(
+                    format!(
+                        "Cache entry of {} bytes exceeds the {} category budget of {} bytes",
+                        size, category, budget
+                    ),
+                    None::<std::convert::Infallible>,
+                ));
+            }
+        }
+
+        if let Some(budget) = self.max_total_bytes {
+            if size > budget {
+                return Err(DsmError::storage(
+                    format!(
+                        "Cache entry of {} bytes exceeds the global cache budget of {} bytes",
+                        size, budget
+                    ),
+                    None::<std::convert::Infallible>,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Terminal state of a vault that can be pruned from the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VaultOutcome {
+    /// The vault's content was claimed
+    Claimed,
+    /// The vault was invalidated or reclaimed by its creator
+    Invalidated,
+}
+
+impl VaultOutcome {
+    /// Outcome and state number of a vault in a terminal state
+    fn of(state: &VaultState) -> Option<(Self, u64)> {
+        match state {
+            VaultState::Claimed {
+                claimed_state_number,
+                ..
+            } => Some((VaultOutcome::Claimed, *claimed_state_number)),
+            VaultState::Invalidated {
+                invalidated_state_number,
+                ..
+            } => Some((VaultOutcome::Invalidated, *invalidated_state_number)),
+            // Returned to its creator, so pruned like an invalidated vault
+            VaultState::Reclaimed {
+                reclaimed_state_number,
+                ..
+            } => Some((VaultOutcome::Invalidated, *reclaimed_state_number)),
+            VaultState::Limbo | VaultState::Unlocked { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for VaultOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultOutcome::Claimed => f.write_str("claimed"),
+            VaultOutcome::Invalidated => f.write_str("invalidated"),
+        }
+    }
+}
+
+/// Compact record kept in place of a pruned vault
+///
+/// Lets lookups answer that a vault is known to be claimed or invalidated
+/// while offline, without holding the vault itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultTombstone {
+    /// Vault identifier
+    pub vault_id: String,
+
+    /// Terminal state the vault was in when pruned
+    pub outcome: VaultOutcome,
+
+    /// State number at which the vault was claimed or invalidated
+    pub state_number: u64,
+
+    /// Hash of the serialized vault
+    pub vault_hash: [u8; 32],
+
+    /// When the vault was pruned
+    pub pruned_at: SystemTime,
+}
+
+/// How long tombstones of pruned vaults are kept by default
+pub const DEFAULT_VAULT_TOMBSTONE_MAX_AGE: Duration = Duration::from_secs(86400 * 30);
+
+/// Which vaults `StorageCache::prune_vaults` removes
+///
+/// Ages are measured from when the vault was last cached. The default policy
+/// keeps every vault, and drops tombstones once they are older than
+/// `DEFAULT_VAULT_TOMBSTONE_MAX_AGE`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultPrunePolicy {
+    /// Remove claimed vaults cached at least this long ago (None = keep)
+    pub claimed_max_age: Option<Duration>,
+
+    /// Remove invalidated vaults cached at least this long ago (None = keep)
+    pub invalidated_max_age: Option<Duration>,
+
+    /// Remove tombstones of vaults pruned at least this long ago
+    #[serde(default = "default_tombstone_max_age")]
+    pub tombstone_max_age: Duration,
+}
+
+fn default_tombstone_max_age() -> Duration {
+    DEFAULT_VAULT_TOMBSTONE_MAX_AGE
+}
+
+impl Default for VaultPrunePolicy {
+    fn default() -> Self {
+        Self {
+            claimed_max_age: None,
+            invalidated_max_age: None,
+            tombstone_max_age: DEFAULT_VAULT_TOMBSTONE_MAX_AGE,
+        }
+    }
+}
+
+impl VaultPrunePolicy {
+    /// Policy that keeps every vault
+    pub fn keep_all() -> Self {
+        Self::default()
+    }
+
+    /// Remove claimed vaults cached at least `age` ago
+    pub fn remove_claimed_older_than(mut self, age: Duration) -> Self {
+        self.claimed_max_age = Some(age);
+        self
+    }
+
+    /// Remove invalidated vaults cached at least `age` ago
+    pub fn remove_invalidated_older_than(mut self, age: Duration) -> Self {
+        self.invalidated_max_age = Some(age);
+        self
+    }
+
+    /// Remove invalidated vaults as soon as pruning runs
+    pub fn remove_invalidated_immediately(self) -> Self {
+        self.remove_invalidated_older_than(Duration::ZERO)
+    }
+
+    /// Remove tombstones of vaults pruned at least `age` ago
+    pub fn remove_tombstones_older_than(mut self, age: Duration) -> Self {
+        self.tombstone_max_age = age;
+        self
+    }
+
+    fn max_age(&self, outcome: VaultOutcome) -> Option<Duration> {
+        match outcome {
+            VaultOutcome::Claimed => self.claimed_max_age,
+            VaultOutcome::Invalidated => self.invalidated_max_age,
+        }
+    }
+}
+
+/// Vaults removed by pruning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultPruneStats {
+    /// Claimed vaults removed
+    pub claimed_removed: usize,
+
+    /// Invalidated vaults removed
+    pub invalidated_removed: usize,
+
+    /// Serialized bytes of the removed vaults
+    pub reclaimed_bytes: usize,
+}
+
+impl VaultPruneStats {
+    /// Total number of vaults removed
+    pub fn removed(&self) -> usize {
+        self.claimed_removed + self.invalidated_removed
+    }
+
+    fn record(&mut self, outcome: VaultOutcome, size_bytes: usize) {
+        match outcome {
+            VaultOutcome::Claimed => self.claimed_removed += 1,
+            VaultOutcome::Invalidated => self.invalidated_removed += 1,
+        }
+        self.reclaimed_bytes += size_bytes;
+    }
+
+    fn add(&mut self, other: &VaultPruneStats) {
+        self.claimed_removed += other.claimed_removed;
+        self.invalidated_removed += other.invalidated_removed;
+        self.reclaimed_bytes += other.reclaimed_bytes;
+    }
+}
+
+/// What a history purge removed from the cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatePurgeStats {
+    /// History states removed
+    pub states_removed: usize,
+
+    /// Checkpoints removed
+    pub checkpoints_removed: usize,
+
+    /// Serialized bytes of all removed entries
+    pub bytes_freed: usize,
+}
+
+/// Default number of genesis hashes prewarmed at once
+pub const DEFAULT_PREWARM_CONCURRENCY: usize = 8;
+
+/// Peer that a cold cache is prewarmed from
+#[async_trait]
+pub trait CachePeer: Send + Sync {
+    /// Fetch a genesis state by hash
+    async fn fetch_genesis(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>, DsmError>;
+
+    /// Fetch the latest checkpoint of the chain rooted at a genesis state
+    async fn fetch_latest_checkpoint(&self, genesis_hash: &[u8])
+        -> Result<Option<State>, DsmError>;
+
+    /// Fetch the invalidation markers published for any of the given states
+    async fn fetch_invalidation_markers(
+        &self,
+        state_hashes: &[Vec<u8>],
+    ) -> Result<Vec<InvalidationMarker>, DsmError>;
+}
+
+/// What a prewarm fetched from the peer
+#[derive(Debug, Default)]
+pub struct PrewarmReport {
+    /// Genesis hashes fetched and cached
+    pub fetched: usize,
+
+    /// Genesis hashes skipped because they were already cached
+    pub skipped: usize,
+
+    /// Genesis hashes that could not be prewarmed, with the reason
+    pub errors: Vec<(Vec<u8>, DsmError)>,
+}
+
+/// Byte accounting over a single category's entries
+trait ByteAccounted {
+    /// Total serialized bytes held
+    fn total_bytes(&self) -> usize;
+
+    /// Access time of the least recently used entry
+    fn oldest_access(&self) -> Option<SystemTime>;
+
+    /// Evict the least recently used entry, returning the bytes freed
+    fn evict_lru(&mut self) -> Option<usize>;
+}
+
+impl<T> ByteAccounted for HashMap<String, CacheEntry<T>> {
+    fn total_bytes(&self) -> usize {
+        self.values().map(|entry| entry.size_bytes).sum()
+    }
+
+    fn oldest_access(&self) -> Option<SystemTime> {
+        self.values().map(|entry| entry.last_accessed).min()
+    }
+
+    fn evict_lru(&mut self) -> Option<usize> {
+        let oldest_key = self
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_accessed)
+            .map(|(k, _)| k.clone())?;
+
+        self.remove(&oldest_key).map(|entry| entry.size_bytes)
+    }
+}
+
+/// Separator between a namespace and the key it qualifies
+///
+/// The ASCII unit separator does not occur in hex digests or identifiers, so
+/// keys in the default namespace can never be mistaken for namespaced ones.
+const NAMESPACE_SEPARATOR: char = '\u{1f}';
+
+/// Remove the states of one namespace numbered below a cutoff from a category map
+///
+/// # Returns
+/// * `(usize, usize)` - Number of entries removed and their serialized bytes
+async fn purge_states_below(
+    cache: &RwLock<HashMap<String, CacheEntry<State>>>,
+    namespace: Option<&str>,
+    state_number: u64,
+) -> (usize, usize) {
+    let mut cache = cache.write().await;
+    let mut removed = (0, 0);
+
+    cache.retain(|key, entry| {
+        let keep =
+            !StorageCache::in_namespace(namespace, key) || entry.data.state_number >= state_number;
+        if !keep {
+            removed.0 += 1;
+            removed.1 += entry.size_bytes;
+        }
+        keep
+    });
+
+    removed
+}
+
+/// Remove every entry of one namespace from a category map
+async fn purge_keys<T>(
+    cache: &RwLock<HashMap<String, CacheEntry<T>>>,
+    namespace: Option<&str>,
+) -> usize {
+    let mut cache = cache.write().await;
+    let before = cache.len();
+    cache.retain(|key, _| !StorageCache::in_namespace(namespace, key));
+    before - cache.len()
+}
+
+/// Callback invoked with the state hash of every newly cached invalidation marker
+pub type InvalidationListener = Box<dyn Fn(&[u8]) + Send + Sync>;
+
+/// Identifies a registered invalidation listener so it can be removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvalidationListenerId(u64);
+
+/// Registered invalidation listeners
+///
+/// Listeners are synchronous and called from the cache write path, so they
+/// live behind a blocking lock that is never held across an await point.
+#[derive(Default)]
+struct InvalidationListeners {
+    listeners: std::sync::RwLock<Vec<(InvalidationListenerId, InvalidationListener)>>,
+    next_id: AtomicU64,
+}
+
+impl InvalidationListeners {
+    fn register(&self, listener: InvalidationListener) -> InvalidationListenerId {
+        let id = InvalidationListenerId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.listeners
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push((id, listener));
+        id
+    }
+
+    fn remove(&self, id: InvalidationListenerId) -> bool {
+        let mut listeners = self
+            .listeners
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let before = listeners.len();
+        listeners.retain(|(registered, _)| *registered != id);
+        listeners.len() < before
+    }
+
+    /// Call every listener, catching and logging panics
+    fn notify(&self, state_hash: &[u8]) {
+        let listeners = self
+            .listeners
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for (_, listener) in listeners.iter() {
+            if let Err(panic) = catch_unwind(AssertUnwindSafe(|| listener(state_hash))) {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                warn!(
+                    state_hash = %hex::encode(state_hash),
+                    panic = %message,
+                    "Invalidation listener panicked"
+                );
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.listeners
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+}
+
+impl std::fmt::Debug for InvalidationListeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InvalidationListeners")
+            .field("count", &self.len())
+            .finish()
+    }
+}
+
 /// Storage cache to enable offline operations
 #[derive(Debug)]
 pub struct StorageCache {
@@ -105,11 +625,35 @@ pub struct StorageCache {
     /// Invalidation marker cache
     invalidation_cache: RwLock<HashMap<String, CacheEntry<InvalidationMarker>>>,
 
+    /// Vault cache
+    vault_cache: RwLock<HashMap<String, CacheEntry<LimboVault>>>,
+
+    /// State history cache, keyed by state hash
+    state_cache: RwLock<HashMap<String, CacheEntry<State>>>,
+
     /// Maximum size of each cache
     max_entries: usize,
 
     /// Default TTL for cache entries in seconds
     default_ttl: u64,
+
+    /// Byte budgets for the cache
+    limits: RwLock<CacheLimits>,
+
+    /// Listeners notified when a new invalidation marker is cached
+    invalidation_listeners: InvalidationListeners,
+
+    /// Vaults whose reference state has been invalidated
+    flagged_vaults: RwLock<HashSet<String>>,
+
+    /// Tombstones of pruned vaults, by cache key
+    vault_tombstones: RwLock<HashMap<String, VaultTombstone>>,
+
+    /// Totals across every pruning pass
+    vault_prune_totals: RwLock<VaultPruneStats>,
+
+    /// Maximum number of genesis hashes prewarmed at once
+    max_concurrency: AtomicUsize,
 }
 
 impl StorageCache {
@@ -120,8 +664,16 @@ pub fn new() -> Self {
             token_cache: RwLock::new(HashMap::new()),
             checkpoint_cache: RwLock::new(HashMap::new()),
             invalidation_cache: RwLock::new(HashMap::new()),
+            vault_cache: RwLock::new(HashMap::new()),
+            state_cache: RwLock::new(HashMap::new()),
             max_entries: 1000,
             default_ttl: 86400 * 30, // 30 days by default
+            limits: RwLock::new(CacheLimits::default()),
+            invalidation_listeners: InvalidationListeners::default(),
+            flagged_vaults: RwLock::new(HashSet::new()),
+            vault_tombstones: RwLock::new(HashMap::new()),
+            vault_prune_totals: RwLock::new(VaultPruneStats::default()),
+            max_concurrency: AtomicUsize::new(DEFAULT_PREWARM_CONCURRENCY),
         }
     }
 
@@ -132,142 +684,236 @@ pub fn with_settings(max_entries: usize, default_ttl: u64) -> Self {
             token_cache: RwLock::new(HashMap::new()),
             checkpoint_cache: RwLock::new(HashMap::new()),
             invalidation_cache: RwLock::new(HashMap::new()),
+            vault_cache: RwLock::new(HashMap::new()),
+            state_cache: RwLock::new(HashMap::new()),
             max_entries,
             default_ttl,
+            limits: RwLock::new(CacheLimits::default()),
+            invalidation_listeners: InvalidationListeners::default(),
+            flagged_vaults: RwLock::new(HashSet::new()),
+            vault_tombstones: RwLock::new(HashMap::new()),
+            vault_prune_totals: RwLock::new(VaultPruneStats::default()),
+            max_concurrency: AtomicUsize::new(DEFAULT_PREWARM_CONCURRENCY),
         }
     }
 
-    /// Calculate cache key from genesis hash
-    fn genesis_key(genesis_hash: &[u8]) -> String {
-        hex::encode(genesis_hash)
+    /// Create a storage cache with custom settings and byte budgets
+    pub fn with_limits(max_entries: usize, default_ttl: u64, limits: CacheLimits) -> Self {
+        Self {
+            limits: RwLock::new(limits),
+            ..Self::with_settings(max_entries, default_ttl)
+        }
     }
 
-    /// Cache a genesis state
-    pub async fn cache_genesis(
+    /// Get the current byte budgets
+    pub async fn limits(&self) -> CacheLimits {
+        self.limits.read().await.clone()
+    }
+
+    /// Replace the byte budgets and enforce them immediately
+    ///
+    /// # Returns
+    /// * `usize` - Number of entries evicted to satisfy the new budgets
+    pub async fn set_limits(&self, limits: CacheLimits) -> usize {
+        *self.limits.write().await = limits.clone();
+        self.enforce_limits(&limits).await
+    }
+
+    /// Insert an entry into one category, enforcing entry-count and byte budgets
+    async fn insert_entry<T>(
         &self,
-        genesis: GenesisState,
-        verified: bool,
-        ttl: Option<u64>,
+        category: CacheCategory,
+        cache: &RwLock<HashMap<String, CacheEntry<T>>>,
+        key: String,
+        entry: CacheEntry<T>,
     ) -> Result<(), DsmError> {
-        let mut cache = self.genesis_cache.write().await;
+        let limits = self.limits.read().await.clone();
+        limits.check_entry_size(category, entry.size_bytes)?;
 
-        // Check if cache is full
-        if cache.len() >= self.max_entries {
-            // Remove oldest entry
-            let oldest_key = cache
-                .iter()
-                .min_by_key(|(_, entry)| entry.last_accessed)
-                .map(|(k, _)| k.clone());
+        {
+            let mut cache = cache.write().await;
 
-            if let Some(key) = oldest_key {
-                cache.remove(&key);
+            // Replacing an entry frees its previous footprint first
+            cache.remove(&key);
+
+            // Check if cache is full
+            if cache.len() >= self.max_entries {
+                cache.evict_lru();
             }
-        }
 
-        // Calculate key
-        let serialized = bincode::serialize(&genesis)?;
-        let hash = *blake3::hash(&serialized).as_bytes();
-        let key = Self::genesis_key(&hash);
+            // Make room within the category budget, oldest entries first
+            if let Some(budget) = limits.category_budgets.get(&category) {
+                while cache.total_bytes() + entry.size_bytes > *budget {
+                    if cache.evict_lru().is_none() {
+                        break;
+                    }
+                }
+            }
 
-        // Create cache entry
-        let entry = CacheEntry::new(genesis, ttl.unwrap_or(self.default_ttl), verified)?;
+            cache.insert(key, entry);
+        }
 
-        // Store in cache
-        cache.insert(key, entry);
+        if limits.max_total_bytes.is_some() {
+            self.enforce_limits(&limits).await;
+        }
 
         Ok(())
     }
 
-    /// Get a cached genesis state
-    pub async fn get_genesis(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>, DsmError> {
-        let mut cache = self.genesis_cache.write().await;
-        let key = Self::genesis_key(genesis_hash);
-
-        if let Some(entry) = cache.get_mut(&key) {
-            // Check if expired
-            if entry.is_expired() {
-                cache.remove(&key);
-                return Ok(None);
+    /// Evict entries until every category budget and the global budget are met
+    ///
+    /// Over-budget categories are trimmed first; any remaining global overage is
+    /// resolved by evicting the least recently used entry across all categories.
+    async fn enforce_limits(&self, limits: &CacheLimits) -> usize {
+        // Locks are always taken in the same order to avoid deadlocks
+        let mut genesis = self.genesis_cache.write().await;
+        let mut token = self.token_cache.write().await;
+        let mut checkpoint = self.checkpoint_cache.write().await;
+        let mut invalidation = self.invalidation_cache.write().await;
+        let mut vault = self.vault_cache.write().await;
+        let mut state = self.state_cache.write().await;
+
+        let mut caches: [(CacheCategory, &mut dyn ByteAccounted); 6] = [
+            (CacheCategory::Genesis, &mut *genesis),
+            (CacheCategory::Token, &mut *token),
+            (CacheCategory::Checkpoint, &mut *checkpoint),
+            (CacheCategory::Invalidation, &mut *invalidation),
+            (CacheCategory::Vault, &mut *vault),
+            (CacheCategory::State, &mut *state),
+        ];
+
+        let mut evicted = 0;
+
+        for (category, cache) in caches.iter_mut() {
+            if let Some(budget) = limits.category_budgets.get(&*category) {
+                while cache.total_bytes() > *budget && cache.evict_lru().is_some() {
+                    evicted += 1;
+                }
             }
+        }
 
-            // Check integrity
-            if !entry.verify_integrity()? {
-                cache.remove(&key);
-                return Err(DsmError::Integrity {
-                    context: "Genesis state cache integrity check failed".into(),
-                    source: None,
-                });
-            }
+        if let Some(budget) = limits.max_total_bytes {
+            loop {
+                let total: usize = caches.iter().map(|(_, cache)| cache.total_bytes()).sum();
+                if total <= budget {
+                    break;
+                }
 
-            // Update last accessed
-            entry.update_accessed();
+                // Find the category holding the least recently used entry
+                let mut oldest: Option<(SystemTime, usize)> = None;
+                for (index, (_, cache)) in caches.iter().enumerate() {
+                    if let Some(accessed) = cache.oldest_access() {
+                        if oldest.is_none_or(|(current, _)| accessed < current) {
+                            oldest = Some((accessed, index));
+                        }
+                    }
+                }
 
-            Ok(Some(entry.data.clone()))
-        } else {
-            Ok(None)
+                match oldest {
+                    Some((_, index)) => {
+                        if caches[index].1.evict_lru().is_none() {
+                            break;
+                        }
+                        evicted += 1;
+                    }
+                    None => break,
+                }
+            }
         }
+
+        evicted
     }
 
-    /// Check if a genesis state is cached
-    pub async fn has_genesis(&self, genesis_hash: &[u8]) -> bool {
-        let cache = self.genesis_cache.read().await;
-        let key = Self::genesis_key(genesis_hash);
+    /// Calculate cache key from genesis hash
+    fn genesis_key(genesis_hash: &[u8]) -> String {
+        hex::encode(genesis_hash)
+    }
 
-        if let Some(entry) = cache.get(&key) {
-            !entry.is_expired()
-        } else {
-            false
+    /// Qualify a cache key with a namespace; `None` is the default namespace
+    fn namespaced_key(namespace: Option<&str>, key: &str) -> String {
+        match namespace {
+            Some(namespace) => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, key),
+            None => key.to_string(),
         }
     }
 
-    /// Cache a token
-    pub async fn cache_token(
-        &self,
-        token_id: &str,
-        token: Token,
-        verified: bool,
-        ttl: Option<u64>,
-    ) -> Result<(), DsmError> {
-        let mut cache = self.token_cache.write().await;
-
-        // Check if cache is full
-        if cache.len() >= self.max_entries {
-            // Remove oldest entry
-            let oldest_key = cache
-                .iter()
-                .min_by_key(|(_, entry)| entry.last_accessed)
-                .map(|(k, _)| k.clone());
-
-            if let Some(key) = oldest_key {
-                cache.remove(&key);
-            }
+    /// Whether a cache key belongs to the given namespace
+    fn in_namespace(namespace: Option<&str>, key: &str) -> bool {
+        match namespace {
+            Some(namespace) => key
+                .strip_prefix(namespace)
+                .is_some_and(|rest| rest.starts_with(NAMESPACE_SEPARATOR)),
+            None => !key.contains(NAMESPACE_SEPARATOR),
         }
+    }
 
-        // Create cache entry
-        let entry = CacheEntry::new(token, ttl.unwrap_or(self.default_ttl), verified)?;
-
-        // Store in cache
-        cache.insert(token_id.to_string(), entry);
+    /// Get a handle whose operations are confined to one namespace
+    ///
+    /// Use a stable per-identity value such as the hex-encoded genesis hash so
+    /// that several identities on one device never see each other's entries.
+    pub fn scoped(&self, namespace: impl Into<String>) -> ScopedStorageCache<'_> {
+        ScopedStorageCache {
+            cache: self,
+            namespace: namespace.into(),
+        }
+    }
 
-        Ok(())
+    /// Remove every entry stored under a namespace
+    ///
+    /// # Returns
+    /// * `usize` - Number of entries removed
+    pub async fn purge_namespace(&self, namespace: &str) -> usize {
+        let namespace = Some(namespace);
+        let mut removed = 0;
+
+        removed += purge_keys(&self.genesis_cache, namespace).await;
+        removed += purge_keys(&self.token_cache, namespace).await;
+        removed += purge_keys(&self.checkpoint_cache, namespace).await;
+        removed += purge_keys(&self.invalidation_cache, namespace).await;
+        removed += purge_keys(&self.vault_cache, namespace).await;
+        removed += purge_keys(&self.state_cache, namespace).await;
+
+        self.flagged_vaults
+            .write()
+            .await
+            .retain(|key| !Self::in_namespace(namespace, key));
+        self.vault_tombstones
+            .write()
+            .await
+            .retain(|key, _| !Self::in_namespace(namespace, key));
+
+        removed
     }
 
-    /// Get a cached token
-    pub async fn get_token(&self, token_id: &str) -> Result<Option<Token>, DsmError> {
-        let mut cache = self.token_cache.write().await;
+    /// Get an unexpired entry, dropping it if it fails its integrity check
+    ///
+    /// Expired entries are kept so they can still be served as stale; they are
+    /// removed by `clear_expired` or eviction.
+    async fn get_entry<T: Serialize + Clone>(
+        cache: &RwLock<HashMap<String, CacheEntry<T>>>,
+        key: &str,
+        description: &str,
+    ) -> Result<Option<T>, DsmError> {
+        Ok(Self::get_entry_with_freshness(cache, key, description)
+            .await?
+            .filter(CacheLookup::is_fresh)
+            .map(CacheLookup::into_value))
+    }
 
-        if let Some(entry) = cache.get_mut(token_id) {
-            // Check if expired
-            if entry.is_expired() {
-                cache.remove(token_id);
-                return Ok(None);
-            }
+    /// Get an entry whether or not it has expired, tagged with its freshness
+    async fn get_entry_with_freshness<T: Serialize + Clone>(
+        cache: &RwLock<HashMap<String, CacheEntry<T>>>,
+        key: &str,
+        description: &str,
+    ) -> Result<Option<CacheLookup<T>>, DsmError> {
+        let mut cache = cache.write().await;
 
+        if let Some(entry) = cache.get_mut(key) {
             // Check integrity
             if !entry.verify_integrity()? {
-                cache.remove(token_id);
+                cache.remove(key);
                 return Err(DsmError::Integrity {
-                    context: "Token cache integrity check failed".into(),
+                    context: format!("{} cache integrity check failed", description),
                     source: None,
                 });
             }
@@ -275,71 +921,300 @@ pub async fn get_token(&self, token_id: &str) -> Result<Option<Token>, DsmError>
             // Update last accessed
             entry.update_accessed();
 
-            Ok(Some(entry.data.clone()))
+            let freshness = if entry.is_expired() {
+                Freshness::Stale
+            } else {
+                Freshness::Fresh
+            };
+
+            Ok(Some(CacheLookup {
+                value: entry.data.clone(),
+                freshness,
+            }))
         } else {
             Ok(None)
         }
     }
 
-    /// Check if a token is cached
-    pub async fn has_token(&self, token_id: &str) -> bool {
-        let cache = self.token_cache.read().await;
+    /// Check if an unexpired entry exists
+    async fn has_entry<T: Serialize + Clone>(
+        cache: &RwLock<HashMap<String, CacheEntry<T>>>,
+        key: &str,
+    ) -> bool {
+        let cache = cache.read().await;
 
-        if let Some(entry) = cache.get(token_id) {
+        if let Some(entry) = cache.get(key) {
             !entry.is_expired()
         } else {
             false
         }
     }
 
-    /// Cache a checkpoint state
-    pub async fn cache_checkpoint(
+    /// Cache a genesis state
+    pub async fn cache_genesis(
         &self,
-        state: State,
+        genesis: GenesisState,
         verified: bool,
         ttl: Option<u64>,
     ) -> Result<(), DsmError> {
-        let mut cache = self.checkpoint_cache.write().await;
-
-        // Check if cache is full
-        if cache.len() >= self.max_entries {
-            // Remove oldest entry
-            let oldest_key = cache
-                .iter()
-                .min_by_key(|(_, entry)| entry.last_accessed)
-                .map(|(k, _)| k.clone());
-
-            if let Some(key) = oldest_key {
-                cache.remove(&key);
-            }
-        }
+        self.cache_genesis_in(None, genesis, verified, ttl).await
+    }
 
-        // Calculate key
-        let key = format!(
-            "checkpoint_{}_{}",
-            state.state_number,
-            hex::encode(state.hash()?)
-        );
+    async fn cache_genesis_in(
+        &self,
+        namespace: Option<&str>,
+        genesis: GenesisState,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        // Calculate key, preferring the genesis hash itself so lookups by hash hit
+        let key = if genesis.hash.is_empty() {
+            let serialized = bincode::serialize(&genesis)?;
+            Self::genesis_key(blake3::hash(&serialized).as_bytes())
+        } else {
+            Self::genesis_key(&genesis.hash)
+        };
+
+        // Create cache entry
+        let entry = CacheEntry::new(genesis, ttl.unwrap_or(self.default_ttl), verified)?;
+
+        // Store in cache
+        self.insert_entry(
+            CacheCategory::Genesis,
+            &self.genesis_cache,
+            Self::namespaced_key(namespace, &key),
+            entry,
+        )
+        .await
+    }
+
+    /// Get a cached genesis state
+    pub async fn get_genesis(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>, DsmError> {
+        self.get_genesis_in(None, genesis_hash).await
+    }
+
+    async fn get_genesis_in(
+        &self,
+        namespace: Option<&str>,
+        genesis_hash: &[u8],
+    ) -> Result<Option<GenesisState>, DsmError> {
+        let key = Self::namespaced_key(namespace, &Self::genesis_key(genesis_hash));
+        Self::get_entry(&self.genesis_cache, &key, "Genesis state").await
+    }
+
+    /// Get a cached genesis state, serving it stale once its TTL has passed
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hash of the genesis state
+    /// * `required` - Minimum acceptable freshness; `Freshness::Fresh` turns a
+    ///   stale entry into an error
+    pub async fn get_genesis_with_freshness(
+        &self,
+        genesis_hash: &[u8],
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<GenesisState>>, DsmError> {
+        self.get_genesis_with_freshness_in(None, genesis_hash, required)
+            .await
+    }
+
+    async fn get_genesis_with_freshness_in(
+        &self,
+        namespace: Option<&str>,
+        genesis_hash: &[u8],
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<GenesisState>>, DsmError> {
+        let key = Self::namespaced_key(namespace, &Self::genesis_key(genesis_hash));
+        Self::get_entry_with_freshness(&self.genesis_cache, &key, "Genesis state")
+            .await?
+            .map(|lookup| lookup.require(required, "genesis state"))
+            .transpose()
+    }
+
+    /// Check if a genesis state is cached
+    pub async fn has_genesis(&self, genesis_hash: &[u8]) -> bool {
+        self.has_genesis_in(None, genesis_hash).await
+    }
+
+    async fn has_genesis_in(&self, namespace: Option<&str>, genesis_hash: &[u8]) -> bool {
+        let key = Self::namespaced_key(namespace, &Self::genesis_key(genesis_hash));
+        Self::has_entry(&self.genesis_cache, &key).await
+    }
+
+    /// Cache a token
+    pub async fn cache_token(
+        &self,
+        token_id: &str,
+        token: Token,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache_token_in(None, token_id, token, verified, ttl)
+            .await
+    }
+
+    async fn cache_token_in(
+        &self,
+        namespace: Option<&str>,
+        token_id: &str,
+        token: Token,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        // Create cache entry
+        let entry = CacheEntry::new(token, ttl.unwrap_or(self.default_ttl), verified)?;
+
+        // Store in cache
+        self.insert_entry(
+            CacheCategory::Token,
+            &self.token_cache,
+            Self::namespaced_key(namespace, token_id),
+            entry,
+        )
+        .await
+    }
+
+    /// Get a cached token
+    pub async fn get_token(&self, token_id: &str) -> Result<Option<Token>, DsmError> {
+        self.get_token_in(None, token_id).await
+    }
+
+    async fn get_token_in(
+        &self,
+        namespace: Option<&str>,
+        token_id: &str,
+    ) -> Result<Option<Token>, DsmError> {
+        let key = Self::namespaced_key(namespace, token_id);
+        Self::get_entry(&self.token_cache, &key, "Token").await
+    }
+
+    /// Get a cached token, serving it stale once its TTL has passed
+    ///
+    /// # Arguments
+    /// * `token_id` - Identifier of the token
+    /// * `required` - Minimum acceptable freshness; `Freshness::Fresh` turns a
+    ///   stale entry into an error
+    pub async fn get_token_with_freshness(
+        &self,
+        token_id: &str,
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<Token>>, DsmError> {
+        self.get_token_with_freshness_in(None, token_id, required)
+            .await
+    }
+
+    async fn get_token_with_freshness_in(
+        &self,
+        namespace: Option<&str>,
+        token_id: &str,
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<Token>>, DsmError> {
+        let key = Self::namespaced_key(namespace, token_id);
+        Self::get_entry_with_freshness(&self.token_cache, &key, "Token")
+            .await?
+            .map(|lookup| lookup.require(required, "token"))
+            .transpose()
+    }
+
+    /// Check if a token is cached
+    pub async fn has_token(&self, token_id: &str) -> bool {
+        self.has_token_in(None, token_id).await
+    }
+
+    async fn has_token_in(&self, namespace: Option<&str>, token_id: &str) -> bool {
+        let key = Self::namespaced_key(namespace, token_id);
+        Self::has_entry(&self.token_cache, &key).await
+    }
+
+    /// Cache a checkpoint state
+    pub async fn cache_checkpoint(
+        &self,
+        state: State,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache_checkpoint_in(None, state, verified, ttl).await
+    }
+
+    async fn cache_checkpoint_in(
+        &self,
+        namespace: Option<&str>,
+        state: State,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        // Calculate key
+        let key = Self::namespaced_key(namespace, &Self::checkpoint_id(&state)?);
 
         // Create cache entry
         let entry = CacheEntry::new(state, ttl.unwrap_or(self.default_ttl), verified)?;
 
         // Store in cache
-        cache.insert(key, entry);
+        self.insert_entry(
+            CacheCategory::Checkpoint,
+            &self.checkpoint_cache,
+            key,
+            entry,
+        )
+        .await
+    }
 
-        Ok(())
+    /// Identifier under which a checkpoint state is cached
+    pub fn checkpoint_id(state: &State) -> Result<String, DsmError> {
+        Ok(Self::checkpoint_id_for(state.state_number, &state.hash()?))
+    }
+
+    /// Identifier of the checkpoint for a state known only by number and hash
+    pub fn checkpoint_id_for(state_number: u64, state_hash: &[u8]) -> String {
+        format!("checkpoint_{}_{}", state_number, hex::encode(state_hash))
+    }
+
+    /// Get a cached checkpoint by its identifier
+    pub async fn get_checkpoint(&self, checkpoint_id: &str) -> Result<Option<State>, DsmError> {
+        self.get_checkpoint_in(None, checkpoint_id).await
+    }
+
+    async fn get_checkpoint_in(
+        &self,
+        namespace: Option<&str>,
+        checkpoint_id: &str,
+    ) -> Result<Option<State>, DsmError> {
+        let key = Self::namespaced_key(namespace, checkpoint_id);
+        Self::get_entry(&self.checkpoint_cache, &key, "Checkpoint").await
+    }
+
+    /// Check if a checkpoint is cached
+    pub async fn has_checkpoint(&self, checkpoint_id: &str) -> bool {
+        self.has_checkpoint_in(None, checkpoint_id).await
+    }
+
+    async fn has_checkpoint_in(&self, namespace: Option<&str>, checkpoint_id: &str) -> bool {
+        let key = Self::namespaced_key(namespace, checkpoint_id);
+        Self::has_entry(&self.checkpoint_cache, &key).await
     }
 
     /// Get closest checkpoint before a given state number
     pub async fn get_closest_checkpoint(
         &self,
         state_number: u64,
+    ) -> Result<Option<State>, DsmError> {
+        self.get_closest_checkpoint_in(None, state_number).await
+    }
+
+    async fn get_closest_checkpoint_in(
+        &self,
+        namespace: Option<&str>,
+        state_number: u64,
     ) -> Result<Option<State>, DsmError> {
         let cache = self.checkpoint_cache.read().await;
 
         let mut closest: Option<(u64, &CacheEntry<State>)> = None;
 
-        for (_, entry) in cache.iter() {
+        for (key, entry) in cache.iter() {
+            // Skip entries belonging to other namespaces
+            if !Self::in_namespace(namespace, key) {
+                continue;
+            }
+
             // Skip expired entries
             if entry.is_expired() {
                 continue;
@@ -382,37 +1257,137 @@ pub async fn cache_invalidation(
         verified: bool,
         ttl: Option<u64>,
     ) -> Result<(), DsmError> {
-        let mut cache = self.invalidation_cache.write().await;
-
-        // Check if cache is full
-        if cache.len() >= self.max_entries {
-            // Remove oldest entry
-            let oldest_key = cache
-                .iter()
-                .min_by_key(|(_, entry)| entry.last_accessed)
-                .map(|(k, _)| k.clone());
-
-            if let Some(key) = oldest_key {
-                cache.remove(&key);
-            }
-        }
+        self.cache_invalidation_in(None, marker, verified, ttl)
+            .await
+    }
 
+    async fn cache_invalidation_in(
+        &self,
+        namespace: Option<&str>,
+        marker: InvalidationMarker,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
         // Calculate key
-        let key = hex::encode(&marker.state_hash);
+        let key = Self::namespaced_key(namespace, &hex::encode(&marker.state_hash));
+        let state_hash = marker.state_hash.clone();
+
+        // Only a marker for a state not already known to be invalid is news to listeners
+        let is_new = !self.is_state_invalidated_in(namespace, &state_hash).await?;
 
         // Create cache entry
         let entry = CacheEntry::new(marker, ttl.unwrap_or(self.default_ttl), verified)?;
 
         // Store in cache
-        cache.insert(key, entry);
+        self.insert_entry(
+            CacheCategory::Invalidation,
+            &self.invalidation_cache,
+            key,
+            entry,
+        )
+        .await?;
+
+        if is_new {
+            self.invalidation_listeners.notify(&state_hash);
+        }
 
         Ok(())
     }
 
+    /// Register a listener fired whenever a new invalidation marker is cached
+    ///
+    /// The listener receives the hash of the invalidated state. It runs on the
+    /// cache write path, so it must not block; panics are caught and logged.
+    /// The listener stays registered until removed with the returned ID.
+    pub fn on_invalidation(&self, listener: InvalidationListener) -> InvalidationListenerId {
+        self.invalidation_listeners.register(listener)
+    }
+
+    /// Remove a listener registered with `on_invalidation`
+    ///
+    /// Returns `false` if the listener was already removed.
+    pub fn remove_invalidation_listener(&self, id: InvalidationListenerId) -> bool {
+        self.invalidation_listeners.remove(id)
+    }
+
+    /// Number of registered invalidation listeners
+    pub fn invalidation_listener_count(&self) -> usize {
+        self.invalidation_listeners.len()
+    }
+
+    /// Drop cached checkpoints derived from an invalidated state
+    ///
+    /// Removes the checkpoint for the state itself and, when its invalidation
+    /// marker is cached, every later checkpoint of the same device.
+    ///
+    /// # Returns
+    /// * `usize` - Number of checkpoints removed
+    pub async fn drop_checkpoints_for_state(&self, state_hash: &[u8]) -> usize {
+        let marker = self
+            .invalidation_cache
+            .read()
+            .await
+            .values()
+            .find(|entry| entry.data.state_hash == state_hash)
+            .map(|entry| entry.data.clone());
+
+        let mut cache = self.checkpoint_cache.write().await;
+        let before = cache.len();
+
+        cache.retain(|_, entry| {
+            let state = &entry.data;
+            let same_state = state.hash().is_ok_and(|hash| hash == state_hash);
+            let descendant = marker.as_ref().is_some_and(|marker| {
+                state.device_info.device_id == marker.device_id
+                    && state.state_number >= marker.state_number
+            });
+            !(same_state || descendant)
+        });
+
+        before - cache.len()
+    }
+
+    /// Flag cached vaults whose reference state has been invalidated
+    ///
+    /// # Returns
+    /// * `Vec<String>` - Cache keys of the vaults that were flagged
+    pub async fn flag_vaults_for_state(&self, state_hash: &[u8]) -> Vec<String> {
+        let affected: Vec<String> = self
+            .vault_cache
+            .read()
+            .await
+            .iter()
+            .filter(|(_, entry)| entry.data.reference_state_hash == state_hash)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        if !affected.is_empty() {
+            self.flagged_vaults
+                .write()
+                .await
+                .extend(affected.iter().cloned());
+        }
+
+        affected
+    }
+
+    /// Check if a vault has been flagged because its reference state was invalidated
+    pub async fn is_vault_flagged(&self, vault_id: &str) -> bool {
+        self.flagged_vaults.read().await.contains(vault_id)
+    }
+
     /// Check if a state has been invalidated
     pub async fn is_state_invalidated(&self, state_hash: &[u8]) -> Result<bool, DsmError> {
+        self.is_state_invalidated_in(None, state_hash).await
+    }
+
+    async fn is_state_invalidated_in(
+        &self,
+        namespace: Option<&str>,
+        state_hash: &[u8],
+    ) -> Result<bool, DsmError> {
         let cache = self.invalidation_cache.read().await;
-        let key = hex::encode(state_hash);
+        let key = Self::namespaced_key(namespace, &hex::encode(state_hash));
 
         if let Some(entry) = cache.get(&key) {
             if entry.is_expired() {
@@ -434,31 +1409,259 @@ pub async fn get_invalidation(
         &self,
         state_hash: &[u8],
     ) -> Result<Option<InvalidationMarker>, DsmError> {
-        let mut cache = self.invalidation_cache.write().await;
-        let key = hex::encode(state_hash);
+        self.get_invalidation_in(None, state_hash).await
+    }
 
-        if let Some(entry) = cache.get_mut(&key) {
-            // Check if expired
-            if entry.is_expired() {
-                cache.remove(&key);
-                return Ok(None);
+    async fn get_invalidation_in(
+        &self,
+        namespace: Option<&str>,
+        state_hash: &[u8],
+    ) -> Result<Option<InvalidationMarker>, DsmError> {
+        let key = Self::namespaced_key(namespace, &hex::encode(state_hash));
+        Self::get_entry(&self.invalidation_cache, &key, "Invalidation marker").await
+    }
+
+    /// Cache a vault
+    pub async fn cache_vault(
+        &self,
+        vault: LimboVault,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache_vault_in(None, vault, verified, ttl).await
+    }
+
+    async fn cache_vault_in(
+        &self,
+        namespace: Option<&str>,
+        vault: LimboVault,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        let key = Self::namespaced_key(namespace, &vault.id);
+
+        // Create cache entry
+        let entry = CacheEntry::new(vault, ttl.unwrap_or(self.default_ttl), verified)?;
+
+        // The full vault supersedes any tombstone
+        self.vault_tombstones.write().await.remove(&key);
+
+        // Store in cache
+        self.insert_entry(CacheCategory::Vault, &self.vault_cache, key, entry)
+            .await
+    }
+
+    /// Get a cached vault
+    ///
+    /// A vault pruned after being claimed or invalidated is not cached; its
+    /// outcome is available from `get_vault_tombstone`.
+    pub async fn get_vault(&self, vault_id: &str) -> Result<Option<LimboVault>, DsmError> {
+        self.get_vault_in(None, vault_id).await
+    }
+
+    async fn get_vault_in(
+        &self,
+        namespace: Option<&str>,
+        vault_id: &str,
+    ) -> Result<Option<LimboVault>, DsmError> {
+        let key = Self::namespaced_key(namespace, vault_id);
+        Self::get_entry(&self.vault_cache, &key, "Vault").await
+    }
+
+    /// Get the tombstone left by a pruned vault
+    pub async fn get_vault_tombstone(&self, vault_id: &str) -> Option<VaultTombstone> {
+        self.get_vault_tombstone_in(None, vault_id).await
+    }
+
+    async fn get_vault_tombstone_in(
+        &self,
+        namespace: Option<&str>,
+        vault_id: &str,
+    ) -> Option<VaultTombstone> {
+        let key = Self::namespaced_key(namespace, vault_id);
+        self.vault_tombstones.read().await.get(&key).cloned()
+    }
+
+    /// Remove claimed and invalidated vaults according to a policy
+    ///
+    /// Each removed vault leaves a tombstone so lookups can still report its
+    /// outcome, until the tombstone itself is older than the policy's
+    /// `tombstone_max_age`. Vaults in every namespace are considered.
+    ///
+    /// # Returns
+    /// * `VaultPruneStats` - Vaults removed and bytes reclaimed by this pass
+    pub async fn prune_vaults(&self, policy: &VaultPrunePolicy) -> VaultPruneStats {
+        let mut stats = VaultPruneStats::default();
+        let mut tombstones = Vec::new();
+
+        self.vault_cache.write().await.retain(|key, entry| {
+            let Some((outcome, state_number)) = VaultOutcome::of(&entry.data.state) else {
+                return true;
+            };
+            let Some(max_age) = policy.max_age(outcome) else {
+                return true;
+            };
+            if entry.created_at.elapsed().unwrap_or_default() < max_age {
+                return true;
             }
 
-            // Check integrity
-            if !entry.verify_integrity()? {
-                cache.remove(&key);
-                return Err(DsmError::Integrity {
-                    context: "Invalidation marker cache integrity check failed".into(),
-                    source: None,
-                });
+            stats.record(outcome, entry.size_bytes);
+            tombstones.push((
+                key.clone(),
+                VaultTombstone {
+                    vault_id: entry.data.id.clone(),
+                    outcome,
+                    state_number,
+                    vault_hash: entry.hash,
+                    pruned_at: SystemTime::now(),
+                },
+            ));
+            false
+        });
+
+        self.vault_tombstones.write().await.retain(|_, tombstone| {
+            tombstone.pruned_at.elapsed().unwrap_or_default() < policy.tombstone_max_age
+        });
+
+        if tombstones.is_empty() {
+            return stats;
+        }
+
+        {
+            let mut flagged = self.flagged_vaults.write().await;
+            for (key, _) in &tombstones {
+                flagged.remove(key);
             }
+        }
+        self.vault_tombstones.write().await.extend(tombstones);
+        self.vault_prune_totals.write().await.add(&stats);
 
-            // Update last accessed
-            entry.update_accessed();
+        stats
+    }
 
-            Ok(Some(entry.data.clone()))
-        } else {
-            Ok(None)
+    /// Totals across every pruning pass since the cache was created
+    pub async fn vault_prune_stats(&self) -> VaultPruneStats {
+        *self.vault_prune_totals.read().await
+    }
+
+    /// Prune vaults on a fixed interval for as long as the cache is alive
+    ///
+    /// Must be called from within a tokio runtime. The task holds the cache
+    /// weakly and stops once it is dropped.
+    pub fn spawn_vault_pruning(
+        self: &Arc<Self>,
+        policy: VaultPrunePolicy,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let cache = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                let Some(cache) = cache.upgrade() else {
+                    break;
+                };
+
+                let stats = cache.prune_vaults(&policy).await;
+                if stats.removed() > 0 {
+                    debug!(
+                        claimed_removed = stats.claimed_removed,
+                        invalidated_removed = stats.invalidated_removed,
+                        reclaimed_bytes = stats.reclaimed_bytes,
+                        "Pruned vaults"
+                    );
+                }
+            }
+        })
+    }
+
+    /// Check if a vault is cached
+    pub async fn has_vault(&self, vault_id: &str) -> bool {
+        self.has_vault_in(None, vault_id).await
+    }
+
+    async fn has_vault_in(&self, namespace: Option<&str>, vault_id: &str) -> bool {
+        let key = Self::namespaced_key(namespace, vault_id);
+        Self::has_entry(&self.vault_cache, &key).await
+    }
+
+    /// Cache a state from an identity's history
+    pub async fn cache_state(
+        &self,
+        state: State,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache_state_in(None, state, verified, ttl).await
+    }
+
+    async fn cache_state_in(
+        &self,
+        namespace: Option<&str>,
+        state: State,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        let key = Self::namespaced_key(namespace, &hex::encode(state.hash()?));
+        let entry = CacheEntry::new(state, ttl.unwrap_or(self.default_ttl), verified)?;
+
+        self.insert_entry(CacheCategory::State, &self.state_cache, key, entry)
+            .await
+    }
+
+    /// Get a cached state by its hash
+    pub async fn get_state(&self, state_hash: &[u8]) -> Result<Option<State>, DsmError> {
+        self.get_state_in(None, state_hash).await
+    }
+
+    async fn get_state_in(
+        &self,
+        namespace: Option<&str>,
+        state_hash: &[u8],
+    ) -> Result<Option<State>, DsmError> {
+        let key = Self::namespaced_key(namespace, &hex::encode(state_hash));
+        Self::get_entry(&self.state_cache, &key, "State").await
+    }
+
+    /// Remove a cached state
+    ///
+    /// # Returns
+    /// * `bool` - Whether the state was cached
+    pub async fn remove_state(&self, state_hash: &[u8]) -> bool {
+        self.remove_state_in(None, state_hash).await
+    }
+
+    async fn remove_state_in(&self, namespace: Option<&str>, state_hash: &[u8]) -> bool {
+        let key = Self::namespaced_key(namespace, &hex::encode(state_hash));
+        self.state_cache.write().await.remove(&key).is_some()
+    }
+
+    /// Remove every state and checkpoint numbered below `state_number`
+    ///
+    /// Used when pruning history: a checkpoint at `state_number` or later is
+    /// expected to take the place of everything removed here.
+    pub async fn purge_before_state(&self, state_number: u64) -> StatePurgeStats {
+        self.purge_before_state_in(None, state_number).await
+    }
+
+    async fn purge_before_state_in(
+        &self,
+        namespace: Option<&str>,
+        state_number: u64,
+    ) -> StatePurgeStats {
+        let (states_removed, state_bytes) =
+            purge_states_below(&self.state_cache, namespace, state_number).await;
+        let (checkpoints_removed, checkpoint_bytes) =
+            purge_states_below(&self.checkpoint_cache, namespace, state_number).await;
+
+        StatePurgeStats {
+            states_removed,
+            checkpoints_removed,
+            bytes_freed: state_bytes + checkpoint_bytes,
         }
     }
 
@@ -476,6 +1679,33 @@ pub async fn get_cache_stats(&self) -> HashMap<String, usize> {
             "invalidation".to_string(),
             self.invalidation_cache.read().await.len(),
         );
+        stats.insert("vault".to_string(), self.vault_cache.read().await.len());
+        stats.insert("state".to_string(), self.state_cache.read().await.len());
+
+        stats
+    }
+
+    /// Get the number of cached bytes per category, plus a "total" entry
+    pub async fn get_byte_stats(&self) -> HashMap<String, usize> {
+        let mut stats = HashMap::new();
+
+        let genesis = self.genesis_cache.read().await.total_bytes();
+        let token = self.token_cache.read().await.total_bytes();
+        let checkpoint = self.checkpoint_cache.read().await.total_bytes();
+        let invalidation = self.invalidation_cache.read().await.total_bytes();
+        let vault = self.vault_cache.read().await.total_bytes();
+        let state = self.state_cache.read().await.total_bytes();
+
+        stats.insert(CacheCategory::Genesis.name().to_string(), genesis);
+        stats.insert(CacheCategory::Token.name().to_string(), token);
+        stats.insert(CacheCategory::Checkpoint.name().to_string(), checkpoint);
+        stats.insert(CacheCategory::Invalidation.name().to_string(), invalidation);
+        stats.insert(CacheCategory::Vault.name().to_string(), vault);
+        stats.insert(CacheCategory::State.name().to_string(), state);
+        stats.insert(
+            "total".to_string(),
+            genesis + token + checkpoint + invalidation + vault + state,
+        );
 
         stats
     }
@@ -516,6 +1746,22 @@ pub async fn clear_expired(&self) -> usize {
             total_removed += before - cache.len();
         }
 
+        // Clean vault cache
+        {
+            let mut cache = self.vault_cache.write().await;
+            let before = cache.len();
+            cache.retain(|_, entry| !entry.is_expired());
+            total_removed += before - cache.len();
+        }
+
+        // Clean state cache
+        {
+            let mut cache = self.state_cache.write().await;
+            let before = cache.len();
+            cache.retain(|_, entry| !entry.is_expired());
+            total_removed += before - cache.len();
+        }
+
         total_removed
     }
 
@@ -525,6 +1771,111 @@ pub async fn clear_all(&self) {
         self.token_cache.write().await.clear();
         self.checkpoint_cache.write().await.clear();
         self.invalidation_cache.write().await.clear();
+        self.vault_cache.write().await.clear();
+        self.state_cache.write().await.clear();
+        self.flagged_vaults.write().await.clear();
+        self.vault_tombstones.write().await.clear();
+    }
+
+    /// Maximum number of genesis hashes prewarmed at once
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.load(Ordering::Relaxed)
+    }
+
+    /// Set the maximum number of genesis hashes prewarmed at once
+    pub fn set_max_concurrency(&self, max_concurrency: usize) {
+        self.max_concurrency
+            .store(max_concurrency.max(1), Ordering::Relaxed);
+    }
+
+    /// Populate the cache from a peer before serving live requests
+    ///
+    /// For each genesis hash not already cached and fresh, the genesis state,
+    /// the latest checkpoint of its chain and any invalidation markers for
+    /// either are fetched and cached. Hashes are prewarmed concurrently up to
+    /// `max_concurrency`; a failure for one hash is reported and never aborts
+    /// the others.
+    pub async fn prewarm_from_peer<P: CachePeer + ?Sized>(
+        &self,
+        peer: &P,
+        genesis_hashes: &[Vec<u8>],
+    ) -> Result<PrewarmReport, DsmError> {
+        let outcomes = stream::iter(genesis_hashes)
+            .map(|genesis_hash| async move {
+                if self.has_genesis(genesis_hash).await {
+                    return (genesis_hash, None);
+                }
+                let result = self.prewarm_genesis(peer, genesis_hash).await;
+                (genesis_hash, Some(result))
+            })
+            .buffer_unordered(self.max_concurrency())
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut report = PrewarmReport::default();
+        for (genesis_hash, outcome) in outcomes {
+            match outcome {
+                None => report.skipped += 1,
+                Some(Ok(())) => report.fetched += 1,
+                Some(Err(e)) => {
+                    warn!(
+                        genesis_hash = %hex::encode(genesis_hash),
+                        error = %e,
+                        "Failed to prewarm genesis"
+                    );
+                    report.errors.push((genesis_hash.clone(), e));
+                }
+            }
+        }
+
+        debug!(
+            fetched = report.fetched,
+            skipped = report.skipped,
+            failed = report.errors.len(),
+            "Prewarmed genesis states from peer"
+        );
+        Ok(report)
+    }
+
+    /// Fetch and cache everything known about a single genesis hash
+    ///
+    /// Markers are cached after the checkpoint so that invalidation listeners
+    /// can evict it, and the genesis state is cached last so that a failed
+    /// prewarm is retried in full by the next one.
+    async fn prewarm_genesis<P: CachePeer + ?Sized>(
+        &self,
+        peer: &P,
+        genesis_hash: &[u8],
+    ) -> Result<(), DsmError> {
+        let genesis = peer
+            .fetch_genesis(genesis_hash)
+            .await?
+            .ok_or_else(|| DsmError::not_found("Genesis state", Some(hex::encode(genesis_hash))))?;
+        if genesis.hash != genesis_hash {
+            return Err(DsmError::validation(
+                format!(
+                    "Peer returned a different genesis for {}",
+                    hex::encode(genesis_hash)
+                ),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let checkpoint = peer.fetch_latest_checkpoint(genesis_hash).await?;
+
+        let mut state_hashes = vec![genesis_hash.to_vec()];
+        if let Some(checkpoint) = &checkpoint {
+            state_hashes.push(checkpoint.hash()?);
+        }
+        let markers = peer.fetch_invalidation_markers(&state_hashes).await?;
+
+        if let Some(checkpoint) = checkpoint {
+            self.cache_checkpoint(checkpoint, false, None).await?;
+        }
+        for marker in markers {
+            self.cache_invalidation(marker, false, None).await?;
+        }
+        self.cache_genesis(genesis, false, None).await
     }
 }
 
@@ -533,3 +1884,843 @@ fn default() -> Self {
         Self::new()
     }
 }
+
+/// Handle to a [`StorageCache`] confined to a single namespace
+///
+/// Obtained through [`StorageCache::scoped`]. Every key is prefixed with the
+/// namespace, so identities sharing one cache never collide, while byte
+/// budgets and entry limits remain shared across the whole cache.
+#[derive(Debug, Clone)]
+pub struct ScopedStorageCache<'a> {
+    cache: &'a StorageCache,
+    namespace: String,
+}
+
+impl ScopedStorageCache<'_> {
+    /// The namespace this handle operates in
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    fn ns(&self) -> Option<&str> {
+        Some(&self.namespace)
+    }
+
+    /// Cache a genesis state
+    pub async fn cache_genesis(
+        &self,
+        genesis: GenesisState,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache
+            .cache_genesis_in(self.ns(), genesis, verified, ttl)
+            .await
+    }
+
+    /// Get a cached genesis state
+    pub async fn get_genesis(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>, DsmError> {
+        self.cache.get_genesis_in(self.ns(), genesis_hash).await
+    }
+
+    /// Get a cached genesis state, serving it stale once its TTL has passed
+    pub async fn get_genesis_with_freshness(
+        &self,
+        genesis_hash: &[u8],
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<GenesisState>>, DsmError> {
+        self.cache
+            .get_genesis_with_freshness_in(self.ns(), genesis_hash, required)
+            .await
+    }
+
+    /// Check if a genesis state is cached
+    pub async fn has_genesis(&self, genesis_hash: &[u8]) -> bool {
+        self.cache.has_genesis_in(self.ns(), genesis_hash).await
+    }
+
+    /// Cache a token
+    pub async fn cache_token(
+        &self,
+        token_id: &str,
+        token: Token,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache
+            .cache_token_in(self.ns(), token_id, token, verified, ttl)
+            .await
+    }
+
+    /// Get a cached token
+    pub async fn get_token(&self, token_id: &str) -> Result<Option<Token>, DsmError> {
+        self.cache.get_token_in(self.ns(), token_id).await
+    }
+
+    /// Get a cached token, serving it stale once its TTL has passed
+    pub async fn get_token_with_freshness(
+        &self,
+        token_id: &str,
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<Token>>, DsmError> {
+        self.cache
+            .get_token_with_freshness_in(self.ns(), token_id, required)
+            .await
+    }
+
+    /// Check if a token is cached
+    pub async fn has_token(&self, token_id: &str) -> bool {
+        self.cache.has_token_in(self.ns(), token_id).await
+    }
+
+    /// Cache a checkpoint state
+    pub async fn cache_checkpoint(
+        &self,
+        state: State,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache
+            .cache_checkpoint_in(self.ns(), state, verified, ttl)
+            .await
+    }
+
+    /// Get a cached checkpoint by its identifier
+    pub async fn get_checkpoint(&self, checkpoint_id: &str) -> Result<Option<State>, DsmError> {
+        self.cache.get_checkpoint_in(self.ns(), checkpoint_id).await
+    }
+
+    /// Check if a checkpoint is cached
+    pub async fn has_checkpoint(&self, checkpoint_id: &str) -> bool {
+        self.cache.has_checkpoint_in(self.ns(), checkpoint_id).await
+    }
+
+    /// Get closest checkpoint before a given state number
+    pub async fn get_closest_checkpoint(
+        &self,
+        state_number: u64,
+    ) -> Result<Option<State>, DsmError> {
+        self.cache
+            .get_closest_checkpoint_in(self.ns(), state_number)
+            .await
+    }
+
+    /// Cache an invalidation marker
+    pub async fn cache_invalidation(
+        &self,
+        marker: InvalidationMarker,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache
+            .cache_invalidation_in(self.ns(), marker, verified, ttl)
+            .await
+    }
+
+    /// Check if a state has been invalidated
+    pub async fn is_state_invalidated(&self, state_hash: &[u8]) -> Result<bool, DsmError> {
+        self.cache
+            .is_state_invalidated_in(self.ns(), state_hash)
+            .await
+    }
+
+    /// Get an invalidation marker
+    pub async fn get_invalidation(
+        &self,
+        state_hash: &[u8],
+    ) -> Result<Option<InvalidationMarker>, DsmError> {
+        self.cache.get_invalidation_in(self.ns(), state_hash).await
+    }
+
+    /// Cache a vault
+    pub async fn cache_vault(
+        &self,
+        vault: LimboVault,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache
+            .cache_vault_in(self.ns(), vault, verified, ttl)
+            .await
+    }
+
+    /// Get a cached vault
+    pub async fn get_vault(&self, vault_id: &str) -> Result<Option<LimboVault>, DsmError> {
+        self.cache.get_vault_in(self.ns(), vault_id).await
+    }
+
+    /// Check if a vault is cached
+    pub async fn has_vault(&self, vault_id: &str) -> bool {
+        self.cache.has_vault_in(self.ns(), vault_id).await
+    }
+
+    /// Check if a vault has been flagged because its reference state was invalidated
+    pub async fn is_vault_flagged(&self, vault_id: &str) -> bool {
+        self.cache
+            .is_vault_flagged(&StorageCache::namespaced_key(self.ns(), vault_id))
+            .await
+    }
+
+    /// Get the tombstone left by a pruned vault
+    pub async fn get_vault_tombstone(&self, vault_id: &str) -> Option<VaultTombstone> {
+        self.cache.get_vault_tombstone_in(self.ns(), vault_id).await
+    }
+
+    /// Cache a state from this namespace's history
+    pub async fn cache_state(
+        &self,
+        state: State,
+        verified: bool,
+        ttl: Option<u64>,
+    ) -> Result<(), DsmError> {
+        self.cache
+            .cache_state_in(self.ns(), state, verified, ttl)
+            .await
+    }
+
+    /// Get a cached state by its hash
+    pub async fn get_state(&self, state_hash: &[u8]) -> Result<Option<State>, DsmError> {
+        self.cache.get_state_in(self.ns(), state_hash).await
+    }
+
+    /// Remove a cached state
+    pub async fn remove_state(&self, state_hash: &[u8]) -> bool {
+        self.cache.remove_state_in(self.ns(), state_hash).await
+    }
+
+    /// Remove every state and checkpoint numbered below `state_number`
+    pub async fn purge_before_state(&self, state_number: u64) -> StatePurgeStats {
+        self.cache
+            .purge_before_state_in(self.ns(), state_number)
+            .await
+    }
+
+    /// Remove every entry stored under this namespace
+    pub async fn purge(&self) -> usize {
+        self.cache.purge_namespace(&self.namespace).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::genesis;
+    use crate::types::state_types::DeviceInfo;
+    use crate::types::token_types::Balance;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn token_of_size(owner: &str, payload: usize) -> Token {
+        Token::new(owner, vec![7u8; payload], Vec::new(), Balance::new(10))
+    }
+
+    #[tokio::test]
+    async fn test_entry_sizes_are_tracked() {
+        let cache = StorageCache::new();
+
+        cache
+            .cache_token("small", token_of_size("a", 16), true, None)
+            .await
+            .unwrap();
+        cache
+            .cache_token("large", token_of_size("b", 4096), true, None)
+            .await
+            .unwrap();
+
+        let stats = cache.get_byte_stats().await;
+        assert!(stats["token"] > 4096);
+        assert_eq!(stats["total"], stats["token"]);
+    }
+
+    #[tokio::test]
+    async fn test_category_budget_evicts_lru_within_category() {
+        let limits = CacheLimits::unlimited().with_category_budget(CacheCategory::Token, 3000);
+        let cache = StorageCache::with_limits(1000, 0, limits);
+
+        cache
+            .cache_token("t1", token_of_size("a", 1000), true, None)
+            .await
+            .unwrap();
+        cache
+            .cache_token("t2", token_of_size("b", 1000), true, None)
+            .await
+            .unwrap();
+
+        // Touch t1 so that t2 becomes least recently used
+        cache.get_token("t1").await.unwrap();
+
+        cache
+            .cache_token("t3", token_of_size("c", 1000), true, None)
+            .await
+            .unwrap();
+
+        assert!(cache.has_token("t1").await);
+        assert!(!cache.has_token("t2").await);
+        assert!(cache.has_token("t3").await);
+        assert!(cache.get_byte_stats().await["token"] <= 3000);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_entry_is_rejected() {
+        let limits = CacheLimits::unlimited().with_category_budget(CacheCategory::Token, 512);
+        let cache = StorageCache::with_limits(1000, 0, limits);
+
+        cache
+            .cache_token("keep", token_of_size("a", 16), true, None)
+            .await
+            .unwrap();
+
+        let result = cache
+            .cache_token("huge", token_of_size("b", 4096), true, None)
+            .await;
+
+        assert!(result.is_err());
+        assert!(cache.has_token("keep").await);
+        assert!(!cache.has_token("huge").await);
+    }
+
+    #[tokio::test]
+    async fn test_set_limits_enforces_immediately() {
+        let cache = StorageCache::with_settings(1000, 0);
+
+        for i in 0..5 {
+            cache
+                .cache_token(&format!("t{}", i), token_of_size("a", 1000), true, None)
+                .await
+                .unwrap();
+        }
+
+        let evicted = cache
+            .set_limits(CacheLimits::unlimited().with_total_bytes(2500))
+            .await;
+
+        assert!(evicted >= 3);
+        assert!(cache.get_byte_stats().await["total"] <= 2500);
+        assert!(cache.has_token("t4").await);
+    }
+
+    fn hashed_state(device: &str, state_number: u64) -> State {
+        let mut state = State::new_genesis(
+            vec![state_number as u8; 8],
+            DeviceInfo::new(device, vec![0; 32]),
+        );
+        state.state_number = state_number;
+        state.hash = state.hash().unwrap();
+        state
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_listener_fires_once_per_new_marker() {
+        let cache = StorageCache::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let counter = calls.clone();
+        cache.on_invalidation(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let state = hashed_state("dev", 1);
+        let marker = InvalidationMarker::create(&state, "forked", 1).unwrap();
+
+        cache
+            .cache_invalidation(marker.clone(), true, None)
+            .await
+            .unwrap();
+        cache.cache_invalidation(marker, true, None).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_listener_does_not_break_cache_write() {
+        let cache = StorageCache::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        cache.on_invalidation(Box::new(|_| panic!("listener failure")));
+        let counter = calls.clone();
+        cache.on_invalidation(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let state = hashed_state("dev", 1);
+        let marker = InvalidationMarker::create(&state, "forked", 1).unwrap();
+
+        assert!(cache.cache_invalidation(marker, true, None).await.is_ok());
+        assert!(cache.is_state_invalidated(&state.hash).await.unwrap());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_removed_listener_is_not_called() {
+        let cache = StorageCache::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let counter = calls.clone();
+        let id = cache.on_invalidation(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert!(cache.remove_invalidation_listener(id));
+        assert!(!cache.remove_invalidation_listener(id));
+        assert_eq!(cache.invalidation_listener_count(), 0);
+
+        let state = hashed_state("dev", 1);
+        let marker = InvalidationMarker::create(&state, "forked", 1).unwrap();
+        cache.cache_invalidation(marker, true, None).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drop_checkpoints_for_invalidated_state() {
+        let cache = StorageCache::new();
+
+        let invalid = hashed_state("dev", 5);
+        let later = hashed_state("dev", 7);
+        let earlier = hashed_state("dev", 3);
+        let other_device = hashed_state("other", 9);
+
+        for state in [&invalid, &later, &earlier, &other_device] {
+            cache
+                .cache_checkpoint(state.clone(), true, None)
+                .await
+                .unwrap();
+        }
+
+        let marker = InvalidationMarker::create(&invalid, "forked", 1).unwrap();
+        cache.cache_invalidation(marker, true, None).await.unwrap();
+
+        assert_eq!(cache.drop_checkpoints_for_state(&invalid.hash).await, 2);
+
+        let id = |state: &State| StorageCache::checkpoint_id(state).unwrap();
+        assert!(!cache.has_checkpoint(&id(&invalid)).await);
+        assert!(!cache.has_checkpoint(&id(&later)).await);
+        assert!(cache.has_checkpoint(&id(&earlier)).await);
+        assert!(cache.has_checkpoint(&id(&other_device)).await);
+    }
+
+    #[tokio::test]
+    async fn test_namespaces_are_isolated() {
+        let cache = StorageCache::new();
+        let alice = cache.scoped("alice");
+        let bob = cache.scoped("bob");
+
+        alice
+            .cache_token("shared-id", token_of_size("alice", 16), true, None)
+            .await
+            .unwrap();
+        bob.cache_token("shared-id", token_of_size("bob", 16), true, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            alice
+                .get_token("shared-id")
+                .await
+                .unwrap()
+                .unwrap()
+                .owner_id(),
+            "alice"
+        );
+        assert_eq!(
+            bob.get_token("shared-id")
+                .await
+                .unwrap()
+                .unwrap()
+                .owner_id(),
+            "bob"
+        );
+
+        // The default namespace is separate from every named one
+        assert!(!cache.has_token("shared-id").await);
+        cache
+            .cache_token("shared-id", token_of_size("default", 16), true, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            cache
+                .get_token("shared-id")
+                .await
+                .unwrap()
+                .unwrap()
+                .owner_id(),
+            "default"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_namespace_leaves_others_intact() {
+        let cache = StorageCache::new();
+        let alice = cache.scoped("alice");
+        let bob = cache.scoped("bob");
+
+        let checkpoint = hashed_state("dev", 1);
+        let checkpoint_id = StorageCache::checkpoint_id(&checkpoint).unwrap();
+
+        alice
+            .cache_token("t", token_of_size("alice", 16), true, None)
+            .await
+            .unwrap();
+        alice
+            .cache_checkpoint(checkpoint.clone(), true, None)
+            .await
+            .unwrap();
+        bob.cache_token("t", token_of_size("bob", 16), true, None)
+            .await
+            .unwrap();
+        bob.cache_checkpoint(checkpoint, true, None).await.unwrap();
+        cache
+            .cache_token("t", token_of_size("default", 16), true, None)
+            .await
+            .unwrap();
+
+        assert_eq!(cache.purge_namespace("alice").await, 2);
+
+        assert!(!alice.has_token("t").await);
+        assert!(!alice.has_checkpoint(&checkpoint_id).await);
+        assert!(bob.has_token("t").await);
+        assert!(bob.has_checkpoint(&checkpoint_id).await);
+        assert!(cache.has_token("t").await);
+    }
+
+    #[tokio::test]
+    async fn test_closest_checkpoint_respects_namespace() {
+        let cache = StorageCache::new();
+        let alice = cache.scoped("alice");
+
+        alice
+            .cache_checkpoint(hashed_state("dev", 4), true, None)
+            .await
+            .unwrap();
+        cache
+            .cache_checkpoint(hashed_state("dev", 2), true, None)
+            .await
+            .unwrap();
+
+        let scoped = alice.get_closest_checkpoint(10).await.unwrap().unwrap();
+        assert_eq!(scoped.state_number, 4);
+
+        let default = cache.get_closest_checkpoint(10).await.unwrap().unwrap();
+        assert_eq!(default.state_number, 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_served_stale() {
+        let cache = StorageCache::new();
+        cache
+            .cache_token("t", token_of_size("owner", 4), true, Some(1))
+            .await
+            .unwrap();
+
+        let lookup = cache
+            .get_token_with_freshness("t", Freshness::Fresh)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(lookup.is_fresh());
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+
+        // Plain lookups keep hard-TTL semantics, but the entry is retained
+        assert!(cache.get_token("t").await.unwrap().is_none());
+
+        let lookup = cache
+            .get_token_with_freshness("t", Freshness::Stale)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup.freshness, Freshness::Stale);
+        assert_eq!(lookup.value.owner_id(), "owner");
+
+        // Strict callers get an error instead of stale data
+        assert!(matches!(
+            cache.get_token_with_freshness("t", Freshness::Fresh).await,
+            Err(DsmError::Storage { .. })
+        ));
+
+        // Clearing expired entries removes the stale copy as well
+        assert_eq!(cache.clear_expired().await, 1);
+        assert!(cache
+            .get_token_with_freshness("t", Freshness::Stale)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    fn vault_in_state(id: &str, state: VaultState) -> LimboVault {
+        LimboVault {
+            id: id.to_string(),
+            state,
+            ..LimboVault::default()
+        }
+    }
+
+    fn invalidated(state_number: u64) -> VaultState {
+        VaultState::Invalidated {
+            invalidated_state_number: state_number,
+            reason: "revoked".to_string(),
+            creator_signature: Vec::new(),
+        }
+    }
+
+    fn claimed(state_number: u64) -> VaultState {
+        VaultState::Claimed {
+            claimed_state_number: state_number,
+            claimant: vec![1],
+            claim_proof: vec![2],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pruned_invalidated_vault_leaves_tombstone() {
+        let cache = StorageCache::new();
+        cache
+            .cache_vault(vault_in_state("gone", invalidated(7)), true, None)
+            .await
+            .unwrap();
+        cache
+            .cache_vault(vault_in_state("live", VaultState::Limbo), true, None)
+            .await
+            .unwrap();
+        let vault_bytes = cache.get_byte_stats().await["vault"];
+
+        let policy = VaultPrunePolicy::keep_all().remove_invalidated_immediately();
+        let stats = cache.prune_vaults(&policy).await;
+        assert_eq!(stats.invalidated_removed, 1);
+        assert_eq!(stats.claimed_removed, 0);
+        assert!(stats.reclaimed_bytes > 0);
+        assert_eq!(
+            cache.get_byte_stats().await["vault"],
+            vault_bytes - stats.reclaimed_bytes
+        );
+
+        // The outcome is still known offline, without the vault itself
+        assert!(!cache.has_vault("gone").await);
+        assert!(cache.get_vault("gone").await.unwrap().is_none());
+        let tombstone = cache.get_vault_tombstone("gone").await.unwrap();
+        assert_eq!(tombstone.outcome, VaultOutcome::Invalidated);
+        assert_eq!(tombstone.state_number, 7);
+
+        assert!(cache.get_vault("live").await.unwrap().is_some());
+        assert_eq!(cache.vault_prune_stats().await, stats);
+    }
+
+    #[tokio::test]
+    async fn test_tombstones_expire_past_max_age() {
+        let cache = StorageCache::new();
+        cache
+            .cache_vault(vault_in_state("gone", invalidated(7)), true, None)
+            .await
+            .unwrap();
+
+        let policy = VaultPrunePolicy::keep_all().remove_invalidated_immediately();
+        cache.prune_vaults(&policy).await;
+        cache.prune_vaults(&policy).await;
+        assert!(cache.get_vault_tombstone("gone").await.is_some());
+
+        let expiring = policy.remove_tombstones_older_than(Duration::ZERO);
+        cache.prune_vaults(&expiring).await;
+        assert!(cache.get_vault_tombstone("gone").await.is_none());
+        assert!(cache.get_vault("gone").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_claimed_vaults_pruned_only_past_max_age() {
+        let cache = StorageCache::new();
+        cache
+            .cache_vault(vault_in_state("claimed", claimed(3)), true, None)
+            .await
+            .unwrap();
+
+        let recent =
+            VaultPrunePolicy::keep_all().remove_claimed_older_than(Duration::from_secs(3600));
+        assert_eq!(cache.prune_vaults(&recent).await.removed(), 0);
+        assert!(cache.has_vault("claimed").await);
+
+        let any_age = VaultPrunePolicy::keep_all().remove_claimed_older_than(Duration::ZERO);
+        assert_eq!(cache.prune_vaults(&any_age).await.claimed_removed, 1);
+        assert_eq!(
+            cache.get_vault_tombstone("claimed").await.unwrap().outcome,
+            VaultOutcome::Claimed
+        );
+
+        // Caching the vault again replaces its tombstone
+        cache
+            .cache_vault(vault_in_state("claimed", claimed(3)), true, None)
+            .await
+            .unwrap();
+        assert!(cache.get_vault_tombstone("claimed").await.is_none());
+        assert!(cache.get_vault("claimed").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_purge_before_state_keeps_later_history() {
+        let cache = StorageCache::new();
+        let states: Vec<State> = (0..10).map(|n| hashed_state("device", n)).collect();
+        for state in &states {
+            cache.cache_state(state.clone(), true, None).await.unwrap();
+        }
+        cache
+            .cache_checkpoint(states[2].clone(), true, None)
+            .await
+            .unwrap();
+        cache
+            .cache_checkpoint(states[6].clone(), true, None)
+            .await
+            .unwrap();
+
+        // Another identity's history in its own namespace is left alone
+        let other = cache.scoped("other");
+        other
+            .cache_state(states[1].clone(), true, None)
+            .await
+            .unwrap();
+
+        let stats = cache.purge_before_state(6).await;
+        assert_eq!(stats.states_removed, 6);
+        assert_eq!(stats.checkpoints_removed, 1);
+        assert!(stats.bytes_freed > 0);
+
+        for state in &states {
+            let cached = cache.get_state(&state.hash).await.unwrap();
+            assert_eq!(
+                cached.is_some(),
+                state.state_number >= 6,
+                "state {}",
+                state.state_number
+            );
+        }
+        let checkpoint_id = StorageCache::checkpoint_id(&states[6]).unwrap();
+        assert!(cache.has_checkpoint(&checkpoint_id).await);
+        assert!(other.get_state(&states[1].hash).await.unwrap().is_some());
+    }
+
+    /// Peer serving fixed data and counting genesis fetches
+    #[derive(Default)]
+    struct MockPeer {
+        genesis: HashMap<Vec<u8>, GenesisState>,
+        checkpoints: HashMap<Vec<u8>, State>,
+        markers: Vec<InvalidationMarker>,
+        unreachable: HashSet<Vec<u8>>,
+        genesis_fetches: AtomicUsize,
+    }
+
+    impl MockPeer {
+        fn with_genesis(mut self, hash: &[u8]) -> Self {
+            self.genesis.insert(hash.to_vec(), genesis(hash.to_vec()));
+            self
+        }
+    }
+
+    #[async_trait]
+    impl CachePeer for MockPeer {
+        async fn fetch_genesis(
+            &self,
+            genesis_hash: &[u8],
+        ) -> Result<Option<GenesisState>, DsmError> {
+            self.genesis_fetches.fetch_add(1, Ordering::SeqCst);
+            if self.unreachable.contains(genesis_hash) {
+                return Err(DsmError::network(
+                    "Peer unreachable",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            Ok(self.genesis.get(genesis_hash).cloned())
+        }
+
+        async fn fetch_latest_checkpoint(
+            &self,
+            genesis_hash: &[u8],
+        ) -> Result<Option<State>, DsmError> {
+            Ok(self.checkpoints.get(genesis_hash).cloned())
+        }
+
+        async fn fetch_invalidation_markers(
+            &self,
+            state_hashes: &[Vec<u8>],
+        ) -> Result<Vec<InvalidationMarker>, DsmError> {
+            Ok(self
+                .markers
+                .iter()
+                .filter(|marker| state_hashes.contains(&marker.state_hash))
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_populates_every_bucket() {
+        let cache = StorageCache::new();
+        let (first, second) = (vec![1u8; 32], vec![2u8; 32]);
+
+        let checkpoint = hashed_state("alice", 5);
+        let mut peer = MockPeer::default()
+            .with_genesis(&first)
+            .with_genesis(&second);
+        peer.checkpoints.insert(first.clone(), checkpoint.clone());
+        peer.markers
+            .push(InvalidationMarker::create(&checkpoint, "forked", 1).unwrap());
+
+        let report = cache
+            .prewarm_from_peer(&peer, &[first.clone(), second.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(report.fetched, 2);
+        assert_eq!(report.skipped, 0);
+        assert!(report.errors.is_empty());
+        assert!(cache.has_genesis(&first).await);
+        assert!(cache.has_genesis(&second).await);
+        let checkpoint_id = StorageCache::checkpoint_id(&checkpoint).unwrap();
+        assert!(cache.has_checkpoint(&checkpoint_id).await);
+        assert!(cache.is_state_invalidated(&checkpoint.hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_skips_cached_genesis() {
+        let cache = StorageCache::new();
+        let (cached, cold) = (vec![1u8; 32], vec![2u8; 32]);
+        cache
+            .cache_genesis(genesis(cached.clone()), true, None)
+            .await
+            .unwrap();
+
+        let peer = MockPeer::default()
+            .with_genesis(&cached)
+            .with_genesis(&cold);
+        let hashes = [cached, cold];
+
+        let report = cache.prewarm_from_peer(&peer, &hashes).await.unwrap();
+        assert_eq!((report.fetched, report.skipped), (1, 1));
+        assert_eq!(peer.genesis_fetches.load(Ordering::SeqCst), 1);
+
+        // A second prewarm finds everything cached and asks the peer for nothing
+        let report = cache.prewarm_from_peer(&peer, &hashes).await.unwrap();
+        assert_eq!((report.fetched, report.skipped), (0, 2));
+        assert_eq!(peer.genesis_fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_reports_partial_failures() {
+        let cache = StorageCache::new();
+        cache.set_max_concurrency(2);
+        let (good, unreachable, missing) = (vec![1u8; 32], vec![2u8; 32], vec![3u8; 32]);
+
+        let mut peer = MockPeer::default()
+            .with_genesis(&good)
+            .with_genesis(&unreachable);
+        peer.unreachable.insert(unreachable.clone());
+
+        let report = cache
+            .prewarm_from_peer(&peer, &[good.clone(), unreachable.clone(), missing.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(report.fetched, 1);
+        assert_eq!(report.skipped, 0);
+        let mut failed: Vec<Vec<u8>> = report.errors.iter().map(|(hash, _)| hash.clone()).collect();
+        failed.sort();
+        assert_eq!(failed, vec![unreachable.clone(), missing.clone()]);
+        assert!(cache.has_genesis(&good).await);
+        assert!(!cache.has_genesis(&unreachable).await);
+        assert!(!cache.has_genesis(&missing).await);
+    }
+}
@@ -0,0 +1,320 @@
+//! # Post-Quantum TLS
+//!
+//! Helpers for TLS 1.3 connections whose key exchange resists quantum
+//! attacks, for storage node servers and their clients. The key exchange is
+//! a hybrid of a classical elliptic curve exchange and ML-KEM-768, the
+//! standardized form of Kyber768, so a connection stays confidential as long
+//! as either half holds. Only the hybrid groups are offered and accepted;
+//! peers without them fail the handshake instead of falling back to a
+//! classical exchange.
+//!
+//! Certificates and keys are read from PEM. A server given a CA bundle
+//! requires clients to authenticate with a certificate issued by it.
+
+use crate::types::error::DsmError;
+use rustls_pq::client::WantsClientCert;
+use rustls_pq::crypto::{aws_lc_rs, CryptoProvider, SupportedKxGroup};
+use rustls_pq::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls_pq::server::WebPkiClientVerifier;
+use rustls_pq::{ClientConfig, CommonState, ConfigBuilder, RootCertStore, ServerConfig};
+use std::sync::Arc;
+use tokio_rustls_pq::{TlsAcceptor, TlsConnector};
+
+/// Hybrid key encapsulation mechanisms for the TLS key exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KemAlgorithm {
+    /// X25519 combined with ML-KEM-768 (Kyber768)
+    X25519Kyber768,
+    /// NIST P-256 combined with ML-KEM-768 (Kyber768)
+    P256Kyber768,
+}
+
+impl KemAlgorithm {
+    /// All supported algorithms, in order of preference
+    pub const ALL: [KemAlgorithm; 2] = [KemAlgorithm::X25519Kyber768, KemAlgorithm::P256Kyber768];
+
+    /// The rustls key exchange group implementing this algorithm
+    fn kx_group(self) -> &'static dyn SupportedKxGroup {
+        match self {
+            KemAlgorithm::X25519Kyber768 => aws_lc_rs::kx_group::X25519MLKEM768,
+            KemAlgorithm::P256Kyber768 => aws_lc_rs::kx_group::SECP256R1MLKEM768,
+        }
+    }
+}
+
+/// Key exchange algorithm negotiated on a TLS connection
+///
+/// `None` before the handshake has completed.
+pub fn negotiated_kem(connection: &CommonState) -> Option<KemAlgorithm> {
+    let group = connection.negotiated_key_exchange_group()?.name();
+    KemAlgorithm::ALL
+        .into_iter()
+        .find(|algorithm| algorithm.kx_group().name() == group)
+}
+
+/// Build a TLS acceptor that only negotiates the given hybrid KEMs
+///
+/// # Arguments
+///
+/// * `cert_pem` - PEM certificate chain of the server, leaf first
+/// * `key_pem` - PEM private key of the server
+/// * `pq_kem_algorithms` - Key exchange algorithms to accept, in order of preference
+/// * `client_ca_pem` - PEM CA bundle; if given, clients must present a certificate it issued
+pub fn build_pq_tls_acceptor(
+    cert_pem: &[u8],
+    key_pem: &[u8],
+    pq_kem_algorithms: &[KemAlgorithm],
+    client_ca_pem: Option<&[u8]>,
+) -> Result<TlsAcceptor, DsmError> {
+    if pq_kem_algorithms.is_empty() {
+        return Err(DsmError::invalid_parameter(
+            "At least one post-quantum key exchange algorithm is required",
+        ));
+    }
+
+    let provider = Arc::new(pq_provider(pq_kem_algorithms));
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&[&rustls_pq::version::TLS13])
+        .map_err(|e| DsmError::crypto("Failed to configure TLS versions", Some(e)))?;
+
+    let builder = match client_ca_pem {
+        Some(ca_pem) => {
+            let roots = Arc::new(root_store(ca_pem)?);
+            let verifier = WebPkiClientVerifier::builder_with_provider(roots, provider)
+                .build()
+                .map_err(|e| {
+                    DsmError::crypto("Failed to create client certificate verifier", Some(e))
+                })?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let server_config = builder
+        .with_single_cert(parse_certs(cert_pem)?, parse_key(key_pem)?)
+        .map_err(|e| DsmError::crypto("Failed to create TLS server config", Some(e)))?;
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// Build a TLS connector that offers every supported hybrid KEM
+///
+/// # Arguments
+///
+/// * `ca_pem` - PEM CA bundle trusted to issue server certificates
+pub fn build_pq_tls_connector(ca_pem: &[u8]) -> Result<TlsConnector, DsmError> {
+    let client_config = client_config_builder(ca_pem)?.with_no_client_auth();
+
+    Ok(TlsConnector::from(Arc::new(client_config)))
+}
+
+/// Build a TLS connector that also authenticates the client with a certificate
+///
+/// For servers whose acceptor was given a client CA bundle.
+///
+/// # Arguments
+///
+/// * `ca_pem` - PEM CA bundle trusted to issue server certificates
+/// * `cert_pem` - PEM certificate chain of the client, leaf first
+/// * `key_pem` - PEM private key of the client
+pub fn build_pq_tls_client_auth_connector(
+    ca_pem: &[u8],
+    cert_pem: &[u8],
+    key_pem: &[u8],
+) -> Result<TlsConnector, DsmError> {
+    let client_config = client_config_builder(ca_pem)?
+        .with_client_auth_cert(parse_certs(cert_pem)?, parse_key(key_pem)?)
+        .map_err(|e| DsmError::crypto("Failed to set TLS client certificate", Some(e)))?;
+
+    Ok(TlsConnector::from(Arc::new(client_config)))
+}
+
+fn client_config_builder(
+    ca_pem: &[u8],
+) -> Result<ConfigBuilder<ClientConfig, WantsClientCert>, DsmError> {
+    let builder = ClientConfig::builder_with_provider(Arc::new(pq_provider(&KemAlgorithm::ALL)))
+        .with_protocol_versions(&[&rustls_pq::version::TLS13])
+        .map_err(|e| DsmError::crypto("Failed to configure TLS versions", Some(e)))?;
+
+    Ok(builder.with_root_certificates(root_store(ca_pem)?))
+}
+
+/// The default provider, restricted to the given key exchange groups
+fn pq_provider(algorithms: &[KemAlgorithm]) -> CryptoProvider {
+    CryptoProvider {
+        kx_groups: algorithms
+            .iter()
+            .map(|algorithm| algorithm.kx_group())
+            .collect(),
+        ..aws_lc_rs::default_provider()
+    }
+}
+
+fn root_store(ca_pem: &[u8]) -> Result<RootCertStore, DsmError> {
+    let mut roots = RootCertStore::empty();
+    for cert in parse_certs(ca_pem)? {
+        roots
+            .add(cert)
+            .map_err(|e| DsmError::crypto("Failed to add CA certificate", Some(e)))?;
+    }
+
+    Ok(roots)
+}
+
+fn parse_certs(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, DsmError> {
+    let certs = rustls_pemfile::certs(&mut &pem[..])
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DsmError::crypto("Failed to parse PEM certificates", Some(e)))?;
+    if certs.is_empty() {
+        return Err(DsmError::invalid_parameter(
+            "No certificate found in PEM data",
+        ));
+    }
+
+    Ok(certs)
+}
+
+fn parse_key(pem: &[u8]) -> Result<PrivateKeyDer<'static>, DsmError> {
+    rustls_pemfile::private_key(&mut &pem[..])
+        .map_err(|e| DsmError::crypto("Failed to parse PEM private key", Some(e)))?
+        .ok_or_else(|| DsmError::invalid_parameter("No private key found in PEM data"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+    use rustls_pq::pki_types::ServerName;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// A CA and a certificate it issued for `localhost`, all as PEM
+    struct TestPki {
+        ca_pem: String,
+        cert_pem: String,
+        key_pem: String,
+    }
+
+    fn test_pki() -> TestPki {
+        let mut ca_params = CertificateParams::new(Vec::new());
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "DSM Test CA");
+        let ca = Certificate::from_params(ca_params).unwrap();
+
+        let mut leaf_params = CertificateParams::new(vec!["localhost".to_string()]);
+        leaf_params
+            .distinguished_name
+            .push(DnType::CommonName, "localhost");
+        let leaf = Certificate::from_params(leaf_params).unwrap();
+
+        TestPki {
+            ca_pem: ca.serialize_pem().unwrap(),
+            cert_pem: leaf.serialize_pem_with_signer(&ca).unwrap(),
+            key_pem: leaf.serialize_private_key_pem(),
+        }
+    }
+
+    /// Run one handshake and echo, returning the server's negotiated KEM
+    async fn handshake(
+        acceptor: TlsAcceptor,
+        connector: TlsConnector,
+    ) -> Result<Option<KemAlgorithm>, DsmError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut tls = acceptor
+                .accept(tcp)
+                .await
+                .map_err(|e| DsmError::network(format!("TLS accept failed: {}", e), Some(e)))?;
+            let mut buf = [0u8; 4];
+            tls.read_exact(&mut buf).await.unwrap();
+            tls.write_all(&buf).await.unwrap();
+            Ok::<_, DsmError>(negotiated_kem(tls.get_ref().1))
+        });
+
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let client = async {
+            let mut tls = connector
+                .connect(ServerName::try_from("localhost").unwrap(), tcp)
+                .await?;
+            tls.write_all(b"ping").await?;
+            let mut buf = [0u8; 4];
+            tls.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"ping");
+            Ok::<_, std::io::Error>(negotiated_kem(tls.get_ref().1))
+        };
+        let client_kem = client.await;
+
+        let server_kem = server.await.unwrap()?;
+        assert_eq!(client_kem.ok(), Some(server_kem));
+        Ok(server_kem)
+    }
+
+    #[tokio::test]
+    async fn test_pq_connection_negotiates_hybrid_kem() {
+        let pki = test_pki();
+        let connector = build_pq_tls_connector(pki.ca_pem.as_bytes()).unwrap();
+
+        let acceptor = build_pq_tls_acceptor(
+            pki.cert_pem.as_bytes(),
+            pki.key_pem.as_bytes(),
+            &KemAlgorithm::ALL,
+            None,
+        )
+        .unwrap();
+        let kem = handshake(acceptor, connector.clone()).await.unwrap();
+        assert_eq!(kem, Some(KemAlgorithm::X25519Kyber768));
+
+        // The server's list decides which of the offered KEMs is used
+        let acceptor = build_pq_tls_acceptor(
+            pki.cert_pem.as_bytes(),
+            pki.key_pem.as_bytes(),
+            &[KemAlgorithm::P256Kyber768],
+            None,
+        )
+        .unwrap();
+        let kem = handshake(acceptor, connector).await.unwrap();
+        assert_eq!(kem, Some(KemAlgorithm::P256Kyber768));
+    }
+
+    #[tokio::test]
+    async fn test_client_ca_requires_client_certificate() {
+        let pki = test_pki();
+        let client = test_pki();
+        let acceptor = || {
+            build_pq_tls_acceptor(
+                pki.cert_pem.as_bytes(),
+                pki.key_pem.as_bytes(),
+                &[KemAlgorithm::X25519Kyber768],
+                Some(client.ca_pem.as_bytes()),
+            )
+            .unwrap()
+        };
+
+        let anonymous = build_pq_tls_connector(pki.ca_pem.as_bytes()).unwrap();
+        assert!(handshake(acceptor(), anonymous).await.is_err());
+
+        let authenticated = build_pq_tls_client_auth_connector(
+            pki.ca_pem.as_bytes(),
+            client.cert_pem.as_bytes(),
+            client.key_pem.as_bytes(),
+        )
+        .unwrap();
+        let kem = handshake(acceptor(), authenticated).await.unwrap();
+        assert_eq!(kem, Some(KemAlgorithm::X25519Kyber768));
+    }
+
+    #[test]
+    fn test_acceptor_requires_a_kem() {
+        let pki = test_pki();
+        assert!(
+            build_pq_tls_acceptor(pki.cert_pem.as_bytes(), pki.key_pem.as_bytes(), &[], None)
+                .is_err()
+        );
+        assert!(build_pq_tls_connector(b"not a certificate").is_err());
+    }
+}
@@ -82,8 +82,10 @@ pub trait TransportListener: Send + Sync {
     fn transport_type(&self) -> TransportType;
 }
 
-// Export optimized transport implementations
+// Export optimized transport implementations; both need sockets
+#[cfg(not(target_arch = "wasm32"))]
 pub mod secure_udp;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod tls;
 
 // Optional transport modules
@@ -91,7 +93,9 @@ pub trait TransportListener: Send + Sync {
 pub mod bluetooth_connect;
 
 // Re-export transport implementations
+#[cfg(not(target_arch = "wasm32"))]
 pub use secure_udp::SecureUdpTransport;
+#[cfg(not(target_arch = "wasm32"))]
 pub use tls::TlsTransport;
 
 #[cfg(feature = "bluetooth")]
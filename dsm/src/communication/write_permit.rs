@@ -0,0 +1,316 @@
+//! Write permits for storage node access control
+//!
+//! A storage node configured with an ACL key only accepts inbox and vault
+//! writes that carry a permit it issued. A permit is a short-lived token in a
+//! JWT-like `payload.signature` form: the payload names the identity (genesis
+//! hash) allowed to write and when the permit expires, and the signature is
+//! the node's SPHINCS+ signature over it.
+//!
+//! To get a permit, the identity owner first fetches a single-use challenge
+//! from the node, then signs the genesis hash together with the challenge's
+//! nonce. A captured request therefore cannot be replayed for another permit.
+
+use crate::core::identity::GenesisState;
+use crate::crypto::sphincs;
+use crate::types::error::DsmError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// HTTP header carrying an encoded write permit
+pub const WRITE_PERMIT_HEADER: &str = "x-dsm-write-permit";
+
+/// Storage node path that issues write permits
+pub const WRITE_PERMIT_PATH: &str = "acl/permit";
+
+/// Storage node path that issues write permit challenges
+pub const WRITE_PERMIT_CHALLENGE_PATH: &str = "acl/challenge";
+
+/// How long a write permit challenge can be answered (seconds)
+pub const WRITE_PERMIT_CHALLENGE_LIFETIME: u64 = 60;
+
+/// Renew a permit once less than this percentage of its lifetime remains
+pub const WRITE_PERMIT_RENEWAL_PERCENT: u64 = 10;
+
+/// Permit from a storage node allowing one identity to write to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WritePermitToken {
+    /// Hex genesis hash of the identity allowed to write
+    pub genesis_hash: String,
+
+    /// When the permit was issued (seconds since epoch)
+    pub issued_at: u64,
+
+    /// When the permit stops being accepted (seconds since epoch)
+    pub expires_at: u64,
+
+    /// Node's ACL key signature over `signing_message`
+    #[serde(skip)]
+    pub signature: Vec<u8>,
+}
+
+impl WritePermitToken {
+    /// Issue a permit signed with the node's ACL secret key
+    pub fn issue(
+        genesis_hash: &str,
+        issued_at: u64,
+        lifetime_secs: u64,
+        acl_secret_key: &[u8],
+    ) -> Result<Self, DsmError> {
+        let mut permit = Self {
+            genesis_hash: genesis_hash.to_string(),
+            issued_at,
+            expires_at: issued_at.saturating_add(lifetime_secs),
+            signature: Vec::new(),
+        };
+        permit.signature = sphincs::sphincs_sign(acl_secret_key, &permit.signing_message())?;
+        Ok(permit)
+    }
+
+    /// Message the node signs to issue this permit
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"DSM_WRITE_PERMIT");
+        hasher.update(&(self.genesis_hash.len() as u64).to_le_bytes());
+        hasher.update(self.genesis_hash.as_bytes());
+        hasher.update(&self.issued_at.to_le_bytes());
+        hasher.update(&self.expires_at.to_le_bytes());
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    /// Whether the permit was signed with the given ACL public key
+    ///
+    /// Malformed signatures fail verification like wrong ones.
+    pub fn verify_signature(&self, acl_public_key: &[u8]) -> bool {
+        sphincs::sphincs_verify(acl_public_key, &self.signing_message(), &self.signature)
+            .unwrap_or(false)
+    }
+
+    /// Whether the permit has expired at the given time
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Whether the permit is within the renewal window at the given time
+    pub fn needs_renewal_at(&self, now: u64) -> bool {
+        let lifetime = self.expires_at.saturating_sub(self.issued_at);
+        let margin = lifetime * WRITE_PERMIT_RENEWAL_PERCENT / 100;
+        now.saturating_add(margin) >= self.expires_at
+    }
+
+    /// Encode the permit as `base64url(payload).base64url(signature)`
+    pub fn encode(&self) -> Result<String, DsmError> {
+        let payload = serde_json::to_vec(self)
+            .map_err(|e| DsmError::serialization("Failed to encode write permit", Some(e)))?;
+        Ok(format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(&self.signature)
+        ))
+    }
+
+    /// Decode a permit produced by `encode`
+    pub fn decode(token: &str) -> Result<Self, DsmError> {
+        let (payload, signature) = token.split_once('.').ok_or_else(|| {
+            DsmError::validation(
+                "Write permit is not of the form payload.signature",
+                None::<DsmError>,
+            )
+        })?;
+
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|e| {
+            DsmError::serialization("Invalid write permit payload encoding", Some(e))
+        })?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|e| {
+            DsmError::serialization("Invalid write permit signature encoding", Some(e))
+        })?;
+
+        let mut permit: Self = serde_json::from_slice(&payload)
+            .map_err(|e| DsmError::serialization("Invalid write permit payload", Some(e)))?;
+        permit.signature = signature;
+        Ok(permit)
+    }
+}
+
+/// Single-use nonce a storage node hands out for a write permit request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WritePermitChallenge {
+    /// Hex-encoded random nonce
+    pub nonce: String,
+
+    /// When the node stops accepting the nonce (seconds since epoch)
+    pub expires_at: u64,
+}
+
+/// Request for a write permit, signed by the identity owner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WritePermitRequest {
+    /// Hex genesis hash of the identity asking to write
+    pub genesis_hash: String,
+
+    /// Nonce of the node's `WritePermitChallenge` this request answers
+    pub nonce: String,
+
+    /// SPHINCS+ public key of the identity owner
+    pub owner_public_key: Vec<u8>,
+
+    /// Owner's signature over `signing_message`
+    pub owner_signature: Vec<u8>,
+}
+
+impl WritePermitRequest {
+    /// Message the identity owner signs to ask for a write permit
+    pub fn signing_message(genesis_hash: &str, nonce: &str) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"DSM_WRITE_PERMIT_REQUEST");
+        for part in [genesis_hash, nonce] {
+            hasher.update(&(part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    /// Whether the owner's signature over this request is valid
+    pub fn verify(&self) -> bool {
+        sphincs::sphincs_verify(
+            &self.owner_public_key,
+            &Self::signing_message(&self.genesis_hash, &self.nonce),
+            &self.owner_signature,
+        )
+        .unwrap_or(false)
+    }
+}
+
+/// Storage node response to a write permit request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritePermitResponse {
+    /// Encoded permit, as sent in the `WRITE_PERMIT_HEADER` header
+    pub permit: String,
+}
+
+/// Ask a storage node for permission to write on behalf of an identity
+///
+/// Fetches a challenge from the node and answers it with a request signed by
+/// `owner_secret_key`, the secret half of the genesis state's signing key.
+#[cfg(feature = "reqwest")]
+pub async fn request_write_permission(
+    genesis: &GenesisState,
+    node_url: &str,
+    owner_secret_key: &[u8],
+) -> Result<WritePermitToken, DsmError> {
+    let client = reqwest::Client::new();
+    let node_url = node_url.trim_end_matches('/');
+
+    let url = format!("{}/{}", node_url, WRITE_PERMIT_CHALLENGE_PATH);
+    let response = client.get(&url).send().await.map_err(|e| {
+        DsmError::network(
+            format!("Failed to request a challenge from {}", url),
+            Some(e),
+        )
+    })?;
+    if !response.status().is_success() {
+        return Err(DsmError::network(
+            format!(
+                "Write permit challenge from {} failed: {}",
+                url,
+                response.status()
+            ),
+            None::<DsmError>,
+        ));
+    }
+    let challenge: WritePermitChallenge = response
+        .json()
+        .await
+        .map_err(|e| DsmError::serialization("Failed to parse write permit challenge", Some(e)))?;
+
+    let genesis_hash = hex::encode(&genesis.hash);
+    let owner_signature = sphincs::sphincs_sign(
+        owner_secret_key,
+        &WritePermitRequest::signing_message(&genesis_hash, &challenge.nonce),
+    )?;
+    let request = WritePermitRequest {
+        genesis_hash,
+        nonce: challenge.nonce,
+        owner_public_key: genesis.signing_key.public_key.clone(),
+        owner_signature,
+    };
+    let url = format!("{}/{}", node_url, WRITE_PERMIT_PATH);
+
+    let response = client.post(&url).json(&request).send().await.map_err(|e| {
+        DsmError::network(
+            format!("Failed to request write permit from {}", url),
+            Some(e),
+        )
+    })?;
+
+    if !response.status().is_success() {
+        return Err(DsmError::network(
+            format!(
+                "Write permit request to {} failed: {}",
+                url,
+                response.status()
+            ),
+            None::<DsmError>,
+        ));
+    }
+
+    let response: WritePermitResponse = response
+        .json()
+        .await
+        .map_err(|e| DsmError::serialization("Failed to parse write permit response", Some(e)))?;
+    let permit = WritePermitToken::decode(&response.permit)?;
+
+    if permit.genesis_hash != request.genesis_hash {
+        return Err(DsmError::validation(
+            "Storage node issued a write permit for another identity",
+            None::<DsmError>,
+        ));
+    }
+
+    Ok(permit)
+}
+
+/// Ask a storage node for permission to write on behalf of an identity
+#[cfg(not(feature = "reqwest"))]
+pub async fn request_write_permission(
+    _genesis: &GenesisState,
+    _node_url: &str,
+    _owner_secret_key: &[u8],
+) -> Result<WritePermitToken, DsmError> {
+    Err(DsmError::feature_not_available(
+        "Requesting write permits requires the 'reqwest' feature",
+        None::<String>,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permit_round_trips_through_encoding() {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let permit = WritePermitToken::issue("abcd", 1_000, 600, &secret_key).unwrap();
+
+        let decoded = WritePermitToken::decode(&permit.encode().unwrap()).unwrap();
+
+        assert_eq!(decoded, permit);
+        assert!(decoded.verify_signature(&public_key));
+        assert!(WritePermitToken::decode("not-a-permit").is_err());
+    }
+
+    #[test]
+    fn test_permit_renewal_window() {
+        let permit = WritePermitToken {
+            genesis_hash: "abcd".to_string(),
+            issued_at: 1_000,
+            expires_at: 2_000,
+            signature: Vec::new(),
+        };
+
+        assert!(!permit.needs_renewal_at(1_899));
+        assert!(permit.needs_renewal_at(1_900));
+        assert!(!permit.is_expired_at(1_999));
+        assert!(permit.is_expired_at(2_000));
+    }
+}
@@ -1,16 +1,18 @@
 use crate::core::identity::Identity;
 use crate::types::error::DsmError;
+use crate::types::token_types::Balance;
+use crate::types::versioned::{LegacyDecoder, LegacySchema};
 use pqcrypto_traits::kem::Ciphertext;
 use pqcrypto_traits::kem::SharedSecret;
 
-use pqcrypto_mlkem as kyber;
+use crate::crypto::mlkem as kyber;
 use pqcrypto_traits::kem::{PublicKey as KemPublicKey, SecretKey as KemSecretKey};
 // Use our own SPHINCS+ implementation
 use crate::crypto::sphincs;
 use rand::{thread_rng, RngCore};
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Sha3_512};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 fn generate_secure_random(rng: &mut impl RngCore, len: usize) -> Result<Vec<u8>, DsmError> {
     let mut bytes = vec![0u8; len];
@@ -73,6 +75,81 @@ pub struct Contribution {
     pub verified: bool,
 }
 
+/// Supply rules of a token, fixed when the identity's genesis is created
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisTokenPolicy {
+    /// Maximum total supply, or `None` for an uncapped token
+    pub max_supply: Option<Balance>,
+
+    /// Identities allowed to authorize mints; empty allows none
+    pub mintable_by: Vec<String>,
+
+    /// Whether the token may be burned
+    pub burnable: bool,
+}
+
+impl GenesisTokenPolicy {
+    /// Check a mint of `amount` authorized by `authorized_by`
+    ///
+    /// # Arguments
+    /// * `token_id` - Token being minted
+    /// * `authorized_by` - Identity that authorized the mint
+    /// * `current_supply` - Total supply of the token before the mint
+    /// * `amount` - Amount to mint
+    ///
+    /// # Returns
+    /// * `Err(DsmError::Unauthorized)` - If the identity may not mint the token
+    /// * `Err(DsmError::SupplyCapExceeded)` - If the mint would exceed the maximum supply
+    pub fn check_mint(
+        &self,
+        token_id: &str,
+        authorized_by: &str,
+        current_supply: &Balance,
+        amount: &Balance,
+    ) -> Result<(), DsmError> {
+        if !self
+            .mintable_by
+            .iter()
+            .any(|minter| minter == authorized_by)
+        {
+            return Err(DsmError::unauthorized(
+                format!("{} may not mint {}", authorized_by, token_id),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if let Some(max_supply) = &self.max_supply {
+            let exceeds = current_supply
+                .value()
+                .checked_add(amount.value())
+                .is_none_or(|supply| supply > max_supply.value());
+            if exceeds {
+                return Err(DsmError::SupplyCapExceeded {
+                    token_id: token_id.to_string(),
+                    current_supply: current_supply.clone(),
+                    requested_amount: amount.clone(),
+                    max_supply: max_supply.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check a burn of the token
+    pub fn check_burn(&self, token_id: &str) -> Result<(), DsmError> {
+        if !self.burnable {
+            return Err(DsmError::PolicyViolation {
+                token_id: token_id.to_string(),
+                message: "Token is not burnable".to_string(),
+                source: None,
+            });
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenesisState {
     pub hash: Vec<u8>,
@@ -84,6 +161,28 @@ pub struct GenesisState {
     pub signing_key: SigningKey,      // Quantum-resistant signing
     pub kyber_keypair: KyberKey,      // Quantum-resistant KEM
     pub contributions: Vec<Contribution>,
+    /// Supply rules of the identity's tokens by token ID, fixed at genesis
+    #[serde(default)]
+    pub token_policies: HashMap<String, GenesisTokenPolicy>,
+}
+
+/// Layout of [`GenesisState`] under schema version 1, before `token_policies` was added
+#[derive(Serialize, Deserialize)]
+struct GenesisStateV1 {
+    hash: Vec<u8>,
+    initial_entropy: Vec<u8>,
+    threshold: usize,
+    participants: HashSet<String>,
+    merkle_root: Option<Vec<u8>>,
+    device_id: Option<String>,
+    signing_key: SigningKey,
+    kyber_keypair: KyberKey,
+    contributions: Vec<Contribution>,
+}
+
+impl LegacySchema for GenesisState {
+    const SCHEMA_VERSION: u16 = 2;
+    const LEGACY_SCHEMAS: &'static [(u16, LegacyDecoder<Self>)] = &[(1, Self::from_v1)];
 }
 
 impl SigningKey {
@@ -171,12 +270,26 @@ fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, DsmError> {
     }
 }
 
-fn calculate_genesis_hash(contributions: &[Vec<u8>], anchor: &str) -> Result<Vec<u8>, DsmError> {
+fn calculate_genesis_hash(
+    contributions: &[Vec<u8>],
+    anchor: &str,
+    token_policies: &HashMap<String, GenesisTokenPolicy>,
+) -> Result<Vec<u8>, DsmError> {
     let mut hasher = Sha3_512::new();
     hasher.update(anchor.as_bytes());
     for contrib in contributions {
         hasher.update(contrib);
     }
+
+    // Token policies in token ID order; omitted when there are none, so
+    // hashes of geneses without policies are unchanged
+    if !token_policies.is_empty() {
+        let policies: BTreeMap<&String, &GenesisTokenPolicy> = token_policies.iter().collect();
+        let policy_bytes = bincode::serialize(&policies)
+            .map_err(|e| DsmError::serialization("Failed to serialize token policies", Some(e)))?;
+        hasher.update(&policy_bytes);
+    }
+
     Ok(hasher.finalize().to_vec())
 }
 
@@ -209,6 +322,18 @@ fn calculate_device_entropy(
 pub fn create_genesis_state(
     threshold: usize,
     participants: impl IntoIterator<Item = String>,
+) -> Result<GenesisState, DsmError> {
+    create_genesis_state_with_policies(threshold, participants, HashMap::new())
+}
+
+/// Create a genesis state that fixes the supply rules of the identity's tokens
+///
+/// The policies are committed to by the genesis hash, so they cannot be
+/// changed without [`verify_genesis_state`] failing.
+pub fn create_genesis_state_with_policies(
+    threshold: usize,
+    participants: impl IntoIterator<Item = String>,
+    token_policies: HashMap<String, GenesisTokenPolicy>,
 ) -> Result<GenesisState, DsmError> {
     // Convert iterator to a collection so we can check its length
     let participants_set: HashSet<String> = participants.into_iter().collect();
@@ -254,7 +379,7 @@ pub fn create_genesis_state(
 
     // Calculate genesis hash and initial entropy
     let anchor = "genesis"; // Anchor string for genesis hash calculation
-    let genesis_hash = calculate_genesis_hash(&selected, anchor)?;
+    let genesis_hash = calculate_genesis_hash(&selected, anchor, &token_policies)?;
     let initial_entropy = calculate_initial_entropy(&genesis_hash, &selected)?;
 
     // Generate quantum-resistant keys
@@ -277,6 +402,7 @@ pub fn create_genesis_state(
                 verified: true,
             })
             .collect(),
+        token_policies,
     })
 }
 
@@ -317,6 +443,7 @@ pub fn derive_device_genesis(
             verified: true,
         }],
         threshold: 1, // Set to 1 for device genesis since there's only one participant
+        token_policies: master_genesis.token_policies.clone(),
     })
 }
 
@@ -381,7 +508,8 @@ pub fn verify_genesis_state(genesis: &GenesisState) -> Result<bool, DsmError> {
         .map(|c| c.data.clone())
         .collect();
 
-    let calculated_hash = calculate_genesis_hash(&contribution_data, anchor)?;
+    let calculated_hash =
+        calculate_genesis_hash(&contribution_data, anchor, &genesis.token_policies)?;
 
     // Verify calculated hash matches stored hash
     if calculated_hash != genesis.hash {
@@ -416,7 +544,7 @@ pub fn create_composite_genesis(
     let selected = select_random_subset(participant_contributions, threshold, &mut rng)?;
 
     // Calculate genesis hash and initial entropy
-    let genesis_hash = calculate_genesis_hash(&selected, anchor)?;
+    let genesis_hash = calculate_genesis_hash(&selected, anchor, &HashMap::new())?;
     let initial_entropy = calculate_initial_entropy(&genesis_hash, &selected)?;
 
     // Generate quantum-resistant keys
@@ -444,6 +572,7 @@ pub fn create_composite_genesis(
                 verified: true,
             })
             .collect(),
+        token_policies: HashMap::new(),
     })
 }
 
@@ -454,6 +583,24 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 }
 
 impl GenesisState {
+    /// Migrate a schema version 1 genesis, which has no token policies
+    fn from_v1(bytes: &[u8]) -> Result<Self, DsmError> {
+        let v1: GenesisStateV1 = bincode::deserialize(bytes)?;
+
+        Ok(Self {
+            hash: v1.hash,
+            initial_entropy: v1.initial_entropy,
+            threshold: v1.threshold,
+            participants: v1.participants,
+            merkle_root: v1.merkle_root,
+            device_id: v1.device_id,
+            signing_key: v1.signing_key,
+            kyber_keypair: v1.kyber_keypair,
+            contributions: v1.contributions,
+            token_policies: HashMap::new(),
+        })
+    }
+
     pub fn get_signing_key_bytes(&self) -> Result<Vec<u8>, DsmError> {
         Ok(self.signing_key.secret_key.clone())
     }
@@ -524,6 +671,30 @@ fn test_verification() {
         assert!(verification_result.unwrap());
     }
 
+    #[test]
+    fn test_token_policies_are_committed_to_the_genesis_hash() {
+        let participants = vec!["treasury".to_string()];
+        let policy = GenesisTokenPolicy {
+            max_supply: Some(Balance::new(1_000)),
+            mintable_by: vec!["treasury".to_string()],
+            burnable: false,
+        };
+        let mut genesis = create_genesis_state_with_policies(
+            1,
+            participants,
+            HashMap::from([("ROOT".to_string(), policy)]),
+        )
+        .unwrap();
+        assert!(verify_genesis_state(&genesis).unwrap());
+
+        // Raising the cap after creation no longer matches the genesis hash
+        genesis.token_policies.get_mut("ROOT").unwrap().max_supply = Some(Balance::new(2_000));
+        assert!(!verify_genesis_state(&genesis).unwrap());
+
+        genesis.token_policies.clear();
+        assert!(!verify_genesis_state(&genesis).unwrap());
+    }
+
     #[test]
     fn test_quantum_resistant_keys() {
         let participants = vec!["participant1".to_string(), "participant2".to_string()];
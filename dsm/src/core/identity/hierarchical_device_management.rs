@@ -227,13 +227,13 @@ pub fn verify_merkle_proof(&self, root_hash: &[u8]) -> Result<bool, DsmError> {
 
             // Add debug logging for hashes
             tracing::debug!(
-                "Verifying Merkle proof for device {} - leaf_index={}, proof_len={}",
-                self.device_id,
-                proof.leaf_index,
-                proof.path.len()
+                device_id = %self.device_id,
+                leaf_index = proof.leaf_index,
+                proof_len = proof.path.len(),
+                device_hash = %hex::encode(&device_hash),
+                root_hash = %hex::encode(root_hash),
+                "Verifying Merkle proof for device"
             );
-            tracing::debug!("Device hash: {}", hex::encode(&device_hash));
-            tracing::debug!("Root hash:   {}", hex::encode(root_hash));
 
             // Ensure we have 32-byte hashes
             let mut root = [0u8; 32];
@@ -241,9 +241,9 @@ pub fn verify_merkle_proof(&self, root_hash: &[u8]) -> Result<bool, DsmError> {
 
             if root_hash.len() < 32 || device_hash.len() < 32 {
                 tracing::warn!(
-                    "Invalid hash length - root: {}, leaf: {}",
-                    root_hash.len(),
-                    device_hash.len()
+                    root_len = root_hash.len(),
+                    leaf_len = device_hash.len(),
+                    "Invalid hash length"
                 );
                 Ok(false)
             } else {
@@ -259,7 +259,7 @@ pub fn verify_merkle_proof(&self, root_hash: &[u8]) -> Result<bool, DsmError> {
                 // Ok(result)
             }
         } else {
-            tracing::warn!("No Merkle proof available for device {}", self.device_id);
+            tracing::warn!(device_id = %self.device_id, "No Merkle proof available for device");
             Ok(false)
         }
     }
@@ -349,11 +349,11 @@ pub fn add_device(
             ));
         }
 
-        tracing::debug!("Adding new device: {}", device_id);
+        tracing::debug!(device_id, "Adding new device");
 
         // Generate sub-Genesis state for this device
         let sub_genesis = self.generate_sub_genesis(device_id, device_entropy)?;
-        tracing::debug!("Generated sub-genesis state for device {}", device_id);
+        tracing::debug!(device_id, "Generated sub-genesis state for device");
 
         // Create the device sub-identity without proof (will be added later)
         let device_identity = DeviceSubIdentity::new(device_id.to_string(), sub_genesis.clone());
@@ -364,14 +364,14 @@ pub fn add_device(
             .insert(device_id_string.clone(), device_identity);
 
         // Now rebuild the entire Merkle tree with all devices including the new one
-        tracing::debug!("Rebuilding Merkle tree after adding device {}", device_id);
+        tracing::debug!(device_id, "Rebuilding Merkle tree after adding device");
         self.rebuild_merkle_tree()?;
 
         // Get a clone of the updated device
         if let Some(updated_device) = self.devices.get(&device_id_string).cloned() {
             tracing::debug!(
-                "Successfully added device {} and rebuilt Merkle tree",
-                device_id
+                device_id,
+                "Successfully added device and rebuilt Merkle tree"
             );
 
             // Verify Merkle proof to ensure correctness
@@ -380,13 +380,13 @@ pub fn add_device(
 
             if !proof_verification {
                 tracing::warn!(
-                    "Merkle proof verification failed for newly added device {}",
-                    device_id
+                    device_id,
+                    "Merkle proof verification failed for newly added device"
                 );
             } else {
                 tracing::debug!(
-                    "Merkle proof verification successful for newly added device {}",
-                    device_id
+                    device_id,
+                    "Merkle proof verification successful for newly added device"
                 );
             }
 
@@ -394,8 +394,8 @@ pub fn add_device(
         } else {
             // This should never happen - fallback just in case
             tracing::error!(
-                "Device {} not found after adding - this is unexpected",
-                device_id
+                device_id,
+                "Device not found after adding - this is unexpected"
             );
             Err(DsmError::validation(
                 format!("Device {} not found after adding", device_id),
@@ -534,10 +534,10 @@ fn rebuild_merkle_tree(&mut self) -> Result<(), DsmError> {
 
                 // Add debug logging
                 tracing::debug!(
-                    "Generated proof for device {} at index {}: path_len={}",
-                    device_id,
-                    idx,
-                    proof.path.len()
+                    device_id = %device_id,
+                    index = idx,
+                    path_len = proof.path.len(),
+                    "Generated proof for device"
                 );
 
                 (device_id.to_string(), proof)
@@ -593,17 +593,17 @@ pub fn cross_device_verify(
         })?;
 
         tracing::debug!(
-            "Cross-device verification: verifying_device={}, target_device={}, target_state_number={}",
-            verifying_device_id,
-            target_device_id,
-            target_state.state_number
+            verifying_device = verifying_device_id,
+            target_device = target_device_id,
+            target_state_number = target_state.state_number,
+            "Cross-device verification"
         );
 
         // Get the Merkle root with proper error handling
         let merkle_root = self.merkle_root();
 
         // Log the merkle root for debugging
-        tracing::debug!("Merkle root: {}", hex::encode(&merkle_root));
+        tracing::debug!(merkle_root = %hex::encode(&merkle_root), "Merkle root");
 
         // Ensure this is a valid tree structure
         if merkle_root.iter().all(|&b| b == 0) {
@@ -617,14 +617,18 @@ pub fn cross_device_verify(
         // Verify verifying device's proof against the Merkle root
         let verifying_result = verifying_device.verify_merkle_proof(&merkle_root);
         if let Err(err) = verifying_result {
-            tracing::warn!("Failed to verify proof for verifying device: {}", err);
+            tracing::warn!(
+                device_id = verifying_device_id,
+                error = %err,
+                "Failed to verify proof for verifying device"
+            );
             return Ok(false);
         }
 
         if !verifying_result.unwrap() {
             tracing::warn!(
-                "Verifying device proof validation failed for device {}",
-                verifying_device_id
+                device_id = verifying_device_id,
+                "Verifying device proof validation failed"
             );
             return Ok(false);
         }
@@ -633,17 +637,17 @@ pub fn cross_device_verify(
         let target_result = target_device.verify_merkle_proof(&merkle_root);
         if let Err(err) = target_result {
             tracing::warn!(
-                "Failed to verify proof for target device {}: {}",
-                target_device_id,
-                err
+                device_id = target_device_id,
+                error = %err,
+                "Failed to verify proof for target device"
             );
             return Ok(false);
         }
 
         if !target_result.unwrap() {
             tracing::warn!(
-                "Target device proof validation failed for device {}",
-                target_device_id
+                device_id = target_device_id,
+                "Target device proof validation failed"
             );
             return Ok(false);
         }
@@ -668,9 +672,9 @@ pub fn cross_device_verify(
                 let hash_match = target_state.prev_state_hash == sub_genesis_hash;
                 if hash_match {
                     tracing::warn!(
-                        "State 1 prev_hash mismatch: got={}, expected={}",
-                        hex::encode(&target_state.prev_state_hash),
-                        hex::encode(&sub_genesis_hash)
+                        got = %hex::encode(&target_state.prev_state_hash),
+                        expected = %hex::encode(&sub_genesis_hash),
+                        "State 1 prev_hash mismatch"
                     );
                 }
                 Ok(hash_match)
@@ -682,8 +686,8 @@ pub fn cross_device_verify(
                     verifier.verify_chain(&target_device.sub_genesis, target_state)?;
                 if chain_result {
                     tracing::warn!(
-                        "Chain verification failed for state {}",
-                        target_state.state_number
+                        state_number = target_state.state_number,
+                        "Chain verification failed for state"
                     );
                 }
                 Ok(chain_result)
@@ -696,15 +700,15 @@ pub fn cross_device_verify(
         // Log final verification result
         if verification_result {
             tracing::debug!(
-                "Cross-device verification successful between {} and {}",
-                verifying_device_id,
-                target_device_id
+                verifying_device = verifying_device_id,
+                target_device = target_device_id,
+                "Cross-device verification successful"
             );
         } else {
             tracing::warn!(
-                "Cross-device verification failed between {} and {}",
-                verifying_device_id,
-                target_device_id
+                verifying_device = verifying_device_id,
+                target_device = target_device_id,
+                "Cross-device verification failed"
             );
         }
 
@@ -1224,9 +1228,9 @@ fn test_cross_device_verification() -> Result<(), DsmError> {
         for (id, entropy) in &devices {
             let device = manager.add_device(id, entropy)?;
             tracing::debug!(
-                "Added device {} - Genesis hash: {}",
-                id,
-                hex::encode(&device.sub_genesis.hash()?)
+                device_id = id,
+                genesis_hash = %hex::encode(&device.sub_genesis.hash()?),
+                "Added device"
             );
         }
 
@@ -1234,7 +1238,7 @@ fn test_cross_device_verification() -> Result<(), DsmError> {
         manager.rebuild_merkle_tree()?;
 
         let root_hash = manager.merkle_root();
-        tracing::debug!("Merkle root after rebuild: {}", hex::encode(&root_hash));
+        tracing::debug!(merkle_root = %hex::encode(&root_hash), "Merkle root after rebuild");
 
         // Verify each device's proof against the root
         for (id, _) in &devices {
@@ -1243,11 +1247,11 @@ fn test_cross_device_verification() -> Result<(), DsmError> {
             let device_hash = device.sub_genesis.hash()?;
 
             tracing::debug!(
-                "Verifying {} - Hash: {}, Proof index: {}, Proof path: {:?}",
-                id,
-                hex::encode(&device_hash),
-                proof.leaf_index,
-                proof.path.iter().map(hex::encode).collect::<Vec<_>>()
+                device_id = id,
+                device_hash = %hex::encode(&device_hash),
+                leaf_index = proof.leaf_index,
+                proof_path = ?proof.path.iter().map(hex::encode).collect::<Vec<_>>(),
+                "Verifying device"
             );
 
             assert!(
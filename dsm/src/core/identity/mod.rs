@@ -5,9 +5,11 @@
 //! - Hierarchical device-specific sub-identities
 //! - Device management and invalidation
 //! - Cross-device identity verification
+//! - Threshold recovery of the identity signing key
 
 pub mod genesis;
 pub mod hierarchical_device_management;
+pub mod secret_sharing;
 
 use rand::Rng;
 use std::collections::HashMap;
@@ -19,14 +21,17 @@
 
 // Re-export key components for easier access
 pub use genesis::{
-    create_composite_genesis, create_genesis_state, derive_device_genesis, verify_genesis_state,
-    GenesisState, KyberKey, SigningKey,
+    create_composite_genesis, create_genesis_state, create_genesis_state_with_policies,
+    derive_device_genesis, verify_genesis_state, GenesisState, GenesisTokenPolicy, KyberKey,
+    SigningKey,
 };
 
 pub use hierarchical_device_management::{
     DeviceInvalidationMarker, DeviceSubIdentity, HierarchicalDeviceManager,
 };
 
+pub use secret_sharing::{recover_identity_from_shares, split_identity_secret, IdentityShare};
+
 /// Error types specific to identity operations
 #[derive(Debug, thiserror::Error)]
 pub enum IdentityError {
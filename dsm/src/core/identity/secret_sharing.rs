@@ -0,0 +1,252 @@
+//! Threshold Secret Sharing for Identity Recovery
+//!
+//! Splits the SPHINCS+ signing key of an identity into Shamir shares, so the
+//! identity can be recovered from any `threshold` of them while fewer shares
+//! reveal nothing about the key. Each share carries the public parts of the
+//! identity and a commitment to the key, which recovery checks the
+//! reconstructed key against.
+//!
+//! Only the signing key is shared. Shares carry no secret key material
+//! besides their own share, so the Kyber secret key of a recovered identity
+//! is empty and must be regenerated.
+
+use serde::{Deserialize, Serialize};
+use sharks::{Share, Sharks};
+use std::collections::HashMap;
+
+use super::{GenesisState, Identity};
+use crate::types::error::DsmError;
+
+/// Domain separator for the commitment to the shared secret
+const COMMITMENT_DOMAIN: &[u8] = b"DSM_IDENTITY_SECRET_COMMITMENT";
+
+/// One Shamir share of an identity's signing key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityShare {
+    /// Name of the shared identity
+    pub name: String,
+
+    /// Master genesis of the identity, with its secret keys removed
+    pub public_genesis: GenesisState,
+
+    /// Share index (the x coordinate, starting at 1)
+    pub index: u8,
+
+    /// Number of shares needed to recover the identity
+    pub threshold: u8,
+
+    /// Share bytes (the y coordinates)
+    pub share: Vec<u8>,
+
+    /// BLAKE3 commitment to the shared signing key
+    pub commitment: Vec<u8>,
+}
+
+/// Split an identity's signing key into `total_shares` Shamir shares
+///
+/// Any `threshold` of the shares recover the identity with
+/// [`recover_identity_from_shares`].
+///
+/// # Arguments
+/// * `identity` - Identity whose SPHINCS+ secret key is shared
+/// * `threshold` - Shares needed for recovery, at least 2
+/// * `total_shares` - Shares to create, at least `threshold`
+pub fn split_identity_secret(
+    identity: &Identity,
+    threshold: u8,
+    total_shares: u8,
+) -> Result<Vec<IdentityShare>, DsmError> {
+    if threshold < 2 || threshold > total_shares {
+        return Err(DsmError::invalid_parameter(
+            "Threshold must be at least 2 and not larger than the number of shares",
+        ));
+    }
+
+    let secret = &identity.master_genesis.signing_key.secret_key;
+    if secret.is_empty() {
+        return Err(DsmError::invalid_parameter(
+            "Identity has no signing key to share",
+        ));
+    }
+
+    let mut public_genesis = identity.master_genesis.clone();
+    public_genesis.signing_key.secret_key.clear();
+    public_genesis.kyber_keypair.secret_key.clear();
+    let commitment = commit_to_secret(secret);
+
+    let shares = Sharks(threshold)
+        .dealer(secret)
+        .take(total_shares as usize)
+        .map(|share| {
+            let bytes = Vec::from(&share);
+            IdentityShare {
+                name: identity.name.clone(),
+                public_genesis: public_genesis.clone(),
+                index: bytes[0],
+                threshold,
+                share: bytes[1..].to_vec(),
+                commitment: commitment.clone(),
+            }
+        })
+        .collect();
+
+    Ok(shares)
+}
+
+/// Recover an identity from its Shamir shares
+///
+/// Needs at least `threshold` shares with distinct indices, all from the same
+/// split. Fails if the recovered key does not match the shares' commitment.
+pub fn recover_identity_from_shares(shares: &[IdentityShare]) -> Result<Identity, DsmError> {
+    let first = shares
+        .first()
+        .ok_or_else(|| DsmError::invalid_parameter("No shares to recover from"))?;
+
+    let mut distinct = HashMap::new();
+    for share in shares {
+        if share.threshold != first.threshold
+            || share.commitment != first.commitment
+            || share.public_genesis.hash != first.public_genesis.hash
+        {
+            return Err(DsmError::invalid_parameter(
+                "Shares do not belong to the same identity split",
+            ));
+        }
+
+        let mut bytes = Vec::with_capacity(share.share.len() + 1);
+        bytes.push(share.index);
+        bytes.extend_from_slice(&share.share);
+        let parsed = Share::try_from(bytes.as_slice())
+            .map_err(|e| DsmError::invalid_parameter(format!("Malformed share: {}", e)))?;
+        distinct.insert(share.index, parsed);
+    }
+
+    if distinct.len() < first.threshold as usize {
+        return Err(DsmError::invalid_parameter(format!(
+            "Recovery needs {} distinct shares, got {}",
+            first.threshold,
+            distinct.len()
+        )));
+    }
+
+    let secret = Sharks(first.threshold)
+        .recover(distinct.values())
+        .map_err(|e| {
+            DsmError::crypto(
+                format!("Failed to recover secret: {}", e),
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+    if commit_to_secret(&secret) != first.commitment {
+        return Err(DsmError::verification(
+            "Recovered secret does not match the share commitment",
+        ));
+    }
+
+    let mut master_genesis = first.public_genesis.clone();
+    master_genesis.signing_key.secret_key = secret;
+
+    Ok(Identity {
+        name: first.name.clone(),
+        master_genesis,
+        devices: Vec::new(),
+        invalidated: false,
+    })
+}
+
+/// Commitment to a shared secret
+fn commit_to_secret(secret: &[u8]) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update(secret);
+    hasher.finalize().as_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::identity::create_genesis_state;
+
+    fn identity() -> Identity {
+        let participants = ["alice", "bob", "carol"].map(String::from);
+        Identity {
+            name: "recoverable".to_string(),
+            master_genesis: create_genesis_state(2, participants).unwrap(),
+            devices: Vec::new(),
+            invalidated: false,
+        }
+    }
+
+    #[test]
+    fn test_recover_with_threshold_shares() -> Result<(), DsmError> {
+        let identity = identity();
+        let shares = split_identity_secret(&identity, 3, 5)?;
+        assert_eq!(shares.len(), 5);
+
+        let recovered = recover_identity_from_shares(&shares[1..4])?;
+        assert_eq!(recovered.name, identity.name);
+        assert_eq!(recovered.master_genesis.hash, identity.master_genesis.hash);
+        assert_eq!(
+            recovered.master_genesis.signing_key.secret_key,
+            identity.master_genesis.signing_key.secret_key
+        );
+        assert_eq!(
+            recovered.master_genesis.signing_key.public_key,
+            identity.master_genesis.signing_key.public_key
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_fails_below_threshold() -> Result<(), DsmError> {
+        let shares = split_identity_secret(&identity(), 3, 5)?;
+
+        assert!(recover_identity_from_shares(&shares[..2]).is_err());
+
+        // Repeating a share does not make up for a missing one
+        let repeated = [shares[0].clone(), shares[1].clone(), shares[1].clone()];
+        assert!(recover_identity_from_shares(&repeated).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shares_do_not_leak_secret() -> Result<(), DsmError> {
+        let identity = identity();
+        let secret = &identity.master_genesis.signing_key.secret_key;
+
+        // Share bytes from many splits of the same key should look uniform
+        let mut counts = [0usize; 256];
+        let mut total = 0;
+        for _ in 0..64 {
+            let shares = split_identity_secret(&identity, 2, 2)?;
+            assert!(shares[0].public_genesis.signing_key.secret_key.is_empty());
+            assert_ne!(&shares[0].share, secret);
+            for byte in &shares[0].share {
+                counts[*byte as usize] += 1;
+                total += 1;
+            }
+        }
+
+        let entropy: f64 = counts
+            .iter()
+            .filter(|count| **count > 0)
+            .map(|count| {
+                let p = *count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum();
+        assert!(entropy > 7.5, "share entropy {} bits per byte", entropy);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_threshold_rejected() {
+        let identity = identity();
+        assert!(split_identity_secret(&identity, 1, 3).is_err());
+        assert!(split_identity_secret(&identity, 4, 3).is_err());
+    }
+}
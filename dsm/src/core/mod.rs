@@ -8,6 +8,7 @@
 //! * Token management and tracking
 //! * Cryptographic verification services
 //! * State indexing for efficient lookups
+//! * Resource estimation for operations before execution
 //! * Entropy sources for randomness
 //!
 //! The Core Module serves as the central component responsible for initializing the DSM node,
@@ -17,6 +18,7 @@
 pub mod debug_helpers;
 pub mod identity;
 pub mod index;
+pub mod resources;
 pub mod state_machine;
 pub mod token;
 pub mod verification;
@@ -0,0 +1,268 @@
+//! # Transition Resource Estimation
+//!
+//! Estimates the resources a state transition will consume before it is
+//! executed, so callers can refuse operations that would be too expensive.
+//! Each operation has a base cost from a fixed table, to which dynamic costs
+//! are added for the parts of the current state it has to scan, such as a
+//! full balance scan for token operations. Operations wrapping another
+//! operation add the cost of the inner one.
+//!
+//! The cost table is calibrated with the `resource_estimation` benchmark,
+//! which sets measured execution times against the estimated `cpu_units`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::error::DsmError;
+use crate::types::operations::Operation;
+use crate::types::state_types::State;
+
+/// Estimated nanoseconds of execution per CPU unit
+pub const NS_PER_CPU_UNIT: u64 = 100;
+
+/// CPU units per token balance entry scanned
+const CPU_PER_BALANCE_ENTRY: u64 = 10;
+
+/// CPU units per committed balance scanned
+const CPU_PER_COMMITTED_BALANCE: u64 = 50;
+
+/// CPU units per dependency looked up in the executed operations
+const CPU_PER_DEPENDENCY: u64 = 20;
+
+/// CPU units per authority signature checked during recovery
+const CPU_PER_AUTHORITY_SIGNATURE: u64 = 2_000;
+
+/// CPU units per 64 bytes of operation hashed into the next state
+const CPU_PER_HASHED_BLOCK: u64 = 1;
+
+/// Memory of a state before its balances are counted
+const STATE_BASE_BYTES: u64 = 1_024;
+
+/// Memory of a token balance entry in a state
+const BALANCE_ENTRY_BYTES: u64 = 96;
+
+/// Memory of a committed balance in a state
+const COMMITTED_BALANCE_BYTES: u64 = 512;
+
+/// Resources a state transition is estimated to consume
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceEstimate {
+    /// Abstract CPU cost of the transition
+    pub cpu_units: u64,
+
+    /// Peak memory the transition holds, in bytes
+    pub memory_bytes: u64,
+
+    /// Number of records the transition writes to storage
+    pub storage_writes: u64,
+
+    /// Expected wall-clock duration of the transition, in nanoseconds
+    pub estimated_duration_ns: u64,
+}
+
+impl ResourceEstimate {
+    /// Check the estimate against a ceiling
+    ///
+    /// Every resource is compared separately, so a ceiling of zero for one
+    /// resource only allows transitions that use none of it.
+    ///
+    /// # Returns
+    /// * `Ok(())` if every resource is within the ceiling
+    /// * `Err(DsmError::ResourceExceeded)` naming the first resource over it
+    pub fn check_within(&self, ceiling: &ResourceEstimate) -> Result<(), DsmError> {
+        let resources = [
+            ("cpu_units", self.cpu_units, ceiling.cpu_units),
+            ("memory_bytes", self.memory_bytes, ceiling.memory_bytes),
+            (
+                "storage_writes",
+                self.storage_writes,
+                ceiling.storage_writes,
+            ),
+            (
+                "estimated_duration_ns",
+                self.estimated_duration_ns,
+                ceiling.estimated_duration_ns,
+            ),
+        ];
+
+        match resources
+            .into_iter()
+            .find(|(_, estimated, limit)| estimated > limit)
+        {
+            Some((resource, estimated, limit)) => Err(DsmError::ResourceExceeded {
+                resource: resource.to_string(),
+                estimated,
+                limit,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Estimate the resources executing `op` on top of `state` will consume
+pub fn estimate_resources(op: &Operation, state: &State) -> ResourceEstimate {
+    let cpu_units = cpu_cost(op, state);
+    let memory_bytes = STATE_BASE_BYTES
+        .saturating_add((state.token_balances.len() as u64).saturating_mul(BALANCE_ENTRY_BYTES))
+        .saturating_add(
+            (state.committed_balances.len() as u64).saturating_mul(COMMITTED_BALANCE_BYTES),
+        )
+        .saturating_add(operation_size(op));
+
+    ResourceEstimate {
+        cpu_units,
+        memory_bytes,
+        // The new state itself is always written
+        storage_writes: 1 + extra_writes(op),
+        estimated_duration_ns: cpu_units.saturating_mul(NS_PER_CPU_UNIT),
+    }
+}
+
+/// Base CPU cost of an operation, plus its scans of the state
+fn cpu_cost(op: &Operation, state: &State) -> u64 {
+    let balance_scan = (state.token_balances.len() as u64).saturating_mul(CPU_PER_BALANCE_ENTRY);
+    let committed_scan =
+        (state.committed_balances.len() as u64).saturating_mul(CPU_PER_COMMITTED_BALANCE);
+    let hashing = operation_size(op)
+        .div_ceil(64)
+        .saturating_mul(CPU_PER_HASHED_BLOCK);
+
+    let base = match op {
+        Operation::Genesis => 2_000,
+        Operation::Create { .. } => 3_000,
+        Operation::Update { .. } => 1_500,
+        Operation::Transfer { .. } => 1_000 + balance_scan,
+        Operation::Mint { .. } | Operation::Burn { .. } => 800 + balance_scan,
+        Operation::LockToken { .. } | Operation::UnlockToken { .. } => 600 + balance_scan,
+        Operation::AddRelationship { .. }
+        | Operation::CreateRelationship { .. }
+        | Operation::RemoveRelationship { .. } => 1_200,
+        Operation::Recovery { authority_sigs, .. } => (authority_sigs.len() as u64)
+            .saturating_mul(CPU_PER_AUTHORITY_SIGNATURE)
+            .saturating_add(5_000),
+        Operation::Delete { .. } | Operation::Invalidate { .. } => 1_000,
+        Operation::Link { .. } | Operation::Unlink { .. } => 700,
+        Operation::Generic { .. } => 500,
+        Operation::WithDependencies {
+            dependencies,
+            inner,
+        } => {
+            // The inner operation's hashing is counted with the wrapper's
+            let inner_cost = cpu_cost(inner, state)
+                .saturating_sub(operation_size(inner).div_ceil(64) * CPU_PER_HASHED_BLOCK);
+            (dependencies.len() as u64)
+                .saturating_mul(CPU_PER_DEPENDENCY)
+                .saturating_add(500)
+                .saturating_add(inner_cost)
+        }
+        // Verifying the transfer proof dominates the cost
+        Operation::CommittedTransfer { .. } => 20_000 + committed_scan,
+        Operation::RevealBalance { .. } => 10_000 + committed_scan,
+    };
+
+    base.saturating_add(hashing)
+}
+
+/// Storage writes beyond the new state
+fn extra_writes(op: &Operation) -> u64 {
+    match op {
+        Operation::Transfer { .. } | Operation::CommittedTransfer { .. } => 1,
+        Operation::Recovery { .. } => 2,
+        Operation::WithDependencies { inner, .. } => extra_writes(inner),
+        _ => 0,
+    }
+}
+
+/// Serialized size of an operation in bytes
+fn operation_size(op: &Operation) -> u64 {
+    bincode::serialized_size(op).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::state_types::DeviceInfo;
+    use crate::types::token_types::Balance;
+
+    fn state_with_balances(count: usize) -> State {
+        let mut state = State::new_genesis(vec![1, 2, 3, 4], DeviceInfo::new("device", vec![1]));
+        for i in 0..count {
+            state
+                .token_balances
+                .insert(format!("owner:TOKEN{}", i), Balance::new(100));
+        }
+        state
+    }
+
+    fn generic(data: Vec<u8>) -> Operation {
+        Operation::Generic {
+            operation_type: "test".to_string(),
+            data,
+            message: String::new(),
+        }
+    }
+
+    fn mint() -> Operation {
+        Operation::Mint {
+            amount: Balance::new(10),
+            token_id: "ROOT".to_string(),
+            authorized_by: "issuer".to_string(),
+            proof_of_authorization: vec![],
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_balance_operations_scale_with_state_size() {
+        let small = estimate_resources(&mint(), &state_with_balances(1));
+        let large = estimate_resources(&mint(), &state_with_balances(101));
+        assert_eq!(
+            large.cpu_units - small.cpu_units,
+            100 * CPU_PER_BALANCE_ENTRY
+        );
+        assert!(large.memory_bytes > small.memory_bytes);
+        assert_eq!(
+            large.estimated_duration_ns,
+            large.cpu_units * NS_PER_CPU_UNIT
+        );
+
+        // Operations that do not touch balances ignore them
+        let op = generic(vec![0; 16]);
+        assert_eq!(
+            estimate_resources(&op, &state_with_balances(1)).cpu_units,
+            estimate_resources(&op, &state_with_balances(101)).cpu_units
+        );
+    }
+
+    #[test]
+    fn test_wrapped_operations_add_their_inner_cost() {
+        let state = state_with_balances(10);
+        let inner = estimate_resources(&mint(), &state);
+        let wrapped = estimate_resources(
+            &Operation::WithDependencies {
+                dependencies: vec![[1; 32], [2; 32]],
+                inner: Box::new(mint()),
+            },
+            &state,
+        );
+        assert!(wrapped.cpu_units >= inner.cpu_units + 500 + 2 * CPU_PER_DEPENDENCY);
+        assert_eq!(wrapped.storage_writes, inner.storage_writes);
+    }
+
+    #[test]
+    fn test_check_within_names_the_exceeded_resource() {
+        let estimate = estimate_resources(&generic(vec![0; 4_096]), &state_with_balances(0));
+        assert!(estimate.check_within(&estimate).is_ok());
+
+        let ceiling = ResourceEstimate {
+            memory_bytes: estimate.memory_bytes - 1,
+            ..estimate
+        };
+        assert!(matches!(
+            estimate.check_within(&ceiling),
+            Err(DsmError::ResourceExceeded { resource, estimated, limit })
+                if resource == "memory_bytes"
+                    && estimated == estimate.memory_bytes
+                    && limit == ceiling.memory_bytes
+        ));
+    }
+}
@@ -214,14 +214,17 @@ pub fn verify(&mut self, prev_state_hash: &[u8]) -> Result<bool, DsmError> {
         }
 
         // All checks passed
-        tracing::info!("Batch verification successful");
+        tracing::info!(
+            batch_number = self.batch_number,
+            "Batch verification successful"
+        );
         tracing::debug!(
-            "Verification details: hash={:?}, root={:?}, prev_hash={:?}, count={}, time_range={:?}",
-            computed_hash,
-            self.transitions_root,
-            self.prev_state_hash,
-            self.transition_count,
-            self.time_range
+            hash = %hex::encode(&computed_hash),
+            root = %hex::encode(&self.transitions_root),
+            prev_hash = %hex::encode(&self.prev_state_hash),
+            count = self.transition_count,
+            time_range = ?self.time_range,
+            "Verification details"
         );
 
         // Invalidate cached hash after verification
@@ -292,7 +295,7 @@ pub fn start_batch(&mut self, prev_state: Option<&State>) -> Result<(), DsmError
         // Determine the previous state hash for chaining
         let prev_state_hash = match prev_state {
             Some(state) => state.hash().map_err(|e| {
-                tracing::error!("Failed to hash previous state: {:?}", e);
+                tracing::error!(error = ?e, "Failed to hash previous state");
                 DsmError::batch("Failed to hash previous state")
             })?,
             None => vec![0u8; 32], // Default hash for genesis batch
@@ -307,9 +310,9 @@ pub fn start_batch(&mut self, prev_state: Option<&State>) -> Result<(), DsmError
         }
 
         tracing::debug!(
-            "Started new batch {} with prev_state: {}",
-            self.batch_counter,
-            prev_state.is_some()
+            batch_number = self.batch_counter,
+            has_prev_state = prev_state.is_some(),
+            "Started new batch"
         );
 
         Ok(())
@@ -330,9 +333,9 @@ pub fn add_transition(&mut self, transition: StateTransition) -> Result<(), DsmE
 
             // For debugging
             tracing::debug!(
-                "Added transition to batch {}: device={}",
-                builder.batch_number,
-                transition.device_id
+                batch_number = builder.batch_number,
+                device_id = %transition.device_id,
+                "Added transition to batch"
             );
 
             Ok(())
@@ -347,9 +350,9 @@ pub fn add_transition(&mut self, transition: StateTransition) -> Result<(), DsmE
             builder.add_transition(transition.clone())?;
 
             tracing::debug!(
-                "Auto-created batch {} and added transition: device={}",
-                builder.batch_number,
-                transition.device_id
+                batch_number = builder.batch_number,
+                device_id = %transition.device_id,
+                "Auto-created batch and added transition"
             );
 
             Ok(())
@@ -381,9 +384,9 @@ pub fn finalize_batch(&mut self) -> Result<StateBatch, DsmError> {
             self.batches.insert(batch_number, batch.clone());
 
             tracing::info!(
-                "Finalized batch {} with {} transitions",
                 batch_number,
-                batch.transition_count
+                transition_count = batch.transition_count,
+                "Finalized batch"
             );
 
             // Increment batch counter for next batch
@@ -731,10 +734,10 @@ pub fn verify_batch(&self, batch: &StateBatch, last_state: &State) -> Result<boo
         let last_state_hash = last_state.hash()?;
         if batch.prev_state_hash != last_state_hash {
             tracing::warn!(
-                "Batch {} has invalid previous state hash: expected {:?}, got {:?}",
-                batch.batch_number,
-                last_state_hash,
-                batch.prev_state_hash
+                batch_number = batch.batch_number,
+                expected = %hex::encode(&last_state_hash),
+                got = %hex::encode(&batch.prev_state_hash),
+                "Batch has invalid previous state hash"
             );
             return Ok(false);
         }
@@ -744,9 +747,9 @@ pub fn verify_batch(&self, batch: &StateBatch, last_state: &State) -> Result<boo
             Ok(txs) => txs,
             Err(e) => {
                 tracing::warn!(
-                    "Failed to retrieve transitions for batch {}: {:?}",
-                    batch.batch_number,
-                    e
+                    batch_number = batch.batch_number,
+                    error = ?e,
+                    "Failed to retrieve transitions for batch"
                 );
                 return Ok(false);
             }
@@ -760,14 +763,14 @@ pub fn verify_batch(&self, batch: &StateBatch, last_state: &State) -> Result<boo
             let serialized = match bincode::serialize(transition) {
                 Ok(bytes) => bytes,
                 Err(e) => {
-                    tracing::warn!("Failed to serialize transition {}: {:?}", idx, e);
+                    tracing::warn!(index = idx, error = ?e, "Failed to serialize transition");
                     return Ok(false);
                 }
             };
 
             // Insert into tree
             if let Err(e) = sparse_merkle_tree::insert(&mut tree, idx as u64, &serialized) {
-                tracing::warn!("Failed to insert transition {} into tree: {:?}", idx, e);
+                tracing::warn!(index = idx, error = ?e, "Failed to insert transition into tree");
                 return Ok(false);
             }
         }
@@ -777,10 +780,10 @@ pub fn verify_batch(&self, batch: &StateBatch, last_state: &State) -> Result<boo
 
         if computed_root != batch.transitions_root {
             tracing::warn!(
-                "Merkle root mismatch for batch {}: computed {:?}, batch has {:?}",
-                batch.batch_number,
-                computed_root,
-                batch.transitions_root
+                batch_number = batch.batch_number,
+                computed = %hex::encode(&computed_root),
+                expected = %hex::encode(&batch.transitions_root),
+                "Merkle root mismatch for batch"
             );
             return Ok(false);
         }
@@ -790,16 +793,19 @@ pub fn verify_batch(&self, batch: &StateBatch, last_state: &State) -> Result<boo
             // Verify commitment is for a valid transition index
             if commitment.transition_index >= transitions.len() as u64 {
                 tracing::warn!(
-                    "Commitment for invalid transition index {} in batch {}",
-                    commitment.transition_index,
-                    batch.batch_number
+                    transition_index = commitment.transition_index,
+                    batch_number = batch.batch_number,
+                    "Commitment for invalid transition index"
                 );
                 return Ok(false);
             }
         }
 
         // All checks passed
-        tracing::info!("Batch {} verification successful", batch.batch_number);
+        tracing::info!(
+            batch_number = batch.batch_number,
+            "Batch verification successful"
+        );
         Ok(true)
     }
 } // Added closing brace to end impl BatchManager
@@ -995,6 +1001,7 @@ fn test_batch_error() {
     }
 
     #[test]
+    #[tracing_test::traced_test]
     fn test_batch_operations() {
         let mut manager = BatchManager::new();
 
@@ -1049,6 +1056,12 @@ fn test_batch_operations() {
         // Verify that the batch counter was incremented
         assert_eq!(manager.batch_counter, 1, "Batch counter not incremented");
 
+        // Batch events carry the batch and device as structured fields
+        assert!(logs_contain("Added transition to batch"));
+        assert!(logs_contain("device_id=device_0"));
+        assert!(logs_contain("Finalized batch"));
+        assert!(logs_contain("transition_count=1"));
+
         // Try to get a transition proof from the batch
         let proof_result = manager.generate_transition_proof(0, 0);
         assert!(
@@ -364,7 +364,7 @@ pub fn create_genesis(
         identity_claim: &IdentityClaim,
         initial_tokens: Option<u64>,
     ) -> Result<State, DsmError> {
-        info!("Creating genesis state for identity: {}", identity_claim.identity_id);
+        info!(identity_id = %identity_claim.identity_id, "Creating genesis state");
         
         // 1. Verify identity claim
         let mut state_builder = StateBuilder::new()
@@ -389,7 +389,7 @@ pub fn create_genesis(
             .with_entropy((*entropy_seed.as_bytes()).to_vec())
             .build()?;
             
-        debug!("Genesis state created with ID: {}", state.id);
+        debug!(state_id = %state.id, "Genesis state created");
         
         Ok(state)
     }
@@ -384,6 +384,53 @@ pub fn has_state_with_hash(&self, hash: &Vec<u8>) -> Result<bool, DsmError> {
         Ok(false)
     }
 
+    /// States numbered below `state_number`, in ascending state number order
+    pub fn states_before(&self, state_number: u64) -> Vec<&State> {
+        let mut states: Vec<&State> = self
+            .states
+            .values()
+            .filter(|state| state.state_number < state_number)
+            .collect();
+        states.sort_by_key(|state| state.state_number);
+        states
+    }
+
+    /// Remove every state numbered below `state_number` from the chain
+    ///
+    /// The current state is never removed. Once history has been pruned the
+    /// chain no longer starts at genesis, so `verify_chain` will reject it;
+    /// the oldest remaining state is expected to be backed by a checkpoint.
+    ///
+    /// # Arguments
+    /// * `state_number` - Number of the oldest state to keep
+    ///
+    /// # Returns
+    /// * `Vec<State>` - The removed states, in ascending state number order
+    pub fn prune_before(&mut self, state_number: u64) -> Vec<State> {
+        let cutoff = match &self.current_state {
+            Some(current) => state_number.min(current.state_number),
+            None => return Vec::new(),
+        };
+
+        let pruned_ids: Vec<String> = self
+            .states
+            .iter()
+            .filter(|(_, state)| state.state_number < cutoff)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut pruned: Vec<State> = pruned_ids
+            .iter()
+            .filter_map(|id| self.states.remove(id))
+            .collect();
+        pruned.sort_by_key(|state| state.state_number);
+
+        self.sparse_checkpoints
+            .retain(|&number, _| number >= cutoff);
+
+        pruned
+    }
+
     /// Verify the integrity of the entire chain
     ///
     /// # Returns
@@ -47,7 +47,7 @@
 pub use batch::{BatchBuilder, BatchCommitment, BatchManager, StateBatch};
 pub use relationship::{RelationshipManager, RelationshipStatePair};
 pub use transition::{create_transition, generate_position_sequence, StateTransition};
-pub use utils::{constant_time_eq, verify_state_hash}; // Export utility functions and remove hash_blake3 export
+pub use utils::{constant_time_eq, verify_chain_link, verify_state_hash}; // Export utility functions and remove hash_blake3 export
 
 /// Type definition for precommitment generation function
 type PrecommitmentGenFn = fn(&State, &Operation, &Hash) -> Result<(Hash, Vec<Position>), DsmError>;
@@ -157,6 +157,11 @@ pub fn verify_operation_adherence(&self, operation: &Operation) -> Result<bool,
                 Operation::Invalidate { .. } => b"invalid_",
                 Operation::LockToken { .. } => b"lock____",
                 Operation::UnlockToken { .. } => b"unlock__",
+                Operation::WithDependencies { ref inner, .. } => {
+                    return self.verify_operation_adherence(inner)
+                }
+                Operation::CommittedTransfer { .. } => b"cmt_xfer",
+                Operation::RevealBalance { .. } => b"reveal__",
             };
 
             if actual_op != expected_op.as_slice() {
@@ -493,6 +498,11 @@ pub fn validate_against_forward_commitment(
                         Operation::Invalidate { .. } => b"invalid_",
                         Operation::LockToken { .. } => b"lock____",
                         Operation::UnlockToken { .. } => b"unlock__",
+                        Operation::WithDependencies { inner, .. } => {
+                            return self.validate_against_forward_commitment(inner)
+                        }
+                        Operation::CommittedTransfer { .. } => b"cmt_xfer",
+                        Operation::RevealBalance { .. } => b"reveal__",
                     };
 
                     if value != op_type {
@@ -613,6 +613,10 @@ pub fn create_next_state_optimized(
         next_state.state_type = "benchmark".to_string();
     }
 
+    next_state.record_executed_operation(operation);
+    next_state.apply_committed_operation(operation)?;
+    next_state.is_checkpoint = false;
+
     // Compute hash only once
     let computed_hash = next_state.compute_hash()?;
     next_state.hash = computed_hash;
@@ -680,6 +684,12 @@ pub fn create_next_state(
         crate::types::state_types::State::calculate_sparse_indices(next_state.state_number)?;
     next_state.sparse_index = crate::types::state_types::SparseIndex::new(sparse_indices);
 
+    // Record the operation so later operations can depend on it
+    next_state.record_executed_operation(&operation_clone);
+    next_state.apply_committed_operation(&operation_clone)?;
+    next_state.charge_transfer_fee(&operation_clone)?;
+    next_state.is_checkpoint = false;
+
     // Recompute the hash for the new state
     let computed_hash = next_state.compute_hash()?;
     next_state.hash = computed_hash;
@@ -46,6 +46,25 @@ pub fn verify_state_hash(state: &State) -> Result<bool, DsmError> {
     Ok(constant_time_eq(&computed_hash, &state.hash))
 }
 
+/// Verify that `next` directly follows `prev` in a hash chain
+///
+/// `next` must carry the following state number and the hash of `prev`, and
+/// both states' stored hashes must match their recomputed contents, so the
+/// link holds for states received from another party.
+pub fn verify_chain_link(prev: &State, next: &State) -> Result<bool, DsmError> {
+    if prev.state_number.checked_add(1) != Some(next.state_number) {
+        return Ok(false);
+    }
+
+    for state in [prev, next] {
+        if !constant_time_eq(&state.compute_hash()?, &state.hash) {
+            return Ok(false);
+        }
+    }
+
+    Ok(constant_time_eq(&next.prev_state_hash, &prev.hash))
+}
+
 /// Calculate the next entropy based on current entropy, operation, and state number
 ///
 /// This implements the deterministic entropy evolution function from whitepaper Section 6:
@@ -1,3 +1,4 @@
+use crate::crypto::pedersen::PedersenParams;
 use crate::types::error::DsmError;
 use crate::types::operations::{Operation, TransactionMode};
 use crate::types::state_types::{DeviceInfo, SparseIndex, State};
@@ -221,6 +222,9 @@ pub fn validate_state(&self, state: &State) -> Result<(), DsmError> {
             Operation::Invalidate { .. } => Ok(()),
             Operation::LockToken { .. } => Ok(()),
             Operation::UnlockToken { .. } => Ok(()),
+            Operation::WithDependencies { .. } => Ok(()),
+            Operation::CommittedTransfer { .. } => Ok(()),
+            Operation::RevealBalance { .. } => Ok(()),
         }
     }
 
@@ -257,6 +261,35 @@ pub fn validate_operation(&self, operation: &Operation) -> Result<(), DsmError>
                 // Implement appropriate validation logic
                 Ok(())
             }
+            Operation::WithDependencies { inner, .. } => self.validate_operation(inner),
+            Operation::CommittedTransfer {
+                from_commitment,
+                to_commitment,
+                proof,
+                ..
+            } => {
+                // Conservation needs the sender's balance; the ranges can be checked here
+                let params = PedersenParams::balance_params(from_commitment.security_level);
+                if !proof.verify_ranges(params, from_commitment, to_commitment) {
+                    return Err(DsmError::verification(format!(
+                        "Committed transfer of {} has an invalid range proof",
+                        proof.token_id
+                    )));
+                }
+                Ok(())
+            }
+            Operation::RevealBalance {
+                token_id,
+                blinding_factor,
+                ..
+            } => {
+                if token_id.is_empty() || blinding_factor.is_empty() {
+                    return Err(DsmError::invalid_operation(
+                        "Balance disclosure needs a token and a blinding factor",
+                    ));
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -294,10 +327,13 @@ pub fn verify_state_operation(state: &State) -> Result<(), DsmError> {
             // Implement appropriate validation logic
             Ok(())
         }
+        Operation::WithDependencies { .. } => Ok(()),
+        Operation::CommittedTransfer { .. } => Ok(()),
+        Operation::RevealBalance { .. } => Ok(()),
     }
 }
 
-pub fn verify_state_transition(_state: &State, operation: &Operation) -> Result<(), DsmError> {
+pub fn verify_state_transition(state: &State, operation: &Operation) -> Result<(), DsmError> {
     match operation {
         Operation::Genesis => Ok(()),
         Operation::Generic { .. } => Ok(()),
@@ -330,6 +366,10 @@ pub fn verify_state_transition(_state: &State, operation: &Operation) -> Result<
             // Implement appropriate validation logic
             Ok(())
         }
+        Operation::WithDependencies { inner, .. } => verify_state_transition(state, inner),
+        Operation::CommittedTransfer { .. } | Operation::RevealBalance { .. } => {
+            state.verify_committed_operation(operation)
+        }
     }
 }
 
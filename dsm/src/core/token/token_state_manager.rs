@@ -91,7 +91,7 @@ pub fn insufficient_balance(message: impl Into<String>) -> DsmError {
 impl TokenStateManager {
     /// Create a new TokenStateManager
     pub fn new() -> Self {
-        let runtime = Runtime::new().ok();
+        let runtime = crate::utils::blocking_runtime().ok();
 
         Self {
             token_store: Arc::new(RwLock::new(HashMap::new())),
@@ -103,7 +103,7 @@ pub fn new() -> Self {
 
     /// Create a new TokenStateManager with a policy store
     pub fn with_policy_store(policy_store: Arc<PolicyStore>) -> Self {
-        let runtime = Runtime::new().ok();
+        let runtime = crate::utils::blocking_runtime().ok();
 
         Self {
             token_store: Arc::new(RwLock::new(HashMap::new())),
@@ -115,7 +115,7 @@ pub fn with_policy_store(policy_store: Arc<PolicyStore>) -> Self {
 
     /// Create a new TokenStateManager with a policy anchor
     pub fn with_policy_anchor(_policy_anchor: &PolicyAnchor) -> Self {
-        let runtime = Runtime::new().ok();
+        let runtime = crate::utils::blocking_runtime().ok();
 
         Self {
             token_store: Arc::new(RwLock::new(HashMap::new())),
@@ -12,11 +12,11 @@
     io::ErrorKind,
     path::PathBuf,
     sync::Arc,
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    time::Duration,
 };
 
 use parking_lot::RwLock;
-use tokio::{fs::File, io::AsyncWriteExt};
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
 
 use crate::{
     cpta::policy_verification::verify_policy_anchor,
@@ -24,6 +24,7 @@
         error::DsmError,
         policy_types::{PolicyAnchor, PolicyFile, TokenPolicy},
     },
+    utils::async_fs,
 };
 
 /// Cache entry with expiration time
@@ -111,21 +112,19 @@ pub async fn store_policy(&self, policy: &PolicyFile) -> Result<PolicyAnchor, Ds
         // Write policy file
         let policy_path = self.get_policy_path(&anchor);
 
-        let mut file = File::create(&policy_path).await.map_err(|e| {
-            DsmError::storage(format!("Failed to create policy file: {}", e), Some(e))
-        })?;
-
-        file.write_all(policy_json.as_bytes()).await.map_err(|e| {
-            DsmError::storage(format!("Failed to write policy file: {}", e), Some(e))
-        })?;
+        async_fs::write(&policy_path, policy_json.as_bytes())
+            .await
+            .map_err(|e| {
+                DsmError::storage(format!("Failed to write policy file: {}", e), Some(e))
+            })?;
 
         // Create TokenPolicy for cache
         let token_policy = TokenPolicy {
             file: policy.clone(),
             anchor: anchor.clone(),
             verified: true,
-            last_verified: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
+            last_verified: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
         };
@@ -305,7 +304,7 @@ pub async fn delete_policy(&self, anchor: &PolicyAnchor) -> Result<(), DsmError>
         let policy_path = self.get_policy_path(anchor);
 
         if policy_path.exists() {
-            tokio::fs::remove_file(&policy_path).await.map_err(|e| {
+            async_fs::remove_file(&policy_path).await.map_err(|e| {
                 DsmError::storage(format!("Failed to delete policy file: {}", e), Some(e))
             })?;
         }
@@ -5,7 +5,7 @@
 use crate::types::error::DsmError;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use aes_gcm::{
     aead::{generic_array::GenericArray, Aead, KeyInit},
@@ -13,12 +13,13 @@
 };
 use blake3::Hasher;
 use once_cell::sync::Lazy;
-use pqcrypto_mlkem::mlkem768;
+use crate::crypto::mlkem::mlkem768;
 use pqcrypto_traits::kem::SecretKey;
 use pqcrypto_traits::kem::{Ciphertext, PublicKey, SharedSecret};
 use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, trace};
+use web_time::Instant;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 // Cryptographic constants
@@ -0,0 +1,129 @@
+//! # ML-KEM Parameter Sets
+//!
+//! ML-KEM-512, ML-KEM-768 and ML-KEM-1024 with the `pqcrypto-mlkem`
+//! interface, for every target.
+//!
+//! Native targets re-export `pqcrypto-mlkem`, which compiles the PQClean C
+//! sources. Those sources do not build for wasm32, so there each parameter
+//! set is backed by the pure-Rust `ml-kem` crate instead. Both implement FIPS
+//! 203 with the same key and ciphertext encodings, so keys and ciphertexts
+//! produced by a browser client are accepted natively and vice versa.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use pqcrypto_mlkem::{mlkem1024, mlkem512, mlkem768};
+
+#[cfg(target_arch = "wasm32")]
+pub use self::wasm::{mlkem1024, mlkem512, mlkem768};
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    /// Define a fixed-length byte string implementing a `pqcrypto_traits` trait
+    macro_rules! byte_type {
+        ($name:ident, $len:expr, $trait:ident) => {
+            #[derive(Clone, Copy)]
+            pub struct $name([u8; $len]);
+
+            impl $name {
+                fn copy_from(bytes: &[u8]) -> Self {
+                    let mut out = [0u8; $len];
+                    out.copy_from_slice(bytes);
+                    Self(out)
+                }
+            }
+
+            impl pqcrypto_traits::kem::$trait for $name {
+                fn as_bytes(&self) -> &[u8] {
+                    &self.0
+                }
+
+                fn from_bytes(bytes: &[u8]) -> pqcrypto_traits::Result<Self> {
+                    if bytes.len() != $len {
+                        return Err(pqcrypto_traits::Error::BadLength {
+                            name: stringify!($name),
+                            actual: bytes.len(),
+                            expected: $len,
+                        });
+                    }
+                    Ok(Self::copy_from(bytes))
+                }
+            }
+        };
+    }
+
+    /// Define one parameter set with the `pqcrypto-mlkem` module interface
+    macro_rules! mlkem_parameter_set {
+        ($module:ident, $kem:ty, $pk:expr, $sk:expr, $ct:expr) => {
+            pub mod $module {
+                use ml_kem::kem::{Decapsulate, Encapsulate};
+                use ml_kem::{EncodedSizeUser, KemCore};
+                use rand::rngs::OsRng;
+
+                type Kem = $kem;
+                type EncapsulationKey = <Kem as KemCore>::EncapsulationKey;
+                type DecapsulationKey = <Kem as KemCore>::DecapsulationKey;
+
+                const PUBLIC_KEY_BYTES: usize = $pk;
+                const SECRET_KEY_BYTES: usize = $sk;
+                const CIPHERTEXT_BYTES: usize = $ct;
+                const SHARED_SECRET_BYTES: usize = 32;
+
+                byte_type!(PublicKey, PUBLIC_KEY_BYTES, PublicKey);
+                byte_type!(SecretKey, SECRET_KEY_BYTES, SecretKey);
+                byte_type!(Ciphertext, CIPHERTEXT_BYTES, Ciphertext);
+                byte_type!(SharedSecret, SHARED_SECRET_BYTES, SharedSecret);
+
+                /// Length of a public key in bytes
+                pub const fn public_key_bytes() -> usize {
+                    PUBLIC_KEY_BYTES
+                }
+
+                /// Length of a secret key in bytes
+                pub const fn secret_key_bytes() -> usize {
+                    SECRET_KEY_BYTES
+                }
+
+                /// Length of a ciphertext in bytes
+                pub const fn ciphertext_bytes() -> usize {
+                    CIPHERTEXT_BYTES
+                }
+
+                /// Length of a shared secret in bytes
+                pub const fn shared_secret_bytes() -> usize {
+                    SHARED_SECRET_BYTES
+                }
+
+                /// Generate a fresh keypair
+                pub fn keypair() -> (PublicKey, SecretKey) {
+                    let (dk, ek) = Kem::generate(&mut OsRng);
+                    (
+                        PublicKey::copy_from(&ek.as_bytes()),
+                        SecretKey::copy_from(&dk.as_bytes()),
+                    )
+                }
+
+                /// Encapsulate a fresh shared secret to a public key
+                pub fn encapsulate(pk: &PublicKey) -> (SharedSecret, Ciphertext) {
+                    let ek = EncapsulationKey::from_bytes(&pk.0.into());
+                    let (ct, ss) = match ek.encapsulate(&mut OsRng) {
+                        Ok(encapsulated) => encapsulated,
+                        Err(never) => match never {},
+                    };
+                    (SharedSecret::copy_from(&ss), Ciphertext::copy_from(&ct))
+                }
+
+                /// Recover the shared secret from a ciphertext
+                pub fn decapsulate(ct: &Ciphertext, sk: &SecretKey) -> SharedSecret {
+                    let dk = DecapsulationKey::from_bytes(&sk.0.into());
+                    match dk.decapsulate(&ct.0.into()) {
+                        Ok(ss) => SharedSecret::copy_from(&ss),
+                        Err(never) => match never {},
+                    }
+                }
+            }
+        };
+    }
+
+    mlkem_parameter_set!(mlkem512, ml_kem::MlKem512, 800, 1632, 768);
+    mlkem_parameter_set!(mlkem768, ml_kem::MlKem768, 1184, 2400, 1088);
+    mlkem_parameter_set!(mlkem1024, ml_kem::MlKem1024, 1568, 3168, 1568);
+}
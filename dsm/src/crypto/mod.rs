@@ -2,12 +2,14 @@
 //!
 //! This module provides cryptographic primitives and operations for the DSM system, including:
 //!
-//! * Post-quantum secure encryption using Kyber
+//! * Post-quantum secure encryption using Kyber (ML-KEM), on native and wasm32 targets
 //! * Post-quantum secure signatures using SPHINCS+
 //! * Hash functions (Blake3, SHA3)
 //! * Pedersen commitments
 //! * Secure RNG utilities
 //! * Privacy-preserving random walks
+//! * Stealth addresses for unlinkable payment recipients
+//! * Ring signatures for anonymous sender proofs
 //!
 //! The module implements a hybrid encryption approach using post-quantum algorithms combined
 //! with symmetric encryption (ChaCha20Poly1305) for data protection.
@@ -29,12 +31,15 @@
 pub mod blake3;
 pub mod hash;
 pub mod kyber;
+pub mod mlkem;
 pub mod pedersen;
 pub mod random_walk_privacy;
+pub mod ring;
 pub mod rng;
 pub mod sha3;
 pub mod signatures;
 pub mod sphincs;
+pub mod stealth;
 
 // A simple in-memory key store for development purposes
 // In production, this would be replaced with secure storage (HSM, TEE, etc.)
@@ -3,11 +3,8 @@
 //! Implements quantum-resistant Pedersen commitments using post-quantum secure
 //! primitives only. No classical variants are supported.
 
-use std::str::FromStr;
-
-use blake3;
 use num_bigint::BigUint;
-use num_primes::Generator;
+use once_cell::sync::Lazy;
 use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
@@ -28,6 +25,41 @@ pub enum SecurityLevel {
 }
 
 const DOMAIN_COMMIT: &[u8] = b"DSM.v1.pedersen.commit";
+const DOMAIN_BALANCE_PARAMS: &[u8] = b"DSM.v1.pedersen.balance-params";
+const DOMAIN_TRANSFER_PROOF: &[u8] = b"DSM.v1.pedersen.transfer-proof";
+const DOMAIN_RANGE_PROOF: &[u8] = b"DSM.v1.pedersen.range-proof";
+const DOMAIN_VAULT_CONTENT_PARAMS: &[u8] = b"DSM.v1.pedersen.vault-content-params";
+
+/// Shared parameters for committed token balances, derived once per process
+static STANDARD_BALANCE_PARAMS: Lazy<PedersenParams> =
+    Lazy::new(|| PedersenParams::deterministic(SecurityLevel::Standard128, DOMAIN_BALANCE_PARAMS));
+static MEDIUM_BALANCE_PARAMS: Lazy<PedersenParams> =
+    Lazy::new(|| PedersenParams::deterministic(SecurityLevel::Medium192, DOMAIN_BALANCE_PARAMS));
+static HIGH_BALANCE_PARAMS: Lazy<PedersenParams> =
+    Lazy::new(|| PedersenParams::deterministic(SecurityLevel::High256, DOMAIN_BALANCE_PARAMS));
+
+/// Shared parameters for vault content commitments
+static VAULT_CONTENT_PARAMS: Lazy<PedersenParams> = Lazy::new(|| {
+    PedersenParams::deterministic(SecurityLevel::Standard128, DOMAIN_VAULT_CONTENT_PARAMS)
+});
+
+/// Modulus and subgroup sizes in bits for a security level
+fn param_bits(security_level: SecurityLevel) -> (usize, usize) {
+    match security_level {
+        SecurityLevel::Standard128 => (3072, 256),
+        SecurityLevel::Medium192 => (7680, 384),
+        SecurityLevel::High256 => (15360, 512),
+    }
+}
+
+/// Number of hash rounds for a security level
+fn hash_rounds_for(security_level: SecurityLevel) -> u32 {
+    match security_level {
+        SecurityLevel::Standard128 => 10,
+        SecurityLevel::Medium192 => 14,
+        SecurityLevel::High256 => 20,
+    }
+}
 
 /// Parameters for quantum-resistant Pedersen commitment
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -45,16 +77,34 @@ pub struct PedersenParams {
 }
 impl PedersenParams {
     /// Create new parameters based on security level
+    ///
+    /// The group is the precomputed group of the security level; both
+    /// generators are fresh.
     pub fn new(security_level: SecurityLevel) -> Self {
-        // Select parameters based on quantum security requirements
-        let (p_bits, q_bits) = match security_level {
-            SecurityLevel::Standard128 => (3072, 256),
-            SecurityLevel::Medium192 => (7680, 384),
-            SecurityLevel::High256 => (15360, 512),
-        };
+        let (p, q) = group(security_level);
+        let (g, h) = random_generators(&p, &q);
+
+        Self {
+            g,
+            h,
+            p,
+            q,
+            security_level,
+        }
+    }
 
-        // Generate safe prime and generator
-        let (p, q, g, h) = generate_pedersen_params(p_bits, q_bits);
+    /// Derive parameters deterministically from a public seed
+    ///
+    /// The modulus and subgroup are the precomputed group of the security
+    /// level, see [`group`]. Both generators are obtained by hashing into the prime-order subgroup
+    /// under the seed, so nobody (including whoever picked the seed) knows the
+    /// discrete log of `h` to base `g`, and parameters for different uses
+    /// never share generators. Every party deriving from the same seed gets the
+    /// same parameters, which lets commitments be verified without exchanging them.
+    pub fn deterministic(security_level: SecurityLevel, seed: &[u8]) -> Self {
+        let (p, q) = group(security_level);
+        let g = hash_to_subgroup(seed, b"g", &p, &q);
+        let h = hash_to_subgroup(seed, b"h", &p, &q);
 
         Self {
             g,
@@ -64,9 +114,23 @@ pub fn new(security_level: SecurityLevel) -> Self {
             security_level,
         }
     }
+
+    /// Network-wide parameters used for committed token balances
+    pub fn balance_params(security_level: SecurityLevel) -> &'static Self {
+        match security_level {
+            SecurityLevel::Standard128 => &STANDARD_BALANCE_PARAMS,
+            SecurityLevel::Medium192 => &MEDIUM_BALANCE_PARAMS,
+            SecurityLevel::High256 => &HIGH_BALANCE_PARAMS,
+        }
+    }
+
+    /// Network-wide parameters used for vault content commitments
+    pub fn vault_content_params() -> &'static Self {
+        &VAULT_CONTENT_PARAMS
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde_as]
 pub struct PedersenCommitment {
     /// The commitment value
@@ -130,11 +194,7 @@ pub fn commit<R: RngCore + CryptoRng>(
         rng: &mut R,
     ) -> DsmResult<(Self, BigUint)> {
         // Get number of hash rounds based on security level
-        let hash_rounds = match params.security_level {
-            SecurityLevel::Standard128 => 10,
-            SecurityLevel::Medium192 => 14,
-            SecurityLevel::High256 => 20,
-        };
+        let hash_rounds = hash_rounds_for(params.security_level);
 
         // Generate randomness
         let r = Self::generate_randomness(rng, &params.q);
@@ -154,6 +214,24 @@ pub fn commit<R: RngCore + CryptoRng>(
         ))
     }
 
+    /// Commit to a value with a known blinding factor
+    pub fn commit_with_blinding(
+        params: &PedersenParams,
+        value: &[u8],
+        blinding_factor: &BigUint,
+    ) -> DsmResult<Self> {
+        let hash_rounds = hash_rounds_for(params.security_level);
+        let commitment = Self::compute_commitment(value, blinding_factor, params)?;
+        let commitment_hash = hash_commitment(&commitment, hash_rounds)?;
+
+        Ok(Self {
+            commitment,
+            commitment_hash,
+            hash_rounds,
+            security_level: params.security_level,
+        })
+    }
+
     /// Generate secure randomness for the commitment
     fn generate_randomness<R: RngCore + CryptoRng>(rng: &mut R, q: &BigUint) -> BigUint {
         // Determine how many bytes we need
@@ -199,6 +277,58 @@ pub fn combine(&self, other: &Self, params: &PedersenParams) -> DsmResult<Self>
         })
     }
 
+    /// Homomorphically subtract a commitment, yielding a commitment to the
+    /// difference of the values under the difference of the blinding factors
+    pub fn subtract(&self, other: &Self, params: &PedersenParams) -> DsmResult<Self> {
+        if self.security_level != other.security_level {
+            return Err(DsmError::crypto(
+                String::from("Cannot subtract commitments with different security levels"),
+                None::<std::io::Error>,
+            ));
+        }
+
+        // Multiply by the modular inverse; p is prime so x^(p-2) = x^-1
+        let exponent = &params.p - BigUint::from(2u32);
+        let inverse = other.commitment.modpow(&exponent, &params.p);
+        let difference = (&self.commitment * inverse) % &params.p;
+        let commitment_hash = hash_commitment(&difference, self.hash_rounds)?;
+
+        Ok(Self {
+            commitment: difference,
+            commitment_hash,
+            hash_rounds: self.hash_rounds,
+            security_level: self.security_level,
+        })
+    }
+
+    /// Commit to a token amount with a fresh blinding factor
+    pub fn commit_amount<R: RngCore + CryptoRng>(
+        params: &PedersenParams,
+        amount: u64,
+        rng: &mut R,
+    ) -> DsmResult<(Self, BigUint)> {
+        Self::commit(params, &amount.to_le_bytes(), rng)
+    }
+
+    /// Commit to a token amount with a known blinding factor
+    pub fn commit_amount_with_blinding(
+        params: &PedersenParams,
+        amount: u64,
+        blinding_factor: &BigUint,
+    ) -> DsmResult<Self> {
+        Self::commit_with_blinding(params, &amount.to_le_bytes(), blinding_factor)
+    }
+
+    /// Verify that this commitment opens to a token amount
+    pub fn verify_amount(
+        &self,
+        amount: u64,
+        blinding_factor: &BigUint,
+        params: &PedersenParams,
+    ) -> DsmResult<bool> {
+        self.verify(&amount.to_le_bytes(), blinding_factor, params)
+    }
+
     /// Compute commitment with quantum resistance
     fn compute_commitment(
         value: &[u8],
@@ -245,6 +375,13 @@ pub fn smart_commit<R: RngCore + CryptoRng>(
         Self::commit(params, domain_separated.as_bytes(), rng)
     }
 
+    /// Whether this is the zero placeholder of `Default` rather than a commitment
+    ///
+    /// Every real commitment is a non-zero group element.
+    pub fn is_empty(&self) -> bool {
+        self.commitment.bits() == 0
+    }
+
     /// Convert commitment to bytes for serialization
     pub fn to_bytes(&self) -> Vec<u8> {
         bincode::serialize(self).unwrap_or_default()
@@ -257,6 +394,460 @@ pub fn from_bytes(bytes: &[u8]) -> Result<Self, DsmError> {
     }
 }
 
+/// Zero-knowledge proof that a committed transfer conserves value
+///
+/// For a balance commitment `B`, the sender's remaining balance `F` and the
+/// transferred amount `T`, the residue `D = B / (F * T)` commits to
+/// `balance - remaining - transferred` under the blinding difference `δ`.
+/// When value is conserved `D = h^δ`, and the proof is a Schnorr
+/// proof of knowledge of `δ` to base `h`, made non-interactive with
+/// Fiat-Shamir. Range proofs show that `F` and `T` commit to amounts in
+/// `[0, 2^64)`, so the sum cannot wrap around the group order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransferProof {
+    /// Token whose committed balance is being transferred
+    pub token_id: String,
+
+    /// Nonce commitment `R = h^k`
+    #[serde(with = "biguint_serde")]
+    pub nonce_commitment: BigUint,
+
+    /// Response `s = k + c·δ mod q`
+    #[serde(with = "biguint_serde")]
+    pub response: BigUint,
+
+    /// Range proof for the sender's remaining balance
+    pub remaining_range: RangeProof,
+
+    /// Range proof for the transferred amount
+    pub transferred_range: RangeProof,
+}
+
+impl TransferProof {
+    /// Prove that `balance` opens to the sum of `remaining` and `transferred`
+    ///
+    /// `balance` pairs a commitment with its blinding factor; `remaining` and
+    /// `transferred` also carry the committed amounts, which are range-proven.
+    pub fn prove<R: RngCore + CryptoRng>(
+        params: &PedersenParams,
+        token_id: &str,
+        balance: (&PedersenCommitment, &BigUint),
+        remaining: (&PedersenCommitment, u64, &BigUint),
+        transferred: (&PedersenCommitment, u64, &BigUint),
+        rng: &mut R,
+    ) -> DsmResult<Self> {
+        let q = &params.q;
+
+        // δ = r_balance - r_remaining - r_transferred (mod q)
+        let delta =
+            ((balance.1 % q) + (q * BigUint::from(2u32)) - (remaining.2 % q) - (transferred.2 % q))
+                % q;
+
+        let residue = transfer_residue(params, balance.0, remaining.0, transferred.0)?;
+
+        let k = PedersenCommitment::generate_randomness(rng, q);
+        let nonce_commitment = params.h.modpow(&k, &params.p);
+
+        let challenge = transfer_challenge(
+            params,
+            token_id,
+            [balance.0, remaining.0, transferred.0],
+            &residue,
+            &nonce_commitment,
+        );
+        let response = (k + challenge * delta) % q;
+
+        let remaining_range = RangeProof::prove(
+            params,
+            &range_context(token_id, b"remaining"),
+            remaining.0,
+            remaining.1,
+            remaining.2,
+            rng,
+        );
+        let transferred_range = RangeProof::prove(
+            params,
+            &range_context(token_id, b"transferred"),
+            transferred.0,
+            transferred.1,
+            transferred.2,
+            rng,
+        );
+
+        Ok(Self {
+            token_id: token_id.to_string(),
+            nonce_commitment,
+            response,
+            remaining_range,
+            transferred_range,
+        })
+    }
+
+    /// Verify that the remaining balance and transferred amount are in range
+    ///
+    /// Needs no balance, so it can be checked before the sender's state is known.
+    pub fn verify_ranges(
+        &self,
+        params: &PedersenParams,
+        remaining: &PedersenCommitment,
+        transferred: &PedersenCommitment,
+    ) -> bool {
+        self.remaining_range.verify(
+            params,
+            &range_context(&self.token_id, b"remaining"),
+            remaining,
+        ) && self.transferred_range.verify(
+            params,
+            &range_context(&self.token_id, b"transferred"),
+            transferred,
+        )
+    }
+
+    /// Verify the proof against the commitments without learning any amount
+    pub fn verify(
+        &self,
+        params: &PedersenParams,
+        balance: &PedersenCommitment,
+        remaining: &PedersenCommitment,
+        transferred: &PedersenCommitment,
+    ) -> DsmResult<bool> {
+        if !self.verify_ranges(params, remaining, transferred) {
+            return Ok(false);
+        }
+
+        if self.response >= params.q
+            || self.nonce_commitment == BigUint::from(0u32)
+            || self.nonce_commitment >= params.p
+        {
+            return Ok(false);
+        }
+
+        let residue = match transfer_residue(params, balance, remaining, transferred) {
+            Ok(residue) => residue,
+            // Mismatched security levels can never form a valid transfer
+            Err(_) => return Ok(false),
+        };
+
+        let challenge = transfer_challenge(
+            params,
+            &self.token_id,
+            [balance, remaining, transferred],
+            &residue,
+            &self.nonce_commitment,
+        );
+
+        // h^s == R * D^c (mod p)
+        let lhs = params.h.modpow(&self.response, &params.p);
+        let rhs = (&self.nonce_commitment * residue.modpow(&challenge, &params.p)) % &params.p;
+
+        Ok(lhs == rhs)
+    }
+}
+
+/// Residue `B / (F * T)` of a committed transfer
+fn transfer_residue(
+    params: &PedersenParams,
+    balance: &PedersenCommitment,
+    remaining: &PedersenCommitment,
+    transferred: &PedersenCommitment,
+) -> DsmResult<BigUint> {
+    Ok(balance
+        .subtract(remaining, params)?
+        .subtract(transferred, params)?
+        .commitment)
+}
+
+/// Fiat-Shamir challenge binding the proof to the token and all commitments
+fn transfer_challenge(
+    params: &PedersenParams,
+    token_id: &str,
+    commitments: [&PedersenCommitment; 3],
+    residue: &BigUint,
+    nonce_commitment: &BigUint,
+) -> BigUint {
+    let mut hasher = Sha3_512::new();
+    sha3::Digest::update(&mut hasher, DOMAIN_TRANSFER_PROOF);
+    sha3::Digest::update(&mut hasher, token_id.as_bytes());
+    for commitment in commitments {
+        sha3::Digest::update(&mut hasher, commitment.commitment.to_bytes_be());
+    }
+    sha3::Digest::update(&mut hasher, residue.to_bytes_be());
+    sha3::Digest::update(&mut hasher, nonce_commitment.to_bytes_be());
+
+    BigUint::from_bytes_be(&hasher.finalize()) % &params.q
+}
+
+/// Context binding a range proof to its token and role in a transfer
+fn range_context(token_id: &str, role: &[u8]) -> Vec<u8> {
+    let mut context = token_id.as_bytes().to_vec();
+    context.push(0);
+    context.extend_from_slice(role);
+    context
+}
+
+/// Number of bits a range proof covers; committed amounts are `u64`
+const RANGE_BITS: usize = 64;
+
+/// Zero-knowledge proof that a commitment opens to an amount in `[0, 2^64)`
+///
+/// The amount is split into bits, each committed separately as
+/// `C_i = g^b_i · h^r_i` with blinding factors chosen so that
+/// `∏ C_i^(2^i)` equals the commitment. Each bit carries a
+/// Cramer-Damgård-Schoenmakers OR proof that `C_i` commits to 0 or to 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RangeProof {
+    /// One proof per bit, least significant first
+    bits: Vec<BitProof>,
+}
+
+/// OR proof that a bit commitment opens to 0 or to 1
+///
+/// Branch `j` proves knowledge of the discrete log of `C / g^j` to base `h`.
+/// One branch is simulated, and the two challenges must sum to the
+/// Fiat-Shamir challenge.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct BitProof {
+    #[serde(with = "biguint_serde")]
+    commitment: BigUint,
+    #[serde(with = "biguint_serde")]
+    challenge_zero: BigUint,
+    #[serde(with = "biguint_serde")]
+    challenge_one: BigUint,
+    #[serde(with = "biguint_serde")]
+    response_zero: BigUint,
+    #[serde(with = "biguint_serde")]
+    response_one: BigUint,
+}
+
+impl RangeProof {
+    /// Prove that `commitment` opens to `amount` under `blinding_factor`
+    ///
+    /// `context` binds the proof to its use, so it cannot be replayed for
+    /// another token or role.
+    pub fn prove<R: RngCore + CryptoRng>(
+        params: &PedersenParams,
+        context: &[u8],
+        commitment: &PedersenCommitment,
+        amount: u64,
+        blinding_factor: &BigUint,
+        rng: &mut R,
+    ) -> Self {
+        let q = &params.q;
+
+        // Pick all but the lowest bit's blinding at random, then solve for it so
+        // that Σ 2^i·r_i = r (mod q)
+        let mut blindings: Vec<BigUint> = (1..RANGE_BITS)
+            .map(|_| PedersenCommitment::generate_randomness(rng, q))
+            .collect();
+        let weighted = blindings
+            .iter()
+            .enumerate()
+            .fold(BigUint::from(0u32), |sum, (i, r)| sum + (r << (i + 1)))
+            % q;
+        blindings.insert(0, ((blinding_factor % q) + q - weighted) % q);
+
+        let bits = blindings
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let bit = (amount >> i) & 1 == 1;
+                BitProof::prove(params, context, &commitment.commitment, i, bit, r, rng)
+            })
+            .collect();
+
+        Self { bits }
+    }
+
+    /// Verify the proof against a commitment without learning the amount
+    pub fn verify(
+        &self,
+        params: &PedersenParams,
+        context: &[u8],
+        commitment: &PedersenCommitment,
+    ) -> bool {
+        if self.bits.len() != RANGE_BITS {
+            return false;
+        }
+
+        let mut product = BigUint::from(1u32);
+        for (i, bit) in self.bits.iter().enumerate() {
+            if !bit.verify(params, context, &commitment.commitment, i) {
+                return false;
+            }
+            let weight = BigUint::from(1u32) << i;
+            product = (product * bit.commitment.modpow(&weight, &params.p)) % &params.p;
+        }
+
+        product == commitment.commitment
+    }
+}
+
+impl BitProof {
+    fn prove<R: RngCore + CryptoRng>(
+        params: &PedersenParams,
+        context: &[u8],
+        commitment: &BigUint,
+        index: usize,
+        bit: bool,
+        blinding: &BigUint,
+        rng: &mut R,
+    ) -> Self {
+        let (p, q) = (&params.p, &params.q);
+        let bit_commitment = if bit {
+            (&params.g * params.h.modpow(blinding, p)) % p
+        } else {
+            params.h.modpow(blinding, p)
+        };
+        let targets = bit_targets(params, &bit_commitment);
+        let (real, simulated) = if bit { (1, 0) } else { (0, 1) };
+
+        // Simulate the false branch: a = h^z · Y^-c
+        let mut challenges = [BigUint::from(0u32), BigUint::from(0u32)];
+        let mut responses = [BigUint::from(0u32), BigUint::from(0u32)];
+        let mut nonces = [BigUint::from(0u32), BigUint::from(0u32)];
+        challenges[simulated] = PedersenCommitment::generate_randomness(rng, q);
+        responses[simulated] = PedersenCommitment::generate_randomness(rng, q);
+        nonces[simulated] = branch_nonce(
+            params,
+            &targets[simulated],
+            &challenges[simulated],
+            &responses[simulated],
+        );
+
+        // Commit honestly on the true branch
+        let k = PedersenCommitment::generate_randomness(rng, q);
+        nonces[real] = params.h.modpow(&k, p);
+
+        let challenge = bit_challenge(params, context, commitment, index, &bit_commitment, &nonces);
+        challenges[real] = (challenge + q - &challenges[simulated]) % q;
+        responses[real] = (k + &challenges[real] * blinding) % q;
+
+        let [challenge_zero, challenge_one] = challenges;
+        let [response_zero, response_one] = responses;
+        Self {
+            commitment: bit_commitment,
+            challenge_zero,
+            challenge_one,
+            response_zero,
+            response_one,
+        }
+    }
+
+    fn verify(
+        &self,
+        params: &PedersenParams,
+        context: &[u8],
+        commitment: &BigUint,
+        index: usize,
+    ) -> bool {
+        let (p, q) = (&params.p, &params.q);
+        let one = BigUint::from(1u32);
+
+        // The bit commitment must lie in the order-q subgroup
+        if self.commitment <= one || self.commitment >= *p || self.commitment.modpow(q, p) != one {
+            return false;
+        }
+        if [
+            &self.challenge_zero,
+            &self.challenge_one,
+            &self.response_zero,
+            &self.response_one,
+        ]
+        .iter()
+        .any(|scalar| **scalar >= *q)
+        {
+            return false;
+        }
+
+        let targets = bit_targets(params, &self.commitment);
+        let nonces = [
+            branch_nonce(
+                params,
+                &targets[0],
+                &self.challenge_zero,
+                &self.response_zero,
+            ),
+            branch_nonce(params, &targets[1], &self.challenge_one, &self.response_one),
+        ];
+        let challenge = bit_challenge(
+            params,
+            context,
+            commitment,
+            index,
+            &self.commitment,
+            &nonces,
+        );
+
+        (&self.challenge_zero + &self.challenge_one) % q == challenge
+    }
+}
+
+/// Statements `C` and `C / g` of the two branches of a bit proof
+fn bit_targets(params: &PedersenParams, bit_commitment: &BigUint) -> [BigUint; 2] {
+    let g_inverse = params
+        .g
+        .modpow(&(&params.q - BigUint::from(1u32)), &params.p);
+    [
+        bit_commitment.clone(),
+        (bit_commitment * g_inverse) % &params.p,
+    ]
+}
+
+/// Nonce `h^z · Y^-c` a branch's challenge and response imply
+fn branch_nonce(
+    params: &PedersenParams,
+    target: &BigUint,
+    challenge: &BigUint,
+    response: &BigUint,
+) -> BigUint {
+    let (p, q) = (&params.p, &params.q);
+    let inverse_power = target.modpow(&((q - challenge % q) % q), p);
+    (params.h.modpow(response, p) * inverse_power) % p
+}
+
+/// Fiat-Shamir challenge binding a bit proof to its commitment and position
+fn bit_challenge(
+    params: &PedersenParams,
+    context: &[u8],
+    commitment: &BigUint,
+    index: usize,
+    bit_commitment: &BigUint,
+    nonces: &[BigUint; 2],
+) -> BigUint {
+    let mut hasher = Sha3_512::new();
+    sha3::Digest::update(&mut hasher, DOMAIN_RANGE_PROOF);
+    sha3::Digest::update(&mut hasher, (context.len() as u64).to_le_bytes());
+    sha3::Digest::update(&mut hasher, context);
+    sha3::Digest::update(&mut hasher, commitment.to_bytes_be());
+    sha3::Digest::update(&mut hasher, (index as u32).to_le_bytes());
+    sha3::Digest::update(&mut hasher, bit_commitment.to_bytes_be());
+    for nonce in nonces {
+        sha3::Digest::update(&mut hasher, nonce.to_bytes_be());
+    }
+
+    BigUint::from_bytes_be(&hasher.finalize()) % &params.q
+}
+
+/// Homomorphically sum committed balances into a commitment to their total
+///
+/// The product of the commitments commits to the sum of the amounts under
+/// the sum of the blinding factors, so the total can be checked with the
+/// aggregate blinding factor alone, without opening any single balance.
+/// Balances must be committed with `PedersenParams::balance_params` at one
+/// security level.
+pub fn homomorphic_total_supply(
+    committed_balances: &[PedersenCommitment],
+) -> DsmResult<PedersenCommitment> {
+    let (first, rest) = committed_balances
+        .split_first()
+        .ok_or_else(|| DsmError::invalid_parameter("No committed balances to sum"))?;
+    let params = PedersenParams::balance_params(first.security_level);
+
+    rest.iter().try_fold(first.clone(), |total, balance| {
+        total.combine(balance, params)
+    })
+}
+
 /// External verification function for commitments
 pub fn verify(commitment: &PedersenCommitment) -> DsmResult<bool> {
     // For verification, we only need the commitment
@@ -270,51 +861,185 @@ pub fn verify(commitment: &PedersenCommitment) -> DsmResult<bool> {
     ))
 }
 
-/// Generate Pedersen parameters
-#[allow(clippy::many_single_char_names)]
-fn generate_pedersen_params(_p_bits: usize, q_bits: usize) -> (BigUint, BigUint, BigUint, BigUint) {
-    // Generate safe prime p = 2q + 1 where q is also prime
-    let (p, q) = loop {
-        let q = BigUint::from_bytes_be(&Generator::new_prime(q_bits).to_bytes_be());
-        let p = &q * BigUint::from(2u32) + BigUint::from(1u32);
-
-        let p_check = num_primes::BigUint::from_str(&p.to_string()).unwrap();
-        if num_primes::Verification::is_prime(&p_check) {
-            break (p, q);
-        }
+// Precomputed groups, one per security level
+//
+// Each is a prime `p` with a prime `q` dividing `p - 1`, of the sizes in
+// `param_bits`. They were generated once, offline, from public seeds so they
+// carry no chosen structure: `q` is the first prime at or above
+// SHAKE256("DSM.v1.pedersen.group" || label || "q") read as a big-endian
+// integer of `q_bits` bits with the top bit set, and `p` is the first prime of
+// the form `2kq + 1` at or above SHAKE256("DSM.v1.pedersen.group" || label ||
+// "p") read the same way at `p_bits` bits. The labels are "standard-128",
+// "medium-192" and "high-256". The tests check that both numbers are prime and
+// that `q` divides `p - 1`.
+
+/// 3072-bit modulus of the standard-128 group
+const STANDARD_128_P: &str = concat!(
+    "9405ac5f0ed2c5886b8d77057c0e2854119f9696122b267f3e9501aa6980f9b85e70fe1af8d3955e",
+    "609eab62d94a14df002b045afe7c5e582384d50267af9880be9fc115a3c51f82879e5a93334acd13",
+    "7f22efaa8629c2ddb7a4dc28cf12dd979d6c9612dde8ff04938c7e9a315a8022e0792aaa6793cb66",
+    "f5346a0ba72fb06eeeb1464fd9cd62e65c7dfc61f1b4268580c6ead88a7746210549dad1f07489a6",
+    "f1ae3c2b6f43b0c375106deccd80501a134784d5fd9d7c196560b33954f52eec02936071cc8da697",
+    "251304bc8b55ef89760cde51f4542947e4912bdc6cc0c4b2512a419c18a32e9d240d85a213a46dec",
+    "b78cdc41324849ad623498ef9afbb9b226c1341d26a9e5b36eba48e98c92eb024ab3b70785d9edc4",
+    "a42b66c7eab70d58561399f3e0ee4afb4af37c23ac30dd9370b7ae56a1cab2d59aaf16e25ded5237",
+    "6c98c38cbc05b80556f7eeb2ec9b23f4116d3719d2465c7adeaa304bd27e821eba5558135487d348",
+    "2893bfdac15426f652088386f18bd4162355936249a2a4c7",
+);
+
+/// 256-bit subgroup order of the standard-128 group
+const STANDARD_128_Q: &str = "f56283169c1b42fc8bcea4a5b24b92a6d3baea0a41c4b5df0b5d94d846aeda7d";
+
+/// 7680-bit modulus of the medium-192 group
+const MEDIUM_192_P: &str = concat!(
+    "a3c46a6a07e5d67ecbaf8c39b58cf3d00b55442d401192e33d2aadcbf47c127f185e3bab1b67206d",
+    "ecaa75a79f5d81d3fb29b5b257d09b99026e130c38e11c5b722c40b1187691cc96b6a3c3eba5f15e",
+    "6f9bbdf107258219272b54ebea0756458b0f1add26f404620828cb55674bf103d8dabec5c209b1cf",
+    "0f7dfa76dadfd14a083309b33f48d56febedeefbf29e7f7486e510de45e2d3dc9b0d034c3d9df19b",
+    "a45b9c496bb2f3394c8639e9a3df3b871096de4e77e5eb53d1b139fe5196e957285613829f0c7cd2",
+    "0b5864c1ed0bd211ec7da3c00cc862012578f529a1cf1aac225bb4cdd056d49dadc12715a3cb0682",
+    "0c1c85b7fa20849012eaebcac18790bc48b0ebdd2bfb130e2f3a71f3cfdffc8ca3e5ecf44a259d7e",
+    "f40de7b198d0af570b0d3f46894c113bd3db6800fa8ffae89dff9789ffe9346ea53f701ae20e927f",
+    "227e22815d4b3ddf70be2a280c611180bce07e3d4be8ad4985c3902ec81418c1a8e9ed57ca1d9e7c",
+    "c60aaa126ea3f58a288e63b26ac62c8ae5d6762a880e313bd3ec3f3454cf0dc8cb607ce959310a65",
+    "67e7f88ad86ac1c022872809d263c483b7e0b29c67e89aa11dc9830578a7e80250775544486056d0",
+    "59deb0bd6fd9cc04791ea41f0159d09b085fbd4f74d11e04d8621e30fcd7e0393e81ecee02644774",
+    "67e8ac33c2c3a6314e99ed9028256ee267ab6eec88ba0179e00cdfe28f98a59b4f71cce973b3f75d",
+    "aab99c4bd48d210aee9194e708c5eb571873081c48d16b77f6a03136b72b43a4e468be1fea334cda",
+    "2cbfb878658fb7e94b17dc1a34b8a4eec88cb063b2b85de802f549738e9c4efe64f99b6908544c5c",
+    "4ae8264cfa1c3e3770e964261ef147c495f21318266107dfdb3c2c8189037aa16290e9677f386979",
+    "496173bf30e5809f2178ebb0d6e459706f0546c1ff23e57bc88152f84f1254709c772bafe7d2dd6c",
+    "9dc769a74f0b467c11f64bf64df0ba68a6c96149fb68c8d69cfab7c50eee1e6ffad3ee3415d4c3f1",
+    "12c280a6fd23d7721fbabb5d9c1fbe557eb38c03f6d9eff0f32684e04972c6dda5d214d0e10e81e2",
+    "0c06160c8fbd4512a7ff8a9909da1967ee1b1c43714e5a32d6755c4981b7509353ed1073699aa5c6",
+    "9831088e2d2ac0ef55a482b8e7d3863026aab9851bd05ab0191d9fe0b6b145baad42515878af6d8b",
+    "f65fc8bb95422625adce8c7cbce0f4b941d55ca94bae06b51543011458fabd4424cb565b03a7ce79",
+    "1e2f9dea91f1181ba12c23561b0aaa65925ecb62bbe8a490d5dfbc2c5bcd1f43d9ef6c838755cf86",
+    "3b333417724e70891b0291ebe3a4bbbe43b4da8bf2e99c645fc3408f2fae3c4e240131605750e0f5",
+);
+
+/// 384-bit subgroup order of the medium-192 group
+const MEDIUM_192_Q: &str = concat!(
+    "e7b53dbec2a02cd4bd0ff822bd102c475455ae7d8d796eeef65f6119cefd25ddd9e7b318cde57ac2",
+    "0211c879d3451f95",
+);
+
+/// 15360-bit modulus of the high-256 group
+const HIGH_256_P: &str = concat!(
+    "bd50d903d05c24f5fc3b2e0b0f4c31af7a0aa7496d96a8bfec43a424f572852413cdd57b9405dd76",
+    "0924f3490a8dcde75d4aef76eb392f50a9c5c0ab4bfc74b74b0d9359f16aeb4aed93d81d48886866",
+    "18f4da50ee9b8e58f680d64f8c58a1b9d581b28884e4e879450e0ce7c95092bcb93fd2ec2e91e248",
+    "facb468eb9bb5d4862f2ef9bf14ceaaffa97f259c208b965ad53c9346fb01bb74cf3ea0727cd49a8",
+    "23ebe95d2df7c667a8b757522e90de9000d1436a25efde5e9e2934e9abc4ff6902972c51ed88d70d",
+    "18903982c9fec37d0ec48740339ab824431fa5343e269efbc4de49ba64b715ffcbfde7f7570172d4",
+    "5ff732c3622487c305410114b4e02caa23526225a38bb6baef0f676dc21c7fe9b530f360b65a758b",
+    "9fc690c698372abe9421090f1e3e2625466b6d13f5788a45faf3e04cc649d31d9a91ea70cf0a4ed0",
+    "bcd96219871dbaf09a4bb9c89b72f97d723d118d02554a6022b98e7df05dbba646591c11a7d0b04e",
+    "e204be47d59d8de8699f791f7c5aaa2e9c4b0a21df721dc62f062723bbcfc3f3da022fab12e7b9cc",
+    "1415754c542390394e6a8b9afbffe68ba3f801a687347c9774f91257965fd3cdafb6db8cf1a01d19",
+    "7009e098680ca99870379125ed32806d1b9c3cc53c290b5821bbf051285b7e5d91d366aa3f17648c",
+    "f4797386ec5b52fa0bfbfd9a71e01baa7d69933c599a143fffb7a49e6c1193dfcd6642fa7f604b7b",
+    "770ae9aff1baa645ba8a6fd037fe647d8aedd84b20deeb0242e043abb92aa2c2f7a434b10de9be15",
+    "6c3344ec65c61719395fd449e950ab60e50963969d38dd6a68e0f54619509c1a95b6af60b056f64a",
+    "ad11cca58f68896efa845eacfd62f541967e4f65c9668edb0aca3e78fdf5d1dc981e0e02dcc3ae7e",
+    "1f5f465fe4a394aab63f56dfb3cc732eb91263dcfadbde436d214c8f3dfa742608fb4f8d0591acfb",
+    "ac8fc9f60ba61c50cf6be3ce9d45c5b161bb21ab017bb44561ea4dc3c8d9e7112cb9a7dd3c7165f3",
+    "ad991b2f634db57c5e7c433fbc85ece0a0b9c2147a20ed07c3221be8aff14efd27a31984af956791",
+    "84b5c19245f8c4cb9ba04330addf3cc7da2af99c2bcb6dc5ac90f9e2f86cf6dfd6296a9c6b31f897",
+    "b2f34e3936281866e5cc6b3679764f2a2a461ae447370a95d05eee401ed9301cd16d6a9a4386453f",
+    "3d832f55deb5a4451cfdc6ca29114e159d19bd46dba5c2c56e3fefbc5855ad366a903a14a58e3a36",
+    "5971792990604565f81c0d39b580dd418665b91818369892f7e378e826892e3319233b8f96e05357",
+    "b082fcf56ac7836ab47de469b80c1af7c7e4a0acd16bf68e94c54c8861addd1d67e66daf5c499110",
+    "8fc2997151656eb944f4c01711c7df17e27cda89661f9ffce4051ec58809b3357695013014e350e4",
+    "5e329a12198724fcf7c7f77752597d73e1c45525868ede19529067e13b7a87f90638c0023ac55180",
+    "76b85ba5a84aff72d803e5e003871120afac35c52ca9486731b608dd536a670edf260bb60f370069",
+    "05518f35372c5ba539871117bb611d466a9b71d7178dd7657b95679455441b072353d3e8885eb0be",
+    "6dfebd659a548a0747b2e4a63f402dc25628ca4b63fc63898d0fe0ae761cf729e8a276ce8d11c8ac",
+    "c41156cd38b5deaf8ccaed0daaf4591be5fcae192acf5f4332463658943072d87bee7b0302364576",
+    "641e7e4754418d122c7de8d8a0c26fa28a09038e501e9f3b11c6606ddf83ea97694d52c578831027",
+    "9b60773618c7faca30190c5e375ffad06c8e4a2ba899be47c08e21ded7dcd14adae5ea81705417e5",
+    "1087d356661d5479db7e4454c8decacaa4821081f0b2d645437facd2a9e9160fbe84a8da4a7d2d7c",
+    "8e2178794347e0a9a427c33be9ce6f17cb9bb7f032ab46836aaac0be6613591700968fb0431c304a",
+    "77c6e9cc2966f9feda07dc427cd66ae819ec110fbbac472714c7ef31214f915f9b2a87baab3f6760",
+    "de39c235bd6d6d8e8fc8b9f15067b42896066a045ddf998e1777f144633dfcca2d79cd02424c2923",
+    "9abd4349e8dbe0c4931352e2811c5820634a56e97fa47992dae6474697b0709f482ba18855e8c698",
+    "d5fd5d8d825c9d8e817d46faf755234213813089276c90dd702a5886fd5c21d9c8b53381ce993513",
+    "a1dd97814ceaca3e3754cf4ac146659ac088db5edace26cd245e1d56633fc10c2dee77ec1024191d",
+    "d6123b98e9c102948c1dabd5ada785a2356f7cba9cb4a87d1cef311d30987bc6d85b17318cc5c4db",
+    "f1351f1107fd2bbc324fa1bee5c452a09d71ddc01ab54ad7b40cdfc22fc46f5597402359f26fe9fd",
+    "eb26dafb9120a0f6767db88d3eaca7b6dd00ebdc99f0d7c47b9219035b87bb950339437382212133",
+    "a658e60b2538396d5ca763bb47fffae0dac040230e20954c13280863022d22ad5b16ca2faa9d2fd3",
+    "4676b1477d1284f8e0efd1b23499db8421675be4df7d61e67c2fc31d5f518bdbd319d53d36c48525",
+    "f63e9b59cd79351fd9ebb01efa4f72750147d6fb790125a76b0cd5a685fbd949f7f50999fc430d9e",
+    "478362ad053c875e04ee6c5c2a9b215f300e07c285414609d3bf20a68695b808ea1282fa5f85283e",
+    "11869e902ce54496b2e92d1683259bed11186489b8de5afc7cde1df4aa9c5d0d3cf43dba232710d8",
+    "06b1e075aa542eb81e657621071d89ca39c0d3a1338b3ee8e207d3d0387fe3b08340dd67ba1a4f2f",
+);
+
+/// 512-bit subgroup order of the high-256 group
+const HIGH_256_Q: &str = concat!(
+    "b243867cfbcb3d47a5f1a653584cbcfbf06103b1a99d7cf5cb61b7d843aba14d295239d8d6b10265",
+    "347baf98b631631bda2ee5127263e40804d5eef4ab529e0b",
+);
+
+/// Modulus `p` and subgroup order `q` of the group of a security level
+fn group(security_level: SecurityLevel) -> (BigUint, BigUint) {
+    let (p, q) = match security_level {
+        SecurityLevel::Standard128 => (STANDARD_128_P, STANDARD_128_Q),
+        SecurityLevel::Medium192 => (MEDIUM_192_P, MEDIUM_192_Q),
+        SecurityLevel::High256 => (HIGH_256_P, HIGH_256_Q),
     };
 
+    let parse =
+        |hex: &str| BigUint::parse_bytes(hex.as_bytes(), 16).expect("group constant is hex");
+    (parse(p), parse(q))
+}
+
+/// Random generators `g` and `h = g^x` of the order-q subgroup modulo `p`
+fn random_generators(p: &BigUint, q: &BigUint) -> (BigUint, BigUint) {
     let mut rng = rand::thread_rng();
+    let one = BigUint::from(1u32);
+    let cofactor = (p - &one) / q;
 
-    // Find generator g of order q in Z*_p
+    // Raising a random element to the cofactor lands in the subgroup
+    let mut buf = vec![0u8; (p.bits() as usize).div_ceil(8)];
     let g = loop {
-        // Generate random value between 2 and p-1
-        let two = BigUint::from(2u32);
-        let p_minus_1 = &p - BigUint::from(1u32);
-
-        // Generate random bytes
-        let bytes_needed = p.bits().div_ceil(8);
-        let mut buf = vec![0u8; bytes_needed.try_into().unwrap()];
         rng.fill_bytes(&mut buf);
-
-        // Convert to BigUint and ensure in range [2, p-1]
-        let mut candidate = BigUint::from_bytes_be(&buf);
-        candidate = &two + (&candidate % &(&p_minus_1 - &two));
-
-        let g = candidate.modpow(&BigUint::from(2u32), &p);
-
-        if g.modpow(&q, &p) == BigUint::from(1u32) {
-            break g;
+        let candidate = (BigUint::from_bytes_be(&buf) % p).modpow(&cofactor, p);
+        if candidate > one {
+            break candidate;
         }
     };
 
-    // Generate random h as h = g^x mod p for random x
-    let bytes_needed = q.bits().div_ceil(8);
-    let mut buf = vec![0u8; bytes_needed.try_into().unwrap()];
+    let mut buf = vec![0u8; (q.bits() as usize).div_ceil(8)];
     rng.fill_bytes(&mut buf);
-    let x = BigUint::from_bytes_be(&buf) % &q;
-    let h = g.modpow(&x, &p);
-    (p, q, g, h)
+    let x = BigUint::from_bytes_be(&buf) % q;
+    let h = g.modpow(&x, p);
+    (g, h)
+}
+
+/// Hash into the order-q subgroup modulo `p` under a per-use domain
+fn hash_to_subgroup(domain: &[u8], label: &[u8], p: &BigUint, q: &BigUint) -> BigUint {
+    let one = BigUint::from(1u32);
+    let cofactor = (p - &one) / q;
+    let mut counter = 0u32;
+
+    loop {
+        let mut shake = sha3::Shake256::default();
+        shake.update(domain);
+        shake.update(label);
+        shake.update(&counter.to_le_bytes());
+
+        // Oversample so the reduction mod p is close to uniform
+        let mut output = vec![0u8; (p.bits() as usize).div_ceil(8) + 16];
+        shake.finalize_xof().read(&mut output);
+
+        let candidate = (BigUint::from_bytes_be(&output) % p).modpow(&cofactor, p);
+        if candidate > one {
+            return candidate;
+        }
+        counter += 1;
+    }
 }
 
 /// Hash a commitment for quantum resistance using hash sandwich technique
@@ -391,11 +1116,12 @@ fn test_commitment_flow() {
         let params = PedersenParams::new(SecurityLevel::Standard128);
 
         let value = b"test value";
-        let (commit, _r) = PedersenCommitment::commit(&params, value, &mut rng).unwrap();
+        let (commit, r) = PedersenCommitment::commit(&params, value, &mut rng).unwrap();
 
-        // Add verify method implementation or replace this test
-        // assert!(commit.verify(value, &r, &params).unwrap());
+        assert!(commit.verify(value, &r, &params).unwrap());
         assert!(!commit.commitment_hash.is_empty());
+        assert!(!commit.is_empty());
+        assert!(PedersenCommitment::default().is_empty());
     }
     #[test]
     fn test_homomorphic_combination() {
@@ -422,6 +1148,245 @@ fn test_verify_commitment() {
         assert!(!commit.verify(b"wrong value", &r, &params).unwrap());
     }
 
+    #[test]
+    fn test_verify_with_known_blinding() {
+        let params = PedersenParams::vault_content_params();
+        let blinding = BigUint::from(0x1234_5678u32);
+
+        let commit =
+            PedersenCommitment::commit_with_blinding(params, b"content", &blinding).unwrap();
+        assert!(commit.verify(b"content", &blinding, params).unwrap());
+        assert!(!commit.verify(b"c0ntent", &blinding, params).unwrap());
+        assert!(!commit
+            .verify(b"content", &(blinding + 1u32), params)
+            .unwrap());
+        assert!(!PedersenCommitment::default()
+            .verify(b"content", &BigUint::from(0u32), params)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_homomorphic_amounts_sum() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::balance_params(SecurityLevel::Standard128);
+
+        let (c100, r100) = PedersenCommitment::commit_amount(params, 100, &mut rng).unwrap();
+        let (c200, r200) = PedersenCommitment::commit_amount(params, 200, &mut rng).unwrap();
+
+        let sum = c100.combine(&c200, params).unwrap();
+        let combined_blinding = (&r100 + &r200) % &params.q;
+
+        assert!(sum.verify_amount(300, &combined_blinding, params).unwrap());
+        assert_eq!(
+            sum,
+            PedersenCommitment::commit_amount_with_blinding(params, 300, &combined_blinding)
+                .unwrap()
+        );
+        assert!(!sum.verify_amount(299, &combined_blinding, params).unwrap());
+    }
+
+    #[test]
+    fn test_total_supply_sums_committed_balances() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::balance_params(SecurityLevel::Standard128);
+
+        let (balances, blindings): (Vec<_>, Vec<_>) = [100u64, 250, 650]
+            .iter()
+            .map(|amount| PedersenCommitment::commit_amount(params, *amount, &mut rng).unwrap())
+            .unzip();
+        let audit_blinding = blindings.iter().sum::<BigUint>() % &params.q;
+
+        let total = homomorphic_total_supply(&balances).unwrap();
+        assert_eq!(
+            total,
+            PedersenCommitment::commit_amount_with_blinding(params, 1_000, &audit_blinding)
+                .unwrap()
+        );
+        assert!(total.verify_amount(1_000, &audit_blinding, params).unwrap());
+        assert!(!total.verify_amount(999, &audit_blinding, params).unwrap());
+        assert!(!total.verify_amount(1_000, &blindings[0], params).unwrap());
+
+        assert!(homomorphic_total_supply(&[]).is_err());
+        let mut mixed = balances.clone();
+        mixed[1].security_level = SecurityLevel::Medium192;
+        assert!(homomorphic_total_supply(&mixed).is_err());
+    }
+
+    #[test]
+    fn test_balance_params_are_deterministic() {
+        let a = PedersenParams::deterministic(SecurityLevel::Standard128, b"seed");
+        let b = PedersenParams::deterministic(SecurityLevel::Standard128, b"seed");
+
+        assert_eq!(a.p, b.p);
+        assert_eq!(a.g, b.g);
+        assert_eq!(a.h, b.h);
+        assert_eq!(a.g.modpow(&a.q, &a.p), BigUint::from(1u32));
+        assert_eq!(a.h.modpow(&a.q, &a.p), BigUint::from(1u32));
+    }
+
+    #[test]
+    fn test_transfer_proof() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::balance_params(SecurityLevel::Standard128);
+
+        let (balance, rb) = PedersenCommitment::commit_amount(params, 500, &mut rng).unwrap();
+        let (remaining, rr) = PedersenCommitment::commit_amount(params, 380, &mut rng).unwrap();
+        let (sent, rs) = PedersenCommitment::commit_amount(params, 120, &mut rng).unwrap();
+
+        let proof = TransferProof::prove(
+            params,
+            "ROOT",
+            (&balance, &rb),
+            (&remaining, 380, &rr),
+            (&sent, 120, &rs),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(proof.verify(params, &balance, &remaining, &sent).unwrap());
+
+        // Inflating the transferred amount breaks conservation
+        let (inflated, ri) = PedersenCommitment::commit_amount(params, 121, &mut rng).unwrap();
+        let forged = TransferProof::prove(
+            params,
+            "ROOT",
+            (&balance, &rb),
+            (&remaining, 380, &rr),
+            (&inflated, 121, &ri),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(!forged
+            .verify(params, &balance, &remaining, &inflated)
+            .unwrap());
+
+        // The proof is bound to its token
+        let mut other_token = proof.clone();
+        other_token.token_id = "OTHER".to_string();
+        assert!(!other_token
+            .verify(params, &balance, &remaining, &sent)
+            .unwrap());
+
+        // Swapping the range proofs breaks their binding to each role
+        let mut swapped = proof.clone();
+        std::mem::swap(&mut swapped.remaining_range, &mut swapped.transferred_range);
+        assert!(!swapped.verify_ranges(params, &remaining, &sent));
+    }
+
+    #[test]
+    fn test_transfer_proof_rejects_negative_amounts() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::balance_params(SecurityLevel::Standard128);
+        let q = &params.q;
+
+        // Sending 600 out of 500 leaves a remaining balance of -100 ≡ q - 100
+        let (balance, rb) = PedersenCommitment::commit_amount(params, 500, &mut rng).unwrap();
+        let (sent, rs) = PedersenCommitment::commit_amount(params, 600, &mut rng).unwrap();
+        let rr = PedersenCommitment::generate_randomness(&mut rng, q);
+        let negative = PedersenCommitment::commit_with_blinding(
+            params,
+            &(q - BigUint::from(100u32)).to_bytes_le(),
+            &rr,
+        )
+        .unwrap();
+        assert_eq!(
+            negative.combine(&sent, params).unwrap().commitment,
+            PedersenCommitment::commit_amount_with_blinding(params, 500, &((&rr + &rs) % q))
+                .unwrap()
+                .commitment
+        );
+
+        // The best a prover can do is a range proof over the truncated amount
+        let proof = TransferProof::prove(
+            params,
+            "ROOT",
+            (&balance, &rb),
+            (&negative, u64::MAX - 99, &rr),
+            (&sent, 600, &rs),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(!proof.verify(params, &balance, &negative, &sent).unwrap());
+        assert!(!proof.verify_ranges(params, &negative, &sent));
+    }
+
+    #[test]
+    fn test_range_proof() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::balance_params(SecurityLevel::Standard128);
+
+        for amount in [0, 1, 42, u64::MAX] {
+            let (commitment, r) =
+                PedersenCommitment::commit_amount(params, amount, &mut rng).unwrap();
+            let proof = RangeProof::prove(params, b"ctx", &commitment, amount, &r, &mut rng);
+            assert!(proof.verify(params, b"ctx", &commitment));
+            assert!(!proof.verify(params, b"other", &commitment));
+        }
+
+        // A proof for one amount does not verify against another commitment
+        let (commitment, r) = PedersenCommitment::commit_amount(params, 7, &mut rng).unwrap();
+        let (other, _) = PedersenCommitment::commit_amount(params, 7, &mut rng).unwrap();
+        let proof = RangeProof::prove(params, b"ctx", &commitment, 7, &r, &mut rng);
+        assert!(!proof.verify(params, b"ctx", &other));
+    }
+
+    /// Miller-Rabin with the first twelve primes as bases
+    fn is_probable_prime(n: &BigUint) -> bool {
+        let one = BigUint::from(1u32);
+        let n_minus_1 = n - &one;
+        let s = n_minus_1.trailing_zeros().unwrap();
+        let d = &n_minus_1 >> s;
+
+        let bases = [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+        bases.iter().all(|&base| {
+            let mut x = BigUint::from(base).modpow(&d, n);
+            if x == one || x == n_minus_1 {
+                return true;
+            }
+            for _ in 1..s {
+                x = x.modpow(&BigUint::from(2u32), n);
+                if x == n_minus_1 {
+                    return true;
+                }
+            }
+            false
+        })
+    }
+
+    fn assert_valid_group(security_level: SecurityLevel) {
+        let (p, q) = group(security_level);
+        let (p_bits, q_bits) = param_bits(security_level);
+
+        assert_eq!(p.bits(), p_bits as u64);
+        assert_eq!(q.bits(), q_bits as u64);
+        assert_eq!((&p - 1u32) % &q, BigUint::from(0u32));
+        assert!(is_probable_prime(&q));
+        assert!(is_probable_prime(&p));
+    }
+
+    #[test]
+    fn test_standard_group_is_valid() {
+        assert_valid_group(SecurityLevel::Standard128);
+    }
+
+    #[test]
+    #[ignore] // Slow in debug builds; run with --ignored
+    fn test_larger_groups_are_valid() {
+        assert_valid_group(SecurityLevel::Medium192);
+        assert_valid_group(SecurityLevel::High256);
+    }
+
+    #[test]
+    fn test_param_domains_are_separate() {
+        let balance = PedersenParams::balance_params(SecurityLevel::Standard128);
+        let vault = PedersenParams::vault_content_params();
+
+        let (p_bits, q_bits) = param_bits(SecurityLevel::Standard128);
+        assert_eq!(balance.p.bits(), p_bits as u64);
+        assert_eq!(balance.q.bits(), q_bits as u64);
+        assert_ne!(balance.g, vault.g);
+        assert_ne!(balance.h, vault.h);
+    }
+
     #[test]
     fn test_smart_commitment() {
         let mut rng = thread_rng();
@@ -0,0 +1,284 @@
+//! # Ring Signatures
+//!
+//! Proofs that a message was signed by one member of a set of public keys,
+//! without revealing which member. A unilateral transaction carrying a ring
+//! signature shows that its sender belongs to a group the recipient knows,
+//! while the sender's genesis hash stays hidden.
+//!
+//! The construction is a ring of Schnorr signatures over Ristretto255. Each
+//! member `i` with public key `P_i` contributes the Pedersen commitment
+//! `R_i = s_i·G + c_i·P_i` to its response `s_i` and challenge `c_i`, and the
+//! next challenge is `c_{i+1} = H(L, m, R_i)` over the ring `L` and message
+//! `m`. The signer closes the ring with its secret key, so the challenges
+//! only chain back to `c_0` if some member signed. With a single key the
+//! ring is an ordinary Schnorr signature.
+
+use crate::types::error::DsmError;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+const DOMAIN_RING: &[u8] = b"DSM.v1.ring.members";
+const DOMAIN_CHALLENGE: &[u8] = b"DSM.v1.ring.challenge";
+
+/// Proof that one member of a ring of public keys signed a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingSignature {
+    /// Challenge entering the first member of the ring
+    pub challenge: [u8; 32],
+
+    /// One response per ring member, in ring order
+    pub responses: Vec<[u8; 32]>,
+}
+
+/// Generate a keypair for signing as a ring member
+///
+/// # Returns
+///
+/// The 32-byte secret key and the 32-byte public key to place in rings
+pub fn generate_ring_keypair() -> (Vec<u8>, Vec<u8>) {
+    let secret = Scalar::random(&mut OsRng);
+    let public = (secret * RISTRETTO_BASEPOINT_POINT).compress();
+    (secret.to_bytes().to_vec(), public.to_bytes().to_vec())
+}
+
+/// Sign a message as an unidentified member of a ring
+///
+/// # Arguments
+///
+/// * `message` - Message to sign
+/// * `signer_sk` - The signer's ring secret key
+/// * `ring_public_keys` - Public keys of the ring, including the signer's
+///
+/// # Returns
+///
+/// The ring signature, `InvalidSecretKey` or `InvalidPublicKey` for malformed
+/// keys, or an invalid parameter error if the signer is not in the ring
+pub fn generate_ring_signature(
+    message: &[u8],
+    signer_sk: &[u8],
+    ring_public_keys: &[Vec<u8>],
+) -> Result<RingSignature, DsmError> {
+    let secret = decode_scalar(signer_sk).ok_or(DsmError::InvalidSecretKey)?;
+    let ring = decode_ring(ring_public_keys).ok_or(DsmError::InvalidPublicKey)?;
+
+    let signer_public = secret * RISTRETTO_BASEPOINT_POINT;
+    let signer = ring
+        .iter()
+        .position(|key| *key == signer_public)
+        .ok_or_else(|| DsmError::invalid_parameter("Signer's public key is not in the ring"))?;
+
+    let ring_digest = ring_digest(ring_public_keys);
+    let size = ring.len();
+    let mut challenges = vec![Scalar::ZERO; size];
+    let mut responses = vec![Scalar::ZERO; size];
+
+    // Start the ring after the signer, simulate every other member, and close
+    // it at the signer with the secret key
+    let nonce = Scalar::random(&mut OsRng);
+    challenges[(signer + 1) % size] =
+        challenge(&ring_digest, message, &(nonce * RISTRETTO_BASEPOINT_POINT));
+    for offset in 1..size {
+        let member = (signer + offset) % size;
+        responses[member] = Scalar::random(&mut OsRng);
+        let commitment =
+            responses[member] * RISTRETTO_BASEPOINT_POINT + challenges[member] * ring[member];
+        challenges[(member + 1) % size] = challenge(&ring_digest, message, &commitment);
+    }
+    responses[signer] = nonce - challenges[signer] * secret;
+
+    Ok(RingSignature {
+        challenge: challenges[0].to_bytes(),
+        responses: responses.iter().map(Scalar::to_bytes).collect(),
+    })
+}
+
+/// Whether a ring signature over a message was made by a member of the ring
+///
+/// Malformed keys or signatures, and rings in a different order than signed,
+/// fail verification.
+pub fn verify_ring_signature(
+    message: &[u8],
+    ring_public_keys: &[Vec<u8>],
+    sig: &RingSignature,
+) -> bool {
+    let Some(ring) = decode_ring(ring_public_keys) else {
+        return false;
+    };
+    if sig.responses.len() != ring.len() {
+        return false;
+    }
+    let Some(first_challenge) = decode_scalar(&sig.challenge) else {
+        return false;
+    };
+
+    let ring_digest = ring_digest(ring_public_keys);
+    let mut next_challenge = first_challenge;
+    for (key, response) in ring.iter().zip(&sig.responses) {
+        let Some(response) = decode_scalar(response) else {
+            return false;
+        };
+        let commitment = response * RISTRETTO_BASEPOINT_POINT + next_challenge * key;
+        next_challenge = challenge(&ring_digest, message, &commitment);
+    }
+
+    next_challenge == first_challenge
+}
+
+/// Hash binding a signature to the ring's members and their order
+fn ring_digest(ring_public_keys: &[Vec<u8>]) -> [u8; 32] {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(DOMAIN_RING);
+    hasher.update(&(ring_public_keys.len() as u64).to_le_bytes());
+    for key in ring_public_keys {
+        hasher.update(key);
+    }
+    *hasher.finalize().as_bytes()
+}
+
+/// Challenge `H(L, m, R)` following a member's commitment
+fn challenge(ring_digest: &[u8; 32], message: &[u8], commitment: &RistrettoPoint) -> Scalar {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(DOMAIN_CHALLENGE);
+    hasher.update(ring_digest);
+    hasher.update(commitment.compress().as_bytes());
+    hasher.update(message);
+    let mut wide = [0u8; 64];
+    hasher.finalize_xof().fill(&mut wide);
+    let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+    scalar
+}
+
+/// Decode every key of a non-empty ring
+fn decode_ring(ring_public_keys: &[Vec<u8>]) -> Option<Vec<RistrettoPoint>> {
+    if ring_public_keys.is_empty() {
+        return None;
+    }
+    ring_public_keys
+        .iter()
+        .map(|key| decode_point(key))
+        .collect()
+}
+
+fn decode_point(bytes: &[u8]) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(bytes).ok()?.decompress()
+}
+
+fn decode_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    Option::from(Scalar::from_canonical_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring_of(size: usize) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        (0..size).map(|_| generate_ring_keypair()).unzip()
+    }
+
+    #[test]
+    fn test_any_member_signs_for_the_ring() {
+        let (secret_keys, ring) = ring_of(4);
+        let message = b"unilateral transfer";
+
+        for secret_key in &secret_keys {
+            let signature = generate_ring_signature(message, secret_key, &ring).unwrap();
+            assert_eq!(signature.responses.len(), 4);
+            assert!(verify_ring_signature(message, &ring, &signature));
+            assert!(!verify_ring_signature(
+                b"another transfer",
+                &ring,
+                &signature
+            ));
+        }
+
+        // The signature is bound to the ring as signed
+        let signature = generate_ring_signature(message, &secret_keys[1], &ring).unwrap();
+        let mut reordered = ring.clone();
+        reordered.swap(0, 3);
+        assert!(!verify_ring_signature(message, &reordered, &signature));
+        assert!(!verify_ring_signature(message, &ring[..3], &signature));
+    }
+
+    #[test]
+    fn test_signer_outside_ring_cannot_sign() {
+        let (_, ring) = ring_of(3);
+        let (outsider_secret, outsider_public) = generate_ring_keypair();
+
+        assert!(generate_ring_signature(b"message", &outsider_secret, &ring).is_err());
+
+        // Signing for a ring with the outsider in it does not cover the original ring
+        let mut with_outsider = ring.clone();
+        with_outsider[2] = outsider_public;
+        let signature =
+            generate_ring_signature(b"message", &outsider_secret, &with_outsider).unwrap();
+        assert!(verify_ring_signature(
+            b"message",
+            &with_outsider,
+            &signature
+        ));
+        assert!(!verify_ring_signature(b"message", &ring, &signature));
+    }
+
+    #[test]
+    fn test_forged_signatures_fail() {
+        let (secret_keys, ring) = ring_of(3);
+        let signature = generate_ring_signature(b"message", &secret_keys[0], &ring).unwrap();
+
+        let mut tampered = signature.clone();
+        tampered.responses[1] = Scalar::random(&mut OsRng).to_bytes();
+        assert!(!verify_ring_signature(b"message", &ring, &tampered));
+
+        let mut tampered = signature.clone();
+        tampered.challenge = Scalar::random(&mut OsRng).to_bytes();
+        assert!(!verify_ring_signature(b"message", &ring, &tampered));
+
+        let mut truncated = signature.clone();
+        truncated.responses.pop();
+        assert!(!verify_ring_signature(b"message", &ring, &truncated));
+
+        let forged = RingSignature {
+            challenge: Scalar::random(&mut OsRng).to_bytes(),
+            responses: (0..3)
+                .map(|_| Scalar::random(&mut OsRng).to_bytes())
+                .collect(),
+        };
+        assert!(!verify_ring_signature(b"message", &ring, &forged));
+
+        // Non-canonical scalars and malformed rings are rejected
+        let mut non_canonical = signature;
+        non_canonical.responses[0] = [0xff; 32];
+        assert!(!verify_ring_signature(b"message", &ring, &non_canonical));
+        assert!(!verify_ring_signature(b"message", &[], &forged));
+        assert!(matches!(
+            generate_ring_signature(b"message", &secret_keys[0], &[vec![1, 2, 3]]),
+            Err(DsmError::InvalidPublicKey)
+        ));
+    }
+
+    #[test]
+    fn test_ring_of_one_is_a_schnorr_signature() {
+        let (secret_key, public_key) = generate_ring_keypair();
+        let ring = vec![public_key.clone()];
+        let signature = generate_ring_signature(b"message", &secret_key, &ring).unwrap();
+        assert!(verify_ring_signature(b"message", &ring, &signature));
+
+        // s·G + c·P commits to the nonce whose hash is c
+        let c = decode_scalar(&signature.challenge).unwrap();
+        let s = decode_scalar(&signature.responses[0]).unwrap();
+        let nonce_commitment =
+            s * RISTRETTO_BASEPOINT_POINT + c * decode_point(&public_key).unwrap();
+        assert_eq!(
+            challenge(&ring_digest(&ring), b"message", &nonce_commitment),
+            c
+        );
+
+        let (_, other) = generate_ring_keypair();
+        assert!(!verify_ring_signature(b"message", &[other], &signature));
+    }
+}
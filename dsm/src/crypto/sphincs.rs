@@ -492,6 +492,17 @@ pub fn sphincs_verify(pk: &[u8], message: &[u8], signature: &[u8]) -> Result<boo
     Ok((pk, sk))
 }
 
+/// Extract the public key a SPHINCS+ secret key embeds
+///
+/// The secret key ends with the public seed and root that make up the
+/// public key.
+pub fn public_key_from_secret_key(sk: &[u8]) -> Result<Vec<u8>, DsmError> {
+    if sk.len() != CRYPTO_SECRETKEYBYTES {
+        return Err(DsmError::InvalidSecretKey);
+    }
+    Ok(sk[2 * CRYPTO_N..].to_vec())
+}
+
 // Add these size-exposing functions explicitly
 pub fn public_key_bytes() -> usize {
     CRYPTO_PUBLICKEYBYTES
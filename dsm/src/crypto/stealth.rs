@@ -0,0 +1,358 @@
+//! # Stealth Addresses
+//!
+//! One-time recipient addresses for unilateral transactions. Addressing a
+//! transaction to the recipient's genesis hash reveals who is paying whom;
+//! a stealth address is instead derived from the recipient's stealth public
+//! key and a fresh ephemeral key, so only the recipient can recognise it.
+//!
+//! The construction is ECDH over Ristretto255. With the recipient's key
+//! `A = a·G` and the sender's ephemeral key `R = r·G`, both sides compute the
+//! shared secret `S = r·A = a·R`, and the address is `P = A + H(S)·G`. The
+//! recipient controls it through the one-time secret key `a + H(S)`.
+
+use crate::types::error::DsmError;
+use crate::unilateral::InboxEntry;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use zeroize::Zeroize;
+
+const DOMAIN_SHARED_SECRET: &[u8] = b"DSM.v1.stealth.shared-secret";
+
+/// Inbox entry metadata key carrying the hex-encoded ephemeral public key
+///
+/// The entry's recipient field holds the hex-encoded stealth address.
+pub const STEALTH_EPHEMERAL_KEY_METADATA: &str = "stealth_ephemeral_key";
+
+/// One-time address a stealth payment is sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StealthAddress(pub [u8; 32]);
+
+impl StealthAddress {
+    /// Hex encoding, used as the recipient of inbox entries
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Parse a hex-encoded address
+    pub fn from_hex(encoded: &str) -> Result<Self, DsmError> {
+        Ok(Self(
+            decode_key_bytes(encoded).ok_or(DsmError::InvalidPublicKey)?,
+        ))
+    }
+}
+
+/// Sender's ephemeral public key, published alongside a stealth address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EphemeralPublicKey(pub [u8; 32]);
+
+impl EphemeralPublicKey {
+    /// Hex encoding, used in inbox entry metadata
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Parse a hex-encoded ephemeral key
+    pub fn from_hex(encoded: &str) -> Result<Self, DsmError> {
+        Ok(Self(
+            decode_key_bytes(encoded).ok_or(DsmError::InvalidPublicKey)?,
+        ))
+    }
+}
+
+/// What the sender keeps from generating a stealth address
+///
+/// The ephemeral public key must be delivered with the payment; the shared
+/// secret is zeroed when dropped.
+#[derive(Clone)]
+pub struct StealthSecret {
+    ephemeral_public_key: EphemeralPublicKey,
+    shared_secret: [u8; 32],
+}
+
+impl StealthSecret {
+    /// Ephemeral public key the recipient needs to recognise the payment
+    pub fn ephemeral_public_key(&self) -> EphemeralPublicKey {
+        self.ephemeral_public_key
+    }
+
+    /// Secret shared with the recipient
+    pub fn shared_secret(&self) -> &[u8; 32] {
+        &self.shared_secret
+    }
+}
+
+impl std::fmt::Debug for StealthSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StealthSecret")
+            .field("ephemeral_public_key", &self.ephemeral_public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for StealthSecret {
+    fn drop(&mut self) {
+        self.shared_secret.zeroize();
+    }
+}
+
+/// Generate a stealth keypair for receiving payments
+///
+/// # Returns
+///
+/// The 32-byte secret key and the 32-byte public key to hand to senders
+pub fn generate_stealth_keypair() -> (Vec<u8>, Vec<u8>) {
+    let secret = Scalar::random(&mut OsRng);
+    let public = (secret * RISTRETTO_BASEPOINT_POINT).compress();
+    (secret.to_bytes().to_vec(), public.to_bytes().to_vec())
+}
+
+/// Derive a fresh one-time address for a recipient
+///
+/// # Arguments
+///
+/// * `recipient_public_key` - The recipient's stealth public key
+///
+/// # Returns
+///
+/// The address to pay and the secret the sender keeps, or
+/// `InvalidPublicKey` if the key is not a valid Ristretto255 point
+pub fn generate_stealth_address(
+    recipient_public_key: &[u8],
+) -> Result<(StealthAddress, StealthSecret), DsmError> {
+    let recipient = decode_point(recipient_public_key).ok_or(DsmError::InvalidPublicKey)?;
+
+    let ephemeral_secret = Scalar::random(&mut OsRng);
+    let ephemeral_public = (ephemeral_secret * RISTRETTO_BASEPOINT_POINT).compress();
+    let shared_secret = (ephemeral_secret * recipient).compress().to_bytes();
+
+    let address = recipient + shared_scalar(&shared_secret) * RISTRETTO_BASEPOINT_POINT;
+
+    Ok((
+        StealthAddress(address.compress().to_bytes()),
+        StealthSecret {
+            ephemeral_public_key: EphemeralPublicKey(ephemeral_public.to_bytes()),
+            shared_secret,
+        },
+    ))
+}
+
+/// Derive the one-time secret key controlling a stealth address
+///
+/// # Arguments
+///
+/// * `my_secret_key` - The recipient's stealth secret key
+/// * `ephemeral_public_key` - Ephemeral key published with the payment
+///
+/// # Returns
+///
+/// The 32-byte secret key whose public key is the stealth address
+pub fn derive_stealth_secret_key(
+    my_secret_key: &[u8],
+    ephemeral_public_key: &EphemeralPublicKey,
+) -> Result<Vec<u8>, DsmError> {
+    let secret = decode_scalar(my_secret_key).ok_or(DsmError::InvalidSecretKey)?;
+    let ephemeral = decode_point(&ephemeral_public_key.0).ok_or(DsmError::InvalidPublicKey)?;
+    Ok(one_time_secret(&secret, &ephemeral).to_bytes().to_vec())
+}
+
+/// Whether a stealth address was derived from the recipient's key
+///
+/// # Arguments
+///
+/// * `my_secret_key` - The recipient's stealth secret key
+/// * `address` - Address the payment was sent to
+/// * `ephemeral_public_key` - Ephemeral key published with the payment
+pub fn is_stealth_address_for(
+    my_secret_key: &[u8],
+    address: &StealthAddress,
+    ephemeral_public_key: &EphemeralPublicKey,
+) -> Result<bool, DsmError> {
+    let secret = decode_scalar(my_secret_key).ok_or(DsmError::InvalidSecretKey)?;
+    Ok(addressed_to(&secret, address, ephemeral_public_key))
+}
+
+/// Select the inbox entries sent to stealth addresses of the recipient
+///
+/// Entries without an ephemeral key, or whose recipient is not a stealth
+/// address, are skipped.
+///
+/// # Arguments
+///
+/// * `my_secret_key` - The recipient's stealth secret key
+/// * `transactions` - Inbox entries to scan
+///
+/// # Returns
+///
+/// The entries addressed to the recipient, in their original order
+pub fn scan_for_stealth_payments(
+    my_secret_key: &[u8],
+    transactions: &[InboxEntry],
+) -> Vec<InboxEntry> {
+    let Some(secret) = decode_scalar(my_secret_key) else {
+        warn!("Cannot scan for stealth payments with an invalid secret key");
+        return Vec::new();
+    };
+
+    transactions
+        .iter()
+        .filter(|entry| {
+            let Some(ephemeral) = entry.metadata.get(STEALTH_EPHEMERAL_KEY_METADATA) else {
+                return false;
+            };
+            match (
+                StealthAddress::from_hex(&entry.recipient_genesis_hash),
+                EphemeralPublicKey::from_hex(ephemeral),
+            ) {
+                (Ok(address), Ok(ephemeral)) => addressed_to(&secret, &address, &ephemeral),
+                _ => false,
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether the one-time key for an ephemeral key matches an address
+fn addressed_to(
+    secret: &Scalar,
+    address: &StealthAddress,
+    ephemeral_public_key: &EphemeralPublicKey,
+) -> bool {
+    match decode_point(&ephemeral_public_key.0) {
+        Some(ephemeral) => {
+            let expected = one_time_secret(secret, &ephemeral) * RISTRETTO_BASEPOINT_POINT;
+            expected.compress().to_bytes() == address.0
+        }
+        None => false,
+    }
+}
+
+/// One-time secret key `a + H(a·R)`
+fn one_time_secret(secret: &Scalar, ephemeral: &RistrettoPoint) -> Scalar {
+    let mut shared_secret = (secret * ephemeral).compress().to_bytes();
+    let one_time = secret + shared_scalar(&shared_secret);
+    shared_secret.zeroize();
+    one_time
+}
+
+/// Scalar `H(S)` derived from a shared secret
+fn shared_scalar(shared_secret: &[u8; 32]) -> Scalar {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(DOMAIN_SHARED_SECRET);
+    hasher.update(shared_secret);
+    let mut wide = [0u8; 64];
+    hasher.finalize_xof().fill(&mut wide);
+    let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+    scalar
+}
+
+fn decode_key_bytes(encoded: &str) -> Option<[u8; 32]> {
+    hex::decode(encoded).ok()?.try_into().ok()
+}
+
+fn decode_point(bytes: &[u8]) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(bytes).ok()?.decompress()
+}
+
+fn decode_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    Option::from(Scalar::from_canonical_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn stealth_entry(id: &str, address: &StealthAddress, secret: &StealthSecret) -> InboxEntry {
+        InboxEntry {
+            id: id.to_string(),
+            sender_genesis_hash: "sender".to_string(),
+            recipient_genesis_hash: address.to_hex(),
+            transaction: id.as_bytes().to_vec(),
+            signature: Vec::new(),
+            timestamp: 0,
+            expires_at: 0,
+            metadata: HashMap::from([(
+                STEALTH_EPHEMERAL_KEY_METADATA.to_string(),
+                secret.ephemeral_public_key().to_hex(),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_addresses_are_unlinkable_and_recognised() {
+        let (secret_key, public_key) = generate_stealth_keypair();
+        let (first, first_secret) = generate_stealth_address(&public_key).unwrap();
+        let (second, second_secret) = generate_stealth_address(&public_key).unwrap();
+
+        // Every payment to the same recipient gets a fresh address
+        assert_ne!(first, second);
+        assert_ne!(first.0.as_slice(), public_key.as_slice());
+
+        let first_ephemeral = first_secret.ephemeral_public_key();
+        let second_ephemeral = second_secret.ephemeral_public_key();
+        assert!(is_stealth_address_for(&secret_key, &first, &first_ephemeral).unwrap());
+        assert!(is_stealth_address_for(&secret_key, &second, &second_ephemeral).unwrap());
+        assert!(!is_stealth_address_for(&secret_key, &first, &second_ephemeral).unwrap());
+
+        // The one-time secret key controls the address
+        let one_time = derive_stealth_secret_key(&secret_key, &first_ephemeral).unwrap();
+        let one_time = decode_scalar(&one_time).unwrap();
+        assert_eq!(
+            (one_time * RISTRETTO_BASEPOINT_POINT).compress().to_bytes(),
+            first.0
+        );
+    }
+
+    #[test]
+    fn test_scan_detects_exactly_own_payments() {
+        let (alice_secret, alice_public) = generate_stealth_keypair();
+        let (bob_secret, bob_public) = generate_stealth_keypair();
+
+        let mut entries = Vec::new();
+        let recipients = [
+            ("a1", &alice_public),
+            ("b1", &bob_public),
+            ("a2", &alice_public),
+        ];
+        for (id, public_key) in recipients {
+            let (address, secret) = generate_stealth_address(public_key).unwrap();
+            entries.push(stealth_entry(id, &address, &secret));
+        }
+
+        // Entries that are not stealth payments are skipped
+        let (address, secret) = generate_stealth_address(&alice_public).unwrap();
+        let mut plain = stealth_entry("plain", &address, &secret);
+        plain.metadata.clear();
+        entries.push(plain);
+        let mut malformed = stealth_entry("malformed", &address, &secret);
+        malformed.recipient_genesis_hash = "not-hex".to_string();
+        entries.push(malformed);
+
+        let ids = |found: Vec<InboxEntry>| -> Vec<String> {
+            found.into_iter().map(|entry| entry.id).collect()
+        };
+        assert_eq!(
+            ids(scan_for_stealth_payments(&alice_secret, &entries)),
+            ["a1", "a2"]
+        );
+        assert_eq!(
+            ids(scan_for_stealth_payments(&bob_secret, &entries)),
+            ["b1"]
+        );
+        assert!(scan_for_stealth_payments(&[0xff; 32], &entries).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_recipient_key_is_rejected() {
+        assert!(matches!(
+            generate_stealth_address(&[1, 2, 3]),
+            Err(DsmError::InvalidPublicKey)
+        ));
+    }
+}
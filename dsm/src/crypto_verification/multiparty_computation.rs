@@ -8,6 +8,7 @@
 use crate::crypto::kyber::KyberKeyPair;
 use crate::crypto::signatures::SignatureKeyPair;
 use crate::types::error::DsmError;
+use std::collections::HashMap;
 
 /// MpcContribution represents a single party's contribution to the MPC process
 #[derive(Debug, Clone)]
@@ -183,7 +184,8 @@ pub fn create_identity(
                 public_key: kyber_keypair.public_key.clone(),
                 secret_key: kyber_keypair.secret_key.clone(),
             },
-            contributions: vec![]
+            contributions: vec![],
+            token_policies: HashMap::new()
         };
         
         // Create the identity
@@ -231,7 +233,8 @@ pub fn create_test_identity(
                 public_key: kyber_keypair.public_key.clone(),
                 secret_key: kyber_keypair.secret_key.clone(),
             },
-            contributions: vec![]
+            contributions: vec![],
+            token_policies: HashMap::new()
         };
         
         // Create the identity
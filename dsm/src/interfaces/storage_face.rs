@@ -1,9 +1,13 @@
 // Storage interface implementations
 use crate::types::error::DsmError;
 use async_trait::async_trait;
+#[cfg(not(target_arch = "wasm32"))]
 use rocksdb::{DBCompressionType, Options, DB, WriteBatch, Env};
+#[cfg(not(target_arch = "wasm32"))]
 use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+#[cfg(not(target_arch = "wasm32"))]
 use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Storage interface
@@ -22,7 +26,8 @@ pub trait StorageInterface {
     async fn exists(&self, key: &[u8]) -> Result<bool, DsmError>;
 }
 
-/// RocksDB-based storage implementation
+/// RocksDB-based storage implementation (native targets only)
+#[cfg(not(target_arch = "wasm32"))]
 pub struct RocksDbStorage {
     db_path: String,
     db: Option<DB>,
@@ -37,6 +42,7 @@ pub struct DbCorruptionError {
     pub affected_files: Vec<String>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl RocksDbStorage {
     /// Create a new RocksDB storage instance
     pub fn new(db_path: String) -> Self {
@@ -228,6 +234,7 @@ pub fn set_backup_path<P: AsRef<Path>>(&mut self, path: P) {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 #[async_trait]
 impl StorageInterface for RocksDbStorage {
     async fn store(&self, key: &[u8], value: &[u8]) -> Result<(), DsmError> {
@@ -20,6 +20,7 @@
 //! * `core`: Core state machine implementation and identity management
 //! * `crypto`: Cryptographic primitives and operations
 //! * `api`: Public API interfaces for interacting with DSM
+//! * `bridge`: Cross-chain bridges for moving tokens to and from external chains
 //! * `vault`: Deterministic Limbo Vault (DLV) for asset management
 //! * `unilateral`: Unilateral transaction support
 //! * `interfaces`: Abstract interfaces for component interaction
@@ -27,6 +28,7 @@
 
 // Module declarations - expose all modules through the library
 pub mod api;
+pub mod bridge;
 pub mod commitments;
 pub mod common;
 pub mod communication;
@@ -36,6 +38,8 @@
 pub mod interfaces;
 pub mod merkle;
 pub mod recovery;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_support;
 pub mod types;
 pub mod unilateral; // Module for unilateral transactions
 pub mod utils;
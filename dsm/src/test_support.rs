@@ -0,0 +1,33 @@
+//! Shared test fixtures
+//!
+//! Built for this crate's tests, and for dependent crates' tests through the
+//! `test-utils` feature, so each fixture is defined once.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::core::identity::{GenesisState, KyberKey, SigningKey};
+
+/// Genesis state with the given hash and fixed placeholder keys
+///
+/// The keys are not real key pairs; tests that sign or encrypt with the
+/// identity must generate their own.
+pub fn genesis(hash: Vec<u8>) -> GenesisState {
+    GenesisState {
+        hash,
+        initial_entropy: vec![0xbb; 32],
+        threshold: 1,
+        participants: HashSet::from(["participant-1".to_string()]),
+        merkle_root: None,
+        device_id: Some("device-1".to_string()),
+        signing_key: SigningKey {
+            public_key: vec![1; 32],
+            secret_key: vec![2; 64],
+        },
+        kyber_keypair: KyberKey {
+            public_key: vec![3; 32],
+            secret_key: vec![4; 64],
+        },
+        contributions: Vec::new(),
+        token_policies: HashMap::new(),
+    }
+}
@@ -1,5 +1,7 @@
 use std::{error::Error, fmt::Display};
 
+use crate::types::token_types::Balance;
+
 /// Comprehensive error type for DSM operations
 ///
 /// This enumeration encapsulates all possible error conditions that may arise during
@@ -275,6 +277,115 @@ pub enum DsmError {
         /// Optional source error that caused this error
         source: Option<Box<dyn Error + Send + Sync>>,
     },
+
+    /// Dependency not met error
+    ///
+    /// Occurs when an operation declares a prerequisite operation that has not
+    /// yet been executed in the state chain
+    DependencyNotMet {
+        /// Hex-encoded hash of the missing prerequisite operation
+        missing_op_hash: String,
+    },
+
+    /// State conflict error
+    ///
+    /// Occurs when two devices sharing an identity have extended its state
+    /// chain independently, so neither chain is a prefix of the other
+    StateConflict {
+        /// State number of the local chain head
+        local_tip: u64,
+        /// State number of the remote chain head
+        remote_tip: u64,
+    },
+
+    /// Replay divergence error
+    ///
+    /// Occurs when replaying an operation log from genesis reaches an
+    /// operation that is not validly signed or cannot be applied
+    ReplayDiverged {
+        /// Position of the offending operation in the log
+        index: usize,
+        /// Why the operation could not be replayed
+        reason: String,
+    },
+
+    /// Rate limit error
+    ///
+    /// Occurs when a rate limiter has no token left for a request
+    RateLimited {
+        /// Milliseconds until the next token is available
+        retry_after_ms: u64,
+    },
+
+    /// Stale snapshot error
+    ///
+    /// Occurs when operations are committed against a snapshot of a state
+    /// that is no longer the current state
+    SnapshotStale {
+        /// State number the snapshot was taken at
+        snapshot_at: u64,
+        /// State number of the current state
+        current: u64,
+    },
+
+    /// Supply cap error
+    ///
+    /// Occurs when a mint would raise a token's total supply above the
+    /// maximum fixed by its genesis token policy
+    SupplyCapExceeded {
+        /// Token ID whose supply is capped
+        token_id: String,
+        /// Total supply before the mint
+        current_supply: Balance,
+        /// Amount the mint requested
+        requested_amount: Balance,
+        /// Maximum supply of the token
+        max_supply: Balance,
+    },
+
+    /// Commitment mismatch error
+    ///
+    /// Occurs when vault content does not open the Pedersen commitment the
+    /// vault was created with, such as content modified by a storage node
+    CommitmentMismatch {
+        /// ID of the vault whose content failed verification
+        vault_id: String,
+    },
+
+    /// Resource ceiling exceeded error
+    ///
+    /// Occurs when the estimated cost of a transition exceeds the configured
+    /// per-transition resource ceiling
+    ResourceExceeded {
+        /// Name of the resource over its ceiling, such as `cpu_units`
+        resource: String,
+        /// Estimated amount of the resource the transition needs
+        estimated: u64,
+        /// Ceiling configured for the resource
+        limit: u64,
+    },
+
+    /// Invalid vault post error
+    ///
+    /// Occurs when a vault post is unsigned or its signature does not verify
+    /// against the vault creator's key, such as a post forged by a storage node
+    InvalidVaultPost {
+        /// ID of the vault the post is for
+        vault_id: String,
+        /// Why the post was rejected
+        reason: String,
+    },
+
+    /// Incompatible protocol version error
+    ///
+    /// Occurs when the protocol version ranges of a client and the storage
+    /// node it talks to do not overlap
+    VersionIncompatible {
+        /// Version range the client speaks, such as `1.0.0-1.2.0`
+        client: String,
+        /// Version range the storage node accepts
+        server: String,
+    },
 }
 
 impl DsmError {
@@ -661,7 +772,11 @@ pub fn fatal(message: impl Into<String>) -> Self {
     pub fn is_recoverable(&self) -> bool {
         matches!(
             self,
-            DsmError::Network { .. } | DsmError::Serialization { .. } | DsmError::LockError
+            DsmError::Network { .. }
+                | DsmError::Serialization { .. }
+                | DsmError::LockError
+                | DsmError::RateLimited { .. }
+                | DsmError::SnapshotStale { .. }
         )
     }
 
@@ -675,6 +790,7 @@ pub fn is_security_related(&self) -> bool {
                 | DsmError::InvalidSecretKey
                 | DsmError::Verification(_)
                 | DsmError::HashChain(_)
+                | DsmError::InvalidVaultPost { .. }
         )
     }
 
@@ -866,6 +982,82 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 }
                 Ok(())
             }
+            DsmError::DependencyNotMet { missing_op_hash } => {
+                write!(
+                    f,
+                    "Dependency not met: operation {} has not been executed",
+                    missing_op_hash
+                )
+            }
+            DsmError::StateConflict {
+                local_tip,
+                remote_tip,
+            } => {
+                write!(
+                    f,
+                    "State conflict: local head {} diverged from remote head {}",
+                    local_tip, remote_tip
+                )
+            }
+            DsmError::ReplayDiverged { index, reason } => {
+                write!(f, "Replay diverged at operation {}: {}", index, reason)
+            }
+            DsmError::RateLimited { retry_after_ms } => {
+                write!(f, "Rate limited: retry after {} ms", retry_after_ms)
+            }
+            DsmError::SnapshotStale {
+                snapshot_at,
+                current,
+            } => {
+                write!(
+                    f,
+                    "Stale snapshot: taken at state {} but the current state is {}",
+                    snapshot_at, current
+                )
+            }
+            DsmError::SupplyCapExceeded {
+                token_id,
+                current_supply,
+                requested_amount,
+                max_supply,
+            } => {
+                write!(
+                    f,
+                    "Minting {} {} would exceed its maximum supply of {} (current supply {})",
+                    requested_amount.value(),
+                    token_id,
+                    max_supply.value(),
+                    current_supply.value()
+                )
+            }
+            DsmError::CommitmentMismatch { vault_id } => {
+                write!(
+                    f,
+                    "Content of vault {} does not match its commitment",
+                    vault_id
+                )
+            }
+            DsmError::ResourceExceeded {
+                resource,
+                estimated,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "Transition needs an estimated {} {}, above the ceiling of {}",
+                    estimated, resource, limit
+                )
+            }
+            DsmError::InvalidVaultPost { vault_id, reason } => {
+                write!(f, "Invalid post for vault {}: {}", vault_id, reason)
+            }
+            DsmError::VersionIncompatible { client, server } => {
+                write!(
+                    f,
+                    "Incompatible protocol version: client supports {}, server supports {}",
+                    client, server
+                )
+            }
         }
     }
 }
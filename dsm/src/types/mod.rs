@@ -7,6 +7,7 @@
 pub mod state_builder;
 pub mod state_types;
 pub mod token_types;
+pub mod versioned;
 // Re-export correctly named types
 pub use general::{Commitment, DirectoryEntry, KeyPair, SecurityLevel, VerificationResult};
 pub use identity::{IdentityAnchor, IdentityClaim};
@@ -15,3 +16,4 @@
 pub use state_builder::StateBuilder;
 pub use state_types::State;
 pub use token_types::{Token, TokenStatus};
+pub use versioned::{check_schema_compatibility, LegacySchema, Versioned};
@@ -4,6 +4,7 @@
 
 use crate::{
     commitments::precommit::SecurityParameters,
+    crypto::pedersen::{PedersenCommitment, TransferProof},
     types::{error::DsmError, token_types::Balance},
 };
 
@@ -189,6 +190,28 @@ pub enum Operation {
         data: Vec<u8>,
         message: String,
     },
+    /// Wraps an operation that may only execute after the operations
+    /// identified by `dependencies` (operation hashes) have been executed
+    WithDependencies {
+        dependencies: Vec<[u8; 32]>,
+        inner: Box<Operation>,
+    },
+    /// Transfers part of a committed balance without revealing any amount.
+    /// `from_commitment` becomes the sender's new balance and `to_commitment`
+    /// commits to the amount credited to `recipient`.
+    CommittedTransfer {
+        recipient: String,
+        from_commitment: PedersenCommitment,
+        to_commitment: PedersenCommitment,
+        proof: TransferProof,
+    },
+    /// Discloses the amount behind a committed balance for auditing
+    RevealBalance {
+        token_id: String,
+        amount: u64,
+        /// Big-endian blinding factor of the committed balance
+        blinding_factor: Vec<u8>,
+    },
 }
 
 impl Operation {
@@ -203,6 +226,124 @@ pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
     pub fn get_state_number(&self) -> Option<u64> {
         None
     }
+
+    /// Create a transfer between committed balances
+    ///
+    /// The token is taken from the proof, which is bound to it.
+    pub fn transfer_committed(
+        recipient: &str,
+        from_commitment: PedersenCommitment,
+        to_commitment: PedersenCommitment,
+        proof: TransferProof,
+    ) -> Operation {
+        Operation::CommittedTransfer {
+            recipient: recipient.to_string(),
+            from_commitment,
+            to_commitment,
+            proof,
+        }
+    }
+
+    /// The operation that is actually executed, with all dependency wrappers removed
+    pub fn innermost(&self) -> &Operation {
+        match self {
+            Operation::WithDependencies { inner, .. } => inner.innermost(),
+            op => op,
+        }
+    }
+
+    /// Consume the operation and return it with all dependency wrappers removed
+    pub fn into_innermost(self) -> Operation {
+        match self {
+            Operation::WithDependencies { inner, .. } => inner.into_innermost(),
+            op => op,
+        }
+    }
+
+    /// Hash identifying this operation in dependency declarations
+    ///
+    /// Dependency wrappers are transparent: the hash is that of the innermost
+    /// operation, which is what gets recorded once the operation executes.
+    pub fn operation_hash(&self) -> [u8; 32] {
+        *blake3::hash(&self.innermost().to_bytes()).as_bytes()
+    }
+
+    /// All prerequisite operation hashes declared on this operation, including nested wrappers
+    pub fn dependencies(&self) -> Vec<[u8; 32]> {
+        let mut dependencies = Vec::new();
+        let mut current = self;
+
+        while let Operation::WithDependencies {
+            dependencies: declared,
+            inner,
+        } = current
+        {
+            dependencies.extend_from_slice(declared);
+            current = inner.as_ref();
+        }
+
+        dependencies
+    }
+
+    /// Validate that a set of operations forms an acyclic dependency graph
+    ///
+    /// Dependencies on operations outside the set are treated as already
+    /// satisfied; only cycles among the given operations are rejected.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If no operation depends, directly or transitively, on itself
+    /// * `Err(DsmError::Validation)` - If a dependency cycle exists
+    pub fn validate_dependency_graph(operations: &[Operation]) -> Result<(), DsmError> {
+        let graph: HashMap<[u8; 32], Vec<[u8; 32]>> = operations
+            .iter()
+            .map(|op| (op.operation_hash(), op.dependencies()))
+            .collect();
+
+        // Iterative three-colour DFS: absent = unvisited, false = in progress, true = done
+        let mut visited: HashMap<[u8; 32], bool> = HashMap::new();
+
+        for start in graph.keys() {
+            if visited.contains_key(start) {
+                continue;
+            }
+
+            let mut stack = vec![(*start, 0usize)];
+            visited.insert(*start, false);
+
+            while let Some((node, next_edge)) = stack.pop() {
+                let edges = graph.get(&node).map(Vec::as_slice).unwrap_or(&[]);
+
+                if let Some(dependency) = edges.get(next_edge) {
+                    stack.push((node, next_edge + 1));
+
+                    if !graph.contains_key(dependency) {
+                        continue;
+                    }
+
+                    match visited.get(dependency) {
+                        Some(false) => {
+                            return Err(DsmError::validation(
+                                format!(
+                                    "Circular dependency detected involving operation {}",
+                                    hex::encode(dependency)
+                                ),
+                                None::<std::convert::Infallible>,
+                            ));
+                        }
+                        Some(true) => {}
+                        None => {
+                            visited.insert(*dependency, false);
+                            stack.push((*dependency, 0));
+                        }
+                    }
+                } else {
+                    visited.insert(node, true);
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Ops for Operation {
@@ -226,6 +367,7 @@ fn validate(&self) -> Result<bool, DsmError> {
             } => Ok(amount.value() > 0),
             Operation::LockToken { .. } => Ok(true),
             Operation::UnlockToken { .. } => Ok(true),
+            Operation::WithDependencies { inner, .. } => inner.validate(),
             _ => Ok(true),
         }
     }
@@ -253,6 +395,9 @@ fn get_id(&self) -> &str {
             Operation::Invalidate { .. } => "invalidate",
             Operation::LockToken { .. } => "lock_token",
             Operation::UnlockToken { .. } => "unlock_token",
+            Operation::WithDependencies { inner, .. } => inner.get_id(),
+            Operation::CommittedTransfer { .. } => "committed_transfer",
+            Operation::RevealBalance { .. } => "reveal_balance",
         }
     }
 
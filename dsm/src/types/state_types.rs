@@ -1,13 +1,16 @@
 use crate::crypto::blake3::hash_blake3;
+use crate::crypto::pedersen::{PedersenCommitment, PedersenParams};
 use crate::merkle::sparse_merkle_tree::SparseMerkleTreeImpl;
 use crate::types::error::DsmError;
 use crate::types::operations::Operation;
 use crate::types::operations::TransactionMode;
-use crate::types::token_types::Balance;
+use crate::types::token_types::{Balance, FeePolicy};
+use crate::types::versioned::{LegacyDecoder, LegacySchema};
 use blake3::{self, Hash};
+use num_bigint::BigUint;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryInto;
 use std::fmt;
 
@@ -246,6 +249,95 @@ pub fn new(device_id: &str, public_key: Vec<u8>) -> Self {
     }
 }
 
+/// Operations executed in a state chain, used to resolve operation dependencies
+///
+/// Only the most recent [`ExecutedOperations::WINDOW`] operation hashes are kept,
+/// so the set copied into every state stays bounded. Every hash is also folded
+/// into a running `root`, and the state hash commits to that root rather than to
+/// the retained hashes. A dependency on an operation that has aged out of the
+/// window is reported as not met.
+///
+/// A state migrated from an earlier schema keeps its full set of hashes, since
+/// its stored hash was taken over them; the next recorded operation drops them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutedOperations {
+    /// Most recently executed operation hashes, oldest first
+    recent: VecDeque<[u8; 32]>,
+
+    /// Hash chain over every operation hash ever recorded
+    root: [u8; 32],
+
+    /// Sorted hashes a migrated state's hash commits to, empty otherwise
+    #[serde(default)]
+    legacy: Vec<[u8; 32]>,
+}
+
+impl ExecutedOperations {
+    /// Number of operation hashes retained for dependency checks
+    pub const WINDOW: usize = 256;
+
+    /// Record an executed operation hash, returning false if it is already retained
+    pub fn insert(&mut self, op_hash: [u8; 32]) -> bool {
+        self.legacy.clear();
+        if self.contains(&op_hash) {
+            return false;
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.root);
+        hasher.update(&op_hash);
+        self.root = *hasher.finalize().as_bytes();
+
+        if self.recent.len() == Self::WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(op_hash);
+        true
+    }
+
+    /// Whether an operation hash is within the retained window
+    pub fn contains(&self, op_hash: &[u8; 32]) -> bool {
+        self.recent.contains(op_hash)
+    }
+
+    /// Whether no operation has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    /// Number of retained operation hashes
+    pub fn len(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// Commitment to every operation hash recorded so far
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Hashes the state hash commits to individually, if migrated from an earlier schema
+    pub fn legacy_hashes(&self) -> Option<&[[u8; 32]]> {
+        if self.legacy.is_empty() {
+            None
+        } else {
+            Some(&self.legacy)
+        }
+    }
+
+    /// Rebuild from the unordered set stored by earlier schema versions
+    fn from_legacy(executed: HashSet<[u8; 32]>) -> Self {
+        let mut hashes: Vec<[u8; 32]> = executed.into_iter().collect();
+        hashes.sort();
+
+        let mut operations = Self::default();
+        for op_hash in &hashes {
+            operations.insert(*op_hash);
+        }
+        operations.legacy = hashes;
+        operations
+    }
+}
+
 /// Represents the core state structure as defined in the whitepaper.
 /// Each state forms a node in the straight hash chain, containing all
 /// necessary data to cryptographically bind it to its predecessor.
@@ -290,6 +382,26 @@ pub struct State {
 
     /// Relationship context for tracking state relationships
     pub relationship_context: Option<RelationshipContext>,
+
+    /// Operations executed in this state chain, used to resolve operation
+    /// dependencies (see `Operation::WithDependencies`)
+    #[serde(default)]
+    pub executed_operations: ExecutedOperations,
+
+    /// Pedersen commitments to token balances, keyed by token identifier.
+    /// Held alongside (or instead of) the plaintext `token_balances`.
+    #[serde(default)]
+    pub committed_balances: HashMap<String, PedersenCommitment>,
+
+    /// Whether this is a checkpoint snapshot that a chain can be restored from.
+    /// Not part of the state hash, so a checkpoint hashes like the state it copies.
+    #[serde(default)]
+    pub is_checkpoint: bool,
+
+    /// Fee charged on transfers out of this state, see [`State::charge_transfer_fee`].
+    /// Set on the genesis state; every following state carries it over.
+    #[serde(default)]
+    pub fee_policy: Option<FeePolicy>,
     pub(crate) forward_commitment: Option<PreCommitment>,
     pub(crate) position_sequence: Option<PositionSequence>,
     pub(crate) positions: Vec<Vec<i32>>,
@@ -313,6 +425,225 @@ pub enum StateFlag {
     Custom(String),
 }
 
+/// Layout of [`State`] under schema version 3, when every executed operation
+/// hash was kept in an unordered set
+#[derive(Serialize, Deserialize)]
+struct StateV3 {
+    id: String,
+    state_number: u64,
+    entropy: Vec<u8>,
+    hash: Vec<u8>,
+    prev_state_hash: Vec<u8>,
+    sparse_index: SparseIndex,
+    operation: Operation,
+    encapsulated_entropy: Option<Vec<u8>>,
+    device_info: DeviceInfo,
+    flags: HashSet<StateFlag>,
+    token_balances: HashMap<String, Balance>,
+    matches_parameters: bool,
+    relationship_context: Option<RelationshipContext>,
+    executed_operations: HashSet<[u8; 32]>,
+    committed_balances: HashMap<String, PedersenCommitment>,
+    is_checkpoint: bool,
+    fee_policy: Option<FeePolicy>,
+    forward_commitment: Option<PreCommitment>,
+    position_sequence: Option<PositionSequence>,
+    positions: Vec<Vec<i32>>,
+    public_key: Vec<u8>,
+    device_id: String,
+    hashchain_head: Option<Vec<u8>>,
+    external_data: HashMap<String, Vec<u8>>,
+    entity_sig: Option<Vec<u8>>,
+    counterparty_sig: Option<Vec<u8>>,
+    value: Vec<i32>,
+    commitment: Vec<i32>,
+    state_type: String,
+}
+
+/// Layout of [`State`] under schema version 2, before `fee_policy` was added
+#[derive(Serialize, Deserialize)]
+struct StateV2 {
+    id: String,
+    state_number: u64,
+    entropy: Vec<u8>,
+    hash: Vec<u8>,
+    prev_state_hash: Vec<u8>,
+    sparse_index: SparseIndex,
+    operation: Operation,
+    encapsulated_entropy: Option<Vec<u8>>,
+    device_info: DeviceInfo,
+    flags: HashSet<StateFlag>,
+    token_balances: HashMap<String, Balance>,
+    matches_parameters: bool,
+    relationship_context: Option<RelationshipContext>,
+    executed_operations: HashSet<[u8; 32]>,
+    committed_balances: HashMap<String, PedersenCommitment>,
+    is_checkpoint: bool,
+    forward_commitment: Option<PreCommitment>,
+    position_sequence: Option<PositionSequence>,
+    positions: Vec<Vec<i32>>,
+    public_key: Vec<u8>,
+    device_id: String,
+    hashchain_head: Option<Vec<u8>>,
+    external_data: HashMap<String, Vec<u8>>,
+    entity_sig: Option<Vec<u8>>,
+    counterparty_sig: Option<Vec<u8>>,
+    value: Vec<i32>,
+    commitment: Vec<i32>,
+    state_type: String,
+}
+
+/// Layout of [`State`] under schema version 1, before `executed_operations`,
+/// `committed_balances` and `is_checkpoint` were added
+#[derive(Serialize, Deserialize)]
+struct StateV1 {
+    id: String,
+    state_number: u64,
+    entropy: Vec<u8>,
+    hash: Vec<u8>,
+    prev_state_hash: Vec<u8>,
+    sparse_index: SparseIndex,
+    operation: Operation,
+    encapsulated_entropy: Option<Vec<u8>>,
+    device_info: DeviceInfo,
+    flags: HashSet<StateFlag>,
+    token_balances: HashMap<String, Balance>,
+    matches_parameters: bool,
+    relationship_context: Option<RelationshipContext>,
+    forward_commitment: Option<PreCommitment>,
+    position_sequence: Option<PositionSequence>,
+    positions: Vec<Vec<i32>>,
+    public_key: Vec<u8>,
+    device_id: String,
+    hashchain_head: Option<Vec<u8>>,
+    external_data: HashMap<String, Vec<u8>>,
+    entity_sig: Option<Vec<u8>>,
+    counterparty_sig: Option<Vec<u8>>,
+    value: Vec<i32>,
+    commitment: Vec<i32>,
+    state_type: String,
+}
+
+impl LegacySchema for State {
+    const SCHEMA_VERSION: u16 = 4;
+    const LEGACY_SCHEMAS: &'static [(u16, LegacyDecoder<Self>)] =
+        &[(1, Self::from_v1), (2, Self::from_v2), (3, Self::from_v3)];
+}
+
+impl State {
+    /// Migrate a schema version 1 state, leaving the fields added since empty
+    fn from_v1(bytes: &[u8]) -> Result<Self, DsmError> {
+        let v1: StateV1 = bincode::deserialize(bytes)?;
+
+        Ok(Self {
+            id: v1.id,
+            state_number: v1.state_number,
+            entropy: v1.entropy,
+            hash: v1.hash,
+            prev_state_hash: v1.prev_state_hash,
+            sparse_index: v1.sparse_index,
+            operation: v1.operation,
+            encapsulated_entropy: v1.encapsulated_entropy,
+            device_info: v1.device_info,
+            flags: v1.flags,
+            token_balances: v1.token_balances,
+            matches_parameters: v1.matches_parameters,
+            relationship_context: v1.relationship_context,
+            executed_operations: ExecutedOperations::default(),
+            committed_balances: HashMap::new(),
+            is_checkpoint: false,
+            fee_policy: None,
+            forward_commitment: v1.forward_commitment,
+            position_sequence: v1.position_sequence,
+            positions: v1.positions,
+            public_key: v1.public_key,
+            device_id: v1.device_id,
+            hashchain_head: v1.hashchain_head,
+            external_data: v1.external_data,
+            entity_sig: v1.entity_sig,
+            counterparty_sig: v1.counterparty_sig,
+            value: v1.value,
+            commitment: v1.commitment,
+            state_type: v1.state_type,
+        })
+    }
+
+    /// Migrate a schema version 2 state, which has no fee policy
+    fn from_v2(bytes: &[u8]) -> Result<Self, DsmError> {
+        let v2: StateV2 = bincode::deserialize(bytes)?;
+
+        Ok(Self {
+            id: v2.id,
+            state_number: v2.state_number,
+            entropy: v2.entropy,
+            hash: v2.hash,
+            prev_state_hash: v2.prev_state_hash,
+            sparse_index: v2.sparse_index,
+            operation: v2.operation,
+            encapsulated_entropy: v2.encapsulated_entropy,
+            device_info: v2.device_info,
+            flags: v2.flags,
+            token_balances: v2.token_balances,
+            matches_parameters: v2.matches_parameters,
+            relationship_context: v2.relationship_context,
+            executed_operations: ExecutedOperations::from_legacy(v2.executed_operations),
+            committed_balances: v2.committed_balances,
+            is_checkpoint: v2.is_checkpoint,
+            fee_policy: None,
+            forward_commitment: v2.forward_commitment,
+            position_sequence: v2.position_sequence,
+            positions: v2.positions,
+            public_key: v2.public_key,
+            device_id: v2.device_id,
+            hashchain_head: v2.hashchain_head,
+            external_data: v2.external_data,
+            entity_sig: v2.entity_sig,
+            counterparty_sig: v2.counterparty_sig,
+            value: v2.value,
+            commitment: v2.commitment,
+            state_type: v2.state_type,
+        })
+    }
+
+    /// Migrate a schema version 3 state, keeping only the most recent executed
+    /// operations
+    fn from_v3(bytes: &[u8]) -> Result<Self, DsmError> {
+        let v3: StateV3 = bincode::deserialize(bytes)?;
+
+        Ok(Self {
+            id: v3.id,
+            state_number: v3.state_number,
+            entropy: v3.entropy,
+            hash: v3.hash,
+            prev_state_hash: v3.prev_state_hash,
+            sparse_index: v3.sparse_index,
+            operation: v3.operation,
+            encapsulated_entropy: v3.encapsulated_entropy,
+            device_info: v3.device_info,
+            flags: v3.flags,
+            token_balances: v3.token_balances,
+            matches_parameters: v3.matches_parameters,
+            relationship_context: v3.relationship_context,
+            executed_operations: ExecutedOperations::from_legacy(v3.executed_operations),
+            committed_balances: v3.committed_balances,
+            is_checkpoint: v3.is_checkpoint,
+            fee_policy: v3.fee_policy,
+            forward_commitment: v3.forward_commitment,
+            position_sequence: v3.position_sequence,
+            positions: v3.positions,
+            public_key: v3.public_key,
+            device_id: v3.device_id,
+            hashchain_head: v3.hashchain_head,
+            external_data: v3.external_data,
+            entity_sig: v3.entity_sig,
+            counterparty_sig: v3.counterparty_sig,
+            value: v3.value,
+            commitment: v3.commitment,
+            state_type: v3.state_type,
+        })
+    }
+}
+
 impl State {
     /// Create a new state using the parameter object pattern
     ///
@@ -337,6 +668,10 @@ pub fn new(params: StateParams) -> Self {
             flags: HashSet::new(),
             token_balances: HashMap::new(),
             relationship_context: None,
+            executed_operations: ExecutedOperations::default(),
+            committed_balances: HashMap::new(),
+            is_checkpoint: false,
+            fee_policy: None,
             forward_commitment: params.forward_commitment,
             positions: Vec::new(),
             position_sequence: None,
@@ -385,6 +720,10 @@ pub fn new_genesis(initial_entropy: Vec<u8>, device_info: DeviceInfo) -> Self {
             flags,
             token_balances: HashMap::new(), // Initialize empty token balances
             relationship_context: None,
+            executed_operations: ExecutedOperations::default(),
+            committed_balances: HashMap::new(),
+            is_checkpoint: false,
+            fee_policy: None,
             forward_commitment: None,
             positions: Vec::new(),
             position_sequence: None,
@@ -448,6 +787,220 @@ pub fn add_flag(&mut self, flag: StateFlag) {
         self.flags.insert(flag);
     }
 
+    /// Record an operation as executed in this state chain
+    pub fn record_executed_operation(&mut self, operation: &Operation) {
+        self.executed_operations.insert(operation.operation_hash());
+    }
+
+    /// Check that every prerequisite declared by an operation has already been executed
+    ///
+    /// # Returns
+    /// * `Err(DsmError::Validation)` - If the operation depends on itself
+    /// * `Err(DsmError::DependencyNotMet)` - For the first prerequisite not yet executed
+    pub fn check_dependencies(&self, operation: &Operation) -> Result<(), DsmError> {
+        let own_hash = operation.operation_hash();
+
+        for dependency in operation.dependencies() {
+            if dependency == own_hash {
+                return Err(DsmError::validation(
+                    format!(
+                        "Circular dependency: operation {} depends on itself",
+                        hex::encode(own_hash)
+                    ),
+                    None::<std::convert::Infallible>,
+                ));
+            }
+
+            if !self.executed_operations.contains(&dependency) {
+                return Err(DsmError::DependencyNotMet {
+                    missing_op_hash: hex::encode(dependency),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store a committed balance for a token
+    ///
+    /// When `omit_plaintext` is set, any plaintext balance for the token is
+    /// removed so the amount is only recoverable from the commitment opening.
+    /// Commitments must be made with `PedersenParams::balance_params`.
+    pub fn commit_balance(
+        &mut self,
+        token_id: &str,
+        commitment: PedersenCommitment,
+        omit_plaintext: bool,
+    ) {
+        if omit_plaintext {
+            let dotted = format!(".{token_id}");
+            let scoped = format!(":{token_id}");
+            self.token_balances.retain(|key, _| {
+                key != token_id && !key.ends_with(&dotted) && !key.ends_with(&scoped)
+            });
+        }
+        self.committed_balances.insert(token_id.to_string(), commitment);
+    }
+
+    /// Verify a committed-balance operation against this state without plaintexts
+    ///
+    /// Operations that do not touch committed balances pass unchanged.
+    ///
+    /// # Returns
+    /// * `Err(DsmError::InvalidOperation)` - If the token has no committed balance
+    /// * `Err(DsmError::Verification)` - If the transfer proof or disclosure is invalid
+    pub fn verify_committed_operation(&self, operation: &Operation) -> Result<(), DsmError> {
+        match operation.innermost() {
+            Operation::CommittedTransfer {
+                from_commitment,
+                to_commitment,
+                proof,
+                ..
+            } => {
+                let balance = self.committed_balance(&proof.token_id)?;
+                let params = PedersenParams::balance_params(balance.security_level);
+                if !proof.verify(params, balance, from_commitment, to_commitment)? {
+                    return Err(DsmError::verification(format!(
+                        "Invalid transfer proof for committed balance of {}",
+                        proof.token_id
+                    )));
+                }
+                Ok(())
+            }
+            Operation::RevealBalance {
+                token_id,
+                amount,
+                blinding_factor,
+            } => {
+                let balance = self.committed_balance(token_id)?;
+                let params = PedersenParams::balance_params(balance.security_level);
+                let blinding = BigUint::from_bytes_be(blinding_factor);
+                if !balance.verify_amount(*amount, &blinding, params)? {
+                    return Err(DsmError::verification(format!(
+                        "Disclosed amount does not open committed balance of {token_id}"
+                    )));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Verify a committed-balance operation and apply its balance change to this state
+    ///
+    /// A committed transfer replaces the sender's balance with `from_commitment`
+    /// and credits `to_commitment` to the recipient under `"recipient.token_id"`,
+    /// homomorphically adding it to any balance the recipient already holds.
+    ///
+    /// # Returns
+    /// * `Err` - As for [`State::verify_committed_operation`]
+    pub fn apply_committed_operation(&mut self, operation: &Operation) -> Result<(), DsmError> {
+        self.verify_committed_operation(operation)?;
+
+        if let Operation::CommittedTransfer {
+            recipient,
+            from_commitment,
+            to_commitment,
+            proof,
+        } = operation.innermost()
+        {
+            let recipient_key = format!("{}.{}", recipient, proof.token_id);
+            let credited = match self.committed_balances.get(&recipient_key) {
+                Some(existing) => {
+                    let params = PedersenParams::balance_params(existing.security_level);
+                    existing.combine(to_commitment, params)?
+                }
+                None => to_commitment.clone(),
+            };
+
+            self.committed_balances
+                .insert(proof.token_id.clone(), from_commitment.clone());
+            self.committed_balances.insert(recipient_key, credited);
+        }
+
+        Ok(())
+    }
+
+    /// Fee the state's fee policy charges for an operation
+    ///
+    /// Zero for operations other than transfers and for states without a fee policy.
+    pub fn transfer_fee(&self, operation: &Operation) -> Result<u64, DsmError> {
+        match (&self.fee_policy, operation.innermost()) {
+            (Some(policy), Operation::Transfer { amount, .. }) => policy.fee_for(amount.value()),
+            _ => Ok(0),
+        }
+    }
+
+    /// Charge the fee of a transfer to the balances of this state
+    ///
+    /// Applied to the state a transfer produces, before its hash is computed.
+    /// The sender's balance must cover both the transferred amount and the
+    /// fee. The fee is deducted from it and credited to the fee policy's
+    /// recipient under `"fee_recipient.token_id"`. Operations without a fee
+    /// leave the state unchanged.
+    ///
+    /// # Returns
+    /// * `Err(DsmError::InsufficientBalance)` - If the sender cannot pay amount and fee
+    pub fn charge_transfer_fee(&mut self, operation: &Operation) -> Result<(), DsmError> {
+        let Some(policy) = self.fee_policy.clone() else {
+            return Ok(());
+        };
+        let Operation::Transfer {
+            amount, token_id, ..
+        } = operation.innermost()
+        else {
+            return Ok(());
+        };
+        let fee = policy.fee_for(amount.value())?;
+
+        let sender_key = self.balance_key(token_id);
+        let available = self.token_balances.get(&sender_key).map_or(0, Balance::value);
+        let required = amount
+            .value()
+            .checked_add(fee)
+            .ok_or_else(|| DsmError::invalid_parameter("Transfer amount and fee overflow"))?;
+        if required > available {
+            return Err(DsmError::insufficient_balance(
+                token_id.clone(),
+                available,
+                required,
+            ));
+        }
+
+        if fee == 0 {
+            return Ok(());
+        }
+
+        if let Some(sender) = self.token_balances.get(&sender_key) {
+            let charged = sender.with_value(available - fee);
+            self.token_balances.insert(sender_key, charged);
+        }
+
+        let recipient_key = format!("{}.{}", policy.fee_recipient, token_id);
+        let credited = match self.token_balances.get(&recipient_key) {
+            Some(balance) => balance.with_value(balance.value().saturating_add(fee)),
+            None => Balance::from_transition(fee, self.prev_state_hash.clone()),
+        };
+        self.token_balances.insert(recipient_key, credited);
+
+        Ok(())
+    }
+
+    /// Key of the device's balance of a token, `"device_id.token_id"` or the bare token ID
+    fn balance_key(&self, token_id: &str) -> String {
+        let device_key = format!("{}.{}", self.device_info.device_id, token_id);
+        match self.token_balances.contains_key(&device_key) {
+            false if self.token_balances.contains_key(token_id) => token_id.to_string(),
+            _ => device_key,
+        }
+    }
+
+    fn committed_balance(&self, token_id: &str) -> Result<&PedersenCommitment, DsmError> {
+        self.committed_balances.get(token_id).ok_or_else(|| {
+            DsmError::invalid_operation(format!("No committed balance for token {token_id}"))
+        })
+    }
+
     /// Add metadata to the state's external data
     pub fn add_metadata(&mut self, key: &str, value: Vec<u8>) -> Result<(), DsmError> {
         self.external_data.insert(key.to_string(), value);
@@ -497,6 +1050,36 @@ pub fn compute_hash(&self) -> Result<Vec<u8>, DsmError> {
             hasher.update(&fc_bytes);
         }
 
+        // Executed operations are committed through their running root; omitted
+        // when empty so hashes of states without dependencies are unchanged.
+        // States migrated from earlier schemas keep the sorted hashes they were
+        // hashed over, so their stored hashes still verify
+        if let Some(legacy) = self.executed_operations.legacy_hashes() {
+            for op_hash in legacy {
+                hasher.update(op_hash);
+            }
+        } else if !self.executed_operations.is_empty() {
+            hasher.update(&self.executed_operations.root());
+        }
+
+        // Committed balances are sorted likewise and omitted when empty
+        if !self.committed_balances.is_empty() {
+            let mut committed: Vec<(&String, &PedersenCommitment)> =
+                self.committed_balances.iter().collect();
+            committed.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            for (token_id, commitment) in committed {
+                hasher.update(token_id.as_bytes());
+                hasher.update(&commitment.to_bytes());
+            }
+        }
+
+        // The fee policy is omitted when unset, like the fields above
+        if let Some(policy) = &self.fee_policy {
+            let policy_bytes = bincode::serialize(policy)
+                .map_err(|e| DsmError::serialization("Failed to serialize fee policy", Some(e)))?;
+            hasher.update(&policy_bytes);
+        }
+
         // Token balances must be sorted for deterministic ordering
         let mut sorted_balances: Vec<(&String, &Balance)> = self.token_balances.iter().collect();
         sorted_balances.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
@@ -678,6 +1261,8 @@ pub fn new_with_operation(prev_state: &Self, operation: Operation) -> Result<Sel
         let sparse_index = SparseIndex::new(sparse_index_vec);
 
         let new_hash_cloned = new_hash.clone();
+        let mut executed_operations = prev_state.executed_operations.clone();
+        executed_operations.insert(operation.operation_hash());
         Ok(State {
             id: format!("state_{}", prev_state.state_number + 1),
             state_number: prev_state.state_number + 1,
@@ -692,6 +1277,10 @@ pub fn new_with_operation(prev_state: &Self, operation: Operation) -> Result<Sel
             token_balances: prev_state.token_balances.clone(),
             matches_parameters: false,
             relationship_context: None,
+            executed_operations,
+            committed_balances: prev_state.committed_balances.clone(),
+            is_checkpoint: false,
+            fee_policy: prev_state.fee_policy.clone(),
             forward_commitment: None,
             position_sequence: None,
             positions: Vec::new(),
@@ -1522,3 +2111,273 @@ pub fn new(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::token_types::Ratio;
+    use crate::types::versioned::{check_schema_compatibility, Versioned};
+
+    /// State hash as computed before schema version 4, when every executed
+    /// operation hash was fed to the hasher in sorted order
+    fn pre_v4_hash(state: &State, executed: &HashSet<[u8; 32]>) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&state.state_number.to_le_bytes());
+        hasher.update(&state.prev_state_hash);
+        hasher.update(&state.entropy);
+        if let Some(enc) = &state.encapsulated_entropy {
+            hasher.update(enc);
+        }
+        hasher.update(&bincode::serialize(&state.operation).unwrap());
+        hasher.update(state.device_info.device_id.as_bytes());
+        hasher.update(&state.device_info.public_key);
+        if let Some(fc) = &state.forward_commitment {
+            hasher.update(&bincode::serialize(fc).unwrap());
+        }
+
+        let mut sorted: Vec<&[u8; 32]> = executed.iter().collect();
+        sorted.sort();
+        for op_hash in sorted {
+            hasher.update(op_hash);
+        }
+
+        let mut committed: Vec<(&String, &PedersenCommitment)> =
+            state.committed_balances.iter().collect();
+        committed.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        for (token_id, commitment) in committed {
+            hasher.update(token_id.as_bytes());
+            hasher.update(&commitment.to_bytes());
+        }
+        if let Some(policy) = &state.fee_policy {
+            hasher.update(&bincode::serialize(policy).unwrap());
+        }
+
+        let mut balances: Vec<(&String, &Balance)> = state.token_balances.iter().collect();
+        balances.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        for (token_id, balance) in balances {
+            hasher.update(token_id.as_bytes());
+            hasher.update(&bincode::serialize(balance).unwrap());
+        }
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_schema_v1_state_is_upgraded() {
+        let mut state = State::new_genesis(vec![7; 32], DeviceInfo::new("device", vec![1; 32]));
+        state.token_balances.insert("device:ROOT".to_string(), Balance::new(100));
+        state.external_data.insert("note".to_string(), vec![1, 2, 3]);
+        state.hash = state.compute_hash().unwrap();
+
+        // Bytes written before the executed-operation, committed-balance and
+        // checkpoint fields existed
+        let old = Versioned {
+            version: 1,
+            payload: StateV1 {
+                id: state.id.clone(),
+                state_number: state.state_number,
+                entropy: state.entropy.clone(),
+                hash: state.hash.clone(),
+                prev_state_hash: state.prev_state_hash.clone(),
+                sparse_index: state.sparse_index.clone(),
+                operation: state.operation.clone(),
+                encapsulated_entropy: state.encapsulated_entropy.clone(),
+                device_info: state.device_info.clone(),
+                flags: state.flags.clone(),
+                token_balances: state.token_balances.clone(),
+                matches_parameters: state.matches_parameters,
+                relationship_context: state.relationship_context.clone(),
+                forward_commitment: state.forward_commitment.clone(),
+                position_sequence: state.position_sequence.clone(),
+                positions: state.positions.clone(),
+                public_key: state.public_key.clone(),
+                device_id: state.device_id.clone(),
+                hashchain_head: state.hashchain_head.clone(),
+                external_data: state.external_data.clone(),
+                entity_sig: state.entity_sig.clone(),
+                counterparty_sig: state.counterparty_sig.clone(),
+                value: state.value.clone(),
+                commitment: state.commitment.clone(),
+                state_type: state.state_type.clone(),
+            },
+        };
+        let bytes = bincode::serialize(&old).unwrap();
+
+        assert_eq!(check_schema_compatibility(&bytes).unwrap(), 1);
+
+        let upgraded = Versioned::<State>::deserialize(&bytes).unwrap();
+        assert_eq!(upgraded.version, State::SCHEMA_VERSION);
+
+        let migrated = upgraded.payload;
+        assert_eq!(migrated.compute_hash().unwrap(), state.hash);
+        assert_eq!(migrated.token_balances.len(), 1);
+        assert_eq!(migrated.external_data, state.external_data);
+        assert!(migrated.executed_operations.is_empty());
+        assert!(migrated.committed_balances.is_empty());
+        assert!(!migrated.is_checkpoint);
+    }
+
+    #[test]
+    fn test_schema_v2_state_is_upgraded() {
+        let mut state = State::new_genesis(vec![7; 32], DeviceInfo::new("device", vec![1; 32]));
+        let executed = HashSet::from([[9; 32], [3; 32]]);
+        state.is_checkpoint = true;
+        state.hash = pre_v4_hash(&state, &executed);
+
+        // Bytes written before the fee policy existed
+        let old = Versioned {
+            version: 2,
+            payload: StateV2 {
+                id: state.id.clone(),
+                state_number: state.state_number,
+                entropy: state.entropy.clone(),
+                hash: state.hash.clone(),
+                prev_state_hash: state.prev_state_hash.clone(),
+                sparse_index: state.sparse_index.clone(),
+                operation: state.operation.clone(),
+                encapsulated_entropy: state.encapsulated_entropy.clone(),
+                device_info: state.device_info.clone(),
+                flags: state.flags.clone(),
+                token_balances: state.token_balances.clone(),
+                matches_parameters: state.matches_parameters,
+                relationship_context: state.relationship_context.clone(),
+                executed_operations: executed.clone(),
+                committed_balances: state.committed_balances.clone(),
+                is_checkpoint: state.is_checkpoint,
+                forward_commitment: state.forward_commitment.clone(),
+                position_sequence: state.position_sequence.clone(),
+                positions: state.positions.clone(),
+                public_key: state.public_key.clone(),
+                device_id: state.device_id.clone(),
+                hashchain_head: state.hashchain_head.clone(),
+                external_data: state.external_data.clone(),
+                entity_sig: state.entity_sig.clone(),
+                counterparty_sig: state.counterparty_sig.clone(),
+                value: state.value.clone(),
+                commitment: state.commitment.clone(),
+                state_type: state.state_type.clone(),
+            },
+        };
+        let bytes = bincode::serialize(&old).unwrap();
+
+        assert_eq!(check_schema_compatibility(&bytes).unwrap(), 2);
+
+        let migrated = Versioned::<State>::deserialize(&bytes).unwrap().payload;
+        assert_eq!(migrated.compute_hash().unwrap(), state.hash);
+        assert!(migrated.executed_operations.contains(&[9; 32]));
+        assert!(migrated.executed_operations.contains(&[3; 32]));
+        assert!(migrated.is_checkpoint);
+        assert!(migrated.fee_policy.is_none());
+    }
+
+    #[test]
+    fn test_schema_v3_state_is_upgraded() {
+        let mut state = State::new_genesis(vec![7; 32], DeviceInfo::new("device", vec![1; 32]));
+        let executed = HashSet::from([[9; 32], [3; 32]]);
+        state.is_checkpoint = true;
+        state.fee_policy = Some(FeePolicy {
+            base_fee: Balance::from_transition(1, vec![0; 32]),
+            fee_rate: Ratio::new(1, 100),
+            fee_recipient: "storage_node".to_string(),
+        });
+        state.hash = pre_v4_hash(&state, &executed);
+
+        // Bytes written while every executed operation was kept
+        let old = Versioned {
+            version: 3,
+            payload: StateV3 {
+                id: state.id.clone(),
+                state_number: state.state_number,
+                entropy: state.entropy.clone(),
+                hash: state.hash.clone(),
+                prev_state_hash: state.prev_state_hash.clone(),
+                sparse_index: state.sparse_index.clone(),
+                operation: state.operation.clone(),
+                encapsulated_entropy: state.encapsulated_entropy.clone(),
+                device_info: state.device_info.clone(),
+                flags: state.flags.clone(),
+                token_balances: state.token_balances.clone(),
+                matches_parameters: state.matches_parameters,
+                relationship_context: state.relationship_context.clone(),
+                executed_operations: executed.clone(),
+                committed_balances: state.committed_balances.clone(),
+                is_checkpoint: state.is_checkpoint,
+                fee_policy: state.fee_policy.clone(),
+                forward_commitment: state.forward_commitment.clone(),
+                position_sequence: state.position_sequence.clone(),
+                positions: state.positions.clone(),
+                public_key: state.public_key.clone(),
+                device_id: state.device_id.clone(),
+                hashchain_head: state.hashchain_head.clone(),
+                external_data: state.external_data.clone(),
+                entity_sig: state.entity_sig.clone(),
+                counterparty_sig: state.counterparty_sig.clone(),
+                value: state.value.clone(),
+                commitment: state.commitment.clone(),
+                state_type: state.state_type.clone(),
+            },
+        };
+        let bytes = bincode::serialize(&old).unwrap();
+
+        assert_eq!(check_schema_compatibility(&bytes).unwrap(), 3);
+
+        let migrated = Versioned::<State>::deserialize(&bytes).unwrap().payload;
+        assert_eq!(migrated.compute_hash().unwrap(), state.hash);
+        assert_eq!(migrated.executed_operations.len(), 2);
+        assert!(migrated.is_checkpoint);
+        assert_eq!(migrated.fee_policy, state.fee_policy);
+
+        // The migrated hash survives a round trip at the current schema
+        let bytes = Versioned::new(migrated.clone()).to_bytes().unwrap();
+        let decoded = Versioned::<State>::deserialize(&bytes).unwrap().payload;
+        assert_eq!(decoded.compute_hash().unwrap(), state.hash);
+
+        // The next recorded operation moves the chain onto the running root
+        let mut next = migrated.executed_operations.clone();
+        assert!(next.insert([5; 32]));
+        assert!(next.legacy_hashes().is_none());
+        assert!(next.contains(&[3; 32]));
+    }
+
+    #[test]
+    fn test_executed_operations_window_is_bounded() {
+        let mut executed = ExecutedOperations::default();
+        let mut previous_root = executed.root();
+
+        for i in 0..=ExecutedOperations::WINDOW as u32 {
+            let op_hash = *blake3::hash(&i.to_le_bytes()).as_bytes();
+            assert!(executed.insert(op_hash));
+            assert_ne!(executed.root(), previous_root);
+            previous_root = executed.root();
+        }
+
+        // The oldest operation aged out; the root still commits to it
+        assert_eq!(executed.len(), ExecutedOperations::WINDOW);
+        assert!(!executed.contains(blake3::hash(&0u32.to_le_bytes()).as_bytes()));
+        assert!(executed.contains(blake3::hash(&1u32.to_le_bytes()).as_bytes()));
+
+        // Re-recording a retained operation changes nothing
+        let last = *blake3::hash(&(ExecutedOperations::WINDOW as u32).to_le_bytes()).as_bytes();
+        assert!(!executed.insert(last));
+        assert_eq!(executed.root(), previous_root);
+    }
+
+    #[test]
+    fn test_current_state_schema_round_trip() {
+        let mut state = State::new_genesis(vec![7; 32], DeviceInfo::new("device", vec![1; 32]));
+        state.executed_operations.insert([9; 32]);
+        state.is_checkpoint = true;
+        state.fee_policy = Some(FeePolicy {
+            base_fee: Balance::from_transition(1, vec![0; 32]),
+            fee_rate: Ratio::new(1, 100),
+            fee_recipient: "storage_node".to_string(),
+        });
+
+        let bytes = Versioned::new(state.clone()).to_bytes().unwrap();
+        assert_eq!(check_schema_compatibility(&bytes).unwrap(), State::SCHEMA_VERSION);
+
+        let decoded = Versioned::<State>::deserialize(&bytes).unwrap().payload;
+        assert_eq!(decoded.executed_operations, state.executed_operations);
+        assert!(decoded.is_checkpoint);
+        assert_eq!(decoded.fee_policy, state.fee_policy);
+    }
+}
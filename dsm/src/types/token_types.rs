@@ -466,6 +466,29 @@ pub fn formatted(&self, decimals: u8) -> String {
         )
     }
 
+    /// Balance opened by a state transition
+    ///
+    /// Unlike the other constructors this does not read the clock, so a
+    /// replayed transition derives the same balance and state hash.
+    pub fn from_transition(value: u64, state_hash: Vec<u8>) -> Self {
+        Self {
+            value,
+            locked: 0,
+            last_updated: 0,
+            state_hash: Some(state_hash),
+        }
+    }
+
+    /// Copy of the balance holding `value`, as set by a state transition
+    ///
+    /// The timestamp is kept for the same reason as in [`Self::from_transition`].
+    pub fn with_value(&self, value: u64) -> Self {
+        Self {
+            value,
+            ..self.clone()
+        }
+    }
+
     /// Set state hash reference
     pub fn with_state_hash(mut self, hash: Vec<u8>) -> Self {
         self.state_hash = Some(hash);
@@ -485,6 +508,55 @@ pub fn to_le_bytes(&self) -> Vec<u8> {
     }
 }
 
+/// A fraction of a token amount, such as a fee rate
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Ratio {
+    /// Numerator of the fraction
+    pub numerator: u64,
+    /// Denominator of the fraction; a zero denominator makes the ratio zero
+    pub denominator: u64,
+}
+
+impl Ratio {
+    /// Create a ratio of `numerator / denominator`
+    pub fn new(numerator: u64, denominator: u64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// The ratio applied to `amount`, rounded down
+    pub fn apply_to(&self, amount: u64) -> u64 {
+        let scaled = u128::from(amount) * u128::from(self.numerator);
+        let applied = scaled.checked_div(u128::from(self.denominator)).unwrap_or(0);
+        u64::try_from(applied).unwrap_or(u64::MAX)
+    }
+}
+
+/// Fee charged on every transfer out of a state, see `State::fee_policy`
+///
+/// The fee is paid in the transferred token, on top of the transferred amount.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// Flat part of the fee, charged on every transfer
+    pub base_fee: Balance,
+    /// Part of the fee proportional to the transferred amount
+    pub fee_rate: Ratio,
+    /// Address the fees are credited to, such as a storage node's
+    pub fee_recipient: String,
+}
+
+impl FeePolicy {
+    /// Fee for transferring `amount`: `base_fee + fee_rate.apply_to(amount)`
+    pub fn fee_for(&self, amount: u64) -> Result<u64, DsmError> {
+        self.base_fee
+            .value()
+            .checked_add(self.fee_rate.apply_to(amount))
+            .ok_or_else(|| DsmError::invalid_parameter("Transfer fee overflows"))
+    }
+}
+
 /// Token Registry for managing token metadata and supply information
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct TokenRegistry {
@@ -0,0 +1,181 @@
+//! Schema-versioned serialization
+//!
+//! Persisted types such as `State` and `GenesisState` are written behind a
+//! two-byte schema version. Bytes are decoded with the current schema when the
+//! version matches, and otherwise with the registered legacy schema for that
+//! version, whose `from_v<N>` migration upgrades the payload to the current type.
+//!
+//! Adding a field to a versioned type means incrementing its `SCHEMA_VERSION`,
+//! keeping the previous layout as a legacy schema and writing a `from_v<prev>`
+//! migration that sets the new field to its default.
+
+use crate::types::error::DsmError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Length of the schema version header in serialized bytes
+const VERSION_HEADER_LEN: usize = 2;
+
+/// Decoder for an older schema of `T`
+///
+/// Receives the payload bytes following the version header and returns the
+/// payload migrated to the current schema.
+pub type LegacyDecoder<T> = fn(&[u8]) -> Result<T, DsmError>;
+
+/// A type whose serialized form carries a schema version
+pub trait LegacySchema: Serialize + DeserializeOwned {
+    /// Schema version written by this build
+    const SCHEMA_VERSION: u16;
+
+    /// Older schema versions that can still be read, with their decoders
+    const LEGACY_SCHEMAS: &'static [(u16, LegacyDecoder<Self>)];
+}
+
+/// A payload tagged with the schema version it was serialized with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    /// Schema version of the payload
+    pub version: u16,
+
+    /// The versioned value
+    pub payload: T,
+}
+
+impl<T: LegacySchema> Versioned<T> {
+    /// Wrap a value in the current schema version
+    pub fn new(payload: T) -> Self {
+        Self {
+            version: T::SCHEMA_VERSION,
+            payload,
+        }
+    }
+
+    /// Serialize the version header followed by the payload
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DsmError> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Deserialize bytes written with the current or a registered older schema
+    ///
+    /// Older payloads are migrated, so the result always carries the current
+    /// schema version.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DsmError> {
+        let version = check_schema_compatibility(bytes)?;
+        let payload_bytes = &bytes[VERSION_HEADER_LEN..];
+
+        if version == T::SCHEMA_VERSION {
+            return Ok(Self::new(bincode::deserialize(payload_bytes)?));
+        }
+
+        let (_, decode) = T::LEGACY_SCHEMAS
+            .iter()
+            .find(|(legacy_version, _)| *legacy_version == version)
+            .ok_or_else(|| {
+                DsmError::serialization(
+                    format!(
+                        "Unsupported schema version {} (current version is {})",
+                        version,
+                        T::SCHEMA_VERSION
+                    ),
+                    None::<std::convert::Infallible>,
+                )
+            })?;
+
+        Ok(Self::new(decode(payload_bytes)?))
+    }
+}
+
+/// Read the schema version of serialized bytes without decoding the payload
+pub fn check_schema_compatibility(bytes: &[u8]) -> Result<u16, DsmError> {
+    let header = bytes
+        .get(..VERSION_HEADER_LEN)
+        .and_then(|header| <[u8; VERSION_HEADER_LEN]>::try_from(header).ok())
+        .ok_or_else(|| {
+            DsmError::serialization(
+                "Serialized data is too short to hold a schema version",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+    Ok(u16::from_le_bytes(header))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Schema 1 of `Record`, before `tags` was added
+    #[derive(Serialize, Deserialize)]
+    struct RecordV1 {
+        name: String,
+        count: u64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        name: String,
+        tags: Vec<String>,
+        count: u64,
+    }
+
+    impl Record {
+        fn from_v1(bytes: &[u8]) -> Result<Self, DsmError> {
+            let v1: RecordV1 = bincode::deserialize(bytes)?;
+            Ok(Self {
+                name: v1.name,
+                tags: Vec::new(),
+                count: v1.count,
+            })
+        }
+    }
+
+    impl LegacySchema for Record {
+        const SCHEMA_VERSION: u16 = 2;
+        const LEGACY_SCHEMAS: &'static [(u16, LegacyDecoder<Self>)] = &[(1, Self::from_v1)];
+    }
+
+    fn record() -> Record {
+        Record {
+            name: "record".to_string(),
+            tags: vec!["tag".to_string()],
+            count: 7,
+        }
+    }
+
+    #[test]
+    fn test_current_schema_round_trip() {
+        let bytes = Versioned::new(record()).to_bytes().unwrap();
+
+        assert_eq!(check_schema_compatibility(&bytes).unwrap(), 2);
+        let decoded = Versioned::<Record>::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.version, 2);
+        assert_eq!(decoded.payload, record());
+    }
+
+    #[test]
+    fn test_legacy_schema_is_migrated() {
+        let old = Versioned {
+            version: 1,
+            payload: RecordV1 {
+                name: "record".to_string(),
+                count: 7,
+            },
+        };
+        let bytes = bincode::serialize(&old).unwrap();
+
+        assert_eq!(check_schema_compatibility(&bytes).unwrap(), 1);
+        let decoded = Versioned::<Record>::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.version, Record::SCHEMA_VERSION);
+        assert_eq!(decoded.payload.tags, Vec::<String>::new());
+        assert_eq!(decoded.payload.count, 7);
+    }
+
+    #[test]
+    fn test_unknown_schema_rejected() {
+        let mut bytes = Versioned::new(record()).to_bytes().unwrap();
+        bytes[..VERSION_HEADER_LEN].copy_from_slice(&3u16.to_le_bytes());
+
+        assert!(Versioned::<Record>::deserialize(&bytes).is_err());
+        assert!(check_schema_compatibility(&[1]).is_err());
+    }
+}
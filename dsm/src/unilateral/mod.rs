@@ -8,7 +8,7 @@
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
-use tokio::fs;
+use crate::utils::async_fs as fs;
 use uuid::Uuid;
 
 /// Inbox entry for unilateral transactions
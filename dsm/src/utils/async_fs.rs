@@ -0,0 +1,53 @@
+//! Async file system calls
+//!
+//! Native targets re-export `tokio::fs`. tokio's `fs` feature does not build
+//! for wasm32, so there the same calls are thin async wrappers over
+//! `std::fs`, which runs on WASI and returns `Unsupported` in the browser.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use tokio::fs::{create_dir_all, read_dir, read_to_string, remove_file, write, ReadDir};
+
+#[cfg(target_arch = "wasm32")]
+pub use self::wasm::{create_dir_all, read_dir, read_to_string, remove_file, write, ReadDir};
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use std::fs::DirEntry;
+    use std::io;
+    use std::path::Path;
+
+    /// Recursively create a directory and all of its missing parents
+    pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    /// Write a whole file, replacing any previous contents
+    pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    /// Read a whole file into a string
+    pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    /// Remove a file
+    pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    /// Open a directory for iteration
+    pub async fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
+        std::fs::read_dir(path).map(ReadDir)
+    }
+
+    /// Directory iterator with the same interface as `tokio::fs::ReadDir`
+    pub struct ReadDir(std::fs::ReadDir);
+
+    impl ReadDir {
+        /// Return the next entry, or `None` once the directory is exhausted
+        pub async fn next_entry(&mut self) -> io::Result<Option<DirEntry>> {
+            self.0.next().transpose()
+        }
+    }
+}
@@ -6,14 +6,36 @@
 //!
 //! ## Sub-modules
 //!
+//! * `async_fs`: Async file system calls that also build for wasm32
 //! * `file`: File system operations and helpers
 //! * `serialization`: Data serialization and deserialization utilities
 //! * `time`: Time-related utilities and formatting functions
 
+pub mod async_fs;
 pub mod file;
 pub mod serialization;
 pub mod time;
 
+/// Create a runtime for driving async code from synchronous callers
+///
+/// Native targets get tokio's multi-threaded runtime. wasm32 has no threads,
+/// so it gets a current-thread runtime instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn blocking_runtime() -> std::io::Result<tokio::runtime::Runtime> {
+    tokio::runtime::Runtime::new()
+}
+
+/// Create a runtime for driving async code from synchronous callers
+///
+/// Native targets get tokio's multi-threaded runtime. wasm32 has no threads,
+/// so it gets a current-thread runtime instead.
+#[cfg(target_arch = "wasm32")]
+pub fn blocking_runtime() -> std::io::Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+}
+
 /// Convert a byte array to a hexadecimal string
 ///
 /// This function converts a raw byte array into a hexadecimal string representation,
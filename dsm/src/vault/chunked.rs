@@ -0,0 +1,254 @@
+//! Chunked Vault Content
+//!
+//! Contents too large to travel inside a vault are encrypted as usual and the
+//! ciphertext is split into fixed-size chunks, stored on storage nodes apart
+//! from the vault. The vault keeps only the ordered list of chunk hashes, and
+//! its signed parameters cover that list, so every chunk fetched from an
+//! untrusted node is checked before use and chunks cannot be swapped,
+//! reordered or dropped.
+
+use std::future::Future;
+
+use futures::stream::{self, Stream, TryStreamExt};
+
+use crate::crypto::blake3;
+use crate::types::error::DsmError;
+
+use super::EncryptedContent;
+
+/// Default size of a content chunk (1 MiB)
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Domain separator for the committed chunk hash list
+const CHUNK_LIST_DOMAIN: &[u8] = b"DSM_VAULT_CHUNK_LIST";
+
+/// Split a ciphertext into chunks of `chunk_size` bytes and hash each one
+///
+/// The last chunk holds the remainder and may be shorter.
+///
+/// # Returns
+/// * `Result<(Vec<[u8; 32]>, Vec<Vec<u8>>), DsmError>` - Chunk hashes and chunks, in order
+pub fn split_into_chunks(
+    data: &[u8],
+    chunk_size: usize,
+) -> Result<(Vec<[u8; 32]>, Vec<Vec<u8>>), DsmError> {
+    if chunk_size == 0 {
+        return Err(DsmError::invalid_parameter(
+            "Chunk size must be at least one byte",
+        ));
+    }
+
+    let chunks: Vec<Vec<u8>> = data.chunks(chunk_size).map(<[u8]>::to_vec).collect();
+    let chunk_hashes = chunks
+        .iter()
+        .map(|chunk| *blake3::hash(chunk).as_bytes())
+        .collect();
+
+    Ok((chunk_hashes, chunks))
+}
+
+/// Bytes a chunked vault's signed parameters commit to
+///
+/// Encodes the chunk hashes in order, prefixed with their count.
+pub fn chunk_list_bytes(chunk_hashes: &[[u8; 32]]) -> Vec<u8> {
+    let mut bytes = CHUNK_LIST_DOMAIN.to_vec();
+    bytes.extend_from_slice(&(chunk_hashes.len() as u64).to_le_bytes());
+    for hash in chunk_hashes {
+        bytes.extend_from_slice(hash);
+    }
+    bytes
+}
+
+/// Check a fetched chunk against the hash and length expected at `index`
+pub fn verify_chunk(
+    content: &EncryptedContent,
+    index: usize,
+    chunk: &[u8],
+) -> Result<(), DsmError> {
+    let EncryptedContent::Chunked {
+        chunk_hashes,
+        chunk_size,
+        total_len,
+        ..
+    } = content
+    else {
+        return Err(DsmError::invalid_parameter("Vault content is not chunked"));
+    };
+
+    let Some(expected_hash) = chunk_hashes.get(index) else {
+        return Err(DsmError::validation(
+            format!(
+                "Chunk {} is out of range for {} chunks",
+                index,
+                chunk_hashes.len()
+            ),
+            None::<std::convert::Infallible>,
+        ));
+    };
+
+    // Every chunk but the last is full; the last holds the remainder
+    let offset = (index as u64).saturating_mul(*chunk_size as u64);
+    let expected_len = total_len.saturating_sub(offset).min(*chunk_size as u64);
+    if chunk.len() as u64 != expected_len {
+        return Err(DsmError::validation(
+            format!(
+                "Chunk {} is {} bytes, expected {}",
+                index,
+                chunk.len(),
+                expected_len
+            ),
+            None::<std::convert::Infallible>,
+        ));
+    }
+
+    if !constant_time_eq::constant_time_eq(blake3::hash(chunk).as_bytes(), expected_hash) {
+        return Err(DsmError::validation(
+            format!("Chunk {} does not match its committed hash", index),
+            None::<std::convert::Infallible>,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Stream a vault's ciphertext, verifying each chunk as it arrives
+///
+/// Chunks are requested from `fetch_chunk` in order. The stream ends after
+/// the first error, so no chunk following a missing or tampered one is
+/// yielded. Single-blob content is yielded whole, without fetching.
+pub fn stream_content<'a, F, Fut>(
+    content: &'a EncryptedContent,
+    fetch_chunk: F,
+) -> impl Stream<Item = Result<Vec<u8>, DsmError>> + 'a
+where
+    F: FnMut(usize) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<u8>, DsmError>> + 'a,
+{
+    let chunk_count = match content {
+        EncryptedContent::Blob { .. } => 1,
+        EncryptedContent::Chunked { chunk_hashes, .. } => chunk_hashes.len(),
+    };
+
+    stream::unfold(
+        (0, fetch_chunk, false),
+        move |(index, mut fetch_chunk, failed)| async move {
+            if failed || index >= chunk_count {
+                return None;
+            }
+
+            let chunk = match content {
+                EncryptedContent::Blob { encrypted_data, .. } => Ok(encrypted_data.clone()),
+                EncryptedContent::Chunked { .. } => match fetch_chunk(index).await {
+                    Ok(chunk) => verify_chunk(content, index, &chunk).map(|()| chunk),
+                    Err(e) => Err(e),
+                },
+            };
+
+            let failed = chunk.is_err();
+            Some((chunk, (index + 1, fetch_chunk, failed)))
+        },
+    )
+}
+
+/// Reassemble a vault's ciphertext, verifying each chunk as it arrives
+///
+/// # Returns
+/// * `Result<Vec<u8>, DsmError>` - The complete ciphertext, ready to claim
+pub async fn assemble_content<F, Fut>(
+    content: &EncryptedContent,
+    fetch_chunk: F,
+) -> Result<Vec<u8>, DsmError>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>, DsmError>>,
+{
+    let mut assembled = Vec::new();
+    let mut chunks = std::pin::pin!(stream_content(content, fetch_chunk));
+    while let Some(chunk) = chunks.try_next().await? {
+        assembled.extend_from_slice(&chunk);
+    }
+
+    Ok(assembled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn chunked_content(data: &[u8], chunk_size: usize) -> (EncryptedContent, Vec<Vec<u8>>) {
+        let (chunk_hashes, chunks) = split_into_chunks(data, chunk_size).unwrap();
+        let content = EncryptedContent::Chunked {
+            encapsulated_key: vec![1, 2, 3, 4],
+            nonce: vec![0; 12],
+            aad: Vec::new(),
+            chunk_hashes,
+            chunk_size,
+            total_len: data.len() as u64,
+        };
+        (content, chunks)
+    }
+
+    #[test]
+    fn test_assemble_verifies_each_chunk() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let (content, chunks) = chunked_content(&data, 64);
+        assert_eq!(chunks.len(), 16);
+        assert_eq!(chunks[15].len(), 1000 - 15 * 64);
+
+        let fetch = |index: usize| {
+            let chunk = chunks[index].clone();
+            async move { Ok(chunk) }
+        };
+        let assembled = futures::executor::block_on(assemble_content(&content, fetch)).unwrap();
+        assert_eq!(assembled, data);
+
+        // A tampered chunk is rejected
+        let mut tampered = chunks.clone();
+        tampered[3][0] ^= 1;
+        let fetch = |index: usize| {
+            let chunk = tampered[index].clone();
+            async move { Ok(chunk) }
+        };
+        assert!(futures::executor::block_on(assemble_content(&content, fetch)).is_err());
+
+        // So is a truncated last chunk, even if its hash were to match
+        assert!(verify_chunk(&content, 15, &chunks[15][..10]).is_err());
+        assert!(verify_chunk(&content, 16, &chunks[0]).is_err());
+    }
+
+    #[test]
+    fn test_stream_stops_at_first_bad_chunk() {
+        let data = vec![7u8; 300];
+        let (content, mut chunks) = chunked_content(&data, 100);
+        chunks[0] = vec![8u8; 100];
+
+        let fetched = std::cell::Cell::new(0);
+        let fetch = |index: usize| {
+            fetched.set(fetched.get() + 1);
+            let chunk = chunks[index].clone();
+            async move { Ok(chunk) }
+        };
+        let results: Vec<_> =
+            futures::executor::block_on(stream_content(&content, fetch).collect::<Vec<_>>());
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+        assert_eq!(fetched.get(), 1);
+    }
+
+    #[test]
+    fn test_blob_content_streams_whole() {
+        let content = EncryptedContent::Blob {
+            encapsulated_key: vec![1, 2, 3, 4],
+            encrypted_data: vec![9; 10],
+            nonce: vec![0; 12],
+            aad: Vec::new(),
+        };
+        let fetch = |_: usize| async { Err::<Vec<u8>, _>(DsmError::invalid_parameter("unused")) };
+
+        let assembled = futures::executor::block_on(assemble_content(&content, fetch)).unwrap();
+        assert_eq!(assembled, vec![9; 10]);
+        assert!(split_into_chunks(&[1, 2, 3], 0).is_err());
+    }
+}
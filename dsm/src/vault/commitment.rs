@@ -0,0 +1,127 @@
+//! Vault Content Commitments
+//!
+//! A vault commits to its plaintext content with a Pedersen commitment under
+//! the network-wide vault content parameters. The blinding factor is
+//! encrypted together with the content: the sealed envelope holds the
+//! blinding factor followed by the content, so only whoever can decrypt the
+//! vault learns it. Claiming a vault opens the envelope and checks the
+//! decrypted content against the commitment, so ciphertext swapped by a
+//! storage node is refused rather than handed to the claimant.
+//!
+//! The blinding factor is derived from the creator's secret key and the vault
+//! ID, so a creator who kept the content can seal the same envelope again to
+//! re-encrypt the vault for a new recipient key, or hand the factor to a
+//! recipient out of band to be checked by `DLVManager::claim_vault_content`.
+//!
+//! Vaults created before envelopes existed (`envelope_version` 0) encrypt
+//! the bare content and have a commitment that cannot be opened.
+
+use num_bigint::BigUint;
+
+use crate::crypto::pedersen::{PedersenCommitment, PedersenParams};
+use crate::types::error::DsmError;
+
+use super::LimboVault;
+
+/// Length in bytes of a vault content blinding factor
+pub const BLINDING_FACTOR_LEN: usize = 32;
+
+/// Envelope version of vaults whose content is sealed with its blinding factor
+pub const CONTENT_ENVELOPE_VERSION: u8 = 1;
+
+/// Commit to vault content under a blinding factor
+pub(crate) fn commit_vault_content(
+    content: &[u8],
+    blinding_factor: &[u8; BLINDING_FACTOR_LEN],
+) -> Result<PedersenCommitment, DsmError> {
+    let params = PedersenParams::vault_content_params();
+    PedersenCommitment::commit_with_blinding(
+        params,
+        content,
+        &blinding_scalar(params, blinding_factor),
+    )
+}
+
+/// Derive the blinding factor of a vault's content from its creator's secret key
+pub(crate) fn derive_blinding_factor(
+    creator_secret_key: &[u8],
+    vault_id: &str,
+) -> [u8; BLINDING_FACTOR_LEN] {
+    let mut hasher = ::blake3::Hasher::new_derive_key("DSM vault content blinding factor");
+    hasher.update(&(creator_secret_key.len() as u64).to_le_bytes());
+    hasher.update(creator_secret_key);
+    hasher.update(vault_id.as_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+/// Seal content with its blinding factor, ready to be encrypted
+///
+/// # Returns
+/// * `Result<(Vec<u8>, PedersenCommitment), DsmError>` - The envelope and the content commitment
+pub(crate) fn seal_content_envelope(
+    content: &[u8],
+    blinding_factor: &[u8; BLINDING_FACTOR_LEN],
+) -> Result<(Vec<u8>, PedersenCommitment), DsmError> {
+    let commitment = commit_vault_content(content, blinding_factor)?;
+
+    let mut envelope = Vec::with_capacity(BLINDING_FACTOR_LEN + content.len());
+    envelope.extend_from_slice(blinding_factor);
+    envelope.extend_from_slice(content);
+    Ok((envelope, commitment))
+}
+
+/// Open a decrypted envelope and check its content against the vault's commitment
+///
+/// Content of vaults without an envelope is returned as decrypted.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The vault content
+/// * `Err(DsmError::CommitmentMismatch)` - If the content does not open the commitment
+pub fn open_content_envelope(vault: &LimboVault, envelope: &[u8]) -> Result<Vec<u8>, DsmError> {
+    match vault.envelope_version {
+        0 => return Ok(envelope.to_vec()),
+        CONTENT_ENVELOPE_VERSION => {}
+        version => {
+            return Err(DsmError::validation(
+                format!("Unsupported content envelope version {}", version),
+                None::<std::convert::Infallible>,
+            ))
+        }
+    }
+
+    match envelope.split_first_chunk::<BLINDING_FACTOR_LEN>() {
+        Some((blinding_factor, content))
+            if verify_vault_content_commitment(vault, content, blinding_factor) =>
+        {
+            Ok(content.to_vec())
+        }
+        _ => Err(DsmError::CommitmentMismatch {
+            vault_id: vault.id.clone(),
+        }),
+    }
+}
+
+/// Check that content opens a vault's content commitment
+///
+/// Recomputes the Pedersen commitment from `content` and `blinding_factor`
+/// and compares it with `vault.content_commitment`. The commitment covers
+/// the plaintext, so it survives re-encryption of the vault.
+pub fn verify_vault_content_commitment(
+    vault: &LimboVault,
+    content: &[u8],
+    blinding_factor: &[u8; BLINDING_FACTOR_LEN],
+) -> bool {
+    let params = PedersenParams::vault_content_params();
+    vault
+        .content_commitment
+        .verify(content, &blinding_scalar(params, blinding_factor), params)
+        .unwrap_or(false)
+}
+
+/// Reduce blinding factor bytes into the commitment's scalar field
+fn blinding_scalar(
+    params: &PedersenParams,
+    blinding_factor: &[u8; BLINDING_FACTOR_LEN],
+) -> BigUint {
+    BigUint::from_bytes_be(blinding_factor) % &params.q
+}
@@ -0,0 +1,85 @@
+//! Vault Content Updates
+//!
+//! When a vault's recipient rotates its Kyber key, the content encrypted to
+//! the old key can no longer be claimed. The creator, who kept the content,
+//! encrypts it again to the new key and publishes the new ciphertext as a
+//! content update. Each update is signed by the creator and numbers the
+//! recipient key generation it encrypts to, so storage nodes can refuse
+//! updates older than the ciphertext they hold.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::sphincs;
+use crate::types::error::DsmError;
+
+use super::EncryptedContent;
+
+/// Domain separator for vault content update signatures
+const CONTENT_UPDATE_DOMAIN: &[u8] = b"DSM_VAULT_CONTENT_UPDATE";
+
+/// Content of a vault re-encrypted to its recipient's new key, signed by the creator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultContentUpdate {
+    /// ID of the re-encrypted vault
+    pub vault_id: String,
+
+    /// Recipient key generation the content is encrypted to
+    pub recipient_key_generation: u32,
+
+    /// Kyber public key the content is encrypted to
+    pub new_recipient: Vec<u8>,
+
+    /// Content encrypted to `new_recipient`
+    pub encrypted_content: EncryptedContent,
+
+    /// SPHINCS+ public key of the creator who signed the update
+    pub creator: Vec<u8>,
+
+    /// Creator's SPHINCS+ signature over the update
+    pub creator_signature: Vec<u8>,
+}
+
+impl VaultContentUpdate {
+    /// Sign an update with the creator's SPHINCS+ keypair
+    pub fn sign(
+        vault_id: &str,
+        recipient_key_generation: u32,
+        new_recipient: &[u8],
+        encrypted_content: EncryptedContent,
+        creator_keypair: (&[u8], &[u8]),
+    ) -> Result<Self, DsmError> {
+        let mut update = Self {
+            vault_id: vault_id.to_string(),
+            recipient_key_generation,
+            new_recipient: new_recipient.to_vec(),
+            encrypted_content,
+            creator: creator_keypair.0.to_vec(),
+            creator_signature: Vec::new(),
+        };
+        update.creator_signature =
+            sphincs::sphincs_sign(creator_keypair.1, &update.signing_bytes())?;
+
+        Ok(update)
+    }
+
+    /// Verify the signature against the signing creator's public key
+    pub fn verify(&self) -> Result<bool, DsmError> {
+        sphincs::sphincs_verify(
+            &self.creator,
+            &self.signing_bytes(),
+            &self.creator_signature,
+        )
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let content = bincode::serialize(&self.encrypted_content).unwrap_or_default();
+        let mut bytes = CONTENT_UPDATE_DOMAIN.to_vec();
+        for field in [self.vault_id.as_bytes(), &self.new_recipient, &content] {
+            // Length-prefixed so the variable-length fields cannot be shifted
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.extend_from_slice(&self.recipient_key_generation.to_le_bytes());
+        bytes
+    }
+}
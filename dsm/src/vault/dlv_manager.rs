@@ -4,17 +4,131 @@
 //! providing functionality for creating, tracking, and interacting with vaults
 //! in a thread-safe manner.
 
-use super::{LimboVault, VaultState, FulfillmentMechanism, FulfillmentProof};
+use super::{
+    verify_vault_content_commitment, EncryptedContent, FulfillmentMechanism, FulfillmentProof,
+    LimboVault, OwnershipTransferRecord, PaymentLedger, RecipientReassignment, ShareClaim,
+    TimelockExtension, VaultContentUpdate, VaultPost, VaultSplit, VaultState, BLINDING_FACTOR_LEN,
+};
 use crate::types::{error::DsmError, state_types::State};
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex, RwLock},
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, RwLock, RwLockWriteGuard},
 };
 
+/// IDs of the vaults in each lifecycle state
+type StateIndex = HashMap<VaultStateKind, HashSet<String>>;
+
+/// Lifecycle state of a vault, without the state's details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VaultStateKind {
+    /// Waiting for its fulfillment condition
+    Limbo,
+
+    /// Condition fulfilled, content not yet claimed
+    Unlocked,
+
+    /// Content claimed
+    Claimed,
+
+    /// Invalidated by the creator
+    Invalidated,
+
+    /// Expired and returned to the creator
+    Reclaimed,
+}
+
+impl From<&VaultState> for VaultStateKind {
+    fn from(state: &VaultState) -> Self {
+        match state {
+            VaultState::Limbo => Self::Limbo,
+            VaultState::Unlocked { .. } => Self::Unlocked,
+            VaultState::Claimed { .. } => Self::Claimed,
+            VaultState::Invalidated { .. } => Self::Invalidated,
+            VaultState::Reclaimed { .. } => Self::Reclaimed,
+        }
+    }
+}
+
+/// Which vaults [`DLVManager::list_vaults`] returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultStateFilter {
+    /// Vaults waiting for their fulfillment condition
+    Limbo,
+
+    /// Vaults whose condition is fulfilled but content is unclaimed
+    Unlocked,
+
+    /// Vaults whose content was claimed
+    Claimed,
+
+    /// Vaults invalidated by their creator
+    Invalidated,
+
+    /// Vaults returned to their creator after expiring
+    Reclaimed,
+
+    /// Unresolved (`Limbo` or `Unlocked`) vaults whose `expires_at_time`
+    /// is before the given time (seconds since epoch)
+    ExpiringBefore(u64),
+}
+
+/// Overview of a vault held by a [`DLVManager`], without its content
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultSummaryLocal {
+    /// Vault ID
+    pub id: String,
+
+    /// Public key of the vault's current creator
+    pub creator_public_key: Vec<u8>,
+
+    /// Public key of the intended recipient (if specified)
+    pub intended_recipient: Option<Vec<u8>>,
+
+    /// Current vault state
+    pub state: VaultState,
+
+    /// Content type identifier
+    pub content_type: String,
+
+    /// State number the vault was created at
+    pub created_at_state: u64,
+
+    /// State number from which the creator may reclaim the vault
+    pub expires_at_state: Option<u64>,
+
+    /// Time (seconds since epoch) from which the creator may reclaim the vault
+    pub expires_at_time: Option<u64>,
+}
+
+impl From<&LimboVault> for VaultSummaryLocal {
+    fn from(vault: &LimboVault) -> Self {
+        Self {
+            id: vault.id.clone(),
+            creator_public_key: vault.creator().to_vec(),
+            intended_recipient: vault.intended_recipient.clone(),
+            state: vault.state.clone(),
+            content_type: vault.content_type.clone(),
+            created_at_state: vault.created_at_state,
+            expires_at_state: vault.expires_at_state,
+            expires_at_time: vault.expires_at_time,
+        }
+    }
+}
+
 /// Manages Limbo Vaults
 pub struct DLVManager {
     /// Vaults managed by this instance, keyed by vault ID
     vaults: RwLock<HashMap<String, Arc<Mutex<LimboVault>>>>,
+
+    /// IDs of the vaults in each lifecycle state
+    ///
+    /// Updated while the transitioning vault is still locked, so each
+    /// transition moves a vault between buckets exactly once. Locked after
+    /// `vaults` or a vault, never before.
+    state_index: RwLock<StateIndex>,
+
+    /// Ledger that the `TokenPayment` vaults created here check payments against
+    payment_ledger: Option<Arc<dyn PaymentLedger>>,
 }
 
 impl DLVManager {
@@ -22,9 +136,19 @@ impl DLVManager {
     pub fn new() -> Self {
         Self {
             vaults: RwLock::new(HashMap::new()),
+            state_index: RwLock::new(HashMap::new()),
+            payment_ledger: None,
         }
     }
 
+    /// Check the token payments of vaults created by this manager against `ledger`
+    ///
+    /// Without a ledger, `TokenPayment` vaults cannot be unlocked.
+    pub fn with_payment_ledger(mut self, ledger: Arc<dyn PaymentLedger>) -> Self {
+        self.payment_ledger = Some(ledger);
+        self
+    }
+
     /// Create a new vault
     pub fn create_vault(
         &self,
@@ -44,21 +168,38 @@ pub fn create_vault(
             reference_state,
         )?;
 
-        let vault_id = vault.id.clone();
-
-        let mut vaults = self.vaults.write().map_err(|_| {
-            DsmError::internal(
-                "Failed to acquire write lock on vaults",
-                None::<std::convert::Infallible>,
-            )
-        })?;
+        self.insert_vault(vault)
+    }
 
-        vaults.insert(vault_id.clone(), Arc::new(Mutex::new(vault)));
+    /// Create a vault whose recipients each claim their share of `split`
+    ///
+    /// See [`LimboVault::with_split`].
+    pub fn create_splittable_vault(
+        &self,
+        creator_keypair: (&[u8], &[u8]),
+        condition: FulfillmentMechanism,
+        content: &[u8],
+        content_type: &str,
+        split: VaultSplit,
+        reference_state: &State,
+    ) -> Result<String, DsmError> {
+        let vault = LimboVault::new(
+            creator_keypair,
+            condition,
+            content,
+            content_type,
+            None,
+            reference_state,
+        )?
+        .with_split(split, creator_keypair.1)?;
 
-        Ok(vault_id)
+        self.insert_vault(vault)
     }
 
     /// Get a vault by ID
+    ///
+    /// State transitions must go through the manager's methods, which keep
+    /// the state index up to date, rather than through the returned lock.
     pub fn get_vault(&self, vault_id: &str) -> Result<Arc<Mutex<LimboVault>>, DsmError> {
         let vaults = self.vaults.read().map_err(|_| {
             DsmError::internal(
@@ -75,16 +216,113 @@ pub fn get_vault(&self, vault_id: &str) -> Result<Arc<Mutex<LimboVault>>, DsmErr
         })
     }
 
-    /// List all vault IDs
-    pub fn list_vaults(&self) -> Result<Vec<String>, DsmError> {
-        let vaults = self.vaults.read().map_err(|_| {
+    /// Manage a vault, replacing any held under its ID
+    ///
+    /// Also rebuilds the managed vaults from saved ones after a restart: the
+    /// vault is indexed under its current state and checks payments against
+    /// this manager's ledger.
+    pub fn insert_vault(&self, mut vault: LimboVault) -> Result<String, DsmError> {
+        vault.payment_ledger = self.payment_ledger.clone();
+
+        let vault_id = vault.id.clone();
+
+        let mut vaults = self.vaults.write().map_err(|_| {
             DsmError::internal(
-                "Failed to acquire read lock on vaults",
+                "Failed to acquire write lock on vaults",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        let mut index = self.index_mut()?;
+        for ids in index.values_mut() {
+            ids.remove(&vault_id);
+        }
+        index
+            .entry(VaultStateKind::from(&vault.state))
+            .or_default()
+            .insert(vault_id.clone());
+        vaults.insert(vault_id.clone(), Arc::new(Mutex::new(vault)));
+
+        Ok(vault_id)
+    }
+
+    /// List the vaults matching `filter`, from the state index
+    ///
+    /// A vault that changes state while the list is built is left out if it
+    /// no longer matches.
+    pub fn list_vaults(
+        &self,
+        filter: VaultStateFilter,
+    ) -> Result<Vec<VaultSummaryLocal>, DsmError> {
+        let kinds: &[VaultStateKind] = match filter {
+            VaultStateFilter::Limbo => &[VaultStateKind::Limbo],
+            VaultStateFilter::Unlocked => &[VaultStateKind::Unlocked],
+            VaultStateFilter::Claimed => &[VaultStateKind::Claimed],
+            VaultStateFilter::Invalidated => &[VaultStateKind::Invalidated],
+            VaultStateFilter::Reclaimed => &[VaultStateKind::Reclaimed],
+            VaultStateFilter::ExpiringBefore(_) => {
+                &[VaultStateKind::Limbo, VaultStateKind::Unlocked]
+            }
+        };
+
+        // Collect the IDs first: vaults are locked before the index, never after
+        let ids: Vec<String> = {
+            let index = self.state_index.read().map_err(|_| {
+                DsmError::internal(
+                    "Failed to acquire read lock on vault state index",
+                    None::<std::convert::Infallible>,
+                )
+            })?;
+            kinds
+                .iter()
+                .filter_map(|kind| index.get(kind))
+                .flatten()
+                .cloned()
+                .collect()
+        };
+
+        let mut summaries = Vec::new();
+        for id in ids {
+            let vault_lock = self.get_vault(&id)?;
+            let vault = vault_lock.lock().map_err(|_| {
+                DsmError::internal(
+                    "Failed to acquire lock on vault",
+                    None::<std::convert::Infallible>,
+                )
+            })?;
+
+            let matches = kinds.contains(&VaultStateKind::from(&vault.state))
+                && match filter {
+                    VaultStateFilter::ExpiringBefore(time) => {
+                        vault.expires_at_time.is_some_and(|expiry| expiry < time)
+                    }
+                    _ => true,
+                };
+            if matches {
+                summaries.push(VaultSummaryLocal::from(&*vault));
+            }
+        }
+        summaries.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Ok(summaries)
+    }
+
+    /// Number of vaults in each lifecycle state
+    ///
+    /// States without vaults are left out.
+    pub fn count_by_state(&self) -> Result<HashMap<VaultStateKind, usize>, DsmError> {
+        let index = self.state_index.read().map_err(|_| {
+            DsmError::internal(
+                "Failed to acquire read lock on vault state index",
                 None::<std::convert::Infallible>,
             )
         })?;
 
-        Ok(vaults.keys().cloned().collect())
+        Ok(index
+            .iter()
+            .filter(|(_, ids)| !ids.is_empty())
+            .map(|(kind, ids)| (*kind, ids.len()))
+            .collect())
     }
 
     /// Get vaults by status
@@ -115,35 +353,265 @@ pub fn try_unlock_vault(
         requester: &[u8],
         reference_state: &State,
     ) -> Result<bool, DsmError> {
-        let vault_lock = self.get_vault(vault_id)?;
-        let mut vault = vault_lock.lock().map_err(|_| {
-            DsmError::internal(
-                "Failed to acquire lock on vault",
-                None::<std::convert::Infallible>,
-            )
-        })?;
+        self.with_vault(vault_id, |vault| {
+            vault.unlock(proof, requester, reference_state)
+        })
+    }
 
-        vault.unlock(proof, requester, reference_state)
+    /// Push back the unlock time of a `TimeRelease` vault
+    ///
+    /// Signs an extension that moves the unlock time `additional_time` later,
+    /// in the units of the vault's unlock time, and applies it to the local
+    /// vault. The returned extension is meant to be published to the storage
+    /// nodes holding the vault, which check it the same way.
+    ///
+    /// Fails once the unlock time has passed, as the vault is claimable then.
+    pub fn extend_vault_timelock(
+        &self,
+        vault_id: &str,
+        additional_time: u64,
+        creator_keypair: (&[u8], &[u8]),
+        current_state: &State,
+    ) -> Result<TimelockExtension, DsmError> {
+        self.with_vault(vault_id, |vault| {
+            if vault.creator() != creator_keypair.0 {
+                return Err(DsmError::validation(
+                    "Only the vault creator can extend its time lock",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+
+            let unlock_time = vault.unlock_time().ok_or_else(|| {
+                DsmError::validation(
+                    "Only TimeRelease vaults have a time lock to extend",
+                    None::<std::convert::Infallible>,
+                )
+            })?;
+            let new_unlock_time = unlock_time
+                .checked_add(additional_time)
+                .ok_or_else(|| DsmError::invalid_parameter("Extended unlock time overflows"))?;
+
+            let extension = TimelockExtension::sign(
+                vault_id,
+                new_unlock_time,
+                current_state.state_number,
+                creator_keypair.1,
+            )?;
+            vault.extend_timelock(extension.clone(), current_state)?;
+
+            Ok(extension)
+        })
+    }
+
+    /// Apply a time-lock extension signed by a vault's creator
+    pub fn apply_timelock_extension(
+        &self,
+        extension: TimelockExtension,
+        current_state: &State,
+    ) -> Result<(), DsmError> {
+        self.with_vault(&extension.vault_id, |vault| {
+            vault.extend_timelock(extension, current_state)
+        })
+    }
+
+    /// Hand a vault's creator rights to another identity
+    ///
+    /// Signs a transfer to `new_creator_public_key` with the current creator's
+    /// keypair and applies it to the local vault. From then on only the new
+    /// creator can extend the vault's time lock or invalidate it. The returned
+    /// record is meant to be published to the storage nodes holding the vault.
+    pub fn transfer_vault_ownership(
+        &self,
+        vault_id: &str,
+        new_creator_public_key: &[u8],
+        old_creator_keypair: (&[u8], &[u8]),
+        current_state: &State,
+    ) -> Result<OwnershipTransferRecord, DsmError> {
+        self.with_vault(vault_id, |vault| {
+            if vault.creator() != old_creator_keypair.0 {
+                return Err(DsmError::validation(
+                    "Only the vault creator can transfer its ownership",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+
+            let transfer = OwnershipTransferRecord::sign(
+                vault_id,
+                new_creator_public_key,
+                current_state.state_number,
+                old_creator_keypair,
+            )?;
+            vault.transfer_ownership(transfer.clone())?;
+
+            Ok(transfer)
+        })
+    }
+
+    /// Apply an ownership transfer signed by a vault's creator
+    pub fn apply_ownership_transfer(
+        &self,
+        transfer: OwnershipTransferRecord,
+    ) -> Result<(), DsmError> {
+        self.with_vault(&transfer.vault_id, |vault| {
+            vault.transfer_ownership(transfer)
+        })
+    }
+
+    /// Redirect an unclaimed vault to a new recipient
+    ///
+    /// `creator_signature` is the creator's signature over
+    /// [`RecipientReassignment::signing_message`]. The returned record is meant
+    /// to be published to the storage nodes holding the vault.
+    pub fn reassign_vault_recipient(
+        &self,
+        vault_id: &str,
+        new_recipient_public_key: &[u8],
+        creator_signature: &[u8],
+        reference_state: &State,
+    ) -> Result<RecipientReassignment, DsmError> {
+        self.with_vault(vault_id, |vault| {
+            vault.reassign_recipient(new_recipient_public_key, creator_signature, reference_state)
+        })
+    }
+
+    /// Re-encrypt a vault's content to its creator's rotated key
+    ///
+    /// See [`LimboVault::reencrypt_for_creator`]. The vault is replaced only
+    /// once it has been re-encrypted and re-signed, so a failure leaves it as
+    /// it was.
+    pub fn reencrypt_vault_content(
+        &self,
+        vault_id: &str,
+        old_keypair: (&[u8], &[u8]),
+        new_keypair: (&[u8], &[u8]),
+        current_state: &State,
+    ) -> Result<(), DsmError> {
+        self.reencrypt_and_publish_vault_content(
+            vault_id,
+            old_keypair,
+            new_keypair,
+            current_state,
+            |_| Ok(()),
+        )
+    }
+
+    /// Re-encrypt a vault's content and publish it before replacing it here
+    ///
+    /// `publish` receives the re-encrypted vault, for example to write it to
+    /// the storage nodes holding the vault. If publishing fails, the vault is
+    /// left as it was. The vault stays locked throughout, so a concurrent
+    /// re-encryption finds the rotated key and fails.
+    pub fn reencrypt_and_publish_vault_content(
+        &self,
+        vault_id: &str,
+        old_keypair: (&[u8], &[u8]),
+        new_keypair: (&[u8], &[u8]),
+        current_state: &State,
+        publish: impl FnOnce(&LimboVault) -> Result<(), DsmError>,
+    ) -> Result<(), DsmError> {
+        self.with_vault(vault_id, |vault| {
+            let reencrypted =
+                vault.reencrypt_for_creator(old_keypair, new_keypair, current_state)?;
+            publish(&reencrypted)?;
+            *vault = reencrypted;
+
+            Ok(())
+        })
+    }
+
+    /// Re-encrypt a vault's content to its recipient's new Kyber key
+    ///
+    /// See [`LimboVault::reencrypt_for_recipient`]; `content` is the content
+    /// the creator kept. The vault is replaced only once it has been
+    /// re-encrypted and re-signed. The returned update is meant to be
+    /// published to the storage nodes holding the vault, which refuse
+    /// updates that are not newer than the ciphertext they hold.
+    pub fn reencrypt_vault_for(
+        &self,
+        vault_id: &str,
+        new_recipient_kyber_pk: &[u8],
+        creator_keypair: (&[u8], &[u8]),
+        content: &[u8],
+    ) -> Result<VaultContentUpdate, DsmError> {
+        self.with_vault(vault_id, |vault| {
+            let (reencrypted, update) =
+                vault.reencrypt_for_recipient(new_recipient_kyber_pk, creator_keypair, content)?;
+            *vault = reencrypted;
+
+            Ok(update)
+        })
     }
 
     /// Claim vault content
+    ///
+    /// A splittable vault yields only the claimant's share, as a serialized
+    /// [`ShareClaim`]; see [`Self::claim_vault_share`]. Other vaults yield
+    /// their decrypted content once it is checked against the vault's content
+    /// commitment with the blinding factor sealed alongside it; content that
+    /// does not open it fails the claim with `CommitmentMismatch`.
+    ///
+    /// A blinding factor received from the creator out of band can be given
+    /// as well. The content must then also open the commitment under that
+    /// factor, or the claim fails with `CommitmentMismatch` and the vault is
+    /// left unlocked. Shares carry no content to check, so a splittable vault
+    /// cannot be claimed with a blinding factor.
     pub fn claim_vault_content(
         &self,
         vault_id: &str,
         claimant: &[u8],
         reference_state: &State,
+        blinding_factor: Option<&[u8; BLINDING_FACTOR_LEN]>,
     ) -> Result<Vec<u8>, DsmError> {
-        let vault_lock = self.get_vault(vault_id)?;
-        let mut vault = vault_lock.lock().map_err(|_| {
-            DsmError::internal(
-                "Failed to acquire lock on vault",
-                None::<std::convert::Infallible>,
-            )
-        })?;
+        self.with_vault(vault_id, |vault| {
+            if vault.split.is_some() {
+                if blinding_factor.is_some() {
+                    return Err(DsmError::validation(
+                        format!(
+                            "Shares of splittable vault {} carry no content to check against a \
+                             blinding factor",
+                            vault.id
+                        ),
+                        None::<std::convert::Infallible>,
+                    ));
+                }
+                let share = vault.claim_share(claimant, reference_state)?;
+                return bincode::serialize(&share).map_err(|e| {
+                    DsmError::serialization("Failed to serialize share claim", Some(e))
+                });
+            }
+
+            let Some(blinding_factor) = blinding_factor else {
+                return vault
+                    .claim(claimant, reference_state)
+                    .map(|result| result.content);
+            };
 
-        vault
-            .claim(claimant, reference_state)
-            .map(|result| result.content)
+            // Claim a copy, so content the given factor does not open leaves
+            // the vault unlocked
+            let result = vault.clone().claim(claimant, reference_state)?;
+            if !verify_vault_content_commitment(&result.vault, &result.content, blinding_factor) {
+                return Err(DsmError::CommitmentMismatch {
+                    vault_id: vault.id.clone(),
+                });
+            }
+            *vault = result.vault;
+            Ok(result.content)
+        })
+    }
+
+    /// Claim a recipient's share of a splittable vault
+    ///
+    /// Each recipient can claim its allocation once; the vault is `Claimed`
+    /// once every allocation is.
+    pub fn claim_vault_share(
+        &self,
+        vault_id: &str,
+        claimant: &[u8],
+        reference_state: &State,
+    ) -> Result<ShareClaim, DsmError> {
+        self.with_vault(vault_id, |vault| {
+            vault.claim_share(claimant, reference_state)
+        })
     }
 
     /// Invalidate a vault
@@ -154,23 +622,50 @@ pub fn invalidate_vault(
         creator_private_key: &[u8],
         reference_state: &State,
     ) -> Result<(), DsmError> {
-        let vault_lock = self.get_vault(vault_id)?;
-        let mut vault = vault_lock.lock().map_err(|_| {
-            DsmError::internal(
-                "Failed to acquire lock on vault",
-                None::<std::convert::Infallible>,
-            )
-        })?;
+        self.with_vault(vault_id, |vault| {
+            vault.invalidate(reason, creator_private_key, reference_state)
+        })
+    }
 
-        vault.invalidate(reason, creator_private_key, reference_state)
+    /// Invalidate a vault with the creator's signature over its invalidation
+    ///
+    /// See [`LimboVault::invalidate_with_signature`].
+    pub fn invalidate_vault_with_signature(
+        &self,
+        vault_id: &str,
+        reason: &str,
+        creator_signature: &[u8],
+        state_number: u64,
+    ) -> Result<(), DsmError> {
+        self.with_vault(vault_id, |vault| {
+            vault.invalidate_with_signature(reason, creator_signature, state_number)
+        })
+    }
+
+    /// Return an expired vault to its creator, given the creator's reclaim signature
+    pub fn reclaim_expired_vault(
+        &self,
+        vault_id: &str,
+        creator_signature: &[u8],
+        reference_state: &State,
+    ) -> Result<EncryptedContent, DsmError> {
+        self.with_vault(vault_id, |vault| {
+            vault.reclaim_expired(creator_signature, reference_state)
+        })
     }
 
-    /// Create a vault post
+    /// Create a vault post signed by the vault creator
+    ///
+    /// `creator_keypair` is `(public_key, private_key)`; the public key must
+    /// be the vault's current creator and the private key must belong to its
+    /// signing key (see [`LimboVault::signing_key`]). Readers check the post
+    /// with [`VaultPost::verify`] or [`Self::verify_vault_post`].
     pub fn create_vault_post(
         &self,
         vault_id: &str,
         purpose: &str,
         timeout: Option<u64>,
+        creator_keypair: (&[u8], &[u8]),
     ) -> Result<Vec<u8>, DsmError> {
         let vault_lock = self.get_vault(vault_id)?;
         let vault = vault_lock.lock().map_err(|_| {
@@ -180,10 +675,81 @@ pub fn create_vault_post(
             )
         })?;
 
-        let post = vault.to_vault_post(purpose, timeout)?;
+        if creator_keypair.0 != vault.creator() {
+            return Err(DsmError::verification(
+                "Only the vault creator can sign its post",
+            ));
+        }
+
+        let mut post = vault.to_vault_post(purpose, timeout)?;
+        post.sign(creator_keypair.1)?;
+        post.verify(vault.signing_key()).map_err(|_| {
+            DsmError::verification("Secret key does not belong to the vault's signing key")
+        })?;
         bincode::serialize(&post)
             .map_err(|e| DsmError::serialization("Failed to serialize vault post", Some(e)))
     }
+
+    /// Verify a vault post against the signing key of the vault held here
+    ///
+    /// The key is read from the managed vault rather than from the post, so
+    /// a forged post cannot vouch for itself.
+    pub fn verify_vault_post(&self, post: &VaultPost) -> Result<(), DsmError> {
+        let vault_lock = self.get_vault(&post.vault_id)?;
+        let signing_key = vault_lock
+            .lock()
+            .map_err(|_| {
+                DsmError::internal(
+                    "Failed to acquire lock on vault",
+                    None::<std::convert::Infallible>,
+                )
+            })?
+            .signing_key()
+            .to_vec();
+
+        post.verify(&signing_key)
+    }
+
+    /// Run `f` on a locked vault, moving it to its new state's index bucket
+    ///
+    /// The index is updated before the vault is unlocked, whether or not `f`
+    /// succeeds, so concurrent transitions of a vault are indexed in the
+    /// order they happen.
+    fn with_vault<T>(
+        &self,
+        vault_id: &str,
+        f: impl FnOnce(&mut LimboVault) -> Result<T, DsmError>,
+    ) -> Result<T, DsmError> {
+        let vault_lock = self.get_vault(vault_id)?;
+        let mut vault = vault_lock.lock().map_err(|_| {
+            DsmError::internal(
+                "Failed to acquire lock on vault",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        let before = VaultStateKind::from(&vault.state);
+        let result = f(&mut *vault);
+        let after = VaultStateKind::from(&vault.state);
+        if before != after {
+            let mut index = self.index_mut()?;
+            if let Some(ids) = index.get_mut(&before) {
+                ids.remove(vault_id);
+            }
+            index.entry(after).or_default().insert(vault_id.to_string());
+        }
+
+        result
+    }
+
+    fn index_mut(&self) -> Result<RwLockWriteGuard<'_, StateIndex>, DsmError> {
+        self.state_index.write().map_err(|_| {
+            DsmError::internal(
+                "Failed to acquire write lock on vault state index",
+                None::<std::convert::Infallible>,
+            )
+        })
+    }
 }
 
 impl Default for DLVManager {
@@ -191,3 +757,1326 @@ fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{kyber, sphincs};
+    use crate::core::state_machine::transition::create_next_state;
+    use crate::types::operations::{Operation, TransactionMode, VerificationType};
+    use crate::types::state_types::DeviceInfo;
+    use crate::types::token_types::{Balance, Ratio};
+    use crate::vault::commitment::{
+        commit_vault_content, derive_blinding_factor, open_content_envelope,
+        CONTENT_ENVELOPE_VERSION,
+    };
+    use crate::vault::{MemoryPaymentLedger, SplitAllocation};
+
+    /// Reference state shared by every time proof in these tests
+    const REFERENCE: [u8; 32] = [7; 32];
+
+    fn state_at(state_number: u64) -> State {
+        let mut state = State::new_genesis(vec![1, 2, 3, 4], DeviceInfo::new("creator", vec![1]));
+        state.state_number = state_number;
+        state.hash = vec![9; 32];
+        state
+    }
+
+    fn time_proof() -> FulfillmentProof {
+        // Find a proof the simplified state reference check accepts
+        let state_proof = (0u8..=255)
+            .map(|nonce| vec![nonce])
+            .find(|proof| {
+                let hash = ::blake3::hash(&[&REFERENCE[..], proof, &state_at(0).hash].concat());
+                hash.as_bytes()[0] < 128
+            })
+            .unwrap();
+
+        FulfillmentProof::TimeProof {
+            reference_state: REFERENCE.to_vec(),
+            state_proof,
+        }
+    }
+
+    fn time_locked_vault(manager: &DLVManager, creator: &(Vec<u8>, Vec<u8>)) -> String {
+        manager
+            .create_vault(
+                (&creator.0, &creator.1),
+                FulfillmentMechanism::TimeRelease {
+                    unlock_time: 10,
+                    reference_states: vec![REFERENCE.to_vec()],
+                },
+                b"delayed distribution",
+                "text/plain",
+                None,
+                &state_at(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extended_timelock_delays_unlock() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let extension =
+            manager.extend_vault_timelock(&vault_id, 10, (&creator.0, &creator.1), &state_at(5))?;
+        assert_eq!(extension.new_unlock_time, 20);
+        assert!(extension.verify(&creator.0)?);
+
+        // The original unlock time no longer opens the vault
+        assert!(!manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(15))?);
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(20))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extension_rejected_after_unlock_time() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let result =
+            manager.extend_vault_timelock(&vault_id, 10, (&creator.0, &creator.1), &state_at(10));
+        assert!(result.is_err());
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(10))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extension_requires_creator_signature() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let result =
+            manager.extend_vault_timelock(&vault_id, 10, (&intruder.0, &intruder.1), &state_at(5));
+        assert!(result.is_err());
+
+        // A forged extension is rejected when applied directly
+        let forged = TimelockExtension::sign(&vault_id, 20, 5, &intruder.1)?;
+        assert!(manager
+            .apply_timelock_extension(forged, &state_at(5))
+            .is_err());
+
+        let genuine = TimelockExtension::sign(&vault_id, 20, 5, &creator.1)?;
+        manager.apply_timelock_extension(genuine, &state_at(5))?;
+        let vault_lock = manager.get_vault(&vault_id)?;
+        assert_eq!(vault_lock.lock().unwrap().unlock_time(), Some(20));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalidation_requires_creator_signature() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let message = LimboVault::invalidation_message(&vault_id, "fraud", &state_at(0).hash);
+        let forged = sphincs::sphincs_sign(&intruder.1, &message)?;
+        assert!(manager
+            .invalidate_vault_with_signature(&vault_id, "fraud", &forged, 5)
+            .is_err());
+
+        let genuine = sphincs::sphincs_sign(&creator.1, &message)?;
+        manager.invalidate_vault_with_signature(&vault_id, "fraud", &genuine, 5)?;
+        assert!(matches!(
+            manager.get_vault(&vault_id)?.lock().unwrap().state,
+            VaultState::Invalidated {
+                invalidated_state_number: 5,
+                ..
+            }
+        ));
+
+        // An invalidated vault never unlocks, and is not invalidated twice
+        assert!(manager
+            .try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(10))
+            .is_err());
+        assert!(manager
+            .invalidate_vault_with_signature(&vault_id, "fraud", &genuine, 6)
+            .is_err());
+
+        Ok(())
+    }
+
+    fn reassignment_signature(
+        vault_id: &str,
+        old_recipient: Option<&[u8]>,
+        new_recipient: &[u8],
+        creator_secret_key: &[u8],
+    ) -> Result<Vec<u8>, DsmError> {
+        let message = RecipientReassignment::signing_message(
+            vault_id,
+            old_recipient,
+            new_recipient,
+            &state_at(5).hash,
+        );
+        sphincs::sphincs_sign(creator_secret_key, &message)
+    }
+
+    #[test]
+    fn test_creator_reassigns_unclaimed_vault() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+        let vault_lock = manager.get_vault(&vault_id)?;
+        let positions = vault_lock.lock().unwrap().verification_positions.clone();
+
+        let forged = reassignment_signature(&vault_id, None, b"recipient-a", &intruder.1)?;
+        assert!(manager
+            .reassign_vault_recipient(&vault_id, b"recipient-a", &forged, &state_at(5))
+            .is_err());
+
+        let genuine = reassignment_signature(&vault_id, None, b"recipient-a", &creator.1)?;
+        let reassignment =
+            manager.reassign_vault_recipient(&vault_id, b"recipient-a", &genuine, &state_at(5))?;
+        assert!(reassignment.verify()?);
+
+        // Replaying the first reassignment no longer matches the current recipient
+        let recipient_a = Some(&b"recipient-a"[..]);
+        let next = reassignment_signature(&vault_id, recipient_a, b"recipient-b", &creator.1)?;
+        manager.reassign_vault_recipient(&vault_id, b"recipient-b", &next, &state_at(5))?;
+        assert!(manager
+            .reassign_vault_recipient(&vault_id, b"recipient-a", &genuine, &state_at(5))
+            .is_err());
+
+        let mut vault = vault_lock.lock().unwrap();
+        assert_eq!(vault.intended_recipient, Some(b"recipient-b".to_vec()));
+        assert_eq!(vault.original_recipient(), None);
+        assert_eq!(vault.recipient_reassignments.len(), 2);
+        assert_ne!(vault.verification_positions, positions);
+        assert!(vault.verify()?);
+
+        // Only the signed reassignments can change the recipient
+        vault.intended_recipient = Some(b"recipient-c".to_vec());
+        assert!(!vault.verify()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolved_vault_cannot_be_reassigned() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let message = LimboVault::invalidation_message(&vault_id, "fraud", &state_at(0).hash);
+        let invalidation = sphincs::sphincs_sign(&creator.1, &message)?;
+        manager.invalidate_vault_with_signature(&vault_id, "fraud", &invalidation, 5)?;
+
+        let signature = reassignment_signature(&vault_id, None, b"recipient-a", &creator.1)?;
+        assert!(manager
+            .reassign_vault_recipient(&vault_id, b"recipient-a", &signature, &state_at(5))
+            .is_err());
+        let vault_lock = manager.get_vault(&vault_id)?;
+        assert!(vault_lock.lock().unwrap().intended_recipient.is_none());
+
+        Ok(())
+    }
+
+    fn expire_at(
+        manager: &DLVManager,
+        vault_id: &str,
+        state_number: u64,
+        creator: &(Vec<u8>, Vec<u8>),
+    ) -> Result<(), DsmError> {
+        let vault_lock = manager.get_vault(vault_id)?;
+        let mut vault = vault_lock.lock().unwrap();
+        *vault = vault
+            .clone()
+            .with_expiry(Some(state_number), None, &creator.1)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_creator_reclaims_expired_vault() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+        expire_at(&manager, &vault_id, 30, &creator)?;
+
+        let early = LimboVault::reclaim_message(&vault_id, &state_at(20));
+        let early = sphincs::sphincs_sign(&creator.1, &early)?;
+        assert!(manager
+            .reclaim_expired_vault(&vault_id, &early, &state_at(20))
+            .is_err());
+
+        let message = LimboVault::reclaim_message(&vault_id, &state_at(30));
+        let forged = sphincs::sphincs_sign(&intruder.1, &message)?;
+        assert!(manager
+            .reclaim_expired_vault(&vault_id, &forged, &state_at(30))
+            .is_err());
+
+        let genuine = sphincs::sphincs_sign(&creator.1, &message)?;
+        manager.reclaim_expired_vault(&vault_id, &genuine, &state_at(30))?;
+        assert!(matches!(
+            manager.get_vault(&vault_id)?.lock().unwrap().state,
+            VaultState::Reclaimed {
+                reclaimed_state_number: 30,
+                ..
+            }
+        ));
+        assert!(manager
+            .reclaim_expired_vault(&vault_id, &genuine, &state_at(30))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_vault_cannot_be_unlocked_or_claimed() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+        expire_at(&manager, &vault_id, 30, &creator)?;
+        assert!(manager.get_vault(&vault_id)?.lock().unwrap().verify()?);
+
+        assert!(manager
+            .try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(30))
+            .is_err());
+
+        // Unlocked in time but not claimed before the vault expired
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(10))?);
+        assert!(manager
+            .claim_vault_content(&vault_id, b"claimant", &state_at(30), None)
+            .is_err());
+
+        Ok(())
+    }
+
+    fn split_vault(manager: &DLVManager, creator: &(Vec<u8>, Vec<u8>)) -> Result<String, DsmError> {
+        let split = VaultSplit::new(
+            "ROOT",
+            1_000,
+            vec![
+                SplitAllocation {
+                    recipient: b"alice".to_vec(),
+                    share: Ratio::new(3, 5),
+                },
+                SplitAllocation {
+                    recipient: b"bob".to_vec(),
+                    share: Ratio::new(2, 5),
+                },
+            ],
+        )?;
+        manager.create_splittable_vault(
+            (&creator.0, &creator.1),
+            FulfillmentMechanism::TimeRelease {
+                unlock_time: 10,
+                reference_states: vec![REFERENCE.to_vec()],
+            },
+            b"shared rewards",
+            "text/plain",
+            split,
+            &state_at(0),
+        )
+    }
+
+    #[test]
+    fn test_recipients_claim_their_own_shares() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = split_vault(&manager, &creator)?;
+        assert!(manager.get_vault(&vault_id)?.lock().unwrap().verify()?);
+
+        assert!(manager
+            .try_unlock_vault(&vault_id, time_proof(), b"carol", &state_at(10))
+            .is_err());
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"alice", &state_at(10))?);
+
+        let share = manager.claim_vault_share(&vault_id, b"alice", &state_at(10))?;
+        assert_eq!((share.amount, share.token_id.as_str()), (600, "ROOT"));
+        assert!(manager
+            .claim_vault_share(&vault_id, b"alice", &state_at(10))
+            .is_err());
+        assert!(manager
+            .claim_vault_share(&vault_id, b"carol", &state_at(10))
+            .is_err());
+
+        // The vault stays open for the recipients who have not claimed
+        let vault_lock = manager.get_vault(&vault_id)?;
+        {
+            let vault = vault_lock.lock().unwrap();
+            assert!(matches!(vault.state, VaultState::Unlocked { .. }));
+            let split = vault.split.as_ref().unwrap();
+            assert_eq!(split.claimed, [true, false]);
+            assert_eq!(split.unclaimed_recipients(), [&b"bob"[..]]);
+        }
+
+        // Shares carry no content to check against a blinding factor
+        let blinding_factor = derive_blinding_factor(&creator.1, &vault_id);
+        assert!(manager
+            .claim_vault_content(&vault_id, b"bob", &state_at(10), Some(&blinding_factor))
+            .is_err());
+
+        let content = manager.claim_vault_content(&vault_id, b"bob", &state_at(10), None)?;
+        let share: ShareClaim = bincode::deserialize(&content).unwrap();
+        assert_eq!((share.recipient, share.amount), (b"bob".to_vec(), 400));
+        assert!(matches!(
+            vault_lock.lock().unwrap().state,
+            VaultState::Claimed {
+                claimed_state_number: 10,
+                ..
+            }
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_allocations_are_checked_and_committed() -> Result<(), DsmError> {
+        let allocation = |recipient: &[u8], numerator| SplitAllocation {
+            recipient: recipient.to_vec(),
+            share: Ratio::new(numerator, 4),
+        };
+        assert!(VaultSplit::new("ROOT", 100, vec![]).is_err());
+        assert!(VaultSplit::new("ROOT", 100, vec![allocation(b"alice", 0)]).is_err());
+        assert!(VaultSplit::new(
+            "ROOT",
+            100,
+            vec![allocation(b"alice", 3), allocation(b"bob", 2)]
+        )
+        .is_err());
+        assert!(VaultSplit::new(
+            "ROOT",
+            100,
+            vec![allocation(b"alice", 1), allocation(b"alice", 1)]
+        )
+        .is_err());
+
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = split_vault(&manager, &creator)?;
+        let vault_lock = manager.get_vault(&vault_id)?;
+        let mut vault = vault_lock.lock().unwrap();
+
+        // A splittable vault is never claimed whole or handed to one recipient
+        assert!(vault.unlock(time_proof(), b"alice", &state_at(10))?);
+        assert!(vault.claim(b"alice", &state_at(10)).is_err());
+
+        // The allocations cannot be changed without the creator's key
+        if let Some(split) = &mut vault.split {
+            split.allocations[1].share = Ratio::new(3, 5);
+        }
+        assert!(!vault.verify()?);
+
+        Ok(())
+    }
+
+    /// Decrypted envelope of a test vault, which uses a mock encryption
+    fn ids(summaries: Vec<VaultSummaryLocal>) -> Vec<String> {
+        summaries.into_iter().map(|summary| summary.id).collect()
+    }
+
+    #[test]
+    fn test_transitions_move_vaults_between_state_buckets() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creators = (0..3)
+            .map(|_| sphincs::generate_sphincs_keypair())
+            .collect::<Result<Vec<_>, _>>()?;
+        let waiting = time_locked_vault(&manager, &creators[0]);
+        let cancelled = time_locked_vault(&manager, &creators[1]);
+        let shared = split_vault(&manager, &creators[2])?;
+        assert_eq!(
+            manager.count_by_state()?,
+            HashMap::from([(VaultStateKind::Limbo, 3)])
+        );
+
+        // A failed unlock leaves the vault where it was
+        assert!(!manager.try_unlock_vault(&shared, time_proof(), b"alice", &state_at(5))?);
+        assert!(manager.try_unlock_vault(&shared, time_proof(), b"alice", &state_at(10))?);
+        manager.invalidate_vault(&cancelled, "cancelled", &creators[1].1, &state_at(3))?;
+        assert_eq!(
+            manager.count_by_state()?,
+            HashMap::from([
+                (VaultStateKind::Limbo, 1),
+                (VaultStateKind::Unlocked, 1),
+                (VaultStateKind::Invalidated, 1),
+            ])
+        );
+
+        // Claiming one share of two keeps the vault unlocked
+        manager.claim_vault_share(&shared, b"alice", &state_at(10))?;
+        assert_eq!(
+            ids(manager.list_vaults(VaultStateFilter::Unlocked)?),
+            [shared.clone()]
+        );
+        manager.claim_vault_share(&shared, b"bob", &state_at(10))?;
+
+        assert_eq!(
+            ids(manager.list_vaults(VaultStateFilter::Limbo)?),
+            [waiting.clone()]
+        );
+        assert!(manager.list_vaults(VaultStateFilter::Unlocked)?.is_empty());
+        let claimed = manager.list_vaults(VaultStateFilter::Claimed)?;
+        assert_eq!(ids(claimed.clone()), [shared]);
+        assert!(matches!(claimed[0].state, VaultState::Claimed { .. }));
+        assert_eq!(
+            ids(manager.list_vaults(VaultStateFilter::Invalidated)?),
+            [cancelled]
+        );
+        assert_eq!(manager.count_by_state()?.values().sum::<usize>(), 3);
+
+        // Only unresolved vaults with an expiry time before the cutoff are expiring
+        let vault_lock = manager.get_vault(&waiting)?;
+        {
+            let mut vault = vault_lock.lock().unwrap();
+            *vault = vault
+                .clone()
+                .with_expiry(None, Some(1_000), &creators[0].1)?;
+        }
+        assert!(manager
+            .list_vaults(VaultStateFilter::ExpiringBefore(1_000))?
+            .is_empty());
+        assert_eq!(
+            ids(manager.list_vaults(VaultStateFilter::ExpiringBefore(1_001))?),
+            [waiting]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_claims_move_vault_once() -> Result<(), DsmError> {
+        let manager = Arc::new(DLVManager::new());
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = split_vault(&manager, &creator)?;
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"alice", &state_at(10))?);
+        manager.claim_vault_share(&vault_id, b"alice", &state_at(10))?;
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = Arc::clone(&manager);
+                let vault_id = vault_id.clone();
+                std::thread::spawn(move || {
+                    manager.claim_vault_share(&vault_id, b"bob", &state_at(10))
+                })
+            })
+            .collect();
+        let claims = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(Result::is_ok)
+            .count();
+
+        assert_eq!(claims, 1);
+        assert_eq!(
+            manager.count_by_state()?,
+            HashMap::from([(VaultStateKind::Claimed, 1)])
+        );
+
+        Ok(())
+    }
+
+    fn mock_envelope(vault: &LimboVault) -> Vec<u8> {
+        let data = vault.encrypted_content.inline_data().unwrap_or_default();
+        let key = [5u8, 6, 7, 8];
+        data.iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ key[i % key.len()])
+            .collect()
+    }
+
+    /// Plaintext of a test vault, checked against its content commitment
+    fn mock_plaintext(vault: &LimboVault) -> Vec<u8> {
+        open_content_envelope(vault, &mock_envelope(vault)).unwrap()
+    }
+
+    #[test]
+    fn test_content_reencrypted_to_rotated_creator_key() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let rotated = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+        let vault_lock = manager.get_vault(&vault_id)?;
+        let original = vault_lock.lock().unwrap().clone();
+
+        // The old key must be the vault's creator key, held with its secret key
+        for old in [(&intruder.0, &intruder.1), (&creator.0, &intruder.1)] {
+            assert!(manager
+                .reencrypt_vault_content(
+                    &vault_id,
+                    (old.0, old.1),
+                    (&rotated.0, &rotated.1),
+                    &state_at(5)
+                )
+                .is_err());
+        }
+
+        // A failed write leaves the vault as it was
+        let failed = manager.reencrypt_and_publish_vault_content(
+            &vault_id,
+            (&creator.0, &creator.1),
+            (&rotated.0, &rotated.1),
+            &state_at(5),
+            |_| {
+                Err(DsmError::network(
+                    "Storage node unavailable",
+                    None::<std::convert::Infallible>,
+                ))
+            },
+        );
+        assert!(failed.is_err());
+        {
+            let vault = vault_lock.lock().unwrap();
+            assert_eq!(vault.creator_public_key, creator.0);
+            assert_eq!(vault.creator_signature, original.creator_signature);
+            assert!(vault.verify()?);
+        }
+
+        manager.reencrypt_vault_content(
+            &vault_id,
+            (&creator.0, &creator.1),
+            (&rotated.0, &rotated.1),
+            &state_at(5),
+        )?;
+        let vault = vault_lock.lock().unwrap().clone();
+        assert_eq!(vault.creator_public_key, rotated.0);
+        assert_ne!(vault.parameters_hash, original.parameters_hash);
+        assert!(vault.verify()?);
+        assert_eq!(mock_plaintext(&vault), mock_plaintext(&original));
+        assert_eq!(mock_plaintext(&vault), b"delayed distribution");
+
+        // The old key no longer holds the vault
+        assert!(manager
+            .reencrypt_vault_content(
+                &vault_id,
+                (&creator.0, &creator.1),
+                (&intruder.0, &intruder.1),
+                &state_at(5)
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_reencryptions_rotate_once() -> Result<(), DsmError> {
+        let manager = Arc::new(DLVManager::new());
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let manager = Arc::clone(&manager);
+                let creator = creator.clone();
+                let vault_id = vault_id.clone();
+                std::thread::spawn(move || -> Result<Vec<u8>, DsmError> {
+                    let rotated = sphincs::generate_sphincs_keypair()?;
+                    manager.reencrypt_vault_content(
+                        &vault_id,
+                        (&creator.0, &creator.1),
+                        (&rotated.0, &rotated.1),
+                        &state_at(5),
+                    )?;
+                    Ok(rotated.0)
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        let rotated: Vec<_> = results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .collect();
+        assert_eq!(rotated.len(), 1);
+        let vault = manager.get_vault(&vault_id)?.lock().unwrap().clone();
+        assert_eq!(&vault.creator_public_key, rotated[0]);
+        assert!(vault.verify()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_reencrypted_to_new_recipient_key() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = manager.create_vault(
+            (&creator.0, &creator.1),
+            FulfillmentMechanism::TimeRelease {
+                unlock_time: 10,
+                reference_states: vec![REFERENCE.to_vec()],
+            },
+            b"delayed distribution",
+            "text/plain",
+            Some(b"recipient-key-1".to_vec()),
+            &state_at(0),
+        )?;
+        let vault_lock = manager.get_vault(&vault_id)?;
+        let original = vault_lock.lock().unwrap().clone();
+
+        // Only the creator, holding the content, can re-encrypt it
+        assert!(matches!(
+            manager.reencrypt_vault_for(
+                &vault_id,
+                b"recipient-key-2",
+                (&creator.0, &creator.1),
+                b"other content"
+            ),
+            Err(DsmError::CommitmentMismatch { .. })
+        ));
+        for keypair in [(&intruder.0, &intruder.1), (&creator.0, &intruder.1)] {
+            assert!(manager
+                .reencrypt_vault_for(
+                    &vault_id,
+                    b"recipient-key-2",
+                    (keypair.0, keypair.1),
+                    b"delayed distribution"
+                )
+                .is_err());
+        }
+
+        let update = manager.reencrypt_vault_for(
+            &vault_id,
+            b"recipient-key-2",
+            (&creator.0, &creator.1),
+            b"delayed distribution",
+        )?;
+        assert!(update.verify()?);
+        assert_eq!(update.recipient_key_generation, 1);
+
+        let vault = vault_lock.lock().unwrap().clone();
+        assert!(vault.verify()?);
+        assert_eq!(vault.id, original.id);
+        assert_eq!(vault.content_commitment, original.content_commitment);
+        assert_eq!(vault.intended_recipient, Some(b"recipient-key-2".to_vec()));
+        assert_ne!(
+            vault.encrypted_content.encapsulated_key(),
+            original.encrypted_content.encapsulated_key()
+        );
+        assert_eq!(mock_plaintext(&vault), b"delayed distribution");
+
+        // The superseded ciphertext no longer matches the signed parameters
+        // and cannot be claimed
+        let mut stale = vault.clone();
+        stale.encrypted_content = original.encrypted_content.clone();
+        assert!(!stale.verify()?);
+        manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(10))?;
+        stale.state = vault_lock.lock().unwrap().state.clone();
+        assert!(matches!(
+            stale.claim(b"claimant", &state_at(10)),
+            Err(DsmError::Validation { context, .. })
+                if context.contains("recipient key generation 1")
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_opens_its_commitment() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+        let vault = manager.get_vault(&vault_id)?.lock().unwrap().clone();
+        assert_eq!(vault.envelope_version, CONTENT_ENVELOPE_VERSION);
+
+        // The blinding factor travels encrypted, ahead of the content
+        let envelope = mock_envelope(&vault);
+        let (blinding_factor, content) = envelope.split_at(BLINDING_FACTOR_LEN);
+        let blinding_factor: [u8; BLINDING_FACTOR_LEN] = blinding_factor.try_into().unwrap();
+        assert_eq!(content, b"delayed distribution");
+        // The creator can hand the same factor to recipients out of band
+        assert_eq!(
+            blinding_factor,
+            derive_blinding_factor(&creator.1, &vault_id)
+        );
+        assert!(verify_vault_content_commitment(
+            &vault,
+            content,
+            &blinding_factor
+        ));
+        assert_eq!(open_content_envelope(&vault, &envelope)?, content);
+
+        let mut wrong_factor = blinding_factor;
+        wrong_factor[0] ^= 1;
+        assert!(!verify_vault_content_commitment(
+            &vault,
+            content,
+            &wrong_factor
+        ));
+
+        // Swapped content is refused
+        let mut swapped = envelope.clone();
+        *swapped.last_mut().unwrap() ^= 1;
+        assert!(!verify_vault_content_commitment(
+            &vault,
+            &swapped[BLINDING_FACTOR_LEN..],
+            &blinding_factor
+        ));
+        assert!(matches!(
+            open_content_envelope(&vault, &swapped),
+            Err(DsmError::CommitmentMismatch { vault_id: id }) if id == vault_id
+        ));
+        assert!(open_content_envelope(&vault, &envelope[..8]).is_err());
+
+        // A substituted commitment no longer matches the content
+        let mut substituted = vault.clone();
+        substituted.content_commitment =
+            commit_vault_content(b"substituted content", &blinding_factor)?;
+        assert!(!verify_vault_content_commitment(
+            &substituted,
+            content,
+            &blinding_factor
+        ));
+        assert!(!substituted.verify()?);
+
+        // Nor can the envelope be downgraded to skip the check
+        let mut downgraded = vault.clone();
+        downgraded.envelope_version = 0;
+        assert!(!downgraded.verify()?);
+
+        // A claim checked against the wrong factor leaves the vault unlocked
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(10))?);
+        assert!(manager
+            .claim_vault_content(&vault_id, b"claimant", &state_at(10), Some(&wrong_factor))
+            .is_err());
+        assert!(matches!(
+            manager.get_vault(&vault_id)?.lock().unwrap().state,
+            VaultState::Unlocked { .. }
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_without_expiry_is_not_reclaimable() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let message = LimboVault::reclaim_message(&vault_id, &state_at(1_000));
+        let signature = sphincs::sphincs_sign(&creator.1, &message)?;
+        assert!(manager
+            .reclaim_expired_vault(&vault_id, &signature, &state_at(1_000))
+            .is_err());
+
+        // The expiry is committed, so it cannot be moved without the creator's key
+        expire_at(&manager, &vault_id, 30, &creator)?;
+        let vault_lock = manager.get_vault(&vault_id)?;
+        let mut vault = vault_lock.lock().unwrap();
+        vault.expires_at_state = Some(5);
+        assert!(!vault.verify()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_creator_extends_timelock_after_transfer() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let successor = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        let transfer = manager.transfer_vault_ownership(
+            &vault_id,
+            &successor.0,
+            (&creator.0, &creator.1),
+            &state_at(3),
+        )?;
+        assert_eq!(transfer.old_creator, creator.0);
+        assert_eq!(transfer.new_creator, successor.0);
+        assert_eq!(transfer.state_number, 3);
+        assert!(transfer.verify()?);
+
+        let extension = manager.extend_vault_timelock(
+            &vault_id,
+            10,
+            (&successor.0, &successor.1),
+            &state_at(5),
+        )?;
+        assert!(!manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(15))?);
+        assert!(manager.try_unlock_vault(&vault_id, time_proof(), b"claimant", &state_at(20))?);
+
+        // The vault still verifies, and its post names the new creator
+        let vault = manager.get_vault(&vault_id)?.lock().unwrap().clone();
+        assert!(vault.verify()?);
+        assert_eq!(vault.timelock_extensions, vec![extension]);
+        let post: VaultPost = bincode::deserialize(&manager.create_vault_post(
+            &vault_id,
+            "handover",
+            None,
+            (&successor.0, &successor.1),
+        )?)
+        .unwrap();
+        post.verify(&successor.0)?;
+        assert_eq!(post.creator_id, hex::encode(&successor.0));
+        assert_eq!(
+            LimboVault::from_vault_post(&post)?.creator(),
+            successor.0.as_slice()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampered_vault_post_rejected() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        assert!(manager
+            .create_vault_post(&vault_id, "payout", None, (&intruder.0, &intruder.1))
+            .is_err());
+        // The secret key must belong to the creator's public key
+        assert!(manager
+            .create_vault_post(&vault_id, "payout", None, (&creator.0, &intruder.1))
+            .is_err());
+        let post: VaultPost = bincode::deserialize(&manager.create_vault_post(
+            &vault_id,
+            "payout",
+            None,
+            (&creator.0, &creator.1),
+        )?)
+        .unwrap();
+        post.verify(&creator.0)?;
+        assert!(matches!(
+            post.verify(&intruder.0),
+            Err(DsmError::InvalidVaultPost { .. })
+        ));
+
+        // A storage node cannot mark the vault claimed
+        let mut tampered = post.clone();
+        tampered.status = "claimed".to_string();
+        assert!(matches!(
+            tampered.verify(&creator.0),
+            Err(DsmError::InvalidVaultPost { .. })
+        ));
+
+        // Nor re-sign the altered post with its own key
+        tampered.sign(&intruder.1)?;
+        assert!(matches!(
+            tampered.verify(&creator.0),
+            Err(DsmError::InvalidVaultPost { .. })
+        ));
+
+        let mut unsigned = post;
+        unsigned.creator_signature.clear();
+        assert!(matches!(
+            unsigned.verify(&creator.0),
+            Err(DsmError::InvalidVaultPost { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_kyber_keyed_vault_post_checked_against_bound_key() -> Result<(), DsmError> {
+        let (kyber_key, _) = kyber::generate_kyber_keypair()?;
+        let signer = sphincs::generate_sphincs_keypair()?;
+        let intruder = sphincs::generate_sphincs_keypair()?;
+        let create = |manager: &DLVManager, secret_key: &[u8]| {
+            let vault_id = manager.create_vault(
+                (&kyber_key, secret_key),
+                FulfillmentMechanism::TimeRelease {
+                    unlock_time: 10,
+                    reference_states: vec![REFERENCE.to_vec()],
+                },
+                b"reward distribution",
+                "text/plain",
+                None,
+                &state_at(0),
+            )?;
+            let post =
+                manager.create_vault_post(&vault_id, "payout", None, (&kyber_key, secret_key))?;
+            Ok::<_, DsmError>(bincode::deserialize::<VaultPost>(&post).unwrap())
+        };
+
+        // A Kyber key cannot sign, so the SPHINCS+ key is bound to the vault
+        let manager = DLVManager::new();
+        let post = create(&manager, &signer.1)?;
+        let vault = manager.get_vault(&post.vault_id)?.lock().unwrap().clone();
+        assert_eq!(vault.signing_key(), signer.0.as_slice());
+        assert!(vault.verify()?);
+        manager.verify_vault_post(&post)?;
+        post.verify(&signer.0)?;
+        assert!(post.verify(&kyber_key).is_err());
+
+        // A node cannot pass off a vault it signed as the same creator's
+        let forged = create(&DLVManager::new(), &intruder.1)?;
+        assert_eq!(forged.vault_id, post.vault_id);
+        forged.verify(&intruder.0)?;
+        assert!(matches!(
+            manager.verify_vault_post(&forged),
+            Err(DsmError::InvalidVaultPost { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_old_creator_loses_rights_after_transfer() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let successor = sphincs::generate_sphincs_keypair()?;
+        let vault_id = time_locked_vault(&manager, &creator);
+
+        manager.transfer_vault_ownership(
+            &vault_id,
+            &successor.0,
+            (&creator.0, &creator.1),
+            &state_at(3),
+        )?;
+
+        assert!(manager
+            .extend_vault_timelock(&vault_id, 10, (&creator.0, &creator.1), &state_at(5))
+            .is_err());
+        let stale = TimelockExtension::sign(&vault_id, 20, 5, &creator.1)?;
+        assert!(manager
+            .apply_timelock_extension(stale, &state_at(5))
+            .is_err());
+
+        // Nor can the old creator hand the vault on again
+        let buyer = sphincs::generate_sphincs_keypair()?;
+        assert!(manager
+            .transfer_vault_ownership(&vault_id, &buyer.0, (&creator.0, &creator.1), &state_at(6))
+            .is_err());
+        let forged =
+            OwnershipTransferRecord::sign(&vault_id, &buyer.0, 6, (&creator.0, &creator.1))?;
+        assert!(manager.apply_ownership_transfer(forged).is_err());
+
+        let vault_lock = manager.get_vault(&vault_id)?;
+        assert_eq!(vault_lock.lock().unwrap().unlock_time(), Some(10));
+        assert_eq!(vault_lock.lock().unwrap().creator(), successor.0.as_slice());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multisig_vault_needs_threshold_of_distinct_signers() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let signers = (0..3)
+            .map(|_| sphincs::generate_sphincs_keypair())
+            .collect::<Result<Vec<_>, _>>()?;
+        let outsider = sphincs::generate_sphincs_keypair()?;
+
+        let vault_id = manager.create_vault(
+            (&creator.0, &creator.1),
+            FulfillmentMechanism::MultiSig {
+                public_keys: signers.iter().map(|(pk, _)| pk.clone()).collect(),
+                threshold: 2,
+            },
+            b"escrowed release",
+            "text/plain",
+            None,
+            &state_at(0),
+        )?;
+        let parameters_hash = manager
+            .get_vault(&vault_id)?
+            .lock()
+            .unwrap()
+            .parameters_hash
+            .clone();
+        let sign = |sk: &[u8]| sphincs::sphincs_sign(sk, &parameters_hash).unwrap();
+        let try_unlock = |signatures: Vec<(usize, Vec<u8>)>| {
+            manager.try_unlock_vault(
+                &vault_id,
+                FulfillmentProof::MultiSigProof { signatures },
+                b"claimant",
+                &state_at(1),
+            )
+        };
+
+        assert!(!try_unlock(vec![(0, sign(&signers[0].1))])?);
+
+        // The same signer twice does not make two
+        assert!(!try_unlock(vec![
+            (0, sign(&signers[0].1)),
+            (0, sign(&signers[0].1))
+        ])?);
+
+        // Nor do keys outside the set, wherever they are placed
+        assert!(!try_unlock(vec![
+            (0, sign(&signers[0].1)),
+            (1, sign(&outsider.1))
+        ])?);
+        assert!(!try_unlock(vec![
+            (0, sign(&signers[0].1)),
+            (3, sign(&outsider.1))
+        ])?);
+
+        assert!(try_unlock(vec![
+            (2, sign(&signers[2].1)),
+            (0, sign(&signers[0].1))
+        ])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multisig_threshold_must_fit_signers() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+
+        for threshold in [0, 2] {
+            let condition = FulfillmentMechanism::Or(vec![FulfillmentMechanism::MultiSig {
+                public_keys: vec![creator.0.clone()],
+                threshold,
+            }]);
+            let result = manager.create_vault(
+                (&creator.0, &creator.1),
+                condition,
+                b"escrowed release",
+                "text/plain",
+                None,
+                &state_at(0),
+            );
+            assert!(result.is_err());
+        }
+
+        Ok(())
+    }
+
+    fn preimage_condition(preimage: &[u8]) -> FulfillmentMechanism {
+        FulfillmentMechanism::HashPreimage {
+            hash: *::blake3::hash(preimage).as_bytes(),
+            max_preimage_len: 32,
+        }
+    }
+
+    fn vault_with(
+        manager: &DLVManager,
+        creator: &(Vec<u8>, Vec<u8>),
+        condition: FulfillmentMechanism,
+    ) -> Result<String, DsmError> {
+        manager.create_vault(
+            (&creator.0, &creator.1),
+            condition,
+            b"swap payout",
+            "text/plain",
+            None,
+            &state_at(0),
+        )
+    }
+
+    #[test]
+    fn test_hash_preimage_unlocks_only_with_matching_preimage() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let vault_id = vault_with(&manager, &creator, preimage_condition(b"swap secret"))?;
+        let try_unlock = |preimage: &[u8]| {
+            manager.try_unlock_vault(
+                &vault_id,
+                FulfillmentProof::Preimage(preimage.to_vec()),
+                b"claimant",
+                &state_at(1),
+            )
+        };
+
+        assert!(!try_unlock(b"wrong secret")?);
+        assert!(!try_unlock(&[])?);
+        assert!(try_unlock(b"swap secret")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_preimage_rejects_oversized_preimage() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+
+        // The right preimage is still refused when it exceeds the limit
+        let secret = [5u8; 33];
+        let vault_id = vault_with(&manager, &creator, preimage_condition(&secret))?;
+        assert!(!manager.try_unlock_vault(
+            &vault_id,
+            FulfillmentProof::Preimage(secret.to_vec()),
+            b"claimant",
+            &state_at(1),
+        )?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preimage_or_reclaim_after_timeout() -> Result<(), DsmError> {
+        let manager = DLVManager::new();
+        let creator = sphincs::generate_sphincs_keypair()?;
+        let condition = FulfillmentMechanism::Or(vec![
+            preimage_condition(b"swap secret"),
+            FulfillmentMechanism::TimeRelease {
+                unlock_time: 10,
+                reference_states: vec![REFERENCE.to_vec()],
+            },
+        ]);
+        let secret = FulfillmentProof::Preimage(b"swap secret".to_vec());
+        let preimage_proof = FulfillmentProof::CompoundProof(vec![secret]);
+        let timeout_proof = FulfillmentProof::CompoundProof(vec![time_proof()]);
+
+        // Before the timeout only the preimage opens the vault
+        let claimed = vault_with(&manager, &creator, condition.clone())?;
+        let early_reclaim = timeout_proof.clone();
+        assert!(!manager.try_unlock_vault(&claimed, early_reclaim, b"creator", &state_at(5))?);
+        assert!(manager.try_unlock_vault(&claimed, preimage_proof, b"claimant", &state_at(5))?);
+
+        // After it the creator reclaims without the preimage
+        let reclaimed = vault_with(&manager, &creator, condition)?;
+        assert!(manager.try_unlock_vault(&reclaimed, timeout_proof, b"creator", &state_at(10))?);
+
+        Ok(())
+    }
+
+    /// Seller's genesis hash, which payments name as their recipient
+    const SELLER: [u8; 32] = [0x5e; 32];
+
+    /// A buyer's transition paying `amount` of ROOT to the seller for `vault_id`
+    fn buyer_payment(vault_id: &str, amount: u64) -> (State, State) {
+        let mut previous = State::new_genesis(vec![5; 32], DeviceInfo::new("buyer", vec![2]));
+        previous.hash = previous.compute_hash().unwrap();
+
+        let payment = Operation::Transfer {
+            to_address: hex::encode(SELLER),
+            amount: Balance::new(amount),
+            token_id: "ROOT".to_string(),
+            mode: TransactionMode::Unilateral,
+            nonce: vec![1],
+            verification: VerificationType::Standard,
+            pre_commit: None,
+            recipient: hex::encode(SELLER),
+            to: hex::encode(SELLER),
+            message: vault_id.to_string(),
+        };
+        let payment_state = create_next_state(
+            &previous,
+            payment,
+            &[6; 32],
+            &VerificationType::Standard,
+            false,
+        )
+        .unwrap();
+
+        (previous, payment_state)
+    }
+
+    fn token_payment_vault(manager: &DLVManager, creator: &(Vec<u8>, Vec<u8>)) -> String {
+        vault_with(
+            manager,
+            creator,
+            FulfillmentMechanism::TokenPayment {
+                token_id: "ROOT".to_string(),
+                amount: 100,
+                recipient_genesis_hash: SELLER.to_vec(),
+            },
+        )
+        .unwrap()
+    }
+
+    /// Buyer's genesis hash, under which the ledger records the buyer
+    const BUYER: [u8; 32] = [0xb0; 32];
+
+    /// Ledger holding the buyer's genesis key
+    fn payment_ledger(buyer: &(Vec<u8>, Vec<u8>)) -> Arc<MemoryPaymentLedger> {
+        let ledger = Arc::new(MemoryPaymentLedger::new());
+        ledger.register_payer(&BUYER, buyer.0.clone());
+        ledger
+    }
+
+    #[test]
+    fn test_token_payment_unlocks_with_valid_transfer() -> Result<(), DsmError> {
+        let buyer = sphincs::generate_sphincs_keypair()?;
+        let ledger = payment_ledger(&buyer);
+        let manager = DLVManager::new().with_payment_ledger(ledger.clone());
+        let seller = sphincs::generate_sphincs_keypair()?;
+        let vault_id = token_payment_vault(&manager, &seller);
+
+        // A payment made for another vault does not count
+        let (previous, payment) = buyer_payment("other-vault", 100);
+        ledger.set_trusted_head(&BUYER, payment.hash.clone());
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &buyer.1)?;
+        assert!(!manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        let (previous, payment) = buyer_payment(&vault_id, 120);
+        ledger.set_trusted_head(&BUYER, payment.hash.clone());
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &buyer.1)?;
+        assert!(manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_payment_rejects_insufficient_amount() -> Result<(), DsmError> {
+        let buyer = sphincs::generate_sphincs_keypair()?;
+        let ledger = payment_ledger(&buyer);
+        let manager = DLVManager::new().with_payment_ledger(ledger.clone());
+        let seller = sphincs::generate_sphincs_keypair()?;
+        let vault_id = token_payment_vault(&manager, &seller);
+
+        let (previous, payment) = buyer_payment(&vault_id, 99);
+        ledger.set_trusted_head(&BUYER, payment.hash.clone());
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &buyer.1)?;
+        assert!(!manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_payment_rejects_invalidated_or_unlinked_state() -> Result<(), DsmError> {
+        let buyer = sphincs::generate_sphincs_keypair()?;
+        let ledger = payment_ledger(&buyer);
+        let manager = DLVManager::new().with_payment_ledger(ledger.clone());
+        let seller = sphincs::generate_sphincs_keypair()?;
+        let vault_id = token_payment_vault(&manager, &seller);
+
+        // The registry, not the payer's own flags, decides what is invalidated
+        let (previous, payment) = buyer_payment(&vault_id, 100);
+        ledger.set_trusted_head(&BUYER, payment.hash.clone());
+        ledger.record_invalidation(&payment.hash);
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &buyer.1)?;
+        assert!(!manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        // A payment state that does not follow from the previous state
+        let (_, payment) = buyer_payment(&vault_id, 120);
+        let (mut unrelated, _) = buyer_payment(&vault_id, 120);
+        unrelated.entropy = vec![7; 32];
+        unrelated.hash = unrelated.compute_hash()?;
+        ledger.set_trusted_head(&BUYER, payment.hash.clone());
+        let proof = FulfillmentProof::token_payment(&unrelated, &payment, &BUYER, &buyer.1)?;
+        assert!(!manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_payment_rejects_forged_or_unanchored_proof() -> Result<(), DsmError> {
+        let buyer = sphincs::generate_sphincs_keypair()?;
+        let ledger = payment_ledger(&buyer);
+        let manager = DLVManager::new().with_payment_ledger(ledger.clone());
+        let seller = sphincs::generate_sphincs_keypair()?;
+        let vault_id = token_payment_vault(&manager, &seller);
+        let (previous, payment) = buyer_payment(&vault_id, 100);
+
+        // Signed with a key other than the one in the buyer's genesis
+        ledger.set_trusted_head(&BUYER, payment.hash.clone());
+        let forger = sphincs::generate_sphincs_keypair()?;
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &forger.1)?;
+        assert!(!manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        // A self-made chain that does not reach the trusted head
+        ledger.set_trusted_head(&BUYER, vec![0xee; 32]);
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &buyer.1)?;
+        assert!(!manager.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        // Without a ledger no payment is accepted
+        let unchecked = DLVManager::new();
+        let vault_id = token_payment_vault(&unchecked, &seller);
+        let (previous, payment) = buyer_payment(&vault_id, 100);
+        let proof = FulfillmentProof::token_payment(&previous, &payment, &BUYER, &buyer.1)?;
+        assert!(!unchecked.try_unlock_vault(&vault_id, proof, b"buyer", &state_at(1))?);
+
+        Ok(())
+    }
+}
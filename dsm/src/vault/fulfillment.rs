@@ -66,6 +66,44 @@ pub enum FulfillmentMechanism {
 
     /// Compound OR condition (any can be satisfied)
     Or(Vec<FulfillmentMechanism>),
+
+    /// Threshold of distinct signers over the vault's parameters hash
+    ///
+    /// Declared last so that vaults serialized before it keep their variant
+    /// indices.
+    MultiSig {
+        /// SPHINCS+ public keys of the signers, referenced by index in proofs
+        public_keys: Vec<Vec<u8>>,
+        /// Number of distinct signers required for fulfillment
+        threshold: usize,
+    },
+
+    /// Revealing the preimage of a BLAKE3 hash, as in hash time-locked contracts
+    ///
+    /// Combined with `TimeRelease` under `Or`, the vault opens with the
+    /// preimage at any time, or without it once the unlock time has passed.
+    HashPreimage {
+        /// BLAKE3 hash of the secret preimage
+        hash: [u8; 32],
+        /// Longest preimage accepted; longer ones are rejected before hashing
+        max_preimage_len: usize,
+    },
+
+    /// A token transfer to a recipient, proven by the hash-chained states
+    /// recording it
+    ///
+    /// The transfer must name the vault's ID as its message, so that one
+    /// payment cannot release several vaults. The states are checked against
+    /// the vault's [`PaymentLedger`](super::PaymentLedger), without which the
+    /// condition cannot be met.
+    TokenPayment {
+        /// Token to be paid with
+        token_id: String,
+        /// Minimum amount to be paid
+        amount: u64,
+        /// Genesis hash of the payment's recipient
+        recipient_genesis_hash: Vec<u8>,
+    },
 }
 
 impl fmt::Display for FulfillmentMechanism {
@@ -91,6 +129,16 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             FulfillmentMechanism::Or(conditions) => {
                 write!(f, "OR({} conditions)", conditions.len())
             }
+            FulfillmentMechanism::MultiSig {
+                public_keys,
+                threshold,
+            } => write!(f, "{}-of-{} MultiSig", threshold, public_keys.len()),
+            FulfillmentMechanism::HashPreimage { hash, .. } => {
+                write!(f, "HashPreimage of {}", hex::encode(hash))
+            }
+            FulfillmentMechanism::TokenPayment {
+                token_id, amount, ..
+            } => write!(f, "TokenPayment of {} {}", amount, token_id),
         }
     }
 }
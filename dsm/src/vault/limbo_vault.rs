@@ -10,27 +10,39 @@
 //! a deterministic process that provides non-repudiation and verifiability.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use crate::core::state_machine::random_walk::algorithms::{generate_positions, generate_seed, Position};
+use crate::core::state_machine::utils::verify_chain_link;
 use crate::crypto::blake3;
 use crate::crypto::kyber;
-use crate::crypto::pedersen::{PedersenCommitment, PedersenParams, SecurityLevel};
+use crate::crypto::pedersen::PedersenCommitment;
 use crate::crypto::sphincs;
 use crate::types::error::DsmError;
+use crate::types::operations::Operation;
 use crate::types::state_types::State;
 use crate::types::policy_types::VaultCondition;
+use crate::types::versioned::Versioned;
 
 use constant_time_eq;
-use pqcrypto_mlkem::mlkem512;
+use crate::crypto::mlkem::mlkem512;
 use pqcrypto_traits::kem::{
     Ciphertext as CiphertextTrait, SecretKey as SecretKeyTrait, SharedSecret as SharedSecretTrait,
 };
 use serde::{Deserialize, Serialize};
 
-use super::FulfillmentMechanism;
+use super::chunked::{chunk_list_bytes, split_into_chunks};
+use super::commitment::{
+    derive_blinding_factor, open_content_envelope, seal_content_envelope,
+    verify_vault_content_commitment, CONTENT_ENVELOPE_VERSION,
+};
+use super::{
+    FulfillmentMechanism, OwnershipTransferRecord, PaymentLedger, RecipientReassignment,
+    ShareClaim, TimelockExtension, VaultContentUpdate, VaultSplit,
+};
 
 // Wrapper types for mlkem512
 #[derive(Clone)] // Remove Debug since underlying types don't implement it
@@ -89,6 +101,12 @@ pub enum VaultState {
         reason: String,
         creator_signature: Vec<u8>,
     },
+
+    /// Expired without being claimed and returned to the creator
+    Reclaimed {
+        reclaimed_state_number: u64,
+        creator_signature: Vec<u8>,
+    },
 }
 
 /// Proof that a condition has been fulfilled
@@ -136,22 +154,340 @@ pub enum FulfillmentProof {
 
     /// Multiple proofs (for compound conditions)
     CompoundProof(Vec<FulfillmentProof>),
+
+    /// Signatures over the vault's parameters hash for a `MultiSig` condition
+    ///
+    /// Declared last so that proofs serialized before it keep their variant
+    /// indices.
+    MultiSigProof {
+        /// Signatures with the index of their signer's public key
+        signatures: Vec<(usize, Vec<u8>)>, // (signer index, signature)
+    },
+
+    /// The revealed preimage for a `HashPreimage` condition
+    Preimage(Vec<u8>),
+
+    /// The payer's state transition recording a `TokenPayment`
+    TokenPaymentProof {
+        /// Schema-versioned state preceding the payment
+        previous_state: Vec<u8>,
+        /// Schema-versioned state whose operation is the payment
+        payment_state: Vec<u8>,
+        /// Genesis hash of the payer's identity
+        payer_genesis_hash: Vec<u8>,
+        /// Payer's SPHINCS+ signature over the payment state's hash
+        payer_signature: Vec<u8>,
+    },
+}
+
+impl FulfillmentProof {
+    /// Prove a token payment with the transition that made it
+    ///
+    /// The payment state is signed with the secret key matching the public
+    /// key in the payer's genesis state.
+    pub fn token_payment(
+        previous_state: &State,
+        payment_state: &State,
+        payer_genesis_hash: &[u8],
+        payer_secret_key: &[u8],
+    ) -> Result<Self, DsmError> {
+        let payer_signature = sphincs::sphincs_sign(
+            payer_secret_key,
+            &token_payment_message(&payment_state.hash),
+        )?;
+
+        Ok(FulfillmentProof::TokenPaymentProof {
+            previous_state: Versioned::new(previous_state.clone()).to_bytes()?,
+            payment_state: Versioned::new(payment_state.clone()).to_bytes()?,
+            payer_genesis_hash: payer_genesis_hash.to_vec(),
+            payer_signature,
+        })
+    }
+}
+
+/// Domain separator for payer signatures on token payments
+const TOKEN_PAYMENT_DOMAIN: &[u8] = b"DSM_VAULT_TOKEN_PAYMENT";
+
+/// Message a payer signs to prove a token payment state
+fn token_payment_message(payment_state_hash: &[u8]) -> Vec<u8> {
+    let mut message = TOKEN_PAYMENT_DOMAIN.to_vec();
+    message.extend_from_slice(payment_state_hash);
+    message
+}
+
+/// Domain separator for the expiry committed in a vault's parameters hash
+const VAULT_EXPIRY_DOMAIN: &[u8] = b"DSM_VAULT_EXPIRY";
+
+/// Domain separator for vault reclaim signatures
+const VAULT_RECLAIM_DOMAIN: &[u8] = b"DSM_VAULT_RECLAIM";
+
+/// Domain separator for the metadata committed in a vault's parameters hash
+const VAULT_METADATA_DOMAIN: &[u8] = b"DSM_VAULT_METADATA";
+
+/// Domain separator for the content envelope version in a vault's parameters hash
+const VAULT_ENVELOPE_DOMAIN: &[u8] = b"DSM_VAULT_CONTENT_ENVELOPE";
+
+/// Domain separator for the recipient key generation in a vault's parameters hash
+const VAULT_KEY_GENERATION_DOMAIN: &[u8] = b"DSM_VAULT_RECIPIENT_KEY_GENERATION";
+
+/// Domain separator for the fields of a vault post signed by its creator
+const VAULT_POST_DOMAIN: &[u8] = b"DSM_VAULT_POST";
+
+/// Domain separator for the creator's signing key in a vault's parameters hash
+const VAULT_SIGNING_KEY_DOMAIN: &[u8] = b"DSM_VAULT_CREATOR_SIGNING_KEY";
+
+/// Size caps on the metadata attached to a vault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultMetadataLimits {
+    /// Longest key, in bytes
+    pub max_key_len: usize,
+
+    /// Longest value, in bytes
+    pub max_value_len: usize,
+
+    /// Largest total size of all keys and values, in bytes
+    pub max_total_len: usize,
 }
 
+impl Default for VaultMetadataLimits {
+    fn default() -> Self {
+        Self {
+            max_key_len: 64,
+            max_value_len: 1024,
+            max_total_len: 4096,
+        }
+    }
+}
+
+impl VaultMetadataLimits {
+    /// Check metadata against the caps
+    pub fn check(&self, metadata: &BTreeMap<String, String>) -> Result<(), DsmError> {
+        let mut total_len = 0usize;
+        for (key, value) in metadata {
+            if key.is_empty() || key.len() > self.max_key_len {
+                return Err(DsmError::invalid_parameter(format!(
+                    "Vault metadata key must be 1 to {} bytes, got {}",
+                    self.max_key_len,
+                    key.len()
+                )));
+            }
+            if value.len() > self.max_value_len {
+                return Err(DsmError::invalid_parameter(format!(
+                    "Vault metadata value for {} exceeds {} bytes",
+                    key, self.max_value_len
+                )));
+            }
+            total_len = total_len.saturating_add(key.len() + value.len());
+        }
+
+        if total_len > self.max_total_len {
+            return Err(DsmError::invalid_parameter(format!(
+                "Vault metadata is {} bytes, more than the {} allowed",
+                total_len, self.max_total_len
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Marks a chunk manifest in the wire layout of `EncryptedContent`
+const CHUNK_MANIFEST_MARKER: &[u8] = b"DSM_CHUNKED_VAULT_CONTENT";
+
 /// Represents an encrypted state contained within a Limbo Vault
+///
+/// Single-blob content keeps the wire layout vaults have always had, so
+/// existing vaults deserialize unchanged. Chunked content reuses that layout
+/// with its chunk manifest in place of the ciphertext.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EncryptedContent {
-    /// Kyber-encapsulated key for the content
-    pub encapsulated_key: Vec<u8>,
+#[serde(try_from = "EncryptedContentRepr", into = "EncryptedContentRepr")]
+pub enum EncryptedContent {
+    /// Ciphertext held in the vault itself
+    Blob {
+        /// Kyber-encapsulated key for the content
+        encapsulated_key: Vec<u8>,
+
+        /// The actual encrypted content
+        encrypted_data: Vec<u8>,
+
+        /// Nonce used for encryption
+        nonce: Vec<u8>,
+
+        /// Additional authenticated data
+        aad: Vec<u8>,
+    },
+
+    /// Ciphertext split into chunks stored apart from the vault
+    Chunked {
+        /// Kyber-encapsulated key for the content
+        encapsulated_key: Vec<u8>,
 
-    /// The actual encrypted content
-    pub encrypted_data: Vec<u8>,
+        /// Nonce used for encryption
+        nonce: Vec<u8>,
+
+        /// Additional authenticated data
+        aad: Vec<u8>,
+
+        /// BLAKE3 hash of each ciphertext chunk, in order
+        chunk_hashes: Vec<[u8; 32]>,
+
+        /// Size of every chunk but the last, which holds the remainder
+        chunk_size: usize,
+
+        /// Length of the whole ciphertext
+        total_len: u64,
+    },
+}
+
+impl EncryptedContent {
+    /// Kyber-encapsulated key for the content
+    pub fn encapsulated_key(&self) -> &[u8] {
+        match self {
+            EncryptedContent::Blob {
+                encapsulated_key, ..
+            }
+            | EncryptedContent::Chunked {
+                encapsulated_key, ..
+            } => encapsulated_key,
+        }
+    }
 
     /// Nonce used for encryption
-    pub nonce: Vec<u8>,
+    pub fn nonce(&self) -> &[u8] {
+        match self {
+            EncryptedContent::Blob { nonce, .. } | EncryptedContent::Chunked { nonce, .. } => nonce,
+        }
+    }
+
+    /// Ciphertext held in the vault, `None` for chunked content
+    pub fn inline_data(&self) -> Option<&[u8]> {
+        match self {
+            EncryptedContent::Blob { encrypted_data, .. } => Some(encrypted_data),
+            EncryptedContent::Chunked { .. } => None,
+        }
+    }
+
+    /// Check a reassembled ciphertext against the committed chunk hashes
+    pub fn verify_assembled(&self, data: &[u8]) -> Result<(), DsmError> {
+        let EncryptedContent::Chunked {
+            chunk_hashes,
+            chunk_size,
+            ..
+        } = self
+        else {
+            return Err(DsmError::invalid_parameter("Vault content is not chunked"));
+        };
+
+        let (assembled_hashes, _) = split_into_chunks(data, *chunk_size)?;
+        if assembled_hashes != *chunk_hashes {
+            return Err(DsmError::validation(
+                "Assembled content does not match the committed chunks",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for EncryptedContent {
+    fn default() -> Self {
+        EncryptedContent::Blob {
+            encapsulated_key: Vec::new(),
+            encrypted_data: Vec::new(),
+            nonce: Vec::new(),
+            aad: Vec::new(),
+        }
+    }
+}
+
+/// Wire layout of `EncryptedContent`, unchanged from single-blob vaults
+#[derive(Serialize, Deserialize)]
+struct EncryptedContentRepr {
+    encapsulated_key: Vec<u8>,
+    encrypted_data: Vec<u8>,
+    nonce: Vec<u8>,
+    aad: Vec<u8>,
+}
+
+/// Chunk layout of chunked content, carried in place of the ciphertext
+#[derive(Serialize, Deserialize)]
+struct ChunkManifest {
+    chunk_hashes: Vec<[u8; 32]>,
+    chunk_size: u64,
+    total_len: u64,
+}
+
+impl From<EncryptedContent> for EncryptedContentRepr {
+    fn from(content: EncryptedContent) -> Self {
+        match content {
+            EncryptedContent::Blob {
+                encapsulated_key,
+                encrypted_data,
+                nonce,
+                aad,
+            } => Self {
+                encapsulated_key,
+                encrypted_data,
+                nonce,
+                aad,
+            },
+            EncryptedContent::Chunked {
+                encapsulated_key,
+                nonce,
+                aad,
+                chunk_hashes,
+                chunk_size,
+                total_len,
+            } => {
+                let manifest = ChunkManifest {
+                    chunk_hashes,
+                    chunk_size: chunk_size as u64,
+                    total_len,
+                };
+                // Hashes and lengths only, so encoding cannot fail
+                let mut encrypted_data = CHUNK_MANIFEST_MARKER.to_vec();
+                encrypted_data.extend(bincode::serialize(&manifest).unwrap_or_default());
+
+                Self {
+                    encapsulated_key,
+                    encrypted_data,
+                    nonce,
+                    aad,
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<EncryptedContentRepr> for EncryptedContent {
+    type Error = DsmError;
+
+    fn try_from(repr: EncryptedContentRepr) -> Result<Self, DsmError> {
+        // AES-GCM ciphertext starts with the marker with negligible probability
+        let Some(manifest) = repr.encrypted_data.strip_prefix(CHUNK_MANIFEST_MARKER) else {
+            return Ok(EncryptedContent::Blob {
+                encapsulated_key: repr.encapsulated_key,
+                encrypted_data: repr.encrypted_data,
+                nonce: repr.nonce,
+                aad: repr.aad,
+            });
+        };
 
-    /// Additional authenticated data
-    pub aad: Vec<u8>,
+        let manifest: ChunkManifest = bincode::deserialize(manifest)
+            .map_err(|e| DsmError::serialization("Invalid vault chunk manifest", Some(e)))?;
+        let chunk_size = usize::try_from(manifest.chunk_size)
+            .map_err(|_| DsmError::invalid_parameter("Vault chunk size is too large"))?;
+
+        Ok(EncryptedContent::Chunked {
+            encapsulated_key: repr.encapsulated_key,
+            nonce: repr.nonce,
+            aad: repr.aad,
+            chunk_hashes: manifest.chunk_hashes,
+            chunk_size,
+            total_len: manifest.total_len,
+        })
+    }
 }
 
 /// Vault posting structure for decentralized storage
@@ -184,6 +520,111 @@ pub struct VaultPost {
 
     /// The actual vault data (may be encrypted)
     pub vault_data: Vec<u8>,
+
+    /// SPHINCS+ key the post is signed with, as bound to the vault
+    #[serde(default)]
+    pub creator_signing_key: Vec<u8>,
+
+    /// Hash of the fields above, as signed by the creator
+    #[serde(default)]
+    pub signed_fields_hash: Vec<u8>,
+
+    /// Creator's SPHINCS+ signature over `signed_fields_hash`
+    #[serde(default)]
+    pub creator_signature: Vec<u8>,
+}
+
+impl VaultPost {
+    /// Compute the hash of the fields the creator signs
+    ///
+    /// Covers every field except the hash and the signature. Variable-length
+    /// fields are length-prefixed and metadata is hashed in key order.
+    pub fn compute_signed_fields_hash(&self) -> Vec<u8> {
+        let mut metadata: Vec<_> = self.metadata.iter().collect();
+        metadata.sort();
+
+        let mut bytes = VAULT_POST_DOMAIN.to_vec();
+        bytes.extend_from_slice(&self.timestamp_created.to_le_bytes());
+        bytes.extend_from_slice(&(metadata.len() as u64).to_le_bytes());
+        let fields = [
+            self.vault_id.as_bytes(),
+            self.lock_description.as_bytes(),
+            self.creator_id.as_bytes(),
+            &self.commitment_hash,
+            self.status.as_bytes(),
+            &self.vault_data,
+            &self.creator_signing_key,
+        ];
+        let metadata_fields = metadata
+            .into_iter()
+            .flat_map(|(key, value)| [key.as_bytes(), value.as_bytes()]);
+        for field in fields.into_iter().chain(metadata_fields) {
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+
+        blake3::hash(&bytes).as_bytes().to_vec()
+    }
+
+    /// Sign the post with the creator's SPHINCS+ secret key
+    pub fn sign(&mut self, creator_secret_key: &[u8]) -> Result<(), DsmError> {
+        self.signed_fields_hash = self.compute_signed_fields_hash();
+        self.creator_signature =
+            sphincs::sphincs_sign(creator_secret_key, &self.signed_fields_hash)
+                .map_err(|e| DsmError::crypto("Failed to sign vault post", Some(e)))?;
+
+        Ok(())
+    }
+
+    /// Verify that the post is signed by the vault creator
+    ///
+    /// `signing_key` is the SPHINCS+ key the reader trusts to sign for the
+    /// creator: the creator's public key, or for vaults created under a key
+    /// that cannot sign, such as a Kyber key, the signing key bound to the
+    /// vault (see [`LimboVault::signing_key`]). The post must be signed with
+    /// that key, and the vault it carries must be signed by and bound to it,
+    /// so a post cannot vouch for itself with a key of its own.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If `signing_key` signed the post as it is
+    /// * `Err(DsmError::InvalidVaultPost)` - If the post is unsigned, was
+    ///   modified after signing, or is signed by another key
+    pub fn verify(&self, signing_key: &[u8]) -> Result<(), DsmError> {
+        let invalid = |reason: &str| DsmError::InvalidVaultPost {
+            vault_id: self.vault_id.clone(),
+            reason: reason.to_string(),
+        };
+
+        if self.creator_signature.is_empty() {
+            return Err(invalid("the post is unsigned"));
+        }
+        if self.compute_signed_fields_hash() != self.signed_fields_hash {
+            return Err(invalid("the post was modified after it was signed"));
+        }
+        if self.creator_signing_key != signing_key {
+            return Err(invalid("the post is not signed with the creator's key"));
+        }
+
+        // The vault in the post must be bound to the same key
+        let vault = LimboVault::from_vault_post(self)
+            .map_err(|_| invalid("the post does not carry a valid vault"))?;
+        if vault.signing_key() != signing_key {
+            return Err(invalid("the vault is not bound to the creator's key"));
+        }
+
+        // Malformed keys or signatures are invalid, not internal errors
+        let valid = sphincs::sphincs_verify(
+            signing_key,
+            &self.signed_fields_hash,
+            &self.creator_signature,
+        )
+        .unwrap_or(false);
+        if !valid {
+            return Err(invalid("the signature is not the creator's"));
+        }
+
+        Ok(())
+    }
 }
 
 /// Core Deterministic Limbo Vault structure
@@ -227,6 +668,54 @@ pub struct LimboVault {
 
     /// Reference state hash for timestamp verification
     pub reference_state_hash: Vec<u8>,
+
+    /// Creator-signed extensions of a `TimeRelease` lock, oldest first
+    #[serde(default)]
+    pub timelock_extensions: Vec<TimelockExtension>,
+
+    /// Transfers of the creator rights to other identities, oldest first
+    #[serde(default)]
+    pub ownership_transfers: Vec<OwnershipTransferRecord>,
+
+    /// State number from which the creator may reclaim the vault
+    #[serde(default)]
+    pub expires_at_state: Option<u64>,
+
+    /// Time (seconds since epoch) from which the creator may reclaim the vault
+    #[serde(default)]
+    pub expires_at_time: Option<u64>,
+
+    /// Changes of the intended recipient before the vault was claimed, oldest first
+    #[serde(default)]
+    pub recipient_reassignments: Vec<RecipientReassignment>,
+
+    /// Per-recipient allocations of a splittable vault, claimed share by share
+    #[serde(default)]
+    pub split: Option<VaultSplit>,
+
+    /// Searchable application metadata, such as a title, category or tags
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+
+    /// Layout of the encrypted content: 0 for bare content, or
+    /// `CONTENT_ENVELOPE_VERSION` for content sealed with the blinding factor
+    /// of its commitment
+    #[serde(default)]
+    pub envelope_version: u8,
+
+    /// Number of times the content was re-encrypted to a new recipient key
+    #[serde(default)]
+    pub recipient_key_generation: u32,
+
+    /// SPHINCS+ key that signs for the creator, bound when
+    /// `creator_public_key` cannot sign, such as a Kyber key; empty otherwise
+    #[serde(default)]
+    pub creator_signing_key: Vec<u8>,
+
+    /// Verifier's record of payers that `TokenPayment` proofs are checked
+    /// against; token payments are rejected while it is unset
+    #[serde(skip)]
+    pub payment_ledger: Option<Arc<dyn PaymentLedger>>,
 }
 
 /// Result of a vault content claim operation
@@ -278,7 +767,7 @@ pub fn from_vault_post(post: &VaultPost) -> Result<Self, DsmError> {
         let creator_pubkey = hex::decode(&post.creator_id)
             .map_err(|e| DsmError::validation("Invalid creator ID format", Some(e)))?;
 
-        if vault.creator_public_key != creator_pubkey {
+        if vault.creator() != creator_pubkey.as_slice() {
             return Err(DsmError::validation(
                 "Vault integrity check failed: creator mismatch",
                 None::<std::convert::Infallible>,
@@ -287,7 +776,7 @@ pub fn from_vault_post(post: &VaultPost) -> Result<Self, DsmError> {
 
         // Verify vault's signature to ensure it hasn't been tampered with
         if !sphincs::sphincs_verify(
-            &vault.creator_public_key,
+            vault.original_signing_key(),
             &vault.parameters_hash,
             &vault.creator_signature,
         )? {
@@ -308,6 +797,8 @@ pub fn new(
         intended_recipient: Option<Vec<u8>>,
         reference_state: &State, // Reference state for timestamp anchoring
     ) -> Result<LimboVault, DsmError> {
+        Self::validate_condition(&fulfillment_condition)?;
+
         // Use state number for temporal ordering
         let state_number = reference_state.state_number;
 
@@ -353,10 +844,14 @@ pub fn new(
         aad.extend_from_slice(&state_bytes);
         aad.extend_from_slice(&ref_state_hash);
 
+        // Seal the content with the blinding factor of its commitment
+        let blinding_factor = derive_blinding_factor(creator_keypair.1, &vault_id);
+        let (envelope, commitment) = seal_content_envelope(content, &blinding_factor)?;
+
         // For test environments, simulate encryption with test key
         #[cfg(test)]
         let encrypted_data = {
-            let mut result = content.to_vec();
+            let mut result = envelope;
             for (i, byte) in result.iter_mut().enumerate() {
                 *byte ^= test_shared_key[i % test_shared_key.len()];
             }
@@ -365,16 +860,9 @@ pub fn new(
 
         // For non-test environments, use real encryption
         #[cfg(not(test))]
-        let encrypted_data = kyber::aes_encrypt(&shared_secret, &nonce, content)
+        let encrypted_data = kyber::aes_encrypt(&shared_secret, &nonce, &envelope)
             .map_err(|e| DsmError::crypto("Failed to encrypt vault content", Some(e)))?;
 
-        // Create Pedersen commitment to the content
-        let params = PedersenParams::new(SecurityLevel::Standard128);
-
-        // Create the commitment
-        let (commitment, _r) =
-            PedersenCommitment::commit(&params, content, &mut rand::thread_rng())?;
-
         // Hash all parameters for integrity verification
         let mut parameters = Vec::new();
         parameters.extend_from_slice(creator_keypair.0);
@@ -387,6 +875,13 @@ pub fn new(
             parameters.extend_from_slice(recipient);
         }
         parameters.extend_from_slice(&commitment.to_bytes());
+        parameters.extend_from_slice(VAULT_ENVELOPE_DOMAIN);
+        parameters.push(CONTENT_ENVELOPE_VERSION);
+        let creator_signing_key = Self::creator_signing_key_for(creator_keypair)?;
+        if !creator_signing_key.is_empty() {
+            parameters.extend_from_slice(VAULT_SIGNING_KEY_DOMAIN);
+            parameters.extend_from_slice(&creator_signing_key);
+        }
 
         // Create binding for the hash result to prevent temporary value drop
         let hash_result = blake3::hash(&parameters);
@@ -409,7 +904,7 @@ pub fn new(
             intended_recipient,
             state: VaultState::Limbo,
             content_type: content_type.to_string(),
-            encrypted_content: EncryptedContent {
+            encrypted_content: EncryptedContent::Blob {
                 encapsulated_key,
                 encrypted_data,
                 nonce,
@@ -420,6 +915,17 @@ pub fn new(
             creator_signature,
             verification_positions,
             reference_state_hash: ref_state_hash,
+            timelock_extensions: Vec::new(),
+            ownership_transfers: Vec::new(),
+            expires_at_state: None,
+            expires_at_time: None,
+            recipient_reassignments: Vec::new(),
+            split: None,
+            metadata: BTreeMap::new(),
+            envelope_version: CONTENT_ENVELOPE_VERSION,
+            recipient_key_generation: 0,
+            creator_signing_key,
+            payment_ledger: None,
         };
 
         Ok(vault)
@@ -434,6 +940,8 @@ pub fn from_state(
         content_type: &str,
         intended_recipient: Option<Vec<u8>>,
     ) -> Result<LimboVault, DsmError> {
+        Self::validate_condition(&fulfillment_condition)?;
+
         // Get state entropy as part of the cryptographic material
         let state_entropy = &state.entropy;
 
@@ -469,17 +977,13 @@ pub fn from_state(
         aad.extend_from_slice(&state_number_bytes);
         aad.extend_from_slice(&state.hash);
 
-        // Encrypt the content with the shared secret
-        let encrypted_data = kyber::aes_encrypt(&shared_secret, &nonce, content)
+        // Seal the content with the blinding factor of its commitment, then
+        // encrypt it with the shared secret
+        let blinding_factor = derive_blinding_factor(creator_keypair.1, &vault_id);
+        let (envelope, commitment) = seal_content_envelope(content, &blinding_factor)?;
+        let encrypted_data = kyber::aes_encrypt(&shared_secret, &nonce, &envelope)
             .map_err(|e| DsmError::crypto("Failed to encrypt vault content", Some(e)))?;
 
-        // Create Pedersen commitment to the content
-        let params = PedersenParams::new(SecurityLevel::Standard128);
-
-        // Create the commitment
-        let (commitment, _r) =
-            PedersenCommitment::commit(&params, content, &mut rand::thread_rng())?;
-
         // Hash all parameters for integrity verification
         let mut parameters = Vec::new();
         parameters.extend_from_slice(creator_keypair.0);
@@ -492,6 +996,13 @@ pub fn from_state(
             parameters.extend_from_slice(recipient);
         }
         parameters.extend_from_slice(&commitment.to_bytes());
+        parameters.extend_from_slice(VAULT_ENVELOPE_DOMAIN);
+        parameters.push(CONTENT_ENVELOPE_VERSION);
+        let creator_signing_key = Self::creator_signing_key_for(creator_keypair)?;
+        if !creator_signing_key.is_empty() {
+            parameters.extend_from_slice(VAULT_SIGNING_KEY_DOMAIN);
+            parameters.extend_from_slice(&creator_signing_key);
+        }
 
         // Create binding for the hash result to prevent temporary value drop
         let parameters_hash_result = blake3::hash(&parameters);
@@ -518,7 +1029,7 @@ pub fn from_state(
             intended_recipient,
             state: VaultState::Limbo,
             content_type: content_type.to_string(),
-            encrypted_content: EncryptedContent {
+            encrypted_content: EncryptedContent::Blob {
                 encapsulated_key,
                 encrypted_data,
                 nonce,
@@ -529,78 +1040,396 @@ pub fn from_state(
             creator_signature,
             verification_positions,
             reference_state_hash: state.hash.clone(),
+            timelock_extensions: Vec::new(),
+            ownership_transfers: Vec::new(),
+            expires_at_state: None,
+            expires_at_time: None,
+            recipient_reassignments: Vec::new(),
+            split: None,
+            metadata: BTreeMap::new(),
+            envelope_version: CONTENT_ENVELOPE_VERSION,
+            recipient_key_generation: 0,
+            creator_signing_key,
+            payment_ledger: None,
         };
 
         Ok(vault)
     }
 
-    /// Verify the integrity of a vault
-    pub fn verify(&self) -> Result<bool, DsmError> {
-        // Reconstruct the parameters hash
-        let mut parameters = Vec::new();
-        parameters.extend_from_slice(&self.creator_public_key);
-        parameters.extend_from_slice(self.id.as_bytes());
-        parameters.extend_from_slice(&Self::u64_to_bytes(self.created_at_state));
-        parameters.extend_from_slice(&self.reference_state_hash);
-        parameters.extend_from_slice(
-            &bincode::serialize(&self.fulfillment_condition).unwrap_or_default(),
-        );
-        if let Some(recipient) = &self.intended_recipient {
-            parameters.extend_from_slice(recipient);
+    /// Reject conditions that could never be fulfilled as intended
+    fn validate_condition(condition: &FulfillmentMechanism) -> Result<(), DsmError> {
+        match condition {
+            FulfillmentMechanism::MultiSig {
+                public_keys,
+                threshold,
+            } => {
+                if *threshold == 0 || *threshold > public_keys.len() {
+                    return Err(DsmError::invalid_parameter(format!(
+                        "MultiSig threshold {} is not between 1 and {} signers",
+                        threshold,
+                        public_keys.len()
+                    )));
+                }
+                Ok(())
+            }
+            FulfillmentMechanism::And(conditions) | FulfillmentMechanism::Or(conditions) => {
+                conditions.iter().try_for_each(Self::validate_condition)
+            }
+            _ => Ok(()),
         }
-        parameters.extend_from_slice(&self.content_commitment.to_bytes());
+    }
+
+    /// Create a vault whose ciphertext is stored in chunks apart from it
+    ///
+    /// The content is encrypted as by `new`, then split into chunks of
+    /// `chunk_size` bytes. The signed parameters cover the ordered chunk
+    /// hashes, so every chunk is bound. The returned
+    /// chunks are stored with `put_vault_chunk` and reassembled with
+    /// `assemble_content` before claiming.
+    ///
+    /// # Returns
+    /// * `Result<(LimboVault, Vec<Vec<u8>>), DsmError>` - The vault and its chunks, in order
+    pub fn new_chunked(
+        creator_keypair: (&[u8], &[u8]),
+        fulfillment_condition: FulfillmentMechanism,
+        content: &[u8],
+        content_type: &str,
+        intended_recipient: Option<Vec<u8>>,
+        reference_state: &State,
+        chunk_size: usize,
+    ) -> Result<(LimboVault, Vec<Vec<u8>>), DsmError> {
+        let mut vault = Self::new(
+            creator_keypair,
+            fulfillment_condition,
+            content,
+            content_type,
+            intended_recipient,
+            reference_state,
+        )?;
 
-        let computed_hash = blake3::hash(&parameters);
+        let EncryptedContent::Blob {
+            encapsulated_key,
+            encrypted_data,
+            nonce,
+            aad,
+        } = std::mem::take(&mut vault.encrypted_content)
+        else {
+            return Err(DsmError::internal(
+                "New vault content is already chunked",
+                None::<std::convert::Infallible>,
+            ));
+        };
+        let (chunk_hashes, chunks) = split_into_chunks(&encrypted_data, chunk_size)?;
+
+        vault.encrypted_content = EncryptedContent::Chunked {
+            encapsulated_key,
+            nonce,
+            aad,
+            chunk_hashes,
+            chunk_size,
+            total_len: encrypted_data.len() as u64,
+        };
+        vault.reseal(creator_keypair.1)?;
 
-        // Verify that the stored parameters hash matches the computed one
-        if self.parameters_hash != computed_hash.as_bytes() {
-            return Ok(false);
+        Ok((vault, chunks))
+    }
+
+    /// Let the creator reclaim the vault if it is not claimed in time
+    ///
+    /// The vault expires at `expires_at_state` or `expires_at_time`,
+    /// whichever comes first, after which it can no longer be unlocked or
+    /// claimed and the creator may reclaim it with `reclaim_expired`. The
+    /// expiry is committed in the parameters hash, so the vault is re-signed
+    /// with the original creator's key.
+    pub fn with_expiry(
+        mut self,
+        expires_at_state: Option<u64>,
+        expires_at_time: Option<u64>,
+        creator_private_key: &[u8],
+    ) -> Result<Self, DsmError> {
+        if expires_at_state.is_none() && expires_at_time.is_none() {
+            return Err(DsmError::invalid_parameter(
+                "Vault expiry needs a state number or a time",
+            ));
         }
 
-        // Verify the creator's signature on the parameters hash
-        let signature_valid = sphincs::sphincs_verify(
-            &self.creator_public_key,
+        if expires_at_state.is_some_and(|state_number| state_number <= self.created_at_state) {
+            return Err(DsmError::invalid_parameter(format!(
+                "Vault created at state {} cannot expire before it",
+                self.created_at_state
+            )));
+        }
+
+        self.expires_at_state = expires_at_state;
+        self.expires_at_time = expires_at_time;
+        self.reseal(creator_private_key)?;
+
+        if !sphincs::sphincs_verify(
+            self.original_signing_key(),
             &self.parameters_hash,
             &self.creator_signature,
-        )?;
+        )? {
+            return Err(DsmError::verification(
+                "Vault expiry must be signed by the vault's original creator",
+            ));
+        }
 
-        Ok(signature_valid)
+        Ok(self)
     }
 
-    /// Verify that a proof fulfills the vault's condition
-    pub fn verify_fulfillment(
-        &self,
-        proof: &FulfillmentProof,
-        reference_state: &State,
-    ) -> Result<bool, DsmError> {
-        match (&self.fulfillment_condition, proof) {
-            // Time-based verification against reference state
-            (
-                FulfillmentMechanism::TimeRelease {
-                    unlock_time,
-                    reference_states,
-                },
-                FulfillmentProof::TimeProof {
-                    reference_state: proof_state,
-                    state_proof,
-                },
-            ) => {
-                // Verify the reference state is legitimate using hash chain verification
-                if !reference_states.contains(proof_state) {
-                    return Ok(false);
-                }
+    /// Attach searchable metadata to a newly created vault
+    ///
+    /// The metadata is checked against `limits` and committed in the
+    /// parameters hash, so it cannot be altered without the original
+    /// creator's key, which re-signs the vault.
+    pub fn with_metadata(
+        mut self,
+        metadata: BTreeMap<String, String>,
+        limits: &VaultMetadataLimits,
+        creator_private_key: &[u8],
+    ) -> Result<Self, DsmError> {
+        limits.check(&metadata)?;
 
-                let verification_result =
-                    self.verify_state_reference(proof_state, state_proof, reference_state)?;
-                if !verification_result {
-                    return Ok(false);
-                }
+        self.metadata = metadata;
+        self.reseal(creator_private_key)?;
 
-                if !self.verify_time_condition(*unlock_time, reference_state) {
-                    return Ok(false);
-                }
+        if !sphincs::sphincs_verify(
+            self.original_signing_key(),
+            &self.parameters_hash,
+            &self.creator_signature,
+        )? {
+            return Err(DsmError::verification(
+                "Vault metadata must be signed by the vault's original creator",
+            ));
+        }
 
-                Ok(true)
+        Ok(self)
+    }
+
+    /// Make the vault splittable between the recipients of `split`
+    ///
+    /// Each recipient then claims its own share with `claim_share` instead of
+    /// the whole vault being claimed at once. The allocations are committed in
+    /// the parameters hash, so the vault is re-signed with the original
+    /// creator's key. A vault with an intended recipient cannot be split.
+    pub fn with_split(
+        mut self,
+        split: VaultSplit,
+        creator_private_key: &[u8],
+    ) -> Result<Self, DsmError> {
+        if self.intended_recipient.is_some() {
+            return Err(DsmError::invalid_parameter(
+                "A vault with an intended recipient cannot be split",
+            ));
+        }
+        if !split.is_well_formed() || split.claimed.iter().any(|claimed| *claimed) {
+            return Err(DsmError::invalid_parameter(
+                "A new vault split cannot have claimed allocations",
+            ));
+        }
+
+        self.split = Some(split);
+        self.reseal(creator_private_key)?;
+
+        if !sphincs::sphincs_verify(
+            self.original_signing_key(),
+            &self.parameters_hash,
+            &self.creator_signature,
+        )? {
+            return Err(DsmError::verification(
+                "Vault split must be signed by the vault's original creator",
+            ));
+        }
+
+        Ok(self)
+    }
+
+    /// Recompute and re-sign the parameters hash after a committed field changed
+    fn reseal(&mut self, creator_private_key: &[u8]) -> Result<(), DsmError> {
+        let hash_result = self.compute_parameters_hash();
+        self.parameters_hash = hash_result.as_bytes().to_vec();
+        self.creator_signature = sphincs::sphincs_sign(creator_private_key, &self.parameters_hash)
+            .map_err(|e| DsmError::crypto("Failed to sign vault parameters", Some(e)))?;
+        self.regenerate_positions(&hash_result)?;
+
+        Ok(())
+    }
+
+    /// Derive the verification positions, which also cover the latest reassignment
+    fn regenerate_positions(&mut self, parameters_hash: &blake3::Hash) -> Result<(), DsmError> {
+        let reassignment = self
+            .recipient_reassignments
+            .last()
+            .map(RecipientReassignment::signing_bytes);
+        let seed = generate_seed(parameters_hash, self.id.as_bytes(), reassignment.as_deref());
+        self.verification_positions = generate_positions(&seed, None)?;
+
+        Ok(())
+    }
+
+    /// Hash of the vault parameters the creator signs
+    fn compute_parameters_hash(&self) -> blake3::Hash {
+        let mut parameters = Vec::new();
+        parameters.extend_from_slice(&self.creator_public_key);
+        parameters.extend_from_slice(self.id.as_bytes());
+        parameters.extend_from_slice(&Self::u64_to_bytes(self.created_at_state));
+        parameters.extend_from_slice(&self.reference_state_hash);
+        parameters.extend_from_slice(
+            &bincode::serialize(&self.fulfillment_condition).unwrap_or_default(),
+        );
+        if let Some(recipient) = self.original_recipient() {
+            parameters.extend_from_slice(recipient);
+        }
+        parameters.extend_from_slice(&self.content_commitment.to_bytes());
+        if self.envelope_version != 0 {
+            // Vaults without an envelope keep the parameters hash they always had
+            parameters.extend_from_slice(VAULT_ENVELOPE_DOMAIN);
+            parameters.push(self.envelope_version);
+        }
+        if !self.creator_signing_key.is_empty() {
+            parameters.extend_from_slice(VAULT_SIGNING_KEY_DOMAIN);
+            parameters.extend_from_slice(&self.creator_signing_key);
+        }
+        if let EncryptedContent::Chunked { chunk_hashes, .. } = &self.encrypted_content {
+            // The commitment covers the plaintext, so bind the chunk list directly
+            parameters.extend_from_slice(&chunk_list_bytes(chunk_hashes));
+        }
+        if self.recipient_key_generation != 0 {
+            // Bind the ciphertext of the current generation, so a superseded
+            // one no longer matches the signed parameters
+            parameters.extend_from_slice(VAULT_KEY_GENERATION_DOMAIN);
+            parameters.extend_from_slice(&self.recipient_key_generation.to_le_bytes());
+            let content = bincode::serialize(&self.encrypted_content).unwrap_or_default();
+            parameters.extend_from_slice(blake3::hash(&content).as_bytes());
+        }
+        if self.expires_at_state.is_some() || self.expires_at_time.is_some() {
+            // Vaults without an expiry keep the parameters hash they always had
+            parameters.extend_from_slice(VAULT_EXPIRY_DOMAIN);
+            for expiry in [self.expires_at_state, self.expires_at_time] {
+                parameters.extend_from_slice(&bincode::serialize(&expiry).unwrap_or_default());
+            }
+        }
+        if let Some(split) = &self.split {
+            parameters.extend_from_slice(&split.commitment_bytes());
+        }
+        if !self.metadata.is_empty() {
+            // Vaults without metadata keep the parameters hash they always had
+            parameters.extend_from_slice(VAULT_METADATA_DOMAIN);
+            parameters.extend_from_slice(&(self.metadata.len() as u64).to_le_bytes());
+            for field in self.metadata.iter().flat_map(|(key, value)| [key, value]) {
+                parameters.extend_from_slice(&(field.len() as u64).to_le_bytes());
+                parameters.extend_from_slice(field.as_bytes());
+            }
+        }
+
+        blake3::hash(&parameters)
+    }
+
+    /// Verify the integrity of a vault
+    pub fn verify(&self) -> Result<bool, DsmError> {
+        // Reconstruct the parameters hash
+        let computed_hash = self.compute_parameters_hash();
+
+        // Verify that the stored parameters hash matches the computed one
+        if self.parameters_hash != computed_hash.as_bytes() {
+            return Ok(false);
+        }
+
+        // Verify the creator's signature on the parameters hash
+        let signature_valid = sphincs::sphincs_verify(
+            self.original_signing_key(),
+            &self.parameters_hash,
+            &self.creator_signature,
+        )?;
+        if !signature_valid {
+            return Ok(false);
+        }
+
+        // Each transfer must be signed by the creator it hands the rights from
+        let mut creator = self.creator_public_key.as_slice();
+        for transfer in &self.ownership_transfers {
+            if transfer.vault_id != self.id || transfer.old_creator != creator {
+                return Ok(false);
+            }
+            if !transfer.verify().unwrap_or(false) {
+                return Ok(false);
+            }
+            creator = &transfer.new_creator;
+        }
+
+        // Each reassignment hands the vault on from the recipient before it,
+        // signed by a holder of the creator rights
+        let creators: Vec<&[u8]> = std::iter::once(self.creator_public_key.as_slice())
+            .chain(
+                self.ownership_transfers
+                    .iter()
+                    .map(|t| t.new_creator.as_slice()),
+            )
+            .collect();
+        let mut recipient = self.original_recipient();
+        for reassignment in &self.recipient_reassignments {
+            if reassignment.vault_id != self.id
+                || reassignment.old_recipient.as_deref() != recipient
+                || !creators.contains(&reassignment.creator.as_slice())
+            {
+                return Ok(false);
+            }
+            if !reassignment.verify().unwrap_or(false) {
+                return Ok(false);
+            }
+            recipient = Some(reassignment.new_recipient.as_slice());
+        }
+        if recipient != self.intended_recipient.as_deref() {
+            return Ok(false);
+        }
+
+        if self
+            .split
+            .as_ref()
+            .is_some_and(|split| !split.is_well_formed())
+        {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Verify that a proof fulfills the vault's condition
+    pub fn verify_fulfillment(
+        &self,
+        proof: &FulfillmentProof,
+        reference_state: &State,
+    ) -> Result<bool, DsmError> {
+        match (&self.fulfillment_condition, proof) {
+            // Time-based verification against reference state
+            (
+                FulfillmentMechanism::TimeRelease {
+                    unlock_time,
+                    reference_states,
+                },
+                FulfillmentProof::TimeProof {
+                    reference_state: proof_state,
+                    state_proof,
+                },
+            ) => {
+                // Verify the reference state is legitimate using hash chain verification
+                if !reference_states.contains(proof_state) {
+                    return Ok(false);
+                }
+
+                let verification_result =
+                    self.verify_state_reference(proof_state, state_proof, reference_state)?;
+                if !verification_result {
+                    return Ok(false);
+                }
+
+                // Extensions push the unlock time back
+                let unlock_time = self.unlock_time().unwrap_or(*unlock_time);
+                if !self.verify_time_condition(unlock_time, reference_state) {
+                    return Ok(false);
+                }
+
+                Ok(true)
             }
             // Payment verification through state machine proof
             (
@@ -674,6 +1503,58 @@ pub fn verify_fulfillment(
                 Ok(valid_signatures >= *threshold)
             }
 
+            // Threshold of distinct signers over the parameters hash
+            (
+                FulfillmentMechanism::MultiSig {
+                    public_keys,
+                    threshold,
+                },
+                FulfillmentProof::MultiSigProof { signatures },
+            ) => Ok(self.verify_multisig(public_keys, *threshold, signatures)),
+
+            // Preimage of the condition's hash
+            (
+                FulfillmentMechanism::HashPreimage {
+                    hash,
+                    max_preimage_len,
+                },
+                FulfillmentProof::Preimage(preimage),
+            ) => {
+                // Bound the hashing work an unlock attempt can cause
+                if preimage.len() > *max_preimage_len {
+                    return Ok(false);
+                }
+
+                let revealed = blake3::hash(preimage);
+                Ok(constant_time_eq::constant_time_eq(
+                    revealed.as_bytes(),
+                    hash,
+                ))
+            }
+
+            // Token transfer recorded in the payer's hash chain
+            (
+                FulfillmentMechanism::TokenPayment {
+                    token_id,
+                    amount,
+                    recipient_genesis_hash,
+                },
+                FulfillmentProof::TokenPaymentProof {
+                    previous_state,
+                    payment_state,
+                    payer_genesis_hash,
+                    payer_signature,
+                },
+            ) => self.verify_token_payment(
+                token_id,
+                *amount,
+                recipient_genesis_hash,
+                previous_state,
+                payment_state,
+                payer_genesis_hash,
+                payer_signature,
+            ),
+
             // Random walk verification
             (
                 FulfillmentMechanism::RandomWalkVerification {
@@ -854,7 +1735,676 @@ fn verify_hash_chain(
         let valid =
             blake3::hash(&[hash_chain_proof, &reference_state.hash].concat()).as_bytes()[0] < 128;
 
-        Ok(valid)
+        Ok(valid)
+    }
+
+    /// Check a `TokenPayment` proof
+    ///
+    /// The states come from the payer, so they are checked against the
+    /// vault's payment ledger: the payment state must be signed with the key
+    /// from the payer's genesis, one of the two states must be the payer's
+    /// trusted chain head, and the invalidation registry must not list either.
+    /// The payment state must then follow the previous state in a valid hash
+    /// chain and its operation must transfer at least `amount` of `token_id`
+    /// to the recipient with this vault's ID as the message.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_token_payment(
+        &self,
+        token_id: &str,
+        amount: u64,
+        recipient_genesis_hash: &[u8],
+        previous_state: &[u8],
+        payment_state: &[u8],
+        payer_genesis_hash: &[u8],
+        payer_signature: &[u8],
+    ) -> Result<bool, DsmError> {
+        // Without a ledger nothing in the proof can be trusted
+        let Some(ledger) = &self.payment_ledger else {
+            return Ok(false);
+        };
+
+        // States that cannot be decoded prove nothing
+        let (Ok(previous), Ok(payment)) = (
+            Versioned::<State>::deserialize(previous_state),
+            Versioned::<State>::deserialize(payment_state),
+        ) else {
+            return Ok(false);
+        };
+        let (previous, payment) = (previous.payload, payment.payload);
+
+        let Some(payer_public_key) = ledger.payer_public_key(payer_genesis_hash) else {
+            return Ok(false);
+        };
+        let signed = sphincs::sphincs_verify(
+            &payer_public_key,
+            &token_payment_message(&payment.hash),
+            payer_signature,
+        )
+        .unwrap_or(false);
+        if !signed {
+            return Ok(false);
+        }
+
+        // A forked chain cannot reach the head the ledger trusts
+        let Some(head) = ledger.trusted_head(payer_genesis_hash) else {
+            return Ok(false);
+        };
+        if head != previous.hash && head != payment.hash {
+            return Ok(false);
+        }
+
+        if ledger.is_invalidated(&previous.hash) || ledger.is_invalidated(&payment.hash) {
+            return Ok(false);
+        }
+        if !verify_chain_link(&previous, &payment)? {
+            return Ok(false);
+        }
+
+        let Operation::Transfer {
+            token_id: paid_token_id,
+            amount: paid,
+            recipient,
+            message,
+            ..
+        } = &payment.operation
+        else {
+            return Ok(false);
+        };
+
+        Ok(paid_token_id == token_id
+            && paid.value() >= amount
+            && *recipient == hex::encode(recipient_genesis_hash)
+            && *message == self.id)
+    }
+
+    /// Check a `MultiSig` proof against the vault's parameters hash
+    ///
+    /// The whole proof is rejected if it names a signer twice, names an index
+    /// outside `public_keys` or carries a signature that does not verify, so
+    /// only proofs made entirely by the listed signers are accepted.
+    fn verify_multisig(
+        &self,
+        public_keys: &[Vec<u8>],
+        threshold: usize,
+        signatures: &[(usize, Vec<u8>)],
+    ) -> bool {
+        if threshold == 0 || threshold > public_keys.len() {
+            return false;
+        }
+
+        let mut signers = HashSet::new();
+        for (index, signature) in signatures {
+            let Some(public_key) = public_keys.get(*index) else {
+                return false;
+            };
+            if !signers.insert(*index) {
+                return false;
+            }
+
+            // Malformed signatures fail verification like wrong ones
+            let valid = sphincs::sphincs_verify(public_key, &self.parameters_hash, signature)
+                .unwrap_or(false);
+            if !valid {
+                return false;
+            }
+        }
+
+        signers.len() >= threshold
+    }
+
+    /// Unlock time of a `TimeRelease` vault, taking extensions into account
+    pub fn unlock_time(&self) -> Option<u64> {
+        match &self.fulfillment_condition {
+            FulfillmentMechanism::TimeRelease { unlock_time, .. } => Some(
+                self.timelock_extensions
+                    .last()
+                    .map_or(*unlock_time, |extension| extension.new_unlock_time),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Public key holding the vault's creator rights, taking transfers into account
+    pub fn creator(&self) -> &[u8] {
+        self.ownership_transfers
+            .last()
+            .map_or(&self.creator_public_key, |transfer| &transfer.new_creator)
+    }
+
+    /// SPHINCS+ key that signs for the vault's current creator, such as on its posts
+    ///
+    /// This is [`Self::creator`], except for a vault created under a key that
+    /// cannot sign, such as a Kyber key, and not transferred since. The
+    /// SPHINCS+ key bound into its signed parameters at creation signs then.
+    pub fn signing_key(&self) -> &[u8] {
+        match self.ownership_transfers.last() {
+            Some(transfer) => &transfer.new_creator,
+            None => self.original_signing_key(),
+        }
+    }
+
+    /// SPHINCS+ key that signs the vault's parameters for its original creator
+    fn original_signing_key(&self) -> &[u8] {
+        if self.creator_signing_key.is_empty() {
+            &self.creator_public_key
+        } else {
+            &self.creator_signing_key
+        }
+    }
+
+    /// SPHINCS+ key to bind to a vault created under `creator_keypair`
+    ///
+    /// Empty when the creator's public key is a SPHINCS+ key and signs
+    /// itself. Otherwise it is the public key embedded in the secret key.
+    fn creator_signing_key_for(creator_keypair: (&[u8], &[u8])) -> Result<Vec<u8>, DsmError> {
+        if creator_keypair.0.len() == sphincs::public_key_bytes() {
+            return Ok(Vec::new());
+        }
+        sphincs::public_key_from_secret_key(creator_keypair.1)
+    }
+
+    /// Recipient the vault was created for, before any reassignment
+    pub fn original_recipient(&self) -> Option<&[u8]> {
+        match self.recipient_reassignments.first() {
+            Some(reassignment) => reassignment.old_recipient.as_deref(),
+            None => self.intended_recipient.as_deref(),
+        }
+    }
+
+    /// Redirect an unclaimed vault to a new recipient
+    ///
+    /// `creator_signature` must be the current creator's signature over
+    /// [`RecipientReassignment::signing_message`] for the vault's current and
+    /// new recipient and `reference_state`'s hash. The content key is
+    /// re-encapsulated to the new recipient's Kyber public key, and the
+    /// reassignment is recorded in the vault's verification positions. A
+    /// claimed, invalidated or reclaimed vault can no longer be reassigned,
+    /// nor can one re-encrypted with [`Self::reencrypt_for_recipient`].
+    pub fn reassign_recipient(
+        &mut self,
+        new_recipient: &[u8],
+        creator_signature: &[u8],
+        reference_state: &State,
+    ) -> Result<RecipientReassignment, DsmError> {
+        if matches!(
+            self.state,
+            VaultState::Claimed { .. }
+                | VaultState::Invalidated { .. }
+                | VaultState::Reclaimed { .. }
+        ) {
+            return Err(DsmError::validation(
+                "Vault has been resolved and its recipient cannot be reassigned",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if self.split.is_some() {
+            return Err(DsmError::validation(
+                "A splittable vault goes to its allocations and has no recipient to reassign",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        // Its signed parameters bind the ciphertext, which reassigning would replace
+        if self.recipient_key_generation != 0 {
+            return Err(DsmError::invalid_operation(
+                "A vault re-encrypted to a new recipient key cannot be reassigned",
+            ));
+        }
+
+        if new_recipient.is_empty() || self.intended_recipient.as_deref() == Some(new_recipient) {
+            return Err(DsmError::validation(
+                "Recipient reassignment must name a new recipient",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let reassignment = RecipientReassignment {
+            vault_id: self.id.clone(),
+            old_recipient: self.intended_recipient.clone(),
+            new_recipient: new_recipient.to_vec(),
+            reference_state_hash: reference_state.hash.clone(),
+            creator: self.creator().to_vec(),
+            creator_signature: creator_signature.to_vec(),
+        };
+
+        // Malformed signatures fail verification like wrong ones
+        if !reassignment.verify().unwrap_or(false) {
+            return Err(DsmError::verification(
+                "Recipient reassignment is not signed by the vault creator",
+            ));
+        }
+
+        self.encrypted_content = self.encrypt_for_recipient(new_recipient)?;
+        self.intended_recipient = Some(new_recipient.to_vec());
+        self.recipient_reassignments.push(reassignment.clone());
+        let parameters_hash = self.compute_parameters_hash();
+        self.regenerate_positions(&parameters_hash)?;
+
+        Ok(reassignment)
+    }
+
+    /// Re-encrypt the vault content to a recipient's Kyber public key
+    ///
+    /// The current recipient's key decapsulates the content key, and the
+    /// content is encrypted again under a key encapsulated to the new
+    /// recipient.
+    fn encrypt_for_recipient(&self, new_recipient: &[u8]) -> Result<EncryptedContent, DsmError> {
+        let recipient_sk = self.get_recipient_secret_key()?.ok_or_else(|| {
+            DsmError::crypto(
+                "Unable to retrieve recipient's secret key for re-encryption",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        self.reencrypt_content(&recipient_sk, new_recipient)
+    }
+
+    /// Decrypt the vault content with `secret_key` and encrypt it to `public_key`
+    ///
+    /// Chunked content is stored apart from the vault and cannot be
+    /// re-encrypted.
+    fn reencrypt_content(
+        &self,
+        secret_key: &[u8],
+        public_key: &[u8],
+    ) -> Result<EncryptedContent, DsmError> {
+        let EncryptedContent::Blob {
+            encapsulated_key,
+            encrypted_data,
+            nonce,
+            aad,
+        } = &self.encrypted_content
+        else {
+            return Err(DsmError::invalid_operation(
+                "Chunked vault content cannot be re-encrypted",
+            ));
+        };
+
+        // Test vaults use a mock encapsulation that does not depend on the keys
+        #[cfg(test)]
+        let (encapsulated_key, encrypted_data) = {
+            let _ = (secret_key, public_key);
+            (encapsulated_key.clone(), encrypted_data.clone())
+        };
+
+        #[cfg(not(test))]
+        let (encapsulated_key, encrypted_data) = {
+            let shared_secret = kyber::kyber_decapsulate(secret_key, encapsulated_key)
+                .map_err(|e| DsmError::crypto("Failed to decapsulate content key", Some(e)))?;
+            let content = kyber::aes_decrypt(&shared_secret, nonce, encrypted_data)
+                .map_err(|e| DsmError::crypto("Failed to decrypt vault content", Some(e)))?;
+
+            let (encapsulated_key, shared_secret) = kyber::kyber_encapsulate(public_key)
+                .map_err(|e| DsmError::crypto("Failed to encapsulate key", Some(e)))?;
+            let encrypted_data = kyber::aes_encrypt(&shared_secret, nonce, &content)
+                .map_err(|e| DsmError::crypto("Failed to encrypt vault content", Some(e)))?;
+            (encapsulated_key, encrypted_data)
+        };
+
+        Ok(EncryptedContent::Blob {
+            encapsulated_key,
+            encrypted_data,
+            nonce: nonce.clone(),
+            aad: aad.clone(),
+        })
+    }
+
+    /// Copy of the vault with its content re-encrypted to a rotated creator key
+    ///
+    /// A vault without an intended recipient is encrypted to its creator's
+    /// key. When the creator rotates that key, the content is decrypted with
+    /// the old key, encrypted to the new one, and the vault is re-signed with
+    /// the new key, which becomes its `creator_public_key`. The old secret key
+    /// must match the vault's creator key, and the vault must be unresolved,
+    /// unexpired and never transferred. The vault itself is left untouched.
+    pub fn reencrypt_for_creator(
+        &self,
+        old_keypair: (&[u8], &[u8]),
+        new_keypair: (&[u8], &[u8]),
+        current_state: &State,
+    ) -> Result<LimboVault, DsmError> {
+        if matches!(
+            self.state,
+            VaultState::Claimed { .. }
+                | VaultState::Invalidated { .. }
+                | VaultState::Reclaimed { .. }
+        ) {
+            return Err(DsmError::validation(
+                "Vault has been resolved and its content cannot be re-encrypted",
+                None::<std::convert::Infallible>,
+            ));
+        }
+        self.ensure_not_expired(current_state)?;
+
+        if self.intended_recipient.is_some() {
+            return Err(DsmError::invalid_operation(
+                "Vault content is encrypted to its recipient, not its creator",
+            ));
+        }
+        if !self.ownership_transfers.is_empty() {
+            return Err(DsmError::invalid_operation(
+                "A transferred vault's creator rights move by transfer, not key rotation",
+            ));
+        }
+        if old_keypair.0 != self.creator_public_key.as_slice() || old_keypair.0 == new_keypair.0 {
+            return Err(DsmError::verification(
+                "Old key is not the creator key of this vault",
+            ));
+        }
+
+        // Only the holder of the old secret key can rotate the vault away from it
+        let proof = sphincs::sphincs_sign(old_keypair.1, &self.parameters_hash)
+            .map_err(|e| DsmError::crypto("Failed to sign with the old creator key", Some(e)))?;
+        if !sphincs::sphincs_verify(old_keypair.0, &self.parameters_hash, &proof).unwrap_or(false) {
+            return Err(DsmError::verification(
+                "Old secret key does not match the vault's creator key",
+            ));
+        }
+
+        let mut rotated = self.clone();
+        rotated.encrypted_content = self.reencrypt_content(old_keypair.1, new_keypair.0)?;
+        rotated.creator_public_key = new_keypair.0.to_vec();
+        rotated.reseal(new_keypair.1)?;
+
+        if !rotated.verify()? {
+            return Err(DsmError::verification(
+                "Re-encrypted vault must be signed by the new creator key",
+            ));
+        }
+
+        Ok(rotated)
+    }
+
+    /// Copy of the vault with its content re-encrypted to its recipient's new key
+    ///
+    /// When the recipient rotates its Kyber key, the content encrypted to the
+    /// old key can no longer be claimed. The creator, who kept `content`,
+    /// seals it again with the blinding factor derived from its secret key,
+    /// so the content commitment is unchanged, and encrypts it under a key
+    /// freshly encapsulated to `new_recipient`. The ID and condition are kept,
+    /// the recipient key generation is incremented and the vault is re-signed
+    /// with the creator key, so the superseded ciphertext no longer matches
+    /// the signed parameters. The vault itself is left untouched.
+    ///
+    /// Only the original creator can re-encrypt an unresolved vault sealed in
+    /// a content envelope, and only while it has never been reassigned.
+    /// Content that does not open the commitment, or a secret key other than
+    /// the one the vault was created with, fails with `CommitmentMismatch`.
+    ///
+    /// # Returns
+    /// * `Result<(LimboVault, VaultContentUpdate), DsmError>` - The re-encrypted
+    ///   vault and the signed update to publish
+    pub fn reencrypt_for_recipient(
+        &self,
+        new_recipient: &[u8],
+        creator_keypair: (&[u8], &[u8]),
+        content: &[u8],
+    ) -> Result<(LimboVault, VaultContentUpdate), DsmError> {
+        if matches!(
+            self.state,
+            VaultState::Claimed { .. }
+                | VaultState::Invalidated { .. }
+                | VaultState::Reclaimed { .. }
+        ) {
+            return Err(DsmError::validation(
+                "Vault has been resolved and its content cannot be re-encrypted",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if self.split.is_some() {
+            return Err(DsmError::validation(
+                "A splittable vault goes to its allocations and has no recipient key",
+                None::<std::convert::Infallible>,
+            ));
+        }
+        let Some(current_recipient) = self.intended_recipient.as_deref() else {
+            return Err(DsmError::invalid_operation(
+                "Vault content is encrypted to its creator, not a recipient",
+            ));
+        };
+        if !self.recipient_reassignments.is_empty() {
+            return Err(DsmError::invalid_operation(
+                "A reassigned vault is re-encrypted by reassigning it again",
+            ));
+        }
+        if new_recipient.is_empty() || new_recipient == current_recipient {
+            return Err(DsmError::validation(
+                "Re-encryption must name a new recipient key",
+                None::<std::convert::Infallible>,
+            ));
+        }
+        if !self.ownership_transfers.is_empty()
+            || creator_keypair.0 != self.creator_public_key.as_slice()
+        {
+            return Err(DsmError::verification(
+                "Only the vault's original creator can re-encrypt its content",
+            ));
+        }
+        if self.envelope_version != CONTENT_ENVELOPE_VERSION {
+            return Err(DsmError::invalid_operation(
+                "Vault content predates content envelopes and cannot be re-encrypted",
+            ));
+        }
+
+        // Rebuild the envelope, which must open the commitment it was sealed with
+        let blinding_factor = derive_blinding_factor(creator_keypair.1, &self.id);
+        if !verify_vault_content_commitment(self, content, &blinding_factor) {
+            return Err(DsmError::CommitmentMismatch {
+                vault_id: self.id.clone(),
+            });
+        }
+        let mut envelope = blinding_factor.to_vec();
+        envelope.extend_from_slice(content);
+
+        let generation = self
+            .recipient_key_generation
+            .checked_add(1)
+            .ok_or_else(|| {
+                DsmError::invalid_operation("Recipient key generation of the vault is exhausted")
+            })?;
+        let encrypted_content = self.encrypt_envelope(new_recipient, &envelope)?;
+
+        let mut rotated = self.clone();
+        rotated.encrypted_content = encrypted_content.clone();
+        rotated.intended_recipient = Some(new_recipient.to_vec());
+        rotated.recipient_key_generation = generation;
+        rotated.reseal(creator_keypair.1)?;
+
+        if !rotated.verify()? {
+            return Err(DsmError::verification(
+                "Re-encrypted vault must be signed by the creator key",
+            ));
+        }
+
+        let update = VaultContentUpdate::sign(
+            &self.id,
+            generation,
+            new_recipient,
+            encrypted_content,
+            creator_keypair,
+        )?;
+
+        Ok((rotated, update))
+    }
+
+    /// Encrypt a content envelope under a key freshly encapsulated to `public_key`
+    ///
+    /// Chunked content is stored apart from the vault and cannot be
+    /// re-encrypted.
+    fn encrypt_envelope(
+        &self,
+        public_key: &[u8],
+        envelope: &[u8],
+    ) -> Result<EncryptedContent, DsmError> {
+        let EncryptedContent::Blob { nonce, aad, .. } = &self.encrypted_content else {
+            return Err(DsmError::invalid_operation(
+                "Chunked vault content cannot be re-encrypted",
+            ));
+        };
+
+        // Test vaults use a mock encapsulation, here bound to the public key
+        #[cfg(test)]
+        let (encapsulated_key, encrypted_data) = {
+            let encapsulated_key = blake3::hash(public_key).as_bytes()[..4].to_vec();
+            let test_shared_key = [5, 6, 7, 8];
+            let encrypted_data = envelope
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| byte ^ test_shared_key[i % test_shared_key.len()])
+                .collect();
+            (encapsulated_key, encrypted_data)
+        };
+
+        #[cfg(not(test))]
+        let (encapsulated_key, encrypted_data) = {
+            let (encapsulated_key, shared_secret) = kyber::kyber_encapsulate(public_key)
+                .map_err(|e| DsmError::crypto("Failed to encapsulate key", Some(e)))?;
+            let encrypted_data = kyber::aes_encrypt(&shared_secret, nonce, envelope)
+                .map_err(|e| DsmError::crypto("Failed to encrypt vault content", Some(e)))?;
+            (encapsulated_key, encrypted_data)
+        };
+
+        Ok(EncryptedContent::Blob {
+            encapsulated_key,
+            encrypted_data,
+            nonce: nonce.clone(),
+            aad: aad.clone(),
+        })
+    }
+
+    /// Hand the vault's creator rights to another identity
+    ///
+    /// The transfer must be signed by the current creator. The original
+    /// `creator_public_key` is kept, as the vault's parameters are signed
+    /// with it. A claimed or invalidated vault can no longer be transferred.
+    pub fn transfer_ownership(
+        &mut self,
+        transfer: OwnershipTransferRecord,
+    ) -> Result<(), DsmError> {
+        if transfer.vault_id != self.id {
+            return Err(DsmError::validation(
+                "Ownership transfer is for a different vault",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if matches!(
+            self.state,
+            VaultState::Claimed { .. }
+                | VaultState::Invalidated { .. }
+                | VaultState::Reclaimed { .. }
+        ) {
+            return Err(DsmError::validation(
+                "Vault has been resolved and its ownership cannot be transferred",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if transfer.new_creator.is_empty() || transfer.new_creator == transfer.old_creator {
+            return Err(DsmError::validation(
+                "Ownership transfer must name a new creator",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if transfer.old_creator != self.creator() || !transfer.verify().unwrap_or(false) {
+            return Err(DsmError::verification(
+                "Ownership transfer is not signed by the vault creator",
+            ));
+        }
+
+        self.ownership_transfers.push(transfer);
+
+        Ok(())
+    }
+
+    /// Push back the unlock time of a `TimeRelease` vault
+    ///
+    /// The extension must be signed by the vault's creator and move the
+    /// unlock time later. Once the unlock time has passed the vault is
+    /// claimable, so neither `current_state` nor the state the extension was
+    /// signed in may have reached it.
+    pub fn extend_timelock(
+        &mut self,
+        extension: TimelockExtension,
+        current_state: &State,
+    ) -> Result<(), DsmError> {
+        if extension.vault_id != self.id {
+            return Err(DsmError::validation(
+                "Time-lock extension is for a different vault",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if !matches!(self.state, VaultState::Limbo) {
+            return Err(DsmError::validation(
+                "Vault is not in limbo state and its time lock cannot be extended",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let unlock_time = self.unlock_time().ok_or_else(|| {
+            DsmError::validation(
+                "Only TimeRelease vaults have a time lock to extend",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        if current_state.state_number >= unlock_time || extension.state_number >= unlock_time {
+            return Err(DsmError::validation(
+                format!("Vault time lock already elapsed at state {}", unlock_time),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if extension.new_unlock_time <= unlock_time {
+            return Err(DsmError::validation(
+                "Time-lock extension must move the unlock time later",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if !extension.verify(self.creator())? {
+            return Err(DsmError::verification(
+                "Time-lock extension is not signed by the vault creator",
+            ));
+        }
+
+        self.timelock_extensions.push(extension);
+
+        Ok(())
+    }
+
+    /// Whether the vault has expired by `reference_state`
+    ///
+    /// A vault with an `expires_at_time` has also expired once the clock has
+    /// reached it.
+    pub fn is_expired(&self, reference_state: &State) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.is_expired_at(reference_state, now)
+    }
+
+    /// Whether the vault has expired by `reference_state` at `now` (seconds since epoch)
+    pub fn is_expired_at(&self, reference_state: &State, now: u64) -> bool {
+        self.expires_at_state
+            .is_some_and(|state_number| reference_state.state_number >= state_number)
+            || self.expires_at_time.is_some_and(|time| now >= time)
+    }
+
+    /// Fail if the vault has expired, as it then belongs to its creator again
+    fn ensure_not_expired(&self, reference_state: &State) -> Result<(), DsmError> {
+        if self.is_expired(reference_state) {
+            return Err(DsmError::validation(
+                format!("Vault {} has expired and can only be reclaimed", self.id),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        Ok(())
     }
 
     /// Attempt to unlock the vault with a fulfillment proof
@@ -872,6 +2422,8 @@ pub fn unlock(
             ));
         }
 
+        self.ensure_not_expired(reference_state)?;
+
         // Check that the requester is authorized (if a recipient is specified)
         if let Some(recipient) = &self.intended_recipient {
             if !constant_time_eq::constant_time_eq(recipient, requester) {
@@ -881,6 +2433,14 @@ pub fn unlock(
                 ));
             }
         }
+        if let Some(split) = &self.split {
+            if split.position(requester).is_none() {
+                return Err(DsmError::validation(
+                    "Requester has no allocation in this splittable vault",
+                    None::<std::io::Error>,
+                ));
+            }
+        }
 
         // Verify that the proof satisfies the condition against the reference state
         if !self.verify_fulfillment(&proof, reference_state)? {
@@ -900,7 +2460,9 @@ pub fn unlock(
     ///
     /// This implements the vault resolution mechanism described in whitepaper Section 20.4,
     /// deriving the unlocking key only after condition fulfillment and providing cryptographic
-    /// guarantees of the vault's integrity throughout the process.
+    /// guarantees of the vault's integrity throughout the process. Decrypted content that
+    /// does not open the vault's content commitment fails the claim with
+    /// `CommitmentMismatch` and leaves the vault unlocked.
     ///
     /// # Arguments
     /// * `claimant` - Public key of the entity claiming the vault
@@ -913,27 +2475,58 @@ pub fn claim(
         claimant: &[u8],
         reference_state: &State,
     ) -> Result<ClaimResult, DsmError> {
+        let Some(ciphertext) = self.encrypted_content.inline_data().map(<[u8]>::to_vec) else {
+            return Err(DsmError::validation(
+                "Vault content is chunked and must be claimed with claim_assembled",
+                None::<std::convert::Infallible>,
+            ));
+        };
+
+        self.claim_ciphertext(claimant, reference_state, &ciphertext)
+    }
+
+    /// Claim a chunked vault with its ciphertext from `assemble_content`
+    ///
+    /// The ciphertext is checked against the committed chunk hashes before
+    /// the vault is claimed.
+    pub fn claim_assembled(
+        &mut self,
+        claimant: &[u8],
+        reference_state: &State,
+        ciphertext: &[u8],
+    ) -> Result<ClaimResult, DsmError> {
+        self.encrypted_content.verify_assembled(ciphertext)?;
+        self.claim_ciphertext(claimant, reference_state, ciphertext)
+    }
+
+    fn claim_ciphertext(
+        &mut self,
+        claimant: &[u8],
+        reference_state: &State,
+        ciphertext: &[u8],
+    ) -> Result<ClaimResult, DsmError> {
+        if self.split.is_some() {
+            return Err(DsmError::validation(
+                "Splittable vault must be claimed share by share with claim_share",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
         // Step 1: Check that the vault is in unlocked state as per Section 20.4
-        match &self.state {
-            VaultState::Unlocked {
-                unlocked_state_number: _,
-                fulfillment_proof,
-            } => {
-                // Verify the proof again against the reference state
-                // to ensure continuous validity as described in Section 20.6
-                if !self.verify_fulfillment(fulfillment_proof, reference_state)? {
-                    return Err(DsmError::validation(
-                        "Fulfillment proof no longer valid against current reference state",
-                        None::<std::convert::Infallible>,
-                    ));
-                }
-            }
-            _ => {
-                return Err(DsmError::validation(
-                    "Vault is not in unlocked state and cannot be claimed",
-                    None::<std::convert::Infallible>,
-                ));
-            }
+        self.ensure_claimable(reference_state)?;
+
+        // A re-encrypted vault's signed parameters bind its current
+        // ciphertext, so a superseded one cannot be claimed
+        if self.recipient_key_generation != 0
+            && self.parameters_hash != self.compute_parameters_hash().as_bytes()
+        {
+            return Err(DsmError::validation(
+                format!(
+                    "Vault {} does not hold the ciphertext of recipient key generation {}",
+                    self.id, self.recipient_key_generation
+                ),
+                None::<std::convert::Infallible>,
+            ));
         }
 
         // Step 2: Generate a "proof of claim" using cryptographic binding
@@ -959,20 +2552,12 @@ pub fn claim(
         let proof_hash = blake3::hash(&claim_data);
         let claim_proof = proof_hash.as_bytes().to_vec();
 
-        // Step 3: Update the vault state to claimed using reference state's number
-        // This implements the state transition described in Section 20.4
-        self.state = VaultState::Claimed {
-            claimed_state_number: reference_state.state_number,
-            claimant: claimant.to_vec(),
-            claim_proof: claim_proof.clone(),
-        };
-
-        // Step 4: Compute the unlocking key as described in Section 20.3
+        // Step 3: Compute the unlocking key as described in Section 20.3
         // skV = H(L∥C∥σ)
         // Generate a deterministic unlocking key that only exists AFTER conditions are met
         let unlocking_key = self.compute_unlocking_key(claimant, &claim_proof)?;
 
-        // Step 5: Decrypt the content using the derived unlocking key
+        // Step 4: Decrypt the content using the derived unlocking key
         // This sequence follows the exact procedure in Section 20.6
         if let Some(intended_recipient_sk) = self.get_recipient_secret_key()? {
             // Recreate the Kyber secret key from bytes
@@ -984,7 +2569,7 @@ pub fn claim(
             })?;
 
             // Recreate the Kyber ciphertext from bytes
-            let ct = mlkem512::Ciphertext::from_bytes(&self.encrypted_content.encapsulated_key)
+            let ct = mlkem512::Ciphertext::from_bytes(self.encrypted_content.encapsulated_key())
                 .map_err(|_| {
                     DsmError::crypto(
                         "Invalid ciphertext format",
@@ -1004,16 +2589,25 @@ pub fn claim(
             let final_key = blake3::hash(&composite_key).as_bytes().to_vec();
 
             // Decrypt the content using the final key derived from both shared secret and unlocking key
-            let decrypted = kyber::aes_decrypt(
-                &final_key,
-                &self.encrypted_content.nonce,
-                &self.encrypted_content.encrypted_data,
-            )
-            .map_err(|e| DsmError::crypto("Failed to decrypt vault content", Some(e)))?;
+            let decrypted =
+                kyber::aes_decrypt(&final_key, self.encrypted_content.nonce(), ciphertext)
+                    .map_err(|e| DsmError::crypto("Failed to decrypt vault content", Some(e)))?;
+
+            // Step 5: Check the content against its commitment, so ciphertext
+            // swapped by a storage node fails the claim
+            let content = open_content_envelope(self, &decrypted)?;
+
+            // Step 6: Update the vault state to claimed using reference state's number
+            // This implements the state transition described in Section 20.4
+            self.state = VaultState::Claimed {
+                claimed_state_number: reference_state.state_number,
+                claimant: claimant.to_vec(),
+                claim_proof: claim_proof.clone(),
+            };
 
             Ok(ClaimResult {
                 vault: self.clone(),
-                content: decrypted,
+                content,
                 claim_proof,
             })
         } else {
@@ -1025,6 +2619,101 @@ pub fn claim(
         }
     }
 
+    /// Check that an unlocked vault can still be claimed against `reference_state`
+    fn ensure_claimable(&self, reference_state: &State) -> Result<(), DsmError> {
+        // An unlocked vault left unclaimed past its expiry is the creator's again
+        self.ensure_not_expired(reference_state)?;
+
+        match &self.state {
+            VaultState::Unlocked {
+                unlocked_state_number: _,
+                fulfillment_proof,
+            } => {
+                // Verify the proof again against the reference state
+                // to ensure continuous validity as described in Section 20.6
+                if !self.verify_fulfillment(fulfillment_proof, reference_state)? {
+                    return Err(DsmError::validation(
+                        "Fulfillment proof no longer valid against current reference state",
+                        None::<std::convert::Infallible>,
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(DsmError::validation(
+                "Vault is not in unlocked state and cannot be claimed",
+                None::<std::convert::Infallible>,
+            )),
+        }
+    }
+
+    /// Claim a recipient's share of an unlocked splittable vault
+    ///
+    /// Marks only the claimant's allocation as claimed. The vault stays
+    /// unlocked for the other recipients, and becomes `Claimed` with the
+    /// last share taken.
+    ///
+    /// # Arguments
+    /// * `claimant` - Identity of the recipient claiming its allocation
+    /// * `reference_state` - Current state for timestamp anchoring
+    ///
+    /// # Returns
+    /// * `Result<ShareClaim, DsmError>` - The claimant's share and its claim proof
+    pub fn claim_share(
+        &mut self,
+        claimant: &[u8],
+        reference_state: &State,
+    ) -> Result<ShareClaim, DsmError> {
+        let Some(split) = &self.split else {
+            return Err(DsmError::validation(
+                "Vault is not splittable and must be claimed whole",
+                None::<std::convert::Infallible>,
+            ));
+        };
+        let Some(index) = split.position(claimant) else {
+            return Err(DsmError::validation(
+                "Claimant has no allocation in this splittable vault",
+                None::<std::convert::Infallible>,
+            ));
+        };
+        if split.claimed.get(index).copied().unwrap_or(true) {
+            return Err(DsmError::validation(
+                "Claimant has already claimed its share of this vault",
+                None::<std::convert::Infallible>,
+            ));
+        }
+        let amount = split.amount_of(index).unwrap_or(0);
+        let token_id = split.token_id.clone();
+
+        self.ensure_claimable(reference_state)?;
+
+        let claim_proof = ShareClaim::proof(
+            &self.id,
+            claimant,
+            reference_state.state_number,
+            &self.parameters_hash,
+        );
+
+        if let Some(split) = &mut self.split {
+            split.claimed[index] = true;
+            if split.is_fully_claimed() {
+                self.state = VaultState::Claimed {
+                    claimed_state_number: reference_state.state_number,
+                    claimant: claimant.to_vec(),
+                    claim_proof: claim_proof.clone(),
+                };
+            }
+        }
+
+        Ok(ShareClaim {
+            vault_id: self.id.clone(),
+            recipient: claimant.to_vec(),
+            token_id,
+            amount,
+            claimed_state_number: reference_state.state_number,
+            claim_proof,
+        })
+    }
+
     /// Compute the unlocking key for the vault
     ///
     /// This implements the unlocking key derivation described in Section 20.3,
@@ -1074,21 +2763,15 @@ pub fn invalidate(
         reference_state: &State,
     ) -> Result<(), DsmError> {
         // Generate invalidation signature data
-        let mut invalidation_data = Vec::new();
-        invalidation_data.extend_from_slice(self.id.as_bytes());
-        invalidation_data.extend_from_slice(reason.as_bytes());
-        invalidation_data.extend_from_slice(&reference_state.hash);
+        let invalidation_data = Self::invalidation_message(&self.id, reason, &reference_state.hash);
 
         // Sign invalidation data with creator's private key
         let creator_signature = sphincs::sphincs_sign(creator_private_key, &invalidation_data)
             .map_err(|e| DsmError::crypto("Failed to sign invalidation data", Some(e)))?;
 
         // Verify the signature
-        let valid = sphincs::sphincs_verify(
-            &self.creator_public_key,
-            &invalidation_data,
-            &creator_signature,
-        )?;
+        let valid =
+            sphincs::sphincs_verify(self.creator(), &invalidation_data, &creator_signature)?;
 
         if !valid {
             return Err(DsmError::validation(
@@ -1107,6 +2790,157 @@ pub fn invalidate(
         Ok(())
     }
 
+    /// Message the creator signs to invalidate a vault against a reference state
+    pub fn invalidation_message(
+        vault_id: &str,
+        reason: &str,
+        reference_state_hash: &[u8],
+    ) -> Vec<u8> {
+        let mut invalidation_data = Vec::new();
+        invalidation_data.extend_from_slice(vault_id.as_bytes());
+        invalidation_data.extend_from_slice(reason.as_bytes());
+        invalidation_data.extend_from_slice(reference_state_hash);
+        invalidation_data
+    }
+
+    /// Invalidate a vault with a signature the creator made elsewhere
+    ///
+    /// For holders of the creator's signature but not its private key. The
+    /// signature must cover [`Self::invalidation_message`] for the vault's own
+    /// reference state. A claimed vault can no longer be invalidated.
+    pub fn invalidate_with_signature(
+        &mut self,
+        reason: &str,
+        creator_signature: &[u8],
+        state_number: u64,
+    ) -> Result<(), DsmError> {
+        match self.state {
+            VaultState::Claimed { .. } => {
+                return Err(DsmError::validation(
+                    "Vault has already been claimed and cannot be invalidated",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            VaultState::Invalidated { .. } => {
+                return Err(DsmError::validation(
+                    "Vault has already been invalidated",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            VaultState::Reclaimed { .. } => {
+                return Err(DsmError::validation(
+                    "Vault has been reclaimed by its creator",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            VaultState::Limbo | VaultState::Unlocked { .. } => {}
+        }
+
+        let invalidation_data =
+            Self::invalidation_message(&self.id, reason, &self.reference_state_hash);
+
+        // Malformed signatures fail verification like wrong ones
+        let valid = sphincs::sphincs_verify(self.creator(), &invalidation_data, creator_signature)
+            .unwrap_or(false);
+
+        if !valid {
+            return Err(DsmError::verification(
+                "Invalid creator signature for vault invalidation",
+            ));
+        }
+
+        self.state = VaultState::Invalidated {
+            invalidated_state_number: state_number,
+            reason: reason.to_string(),
+            creator_signature: creator_signature.to_vec(),
+        };
+
+        Ok(())
+    }
+
+    /// Message the creator signs to reclaim an expired vault in a reference state
+    pub fn reclaim_message(vault_id: &str, reference_state: &State) -> Vec<u8> {
+        let mut message = VAULT_RECLAIM_DOMAIN.to_vec();
+        message.extend_from_slice(vault_id.as_bytes());
+        message.extend_from_slice(&reference_state.state_number.to_le_bytes());
+        message.extend_from_slice(&reference_state.hash);
+        message
+    }
+
+    /// Return an expired vault nobody claimed to its creator
+    ///
+    /// Only succeeds once the vault has expired by `reference_state`, and
+    /// needs the current creator's signature over [`Self::reclaim_message`]
+    /// for that state. A reclaimed vault can no longer be claimed.
+    ///
+    /// # Returns
+    /// * `Result<EncryptedContent, DsmError>` - The vault content, released to the creator
+    pub fn reclaim_expired(
+        &mut self,
+        creator_signature: &[u8],
+        reference_state: &State,
+    ) -> Result<EncryptedContent, DsmError> {
+        match self.state {
+            VaultState::Claimed { .. } => {
+                return Err(DsmError::validation(
+                    "Vault has already been claimed and cannot be reclaimed",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            VaultState::Invalidated { .. } => {
+                return Err(DsmError::validation(
+                    "Vault has been invalidated and cannot be reclaimed",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            VaultState::Reclaimed { .. } => {
+                return Err(DsmError::validation(
+                    "Vault has already been reclaimed",
+                    None::<std::convert::Infallible>,
+                ));
+            }
+            VaultState::Limbo | VaultState::Unlocked { .. } => {}
+        }
+
+        if self.expires_at_state.is_none() && self.expires_at_time.is_none() {
+            return Err(DsmError::validation(
+                "Vault has no expiry and cannot be reclaimed",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        if !self.is_expired(reference_state) {
+            return Err(DsmError::validation(
+                format!(
+                    "Vault {} has not expired at state {}",
+                    self.id, reference_state.state_number
+                ),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        // Malformed signatures fail verification like wrong ones
+        let valid = sphincs::sphincs_verify(
+            self.creator(),
+            &Self::reclaim_message(&self.id, reference_state),
+            creator_signature,
+        )
+        .unwrap_or(false);
+
+        if !valid {
+            return Err(DsmError::verification(
+                "Vault reclaim is not signed by the vault creator",
+            ));
+        }
+
+        self.state = VaultState::Reclaimed {
+            reclaimed_state_number: reference_state.state_number,
+            creator_signature: creator_signature.to_vec(),
+        };
+
+        Ok(self.encrypted_content.clone())
+    }
+
     /// Get the secret key for the intended recipient
     ///
     /// In a production implementation, this would retrieve the key from a secure key store
@@ -1154,7 +2988,7 @@ fn verify_time_condition(&self, unlock_time: u64, reference_state: &State) -> bo
     ///
     /// This implements the storage format described in whitepaper Section 20.5,
     /// preparing the vault for posting to decentralized storage with appropriate
-    /// metadata and encryption.
+    /// metadata and encryption. The post is unsigned; see [`VaultPost::sign`].
     ///
     /// # Arguments
     /// * `purpose` - Human-readable purpose for this vault
@@ -1174,6 +3008,7 @@ pub fn to_vault_post(
         // Generate a human-readable lock description based on the fulfillment condition
         let lock_description = match &self.fulfillment_condition {
             FulfillmentMechanism::TimeRelease { unlock_time, .. } => {
+                let unlock_time = self.unlock_time().unwrap_or(*unlock_time);
                 format!("Time-locked until state {}", unlock_time)
             }
             FulfillmentMechanism::Payment {
@@ -1206,6 +3041,25 @@ pub fn to_vault_post(
             FulfillmentMechanism::Or(conditions) => {
                 format!("Any of {} conditions must be met", conditions.len())
             }
+            FulfillmentMechanism::MultiSig {
+                public_keys,
+                threshold,
+            } => {
+                format!("Requires {} of {} signers", threshold, public_keys.len())
+            }
+            FulfillmentMechanism::HashPreimage { hash, .. } => {
+                format!("Preimage of hash {}", hex::encode(hash))
+            }
+            FulfillmentMechanism::TokenPayment {
+                token_id,
+                amount,
+                recipient_genesis_hash,
+            } => format!(
+                "Payment of {} {} to {}",
+                amount,
+                token_id,
+                hex::encode(recipient_genesis_hash)
+            ),
         };
 
         // Create metadata with purpose and optional timeout
@@ -1220,7 +3074,7 @@ pub fn to_vault_post(
         let post = VaultPost {
             vault_id: self.id.clone(),
             lock_description,
-            creator_id: hex::encode(&self.creator_public_key),
+            creator_id: hex::encode(self.creator()),
             commitment_hash: self.parameters_hash.clone(),
             timestamp_created: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -1231,9 +3085,13 @@ pub fn to_vault_post(
                 VaultState::Unlocked { .. } => "unlocked".to_string(),
                 VaultState::Claimed { .. } => "claimed".to_string(),
                 VaultState::Invalidated { .. } => "invalidated".to_string(),
+                VaultState::Reclaimed { .. } => "reclaimed".to_string(),
             },
             metadata,
             vault_data,
+            creator_signing_key: self.signing_key().to_vec(),
+            signed_fields_hash: Vec::new(),
+            creator_signature: Vec::new(),
         };
 
         Ok(post)
@@ -1322,6 +3180,179 @@ fn test_limbo_vault_creation() -> Result<(), DsmError> {
         assert!(vault_result.is_ok());
         Ok(())
     }
+
+    #[test]
+    fn test_multisig_variants_keep_existing_encodings() {
+        // bincode prefixes enum values with their variant index
+        let index = |bytes: Vec<u8>| u32::from_le_bytes(bytes[..4].try_into().unwrap());
+
+        let or = bincode::serialize(&FulfillmentMechanism::Or(Vec::new())).unwrap();
+        assert_eq!(index(or), 7);
+        let compound = bincode::serialize(&FulfillmentProof::CompoundProof(Vec::new())).unwrap();
+        assert_eq!(index(compound), 5);
+
+        let multisig = FulfillmentMechanism::MultiSig {
+            public_keys: vec![vec![1; 32], vec![2; 32]],
+            threshold: 2,
+        };
+        let encoded = bincode::serialize(&multisig).unwrap();
+        assert_eq!(index(encoded.clone()), 8);
+        assert_eq!(
+            bincode::deserialize::<FulfillmentMechanism>(&encoded).unwrap(),
+            multisig
+        );
+    }
+
+    #[test]
+    fn test_preimage_variants_follow_multisig() {
+        let index = |bytes: Vec<u8>| u32::from_le_bytes(bytes[..4].try_into().unwrap());
+
+        let condition = FulfillmentMechanism::HashPreimage {
+            hash: [3; 32],
+            max_preimage_len: 64,
+        };
+        assert_eq!(index(bincode::serialize(&condition).unwrap()), 9);
+        let proof = FulfillmentProof::Preimage(vec![1, 2, 3]);
+        assert_eq!(index(bincode::serialize(&proof).unwrap()), 7);
+    }
+
+    #[test]
+    fn test_blob_content_keeps_struct_encoding() {
+        // Layout of the content before chunked vaults existed
+        #[derive(Serialize)]
+        struct LegacyContent {
+            encapsulated_key: Vec<u8>,
+            encrypted_data: Vec<u8>,
+            nonce: Vec<u8>,
+            aad: Vec<u8>,
+        }
+
+        let legacy = bincode::serialize(&LegacyContent {
+            encapsulated_key: vec![1, 2, 3, 4],
+            encrypted_data: vec![5; 40],
+            nonce: vec![6; 12],
+            aad: vec![7; 8],
+        })
+        .unwrap();
+
+        let content: EncryptedContent = bincode::deserialize(&legacy).unwrap();
+        assert_eq!(content.inline_data(), Some(&[5; 40][..]));
+        assert_eq!(bincode::serialize(&content).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_chunked_vault_commits_to_chunks() -> Result<(), DsmError> {
+        let (pk, sk) = sphincs::generate_sphincs_keypair()?;
+        let mut state = State::new_genesis(
+            vec![1, 2, 3, 4],
+            DeviceInfo::new("test_device", vec![1, 2, 3, 4]),
+        );
+        state.hash = state.hash()?;
+        let condition = FulfillmentMechanism::TimeRelease {
+            unlock_time: 100,
+            reference_states: Vec::new(),
+        };
+        let content: Vec<u8> = (0..=255).cycle().take(1000).collect();
+
+        let (mut vault, chunks) = LimboVault::new_chunked(
+            (&pk, &sk),
+            condition,
+            &content,
+            "application/octet-stream",
+            None,
+            &state,
+            300,
+        )?;
+        assert_eq!(chunks.len(), 4);
+        assert!(vault.verify()?);
+
+        // The manifest survives the vault encoding
+        let encoded = bincode::serialize(&vault).unwrap();
+        let decoded: LimboVault = bincode::deserialize(&encoded).unwrap();
+        assert!(decoded.verify()?);
+        let EncryptedContent::Chunked {
+            chunk_hashes,
+            total_len,
+            ..
+        } = &decoded.encrypted_content
+        else {
+            panic!("Chunked content decoded as a blob");
+        };
+        assert_eq!(chunk_hashes.len(), 4);
+        assert_eq!(*total_len, content.len() as u64);
+
+        // The assembled ciphertext is checked against the chunk hashes
+        let decoded_content = &decoded.encrypted_content;
+        decoded_content.verify_assembled(&chunks.concat())?;
+        let mut tampered = chunks.concat();
+        tampered[500] ^= 1;
+        assert!(decoded_content.verify_assembled(&tampered).is_err());
+
+        // Chunked content cannot be claimed as a blob
+        assert!(vault.claim(&pk, &state).is_err());
+
+        // Reordering the chunks breaks the creator's signature
+        if let EncryptedContent::Chunked { chunk_hashes, .. } = &mut vault.encrypted_content {
+            chunk_hashes.swap(0, 1);
+        }
+        assert!(!vault.verify()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_is_committed_and_limited() -> Result<(), DsmError> {
+        let (pk, sk) = sphincs::generate_sphincs_keypair()?;
+        let mut state = State::new_genesis(
+            vec![1, 2, 3, 4],
+            DeviceInfo::new("test_device", vec![1, 2, 3, 4]),
+        );
+        state.hash = state.hash()?;
+        let condition = FulfillmentMechanism::TimeRelease {
+            unlock_time: 100,
+            reference_states: Vec::new(),
+        };
+        let vault = LimboVault::new((&pk, &sk), condition, b"ticket", "text/plain", None, &state)?;
+        let unlabelled_hash = vault.parameters_hash.clone();
+
+        let metadata = BTreeMap::from([
+            ("category".to_string(), "tickets".to_string()),
+            ("title".to_string(), "Concert".to_string()),
+        ]);
+        let limits = VaultMetadataLimits {
+            max_key_len: 8,
+            max_value_len: 16,
+            max_total_len: 32,
+        };
+        let mut oversized = metadata.clone();
+        oversized.insert("tags".to_string(), "a".repeat(17));
+        assert!(vault
+            .clone()
+            .with_metadata(oversized, &limits, &sk)
+            .is_err());
+        let mut long_key = metadata.clone();
+        long_key.insert("subcategory".to_string(), "rock".to_string());
+        assert!(vault.clone().with_metadata(long_key, &limits, &sk).is_err());
+        let mut too_many = metadata.clone();
+        too_many.insert("venue".to_string(), "Stadium".to_string());
+        assert!(vault.clone().with_metadata(too_many, &limits, &sk).is_err());
+
+        let mut vault = vault.with_metadata(metadata, &limits, &sk)?;
+        assert_ne!(vault.parameters_hash, unlabelled_hash);
+        assert!(vault.verify()?);
+
+        // Metadata cannot be altered after creation
+        vault
+            .metadata
+            .insert("title".to_string(), "Forged".to_string());
+        assert!(!vault.verify()?);
+
+        // Vaults encoded before metadata existed decode with none
+        let mut legacy = serde_json::to_value(LimboVault::default()).unwrap();
+        legacy.as_object_mut().unwrap().remove("metadata");
+        let decoded: LimboVault = serde_json::from_value(legacy).unwrap();
+        assert!(decoded.metadata.is_empty());
+        Ok(())
+    }
 }
 
 impl Default for LimboVault {
@@ -1337,17 +3368,23 @@ fn default() -> Self {
             intended_recipient: None,
             state: VaultState::Limbo,
             content_type: "application/octet-stream".to_string(),
-            encrypted_content: EncryptedContent {
-                encapsulated_key: Vec::new(),
-                encrypted_data: Vec::new(),
-                nonce: Vec::new(),
-                aad: Vec::new(),
-            },
+            encrypted_content: EncryptedContent::default(),
             content_commitment: PedersenCommitment::default(),
             parameters_hash: Vec::new(),
             creator_signature: Vec::new(),
             verification_positions: Vec::new(),
             reference_state_hash: vec![0; 32],
+            timelock_extensions: Vec::new(),
+            ownership_transfers: Vec::new(),
+            expires_at_state: None,
+            expires_at_time: None,
+            recipient_reassignments: Vec::new(),
+            split: None,
+            metadata: BTreeMap::new(),
+            envelope_version: 0,
+            recipient_key_generation: 0,
+            creator_signing_key: Vec::new(),
+            payment_ledger: None,
         }
     }
 }
@@ -1468,13 +3505,18 @@ pub fn from_limbo_vault(
             id: vault.id.clone(),
             creator_id,
             recipient_id,
-            data: vault.encrypted_content.encrypted_data.clone(),
+            data: vault
+                .encrypted_content
+                .inline_data()
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default(),
             condition,
             status: match vault.state {
                 crate::vault::VaultState::Limbo => VaultStatus::Active,
                 crate::vault::VaultState::Claimed { .. } => VaultStatus::Claimed,
                 crate::vault::VaultState::Invalidated { .. } => VaultStatus::Revoked,
                 crate::vault::VaultState::Unlocked { .. } => VaultStatus::Active,
+                crate::vault::VaultState::Reclaimed { .. } => VaultStatus::Expired,
             },
         })
     }
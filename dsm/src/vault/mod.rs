@@ -3,11 +3,27 @@
 //! Core implementation of quantum-resistant cryptographic vaults.
 
 pub mod asset_manager;
+pub mod chunked;
+pub mod commitment;
+pub mod content_update;
 pub mod dlv_manager;
 pub mod fulfillment;
 pub mod limbo_vault;
+pub mod ownership;
+pub mod payment_ledger;
+pub mod recipient;
+pub mod splittable;
+pub mod timelock;
 
 pub use asset_manager::*;
+pub use chunked::*;
+pub use commitment::*;
+pub use content_update::*;
 pub use dlv_manager::*;
 pub use fulfillment::*;
 pub use limbo_vault::*;
+pub use ownership::*;
+pub use payment_ledger::*;
+pub use recipient::*;
+pub use splittable::*;
+pub use timelock::*;
@@ -0,0 +1,80 @@
+//! Vault Ownership Transfers
+//!
+//! A vault's creator rights (extending its time lock, invalidating it) can be
+//! handed to another identity. Each transfer is signed by the current creator
+//! and binds the vault, both creator keys and the old creator's state number
+//! at signing, so storage nodes and other holders of the vault can verify the
+//! chain of creators back to the one who created the vault.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::sphincs;
+use crate::types::error::DsmError;
+
+/// Domain separator for ownership transfer signatures
+const OWNERSHIP_TRANSFER_DOMAIN: &[u8] = b"DSM_VAULT_OWNERSHIP_TRANSFER";
+
+/// A transfer of a vault's creator rights, signed by the outgoing creator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipTransferRecord {
+    /// ID of the transferred vault
+    pub vault_id: String,
+
+    /// SPHINCS+ public key of the outgoing creator
+    pub old_creator: Vec<u8>,
+
+    /// SPHINCS+ public key of the incoming creator
+    pub new_creator: Vec<u8>,
+
+    /// Outgoing creator's state number when the transfer was signed
+    pub state_number: u64,
+
+    /// Outgoing creator's SPHINCS+ signature over the transfer
+    pub old_creator_signature: Vec<u8>,
+}
+
+impl OwnershipTransferRecord {
+    /// Sign a transfer with the outgoing creator's SPHINCS+ keypair
+    pub fn sign(
+        vault_id: &str,
+        new_creator: &[u8],
+        state_number: u64,
+        old_creator_keypair: (&[u8], &[u8]),
+    ) -> Result<Self, DsmError> {
+        let mut record = Self {
+            vault_id: vault_id.to_string(),
+            old_creator: old_creator_keypair.0.to_vec(),
+            new_creator: new_creator.to_vec(),
+            state_number,
+            old_creator_signature: Vec::new(),
+        };
+        record.old_creator_signature =
+            sphincs::sphincs_sign(old_creator_keypair.1, &record.signing_bytes())?;
+
+        Ok(record)
+    }
+
+    /// Verify the signature against the outgoing creator's public key
+    pub fn verify(&self) -> Result<bool, DsmError> {
+        sphincs::sphincs_verify(
+            &self.old_creator,
+            &self.signing_bytes(),
+            &self.old_creator_signature,
+        )
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = OWNERSHIP_TRANSFER_DOMAIN.to_vec();
+        for field in [
+            self.vault_id.as_bytes(),
+            &self.old_creator,
+            &self.new_creator,
+        ] {
+            // Length-prefixed so the variable-length fields cannot be shifted
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.extend_from_slice(&self.state_number.to_le_bytes());
+        bytes
+    }
+}
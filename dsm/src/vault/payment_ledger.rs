@@ -0,0 +1,80 @@
+//! Payment Ledger for Token Payment Vaults
+//!
+//! A `TokenPayment` proof is made by the payer, so nothing it says about the
+//! payer's chain can be taken on trust. A payment ledger is the verifier's own
+//! record of payers: the signing key from each payer's genesis state, the head
+//! of each payer's chain it trusts, and the states the invalidation registry
+//! has marked invalid. Vaults check token payment proofs against it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+
+use parking_lot::RwLock;
+
+/// Trusted record of payers that token payment proofs are checked against
+pub trait PaymentLedger: Debug + Send + Sync {
+    /// SPHINCS+ public key from a payer's genesis state
+    fn payer_public_key(&self, payer_genesis_hash: &[u8]) -> Option<Vec<u8>>;
+
+    /// Hash of the latest state of a payer's chain the ledger trusts
+    fn trusted_head(&self, payer_genesis_hash: &[u8]) -> Option<Vec<u8>>;
+
+    /// Whether the invalidation registry has marked a state invalid
+    fn is_invalidated(&self, state_hash: &[u8]) -> bool;
+}
+
+/// Payment ledger held in memory and filled in by its owner
+///
+/// Keys should come from verified genesis states, heads from the payer's
+/// published identity head, and invalidations from verified markers.
+#[derive(Debug, Default)]
+pub struct MemoryPaymentLedger {
+    /// Genesis signing keys by payer genesis hash
+    public_keys: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+
+    /// Trusted chain heads by payer genesis hash
+    heads: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+
+    /// Hashes of invalidated states
+    invalidated: RwLock<HashSet<Vec<u8>>>,
+}
+
+impl MemoryPaymentLedger {
+    /// Create an empty ledger
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the signing key from a payer's genesis state
+    pub fn register_payer(&self, payer_genesis_hash: &[u8], public_key: Vec<u8>) {
+        self.public_keys
+            .write()
+            .insert(payer_genesis_hash.to_vec(), public_key);
+    }
+
+    /// Record the latest trusted state of a payer's chain
+    pub fn set_trusted_head(&self, payer_genesis_hash: &[u8], head_hash: Vec<u8>) {
+        self.heads
+            .write()
+            .insert(payer_genesis_hash.to_vec(), head_hash);
+    }
+
+    /// Record a state the invalidation registry has marked invalid
+    pub fn record_invalidation(&self, state_hash: &[u8]) {
+        self.invalidated.write().insert(state_hash.to_vec());
+    }
+}
+
+impl PaymentLedger for MemoryPaymentLedger {
+    fn payer_public_key(&self, payer_genesis_hash: &[u8]) -> Option<Vec<u8>> {
+        self.public_keys.read().get(payer_genesis_hash).cloned()
+    }
+
+    fn trusted_head(&self, payer_genesis_hash: &[u8]) -> Option<Vec<u8>> {
+        self.heads.read().get(payer_genesis_hash).cloned()
+    }
+
+    fn is_invalidated(&self, state_hash: &[u8]) -> bool {
+        self.invalidated.read().contains(state_hash)
+    }
+}
@@ -0,0 +1,79 @@
+//! Vault Recipient Reassignment
+//!
+//! An unclaimed vault, such as a voucher, can be redirected to another
+//! recipient. Each reassignment is signed by the vault's creator and binds the
+//! vault, the outgoing and incoming recipient and the hash of the creator's
+//! reference state, so holders of the vault can verify every recipient it has
+//! had back to the one it was created for.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::sphincs;
+use crate::types::error::DsmError;
+
+/// Domain separator for recipient reassignment signatures
+const RECIPIENT_REASSIGNMENT_DOMAIN: &[u8] = b"DSM_VAULT_RECIPIENT_REASSIGNMENT";
+
+/// A change of a vault's intended recipient, signed by the vault's creator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipientReassignment {
+    /// ID of the reassigned vault
+    pub vault_id: String,
+
+    /// Kyber public key of the outgoing recipient (None = the creator)
+    pub old_recipient: Option<Vec<u8>>,
+
+    /// Kyber public key of the incoming recipient
+    pub new_recipient: Vec<u8>,
+
+    /// Hash of the creator's reference state when the reassignment was signed
+    pub reference_state_hash: Vec<u8>,
+
+    /// SPHINCS+ public key of the creator who signed the reassignment
+    pub creator: Vec<u8>,
+
+    /// Creator's SPHINCS+ signature over the reassignment
+    pub creator_signature: Vec<u8>,
+}
+
+impl RecipientReassignment {
+    /// Message the creator signs to reassign a vault's recipient
+    pub fn signing_message(
+        vault_id: &str,
+        old_recipient: Option<&[u8]>,
+        new_recipient: &[u8],
+        reference_state_hash: &[u8],
+    ) -> Vec<u8> {
+        let mut bytes = RECIPIENT_REASSIGNMENT_DOMAIN.to_vec();
+        bytes.push(u8::from(old_recipient.is_some()));
+        for field in [
+            vault_id.as_bytes(),
+            old_recipient.unwrap_or_default(),
+            new_recipient,
+            reference_state_hash,
+        ] {
+            // Length-prefixed so the variable-length fields cannot be shifted
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes
+    }
+
+    /// Verify the signature against the signing creator's public key
+    pub fn verify(&self) -> Result<bool, DsmError> {
+        sphincs::sphincs_verify(
+            &self.creator,
+            &self.signing_bytes(),
+            &self.creator_signature,
+        )
+    }
+
+    pub(crate) fn signing_bytes(&self) -> Vec<u8> {
+        Self::signing_message(
+            &self.vault_id,
+            self.old_recipient.as_deref(),
+            &self.new_recipient,
+            &self.reference_state_hash,
+        )
+    }
+}
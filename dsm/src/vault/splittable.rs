@@ -0,0 +1,192 @@
+//! Splittable Vault Allocations
+//!
+//! A splittable vault divides a token amount between several recipients,
+//! each holding a `Ratio` of it. The allocations are committed in the vault's
+//! parameters hash, and each recipient claims its own share once the vault is
+//! unlocked. The vault keeps a claim bitmap alongside the allocations, and
+//! only becomes `Claimed` once every share has been taken.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::blake3;
+use crate::types::error::DsmError;
+use crate::types::token_types::Ratio;
+
+/// Domain separator for the committed allocation list
+const SPLIT_ALLOCATIONS_DOMAIN: &[u8] = b"DSM_VAULT_SPLIT_ALLOCATIONS";
+
+/// Domain separator for the proof of a share claim
+const SHARE_CLAIM_DOMAIN: &[u8] = b"DSM_VAULT_SHARE_CLAIM";
+
+/// A recipient's share of a splittable vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitAllocation {
+    /// Identity of the recipient entitled to the share
+    pub recipient: Vec<u8>,
+
+    /// Fraction of the vault's token amount the recipient receives
+    pub share: Ratio,
+}
+
+/// Allocations of a splittable vault and which of them have been claimed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultSplit {
+    /// Token the vault's amount is denominated in
+    pub token_id: String,
+
+    /// Amount divided between the allocations
+    pub token_amount: u64,
+
+    /// Recipients' shares, in the order they were declared
+    pub allocations: Vec<SplitAllocation>,
+
+    /// Claim bitmap: whether each allocation, in order, has been claimed
+    pub claimed: Vec<bool>,
+}
+
+/// A recipient's claimed share of a splittable vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareClaim {
+    /// ID of the vault the share was claimed from
+    pub vault_id: String,
+
+    /// Identity of the recipient who claimed the share
+    pub recipient: Vec<u8>,
+
+    /// Token the share is paid in
+    pub token_id: String,
+
+    /// Amount of the share
+    pub amount: u64,
+
+    /// Number of the reference state the share was claimed against
+    pub claimed_state_number: u64,
+
+    /// Proof binding the claim to the vault, recipient and reference state
+    pub claim_proof: Vec<u8>,
+}
+
+impl VaultSplit {
+    /// Divide `token_amount` between distinct recipients, none claimed yet
+    ///
+    /// Every allocation must have a non-zero share, and the shares together
+    /// cannot pay out more than `token_amount`.
+    pub fn new(
+        token_id: &str,
+        token_amount: u64,
+        allocations: Vec<SplitAllocation>,
+    ) -> Result<Self, DsmError> {
+        if allocations.is_empty() {
+            return Err(DsmError::invalid_parameter(
+                "A splittable vault needs at least one allocation",
+            ));
+        }
+
+        let mut allocated: u128 = 0;
+        for (index, allocation) in allocations.iter().enumerate() {
+            if allocation.share.numerator == 0 || allocation.share.denominator == 0 {
+                return Err(DsmError::invalid_parameter(format!(
+                    "Allocation {} has no share of the vault",
+                    index
+                )));
+            }
+            if allocations[..index]
+                .iter()
+                .any(|other| other.recipient == allocation.recipient)
+            {
+                return Err(DsmError::invalid_parameter(format!(
+                    "Allocation {} repeats an earlier recipient",
+                    index
+                )));
+            }
+            allocated += u128::from(allocation.share.apply_to(token_amount));
+        }
+
+        if allocated > u128::from(token_amount) {
+            return Err(DsmError::invalid_parameter(format!(
+                "Allocations exceed the vault's {} {}",
+                token_amount, token_id
+            )));
+        }
+
+        let claimed = vec![false; allocations.len()];
+        Ok(Self {
+            token_id: token_id.to_string(),
+            token_amount,
+            allocations,
+            claimed,
+        })
+    }
+
+    /// Index of a recipient's allocation
+    pub fn position(&self, recipient: &[u8]) -> Option<usize> {
+        self.allocations
+            .iter()
+            .position(|allocation| allocation.recipient == recipient)
+    }
+
+    /// Amount of the allocation at `index`, rounded down
+    pub fn amount_of(&self, index: usize) -> Option<u64> {
+        self.allocations
+            .get(index)
+            .map(|allocation| allocation.share.apply_to(self.token_amount))
+    }
+
+    /// Whether every allocation has been claimed
+    pub fn is_fully_claimed(&self) -> bool {
+        self.claimed.iter().all(|claimed| *claimed)
+    }
+
+    /// Recipients that have not claimed their share, in allocation order
+    pub fn unclaimed_recipients(&self) -> Vec<&[u8]> {
+        self.allocations
+            .iter()
+            .zip(&self.claimed)
+            .filter(|(_, claimed)| !**claimed)
+            .map(|(allocation, _)| allocation.recipient.as_slice())
+            .collect()
+    }
+
+    /// Whether the claim bitmap covers exactly the allocations
+    pub(crate) fn is_well_formed(&self) -> bool {
+        self.claimed.len() == self.allocations.len()
+    }
+
+    /// Bytes the vault's parameters hash commits to
+    ///
+    /// Covers the token and the allocations, but not the claim bitmap, which
+    /// changes as shares are claimed.
+    pub(crate) fn commitment_bytes(&self) -> Vec<u8> {
+        let mut bytes = SPLIT_ALLOCATIONS_DOMAIN.to_vec();
+        bytes.extend_from_slice(&(self.token_id.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(self.token_id.as_bytes());
+        bytes.extend_from_slice(&self.token_amount.to_le_bytes());
+        bytes.extend_from_slice(&(self.allocations.len() as u64).to_le_bytes());
+        for allocation in &self.allocations {
+            // Length-prefixed so recipients cannot be shifted into each other
+            bytes.extend_from_slice(&(allocation.recipient.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&allocation.recipient);
+            bytes.extend_from_slice(&allocation.share.numerator.to_le_bytes());
+            bytes.extend_from_slice(&allocation.share.denominator.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl ShareClaim {
+    /// Proof that `recipient` claimed its share of a vault at a state
+    pub fn proof(
+        vault_id: &str,
+        recipient: &[u8],
+        state_number: u64,
+        parameters_hash: &[u8],
+    ) -> Vec<u8> {
+        let mut bytes = SHARE_CLAIM_DOMAIN.to_vec();
+        for field in [vault_id.as_bytes(), recipient, parameters_hash] {
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.extend_from_slice(&state_number.to_le_bytes());
+        blake3::hash(&bytes).as_bytes().to_vec()
+    }
+}
@@ -0,0 +1,68 @@
+//! Vault Time-Lock Extensions
+//!
+//! The creator of a `TimeRelease` vault can push its unlock time back while the
+//! vault is still locked. Each extension is signed by the creator and binds the
+//! vault, the new unlock time and the creator's state number at signing, so
+//! storage nodes and other holders of the vault can verify it before applying it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::sphincs;
+use crate::types::error::DsmError;
+
+/// Domain separator for time-lock extension signatures
+const TIMELOCK_EXTENSION_DOMAIN: &[u8] = b"DSM_VAULT_TIMELOCK_EXTENSION";
+
+/// A creator-signed extension of a vault's time lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockExtension {
+    /// ID of the extended vault
+    pub vault_id: String,
+
+    /// Unlock time replacing the vault's current one
+    pub new_unlock_time: u64,
+
+    /// Creator's state number when the extension was signed
+    pub state_number: u64,
+
+    /// Creator's SPHINCS+ signature over the extension
+    pub creator_signature: Vec<u8>,
+}
+
+impl TimelockExtension {
+    /// Sign an extension with the vault creator's SPHINCS+ secret key
+    pub fn sign(
+        vault_id: &str,
+        new_unlock_time: u64,
+        state_number: u64,
+        creator_secret_key: &[u8],
+    ) -> Result<Self, DsmError> {
+        let mut extension = Self {
+            vault_id: vault_id.to_string(),
+            new_unlock_time,
+            state_number,
+            creator_signature: Vec::new(),
+        };
+        extension.creator_signature =
+            sphincs::sphincs_sign(creator_secret_key, &extension.signing_bytes())?;
+
+        Ok(extension)
+    }
+
+    /// Verify the signature against the vault creator's SPHINCS+ public key
+    pub fn verify(&self, creator_public_key: &[u8]) -> Result<bool, DsmError> {
+        sphincs::sphincs_verify(
+            creator_public_key,
+            &self.signing_bytes(),
+            &self.creator_signature,
+        )
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = TIMELOCK_EXTENSION_DOMAIN.to_vec();
+        bytes.extend_from_slice(self.vault_id.as_bytes());
+        bytes.extend_from_slice(&self.new_unlock_time.to_le_bytes());
+        bytes.extend_from_slice(&self.state_number.to_le_bytes());
+        bytes
+    }
+}
@@ -37,6 +37,7 @@ async fn main() -> Result<(), DsmError> {
         base_url: "http://127.0.0.1:8080".to_string(),
         api_token: None,
         timeout_seconds: 30,
+        ..Default::default()
     };
     
     let _storage_client = Arc::new(StorageNodeClient::new(config).map_err(|e| {
@@ -134,7 +135,7 @@ async fn main() -> Result<(), DsmError> {
     // Register the sender's genesis state with the storage node
     // Since CoreSDK doesn't have a register_state method, we'll use the execute_transition method
     // to store the genesis state in the hash chain
-    let result = core_sdk.initialize_with_genesis(sender_genesis.clone()).await;
+    let result = core_sdk.initialize_with_genesis(sender_genesis.clone(), None).await;
     match result {
         Ok(_) => println!("Successfully registered sender's genesis state"),
         Err(e) => println!("Failed to register sender's genesis state: {:?}", e),
@@ -144,7 +145,7 @@ async fn main() -> Result<(), DsmError> {
     // In a real implementation, you may need to use a storage node API directly
     // This is a simplified approach
     let receiver_core_sdk = Arc::new(CoreSDK::new());
-    let result = receiver_core_sdk.initialize_with_genesis(receiver_genesis.clone()).await;
+    let result = receiver_core_sdk.initialize_with_genesis(receiver_genesis.clone(), None).await;
     match result {
         Ok(_) => println!("Successfully registered receiver's genesis state"),
         Err(e) => println!("Failed to register receiver's genesis state: {:?}", e),
@@ -410,7 +411,7 @@ async fn main() -> Result<(), DsmError> {
     let receiver_core_sdk = Arc::new(CoreSDK::new());
     
     // Initialize with receiver's genesis
-    receiver_core_sdk.initialize_with_genesis(receiver_genesis.clone()).await?;
+    receiver_core_sdk.initialize_with_genesis(receiver_genesis.clone(), None).await?;
     
     // Attempt to get the updated state for the receiver from the storage node
     let receiver_state = match receiver_core_sdk.get_current_state() {
@@ -36,16 +36,25 @@
 pub mod sdk;
 
 // Re-export commonly used components for convenience
+#[cfg(not(target_arch = "wasm32"))]
 pub use sdk::bluetooth_transport;
 pub use sdk::core_sdk;
+pub use sdk::event_bus;
 pub use sdk::hashchain_sdk;
 pub use sdk::identity_sdk;
+pub use sdk::logging;
+#[cfg(not(target_arch = "wasm32"))]
 pub use sdk::pokemon_bluetooth_sdk;
 pub use sdk::pokemon_sdk;
+pub use sdk::simulation;
 pub use sdk::smart_commitment_sdk;
 pub use sdk::token_sdk;
 pub use sdk::wallet_sdk;
 
+// Browser bindings
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+
 /// Current version of the DSM SDK
 ///
 /// This constant provides the semantic version of the SDK, which follows
@@ -41,7 +41,7 @@
 //!     let genesis = sdk.create_initial_state(&device_info)?;
 //!     
 //!     // Initialize the system with the genesis state
-//!     sdk.initialize_with_genesis(genesis).await?;
+//!     sdk.initialize_with_genesis(genesis, None).await?;
 //!     
 //!     // Create and execute a generic operation
 //!     let operation = sdk.generic_operation("test", vec![1, 2, 3])?;
@@ -54,18 +54,36 @@
 //!     Ok(())
 //! }
 //! ```
-use super::identity_sdk::IdentitySDK;
+use super::identity_sdk::{IdentitySDK, SyncResult};
 use async_trait::async_trait;
 use dsm::types::state_types::StateParams;
 use parking_lot::RwLock;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::event_bus::{
+    DsmEventBus, InboxMessageReceived, StateTransitioned, TokenBalanceChanged,
+};
 use super::hashchain_sdk::HashChainSDK;
+use super::simulation::{DeterministicContext, SessionEvent};
+use dsm::communication::{
+    DrainResult, OfflineQueue, OperationReplayer, QueuedOperation, StorageCache,
+};
+use dsm::core::identity::{GenesisState, GenesisTokenPolicy};
+use dsm::core::resources::{estimate_resources, ResourceEstimate};
 use dsm::core::state_machine::StateMachine;
+use dsm::crypto::sphincs;
 use dsm::types::error::DsmError;
 use dsm::types::operations::{Operation, TransactionMode};
 use dsm::types::state_types::{DeviceInfo, State};
 use dsm::types::token_types::{Balance, TokenOperation};
+use dsm_storage_node::api::InboxEntry;
+use dsm_storage_node::client::StorageNodeClient;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Domain separator for operation signatures
+const OPERATION_SIGNATURE_DOMAIN: &[u8] = b"DSM_SIGNED_OPERATION";
 
 /// Token management functionality as defined in the DSM whitepaper
 ///
@@ -111,6 +129,129 @@ pub trait TokenManager: Send + Sync {
     async fn validate_token_conservation(&self) -> Result<bool, DsmError>;
 }
 
+/// Policy for creating checkpoints automatically as the chain grows
+///
+/// A checkpoint is a full snapshot of a state that the chain can be restored
+/// from without replaying the transitions that led up to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointPolicy {
+    /// Create a checkpoint whenever the state number is a multiple of this value
+    pub every_n_transitions: u64,
+
+    /// Also store checkpoints on the storage node, if a client is registered
+    pub store_to_network: bool,
+}
+
+impl CheckpointPolicy {
+    /// Whether a checkpoint is due for the state with the given number
+    pub fn is_due(&self, state_number: u64) -> bool {
+        self.every_n_transitions > 0
+            && state_number > 0
+            && state_number % self.every_n_transitions == 0
+    }
+}
+
+/// Outcome of pruning an identity's state history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruningReport {
+    /// Number of history states removed from the hash chain
+    pub states_removed: u64,
+
+    /// Serialized bytes of the removed states, plus any cache entries purged with them
+    pub bytes_freed: u64,
+
+    /// State number of the checkpoint that now stands in for the removed history
+    pub checkpoint_state_number: u64,
+}
+
+/// Immutable view of a state and its balances, taken by [`CoreSDK::begin_snapshot`]
+///
+/// Operations planned from a snapshot are applied with
+/// [`CoreSDK::commit_operations`], which fails if the state has moved on.
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    state: State,
+    balances: HashMap<String, Balance>,
+}
+
+impl StateSnapshot {
+    fn new(state: State) -> Self {
+        let balances = state.token_balances.clone();
+        Self { state, balances }
+    }
+
+    /// The state the snapshot was taken of
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// State number the snapshot was taken at
+    pub fn state_number(&self) -> u64 {
+        self.state.state_number
+    }
+
+    /// Token balances of the state, by balance key
+    pub fn balances(&self) -> &HashMap<String, Balance> {
+        &self.balances
+    }
+
+    /// Balance recorded under `key`, if any
+    pub fn balance(&self, key: &str) -> Option<&Balance> {
+        self.balances.get(key)
+    }
+}
+
+/// Predicted outcome of an operation, from [`CoreSDK::simulate_transition`]
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    /// Whether the operation would be executed
+    pub success: bool,
+
+    /// State the operation would produce, or the current state if it would fail
+    pub new_state: State,
+
+    /// Signed balance changes, keyed by `"device_id:token_id"`
+    pub balance_deltas: HashMap<String, i128>,
+
+    /// Why the operation would fail
+    pub errors: Vec<String>,
+}
+
+/// An operation together with the executing identity's SPHINCS+ signature
+///
+/// A log of signed operations is enough to re-derive every state of a chain
+/// from its genesis state, see [`CoreSDK::replay_from_genesis`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedOperation {
+    /// The operation, exactly as it was passed to the transition
+    pub operation: Operation,
+
+    /// SPHINCS+ signature over the operation
+    pub signature: Vec<u8>,
+}
+
+impl SignedOperation {
+    /// Sign an operation with the executing identity's SPHINCS+ secret key
+    pub fn sign(operation: Operation, secret_key: &[u8]) -> Result<Self, DsmError> {
+        let signature = sphincs::sphincs_sign(secret_key, &Self::signing_bytes(&operation))?;
+        Ok(Self {
+            operation,
+            signature,
+        })
+    }
+
+    /// Verify the signature against the executing identity's SPHINCS+ public key
+    pub fn verify(&self, public_key: &[u8]) -> Result<bool, DsmError> {
+        sphincs::sphincs_verify(public_key, &Self::signing_bytes(&self.operation), &self.signature)
+    }
+
+    fn signing_bytes(operation: &Operation) -> Vec<u8> {
+        let mut bytes = OPERATION_SIGNATURE_DOMAIN.to_vec();
+        bytes.extend_from_slice(&operation.to_bytes());
+        bytes
+    }
+}
+
 /// Core SDK for the DSM system integrating all subsystems
 ///
 /// This struct serves as the main entry point for applications using the DSM system.
@@ -129,6 +270,36 @@ pub struct CoreSDK {
     
     /// Token manager for token operations as per whitepaper section 3
     token_manager: RwLock<Option<Arc<dyn TokenManager>>>,
+
+    /// Policy for automatic checkpoint creation
+    checkpoint_policy: RwLock<Option<CheckpointPolicy>>,
+
+    /// Storage node client used to store and fetch checkpoints
+    storage_client: RwLock<Option<Arc<StorageNodeClient>>>,
+
+    /// Cache holding checkpoints for fast restoration
+    storage_cache: RwLock<Arc<StorageCache>>,
+
+    /// Signed operations executed since genesis, in execution order
+    operation_log: RwLock<Vec<SignedOperation>>,
+
+    /// Bus on which state and balance changes are published
+    event_bus: Option<Arc<DsmEventBus>>,
+
+    /// Queue holding new states while the storage node is unreachable
+    offline_queue: Option<Arc<OfflineQueue>>,
+
+    /// Seeded clock and nonce source, when the SDK is deterministic
+    determinism: Option<DeterministicContext>,
+
+    /// Calls made on the SDK with their inputs, for session replay
+    session: RwLock<Vec<SessionEvent>>,
+
+    /// Supply rules of the identity's tokens, loaded from its genesis
+    token_policies: RwLock<HashMap<String, GenesisTokenPolicy>>,
+
+    /// Ceiling on the estimated resources of a single transition
+    max_resources_per_transition: RwLock<Option<ResourceEstimate>>,
 }
 
 impl CoreSDK {
@@ -152,6 +323,23 @@ impl CoreSDK {
     /// let sdk = CoreSDK::new();
     /// ```
     pub fn new() -> Self {
+        Self::with_components(None, None, None)
+    }
+
+    /// Create a CoreSDK instance with its optional components
+    fn with_components(
+        event_bus: Option<Arc<DsmEventBus>>,
+        offline_queue: Option<Arc<OfflineQueue>>,
+        determinism: Option<DeterministicContext>,
+    ) -> Self {
+        info!(
+            identity = "default",
+            event_bus = event_bus.is_some(),
+            offline_queue = offline_queue.is_some(),
+            seed = determinism.as_ref().map(DeterministicContext::seed),
+            "Creating core SDK"
+        );
+
         // Create shared state machine and hash chain components first
         let hash_chain_sdk = Arc::new(HashChainSDK::new());
         let state_machine = Arc::new(RwLock::new(StateMachine::new()));
@@ -167,8 +355,124 @@ pub fn new() -> Self {
             identity_sdk,
             state_machine,
             token_manager: RwLock::new(None),
+            checkpoint_policy: RwLock::new(None),
+            storage_client: RwLock::new(None),
+            storage_cache: RwLock::new(Arc::new(StorageCache::new())),
+            operation_log: RwLock::new(Vec::new()),
+            event_bus,
+            offline_queue,
+            determinism,
+            session: RwLock::new(Vec::new()),
+            token_policies: RwLock::new(HashMap::new()),
+            max_resources_per_transition: RwLock::new(None),
+        }
+    }
+
+    /// Create a CoreSDK instance whose behavior depends only on its seed and inputs
+    ///
+    /// Time and nonces come from a [`DeterministicContext`] seeded with
+    /// `seed` rather than from the system clock and the OS random number
+    /// generator. The SDK has no event bus or offline queue, so every
+    /// operation is processed on the calling task. Two SDKs created with the
+    /// same seed and given the same calls produce identical states; see
+    /// [`crate::simulation`] for recording and replaying sessions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsm_sdk::core_sdk::CoreSDK;
+    ///
+    /// let sdk = CoreSDK::deterministic(42);
+    /// assert_eq!(sdk.seed(), Some(42));
+    /// ```
+    pub fn deterministic(seed: u64) -> Self {
+        Self::with_components(None, None, Some(DeterministicContext::new(seed)))
+    }
+
+    /// Seed of a deterministic SDK, None otherwise
+    pub fn seed(&self) -> Option<u64> {
+        self.determinism.as_ref().map(DeterministicContext::seed)
+    }
+
+    /// Current time in seconds since the Unix epoch
+    ///
+    /// A deterministic SDK reads its simulated clock instead.
+    pub fn now(&self) -> u64 {
+        match &self.determinism {
+            Some(determinism) => determinism.now(),
+            None => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
         }
     }
+
+    /// Generate a nonce for an operation
+    ///
+    /// A deterministic SDK draws it from its seeded random number generator.
+    pub fn generate_nonce(&self) -> Vec<u8> {
+        match &self.determinism {
+            Some(determinism) => determinism.generate_nonce(),
+            None => dsm::crypto::generate_nonce(),
+        }
+    }
+
+    /// Calls made on the SDK so far, see [`crate::simulation::record_session`]
+    pub(crate) fn session_events(&self) -> Vec<SessionEvent> {
+        self.session.read().clone()
+    }
+
+    /// Create a new CoreSDK instance that publishes its changes on an event bus
+    ///
+    /// Every transition publishes a [`StateTransitioned`] event, and every
+    /// transfer additionally publishes a [`TokenBalanceChanged`] event for
+    /// the sending device.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsm_sdk::core_sdk::CoreSDK;
+    /// use dsm_sdk::event_bus::DsmEventBus;
+    /// use std::sync::Arc;
+    ///
+    /// let bus = Arc::new(DsmEventBus::new());
+    /// let sdk = CoreSDK::with_event_bus(bus.clone());
+    /// ```
+    pub fn with_event_bus(bus: Arc<DsmEventBus>) -> Self {
+        Self::with_components(Some(bus), None, None)
+    }
+
+    /// Get the event bus the SDK publishes on, if any
+    pub fn event_bus(&self) -> Option<Arc<DsmEventBus>> {
+        self.event_bus.clone()
+    }
+
+    /// Create a new CoreSDK instance that stores every new state on the storage node
+    ///
+    /// Each transition stores the state it produced through the registered
+    /// storage client. While no client is registered or the storage node is
+    /// unreachable, the operation and its state are appended to `queue`
+    /// instead, and [`Self::drain_offline_queue`] replays them in order once
+    /// the node is reachable again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsm::communication::OfflineQueue;
+    /// use dsm_sdk::core_sdk::CoreSDK;
+    /// use std::sync::Arc;
+    ///
+    /// let queue = Arc::new(OfflineQueue::new());
+    /// let sdk = CoreSDK::with_offline_queue(queue.clone());
+    /// ```
+    pub fn with_offline_queue(queue: Arc<OfflineQueue>) -> Self {
+        Self::with_components(None, Some(queue), None)
+    }
+
+    /// Get the offline queue the SDK stores new states through, if any
+    pub fn offline_queue(&self) -> Option<Arc<OfflineQueue>> {
+        self.offline_queue.clone()
+    }
     
     /// Register a token manager implementation
     ///
@@ -195,14 +499,100 @@ pub fn register_token_manager<T: TokenManager + 'static>(&self, manager: Arc<T>)
         *token_manager = Some(manager);
     }
 
+    /// Set the policy for automatic checkpoint creation
+    ///
+    /// Passing `None` disables automatic checkpoints.
+    pub fn set_checkpoint_policy(&self, policy: Option<CheckpointPolicy>) {
+        *self.checkpoint_policy.write() = policy;
+    }
+
+    /// Set the ceiling on the estimated resources of a single transition
+    ///
+    /// Transitions whose [`estimate_resources`] exceeds the ceiling in any
+    /// resource are refused before execution. Passing `None` removes the
+    /// ceiling.
+    pub fn set_max_resources_per_transition(&self, ceiling: Option<ResourceEstimate>) {
+        *self.max_resources_per_transition.write() = ceiling;
+    }
+
+    /// Register a storage node client for storing and fetching checkpoints
+    ///
+    /// Checkpoints are cached in the client's storage cache from then on. The
+    /// identity SDK uses the same client to register owned devices.
+    pub fn register_storage_client(&self, client: Arc<StorageNodeClient>) {
+        self.identity_sdk.register_storage_client(client.clone());
+        *self.storage_cache.write() = client.storage_cache();
+        *self.storage_client.write() = Some(client);
+    }
+
+    /// Get the cache holding checkpoints
+    pub fn storage_cache(&self) -> Arc<StorageCache> {
+        self.storage_cache.read().clone()
+    }
+
+    /// Total supply of a token: the sum of its balances in the current state
+    pub fn total_supply(&self, token_id: &str) -> Result<Balance, DsmError> {
+        let current = self
+            .state_machine
+            .read()
+            .current_state()
+            .cloned()
+            .ok_or_else(|| DsmError::state("No current state available"))?;
+
+        Ok(total_supply(&current, token_id))
+    }
+
+    /// Check the estimated resources of an operation against the ceiling, if any
+    fn check_resources(&self, state: &State, operation: &Operation) -> Result<(), DsmError> {
+        match *self.max_resources_per_transition.read() {
+            Some(ceiling) => estimate_resources(operation, state).check_within(&ceiling),
+            None => Ok(()),
+        }
+    }
+
+    /// Check an operation against the token policy of the token it affects
+    ///
+    /// Mints must be authorized by a permitted minter and keep the supply
+    /// within its cap; burns must be allowed. Tokens without a policy are
+    /// unrestricted.
+    fn check_token_policy(&self, state: &State, operation: &Operation) -> Result<(), DsmError> {
+        let policies = self.token_policies.read();
+
+        match operation.innermost() {
+            Operation::Mint {
+                amount,
+                token_id,
+                authorized_by,
+                ..
+            } => match policies.get(token_id) {
+                Some(policy) => policy.check_mint(
+                    token_id,
+                    authorized_by,
+                    &total_supply(state, token_id),
+                    amount,
+                ),
+                None => Ok(()),
+            },
+            Operation::Burn { token_id, .. } => match policies.get(token_id) {
+                Some(policy) => policy.check_burn(token_id),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
     /// Initialize the system with a genesis state
     ///
     /// This sets up the initial genesis state (G) as described in whitepaper section 4.
     /// The genesis state serves as the foundation for all subsequent state transitions.
+    /// The token policies of the identity's genesis are enforced on every
+    /// transition from then on.
     ///
     /// # Arguments
     ///
     /// * `genesis_state` - The genesis state (must have state_number = 0)
+    /// * `identity_genesis` - Genesis of the identity whose chain this is, if
+    ///   it has one; `None` leaves every token unrestricted
     ///
     /// # Returns
     ///
@@ -223,10 +613,34 @@ pub fn register_token_manager<T: TokenManager + 'static>(&self, manager: Arc<T>)
     ///     let genesis = sdk.create_initial_state(&device_info).unwrap();
     ///     
     ///     // Initialize with genesis
-    ///     sdk.initialize_with_genesis(genesis).await.unwrap();
+    ///     sdk.initialize_with_genesis(genesis, None).await.unwrap();
     /// }
     /// ```
-    pub async fn initialize_with_genesis(&self, genesis_state: State) -> Result<(), DsmError> {
+    pub async fn initialize_with_genesis(
+        &self,
+        genesis_state: State,
+        identity_genesis: Option<&GenesisState>,
+    ) -> Result<(), DsmError> {
+        let token_policies = identity_genesis
+            .map(|genesis| genesis.token_policies.clone())
+            .unwrap_or_default();
+        self.initialize_with_policies(genesis_state, token_policies)
+    }
+
+    /// Initialize with a genesis state and the token policies of its identity
+    ///
+    /// Shared by [`Self::initialize_with_genesis`] and session replay, which
+    /// records the policies rather than the identity's genesis.
+    pub(crate) fn initialize_with_policies(
+        &self,
+        genesis_state: State,
+        token_policies: HashMap<String, GenesisTokenPolicy>,
+    ) -> Result<(), DsmError> {
+        self.session.write().push(SessionEvent::Genesis {
+            state: genesis_state.clone(),
+            token_policies: token_policies.clone(),
+        });
+
         // Validate the genesis state according to section 4 requirements
         if genesis_state.state_number != 0 {
             return Err(DsmError::validation(
@@ -244,6 +658,8 @@ pub async fn initialize_with_genesis(&self, genesis_state: State) -> Result<(),
             let mut state_machine = self.state_machine.write();
             state_machine.set_state(genesis_state);
         }
+        *self.token_policies.write() = token_policies;
+        self.operation_log.write().clear();
 
         Ok(())
     }
@@ -446,6 +862,10 @@ pub fn get_state_by_number(&self, state_number: u64) -> Result<State, DsmError>
     /// # Returns
     ///
     /// * `Ok(State)` - The new state resulting from the transition
+    /// * `Err(DsmError::DependencyNotMet)` - If an `Operation::WithDependencies` prerequisite
+    ///   has not been executed yet
+    /// * `Err(DsmError::ResourceExceeded)` - If the estimated resources of the operation
+    ///   exceed the ceiling set with [`Self::set_max_resources_per_transition`]
     /// * `Err(DsmError)` - If the transition failed
     ///
     /// # Examples
@@ -463,75 +883,2083 @@ pub fn get_state_by_number(&self, state_number: u64) -> Result<State, DsmError>
     /// }
     /// ```
     pub async fn execute_transition(&self, operation: Operation) -> Result<State, DsmError> {
+        self.session.write().push(SessionEvent::Transition {
+            operation: operation.clone(),
+        });
+
+        self.transition(operation).await
+    }
+
+    /// Execute a state transition without recording it in the session
+    async fn transition(&self, operation: Operation) -> Result<State, DsmError> {
+        let queued_operation = self.offline_queue.as_ref().map(|_| operation.clone());
+
         // Execute the transition in the state machine (deterministic evolution as per Sn+1 = H(Sn∥opn+1))
-        let new_state = {
+        let (old_state, new_state) = {
             let mut state_machine = self.state_machine.write();
-            state_machine.execute_transition(operation)?
+            let old_state = state_machine.current_state().cloned();
+            if let Some(old_state) = &old_state {
+                self.check_resources(old_state, &operation)?;
+                self.check_token_policy(old_state, &operation)?;
+            }
+            (old_state, apply_operation(&mut state_machine, operation)?)
         };
 
-        // Add the new state to the hash chain
-        self.hash_chain_sdk.add_state(new_state.clone())?;
+        self.record_transition(old_state.as_ref(), &new_state, queued_operation).await?;
 
         Ok(new_state)
     }
 
-    /// Create an initial (genesis) state
+    /// Predict the outcome of an operation without executing it
     ///
-    /// Creates a genesis state (G) as described in whitepaper section 4,
-    /// which serves as the foundation for the state chain.
+    /// The operation is applied to a copy of the current state through the
+    /// same dependency, commitment and state machine checks as
+    /// [`Self::execute_transition`], and is also checked against the balances
+    /// of the current state. Neither the current state nor storage is touched.
     ///
     /// # Arguments
     ///
-    /// * `device_info` - Information about the device creating the genesis state
+    /// * `op` - The operation to simulate
     ///
     /// # Returns
     ///
-    /// * `Ok(State)` - The created genesis state if successful
-    /// * `Err(DsmError)` - If genesis state creation failed
+    /// * `Ok(SimulationResult)` - The predicted outcome; a failing operation is
+    ///   reported through `success` and `errors`
+    /// * `Err(DsmError)` - If no current state exists
+    pub fn simulate_transition(&self, op: &Operation) -> Result<SimulationResult, DsmError> {
+        let current = self
+            .state_machine
+            .read()
+            .current_state()
+            .cloned()
+            .ok_or_else(|| DsmError::state("No current state available"))?;
+
+        let mut errors = Vec::new();
+        if let Err(e) = self.check_resources(&current, op) {
+            errors.push(e.to_string());
+        }
+        if let Err(e) = self.check_token_policy(&current, op) {
+            errors.push(e.to_string());
+        }
+
+        let mut scratch = StateMachine::new();
+        scratch.set_state(current.clone());
+        let new_state = match apply_operation(&mut scratch, op.clone()) {
+            Ok(new_state) => Some(new_state),
+            Err(e) => {
+                errors.push(e.to_string());
+                None
+            }
+        };
+
+        let balance_deltas = match balance_deltas(&current, op) {
+            Ok(deltas) => deltas,
+            Err(e) => {
+                errors.push(e.to_string());
+                HashMap::new()
+            }
+        };
+
+        Ok(match new_state {
+            Some(new_state) if errors.is_empty() => SimulationResult {
+                success: true,
+                new_state,
+                balance_deltas,
+                errors,
+            },
+            _ => SimulationResult {
+                success: false,
+                new_state: current,
+                balance_deltas: HashMap::new(),
+                errors,
+            },
+        })
+    }
+
+    /// Take a snapshot of the current state and its balances
     ///
-    /// # Examples
+    /// The snapshot does not change as later transitions execute. Operations
+    /// planned from it are applied with [`Self::commit_operations`], which
+    /// only succeeds while the snapshot is still the current state.
     ///
-    /// ```
-    /// use dsm_sdk::core_sdk::CoreSDK;
-    /// use dsm::types::state_types::DeviceInfo;
+    /// # Returns
     ///
-    /// fn create_genesis(sdk: &CoreSDK) {
-    ///     let device_info = DeviceInfo::new("device_id", vec![1, 2, 3, 4]);
-    ///     let genesis = sdk.create_initial_state(&device_info).unwrap();
-    ///     // Use genesis state to initialize system
-    /// }
-    /// ```
-    pub fn create_initial_state(&self, device_info: &DeviceInfo) -> Result<State, DsmError> {
-        let operation = Operation::Create {
-            message: "Initial state creation".to_string(),
-            identity_data: vec![],
-            public_key: vec![],
-            metadata: vec![],
-            commitment: vec![],
-            proof: vec![],
-            mode: TransactionMode::Bilateral, // Use Bilateral mode
+    /// * `Ok(StateSnapshot)` - The snapshot of the current state
+    /// * `Err(DsmError)` - If no current state exists
+    pub fn begin_snapshot(&self) -> Result<StateSnapshot, DsmError> {
+        let state = self
+            .state_machine
+            .read()
+            .current_state()
+            .cloned()
+            .ok_or_else(|| DsmError::state("No current state available"))?;
+
+        Ok(StateSnapshot::new(state))
+    }
+
+    /// Execute operations against a snapshot, all or none
+    ///
+    /// The operations are applied in order, as successive `execute_transition`
+    /// calls would, provided the snapshot is still the current state. If any
+    /// operation fails, none of them take effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - Snapshot taken with [`Self::begin_snapshot`]
+    /// * `ops` - Operations to execute, in order
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(State)` - The state after the last operation
+    /// * `Err(DsmError::SnapshotStale)` - If another transition executed after
+    ///   the snapshot was taken
+    /// * `Err(DsmError)` - If an operation failed; the state is left unchanged
+    pub async fn commit_operations(
+        &self,
+        snapshot: StateSnapshot,
+        ops: Vec<Operation>,
+    ) -> Result<State, DsmError> {
+        self.session.write().push(SessionEvent::Commit {
+            snapshot_state_number: snapshot.state.state_number,
+            operations: ops.clone(),
+        });
+
+        let queued_operations: Vec<Option<Operation>> = ops
+            .iter()
+            .map(|op| self.offline_queue.as_ref().map(|_| op.clone()))
+            .collect();
+
+        // Check and apply under one lock, so no transition can slip in between
+        let (old_state, new_states) = {
+            let mut state_machine = self.state_machine.write();
+            let current = state_machine
+                .current_state()
+                .cloned()
+                .ok_or_else(|| DsmError::state("No current state available"))?;
+
+            if current.state_number != snapshot.state.state_number
+                || current.hash != snapshot.state.hash
+            {
+                return Err(DsmError::SnapshotStale {
+                    snapshot_at: snapshot.state.state_number,
+                    current: current.state_number,
+                });
+            }
+
+            let mut new_states = Vec::with_capacity(ops.len());
+            for op in ops {
+                match apply_operation(&mut state_machine, op) {
+                    Ok(new_state) => new_states.push(new_state),
+                    Err(e) => {
+                        state_machine.set_state(current);
+                        return Err(e);
+                    }
+                }
+            }
+            (current, new_states)
         };
 
-        // Use the updated StateParams::new constructor with the correct parameter count
-        let params = StateParams::new(
-            0,                   // state_number
-            vec![0u8; 32],       // entropy
-            operation,           // operation
-            device_info.clone(), // device_info
-        );
+        let mut previous = old_state;
+        for (new_state, queued_operation) in new_states.into_iter().zip(queued_operations) {
+            self.record_transition(Some(&previous), &new_state, queued_operation).await?;
+            previous = new_state;
+        }
 
-        // Set additional parameters using the builder pattern
-        let params = params.with_prev_state_hash(vec![0u8; 32]);
+        Ok(previous)
+    }
 
-        Ok(State::new(params))
+    /// Record a transition applied to the state machine
+    ///
+    /// Adds the new state to the hash chain, publishes its events, takes a
+    /// checkpoint when the policy calls for one and stores the state on the
+    /// storage node.
+    async fn record_transition(
+        &self,
+        old_state: Option<&State>,
+        new_state: &State,
+        queued_operation: Option<Operation>,
+    ) -> Result<(), DsmError> {
+        // Add the new state to the hash chain
+        self.hash_chain_sdk.add_state(new_state.clone())?;
+
+        if let (Some(bus), Some(old_state)) = (&self.event_bus, old_state) {
+            publish_transition_events(bus, old_state, new_state);
+        }
+
+        // Take a checkpoint when the policy calls for one
+        let policy = *self.checkpoint_policy.read();
+        if let Some(policy) = policy.filter(|p| p.is_due(new_state.state_number)) {
+            self.store_checkpoint(new_state, policy.store_to_network).await?;
+        }
+
+        if let (Some(queue), Some(operation)) = (&self.offline_queue, queued_operation) {
+            self.store_or_queue(queue, QueuedOperation::new(operation, new_state.clone()))
+                .await?;
+        }
+
+        Ok(())
     }
-}
 
-/// Implements the Default trait for CoreSDK
-///
-/// This allows creating a CoreSDK instance using Default::default()
-impl Default for CoreSDK {
-    fn default() -> Self {
-        Self::new()
+    /// Store a new state on the storage node, queuing it while the node is unreachable
+    ///
+    /// A state is also queued while earlier states are waiting, so the storage
+    /// node receives them in execution order. The transition is already
+    /// committed at this point, so a state the node rejects is logged rather
+    /// than returned.
+    async fn store_or_queue(
+        &self,
+        queue: &OfflineQueue,
+        op: QueuedOperation,
+    ) -> Result<(), DsmError> {
+        let client = self.storage_client.read().clone();
+        if let Some(client) = client.filter(|_| queue.is_empty()) {
+            match client.replay(&op).await {
+                Ok(()) => return Ok(()),
+                Err(e @ DsmError::Network { .. }) => warn!(
+                    state_number = op.state.state_number,
+                    error = %e,
+                    "Storage node unreachable; queuing state"
+                ),
+                Err(e) => {
+                    warn!(
+                        state_number = op.state.state_number,
+                        error = %e,
+                        "Failed to store state"
+                    );
+                    return Ok(());
+                }
+            }
+        }
+
+        queue.enqueue(op)
+    }
+
+    /// Replay the states queued while the storage node was unreachable
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DrainResult)` - How many states were stored, which were rejected and
+    ///   how many are still queued
+    /// * `Err(DsmError::Storage)` - If the SDK has no offline queue or no storage
+    ///   client is registered
+    pub async fn drain_offline_queue(&self) -> Result<DrainResult, DsmError> {
+        let queue = self.offline_queue.clone().ok_or_else(|| {
+            DsmError::storage(
+                "No offline queue configured; nothing to drain",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+        let client = self.storage_client.read().clone().ok_or_else(|| {
+            DsmError::storage(
+                "No storage client registered; cannot drain the offline queue",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        queue.drain_queue(client.as_ref()).await
+    }
+
+    /// Execute a state transition for a signed operation
+    ///
+    /// The signature must verify against the public key of the device that
+    /// owns the chain. Signed operations are kept in the operation log, which
+    /// [`Self::export_operation_log`] returns.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(State)` - The new state resulting from the transition
+    /// * `Err(DsmError::Verification)` - If the signature does not verify
+    /// * `Err(DsmError)` - If the transition failed
+    pub async fn execute_signed_transition(
+        &self,
+        signed: SignedOperation,
+    ) -> Result<State, DsmError> {
+        self.session.write().push(SessionEvent::SignedTransition {
+            signed: signed.clone(),
+        });
+
+        let public_key = self.get_current_state()?.device_info.public_key;
+        if !signed.verify(&public_key)? {
+            return Err(DsmError::verification("Operation signature is invalid"));
+        }
+
+        let new_state = self.transition(signed.operation.clone()).await?;
+        self.operation_log.write().push(signed);
+
+        Ok(new_state)
+    }
+
+    /// Re-derive every state of the chain from its genesis state
+    ///
+    /// Applies the operations in order to the genesis state, as successive
+    /// `execute_transition` calls would, without touching the SDK's own
+    /// chain. Every signature must verify against the genesis device's
+    /// public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `operations` - Signed operations in execution order
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<State>)` - The state after each operation
+    /// * `Err(DsmError::ReplayDiverged)` - If an operation has an invalid signature
+    ///   or cannot be applied, with its index
+    /// * `Err(DsmError::NotFound)` - If the genesis state has been pruned
+    pub fn replay_from_genesis(
+        &self,
+        operations: &[SignedOperation],
+    ) -> Result<Vec<State>, DsmError> {
+        let genesis = self.hash_chain_sdk.get_state_by_number(0)?;
+        let public_key = genesis.device_info.public_key.clone();
+
+        let mut state_machine = StateMachine::new();
+        state_machine.set_state(genesis);
+
+        let mut states = Vec::with_capacity(operations.len());
+        for (index, signed) in operations.iter().enumerate() {
+            let diverged = |reason: String| DsmError::ReplayDiverged { index, reason };
+
+            match signed.verify(&public_key) {
+                Ok(true) => {}
+                Ok(false) => return Err(diverged("invalid signature".to_string())),
+                Err(e) => return Err(diverged(format!("invalid signature: {}", e))),
+            }
+
+            let state = apply_operation(&mut state_machine, signed.operation.clone())
+                .map_err(|e| diverged(e.to_string()))?;
+            states.push(state);
+        }
+
+        Ok(states)
+    }
+
+    /// Export the signed operations that lead from genesis to the current state
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<SignedOperation>)` - The operation log, in execution order
+    /// * `Err(DsmError::State)` - If part of the history was not executed through
+    ///   `execute_signed_transition`, or came from a checkpoint or another device
+    pub fn export_operation_log(&self) -> Result<Vec<SignedOperation>, DsmError> {
+        let current_state = self.get_current_state()?;
+        let log = self.operation_log.read();
+
+        if log.len() as u64 != current_state.state_number {
+            return Err(DsmError::state(format!(
+                "Operation log holds {} signed operations but the chain has {} transitions",
+                log.len(),
+                current_state.state_number
+            )));
+        }
+
+        Ok(log.clone())
+    }
+
+    /// Create a checkpoint snapshot of a state
+    fn create_checkpoint(&self, state: &State) -> Result<State, DsmError> {
+        let mut checkpoint = state.clone();
+        checkpoint.hash = state.hash()?;
+        checkpoint.is_checkpoint = true;
+        Ok(checkpoint)
+    }
+
+    /// Create a checkpoint of a state, cache it, and optionally store it on the network
+    ///
+    /// The transition is already committed at this point, so failing to reach
+    /// the storage node is logged rather than returned.
+    async fn store_checkpoint(
+        &self,
+        state: &State,
+        store_to_network: bool,
+    ) -> Result<(), DsmError> {
+        let checkpoint = self.create_checkpoint(state)?;
+
+        self.storage_cache()
+            .cache_checkpoint(checkpoint.clone(), true, None)
+            .await?;
+
+        if store_to_network {
+            let client = self.storage_client.read().clone();
+            match client {
+                Some(client) => {
+                    if let Err(e) = client.store_checkpoint(&checkpoint).await {
+                        warn!(
+                            state_number = checkpoint.state_number,
+                            error = %e,
+                            "Failed to store checkpoint"
+                        );
+                    }
+                }
+                None => warn!(
+                    state_number = checkpoint.state_number,
+                    "No storage client registered; checkpoint is cached only"
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restore the system from a checkpoint without replaying history
+    ///
+    /// The checkpoint is looked up in the storage cache first and then on the
+    /// storage node, if a client is registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint_id` - Identifier of the checkpoint, as given by `StorageCache::checkpoint_id`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the system now continues from the checkpoint
+    /// * `Err(DsmError::NotFound)` - If the checkpoint could not be found
+    /// * `Err(DsmError::Verification)` - If the checkpoint does not match its identifier
+    pub async fn restore_from_checkpoint(&self, checkpoint_id: &str) -> Result<(), DsmError> {
+        let mut checkpoint = self.storage_cache().get_checkpoint(checkpoint_id).await?;
+
+        if checkpoint.is_none() {
+            let client = self.storage_client.read().clone();
+            if let Some(client) = client {
+                checkpoint = client.fetch_checkpoint(checkpoint_id).await.map_err(|e| {
+                    DsmError::storage(
+                        format!("Failed to fetch checkpoint {}", checkpoint_id),
+                        Some(e),
+                    )
+                })?;
+            }
+        }
+
+        let checkpoint = checkpoint
+            .ok_or_else(|| DsmError::not_found("Checkpoint", Some(checkpoint_id.to_string())))?;
+
+        if !checkpoint.is_checkpoint
+            || checkpoint.compute_hash()? != checkpoint.hash
+            || StorageCache::checkpoint_id(&checkpoint)? != checkpoint_id
+        {
+            return Err(DsmError::verification(format!(
+                "Checkpoint {} failed integrity verification",
+                checkpoint_id
+            )));
+        }
+
+        self.hash_chain_sdk.initialize_from_checkpoint(checkpoint.clone())?;
+        self.state_machine.write().set_state(checkpoint);
+
+        // The history before the checkpoint is not known here
+        self.operation_log.write().clear();
+
+        Ok(())
+    }
+
+    /// Synchronize the state chain with the identity's other devices
+    ///
+    /// Uses the registered storage client; see `IdentitySDK::sync_device_state`.
+    /// Transitions executed afterwards continue from the synchronized head.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SyncResult)` - How many states were applied and the resulting head
+    /// * `Err(DsmError::Storage)` - If no storage client is registered or the node
+    ///   could not be reached
+    /// * `Err(DsmError::StateConflict)` - If this device's chain has diverged
+    pub async fn sync_device_state(&self) -> Result<SyncResult, DsmError> {
+        let client = self.storage_client.read().clone().ok_or_else(|| {
+            DsmError::storage(
+                "No storage client registered; cannot sync device state",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        let result = self.identity_sdk.sync_device_state(&client).await?;
+
+        if let Some(head) = self.hash_chain_sdk.current_state() {
+            self.state_machine.write().set_state(head);
+        }
+
+        Ok(result)
+    }
+
+    /// Receive the entries waiting in an identity's inbox
+    ///
+    /// Every entry is acknowledged on the storage node before it is returned,
+    /// so a restart does not hand it to the application again; the
+    /// application deletes an entry once it has processed it. An entry it
+    /// acknowledges but never deletes is delivered again after the entry's
+    /// `ack_deadline_secs`. Entries that cannot be acknowledged are left for
+    /// the next call. Each returned entry is published as an
+    /// [`InboxMessageReceived`] event on the SDK's event bus.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipient_genesis` - Genesis state of the identity whose inbox to read
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<InboxEntry>)` - The acknowledged entries
+    /// * `Err(DsmError::Storage)` - If no storage client is registered or the
+    ///   inbox could not be fetched
+    pub async fn receive_inbox_entries(
+        &self,
+        recipient_genesis: &GenesisState,
+    ) -> Result<Vec<InboxEntry>, DsmError> {
+        let client = self.storage_client.read().clone().ok_or_else(|| {
+            DsmError::storage(
+                "No storage client registered; cannot receive inbox entries",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        let entries = client
+            .get_inbox_transactions(&hex::encode(&recipient_genesis.hash))
+            .await
+            .map_err(|e| DsmError::storage("Failed to fetch inbox entries", Some(e)))?;
+
+        let mut received = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if let Err(e) = client.acknowledge_inbox_entry(recipient_genesis, &entry.id).await {
+                warn!(entry_id = %entry.id, error = %e, "Failed to acknowledge inbox entry");
+                continue;
+            }
+
+            if let Some(bus) = &self.event_bus {
+                bus.publish(InboxMessageReceived {
+                    entry_id: entry.id.clone(),
+                    sender_hash: entry.sender_genesis_hash.clone(),
+                });
+            }
+            received.push(entry);
+        }
+
+        Ok(received)
+    }
+
+    /// Prune the state history below a state, keeping a checkpoint in its place
+    ///
+    /// A checkpoint of state `keep_from_state` is stored on the storage node
+    /// unless the node already holds one. Only once the node has it are the
+    /// states numbered below `keep_from_state` deleted from the storage node,
+    /// the storage cache and the hash chain. Network deletions happen before
+    /// anything is removed locally, so a failed prune can simply be retried.
+    ///
+    /// Nodes that restrict writes only delete states for a storage client
+    /// configured with this identity's ACL key and genesis hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep_from_state` - Number of the oldest state to keep
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PruningReport)` - What was removed and where the checkpoint is
+    /// * `Err(DsmError::Storage)` - If no storage client is registered or the node
+    ///   could not be reached
+    /// * `Err(DsmError::NotFound)` - If the chain has no state `keep_from_state`
+    pub async fn prune_state_history(
+        &self,
+        keep_from_state: u64,
+    ) -> Result<PruningReport, DsmError> {
+        let client = self.storage_client.read().clone().ok_or_else(|| {
+            DsmError::storage(
+                "No storage client registered; cannot prune history stored on the network",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        let checkpoint =
+            self.create_checkpoint(&self.hash_chain_sdk.get_state_by_number(keep_from_state)?)?;
+        let checkpoint_id = StorageCache::checkpoint_id(&checkpoint)?;
+
+        // The checkpoint must be on the storage node before any history goes
+        let stored = client.checkpoint_exists(&checkpoint_id).await.map_err(|e| {
+            DsmError::storage(format!("Failed to look up checkpoint {}", checkpoint_id), Some(e))
+        })?;
+        if !stored {
+            client.store_checkpoint(&checkpoint).await.map_err(|e| {
+                DsmError::storage(format!("Failed to store checkpoint {}", checkpoint_id), Some(e))
+            })?;
+        }
+        self.storage_cache()
+            .cache_checkpoint(checkpoint.clone(), true, None)
+            .await?;
+
+        for state_hash in self.hash_chain_sdk.state_hashes_before(keep_from_state)? {
+            client.delete_state(&state_hash).await.map_err(|e| {
+                DsmError::storage(
+                    format!("Failed to delete state {}", hex::encode(&state_hash)),
+                    Some(e),
+                )
+            })?;
+        }
+
+        let purged = self.storage_cache().purge_before_state(keep_from_state).await;
+        let pruned = self.hash_chain_sdk.prune_before(keep_from_state);
+
+        let mut bytes_freed = purged.bytes_freed as u64;
+        for state in &pruned {
+            bytes_freed += bincode::serialized_size(state)?;
+        }
+
+        Ok(PruningReport {
+            states_removed: pruned.len() as u64,
+            bytes_freed,
+            checkpoint_state_number: checkpoint.state_number,
+        })
+    }
+
+    /// Create an initial (genesis) state
+    ///
+    /// Creates a genesis state (G) as described in whitepaper section 4,
+    /// which serves as the foundation for the state chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_info` - Information about the device creating the genesis state
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(State)` - The created genesis state if successful
+    /// * `Err(DsmError)` - If genesis state creation failed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dsm_sdk::core_sdk::CoreSDK;
+    /// use dsm::types::state_types::DeviceInfo;
+    ///
+    /// fn create_genesis(sdk: &CoreSDK) {
+    ///     let device_info = DeviceInfo::new("device_id", vec![1, 2, 3, 4]);
+    ///     let genesis = sdk.create_initial_state(&device_info).unwrap();
+    ///     // Use genesis state to initialize system
+    /// }
+    /// ```
+    pub fn create_initial_state(&self, device_info: &DeviceInfo) -> Result<State, DsmError> {
+        let operation = Operation::Create {
+            message: "Initial state creation".to_string(),
+            identity_data: vec![],
+            public_key: vec![],
+            metadata: vec![],
+            commitment: vec![],
+            proof: vec![],
+            mode: TransactionMode::Bilateral, // Use Bilateral mode
+        };
+
+        // Use the updated StateParams::new constructor with the correct parameter count
+        let params = StateParams::new(
+            0,                   // state_number
+            vec![0u8; 32],       // entropy
+            operation,           // operation
+            device_info.clone(), // device_info
+        );
+
+        // Set additional parameters using the builder pattern
+        let params = params.with_prev_state_hash(vec![0u8; 32]);
+
+        Ok(State::new(params))
+    }
+}
+
+/// Apply an operation to the state machine's current state
+///
+/// Shared by transitions and replays, so that both derive identical states.
+fn apply_operation(
+    state_machine: &mut StateMachine,
+    operation: Operation,
+) -> Result<State, DsmError> {
+    // Prerequisite operations must already be recorded in the current state;
+    // committed transfers are verified by the transition itself
+    if let Some(current_state) = state_machine.current_state() {
+        current_state.check_dependencies(&operation)?;
+    }
+
+    // Dependency declarations are resolved; execute the wrapped operation
+    state_machine.execute_transition(operation.into_innermost())
+}
+
+/// Balance of a token held by the state's device
+///
+/// Balances are keyed by device and token, or by token alone.
+fn device_balance(state: &State, token_id: &str) -> Option<Balance> {
+    let device_id = &state.device_info.device_id;
+    state
+        .token_balances
+        .get(&format!("{}.{}", device_id, token_id))
+        .or_else(|| state.token_balances.get(token_id))
+        .cloned()
+}
+
+/// Sum of a token's balances in a state
+///
+/// Balances are keyed by holder and token, or by token alone.
+fn total_supply(state: &State, token_id: &str) -> Balance {
+    let holder_suffix = format!(".{}", token_id);
+    let supply = state
+        .token_balances
+        .iter()
+        .filter(|(key, _)| *key == token_id || key.ends_with(&holder_suffix))
+        .fold(0u64, |supply, (_, balance)| supply.saturating_add(balance.value()));
+
+    Balance::from_state(supply, state.hash.clone())
+}
+
+/// Signed balance changes an operation makes, keyed by `"device_id:token_id"`
+///
+/// Transfers include the fee of the state's fee policy, paid by the device
+/// to the fee recipient. Fails if the operation would spend more than the
+/// state's device holds.
+fn balance_deltas(state: &State, operation: &Operation) -> Result<HashMap<String, i128>, DsmError> {
+    let device_id = &state.device_info.device_id;
+    let spend = |token_id: &str, amount: u64| {
+        let available = device_balance(state, token_id).map_or(0, |b| b.value());
+        if amount > available {
+            return Err(DsmError::insufficient_balance(
+                token_id.to_string(),
+                available,
+                amount,
+            ));
+        }
+        Ok(-i128::from(amount))
+    };
+
+    let mut deltas = HashMap::new();
+    match operation.innermost() {
+        Operation::Transfer {
+            to_address,
+            amount,
+            token_id,
+            ..
+        } => {
+            let fee = state.transfer_fee(operation)?;
+            let total = amount.value().checked_add(fee).ok_or_else(|| {
+                DsmError::invalid_parameter("Transfer amount and fee overflow")
+            })?;
+            *deltas.entry(format!("{}:{}", device_id, token_id)).or_insert(0) +=
+                spend(token_id, total)?;
+            *deltas.entry(format!("{}:{}", to_address, token_id)).or_insert(0) +=
+                i128::from(amount.value());
+            if let Some(policy) = state.fee_policy.as_ref().filter(|_| fee > 0) {
+                *deltas
+                    .entry(format!("{}:{}", policy.fee_recipient, token_id))
+                    .or_insert(0) += i128::from(fee);
+            }
+        }
+        Operation::Mint {
+            amount, token_id, ..
+        } => {
+            deltas.insert(format!("{}:{}", device_id, token_id), i128::from(amount.value()));
+        }
+        Operation::Burn {
+            amount, token_id, ..
+        } => {
+            deltas.insert(
+                format!("{}:{}", device_id, token_id),
+                spend(token_id, amount.value())?,
+            );
+        }
+        _ => {}
+    }
+
+    Ok(deltas)
+}
+
+/// Publish the events caused by the transition from `old_state` to `new_state`
+fn publish_transition_events(bus: &DsmEventBus, old_state: &State, new_state: &State) {
+    bus.publish(StateTransitioned {
+        old_state_number: old_state.state_number,
+        new_state_number: new_state.state_number,
+        operation_hash: new_state.operation.operation_hash(),
+    });
+
+    if let Operation::Transfer {
+        amount, token_id, ..
+    } = &new_state.operation
+    {
+        let device_id = &new_state.device_info.device_id;
+        let old = device_balance(old_state, token_id)
+            .unwrap_or_else(|| Balance::from_state(0, old_state.hash.clone()));
+        let fee = old_state.transfer_fee(&new_state.operation).unwrap_or(0);
+        let new = Balance::from_state(
+            old.value()
+                .saturating_sub(amount.value())
+                .saturating_sub(fee),
+            new_state.hash.clone(),
+        );
+
+        bus.publish(TokenBalanceChanged {
+            device_id: device_id.clone(),
+            token_id: token_id.clone(),
+            old,
+            new,
+        });
+    }
+}
+
+/// Implements the Default trait for CoreSDK
+///
+/// This allows creating a CoreSDK instance using Default::default()
+impl Default for CoreSDK {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::token_sdk::TokenSDK;
+    use dsm::test_support::genesis;
+    use dsm::types::token_types::{FeePolicy, Ratio};
+    use dsm_storage_node::client::StorageNodeClientConfig;
+    use mockito::Matcher;
+
+    async fn initialized_sdk() -> CoreSDK {
+        let sdk = CoreSDK::new();
+        let device_info = DeviceInfo::new("dependency_device", vec![1, 2, 3, 4]);
+        let genesis = sdk.create_initial_state(&device_info).unwrap();
+        sdk.initialize_with_genesis(genesis, None).await.unwrap();
+        sdk
+    }
+
+    fn depends_on(dependencies: Vec<[u8; 32]>, inner: Operation) -> Operation {
+        Operation::WithDependencies {
+            dependencies,
+            inner: Box::new(inner),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_dependency_satisfied() {
+        let sdk = initialized_sdk().await;
+
+        let deposit = sdk.generic_operation("staking_deposit", vec![1]).unwrap();
+        let deposit_hash = deposit.operation_hash();
+        sdk.execute_transition(deposit).await.unwrap();
+
+        let claim = sdk.generic_operation("vault_claim", vec![2]).unwrap();
+        let claim_hash = claim.operation_hash();
+        let state = sdk
+            .execute_transition(depends_on(vec![deposit_hash], claim.clone()))
+            .await
+            .unwrap();
+
+        // The wrapped operation is what executes and gets recorded
+        assert_eq!(state.operation, claim);
+        assert!(state.executed_operations.contains(&deposit_hash));
+        assert!(state.executed_operations.contains(&claim_hash));
+    }
+
+    #[tokio::test]
+    async fn test_multiple_dependencies_satisfied() {
+        let sdk = initialized_sdk().await;
+
+        let first = sdk.generic_operation("first", vec![1]).unwrap();
+        let second = sdk.generic_operation("second", vec![2]).unwrap();
+        let dependencies = vec![first.operation_hash(), second.operation_hash()];
+        sdk.execute_transition(first).await.unwrap();
+        sdk.execute_transition(second).await.unwrap();
+
+        let dependent = sdk.generic_operation("third", vec![3]).unwrap();
+        assert!(sdk
+            .execute_transition(depends_on(dependencies, dependent))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unsatisfied_dependency_blocks_execution() {
+        let sdk = initialized_sdk().await;
+
+        let executed = sdk.generic_operation("executed", vec![1]).unwrap();
+        let pending = sdk.generic_operation("pending", vec![2]).unwrap();
+        let executed_hash = executed.operation_hash();
+        let pending_hash = pending.operation_hash();
+        sdk.execute_transition(executed).await.unwrap();
+
+        let state_before = sdk.state_machine.read().current_state().cloned().unwrap();
+
+        let dependent = sdk.generic_operation("dependent", vec![3]).unwrap();
+        let result = sdk
+            .execute_transition(depends_on(vec![executed_hash, pending_hash], dependent))
+            .await;
+
+        match result {
+            Err(DsmError::DependencyNotMet { missing_op_hash }) => {
+                assert_eq!(missing_op_hash, hex::encode(pending_hash));
+            }
+            other => panic!("expected DependencyNotMet, got {:?}", other),
+        }
+
+        // No transition took place
+        let state_after = sdk.state_machine.read().current_state().cloned().unwrap();
+        assert_eq!(state_after.state_number, state_before.state_number);
+    }
+
+    #[tokio::test]
+    async fn test_circular_dependency_is_rejected() {
+        let sdk = initialized_sdk().await;
+
+        // An operation that depends on itself can never execute
+        let op = sdk.generic_operation("self_referential", vec![1]).unwrap();
+        let own_hash = op.operation_hash();
+        let result = sdk.execute_transition(depends_on(vec![own_hash], op)).await;
+        assert!(matches!(result, Err(DsmError::Validation { .. })));
+
+        // Two operations that each wait on the other form a cycle
+        let a = sdk.generic_operation("a", vec![1]).unwrap();
+        let b = sdk.generic_operation("b", vec![2]).unwrap();
+        let (a_hash, b_hash) = (a.operation_hash(), b.operation_hash());
+        let graph = vec![depends_on(vec![b_hash], a), depends_on(vec![a_hash], b)];
+        assert!(matches!(
+            Operation::validate_dependency_graph(&graph),
+            Err(DsmError::Validation { .. })
+        ));
+
+        // A chain without cycles is accepted
+        let c = sdk.generic_operation("c", vec![3]).unwrap();
+        let d = sdk.generic_operation("d", vec![4]).unwrap();
+        let c_hash = c.operation_hash();
+        assert!(Operation::validate_dependency_graph(&[c, depends_on(vec![c_hash], d)]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resource_ceiling_blocks_expensive_transitions() {
+        let sdk = initialized_sdk().await;
+        let cheap = sdk.generic_operation("cheap", vec![1]).unwrap();
+        let expensive = sdk
+            .generic_operation("expensive", vec![0; 64 * 1024])
+            .unwrap();
+
+        let state = sdk.state_machine.read().current_state().cloned().unwrap();
+        let ceiling = estimate_resources(&cheap, &state);
+        assert!(estimate_resources(&expensive, &state).cpu_units > ceiling.cpu_units);
+        sdk.set_max_resources_per_transition(Some(ceiling));
+
+        let simulated = sdk.simulate_transition(&expensive).unwrap();
+        assert!(!simulated.success);
+        let result = sdk.execute_transition(expensive.clone()).await;
+        assert!(matches!(
+            result,
+            Err(DsmError::ResourceExceeded { ref resource, .. }) if resource == "cpu_units"
+        ));
+        let state_after = sdk.state_machine.read().current_state().cloned().unwrap();
+        assert_eq!(state_after.state_number, state.state_number);
+
+        sdk.execute_transition(cheap).await.unwrap();
+        sdk.set_max_resources_per_transition(None);
+        sdk.execute_transition(expensive).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_committed_transfer_and_reveal() {
+        use dsm::crypto::pedersen::{
+            PedersenCommitment, PedersenParams, SecurityLevel, TransferProof,
+        };
+        use rand::rngs::OsRng;
+
+        let sdk = initialized_sdk().await;
+        let params = PedersenParams::balance_params(SecurityLevel::Standard128);
+        let mut rng = OsRng;
+
+        // Commit a balance of 500, dropping any plaintext balance for the token
+        let (balance, r_balance) =
+            PedersenCommitment::commit_amount(params, 500, &mut rng).unwrap();
+        {
+            let mut state_machine = sdk.state_machine.write();
+            let mut state = state_machine.current_state().cloned().unwrap();
+            state
+                .token_balances
+                .insert("dependency_device.PRIV".to_string(), Balance::new(500));
+            state.commit_balance("PRIV", balance.clone(), true);
+            assert!(state.token_balances.is_empty());
+            state_machine.set_state(state);
+        }
+
+        let (remaining, r_remaining) =
+            PedersenCommitment::commit_amount(params, 380, &mut rng).unwrap();
+        let (sent, r_sent) = PedersenCommitment::commit_amount(params, 120, &mut rng).unwrap();
+
+        // A transfer that inflates the total is rejected without a transition
+        let (inflated, r_inflated) =
+            PedersenCommitment::commit_amount(params, 200, &mut rng).unwrap();
+        let bad_proof = TransferProof::prove(
+            params,
+            "PRIV",
+            (&balance, &r_balance),
+            (&remaining, 380, &r_remaining),
+            (&inflated, 200, &r_inflated),
+            &mut rng,
+        )
+        .unwrap();
+        let bad = Operation::transfer_committed("bob", remaining.clone(), inflated, bad_proof);
+        assert!(matches!(
+            sdk.execute_transition(bad).await,
+            Err(DsmError::Verification(_))
+        ));
+
+        let proof = TransferProof::prove(
+            params,
+            "PRIV",
+            (&balance, &r_balance),
+            (&remaining, 380, &r_remaining),
+            (&sent, 120, &r_sent),
+            &mut rng,
+        )
+        .unwrap();
+        let transfer = Operation::transfer_committed("bob", remaining.clone(), sent.clone(), proof);
+        let state = sdk.execute_transition(transfer).await.unwrap();
+        assert_eq!(state.committed_balances.get("PRIV"), Some(&remaining));
+        assert_eq!(state.committed_balances.get("bob.PRIV"), Some(&sent));
+
+        // Disclosing the new balance succeeds only with the right amount
+        let wrong = Operation::RevealBalance {
+            token_id: "PRIV".to_string(),
+            amount: 500,
+            blinding_factor: r_remaining.to_bytes_be(),
+        };
+        assert!(sdk.execute_transition(wrong).await.is_err());
+
+        let reveal = Operation::RevealBalance {
+            token_id: "PRIV".to_string(),
+            amount: 380,
+            blinding_factor: r_remaining.to_bytes_be(),
+        };
+        assert!(sdk.execute_transition(reveal).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_commit_operations_on_current_snapshot() {
+        let sdk = initialized_sdk().await;
+        let snapshot = sdk.begin_snapshot().unwrap();
+        assert_eq!(snapshot.state_number(), 0);
+
+        let ops = vec![
+            sdk.generic_operation("first", vec![1]).unwrap(),
+            sdk.generic_operation("second", vec![2]).unwrap(),
+        ];
+        let state = sdk.commit_operations(snapshot, ops).await.unwrap();
+
+        assert_eq!(state.state_number, 2);
+        assert_eq!(sdk.get_current_state().unwrap().hash, state.hash);
+        assert_eq!(sdk.get_state_by_number(1).unwrap().state_number, 1);
+    }
+
+    #[tokio::test]
+    async fn test_commit_operations_on_stale_snapshot() {
+        let sdk = initialized_sdk().await;
+        let snapshot = sdk.begin_snapshot().unwrap();
+
+        let intervening = sdk.generic_operation("intervening", vec![1]).unwrap();
+        let current = sdk.execute_transition(intervening).await.unwrap();
+
+        // The snapshot keeps its view of the state it was taken of
+        assert_eq!(snapshot.state_number(), 0);
+
+        let ops = vec![sdk.generic_operation("planned", vec![2]).unwrap()];
+        match sdk.commit_operations(snapshot, ops).await {
+            Err(DsmError::SnapshotStale {
+                snapshot_at,
+                current,
+            }) => assert_eq!((snapshot_at, current), (0, 1)),
+            other => panic!("expected SnapshotStale, got {:?}", other),
+        }
+
+        // The failed commit left the state unchanged
+        assert_eq!(sdk.get_current_state().unwrap().hash, current.hash);
+        let machine_state = sdk.state_machine.read().current_state().cloned().unwrap();
+        assert_eq!(machine_state.hash, current.hash);
+    }
+
+    #[tokio::test]
+    async fn test_failed_operation_rolls_back_commit() {
+        let sdk = initialized_sdk().await;
+        let before = sdk.get_current_state().unwrap();
+        let snapshot = sdk.begin_snapshot().unwrap();
+
+        let ops = vec![
+            sdk.generic_operation("applied", vec![1]).unwrap(),
+            depends_on(vec![[9; 32]], sdk.generic_operation("blocked", vec![2]).unwrap()),
+        ];
+        assert!(matches!(
+            sdk.commit_operations(snapshot.clone(), ops).await,
+            Err(DsmError::DependencyNotMet { .. })
+        ));
+
+        // Neither operation took effect, so the snapshot is still current
+        assert_eq!(sdk.get_current_state().unwrap().hash, before.hash);
+        let ops = vec![sdk.generic_operation("retried", vec![3]).unwrap()];
+        let state = sdk.commit_operations(snapshot, ops).await.unwrap();
+        assert_eq!(state.state_number, 1);
+    }
+
+    fn numbered_operation(sdk: &CoreSDK, i: u64) -> Operation {
+        sdk.generic_operation("checkpointed", i.to_le_bytes().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_created_at_policy_interval() {
+        let sdk = initialized_sdk().await;
+        sdk.set_checkpoint_policy(Some(CheckpointPolicy {
+            every_n_transitions: 100,
+            store_to_network: false,
+        }));
+
+        let mut checkpoint_ids = Vec::new();
+        for i in 1..=1000 {
+            let state = sdk.execute_transition(numbered_operation(&sdk, i)).await.unwrap();
+            let id = StorageCache::checkpoint_id(&state).unwrap();
+            let cached = sdk.storage_cache().has_checkpoint(&id).await;
+            assert_eq!(cached, state.state_number % 100 == 0, "state {}", i);
+            if cached {
+                checkpoint_ids.push((state.state_number, id));
+            }
+        }
+
+        let numbers: Vec<u64> = checkpoint_ids.iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, (1..=10).map(|n| n * 100).collect::<Vec<u64>>());
+
+        let checkpoint = sdk
+            .storage_cache()
+            .get_checkpoint(&checkpoint_ids[0].1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(checkpoint.is_checkpoint);
+    }
+
+    #[tokio::test]
+    async fn test_restore_from_checkpoint_matches_full_replay() {
+        let sdk = initialized_sdk().await;
+        sdk.set_checkpoint_policy(Some(CheckpointPolicy {
+            every_n_transitions: 250,
+            store_to_network: false,
+        }));
+
+        let mut checkpoint_id = None;
+        let mut full_replay = None;
+        for i in 1..=1000 {
+            let state = sdk.execute_transition(numbered_operation(&sdk, i)).await.unwrap();
+            if state.state_number == 750 {
+                checkpoint_id = Some(StorageCache::checkpoint_id(&state).unwrap());
+            }
+            full_replay = Some(state);
+        }
+        let full_replay = full_replay.unwrap();
+
+        // Restore from state 750 and re-apply the last 250 operations
+        sdk.restore_from_checkpoint(&checkpoint_id.unwrap()).await.unwrap();
+        assert_eq!(sdk.get_current_state().unwrap().state_number, 750);
+
+        let mut restored = None;
+        for i in 751..=1000 {
+            restored = Some(sdk.execute_transition(numbered_operation(&sdk, i)).await.unwrap());
+        }
+        let restored = restored.unwrap();
+
+        assert_eq!(restored.state_number, full_replay.state_number);
+        assert_eq!(restored.hash, full_replay.hash);
+        assert!(!restored.is_checkpoint);
+
+        // Unknown checkpoints are reported as missing
+        assert!(matches!(
+            sdk.restore_from_checkpoint("checkpoint_1_00").await,
+            Err(DsmError::NotFound { .. })
+        ));
+    }
+
+    /// SDK with `transitions` states after genesis, registered with a storage node at `server`
+    async fn sdk_with_history(server: &mut mockito::ServerGuard, transitions: u64) -> CoreSDK {
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+
+        let sdk = initialized_sdk().await;
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+        sdk.register_storage_client(Arc::new(client));
+
+        for i in 1..=transitions {
+            let state = sdk.execute_transition(numbered_operation(&sdk, i)).await.unwrap();
+            sdk.storage_cache().cache_state(state, true, None).await.unwrap();
+        }
+        sdk
+    }
+
+    #[tokio::test]
+    async fn test_prune_state_history_keeps_checkpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let sdk = sdk_with_history(&mut server, 20).await;
+        let hash_chain = sdk.hash_chain_sdk();
+
+        let pruned_state = hash_chain.get_state_by_number(5).unwrap();
+        let kept_state = hash_chain.get_state_by_number(15).unwrap();
+        let checkpoint = sdk
+            .create_checkpoint(&hash_chain.get_state_by_number(10).unwrap())
+            .unwrap();
+        let checkpoint_id = StorageCache::checkpoint_id(&checkpoint).unwrap();
+
+        let exists = server
+            .mock("GET", format!("/data/checkpoint:{}/exists", checkpoint_id).as_str())
+            .with_status(404)
+            .create_async()
+            .await;
+        let store = server
+            .mock("POST", "/checkpoint")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "checkpoint_id": checkpoint_id,
+                "state_number": 10,
+            })))
+            .with_body("{}")
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", Matcher::Regex(r"^/state/[0-9a-f]+$".to_string()))
+            .with_body("{}")
+            .expect(10)
+            .create_async()
+            .await;
+        server
+            .mock("GET", Matcher::Regex(r"^/data/state:[0-9a-f]+$".to_string()))
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let report = sdk.prune_state_history(10).await.unwrap();
+        exists.assert_async().await;
+        store.assert_async().await;
+        delete.assert_async().await;
+
+        // Genesis and states 1 to 9 are gone; state 10 onwards is untouched
+        assert_eq!(report.states_removed, 10);
+        assert_eq!(report.checkpoint_state_number, 10);
+        assert!(report.bytes_freed > 0);
+        assert!(hash_chain.get_state_by_number(9).is_err());
+        assert!(hash_chain.get_state_by_number(10).is_ok());
+        assert_eq!(sdk.get_current_state().unwrap().state_number, 20);
+
+        assert!(sdk.storage_cache().has_checkpoint(&checkpoint_id).await);
+
+        let client = sdk.storage_client.read().clone().unwrap();
+        assert!(client.fetch_state(&pruned_state.hash().unwrap()).await.unwrap().is_none());
+        let fetched = client.fetch_state(&kept_state.hash().unwrap()).await.unwrap();
+        assert_eq!(fetched.map(|state| state.hash), Some(kept_state.hash));
+    }
+
+    #[tokio::test]
+    async fn test_prune_state_history_needs_stored_checkpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let sdk = sdk_with_history(&mut server, 5).await;
+
+        server
+            .mock("GET", Matcher::Regex(r"^/data/checkpoint:.*/exists$".to_string()))
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/checkpoint")
+            .with_status(503)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        // Without the checkpoint on the storage node nothing may be removed
+        assert!(sdk.prune_state_history(3).await.is_err());
+        delete.assert_async().await;
+        assert!(sdk.hash_chain_sdk().get_state_by_number(0).is_ok());
+        let first = sdk.hash_chain_sdk().get_state_by_number(1).unwrap();
+        assert!(sdk
+            .storage_cache()
+            .get_state(&first.hash().unwrap())
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    /// Fake storage node that keeps stored data and the identity head in memory
+    async fn shared_storage_node(server: &mut mockito::ServerGuard) {
+        use base64::Engine;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        let objects: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+        let head: Arc<Mutex<Option<Vec<u8>>>> = Arc::default();
+
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+
+        let stored = objects.clone();
+        server
+            .mock("POST", "/data")
+            .with_body_from_request(move |request| {
+                let payload: HashMap<String, String> =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(&payload["data"])
+                    .unwrap();
+                stored.lock().unwrap().insert(payload["key"].clone(), data);
+                b"{}".to_vec()
+            })
+            .create_async()
+            .await;
+
+        server
+            .mock("GET", Matcher::Regex(r"^/data/state:[0-9a-f]+$".to_string()))
+            .with_body_from_request(move |request| {
+                let key = request.path().trim_start_matches("/data/");
+                objects.lock().unwrap().get(key).cloned().unwrap_or_default()
+            })
+            .create_async()
+            .await;
+
+        let published = head.clone();
+        server
+            .mock("PUT", Matcher::Regex(r"^/identity/[0-9a-f]+/head$".to_string()))
+            .with_body_from_request(move |request| {
+                *published.lock().unwrap() = Some(request.body().unwrap().clone());
+                b"{}".to_vec()
+            })
+            .create_async()
+            .await;
+
+        server
+            .mock("GET", Matcher::Regex(r"^/identity/[0-9a-f]+/head$".to_string()))
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |_| {
+                head.lock().unwrap().clone().unwrap_or_else(|| b"null".to_vec())
+            })
+            .create_async()
+            .await;
+    }
+
+    /// Two devices sharing an identity and the storage node at `server`
+    async fn owned_devices(server: &mut mockito::ServerGuard) -> (CoreSDK, CoreSDK) {
+        shared_storage_node(server).await;
+
+        let laptop = CoreSDK::new();
+        let phone = CoreSDK::new();
+        let genesis = laptop
+            .create_initial_state(&DeviceInfo::new("laptop", vec![1, 2, 3, 4]))
+            .unwrap();
+
+        for sdk in [&laptop, &phone] {
+            sdk.initialize_with_genesis(genesis.clone(), None)
+                .await
+                .unwrap();
+            let client = StorageNodeClient::new(StorageNodeClientConfig {
+                base_url: server.url(),
+                ..StorageNodeClientConfig::default()
+            })
+            .unwrap();
+            sdk.register_storage_client(Arc::new(client));
+        }
+
+        (laptop, phone)
+    }
+
+    #[tokio::test]
+    async fn test_sync_brings_devices_to_same_head() {
+        let mut server = mockito::Server::new_async().await;
+        let (laptop, phone) = owned_devices(&mut server).await;
+
+        for i in 1..=3 {
+            laptop.execute_transition(numbered_operation(&laptop, i)).await.unwrap();
+        }
+        let pushed = laptop.sync_device_state().await.unwrap();
+        assert_eq!(
+            pushed,
+            SyncResult {
+                transitions_applied: 0,
+                final_state_number: 3,
+            }
+        );
+
+        let pulled = phone.sync_device_state().await.unwrap();
+        assert_eq!(pulled.transitions_applied, 3);
+        assert_eq!(pulled.final_state_number, 3);
+        assert_eq!(
+            phone.get_current_state().unwrap().hash().unwrap(),
+            laptop.get_current_state().unwrap().hash().unwrap()
+        );
+
+        // The phone continues from the synced head and the laptop catches up
+        for i in 4..=5 {
+            phone.execute_transition(numbered_operation(&phone, i)).await.unwrap();
+        }
+        assert_eq!(phone.sync_device_state().await.unwrap().final_state_number, 5);
+
+        let pulled = laptop.sync_device_state().await.unwrap();
+        assert_eq!(pulled.transitions_applied, 2);
+        assert_eq!(pulled.final_state_number, 5);
+        assert_eq!(
+            laptop.get_current_state().unwrap().hash().unwrap(),
+            phone.get_current_state().unwrap().hash().unwrap()
+        );
+        assert!(laptop.hash_chain_sdk().verify_chain().unwrap());
+
+        // Nothing is applied once both devices are at the same head
+        let resynced = phone.sync_device_state().await.unwrap();
+        assert_eq!(resynced.transitions_applied, 0);
+        assert_eq!(resynced.final_state_number, 5);
+    }
+
+    #[tokio::test]
+    async fn test_sync_detects_diverged_chains() {
+        let mut server = mockito::Server::new_async().await;
+        let (laptop, phone) = owned_devices(&mut server).await;
+
+        let laptop_op = laptop.generic_operation("laptop", vec![1]).unwrap();
+        laptop.execute_transition(laptop_op).await.unwrap();
+        laptop.sync_device_state().await.unwrap();
+
+        for i in 1..=2 {
+            let phone_op = phone.generic_operation("phone", vec![i]).unwrap();
+            phone.execute_transition(phone_op).await.unwrap();
+        }
+
+        // The phone's chain does not contain the published head
+        assert!(matches!(
+            phone.sync_device_state().await,
+            Err(DsmError::StateConflict {
+                local_tip: 2,
+                remote_tip: 1,
+            })
+        ));
+
+        for i in 2..=3 {
+            let laptop_op = laptop.generic_operation("laptop", vec![i]).unwrap();
+            laptop.execute_transition(laptop_op).await.unwrap();
+        }
+        laptop.sync_device_state().await.unwrap();
+
+        // The published states do not build on the phone's head
+        assert!(matches!(
+            phone.sync_device_state().await,
+            Err(DsmError::StateConflict {
+                local_tip: 2,
+                remote_tip: 3,
+            })
+        ));
+        assert_eq!(phone.get_current_state().unwrap().state_number, 2);
+    }
+
+    #[tokio::test]
+    async fn test_register_owned_device() {
+        let mut server = mockito::Server::new_async().await;
+        let (laptop, _) = owned_devices(&mut server).await;
+        let identity = laptop.identity_sdk();
+        let genesis_hash = identity.genesis_hash().unwrap();
+
+        let device_info = DeviceInfo::new("phone", vec![5, 6, 7, 8]);
+        let owner_signature = identity
+            .sign_data(&dsm_storage_node::api::device_registration_message(
+                &genesis_hash,
+                &device_info,
+            ))
+            .unwrap();
+
+        let register = server
+            .mock("POST", format!("/identity/{}/devices", genesis_hash).as_str())
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "device_info": { "device_id": "phone" },
+                "owner_public_key": identity.get_public_key().unwrap(),
+                "owner_signature": owner_signature,
+            })))
+            .with_status(201)
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        identity
+            .register_owned_device(device_info, &owner_signature)
+            .await
+            .unwrap();
+        register.assert_async().await;
+    }
+
+    /// SDK whose genesis device holds a real SPHINCS+ key, and that key's secret
+    async fn signing_sdk() -> (CoreSDK, Vec<u8>) {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let sdk = CoreSDK::new();
+        let device_info = DeviceInfo::new("replay_device", public_key);
+        let genesis = sdk.create_initial_state(&device_info).unwrap();
+        sdk.initialize_with_genesis(genesis, None).await.unwrap();
+        (sdk, secret_key)
+    }
+
+    async fn execute_signed(sdk: &CoreSDK, secret_key: &[u8], i: u64) -> State {
+        let operation = sdk.generic_operation("replayed", i.to_le_bytes().to_vec()).unwrap();
+        let signed = SignedOperation::sign(operation, secret_key).unwrap();
+        sdk.execute_signed_transition(signed).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_operation_log_replays_to_same_states() {
+        let (sdk, secret_key) = signing_sdk().await;
+
+        let mut executed = Vec::new();
+        for i in 1..=100 {
+            executed.push(execute_signed(&sdk, &secret_key, i).await);
+        }
+
+        let log = sdk.export_operation_log().unwrap();
+        assert_eq!(log.len(), 100);
+
+        // The log survives serialization
+        let decoded: Vec<SignedOperation> =
+            bincode::deserialize(&bincode::serialize(&log).unwrap()).unwrap();
+        assert_eq!(decoded, log);
+
+        let replayed = sdk.replay_from_genesis(&decoded).unwrap();
+        let hashes = |states: &[State]| states.iter().map(|s| s.hash.clone()).collect::<Vec<_>>();
+        assert_eq!(hashes(&replayed), hashes(&executed));
+        assert_eq!(replayed.last().unwrap().state_number, 100);
+        assert_eq!(replayed.last().unwrap().hash, sdk.get_current_state().unwrap().hash);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_operation_diverges_at_its_index() {
+        let (sdk, secret_key) = signing_sdk().await;
+        for i in 1..=5 {
+            execute_signed(&sdk, &secret_key, i).await;
+        }
+
+        let mut log = sdk.export_operation_log().unwrap();
+        match &mut log[3].operation {
+            Operation::Generic { data, .. } => data[0] ^= 1,
+            other => panic!("unexpected operation {:?}", other),
+        }
+
+        match sdk.replay_from_genesis(&log) {
+            Err(DsmError::ReplayDiverged { index, .. }) => assert_eq!(index, 3),
+            other => panic!("expected ReplayDiverged, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_signed_history() {
+        let (sdk, secret_key) = signing_sdk().await;
+        execute_signed(&sdk, &secret_key, 1).await;
+
+        // A signature from another identity is refused
+        let (_, other_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let forged =
+            SignedOperation::sign(sdk.generic_operation("forged", vec![1]).unwrap(), &other_key)
+                .unwrap();
+        assert!(matches!(
+            sdk.execute_signed_transition(forged).await,
+            Err(DsmError::Verification(_))
+        ));
+        assert_eq!(sdk.export_operation_log().unwrap().len(), 1);
+
+        // An unsigned transition leaves a gap the log cannot cover
+        let unsigned = sdk.generic_operation("unsigned", vec![2]).unwrap();
+        sdk.execute_transition(unsigned).await.unwrap();
+        assert!(matches!(sdk.export_operation_log(), Err(DsmError::State(_))));
+    }
+
+    async fn sdk_with_event_bus(bus: Arc<DsmEventBus>) -> CoreSDK {
+        let sdk = CoreSDK::with_event_bus(bus);
+        let device_info = DeviceInfo::new("event_device", vec![1, 2, 3, 4]);
+        let genesis = sdk.create_initial_state(&device_info).unwrap();
+        sdk.initialize_with_genesis(genesis, None).await.unwrap();
+        sdk
+    }
+
+    fn transfer(amount: u64) -> Operation {
+        Operation::Transfer {
+            to_address: "recipient".to_string(),
+            amount: Balance::new(amount),
+            token_id: "ROOT".to_string(),
+            mode: TransactionMode::Bilateral,
+            nonce: vec![1],
+            verification: dsm::types::operations::VerificationType::Standard,
+            pre_commit: None,
+            recipient: "recipient".to_string(),
+            to: "recipient".to_string(),
+            message: "event transfer".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transition_publishes_one_state_event() {
+        let bus = Arc::new(DsmEventBus::new());
+        let mut transitions = bus.subscribe::<StateTransitioned>();
+        let mut balances = bus.subscribe::<TokenBalanceChanged>();
+        let sdk = sdk_with_event_bus(bus).await;
+
+        let op = sdk.generic_operation("event", vec![1]).unwrap();
+        let operation_hash = op.operation_hash();
+        let state = sdk.execute_transition(op).await.unwrap();
+
+        assert_eq!(
+            transitions.try_recv().unwrap(),
+            StateTransitioned {
+                old_state_number: state.state_number - 1,
+                new_state_number: state.state_number,
+                operation_hash,
+            }
+        );
+        assert!(transitions.try_recv().is_err());
+        assert!(balances.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_publishes_balance_event() {
+        let bus = Arc::new(DsmEventBus::new());
+        let mut transitions = bus.subscribe::<StateTransitioned>();
+        let mut balances = bus.subscribe::<TokenBalanceChanged>();
+        let sdk = sdk_with_event_bus(bus).await;
+
+        {
+            let mut state_machine = sdk.state_machine.write();
+            let mut state = state_machine.current_state().cloned().unwrap();
+            state
+                .token_balances
+                .insert("event_device.ROOT".to_string(), Balance::new(500));
+            state_machine.set_state(state);
+        }
+
+        sdk.execute_transition(transfer(120)).await.unwrap();
+
+        assert_eq!(transitions.try_recv().unwrap().new_state_number, 1);
+        assert!(transitions.try_recv().is_err());
+
+        let event = balances.try_recv().unwrap();
+        assert_eq!(event.device_id, "event_device");
+        assert_eq!(event.token_id, "ROOT");
+        assert_eq!(event.old.value(), 500);
+        assert_eq!(event.new.value(), 380);
+        assert!(balances.try_recv().is_err());
+    }
+
+    /// Give the event SDK's device a ROOT balance
+    fn set_root_balance(sdk: &CoreSDK, value: u64) {
+        let mut state_machine = sdk.state_machine.write();
+        let mut state = state_machine.current_state().cloned().unwrap();
+        state
+            .token_balances
+            .insert("event_device.ROOT".to_string(), Balance::new(value));
+        state_machine.set_state(state);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_overdraw_fails() {
+        let sdk = sdk_with_event_bus(Arc::new(DsmEventBus::new())).await;
+        set_root_balance(&sdk, 100);
+
+        let result = sdk.simulate_transition(&transfer(150)).unwrap();
+
+        assert!(!result.success);
+        assert_eq!(
+            result.errors,
+            vec!["Insufficient balance for token ROOT: available 100, requested 150".to_string()]
+        );
+        assert!(result.balance_deltas.is_empty());
+        assert_eq!(result.new_state.state_number, 0);
+    }
+
+    fn mint(amount: u64, authorized_by: &str) -> Operation {
+        Operation::Mint {
+            amount: Balance::new(amount),
+            token_id: "ROOT".to_string(),
+            authorized_by: authorized_by.to_string(),
+            proof_of_authorization: Vec::new(),
+            message: "capped mint".to_string(),
+        }
+    }
+
+    /// SDK initialized for an identity whose genesis caps ROOT at 1,000,
+    /// mintable only by the treasury
+    async fn capped_sdk() -> CoreSDK {
+        let identity_genesis = GenesisState {
+            participants: std::collections::HashSet::from(["treasury".to_string()]),
+            device_id: Some("event_device".to_string()),
+            token_policies: HashMap::from([(
+                "ROOT".to_string(),
+                GenesisTokenPolicy {
+                    max_supply: Some(Balance::new(1_000)),
+                    mintable_by: vec!["treasury".to_string()],
+                    burnable: false,
+                },
+            )]),
+            ..genesis(vec![0xaa; 32])
+        };
+
+        let sdk = CoreSDK::with_event_bus(Arc::new(DsmEventBus::new()));
+        let device_info = DeviceInfo::new("event_device", vec![1, 2, 3, 4]);
+        let genesis = sdk.create_initial_state(&device_info).unwrap();
+        sdk.initialize_with_genesis(genesis, Some(&identity_genesis))
+            .await
+            .unwrap();
+        sdk
+    }
+
+    #[tokio::test]
+    async fn test_mint_up_to_supply_cap() {
+        let sdk = Arc::new(capped_sdk().await);
+        set_root_balance(&sdk, 600);
+
+        let token_sdk = TokenSDK::new(sdk.clone());
+        assert_eq!(token_sdk.get_total_supply("ROOT").unwrap().value(), 600);
+
+        // Reaching the cap exactly is allowed
+        let state = sdk.execute_transition(mint(400, "treasury")).await.unwrap();
+        assert_eq!(state.state_number, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mint_over_supply_cap_is_rejected() {
+        let sdk = capped_sdk().await;
+        set_root_balance(&sdk, 600);
+
+        match sdk.execute_transition(mint(401, "treasury")).await {
+            Err(DsmError::SupplyCapExceeded {
+                token_id,
+                current_supply,
+                requested_amount,
+                max_supply,
+            }) => {
+                assert_eq!(token_id, "ROOT");
+                assert_eq!(current_supply.value(), 600);
+                assert_eq!(requested_amount.value(), 401);
+                assert_eq!(max_supply.value(), 1_000);
+            }
+            other => panic!("expected a supply cap error, got {:?}", other),
+        }
+        assert_eq!(sdk.get_current_state().unwrap().state_number, 0);
+        let simulated = sdk.simulate_transition(&mint(401, "treasury")).unwrap();
+        assert!(!simulated.success);
+    }
+
+    #[tokio::test]
+    async fn test_mint_by_unlisted_minter_is_rejected() {
+        let sdk = capped_sdk().await;
+
+        assert!(matches!(
+            sdk.execute_transition(mint(1, "mallory")).await,
+            Err(DsmError::Unauthorized { .. })
+        ));
+
+        // Tokens without a genesis policy stay unrestricted
+        let uncapped = Operation::Mint {
+            amount: Balance::new(5_000),
+            token_id: "OTHER".to_string(),
+            authorized_by: "mallory".to_string(),
+            proof_of_authorization: Vec::new(),
+            message: "uncapped mint".to_string(),
+        };
+        assert!(sdk.execute_transition(uncapped).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_transfer_reports_balance_deltas() {
+        let sdk = sdk_with_event_bus(Arc::new(DsmEventBus::new())).await;
+        set_root_balance(&sdk, 500);
+
+        let result = sdk.simulate_transition(&transfer(120)).unwrap();
+
+        assert!(result.success, "{:?}", result.errors);
+        assert!(result.errors.is_empty());
+        assert_eq!(result.new_state.state_number, 1);
+        assert_eq!(
+            result.balance_deltas,
+            HashMap::from([
+                ("event_device:ROOT".to_string(), -120),
+                ("recipient:ROOT".to_string(), 120),
+            ])
+        );
+    }
+
+    /// Charge transfers out of the event SDK's state 2 ROOT plus 1%
+    fn set_fee_policy(sdk: &CoreSDK) {
+        let mut state_machine = sdk.state_machine.write();
+        let mut state = state_machine.current_state().cloned().unwrap();
+        state.fee_policy = Some(FeePolicy {
+            base_fee: Balance::new(2),
+            fee_rate: Ratio::new(1, 100),
+            fee_recipient: "storage_node".to_string(),
+        });
+        state_machine.set_state(state);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_of_exact_balance_fails_on_fee() {
+        let sdk = Arc::new(sdk_with_event_bus(Arc::new(DsmEventBus::new())).await);
+        set_root_balance(&sdk, 100);
+        set_fee_policy(&sdk);
+
+        let token_sdk = TokenSDK::new(sdk.clone());
+        assert_eq!(token_sdk.estimate_fee("ROOT", Balance::new(100)).unwrap().value(), 3);
+
+        let result = sdk.simulate_transition(&transfer(100)).unwrap();
+        assert!(!result.success);
+
+        let err = sdk.execute_transition(transfer(100)).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                DsmError::InsufficientBalance {
+                    available: 100,
+                    requested: 103,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+        assert_eq!(sdk.get_current_state().unwrap().state_number, 0);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_fee_credited_to_fee_recipient() {
+        let sdk = Arc::new(sdk_with_event_bus(Arc::new(DsmEventBus::new())).await);
+        set_root_balance(&sdk, 500);
+        set_fee_policy(&sdk);
+
+        let token_sdk = TokenSDK::new(sdk.clone());
+        assert_eq!(token_sdk.estimate_fee("ROOT", Balance::new(120)).unwrap().value(), 3);
+
+        let result = sdk.simulate_transition(&transfer(120)).unwrap();
+        assert!(result.success, "{:?}", result.errors);
+        assert_eq!(
+            result.balance_deltas,
+            HashMap::from([
+                ("event_device:ROOT".to_string(), -123),
+                ("recipient:ROOT".to_string(), 120),
+                ("storage_node:ROOT".to_string(), 3),
+            ])
+        );
+
+        let state = sdk.execute_transition(transfer(120)).await.unwrap();
+        assert_eq!(state.hash, result.new_state.hash);
+        assert_eq!(state.token_balances["event_device.ROOT"].value(), 497);
+        assert_eq!(state.token_balances["storage_node.ROOT"].value(), 3);
+        assert!(state.fee_policy.is_some());
+
+        // The policy carries over, and fees accumulate with the recipient
+        let state = sdk.execute_transition(transfer(120)).await.unwrap();
+        assert_eq!(state.token_balances["event_device.ROOT"].value(), 494);
+        assert_eq!(state.token_balances["storage_node.ROOT"].value(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_no_fee_without_fee_policy() {
+        let sdk = Arc::new(sdk_with_event_bus(Arc::new(DsmEventBus::new())).await);
+        set_root_balance(&sdk, 100);
+
+        let token_sdk = TokenSDK::new(sdk.clone());
+        assert_eq!(token_sdk.estimate_fee("ROOT", Balance::new(100)).unwrap().value(), 0);
+
+        let result = sdk.simulate_transition(&transfer(100)).unwrap();
+        assert!(result.success, "{:?}", result.errors);
+        assert_eq!(result.balance_deltas.len(), 2);
+
+        let state = sdk.execute_transition(transfer(100)).await.unwrap();
+        assert_eq!(state.token_balances.len(), 1);
+        assert_eq!(state.token_balances["event_device.ROOT"].value(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_simulation_leaves_state_unchanged() {
+        let bus = Arc::new(DsmEventBus::new());
+        let mut transitions = bus.subscribe::<StateTransitioned>();
+        let sdk = sdk_with_event_bus(bus).await;
+        set_root_balance(&sdk, 500);
+        let before = sdk.begin_snapshot().unwrap();
+
+        let op = transfer(120);
+        let result = sdk.simulate_transition(&op).unwrap();
+        assert!(result.success);
+
+        let after = sdk.begin_snapshot().unwrap();
+        assert_eq!(after.state().hash, before.state().hash);
+        assert_eq!(after.balances(), before.balances());
+        assert_eq!(sdk.get_current_state().unwrap().state_number, 0);
+        assert!(transitions.try_recv().is_err());
+
+        // The simulated state is the one execution then produces
+        let executed = sdk.execute_transition(op).await.unwrap();
+        assert_eq!(executed.hash, result.new_state.hash);
+    }
+
+    #[tokio::test]
+    async fn test_offline_transitions_replay_in_order() {
+        use base64::Engine;
+        use dsm_storage_node::client::SerializationFormat;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        let unavailable = server
+            .mock("POST", "/data")
+            .with_status(503)
+            .create_async()
+            .await;
+
+        let queue = Arc::new(OfflineQueue::new());
+        let sdk = CoreSDK::with_offline_queue(queue.clone());
+        let device_info = DeviceInfo::new("offline_device", vec![1, 2, 3, 4]);
+        let genesis = sdk.create_initial_state(&device_info).unwrap();
+        sdk.initialize_with_genesis(genesis, None).await.unwrap();
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+        sdk.register_storage_client(Arc::new(client));
+
+        // Only the first transition reaches out; the rest queue behind it
+        let mut executed = Vec::new();
+        for i in 1..=10 {
+            executed.push(sdk.execute_transition(numbered_operation(&sdk, i)).await.unwrap());
+        }
+        unavailable.assert_async().await;
+        assert_eq!(queue.len(), 10);
+        unavailable.remove_async().await;
+
+        let stored: Arc<Mutex<Vec<(String, State)>>> = Arc::default();
+        let recorded = stored.clone();
+        let store = server
+            .mock("POST", "/data")
+            .with_body_from_request(move |request| {
+                let payload: HashMap<String, String> =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(&payload["data"])
+                    .unwrap();
+                let state: State =
+                    SerializationFormat::default().deserialize_versioned(&data).unwrap();
+                recorded.lock().unwrap().push((payload["key"].clone(), state));
+                b"{}".to_vec()
+            })
+            .expect(10)
+            .create_async()
+            .await;
+
+        let result = sdk.drain_offline_queue().await.unwrap();
+        store.assert_async().await;
+        assert_eq!(result.replayed, 10);
+        assert!(result.is_complete());
+        assert!(queue.is_empty());
+
+        // The storage node received every state in execution order and ends
+        // at the SDK's current state
+        let stored = stored.lock().unwrap();
+        let keys: Vec<&str> = stored.iter().map(|(key, _)| key.as_str()).collect();
+        let expected: Vec<String> = executed
+            .iter()
+            .map(|state| format!("state:{}", hex::encode(state.hash().unwrap())))
+            .collect();
+        assert_eq!(keys, expected);
+        let last = &stored.last().unwrap().1;
+        assert_eq!(last.hash, sdk.get_current_state().unwrap().hash);
+        assert_eq!(last.state_number, 10);
+    }
+
+    #[tokio::test]
+    async fn test_received_inbox_entries_acknowledged_first() {
+        use dsm_storage_node::api::DEFAULT_INBOX_PRIORITY;
+
+        let recipient = genesis(vec![0xaa; 32]);
+        let inbox = format!("/inbox/{}", hex::encode(&recipient.hash));
+        let entry = |id: &str| InboxEntry {
+            id: id.to_string(),
+            sender_genesis_hash: "sender".to_string(),
+            recipient_genesis_hash: hex::encode(&recipient.hash),
+            transaction: vec![1, 2, 3],
+            signature: vec![4],
+            timestamp: 0,
+            expires_at: 0,
+            metadata: HashMap::new(),
+            ack_deadline_secs: 60,
+            priority: DEFAULT_INBOX_PRIORITY,
+            ring_signature: None,
+        };
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        server
+            .mock("GET", inbox.as_str())
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&[entry("first"), entry("second")]).unwrap())
+            .create_async()
+            .await;
+        let acknowledged = server
+            .mock("PUT", format!("{}/first/ack", inbox).as_str())
+            .with_body("{}")
+            .create_async()
+            .await;
+        server
+            .mock("PUT", format!("{}/second/ack", inbox).as_str())
+            .with_status(503)
+            .create_async()
+            .await;
+
+        let bus = Arc::new(DsmEventBus::new());
+        let mut messages = bus.subscribe::<InboxMessageReceived>();
+        let sdk = CoreSDK::with_event_bus(bus);
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+        sdk.register_storage_client(Arc::new(client));
+
+        // The entry the node did not acknowledge is held back for the next call
+        let received = sdk.receive_inbox_entries(&recipient).await.unwrap();
+        acknowledged.assert_async().await;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].id, "first");
+        assert_eq!(
+            messages.try_recv().unwrap(),
+            InboxMessageReceived {
+                entry_id: "first".to_string(),
+                sender_hash: "sender".to_string(),
+            }
+        );
+        assert!(messages.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sdk_without_event_bus_publishes_nothing() {
+        let sdk = initialized_sdk().await;
+        assert!(sdk.event_bus().is_none());
+
+        let op = sdk.generic_operation("quiet", vec![1]).unwrap();
+        assert!(sdk.execute_transition(op).await.is_ok());
+    }
+
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_constructor_logs_components() {
+        let _sdk = CoreSDK::with_offline_queue(Arc::new(OfflineQueue::new()));
+
+        assert!(logs_contain("Creating core SDK"));
+        assert!(logs_contain("event_bus=false"));
+        assert!(logs_contain("offline_queue=true"));
     }
 }
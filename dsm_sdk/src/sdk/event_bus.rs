@@ -0,0 +1,248 @@
+//! # Event Bus SDK Module
+//!
+//! This module lets applications react to changes in the SDK without polling.
+//! Components publish typed events on a shared [`DsmEventBus`], and every
+//! subscriber to an event type receives its own copy of each event of that
+//! type through a bounded channel.
+//!
+//! ## Events
+//!
+//! * [`StateTransitioned`]: The chain moved to a new state
+//! * [`TokenBalanceChanged`]: A transfer changed a device's token balance
+//! * [`InboxMessageReceived`]: A message arrived in an identity's inbox
+//! * [`VaultStatusChanged`]: A limbo vault changed state
+//!
+//! `CoreSDK` publishes state transitions and balance changes when it is
+//! created with [`CoreSDK::with_event_bus`](super::core_sdk::CoreSDK::with_event_bus),
+//! as well as the inbox messages it receives. Vault events are published by
+//! the components that observe them.
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! use dsm_sdk::core_sdk::CoreSDK;
+//! use dsm_sdk::event_bus::{DsmEventBus, StateTransitioned};
+//! use std::sync::Arc;
+//!
+//! async fn example() {
+//!     let bus = Arc::new(DsmEventBus::new());
+//!     let mut transitions = bus.subscribe::<StateTransitioned>();
+//!     let sdk = CoreSDK::with_event_bus(bus);
+//!
+//!     // ... execute transitions on the SDK ...
+//!
+//!     while let Some(event) = transitions.recv().await {
+//!         println!("Now at state {}", event.new_state_number);
+//!     }
+//! }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use dsm::types::token_types::Balance;
+use dsm::vault::VaultState;
+use parking_lot::RwLock;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Number of undelivered events a subscriber can fall behind by
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// Marker trait for events that can be published on a [`DsmEventBus`]
+pub trait DsmEvent: Clone + Send + Sync + 'static {}
+
+/// The chain moved from one state to the next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateTransitioned {
+    /// State number before the transition
+    pub old_state_number: u64,
+
+    /// State number after the transition
+    pub new_state_number: u64,
+
+    /// Hash of the executed operation
+    pub operation_hash: [u8; 32],
+}
+
+impl DsmEvent for StateTransitioned {}
+
+/// A transfer changed a device's balance of a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBalanceChanged {
+    /// Device whose balance changed
+    pub device_id: String,
+
+    /// Token whose balance changed
+    pub token_id: String,
+
+    /// Balance before the change
+    pub old: Balance,
+
+    /// Balance after the change
+    pub new: Balance,
+}
+
+impl DsmEvent for TokenBalanceChanged {}
+
+/// A message arrived in an identity's inbox
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboxMessageReceived {
+    /// ID of the inbox entry
+    pub entry_id: String,
+
+    /// Genesis hash of the sender
+    pub sender_hash: String,
+}
+
+impl DsmEvent for InboxMessageReceived {}
+
+/// A limbo vault changed state
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultStatusChanged {
+    /// ID of the vault
+    pub vault_id: String,
+
+    /// State the vault is now in
+    pub new_state: VaultState,
+}
+
+impl DsmEvent for VaultStatusChanged {}
+
+/// Publish-subscribe bus for SDK events
+///
+/// Publishing never blocks: a subscriber whose channel is full misses the
+/// event, and subscribers whose receiver was dropped are removed.
+pub struct DsmEventBus {
+    /// Senders of each event type's subscribers, as `mpsc::Sender<E>`
+    subscribers: RwLock<HashMap<TypeId, Vec<Box<dyn Any + Send + Sync>>>>,
+
+    /// Channel capacity of new subscriptions
+    capacity: usize,
+}
+
+impl DsmEventBus {
+    /// Create an event bus with the default channel capacity
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_CAPACITY)
+    }
+
+    /// Create an event bus whose subscribers can fall `capacity` events behind
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            subscribers: RwLock::new(HashMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Subscribe to events of type `E`
+    ///
+    /// The receiver gets every `E` published after this call.
+    pub fn subscribe<E: DsmEvent>(&self) -> mpsc::Receiver<E> {
+        let (sender, receiver) = mpsc::channel::<E>(self.capacity);
+        self.subscribers
+            .write()
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(Box::new(sender));
+        receiver
+    }
+
+    /// Publish an event to every subscriber of its type
+    pub fn publish<E: DsmEvent>(&self, event: E) {
+        let mut subscribers = self.subscribers.write();
+        let Some(senders) = subscribers.get_mut(&TypeId::of::<E>()) else {
+            return;
+        };
+
+        senders.retain(|sender| match sender.downcast_ref::<mpsc::Sender<E>>() {
+            Some(sender) => match sender.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!(
+                        "Event subscriber is full; dropping {}",
+                        std::any::type_name::<E>()
+                    );
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            },
+            None => false,
+        });
+    }
+
+    /// Number of live subscribers to events of type `E`
+    pub fn subscriber_count<E: DsmEvent>(&self) -> usize {
+        self.subscribers
+            .read()
+            .get(&TypeId::of::<E>())
+            .map(|senders| {
+                senders
+                    .iter()
+                    .filter_map(|sender| sender.downcast_ref::<mpsc::Sender<E>>())
+                    .filter(|sender| !sender.is_closed())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+}
+
+impl Default for DsmEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transitioned(new_state_number: u64) -> StateTransitioned {
+        StateTransitioned {
+            old_state_number: new_state_number - 1,
+            new_state_number,
+            operation_hash: [0; 32],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_only_their_event_type() {
+        let bus = DsmEventBus::new();
+        let mut first = bus.subscribe::<StateTransitioned>();
+        let mut second = bus.subscribe::<StateTransitioned>();
+        let mut inbox = bus.subscribe::<InboxMessageReceived>();
+
+        bus.publish(transitioned(1));
+
+        assert_eq!(first.recv().await, Some(transitioned(1)));
+        assert_eq!(second.recv().await, Some(transitioned(1)));
+        assert!(inbox.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_dropped_subscribers_are_removed() {
+        let bus = DsmEventBus::new();
+        let kept = bus.subscribe::<StateTransitioned>();
+        drop(bus.subscribe::<StateTransitioned>());
+        assert_eq!(bus.subscriber_count::<StateTransitioned>(), 1);
+
+        bus.publish(transitioned(1));
+        assert_eq!(
+            bus.subscribers.read()[&TypeId::of::<StateTransitioned>()].len(),
+            1
+        );
+        drop(kept);
+    }
+
+    #[test]
+    fn test_full_subscriber_does_not_block() {
+        let bus = DsmEventBus::with_capacity(1);
+        let mut receiver = bus.subscribe::<StateTransitioned>();
+
+        bus.publish(transitioned(1));
+        bus.publish(transitioned(2));
+
+        assert_eq!(receiver.try_recv().ok(), Some(transitioned(1)));
+        assert!(receiver.try_recv().is_err());
+    }
+}
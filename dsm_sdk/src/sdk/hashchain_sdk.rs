@@ -209,6 +209,9 @@ pub fn initialize_with_genesis(&self, genesis_state: State) -> Result<(), DsmErr
     /// }
     /// ```
     pub fn add_state(&self, state: State) -> Result<(), DsmError> {
+        let state_number = state.state_number;
+        let leaf = Self::merkle_leaf(&state)?;
+
         // Add the state to the hash chain with verification
         {
             let mut hash_chain = self.hash_chain.write();
@@ -229,11 +232,88 @@ pub fn add_state(&self, state: State) -> Result<(), DsmError> {
         }
 
         // Update the Merkle tree
-        self.regenerate_merkle_tree()?;
+        self.insert_merkle_leaf(state_number, leaf)?;
+
+        Ok(())
+    }
+
+    /// Re-initialize the hash chain from a checkpoint state
+    ///
+    /// Discards the current chain and continues from the checkpoint without
+    /// replaying the history that led up to it. Proofs are only available for
+    /// the checkpoint and the states added after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint` - A checkpoint state (`is_checkpoint` must be set)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the chain was re-initialized
+    /// * `Err(DsmError)` - If the state is not a checkpoint
+    pub fn initialize_from_checkpoint(&self, checkpoint: State) -> Result<(), DsmError> {
+        if !checkpoint.is_checkpoint {
+            return Err(DsmError::validation(
+                "Cannot restore hash chain from a non-checkpoint state",
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        let leaf = Self::merkle_leaf(&checkpoint)?;
+
+        {
+            let mut restored = HashChain::new();
+            restored.add_state(checkpoint.clone())?;
+            *self.hash_chain.write() = restored;
+        }
+
+        {
+            let mut state_machine = self.state_machine.write();
+            state_machine.set_state(checkpoint.clone());
+        }
+
+        let mut merkle_tree = SparseMerkleTree::new(32);
+        merkle_tree.leaves.insert(checkpoint.state_number, leaf);
+        *self.merkle_tree.write() = Some(merkle_tree);
 
         Ok(())
     }
 
+    /// Hashes of the states numbered below `state_number`, oldest first
+    pub fn state_hashes_before(&self, state_number: u64) -> Result<Vec<Vec<u8>>, DsmError> {
+        let hash_chain = self.hash_chain.read();
+        hash_chain
+            .states_before(state_number)
+            .into_iter()
+            .map(|state| state.hash())
+            .collect()
+    }
+
+    /// Remove every state numbered below `state_number` from the chain
+    ///
+    /// The current state is never removed. Proofs are no longer available for
+    /// the removed states, and the chain should only be pruned once a
+    /// checkpoint covers the oldest state that is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_number` - Number of the oldest state to keep
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<State>` - The removed states, in ascending state number order
+    pub fn prune_before(&self, state_number: u64) -> Vec<State> {
+        let pruned = self.hash_chain.write().prune_before(state_number);
+
+        if let Some(oldest_kept) = pruned.last().map(|state| state.state_number + 1) {
+            if let Some(tree) = self.merkle_tree.write().as_mut() {
+                tree.leaves.retain(|&number, _| number >= oldest_kept);
+            }
+        }
+
+        pruned
+    }
+
     /// Verify the integrity of the entire hash chain
     ///
     /// Performs a comprehensive verification of the hash chain as described in
@@ -466,6 +546,25 @@ pub fn verify_state_proof(
         Ok(merkle_proof.verify())
     }
 
+    /// Merkle leaf value for a state
+    fn merkle_leaf(state: &State) -> Result<Hash, DsmError> {
+        let state_data = bincode::serialize(state)?;
+        Ok(blake3::hash(&state_data))
+    }
+
+    /// Add a single leaf to the Merkle tree, building the full tree if there is none yet
+    fn insert_merkle_leaf(&self, state_number: u64, leaf: Hash) -> Result<(), DsmError> {
+        {
+            let mut merkle_tree = self.merkle_tree.write();
+            if let Some(tree) = merkle_tree.as_mut() {
+                tree.leaves.insert(state_number, leaf);
+                return Ok(());
+            }
+        }
+
+        self.regenerate_merkle_tree()
+    }
+
     /// Regenerate the Merkle tree from the current hash chain
     ///
     /// This internal function rebuilds the Merkle tree when the hash chain changes.
@@ -487,9 +586,7 @@ fn regenerate_merkle_tree(&self) -> Result<(), DsmError> {
         let mut leaf_values = Vec::new();
 
         for state in states {
-            let state_data = bincode::serialize(&state)?;
-            let state_hash = blake3::hash(&state_data);
-            leaf_values.push((state.state_number, state_hash));
+            leaf_values.push((state.state_number, Self::merkle_leaf(&state)?));
         }
 
         // Create a new merkle tree from the leaf values
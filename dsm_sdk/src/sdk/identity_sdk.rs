@@ -40,12 +40,16 @@
 //! ```
 
 use super::hashchain_sdk::HashChainSDK;
+use dsm::core::identity::GenesisState;
 use dsm::types::error::DsmError;
 use dsm::types::operations::{Operation, TransactionMode};
 use dsm::types::state_types::{DeviceInfo, State};
 use dsm::crypto::signatures::SignatureKeyPair;
+use dsm_storage_node::api::{identity_head_message, DeviceRegistration, IdentityHead};
+use dsm_storage_node::client::StorageNodeClient;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, RwLock};
 
 /// Type alias for state hash values used throughout the identity system
@@ -111,13 +115,33 @@ pub struct ExtendedRelationshipContext {
     pub last_interaction: u64,
 }
 
+/// Outcome of synchronizing a device's state chain with the storage node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncResult {
+    /// Number of states fetched from the storage node and applied locally
+    pub transitions_applied: u64,
+
+    /// State number of the local chain head after the sync
+    pub final_state_number: u64,
+}
+
+/// Outcome of pinning a genesis state to several storage nodes
+#[derive(Debug)]
+pub struct PinningReport {
+    /// Base URLs of the storage nodes confirmed to hold the genesis state
+    pub successful_pins: Vec<String>,
+
+    /// Base URLs of the storage nodes that could not be pinned, with the reason
+    pub failed_pins: Vec<(String, DsmError)>,
+}
+
 /// Identity management SDK for the DSM system
 ///
 /// This SDK provides a comprehensive interface for managing cryptographic
 /// identities in the DSM system, including device management, relationship
 /// tracking, state transitions, and recovery mechanisms as defined in 
 /// sections 4 and 7 of the DSM whitepaper.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct IdentitySDK {
     /// Identifier for this identity
     pub identity_id: String,
@@ -133,6 +157,22 @@ pub struct IdentitySDK {
 
     /// Cryptographic key pair for this identity
     signing_keypair: Arc<RwLock<Option<SignatureKeyPair>>>,
+
+    /// Storage node client used to register the identity's devices
+    storage_client: Arc<RwLock<Option<Arc<StorageNodeClient>>>>,
+}
+
+impl fmt::Debug for IdentitySDK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentitySDK")
+            .field("identity_id", &self.identity_id)
+            .field("device_genesis_states", &self.device_genesis_states)
+            .field("hash_chain_sdk", &self.hash_chain_sdk)
+            .field("relationship_contexts", &self.relationship_contexts)
+            .field("signing_keypair", &self.signing_keypair)
+            .field("storage_client", &"<RwLock>")
+            .finish()
+    }
 }
 
 impl IdentitySDK {
@@ -167,6 +207,7 @@ pub fn new(identity_id: String, hash_chain_sdk: Arc<HashChainSDK>) -> Self {
             hash_chain_sdk,
             relationship_contexts: Arc::new(RwLock::new(HashMap::new())),
             signing_keypair: Arc::new(RwLock::new(None)),
+            storage_client: Arc::new(RwLock::new(None)),
         };
 
         // Initialize cryptographic keys
@@ -852,4 +893,434 @@ pub fn recovery_operation(
             state_hash: vec![0u8; 32], // Would be derived from current state
         })
     }
+
+    /// Register the storage node client used to register owned devices
+    pub fn register_storage_client(&self, client: Arc<StorageNodeClient>) {
+        *self.storage_client.write().unwrap() = Some(client);
+    }
+
+    /// Hex-encoded hash of this identity's genesis state
+    ///
+    /// The storage node keys an identity's devices and chain head by it.
+    pub fn genesis_hash(&self) -> Result<String, DsmError> {
+        Ok(hex::encode(self.hash_chain_sdk.get_state_by_number(0)?.hash()?))
+    }
+
+    /// Register a device as sharing this identity's state chain
+    ///
+    /// Posts the device to `/identity/<genesis_hash>/devices` on the registered
+    /// storage node. The storage node pins the public key of whoever registers
+    /// the identity's first device, so every device must be registered with
+    /// the owner's key.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_info` - The device to register
+    /// * `owner_signature` - Owner's signature over `device_registration_message`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the storage node accepted the registration
+    /// * `Err(DsmError::Storage)` - If no storage client is registered or the
+    ///   storage node rejected the registration
+    pub async fn register_owned_device(
+        &self,
+        device_info: DeviceInfo,
+        owner_signature: &[u8],
+    ) -> Result<(), DsmError> {
+        let client = self.storage_client.read().unwrap().clone().ok_or_else(|| {
+            DsmError::storage(
+                "No storage client registered; cannot register devices",
+                None::<std::convert::Infallible>,
+            )
+        })?;
+
+        let device_id = device_info.device_id.clone();
+        let registration = DeviceRegistration {
+            device_info,
+            owner_public_key: self.get_public_key()?,
+            owner_signature: owner_signature.to_vec(),
+        };
+
+        client
+            .register_device(&self.genesis_hash()?, &registration)
+            .await
+            .map_err(|e| {
+                DsmError::storage(format!("Failed to register device {}", device_id), Some(e))
+            })
+    }
+
+    /// Synchronize the local state chain with the identity's other devices
+    ///
+    /// Compares the local chain head with the head published on the storage
+    /// node. When the storage node is ahead, the missing states are fetched,
+    /// verified to extend the local chain and applied. When the local chain is
+    /// ahead, the states the storage node lacks are stored and the local head
+    /// is published.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_client` - Client for the storage node shared by the devices
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SyncResult)` - How many states were applied and the resulting head
+    /// * `Err(DsmError::StateConflict)` - If the local chain has diverged from
+    ///   the one on the storage node
+    /// * `Err(DsmError::Verification)` - If a fetched state fails verification
+    pub async fn sync_device_state(
+        &self,
+        storage_client: &StorageNodeClient,
+    ) -> Result<SyncResult, DsmError> {
+        let genesis_hash = self.genesis_hash()?;
+        let local_head = self
+            .hash_chain_sdk
+            .current_state()
+            .ok_or_else(|| DsmError::state("Cannot sync an identity without a state chain"))?;
+
+        let remote_head = storage_client
+            .fetch_identity_head(&genesis_hash)
+            .await
+            .map_err(|e| DsmError::storage("Failed to fetch identity head", Some(e)))?;
+
+        let transitions_applied = match remote_head {
+            Some(remote) if remote.state_number > local_head.state_number => {
+                self.pull_states(storage_client, &local_head, &remote).await?
+            }
+            Some(remote) if remote.state_number == local_head.state_number => {
+                if remote.state_hash != hex::encode(local_head.hash()?) {
+                    return Err(DsmError::StateConflict {
+                        local_tip: local_head.state_number,
+                        remote_tip: remote.state_number,
+                    });
+                }
+                0
+            }
+            remote => {
+                self.push_states(
+                    storage_client,
+                    &genesis_hash,
+                    &local_head,
+                    remote.as_ref(),
+                )
+                .await?;
+                0
+            }
+        };
+
+        let final_state_number = self
+            .hash_chain_sdk
+            .current_state()
+            .map_or(local_head.state_number, |state| state.state_number);
+
+        Ok(SyncResult {
+            transitions_applied,
+            final_state_number,
+        })
+    }
+
+    /// Pin a genesis state to several storage nodes
+    ///
+    /// Uploads the genesis state to every node, then confirms each copy by
+    /// fetching it back from the node and comparing its hash, so the identity
+    /// survives the loss of any single node.
+    ///
+    /// # Arguments
+    ///
+    /// * `genesis` - The genesis state to pin
+    /// * `clients` - Clients for the storage nodes to pin it to
+    /// * `min_copies` - Number of nodes that must confirm the genesis state
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PinningReport)` - Which nodes hold the genesis state and which failed
+    /// * `Err(DsmError::Storage)` - If fewer than `min_copies` nodes confirmed it
+    pub async fn pin_genesis(
+        &self,
+        genesis: &GenesisState,
+        clients: &[StorageNodeClient],
+        min_copies: usize,
+    ) -> Result<PinningReport, DsmError> {
+        let results =
+            futures::future::join_all(clients.iter().map(|client| pin_to_node(genesis, client)))
+                .await;
+
+        let mut report = PinningReport {
+            successful_pins: Vec::new(),
+            failed_pins: Vec::new(),
+        };
+        for (client, result) in clients.iter().zip(results) {
+            let node = client.base_url().to_string();
+            match result {
+                Ok(()) => report.successful_pins.push(node),
+                Err(e) => report.failed_pins.push((node, e)),
+            }
+        }
+
+        if report.successful_pins.len() < min_copies {
+            let failed: Vec<&str> =
+                report.failed_pins.iter().map(|(node, _)| node.as_str()).collect();
+            return Err(DsmError::storage(
+                format!(
+                    "Genesis state {} pinned to {} storage nodes, {} required (failed: {})",
+                    hex::encode(&genesis.hash),
+                    report.successful_pins.len(),
+                    min_copies,
+                    failed.join(", ")
+                ),
+                None::<std::convert::Infallible>,
+            ));
+        }
+
+        Ok(report)
+    }
+
+    /// Check which storage nodes still hold a pinned genesis state
+    ///
+    /// Nodes that cannot be reached count as not holding it.
+    ///
+    /// # Arguments
+    ///
+    /// * `genesis_hash` - Hash of the pinned genesis state
+    /// * `clients` - Clients for the storage nodes to check
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<bool>)` - Whether each node, in order, holds the genesis state
+    pub async fn verify_pinning(
+        &self,
+        genesis_hash: &[u8],
+        clients: &[StorageNodeClient],
+    ) -> Result<Vec<bool>, DsmError> {
+        let checks = clients.iter().map(|client| async move {
+            matches!(
+                client.fetch_remote_genesis_state(genesis_hash).await,
+                Ok(Some(genesis)) if genesis.hash == genesis_hash
+            )
+        });
+
+        Ok(futures::future::join_all(checks).await)
+    }
+
+    /// Fetch the states between the local head and a later remote head and apply them
+    ///
+    /// States are fetched backwards from the remote head by following
+    /// `prev_state_hash`, so each one is checked against the hash that led to it.
+    async fn pull_states(
+        &self,
+        storage_client: &StorageNodeClient,
+        local_head: &State,
+        remote: &IdentityHead,
+    ) -> Result<u64, DsmError> {
+        let mut expected_hash = hex::decode(&remote.state_hash).map_err(|e| {
+            DsmError::serialization("Identity head hash is not hex-encoded", Some(e))
+        })?;
+        let mut missing = Vec::new();
+
+        for state_number in (local_head.state_number + 1..=remote.state_number).rev() {
+            let state = storage_client
+                .fetch_state(&expected_hash)
+                .await
+                .map_err(|e| {
+                    DsmError::storage(format!("Failed to fetch state {}", state_number), Some(e))
+                })?
+                .ok_or_else(|| DsmError::not_found("State", Some(hex::encode(&expected_hash))))?;
+
+            if state.state_number != state_number
+                || state.hash()? != expected_hash
+                || state.compute_hash()? != expected_hash
+            {
+                return Err(DsmError::verification(format!(
+                    "State {} fetched from the storage node failed verification",
+                    state_number
+                )));
+            }
+
+            expected_hash = state.prev_state_hash.clone();
+            missing.push(state);
+        }
+
+        // The oldest missing state must build on the local head
+        if expected_hash != local_head.hash()? {
+            return Err(DsmError::StateConflict {
+                local_tip: local_head.state_number,
+                remote_tip: remote.state_number,
+            });
+        }
+
+        let applied = missing.len() as u64;
+        for state in missing.into_iter().rev() {
+            self.hash_chain_sdk.add_state(state)?;
+        }
+
+        Ok(applied)
+    }
+
+    /// Store the states the storage node lacks and publish the local head
+    ///
+    /// The head is signed with this identity's key, which the storage node
+    /// checks against the identity's owner and registered devices.
+    async fn push_states(
+        &self,
+        storage_client: &StorageNodeClient,
+        genesis_hash: &str,
+        local_head: &State,
+        remote: Option<&IdentityHead>,
+    ) -> Result<(), DsmError> {
+        let first_missing = match remote {
+            Some(remote) => {
+                // The published head must be one of our own states
+                let ancestor = self.hash_chain_sdk.get_state_by_number(remote.state_number)?;
+                if remote.state_hash != hex::encode(ancestor.hash()?) {
+                    return Err(DsmError::StateConflict {
+                        local_tip: local_head.state_number,
+                        remote_tip: remote.state_number,
+                    });
+                }
+                remote.state_number + 1
+            }
+            None => 0,
+        };
+
+        for state_number in first_missing..=local_head.state_number {
+            let state = self.hash_chain_sdk.get_state_by_number(state_number)?;
+            storage_client.store_state(&state).await.map_err(|e| {
+                DsmError::storage(format!("Failed to store state {}", state_number), Some(e))
+            })?;
+        }
+
+        let state_hash = hex::encode(local_head.hash()?);
+        let device_id = local_head.device_info.device_id.clone();
+        let signature = self.sign_data(&identity_head_message(
+            genesis_hash,
+            local_head.state_number,
+            &state_hash,
+            &device_id,
+        ))?;
+        let head = IdentityHead {
+            state_number: local_head.state_number,
+            state_hash,
+            device_id,
+            signature,
+        };
+        storage_client
+            .publish_identity_head(genesis_hash, &head)
+            .await
+            .map_err(|e| DsmError::storage("Failed to publish identity head", Some(e)))
+    }
+}
+
+/// Store a genesis state on one storage node and confirm the node returns it
+async fn pin_to_node(genesis: &GenesisState, client: &StorageNodeClient) -> Result<(), DsmError> {
+    client
+        .store_genesis_state(genesis)
+        .await
+        .map_err(|e| DsmError::storage("Failed to store genesis state", Some(e)))?;
+
+    let stored = client
+        .fetch_remote_genesis_state(&genesis.hash)
+        .await
+        .map_err(|e| DsmError::storage("Failed to fetch pinned genesis state", Some(e)))?
+        .ok_or_else(|| DsmError::not_found("Genesis state", Some(hex::encode(&genesis.hash))))?;
+
+    if stored.hash != genesis.hash {
+        return Err(DsmError::verification(format!(
+            "Storage node returned a different genesis state for {}",
+            hex::encode(&genesis.hash)
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use dsm::core::identity::create_genesis_state;
+    use dsm_storage_node::client::StorageNodeClientConfig;
+    use mockito::Matcher;
+    use std::sync::Mutex;
+
+    /// Serve a storage node at `server` that keeps the objects posted to it
+    async fn pinning_node(server: &mut mockito::ServerGuard) {
+        let objects: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+
+        let stored = objects.clone();
+        server
+            .mock("POST", "/data")
+            .with_body_from_request(move |request| {
+                let payload: HashMap<String, String> =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(&payload["data"])
+                    .unwrap();
+                stored.lock().unwrap().insert(payload["key"].clone(), data);
+                b"{}".to_vec()
+            })
+            .create_async()
+            .await;
+
+        server
+            .mock("GET", Matcher::Regex(r"^/data/genesis:[0-9a-f]+$".to_string()))
+            .with_body_from_request(move |request| {
+                let key = request.path().trim_start_matches("/data/");
+                objects.lock().unwrap().get(key).cloned().unwrap_or_default()
+            })
+            .create_async()
+            .await;
+    }
+
+    fn client(base_url: String, timeout_seconds: u64) -> StorageNodeClient {
+        StorageNodeClient::new(StorageNodeClientConfig {
+            base_url,
+            timeout_seconds,
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pin_genesis_tolerates_unreachable_node() {
+        let mut first = mockito::Server::new_async().await;
+        let mut second = mockito::Server::new_async().await;
+        pinning_node(&mut first).await;
+        pinning_node(&mut second).await;
+
+        // Accepts connections but never answers, so requests time out
+        let stalled = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let clients = vec![
+            client(first.url(), 30),
+            client(second.url(), 30),
+            client(format!("http://{}", stalled.local_addr().unwrap()), 1),
+        ];
+
+        let sdk = IdentitySDK::new("pinning".into(), Arc::new(HashChainSDK::new()));
+        let genesis = create_genesis_state(1, ["alice".to_string()]).unwrap();
+
+        let report = sdk.pin_genesis(&genesis, &clients, 2).await.unwrap();
+        assert_eq!(
+            report.successful_pins,
+            vec![clients[0].base_url().to_string(), clients[1].base_url().to_string()]
+        );
+        assert_eq!(report.failed_pins.len(), 1);
+        assert_eq!(report.failed_pins[0].0, clients[2].base_url().to_string());
+
+        assert!(matches!(
+            sdk.pin_genesis(&genesis, &clients, 3).await,
+            Err(DsmError::Storage { .. })
+        ));
+
+        assert_eq!(
+            sdk.verify_pinning(&genesis.hash, &clients).await.unwrap(),
+            vec![true, true, false]
+        );
+    }
 }
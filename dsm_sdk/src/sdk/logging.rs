@@ -0,0 +1,60 @@
+//! # Logging SDK Module
+//!
+//! This module installs the global `tracing` subscriber for applications
+//! built on the SDK. The SDK and the crates below it log with structured
+//! fields, e.g. `warn!(state_number = 7, error = %e, "Failed to store state")`,
+//! so the output format only decides how those fields are rendered:
+//!
+//! * [`init_json_logging`]: One JSON object per event, with the fields as
+//!   keys, for log pipelines in production
+//! * [`init_pretty_logging`]: Multi-line human-readable output for development
+//!
+//! Only one subscriber can be installed per process; a second call fails.
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! use dsm_sdk::logging::init_json_logging;
+//!
+//! init_json_logging(tracing::Level::INFO).expect("logging already initialized");
+//! ```
+
+use dsm::types::error::DsmError;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, registry};
+
+/// Install a subscriber that writes events at `level` and above as JSON lines
+///
+/// Each line carries the timestamp, level, target, message and fields of one
+/// event, along with the spans it was recorded in.
+///
+/// # Returns
+///
+/// * `Result<(), DsmError>` - An error if a global subscriber is already installed
+pub fn init_json_logging(level: tracing::Level) -> Result<(), DsmError> {
+    registry()
+        .with(LevelFilter::from_level(level))
+        .with(
+            fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true),
+        )
+        .try_init()
+        .map_err(|e| DsmError::internal("Failed to install the JSON log subscriber", Some(e)))
+}
+
+/// Install a subscriber that writes events at `level` and above for humans
+///
+/// # Returns
+///
+/// * `Result<(), DsmError>` - An error if a global subscriber is already installed
+pub fn init_pretty_logging(level: tracing::Level) -> Result<(), DsmError> {
+    registry()
+        .with(LevelFilter::from_level(level))
+        .with(fmt::layer().pretty())
+        .try_init()
+        .map_err(|e| DsmError::internal("Failed to install the log subscriber", Some(e)))
+}
@@ -10,6 +10,7 @@
 //! ### Core Foundational Modules
 //!
 //! * `core_sdk`: Central integration point for all DSM functionality
+//! * `event_bus`: Notifies subscribers of state, balance, inbox and vault changes
 //! * `hashchain_sdk`: Manages state transitions and evolution in the DSM system
 //! * `identity_sdk`: Handles cryptographic identity creation and management
 //! * `token_sdk`: Provides token operations and policy enforcement
@@ -31,13 +32,18 @@
 //!
 //! ### Utilities and Metrics
 //!
+//! * `logging`: Installs JSON or human-readable log output
 //! * `protocol_metrics`: Performance monitoring and system diagnostics
+//! * `simulation`: Deterministic mode and session replay for reproducing bugs
 
 pub use protocol_metrics::ProtocolMetricsManager;
+pub mod logging;
 pub mod protocol_metrics;
+pub mod simulation;
 
 // Core SDK modules - fundamental building blocks
 pub mod core_sdk;
+pub mod event_bus;
 pub mod hashchain_sdk;
 pub mod identity_sdk;
 pub mod token_sdk;
@@ -46,20 +52,25 @@
 pub mod smart_commitment_sdk;
 
 // Transport and communication modules
+#[cfg(not(target_arch = "wasm32"))]
 pub mod bluetooth_transport;
 
 // Application-specific SDK implementations
 pub mod contact_sdk;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod pokemon_bluetooth_sdk;
 pub mod pokemon_sdk;
 pub mod wallet_sdk;
 
 
 // Re-export primary SDK components for easier access
+#[cfg(not(target_arch = "wasm32"))]
 pub use bluetooth_transport::{BluetoothMode, BluetoothTransport};
 pub use core_sdk::CoreSDK; 
+pub use event_bus::DsmEventBus;
 pub use hashchain_sdk::HashChainSDK;
 pub use identity_sdk::IdentitySDK;
+#[cfg(not(target_arch = "wasm32"))]
 pub use pokemon_bluetooth_sdk::PokemonBluetoothSDK;
 pub use smart_commitment_sdk::SmartCommitmentSDK;
 pub use token_sdk::TokenSDK;
@@ -0,0 +1,312 @@
+//! # Simulation SDK Module
+//!
+//! This module makes production failures reproducible. A [`CoreSDK`] created
+//! with [`CoreSDK::deterministic`] takes its time and nonces from a seeded
+//! [`DeterministicContext`] instead of the system clock and the OS random
+//! number generator, and processes every operation on the calling task.
+//!
+//! Every SDK records the operations it is asked to execute, together with
+//! their inputs. [`record_session`] captures that recording, which can be
+//! serialized, shipped from production and fed to [`replay_session`] to
+//! rebuild the same chain locally:
+//!
+//! * [`DeterministicContext`]: Seeded clock and nonce source
+//! * [`SessionRecording`]: The seed and the recorded operations of an SDK
+//! * [`canonical_state_bytes`]: Encoding of a state that is equal for equal states
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! use dsm_sdk::core_sdk::CoreSDK;
+//! use dsm_sdk::simulation::{canonical_state_bytes, record_session, replay_session};
+//! use dsm::types::error::DsmError;
+//!
+//! async fn reproduce(sdk: &CoreSDK) -> Result<(), DsmError> {
+//!     // Captured in production, replayed locally
+//!     let recording = record_session(sdk);
+//!     let replayed = replay_session(&recording)?;
+//!
+//!     assert_eq!(
+//!         canonical_state_bytes(&replayed.get_current_state()?)?,
+//!         canonical_state_bytes(&sdk.get_current_state()?)?
+//!     );
+//!     Ok(())
+//! }
+//! ```
+
+use super::core_sdk::{CoreSDK, SignedOperation};
+use dsm::core::identity::GenesisTokenPolicy;
+use dsm::types::error::DsmError;
+use dsm::types::operations::Operation;
+use dsm::types::state_types::State;
+use parking_lot::Mutex;
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::debug;
+
+/// Simulated time of the first clock reading, 2024-01-01T00:00:00Z
+pub const SIMULATION_EPOCH: u64 = 1_704_067_200;
+
+/// Length of a nonce, matching `dsm::crypto::generate_nonce`
+const NONCE_LEN: usize = 12;
+
+/// Seeded replacement for the system clock and the OS random number generator
+///
+/// The clock starts at [`SIMULATION_EPOCH`] and advances by one second on
+/// every reading, so two contexts with the same seed hand out the same times
+/// and nonces as long as they are asked in the same order.
+pub struct DeterministicContext {
+    /// Seed the context was created with
+    seed: u64,
+
+    /// Source of nonces
+    rng: Mutex<SmallRng>,
+
+    /// Clock readings taken so far
+    ticks: AtomicU64,
+}
+
+impl DeterministicContext {
+    /// Create a context from a seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Mutex::new(SmallRng::seed_from_u64(seed)),
+            ticks: AtomicU64::new(0),
+        }
+    }
+
+    /// Seed the context was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Read the simulated clock, in seconds since the Unix epoch
+    pub fn now(&self) -> u64 {
+        SIMULATION_EPOCH + self.ticks.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Generate a nonce from the seeded random number generator
+    pub fn generate_nonce(&self) -> Vec<u8> {
+        let mut nonce = vec![0u8; NONCE_LEN];
+        self.rng.lock().fill_bytes(&mut nonce);
+        nonce
+    }
+}
+
+/// A call made on a [`CoreSDK`], with the inputs needed to make it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// [`CoreSDK::initialize_with_genesis`], with the identity's token policies
+    Genesis {
+        state: State,
+        #[serde(default)]
+        token_policies: HashMap<String, GenesisTokenPolicy>,
+    },
+
+    /// [`CoreSDK::execute_transition`]
+    Transition { operation: Operation },
+
+    /// [`CoreSDK::execute_signed_transition`]
+    SignedTransition { signed: SignedOperation },
+
+    /// [`CoreSDK::commit_operations`] against a snapshot of a state
+    Commit {
+        snapshot_state_number: u64,
+        operations: Vec<Operation>,
+    },
+}
+
+/// Everything needed to rebuild the chain of a [`CoreSDK`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecording {
+    /// Seed of the recorded SDK, if it was deterministic
+    pub seed: Option<u64>,
+
+    /// Calls made on the SDK, in order, including those that failed
+    pub events: Vec<SessionEvent>,
+}
+
+/// Capture the operations an SDK has been asked to execute and their inputs
+pub fn record_session(sdk: &CoreSDK) -> SessionRecording {
+    SessionRecording {
+        seed: sdk.seed(),
+        events: sdk.session_events(),
+    }
+}
+
+/// Re-run a recorded session on a new deterministic SDK
+///
+/// The SDK is seeded with the recording's seed, or 0 if the recorded SDK
+/// was not deterministic. Calls are made in order and synchronously; a call
+/// that fails again is skipped, as it was when recorded. A commit is only
+/// made if the chain is at the recorded snapshot, since otherwise it failed
+/// as stale.
+///
+/// # Returns
+///
+/// * `Ok(CoreSDK)` - The SDK after all recorded calls
+/// * `Err(DsmError)` - If the recording holds no genesis state
+pub fn replay_session(recording: &SessionRecording) -> Result<CoreSDK, DsmError> {
+    if !recording
+        .events
+        .iter()
+        .any(|event| matches!(event, SessionEvent::Genesis { .. }))
+    {
+        return Err(DsmError::validation(
+            "Session recording holds no genesis state",
+            None::<std::convert::Infallible>,
+        ));
+    }
+
+    let sdk = CoreSDK::deterministic(recording.seed.unwrap_or_default());
+    for (index, event) in recording.events.iter().enumerate() {
+        let replayed = futures::executor::block_on(async {
+            match event.clone() {
+                SessionEvent::Genesis {
+                    state,
+                    token_policies,
+                } => sdk.initialize_with_policies(state, token_policies),
+                SessionEvent::Transition { operation } => {
+                    sdk.execute_transition(operation).await.map(|_| ())
+                }
+                SessionEvent::SignedTransition { signed } => {
+                    sdk.execute_signed_transition(signed).await.map(|_| ())
+                }
+                SessionEvent::Commit {
+                    snapshot_state_number,
+                    operations,
+                } => {
+                    let snapshot = sdk.begin_snapshot()?;
+                    if snapshot.state_number() != snapshot_state_number {
+                        return Err(DsmError::SnapshotStale {
+                            snapshot_at: snapshot_state_number,
+                            current: snapshot.state_number(),
+                        });
+                    }
+                    sdk.commit_operations(snapshot, operations)
+                        .await
+                        .map(|_| ())
+                }
+            }
+        });
+
+        if let Err(e) = replayed {
+            debug!(index, error = %e, "Recorded call failed during replay");
+        }
+    }
+
+    Ok(sdk)
+}
+
+/// Encode a state so that equal states always have equal bytes
+///
+/// Plain serialization follows the iteration order of the state's hash sets
+/// and maps, which differs between processes. Their entries are encoded and
+/// sorted here instead.
+pub fn canonical_state_bytes(state: &State) -> Result<Vec<u8>, DsmError> {
+    fn sorted_entries<T: Serialize>(
+        entries: impl IntoIterator<Item = T>,
+    ) -> Result<Vec<Vec<u8>>, DsmError> {
+        let mut encoded = entries
+            .into_iter()
+            .map(|entry| bincode::serialize(&entry))
+            .collect::<Result<Vec<_>, _>>()?;
+        encoded.sort();
+        Ok(encoded)
+    }
+
+    let mut rest = state.clone();
+    let flags = sorted_entries(std::mem::take(&mut rest.flags))?;
+    let token_balances = sorted_entries(std::mem::take(&mut rest.token_balances))?;
+    let committed_balances = sorted_entries(std::mem::take(&mut rest.committed_balances))?;
+
+    Ok(bincode::serialize(&(
+        rest,
+        flags,
+        token_balances,
+        committed_balances,
+    ))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dsm::types::state_types::DeviceInfo;
+    use futures::executor::block_on;
+
+    /// Run the same operations on a fresh deterministic SDK
+    fn run_session(seed: u64) -> CoreSDK {
+        let sdk = CoreSDK::deterministic(seed);
+        let device_info = DeviceInfo::new("simulated_device", vec![1, 2, 3, 4]);
+        let genesis = sdk.create_initial_state(&device_info).unwrap();
+        block_on(sdk.initialize_with_genesis(genesis, None)).unwrap();
+
+        for step in 0..3u8 {
+            // Operation inputs come from the SDK's seeded sources
+            let mut data = sdk.generate_nonce();
+            data.extend_from_slice(&sdk.now().to_le_bytes());
+            let operation = sdk.generic_operation("step", data).unwrap();
+            block_on(sdk.execute_transition(operation)).unwrap();
+
+            let snapshot = sdk.begin_snapshot().unwrap();
+            let batch = vec![sdk.generic_operation("batch", vec![step]).unwrap()];
+            block_on(sdk.commit_operations(snapshot, batch)).unwrap();
+        }
+
+        sdk
+    }
+
+    fn final_bytes(sdk: &CoreSDK) -> Vec<u8> {
+        canonical_state_bytes(&sdk.get_current_state().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_same_seed_produces_identical_states() {
+        let first = run_session(42);
+        let second = run_session(42);
+
+        assert_eq!(first.get_current_state().unwrap().state_number, 6);
+        assert_eq!(final_bytes(&first), final_bytes(&second));
+
+        // Nonces feed into the operations, so another seed diverges
+        assert_ne!(final_bytes(&first), final_bytes(&run_session(7)));
+    }
+
+    #[test]
+    fn test_replayed_session_matches_recorded_sdk() {
+        let sdk = run_session(42);
+
+        // A stale commit is recorded and fails again on replay
+        let stale = sdk.begin_snapshot().unwrap();
+        block_on(sdk.execute_transition(sdk.update_operation().unwrap())).unwrap();
+        let ops = vec![sdk.generic_operation("late", vec![]).unwrap()];
+        assert!(block_on(sdk.commit_operations(stale, ops)).is_err());
+
+        let recording = record_session(&sdk);
+        assert_eq!(recording.seed, Some(42));
+
+        let encoded = serde_json::to_vec(&recording).unwrap();
+        let decoded: SessionRecording = serde_json::from_slice(&encoded).unwrap();
+        let replayed = replay_session(&decoded).unwrap();
+
+        assert_eq!(final_bytes(&replayed), final_bytes(&sdk));
+        assert_eq!(
+            replayed.get_current_state().unwrap().state_number,
+            sdk.get_current_state().unwrap().state_number
+        );
+    }
+
+    #[test]
+    fn test_replay_requires_genesis() {
+        let recording = SessionRecording {
+            seed: Some(1),
+            events: Vec::new(),
+        };
+        assert!(replay_session(&recording).is_err());
+    }
+}
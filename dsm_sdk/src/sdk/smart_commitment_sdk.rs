@@ -62,7 +62,7 @@
 // Removed unused import
 use dsm::types::state_types::State;
 use dsm::types::token_types::Balance;
-use pqcrypto_mlkem::mlkem512 as kyber;
+use dsm::crypto::mlkem::mlkem512 as kyber;
 use pqcrypto_traits::kem::{
     Ciphertext as PqCiphertext, PublicKey as PqPublicKey, SecretKey as PqSecretKey,
     SharedSecret as PqSharedSecret,
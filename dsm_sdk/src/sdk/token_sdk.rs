@@ -53,6 +53,9 @@
 
 use dsm::{
     commitments::SmartCommitment as DsmSmartCommitment,
+    crypto::pedersen::{
+        homomorphic_total_supply, PedersenCommitment, PedersenParams, TransferProof,
+    },
     types::{
         error::DsmError,
         operations::{Operation, TransactionMode, VerificationType},
@@ -60,6 +63,11 @@
         token_types::{Balance, TokenMetadata, TokenOperation, TokenStatus, TokenType},
     },
 };
+#[cfg(not(target_arch = "wasm32"))]
+use dsm_storage_node::error::{Result as StorageResult, StorageNodeError};
+#[cfg(not(target_arch = "wasm32"))]
+use dsm_storage_node::staking::payout::{DistributionExecutor, PayoutTransfer};
+use num_bigint::BigUint;
 use parking_lot::RwLock;
 
 use super::{
@@ -638,6 +646,24 @@ pub fn calculate_fee(&self, operation_type: &str) -> Balance {
         root_token.get_fee(operation_type)
     }
 
+    /// Fee a transfer of `amount` would be charged, without executing it
+    ///
+    /// Computed from the fee policy of the current state, the same way the
+    /// transition charges it; zero if the state has no fee policy.
+    pub fn estimate_fee(&self, token_id: &str, amount: Balance) -> Result<Balance, DsmError> {
+        let current_state = self.core_sdk.get_current_state()?;
+        let transfer = self.create_transfer_operation(
+            String::new(),
+            amount,
+            token_id.to_string(),
+            String::new(),
+            false,
+        )?;
+        let fee = current_state.transfer_fee(&transfer)?;
+
+        Ok(Balance::from_state(fee, current_state.hash))
+    }
+
     /// Process fee payment for an operation
     pub async fn process_fee_payment(
         &self,
@@ -664,6 +690,14 @@ fn get_root_token_info(&self) -> RootToken {
         self.root_token.read().clone()
     }
 
+    /// Get the total supply of a token
+    ///
+    /// Sums the token's balances across all holders in the current state,
+    /// the same supply mints are checked against.
+    pub fn get_total_supply(&self, token_id: &str) -> Result<Balance, DsmError> {
+        self.core_sdk.total_supply(token_id)
+    }
+
     /// Get balance for a specific token with multi-format key support
     pub fn get_token_balance(&self, address: &str, token_id: &str) -> Balance {
         // First try to retrieve from local cache using direct lookup
@@ -792,6 +826,101 @@ pub fn transfer_token_operation(
         })
     }
 
+    /// Create a transfer between committed balances without revealing the amount
+    ///
+    /// `from_commitment` is the sender's balance after the transfer and
+    /// `to_commitment` commits to the amount credited to `recipient`. The
+    /// proof must show that both are in range and together open to the
+    /// current committed balance.
+    pub fn transfer_committed(
+        &self,
+        recipient: &str,
+        from_commitment: PedersenCommitment,
+        to_commitment: PedersenCommitment,
+        proof: TransferProof,
+    ) -> Operation {
+        Operation::transfer_committed(recipient, from_commitment, to_commitment, proof)
+    }
+
+    /// Create an operation disclosing a committed balance for auditing
+    pub fn reveal_balance(
+        &self,
+        token_id: &str,
+        amount: u64,
+        blinding_factor: &BigUint,
+    ) -> Operation {
+        Operation::RevealBalance {
+            token_id: token_id.to_string(),
+            amount,
+            blinding_factor: blinding_factor.to_bytes_be(),
+        }
+    }
+
+    /// Get the committed balance for a token from the current state
+    pub fn get_committed_balance(&self, token_id: &str) -> Option<PedersenCommitment> {
+        self.core_sdk
+            .get_current_state()
+            .ok()
+            .and_then(|state| state.committed_balances.get(token_id).cloned())
+    }
+
+    /// Get a commitment to the sum of this device's committed balances of a token
+    ///
+    /// Homomorphically sums the committed balances in the local device's
+    /// current state, keyed by token or by `"holder.token_id"`. This is the
+    /// supply the local chain knows about, not the token's supply across all
+    /// holders; sum every holder's commitments with
+    /// [`homomorphic_total_supply`] for that.
+    pub fn get_committed_total_supply(
+        &self,
+        token_id: &str,
+    ) -> Result<PedersenCommitment, DsmError> {
+        let state = self.core_sdk.get_current_state()?;
+        let balances: Vec<_> = state
+            .committed_balances
+            .iter()
+            .filter(|(key, _)| {
+                *key == token_id
+                    || key
+                        .split_once('.')
+                        .is_some_and(|(_, key_token)| key_token == token_id)
+            })
+            .map(|(_, commitment)| commitment.clone())
+            .collect();
+        if balances.is_empty() {
+            return Err(DsmError::invalid_operation(format!(
+                "No committed balances for token {token_id}"
+            )));
+        }
+
+        homomorphic_total_supply(&balances)
+    }
+
+    /// Check that this device's committed supply of a token opens to an expected total
+    ///
+    /// Audits the aggregate of [`Self::get_committed_total_supply`].
+    /// `audit_blinding` is the big-endian sum of the aggregated balances'
+    /// blinding factors modulo `q`, disclosed to the auditor in place of the
+    /// individual openings. Returns false if there are no committed balances.
+    pub fn audit_total_supply(
+        &self,
+        token_id: &str,
+        expected_total: Balance,
+        audit_blinding: &[u8; 32],
+    ) -> bool {
+        let Ok(total) = self.get_committed_total_supply(token_id) else {
+            return false;
+        };
+        let params = PedersenParams::balance_params(total.security_level);
+        total
+            .verify_amount(
+                expected_total.value(),
+                &BigUint::from_bytes_be(audit_blinding),
+                params,
+            )
+            .unwrap_or(false)
+    }
+
     /// Execute a smart commitment
     #[allow(dead_code)]
     async fn execute_commitment(
@@ -1323,6 +1452,55 @@ async fn validate_token_conservation(&self) -> Result<bool, DsmError> {
     }
 }
 
+/// Pays out storage node reward distributions as token transfers
+///
+/// Transfers are unilateral, so a recipient that is offline collects its
+/// payout from its inbox on the storage node. The transfer ID goes into the
+/// memo, which the transition commits to, and a transfer whose memo is already
+/// on the chain is not made again.
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait::async_trait]
+impl DistributionExecutor for TokenSDK<IdentitySDK> {
+    async fn execute(&self, transfer: &PayoutTransfer) -> StorageResult<u64> {
+        let memo = format!(
+            "Reward payout {} from vault {}",
+            transfer.transfer_id, transfer.vault_id
+        );
+
+        // Look for an earlier attempt among the states the chain still holds
+        let head = self
+            .core_sdk
+            .get_current_state()
+            .map_err(|e| StorageNodeError::Staking(format!("No current state: {}", e)))?
+            .state_number;
+        let made = (0..=head)
+            .rev()
+            .map_while(|number| self.core_sdk.get_state_by_number(number).ok())
+            .find(|state| {
+                matches!(&state.operation, Operation::Transfer { message, .. } if *message == memo)
+            });
+        if let Some(state) = made {
+            return Ok(state.state_number);
+        }
+
+        let operation = TokenOperation::Transfer {
+            token_id: transfer.token_id.clone(),
+            recipient: transfer.node_id.clone(),
+            amount: transfer.amount,
+            memo: Some(memo),
+        };
+
+        let state = self.execute_token_operation(operation).await.map_err(|e| {
+            StorageNodeError::Staking(format!(
+                "Reward transfer to {} failed: {}",
+                transfer.node_id, e
+            ))
+        })?;
+
+        Ok(state.state_number)
+    }
+}
+
 impl Clone for RootToken {
     fn clone(&self) -> Self {
         Self {
@@ -0,0 +1,101 @@
+//! # WebAssembly Bindings
+//!
+//! JavaScript entry points for browser-side DSM clients, built with the
+//! `wasm` feature:
+//!
+//! ```text
+//! cargo rustc -p dsm_sdk --lib --release --target wasm32-unknown-unknown \
+//!     --crate-type cdylib --no-default-features --features wasm
+//! wasm-bindgen --target web target/wasm32-unknown-unknown/release/dsm_sdk.wasm
+//! ```
+//!
+//! * [`WasmIdentity`] creates an identity and its genesis state in the browser
+//! * [`WasmStorageClient`] talks to a storage node over `fetch`
+//!
+//! See `examples/wasm_demo/` for a page that creates an identity and polls
+//! its inbox.
+
+use crate::hashchain_sdk::HashChainSDK;
+use crate::identity_sdk::IdentitySDK;
+use dsm::types::state_types::{DeviceInfo, State};
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+pub use dsm_storage_node::client::WasmStorageClient;
+
+/// DSM identity usable from JavaScript
+#[wasm_bindgen(js_name = Identity)]
+pub struct WasmIdentity {
+    identity_sdk: IdentitySDK,
+    genesis: State,
+}
+
+#[wasm_bindgen(js_class = Identity)]
+impl WasmIdentity {
+    /// Create an identity with a fresh SPHINCS+ key pair and its genesis state
+    ///
+    /// `entropy` is mixed into the genesis state; pass bytes from
+    /// `crypto.getRandomValues`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        identity_id: String,
+        device_id: String,
+        entropy: Vec<u8>,
+    ) -> Result<WasmIdentity, JsError> {
+        let identity_sdk = IdentitySDK::new(identity_id, Arc::new(HashChainSDK::new()));
+        let public_key = identity_sdk.get_public_key().map_err(js_error)?;
+
+        let genesis = identity_sdk
+            .create_genesis(DeviceInfo::new(&device_id, public_key), vec![entropy], None)
+            .map_err(js_error)?;
+
+        Ok(Self {
+            identity_sdk,
+            genesis,
+        })
+    }
+
+    /// Identifier of this identity
+    #[wasm_bindgen(getter, js_name = identityId)]
+    pub fn identity_id(&self) -> String {
+        self.identity_sdk.get_identity()
+    }
+
+    /// Hex-encoded genesis hash, which addresses this identity's inbox
+    #[wasm_bindgen(getter, js_name = genesisHash)]
+    pub fn genesis_hash(&self) -> String {
+        hex::encode(&self.genesis.hash)
+    }
+
+    /// SPHINCS+ public key of this identity
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Result<Vec<u8>, JsError> {
+        self.identity_sdk.get_public_key().map_err(js_error)
+    }
+
+    /// Sign data with this identity's key
+    pub fn sign(&self, data: Vec<u8>) -> Result<Vec<u8>, JsError> {
+        self.identity_sdk.sign_data(&data).map_err(js_error)
+    }
+
+    /// Resolve to the live entries in this identity's inbox
+    #[wasm_bindgen(js_name = pollInbox)]
+    pub fn poll_inbox(&self, client: &WasmStorageClient) -> js_sys::Promise {
+        let client = client.client();
+        let genesis_hash = self.genesis_hash();
+
+        future_to_promise(async move {
+            let entries = client
+                .get_inbox_transactions(&genesis_hash)
+                .await
+                .map_err(|e| JsError::new(&e.to_string()))?;
+            serde_wasm_bindgen::to_value(&entries).map_err(|e| JsError::new(&e.to_string()).into())
+        })
+    }
+}
+
+/// Convert an SDK error into a JavaScript `Error`
+fn js_error(error: dsm::types::error::DsmError) -> JsError {
+    JsError::new(&error.to_string())
+}
@@ -0,0 +1,193 @@
+//! Storage node request latency over TCP and QUIC
+//!
+//! Starts a mock storage node that answers HTTP/1.1 over TCP and HTTP/3 over
+//! QUIC on the same port, then reports the median and p99 latency of
+//! `fetch_genesis_state` over each transport. Every fetch asks for a new
+//! genesis hash, so no request is answered from the client's caches.
+//!
+//! Run with:
+//! `RUSTFLAGS="--cfg reqwest_unstable" cargo bench -p dsm_storage_node --features quic --bench transport_latency`
+
+use axum::http::{StatusCode, Uri};
+use axum::Router;
+use bytes::Bytes;
+use dsm::test_support::genesis;
+use dsm_storage_node::client::{
+    SerializationFormat, StorageNodeClient, StorageNodeClientConfig, TlsClientConfig,
+    TransportConfig, TransportKind,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Fetches before measuring, to settle connections
+const WARM_UP_ITERATIONS: usize = 50;
+
+/// Measured fetches per transport
+const MEASURED_ITERATIONS: usize = 1_000;
+
+/// Serialized genesis state returned for every key
+fn genesis_bytes() -> Vec<u8> {
+    let genesis = genesis(vec![0xaa; 32]);
+
+    SerializationFormat::default().serialize(&genesis).unwrap()
+}
+
+/// Response body for a request path, as served by both transports
+fn respond(path: &str, genesis: &[u8]) -> Option<Vec<u8>> {
+    match path {
+        "/version" => Some(serde_json::to_vec(&StorageNodeClient::supported_versions()).unwrap()),
+        "/health" => Some(b"OK".to_vec()),
+        _ if path.starts_with("/data/") => Some(genesis.to_vec()),
+        _ => None,
+    }
+}
+
+/// Serve the mock storage node over HTTP/1.1 on a free TCP port
+fn serve_tcp(genesis: Arc<Vec<u8>>) -> SocketAddr {
+    let app = Router::new().fallback(move |uri: Uri| {
+        let genesis = genesis.clone();
+        async move {
+            match respond(uri.path(), &genesis) {
+                Some(body) => (StatusCode::OK, body),
+                None => (StatusCode::NOT_FOUND, Vec::new()),
+            }
+        }
+    });
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let server = axum::Server::bind(&addr).serve(app.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+/// Serve the mock storage node over HTTP/3 on a UDP port
+///
+/// Returns the PEM certificate clients must trust.
+fn serve_quic(addr: SocketAddr, genesis: Arc<Vec<u8>>) -> String {
+    let certificate = rcgen::generate_simple_self_signed(vec![addr.ip().to_string()]).unwrap();
+    let mut tls = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![rustls::Certificate(certificate.serialize_der().unwrap())],
+            rustls::PrivateKey(certificate.serialize_private_key_der()),
+        )
+        .unwrap();
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+
+    let endpoint =
+        quinn::Endpoint::server(quinn::ServerConfig::with_crypto(Arc::new(tls)), addr).unwrap();
+
+    tokio::spawn(async move {
+        while let Some(connecting) = endpoint.accept().await {
+            let genesis = genesis.clone();
+            tokio::spawn(async move {
+                let Ok(connection) = connecting.await else {
+                    return;
+                };
+                let Ok(mut connection) =
+                    h3::server::Connection::<_, Bytes>::new(h3_quinn::Connection::new(connection))
+                        .await
+                else {
+                    return;
+                };
+
+                while let Ok(Some((request, mut stream))) = connection.accept().await {
+                    let genesis = genesis.clone();
+                    tokio::spawn(async move {
+                        let (status, body) = match respond(request.uri().path(), &genesis) {
+                            Some(body) => (StatusCode::OK, body),
+                            None => (StatusCode::NOT_FOUND, Vec::new()),
+                        };
+                        let response = http::Response::builder().status(status).body(()).unwrap();
+
+                        if stream.send_response(response).await.is_ok()
+                            && stream.send_data(Bytes::from(body)).await.is_ok()
+                        {
+                            let _ = stream.finish().await;
+                        }
+                    });
+                }
+            });
+        }
+    });
+
+    certificate.serialize_pem().unwrap()
+}
+
+/// Fetch latencies over a client, sorted ascending
+async fn measure(client: &StorageNodeClient) -> Vec<Duration> {
+    let mut latencies = Vec::with_capacity(MEASURED_ITERATIONS);
+
+    for i in 0..WARM_UP_ITERATIONS + MEASURED_ITERATIONS {
+        // A fresh hash each time keeps the caches out of the measurement
+        let genesis_hash = (i as u64).to_be_bytes();
+        let started = Instant::now();
+        client
+            .fetch_genesis_state(&genesis_hash)
+            .await
+            .unwrap()
+            .expect("mock node returned no genesis state");
+
+        if i >= WARM_UP_ITERATIONS {
+            latencies.push(started.elapsed());
+        }
+    }
+
+    latencies.sort();
+    latencies
+}
+
+/// Nearest-rank percentile of sorted latencies
+fn percentile(sorted: &[Duration], fraction: f64) -> Duration {
+    let rank = ((sorted.len() as f64 * fraction).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+fn report(transport: &str, latencies: &[Duration]) {
+    println!(
+        "{:<5} median {:>10.3?}  p99 {:>10.3?}  ({} fetches)",
+        transport,
+        percentile(latencies, 0.5),
+        percentile(latencies, 0.99),
+        latencies.len()
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    runtime.block_on(async {
+        let genesis = Arc::new(genesis_bytes());
+        let tcp_addr = serve_tcp(genesis.clone());
+        let certificate = serve_quic(tcp_addr, genesis);
+
+        let tcp_client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: format!("http://{}", tcp_addr),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+
+        let quic_client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: format!("https://{}", tcp_addr),
+            transport: TransportConfig::Quic {
+                tls_config: TlsClientConfig {
+                    root_certificates_pem: vec![certificate],
+                    accept_invalid_certificates: false,
+                },
+            },
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+        assert_eq!(
+            quic_client.probe_transport().await.unwrap(),
+            TransportKind::Quic,
+            "mock node did not answer over QUIC"
+        );
+
+        report("TCP", &measure(&tcp_client).await);
+        report("QUIC", &measure(&quic_client).await);
+    });
+}
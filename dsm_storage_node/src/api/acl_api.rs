@@ -0,0 +1,595 @@
+// Access control API for DSM Storage Node
+//
+// This module implements per-identity write permissions: a node with an ACL
+// key issues short-lived write permits to identity owners and only accepts
+// writes that carry a valid permit for the writing identity. A permit request
+// must answer a single-use challenge from the node and be signed with the
+// owner key pinned when the identity registered its devices.
+//
+// Every mutating route checks a permit except those authenticated otherwise:
+// - device registration and identity heads carry owner or device signatures
+// - permit challenges and requests are how a permit is obtained
+// - vault extensions, transfers, reassignments and content updates carry the
+//   vault creator's signature
+// - reward receipts and heartbeats carry client and prober signatures
+// - reward schedules, vaults and distributions act for the node rather than
+//   an identity and are left to the operator's network boundary
+// - emergency pause, resume, backup and restore carry the pause authority's
+//   signature
+
+use crate::api::identity_api::owner_blinded_id;
+use crate::api::AppState;
+use crate::client::platform::now_secs;
+use crate::error::{Result, StorageNodeError};
+use crate::storage::StorageEngine;
+use crate::types::BlindedStateEntry;
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use dsm::communication::write_permit::{
+    WritePermitChallenge, WritePermitRequest, WritePermitResponse, WritePermitToken,
+    WRITE_PERMIT_CHALLENGE_LIFETIME, WRITE_PERMIT_HEADER,
+};
+use parking_lot::Mutex;
+use rand::RngCore;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Default lifetime of an issued write permit (15 minutes)
+pub const DEFAULT_WRITE_PERMIT_LIFETIME: u64 = 900;
+
+/// Most unanswered challenges a node keeps at once
+pub const MAX_OUTSTANDING_CHALLENGES: usize = 10_000;
+
+/// Metadata key recording the identity whose permit stored an entry
+pub const OWNER_METADATA_KEY: &str = "owner_genesis";
+
+/// Write access control of a storage node
+#[derive(Debug, Default)]
+pub struct WriteAclState {
+    /// SPHINCS+ key pair permits are signed with (None = writes are open)
+    key_pair: Option<(Vec<u8>, Vec<u8>)>,
+
+    /// Lifetime of issued permits in seconds
+    permit_lifetime: u64,
+
+    /// Unanswered challenge nonces and when they expire
+    challenges: Mutex<HashMap<String, u64>>,
+}
+
+impl WriteAclState {
+    /// Create the access control for an ACL key pair
+    pub fn new(public_key: Vec<u8>, secret_key: Vec<u8>, permit_lifetime: u64) -> Self {
+        Self {
+            key_pair: Some((public_key, secret_key)),
+            permit_lifetime,
+            challenges: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hand out a single-use nonce for a permit request
+    pub fn challenge(&self, now: u64) -> Result<WritePermitChallenge> {
+        if self.key_pair.is_none() {
+            return Err(StorageNodeError::InvalidOperation(
+                "Node does not issue write permits".into(),
+            ));
+        }
+
+        let mut challenges = self.challenges.lock();
+        challenges.retain(|_, expires_at| *expires_at > now);
+        if challenges.len() >= MAX_OUTSTANDING_CHALLENGES {
+            return Err(StorageNodeError::RateLimitExceeded(
+                "Too many outstanding write permit challenges".into(),
+            ));
+        }
+
+        let mut nonce = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let challenge = WritePermitChallenge {
+            nonce: hex::encode(nonce),
+            expires_at: now.saturating_add(WRITE_PERMIT_CHALLENGE_LIFETIME),
+        };
+        challenges.insert(challenge.nonce.clone(), challenge.expires_at);
+
+        Ok(challenge)
+    }
+
+    /// Issue a permit for a signed request
+    ///
+    /// The identity must have registered a device, and the request must be
+    /// signed with the owner key it registered with over an unexpired
+    /// challenge nonce. Each nonce is accepted once.
+    pub async fn issue(
+        &self,
+        storage: &(dyn StorageEngine + Send + Sync),
+        request: &WritePermitRequest,
+        now: u64,
+    ) -> Result<WritePermitToken> {
+        let (_, secret_key) = self.key_pair.as_ref().ok_or_else(|| {
+            StorageNodeError::InvalidOperation("Node does not issue write permits".into())
+        })?;
+
+        if !request.verify() {
+            return Err(StorageNodeError::Authentication(format!(
+                "Invalid write permit request signature for identity {}",
+                request.genesis_hash
+            )));
+        }
+
+        let owner = storage
+            .retrieve(&owner_blinded_id(&request.genesis_hash))
+            .await?
+            .ok_or_else(|| {
+                StorageNodeError::Authentication(format!(
+                    "Identity {} has not registered an owner key",
+                    request.genesis_hash
+                ))
+            })?;
+        if owner.encrypted_payload != request.owner_public_key {
+            return Err(StorageNodeError::Authentication(format!(
+                "Identity {} is registered to a different owner",
+                request.genesis_hash
+            )));
+        }
+
+        match self.challenges.lock().remove(&request.nonce) {
+            Some(expires_at) if expires_at > now => {}
+            _ => {
+                return Err(StorageNodeError::Authentication(
+                    "Unknown or expired write permit challenge".into(),
+                ))
+            }
+        }
+
+        WritePermitToken::issue(&request.genesis_hash, now, self.permit_lifetime, secret_key)
+            .map_err(|e| StorageNodeError::Encryption(format!("Failed to sign permit: {}", e)))
+    }
+
+    /// Identity a write's permit was issued to
+    ///
+    /// Returns None when the node does not restrict writes.
+    pub fn permit_holder(&self, headers: &HeaderMap, now: u64) -> Result<Option<String>> {
+        let Some((public_key, _)) = &self.key_pair else {
+            return Ok(None);
+        };
+
+        let token = headers
+            .get(WRITE_PERMIT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                StorageNodeError::Authentication("Write requires a write permit".into())
+            })?;
+
+        let permit = WritePermitToken::decode(token).map_err(|e| {
+            StorageNodeError::Authentication(format!("Invalid write permit: {}", e))
+        })?;
+
+        if !permit.verify_signature(public_key) {
+            return Err(StorageNodeError::Authentication(
+                "Write permit was not issued by this node".into(),
+            ));
+        }
+
+        if permit.is_expired_at(now) {
+            return Err(StorageNodeError::Authentication(format!(
+                "Write permit expired at {}",
+                permit.expires_at
+            )));
+        }
+
+        Ok(Some(permit.genesis_hash))
+    }
+
+    /// Check that a write by an identity carries a valid permit
+    pub fn authorize(&self, headers: &HeaderMap, genesis_hash: &str, now: u64) -> Result<()> {
+        match self.permit_holder(headers, now)? {
+            Some(holder) if holder != genesis_hash => {
+                Err(StorageNodeError::Authentication(format!(
+                    "Write permit for identity {} does not cover identity {}",
+                    holder, genesis_hash
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check that a write by an identity carries a valid permit
+pub(crate) fn authorize_write(
+    state: &AppState,
+    headers: &HeaderMap,
+    genesis_hash: &str,
+) -> Result<()> {
+    let result = state.write_acl.authorize(headers, genesis_hash, now_secs());
+    if let Err(e) = &result {
+        warn!("Rejected write by identity {}: {}", genesis_hash, e);
+    }
+    result
+}
+
+/// Identity whose permit a write carries (None = writes are open)
+pub(crate) fn write_permit_holder(state: &AppState, headers: &HeaderMap) -> Result<Option<String>> {
+    let result = state.write_acl.permit_holder(headers, now_secs());
+    if let Err(e) = &result {
+        warn!("Rejected write: {}", e);
+    }
+    result
+}
+
+/// Check that a change to a stored entry carries a permit for its owner
+///
+/// The owner is the identity whose permit stored the entry. Entries stored
+/// without a permit can only be changed while writes are open.
+pub(crate) fn authorize_entry_write(
+    state: &AppState,
+    headers: &HeaderMap,
+    entry: &BlindedStateEntry,
+) -> Result<()> {
+    let Some(holder) = write_permit_holder(state, headers)? else {
+        return Ok(());
+    };
+
+    match entry.metadata.get(OWNER_METADATA_KEY) {
+        Some(owner) if *owner == holder => Ok(()),
+        _ => {
+            warn!(
+                "Rejected write by identity {} to entry {}",
+                holder, entry.blinded_id
+            );
+            Err(StorageNodeError::Authentication(format!(
+                "Entry {} is not owned by identity {}",
+                entry.blinded_id, holder
+            )))
+        }
+    }
+}
+
+/// Record the identity whose permit stored an entry
+///
+/// Owners claimed in the submitted metadata are discarded.
+pub(crate) fn record_entry_owner(metadata: &mut HashMap<String, String>, owner: Option<String>) {
+    metadata.remove(OWNER_METADATA_KEY);
+    if let Some(owner) = owner {
+        metadata.insert(OWNER_METADATA_KEY.to_string(), owner);
+    }
+}
+
+/// Hand out a challenge for a write permit request
+#[axum::debug_handler]
+pub async fn issue_write_permit_challenge(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let challenge = state.write_acl.challenge(now_secs())?;
+
+    Ok((StatusCode::OK, Json(challenge)))
+}
+
+/// Issue a write permit to an identity owner
+#[axum::debug_handler]
+pub async fn issue_write_permit(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<WritePermitRequest>,
+) -> Result<impl IntoResponse> {
+    let permit = state
+        .write_acl
+        .issue(state.storage.as_ref(), &request, now_secs())
+        .await?;
+
+    info!(
+        "Issued write permit for identity {} until {}",
+        permit.genesis_hash, permit.expires_at
+    );
+
+    let permit = permit
+        .encode()
+        .map_err(|e| StorageNodeError::Serialization(format!("Failed to encode permit: {}", e)))?;
+
+    Ok((StatusCode::OK, Json(WritePermitResponse { permit })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::identity_api::{device_registration_message, DeviceRegistration};
+    use crate::test_support::api_server;
+    use axum::body::{Body, HttpBody};
+    use axum::http::Request;
+    use axum::Router;
+    use dsm::crypto::sphincs;
+    use dsm::types::state_types::DeviceInfo;
+    use tower::ServiceExt;
+
+    const GENESIS_HASH: &str = "aabbcc";
+
+    /// Router of a node whose ACL key is the returned key pair
+    fn router() -> (Router, (Vec<u8>, Vec<u8>)) {
+        let acl_key = sphincs::generate_sphincs_keypair().unwrap();
+        let server = api_server().with_write_acl(WriteAclState::new(
+            acl_key.0.clone(),
+            acl_key.1.clone(),
+            DEFAULT_WRITE_PERMIT_LIFETIME,
+        ));
+
+        (server.create_router(), acl_key)
+    }
+
+    /// Store an inbox entry sent by `GENESIS_HASH`, with an optional permit
+    async fn store_inbox(router: &Router, permit: Option<&WritePermitToken>) -> StatusCode {
+        let body = serde_json::json!({
+            "entry": {
+                "id": "entry-1",
+                "sender_genesis_hash": GENESIS_HASH,
+                "recipient_genesis_hash": "ddeeff",
+                "transaction": [1, 2, 3],
+                "signature": [4, 5, 6],
+                "timestamp": now_secs(),
+                "expires_at": 0,
+                "metadata": {},
+            }
+        });
+
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/inbox")
+            .header("content-type", "application/json");
+        if let Some(permit) = permit {
+            request = request.header(WRITE_PERMIT_HEADER, permit.encode().unwrap());
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_write_without_permit_rejected() {
+        let (router, _) = router();
+
+        assert_eq!(store_inbox(&router, None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    /// Send a JSON request and return the status and body
+    async fn send(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Vec<u8>>,
+    ) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, Body::from))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        (status, bytes)
+    }
+
+    /// Pin `owner` as the owner key of `GENESIS_HASH` by registering a device
+    async fn register_owner(router: &Router, (public_key, secret_key): &(Vec<u8>, Vec<u8>)) {
+        let device_info = DeviceInfo::new("laptop", vec![7; 32]);
+        let message = device_registration_message(GENESIS_HASH, &device_info);
+        let registration = DeviceRegistration {
+            device_info,
+            owner_public_key: public_key.clone(),
+            owner_signature: sphincs::sphincs_sign(secret_key, &message).unwrap(),
+        };
+        let uri = format!("/identity/{}/devices", GENESIS_HASH);
+        let body = serde_json::to_vec(&registration).unwrap();
+
+        assert_eq!(
+            send(router, "POST", &uri, Some(body)).await.0,
+            StatusCode::CREATED
+        );
+    }
+
+    /// Fetch a challenge and answer it with a request signed by `owner`
+    async fn permit_request(
+        router: &Router,
+        (public_key, secret_key): &(Vec<u8>, Vec<u8>),
+    ) -> WritePermitRequest {
+        let (status, body) = send(router, "GET", "/acl/challenge", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let challenge: WritePermitChallenge = serde_json::from_slice(&body).unwrap();
+
+        let message = WritePermitRequest::signing_message(GENESIS_HASH, &challenge.nonce);
+        WritePermitRequest {
+            genesis_hash: GENESIS_HASH.to_string(),
+            nonce: challenge.nonce,
+            owner_public_key: public_key.clone(),
+            owner_signature: sphincs::sphincs_sign(secret_key, &message).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_with_valid_permit_accepted() {
+        let (router, _) = router();
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        register_owner(&router, &owner).await;
+
+        let request = serde_json::to_vec(&permit_request(&router, &owner).await).unwrap();
+        let (status, body) = send(&router, "POST", "/acl/permit", Some(request)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let response: WritePermitResponse = serde_json::from_slice(&body).unwrap();
+        let permit = WritePermitToken::decode(&response.permit).unwrap();
+
+        assert_eq!(permit.genesis_hash, GENESIS_HASH);
+        assert!(store_inbox(&router, Some(&permit)).await.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_permit_request_cannot_be_replayed() {
+        let (router, _) = router();
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        register_owner(&router, &owner).await;
+
+        let request = serde_json::to_vec(&permit_request(&router, &owner).await).unwrap();
+        let first = send(&router, "POST", "/acl/permit", Some(request.clone())).await;
+        let replay = send(&router, "POST", "/acl/permit", Some(request)).await;
+
+        assert_eq!(first.0, StatusCode::OK);
+        assert_eq!(replay.0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_permit_requires_registered_owner() {
+        let (router, _) = router();
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        let stranger = sphincs::generate_sphincs_keypair().unwrap();
+
+        // A self-consistent request for an identity nobody has registered
+        let request = serde_json::to_vec(&permit_request(&router, &stranger).await).unwrap();
+        let (status, _) = send(&router, "POST", "/acl/permit", Some(request)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // A key other than the registered owner's
+        register_owner(&router, &owner).await;
+        let request = serde_json::to_vec(&permit_request(&router, &stranger).await).unwrap();
+        let (status, _) = send(&router, "POST", "/acl/permit", Some(request)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_expired_permit_rejected() {
+        let (router, (_, acl_secret_key)) = router();
+        let issued_at = now_secs() - 1_000;
+        let permit = WritePermitToken::issue(GENESIS_HASH, issued_at, 60, &acl_secret_key).unwrap();
+
+        assert_eq!(
+            store_inbox(&router, Some(&permit)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forged_permit_rejected() {
+        let (router, _) = router();
+        let (_, other_secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let forged = WritePermitToken::issue(
+            GENESIS_HASH,
+            now_secs(),
+            DEFAULT_WRITE_PERMIT_LIFETIME,
+            &other_secret_key,
+        )
+        .unwrap();
+
+        assert_eq!(
+            store_inbox(&router, Some(&forged)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    /// Encoded permit the node issued to `genesis_hash`
+    fn permit_for(acl_secret_key: &[u8], genesis_hash: &str) -> String {
+        WritePermitToken::issue(
+            genesis_hash,
+            now_secs(),
+            DEFAULT_WRITE_PERMIT_LIFETIME,
+            acl_secret_key,
+        )
+        .unwrap()
+        .encode()
+        .unwrap()
+    }
+
+    /// Send a JSON request carrying a write permit and return the status
+    async fn send_with_permit(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Vec<u8>>,
+        permit: &str,
+    ) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header(WRITE_PERMIT_HEADER, permit)
+            .body(body.map_or_else(Body::empty, Body::from))
+            .unwrap();
+
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_data_entries_owned_by_permit_holder() {
+        let (router, (_, acl_secret_key)) = router();
+        let owner = permit_for(&acl_secret_key, GENESIS_HASH);
+        let other = permit_for(&acl_secret_key, "ddeeff");
+        let body = serde_json::json!({
+            "blinded_id": "entry-1",
+            "payload": [1, 2, 3],
+            "metadata": { OWNER_METADATA_KEY: "ddeeff" },
+        })
+        .to_string()
+        .into_bytes();
+
+        let (status, _) = send(&router, "POST", "/data", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let status = send_with_permit(&router, "POST", "/data", Some(body.clone()), &owner).await;
+        assert!(status.is_success());
+
+        // The owner claimed in the metadata does not count
+        let status = send_with_permit(&router, "POST", "/data", Some(body), &other).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = send_with_permit(&router, "DELETE", "/data/entry-1", None, &other).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let status = send_with_permit(&router, "DELETE", "/data/entry-1", None, &owner).await;
+        assert!(status.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_history_state_deleted_only_by_owner() {
+        let (router, (_, acl_secret_key)) = router();
+        let owner = permit_for(&acl_secret_key, GENESIS_HASH);
+        let other = permit_for(&acl_secret_key, "ddeeff");
+        let body = serde_json::json!({ "blinded_id": "state:0102", "payload": [1, 2, 3] })
+            .to_string()
+            .into_bytes();
+
+        let status = send_with_permit(&router, "POST", "/data", Some(body), &owner).await;
+        assert!(status.is_success());
+
+        let (status, _) = send(&router, "DELETE", "/state/0102", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = send_with_permit(&router, "DELETE", "/state/0102", None, &other).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let status = send_with_permit(&router, "DELETE", "/state/0102", None, &owner).await;
+        assert!(status.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_inbox_entries_acknowledged_only_by_recipient() {
+        let (router, (_, acl_secret_key)) = router();
+        let sender = permit_for(&acl_secret_key, GENESIS_HASH);
+        let recipient = permit_for(&acl_secret_key, "ddeeff");
+        let permit = WritePermitToken::decode(&sender).unwrap();
+        assert!(store_inbox(&router, Some(&permit)).await.is_success());
+
+        for (method, uri) in [
+            ("PUT", "/inbox/ddeeff/entry-1/ack"),
+            ("DELETE", "/inbox/ddeeff/entry-1"),
+        ] {
+            let (status, _) = send(&router, method, uri, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            let status = send_with_permit(&router, method, uri, None, &sender).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+            let status = send_with_permit(&router, method, uri, None, &recipient).await;
+            assert!(status.is_success());
+        }
+    }
+}
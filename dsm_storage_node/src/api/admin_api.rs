@@ -0,0 +1,552 @@
+// Admin API for DSM Storage Node
+//
+// This module implements the emergency pause: an authority whose public key is
+// part of the node's genesis configuration can freeze every state-mutating
+// endpoint while the node keeps serving reads and stays connected. The same
+// authority approves other admin requests, such as backups and restores, with
+// single-use signed approvals.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::AppState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::platform::now_secs;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::crypto::verify_signature_or_false;
+use crate::error::{Result, StorageNodeError};
+#[cfg(not(target_arch = "wasm32"))]
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::warn;
+
+/// Order from the emergency authority to freeze all state transitions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmergencyPause {
+    /// When the pause was issued (seconds since epoch)
+    pub paused_at: u64,
+
+    /// Why the node is paused
+    pub reason: String,
+
+    /// Authority's signature over `signing_message`
+    pub authority_signature: Vec<u8>,
+
+    /// Lift the pause automatically at this time (None = until resumed)
+    pub resume_after: Option<u64>,
+}
+
+impl EmergencyPause {
+    /// Message the authority signs to issue this pause
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"DSM_EMERGENCY_PAUSE");
+        hasher.update(&self.paused_at.to_le_bytes());
+        hasher.update(&(self.reason.len() as u64).to_le_bytes());
+        hasher.update(self.reason.as_bytes());
+        match self.resume_after {
+            Some(resume_after) => {
+                hasher.update(&[1]);
+                hasher.update(&resume_after.to_le_bytes());
+            }
+            None => {
+                hasher.update(&[0]);
+            }
+        }
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    /// Whether the pause is still in force at the given time
+    pub fn is_active_at(&self, now: u64) -> bool {
+        !matches!(self.resume_after, Some(resume_after) if now >= resume_after)
+    }
+}
+
+/// Order from the emergency authority to lift a pause
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmergencyResume {
+    /// `paused_at` of the pause being lifted
+    pub paused_at: u64,
+
+    /// Authority's signature over `signing_message`
+    pub authority_signature: Vec<u8>,
+}
+
+impl EmergencyResume {
+    /// Message the authority signs to lift the pause issued at `paused_at`
+    ///
+    /// Binding the resume to its pause keeps it from being replayed against a
+    /// later pause.
+    pub fn signing_message(paused_at: u64) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"DSM_EMERGENCY_RESUME");
+        hasher.update(&paused_at.to_le_bytes());
+        hasher.finalize().as_bytes().to_vec()
+    }
+}
+
+/// Header carrying the emergency authority's approval of an admin request
+pub const ADMIN_AUTHORIZATION_HEADER: &str = "x-dsm-admin-authorization";
+
+/// How long an admin approval is accepted after it was issued (5 minutes)
+pub const ADMIN_AUTHORIZATION_LIFETIME: u64 = 300;
+
+/// Emergency authority's approval of a single admin request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdminAuthorization {
+    /// Operation approved, such as `backup` or `restore`
+    pub operation: String,
+
+    /// When the approval was issued (seconds since epoch)
+    pub issued_at: u64,
+
+    /// Random value that keeps the approval from being used twice
+    pub nonce: [u8; 32],
+
+    /// BLAKE3 hash of the request body the approval covers
+    pub body_hash: [u8; 32],
+
+    /// Authority's signature over `signing_message`
+    pub authority_signature: Vec<u8>,
+}
+
+impl AdminAuthorization {
+    /// Approve an operation on a request body with the authority's secret key
+    pub fn sign(
+        operation: &str,
+        body: &[u8],
+        issued_at: u64,
+        authority_secret_key: &[u8],
+    ) -> Result<Self> {
+        let mut authorization = Self {
+            operation: operation.to_string(),
+            issued_at,
+            nonce: rand::random(),
+            body_hash: *blake3::hash(body).as_bytes(),
+            authority_signature: Vec::new(),
+        };
+        authorization.authority_signature = dsm::crypto::sphincs::sphincs_sign(
+            authority_secret_key,
+            &authorization.signing_message(),
+        )
+        .map_err(|e| StorageNodeError::Encryption(format!("Failed to sign approval: {}", e)))?;
+
+        Ok(authorization)
+    }
+
+    /// Message the authority signs to approve the request
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"DSM_ADMIN_AUTHORIZATION");
+        hasher.update(&(self.operation.len() as u64).to_le_bytes());
+        hasher.update(self.operation.as_bytes());
+        hasher.update(&self.issued_at.to_le_bytes());
+        hasher.update(&self.nonce);
+        hasher.update(&self.body_hash);
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    /// Encode the approval for `ADMIN_AUTHORIZATION_HEADER`
+    pub fn encode(&self) -> Result<String> {
+        Ok(URL_SAFE_NO_PAD.encode(bincode::serialize(self)?))
+    }
+
+    /// Decode an approval produced by `encode`
+    pub fn decode(value: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).map_err(|e| {
+            StorageNodeError::Serialization(format!("Invalid admin approval encoding: {}", e))
+        })?;
+
+        Ok(bincode::deserialize(&bytes)?)
+    }
+}
+
+/// Emergency pause status of a storage node
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+pub struct EmergencyPauseState {
+    /// SPHINCS+ public key of the emergency authority (None = pausing disabled)
+    authority_public_key: Option<Vec<u8>>,
+
+    /// Pause in force, if any
+    active: RwLock<Option<EmergencyPause>>,
+
+    /// `paused_at` of the latest accepted pause, so old pauses cannot be replayed
+    latest_paused_at: RwLock<u64>,
+
+    /// Nonces of accepted admin approvals and when they lapse
+    used_admin_nonces: Mutex<HashMap<[u8; 32], u64>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl EmergencyPauseState {
+    /// Create the pause status for an emergency authority
+    pub fn new(authority_public_key: Option<Vec<u8>>) -> Self {
+        Self {
+            authority_public_key,
+            ..Self::default()
+        }
+    }
+
+    /// The pause in force at the given time, if any
+    pub fn active_pause(&self, now: u64) -> Option<EmergencyPause> {
+        let active = self.active.read().ok()?;
+        active.clone().filter(|pause| pause.is_active_at(now))
+    }
+
+    /// Put the node under an emergency pause
+    pub fn pause(&self, pause: EmergencyPause) -> Result<()> {
+        self.verify_authority(&pause.signing_message(), &pause.authority_signature)?;
+
+        let mut latest_paused_at = self
+            .latest_paused_at
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+        if pause.paused_at <= *latest_paused_at {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Pause issued at {} is not newer than the pause issued at {}",
+                pause.paused_at, *latest_paused_at
+            )));
+        }
+
+        *latest_paused_at = pause.paused_at;
+        *self
+            .active
+            .write()
+            .map_err(|_| StorageNodeError::Internal)? = Some(pause);
+
+        Ok(())
+    }
+
+    /// Lift the pause in force
+    pub fn resume(&self, resume: &EmergencyResume) -> Result<()> {
+        let mut active = self
+            .active
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        match active.as_ref() {
+            Some(pause) if pause.paused_at == resume.paused_at => {}
+            _ => {
+                return Err(StorageNodeError::InvalidInput(format!(
+                    "No pause issued at {} is in force",
+                    resume.paused_at
+                )));
+            }
+        }
+
+        self.verify_authority(
+            &EmergencyResume::signing_message(resume.paused_at),
+            &resume.authority_signature,
+        )?;
+        *active = None;
+
+        Ok(())
+    }
+
+    /// Check the authority's approval of an admin request
+    ///
+    /// The approval must be for `operation` and `body`, issued no more than
+    /// `ADMIN_AUTHORIZATION_LIFETIME` from `now`, and not used before.
+    pub fn authorize_admin(
+        &self,
+        headers: &HeaderMap,
+        operation: &str,
+        body: &[u8],
+        now: u64,
+    ) -> Result<()> {
+        let value = headers
+            .get(ADMIN_AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                StorageNodeError::Authentication(format!(
+                    "The {} operation requires the emergency authority's approval",
+                    operation
+                ))
+            })?;
+        let authorization = AdminAuthorization::decode(value)
+            .map_err(|e| StorageNodeError::Authentication(e.to_string()))?;
+
+        if authorization.operation != operation {
+            return Err(StorageNodeError::Authentication(format!(
+                "Approval is for the {} operation, not {}",
+                authorization.operation, operation
+            )));
+        }
+
+        if authorization.body_hash != *blake3::hash(body).as_bytes() {
+            return Err(StorageNodeError::Authentication(
+                "Approval does not cover this request body".into(),
+            ));
+        }
+
+        if authorization.issued_at.abs_diff(now) > ADMIN_AUTHORIZATION_LIFETIME {
+            return Err(StorageNodeError::Authentication(format!(
+                "Approval issued at {} has lapsed",
+                authorization.issued_at
+            )));
+        }
+
+        self.verify_authority(
+            &authorization.signing_message(),
+            &authorization.authority_signature,
+        )?;
+
+        let mut used = self
+            .used_admin_nonces
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?;
+        used.retain(|_, lapses_at| *lapses_at > now);
+        let lapses_at = authorization.issued_at + ADMIN_AUTHORIZATION_LIFETIME;
+        if used.insert(authorization.nonce, lapses_at).is_some() {
+            return Err(StorageNodeError::Authentication(
+                "Approval has already been used".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn verify_authority(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        let public_key = self.authority_public_key.as_deref().ok_or_else(|| {
+            StorageNodeError::Authentication("No emergency authority is configured".into())
+        })?;
+
+        if verify_signature_or_false(public_key, message, signature) {
+            Ok(())
+        } else {
+            Err(StorageNodeError::Authentication(
+                "Invalid emergency authority signature".into(),
+            ))
+        }
+    }
+}
+
+/// Put the node under an emergency pause
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn submit_emergency_pause(
+    State(state): State<Arc<AppState>>,
+    Json(pause): Json<EmergencyPause>,
+) -> Result<impl IntoResponse> {
+    warn!("Emergency pause requested: {}", pause.reason);
+
+    state.emergency_pause.pause(pause.clone())?;
+
+    Ok((StatusCode::OK, Json(pause)))
+}
+
+/// Lift the emergency pause
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn submit_emergency_resume(
+    State(state): State<Arc<AppState>>,
+    Json(resume): Json<EmergencyResume>,
+) -> Result<impl IntoResponse> {
+    state.emergency_pause.resume(&resume)?;
+
+    warn!("Emergency pause issued at {} lifted", resume.paused_at);
+
+    Ok(StatusCode::OK)
+}
+
+/// Get the emergency pause in force, or `null` if the node is not paused
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn get_emergency_pause(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.emergency_pause.active_pause(now_secs()))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::api::{encode_backup, BackupRecord, RESTORE_OPERATION};
+    use crate::test_support::api_server;
+    use crate::types::NodeGenesisConfig;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::Router;
+    use dsm::crypto::sphincs;
+    use tower::ServiceExt;
+
+    /// Router of a node whose emergency authority holds the returned key pair
+    fn router() -> (Router, (Vec<u8>, Vec<u8>)) {
+        let authority = sphincs::generate_sphincs_keypair().unwrap();
+        let server = api_server().with_genesis_config(NodeGenesisConfig {
+            emergency_authority_public_key: Some(authority.0.clone()),
+        });
+
+        (server.create_router(), authority)
+    }
+
+    fn signed_pause(secret_key: &[u8], paused_at: u64) -> EmergencyPause {
+        let mut pause = EmergencyPause {
+            paused_at,
+            reason: "investigating an exploit".to_string(),
+            authority_signature: Vec::new(),
+            resume_after: None,
+        };
+        pause.authority_signature =
+            sphincs::sphincs_sign(secret_key, &pause.signing_message()).unwrap();
+        pause
+    }
+
+    fn signed_resume(secret_key: &[u8], paused_at: u64) -> EmergencyResume {
+        EmergencyResume {
+            paused_at,
+            authority_signature: sphincs::sphincs_sign(
+                secret_key,
+                &EmergencyResume::signing_message(paused_at),
+            )
+            .unwrap(),
+        }
+    }
+
+    async fn send(router: &Router, method: &str, uri: &str, body: serde_json::Value) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    async fn store(router: &Router, blinded_id: &str) -> StatusCode {
+        let body = serde_json::json!({ "blinded_id": blinded_id, "payload": [1, 2, 3] });
+        send(router, "POST", "/data", body).await
+    }
+
+    async fn get(router: &Router, uri: &str) -> StatusCode {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_pause_freezes_writes_until_resumed() {
+        let (router, (_, secret_key)) = router();
+        assert!(store(&router, "before-pause").await.is_success());
+
+        let pause = signed_pause(&secret_key, now_secs());
+        let body = serde_json::to_value(&pause).unwrap();
+        assert_eq!(
+            send(&router, "POST", "/admin/pause", body).await,
+            StatusCode::OK
+        );
+
+        // Writes are rejected while paused
+        assert_eq!(
+            store(&router, "during-pause").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        for uri in ["/inbox", "/vault"] {
+            let status = send(&router, "POST", uri, serde_json::json!({})).await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        }
+
+        // Reads are still served
+        assert_eq!(get(&router, "/data/before-pause").await, StatusCode::OK);
+        assert_eq!(get(&router, "/health").await, StatusCode::OK);
+
+        let resume = serde_json::to_value(signed_resume(&secret_key, pause.paused_at)).unwrap();
+        assert_eq!(
+            send(&router, "POST", "/admin/resume", resume).await,
+            StatusCode::OK
+        );
+
+        assert!(store(&router, "after-resume").await.is_success());
+        assert_eq!(get(&router, "/data/after-resume").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_authority_signature_rejected() {
+        let (router, (_, secret_key)) = router();
+        let (_, other_secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+
+        // A pause signed by anyone else does not pause the node
+        let forged = serde_json::to_value(signed_pause(&other_secret_key, now_secs())).unwrap();
+        assert_eq!(
+            send(&router, "POST", "/admin/pause", forged).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert!(store(&router, "not-paused").await.is_success());
+
+        // Nor does anyone else's signature lift a pause
+        let pause = signed_pause(&secret_key, now_secs());
+        let body = serde_json::to_value(&pause).unwrap();
+        assert_eq!(
+            send(&router, "POST", "/admin/pause", body).await,
+            StatusCode::OK
+        );
+
+        let forged = serde_json::to_value(signed_resume(&other_secret_key, pause.paused_at));
+        assert_eq!(
+            send(&router, "POST", "/admin/resume", forged.unwrap()).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            store(&router, "still-paused").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn test_pause_lifts_after_resume_time() {
+        let pause = EmergencyPause {
+            paused_at: 1_000,
+            reason: "maintenance".to_string(),
+            authority_signature: Vec::new(),
+            resume_after: Some(2_000),
+        };
+
+        assert!(pause.is_active_at(1_999));
+        assert!(!pause.is_active_at(2_000));
+    }
+
+    /// Upload a backup to the restore endpoint with an approval
+    async fn restore(router: &Router, backup: &[u8], approval: &AdminAuthorization) -> StatusCode {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/restore")
+            .header("content-type", "application/gzip")
+            .header(ADMIN_AUTHORIZATION_HEADER, approval.encode().unwrap())
+            .body(Body::from(backup.to_vec()))
+            .unwrap();
+
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_restore_approval_is_single_use_and_bound_to_backup() {
+        let (router, (_, secret_key)) = router();
+        let backup = encode_backup(&[]).unwrap();
+        let other_backup = encode_backup(&[BackupRecord {
+            record_type: 999,
+            payload: vec![1],
+        }])
+        .unwrap();
+
+        let approval =
+            AdminAuthorization::sign(RESTORE_OPERATION, &backup, now_secs(), &secret_key).unwrap();
+        let status = restore(&router, &other_backup, &approval).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(restore(&router, &backup, &approval).await, StatusCode::OK);
+        let replayed = restore(&router, &backup, &approval).await;
+        assert_eq!(replayed, StatusCode::UNAUTHORIZED);
+
+        let lapsed = now_secs() - ADMIN_AUTHORIZATION_LIFETIME - 1;
+        let approval = AdminAuthorization::sign(RESTORE_OPERATION, &backup, lapsed, &secret_key);
+        let status = restore(&router, &backup, &approval.unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
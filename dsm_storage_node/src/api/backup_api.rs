@@ -0,0 +1,379 @@
+// Backup API for DSM Storage Node
+//
+// This module implements disaster recovery: a node exports all of its data as
+// one gzip-compressed backup, and a replacement node restores from it. A
+// backup is a sequence of frames, each a little-endian u32 length followed by
+// a bincode-encoded `BackupRecord`. Records carry a type discriminant ahead of
+// their payload, so a node restoring a backup made by a newer version skips
+// the record types it does not know instead of failing. Backups expose and
+// restores overwrite every entry on the node, so both need the emergency
+// authority's approval.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::AppState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::platform::now_secs;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::{Result, StorageNodeError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::StorageEngine;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::BlindedStateEntry;
+#[cfg(not(target_arch = "wasm32"))]
+use axum::{
+    body::Bytes,
+    extract::{Json, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{Read, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::{info, warn};
+
+/// Header carrying the hex BLAKE3 checksum of a backup
+pub const BACKUP_CHECKSUM_HEADER: &str = "x-dsm-backup-checksum";
+
+/// Header carrying the number of records in a backup
+pub const BACKUP_ENTRIES_HEADER: &str = "x-dsm-backup-entries";
+
+/// Header carrying when a backup was created (seconds since epoch)
+pub const BACKUP_CREATED_AT_HEADER: &str = "x-dsm-backup-created-at";
+
+/// Operation named in the authority's approval of a backup download
+pub const BACKUP_OPERATION: &str = "backup";
+
+/// Operation named in the authority's approval of a restore
+pub const RESTORE_OPERATION: &str = "restore";
+
+/// Largest backup a node accepts for a restore (256 MiB)
+pub const MAX_RESTORE_SIZE: usize = 256 * 1024 * 1024;
+
+/// Number of entry IDs listed from storage at a time while backing up
+#[cfg(not(target_arch = "wasm32"))]
+const BACKUP_LIST_BATCH: usize = 1_000;
+
+/// Kind of data a backup record holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u16)]
+pub enum BackupRecordType {
+    /// A bincode-encoded `BlindedStateEntry`
+    BlindedState = 1,
+}
+
+impl BackupRecordType {
+    /// Record type for a discriminant, if this version knows it
+    pub fn from_discriminant(discriminant: u16) -> Option<Self> {
+        match discriminant {
+            1 => Some(Self::BlindedState),
+            _ => None,
+        }
+    }
+}
+
+/// One frame of a backup
+///
+/// `record_type` is kept as a raw discriminant so that records of unknown
+/// types still decode and can be skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupRecord {
+    /// Discriminant of the `BackupRecordType` of the payload
+    pub record_type: u16,
+
+    /// Encoded record
+    pub payload: Vec<u8>,
+}
+
+impl BackupRecord {
+    /// Type of the record, or None if this version does not know it
+    pub fn kind(&self) -> Option<BackupRecordType> {
+        BackupRecordType::from_discriminant(self.record_type)
+    }
+}
+
+/// Description of a downloaded backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Number of records in the backup
+    pub entry_count: u64,
+
+    /// Size of the compressed backup in bytes
+    pub size_bytes: u64,
+
+    /// BLAKE3 hash of the compressed backup
+    pub checksum: [u8; 32],
+
+    /// When the node created the backup (seconds since epoch)
+    pub created_at: u64,
+}
+
+/// Outcome of restoring a backup
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    /// Records written to storage
+    pub restored: u64,
+
+    /// Records of types this node does not support
+    pub skipped: u64,
+}
+
+/// Compress the records of a backup into the backup format
+#[cfg(not(target_arch = "wasm32"))]
+pub fn encode_backup(records: &[BackupRecord]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+
+    for record in records {
+        let frame = bincode::serialize(record)?;
+        let len = u32::try_from(frame.len())
+            .map_err(|_| StorageNodeError::Serialization("Backup record is too large".into()))?;
+        encoder.write_all(&len.to_le_bytes())?;
+        encoder.write_all(&frame)?;
+    }
+
+    Ok(encoder.finish()?)
+}
+
+/// Decompress a backup into its records
+///
+/// A backup cut short anywhere, including between two records, is rejected:
+/// the gzip trailer only matches once the whole backup has been read.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn decode_backup(backup: &[u8]) -> Result<Vec<BackupRecord>> {
+    let mut data = Vec::new();
+    GzDecoder::new(backup).read_to_end(&mut data).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to decompress backup: {}", e))
+    })?;
+
+    let mut records = Vec::new();
+    let mut rest = data.as_slice();
+    while !rest.is_empty() {
+        let truncated = || StorageNodeError::Serialization("Truncated backup record".to_string());
+
+        let (len, tail) = rest.split_first_chunk::<4>().ok_or_else(truncated)?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if tail.len() < len {
+            return Err(truncated());
+        }
+
+        let (frame, tail) = tail.split_at(len);
+        records.push(bincode::deserialize(frame)?);
+        rest = tail;
+    }
+
+    Ok(records)
+}
+
+/// Collect every entry of a storage engine as backup records
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn backup_records(
+    storage: &(dyn StorageEngine + Send + Sync),
+) -> Result<Vec<BackupRecord>> {
+    // Storage engines list a bounded number of IDs per call
+    let mut blinded_ids = Vec::new();
+    loop {
+        let batch = storage
+            .list(Some(BACKUP_LIST_BATCH), Some(blinded_ids.len()))
+            .await?;
+        let done = batch.len() < BACKUP_LIST_BATCH;
+        blinded_ids.extend(batch);
+        if done {
+            break;
+        }
+    }
+
+    let mut records = Vec::new();
+    for blinded_id in blinded_ids {
+        // Entries deleted or expired since listing are not part of the backup
+        if let Some(entry) = storage.retrieve(&blinded_id).await? {
+            records.push(BackupRecord {
+                record_type: BackupRecordType::BlindedState as u16,
+                payload: bincode::serialize(&entry)?,
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+/// Write backup records to a storage engine, skipping unsupported types
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn restore_records(
+    storage: &(dyn StorageEngine + Send + Sync),
+    records: Vec<BackupRecord>,
+) -> Result<RestoreSummary> {
+    let mut summary = RestoreSummary::default();
+
+    for record in records {
+        match record.kind() {
+            Some(BackupRecordType::BlindedState) => {
+                let entry: BlindedStateEntry = bincode::deserialize(&record.payload)?;
+                storage.store(entry).await?;
+                summary.restored += 1;
+            }
+            None => {
+                warn!(
+                    "Skipping backup record of unknown type {}",
+                    record.record_type
+                );
+                summary.skipped += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Export all data of the node as a compressed backup
+///
+/// The request must carry the emergency authority's approval of a backup.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn create_backup(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    state
+        .emergency_pause
+        .authorize_admin(&headers, BACKUP_OPERATION, &[], now_secs())?;
+
+    let records = backup_records(state.storage.as_ref()).await?;
+    let backup = encode_backup(&records)?;
+
+    info!(
+        "Created backup of {} records ({} bytes)",
+        records.len(),
+        backup.len()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::HeaderName::from_static(BACKUP_CHECKSUM_HEADER),
+                blake3::hash(&backup).to_hex().to_string(),
+            ),
+            (
+                header::HeaderName::from_static(BACKUP_ENTRIES_HEADER),
+                records.len().to_string(),
+            ),
+            (
+                header::HeaderName::from_static(BACKUP_CREATED_AT_HEADER),
+                now_secs().to_string(),
+            ),
+        ],
+        backup,
+    ))
+}
+
+/// Restore the node from a backup
+///
+/// The request must carry the emergency authority's approval of a restore of
+/// this backup. If the upload carries a checksum header, the backup must
+/// match it.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn restore_backup(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    backup: Bytes,
+) -> Result<impl IntoResponse> {
+    state
+        .emergency_pause
+        .authorize_admin(&headers, RESTORE_OPERATION, &backup, now_secs())?;
+
+    if let Some(expected) = headers.get(BACKUP_CHECKSUM_HEADER) {
+        let actual = blake3::hash(&backup).to_hex();
+        if expected.as_bytes() != actual.as_bytes() {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Backup checksum {} does not match the uploaded backup",
+                String::from_utf8_lossy(expected.as_bytes())
+            )));
+        }
+    }
+
+    let records = decode_backup(&backup)?;
+    let summary = restore_records(state.storage.as_ref(), records).await?;
+
+    info!(
+        "Restored {} records from backup, skipped {} of unsupported types",
+        summary.restored, summary.skipped
+    );
+
+    Ok((StatusCode::OK, Json(summary)))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStorage, MemoryStorageConfig};
+    use std::collections::HashMap;
+
+    fn entry(blinded_id: &str) -> BlindedStateEntry {
+        BlindedStateEntry {
+            blinded_id: blinded_id.to_string(),
+            encrypted_payload: vec![1, 2, 3],
+            timestamp: now_secs(),
+            ttl: 0,
+            region: "global".to_string(),
+            priority: 0,
+            proof_hash: *blake3::hash(&[1, 2, 3]).as_bytes(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_skips_unknown_record_types() {
+        let source = MemoryStorage::new(MemoryStorageConfig::default());
+        source.store(entry("first")).await.unwrap();
+        source.store(entry("second")).await.unwrap();
+
+        let mut records = backup_records(&source).await.unwrap();
+        records.push(BackupRecord {
+            record_type: 999,
+            payload: b"from a newer node".to_vec(),
+        });
+        let backup = encode_backup(&records).unwrap();
+
+        let target = MemoryStorage::new(MemoryStorageConfig::default());
+        let summary = restore_records(&target, decode_backup(&backup).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            RestoreSummary {
+                restored: 2,
+                skipped: 1
+            }
+        );
+        assert!(target.exists("first").await.unwrap());
+        assert!(target.exists("second").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_backup_includes_every_entry() {
+        let source = MemoryStorage::new(MemoryStorageConfig::default());
+        for i in 0..250 {
+            source.store(entry(&format!("entry-{}", i))).await.unwrap();
+        }
+
+        assert_eq!(backup_records(&source).await.unwrap().len(), 250);
+    }
+
+    #[test]
+    fn test_truncated_backup_rejected() {
+        let records = vec![BackupRecord {
+            record_type: BackupRecordType::BlindedState as u16,
+            payload: vec![7; 128],
+        }];
+        let backup = encode_backup(&records).unwrap();
+
+        assert_eq!(decode_backup(&backup).unwrap(), records);
+        assert!(decode_backup(&backup[..backup.len() / 2]).is_err());
+    }
+}
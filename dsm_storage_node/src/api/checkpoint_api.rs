@@ -0,0 +1,169 @@
+// Checkpoint API for DSM Storage Node
+//
+// This module implements API handlers for storing checkpoint states, which let
+// clients restore a chain without replaying it from genesis, and for deleting
+// the history states a checkpoint has made redundant.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::acl_api::{authorize_entry_write, record_entry_owner, write_permit_holder};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::AppState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::{Result, StorageNodeError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::BlindedStateEntry;
+#[cfg(not(target_arch = "wasm32"))]
+use axum::{
+    extract::{Json, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::{debug, info};
+
+/// Checkpoint submitted by a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointSubmission {
+    /// Checkpoint identifier, in the form `checkpoint_{state_number}_{state_hash}`
+    pub checkpoint_id: String,
+
+    /// State number of the checkpointed state
+    pub state_number: u64,
+
+    /// Hex-encoded hash of the checkpointed state
+    pub state_hash: String,
+
+    /// Encoded checkpoint state
+    pub payload: Vec<u8>,
+
+    /// Wire format of the payload
+    pub content_type: String,
+}
+
+impl CheckpointSubmission {
+    /// Blinded ID under which the checkpoint is stored
+    pub fn blinded_id(&self) -> String {
+        format!("checkpoint:{}", self.checkpoint_id)
+    }
+}
+
+/// Store a checkpoint state
+///
+/// On nodes that restrict writes, the checkpoint is owned by the identity
+/// whose permit stored it, and only that identity may overwrite it.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn store_checkpoint(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(submission): Json<CheckpointSubmission>,
+) -> Result<impl IntoResponse> {
+    info!("Storing checkpoint: {}", submission.checkpoint_id);
+
+    if submission.payload.is_empty() {
+        return Err(StorageNodeError::InvalidState(
+            "Checkpoint payload cannot be empty".into(),
+        ));
+    }
+
+    let expected_id = format!(
+        "checkpoint_{}_{}",
+        submission.state_number, submission.state_hash
+    );
+    if submission.checkpoint_id != expected_id {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Checkpoint ID {} does not match state {} with hash {}",
+            submission.checkpoint_id, submission.state_number, submission.state_hash
+        )));
+    }
+
+    let owner = write_permit_holder(&state, &headers)?;
+    if let Some(existing) = state.storage.retrieve(&submission.blinded_id()).await? {
+        authorize_entry_write(&state, &headers, &existing)?;
+    }
+
+    let entry = BlindedStateEntry {
+        blinded_id: submission.blinded_id(),
+        encrypted_payload: submission.payload.clone(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        ttl: 0, // Checkpoints do not expire
+        region: "global".to_string(),
+        priority: 2, // Restores depend on checkpoints being available
+        proof_hash: {
+            let hash = blake3::hash(&submission.payload);
+            let mut hash_bytes = [0u8; 32];
+            hash_bytes.copy_from_slice(hash.as_bytes());
+            hash_bytes
+        },
+        metadata: {
+            let mut metadata = HashMap::new();
+            metadata.insert("type".to_string(), "checkpoint".to_string());
+            metadata.insert(
+                "state_number".to_string(),
+                submission.state_number.to_string(),
+            );
+            metadata.insert("state_hash".to_string(), submission.state_hash.clone());
+            metadata.insert("content_type".to_string(), submission.content_type.clone());
+            record_entry_owner(&mut metadata, owner);
+            metadata
+        },
+    };
+
+    let response = state.storage.store(entry).await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Blinded ID under which a history state is stored
+pub fn state_blinded_id(state_hash: &str) -> String {
+    format!("state:{}", state_hash)
+}
+
+/// Delete a history state by its hex-encoded hash
+///
+/// On nodes that restrict writes, only the identity whose permit stored the
+/// state may delete it.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn delete_state(
+    State(state): State<Arc<AppState>>,
+    Path(state_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    info!("Deleting state: {}", state_hash);
+
+    if hex::decode(&state_hash).is_err() {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "State hash {} is not hex-encoded",
+            state_hash
+        )));
+    }
+
+    let blinded_id = state_blinded_id(&state_hash);
+    if let Some(entry) = state.storage.retrieve(&blinded_id).await? {
+        authorize_entry_write(&state, &headers, &entry)?;
+    }
+
+    if !state.storage.delete(&blinded_id).await? {
+        debug!("State not found for deletion: {}", state_hash);
+        return Err(StorageNodeError::NotFound(format!(
+            "State {} not found",
+            state_hash
+        )));
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "success",
+            "message": format!("State {} deleted", state_hash),
+        })),
+    ))
+}
@@ -2,25 +2,48 @@
 //
 // This module implements the API route handlers for the storage node.
 
+use crate::api::acl_api::{authorize_entry_write, record_entry_owner, write_permit_holder};
 use crate::api::AppState;
+use crate::client::SerializationFormat;
 use crate::error::{Result, StorageNodeError};
 use crate::types::storage_types::{DataRetrievalRequest, DataSubmissionRequest};
-use crate::types::BlindedStateEntry;
+use crate::types::{BlindedStateEntry, ProtocolVersionRange};
 use axum::{
+    body::Bytes,
     extract::{Json, Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, info};
 
+/// Wire format named by a request header, if it names a binary format
+fn header_format(headers: &HeaderMap, name: header::HeaderName) -> Option<SerializationFormat> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(SerializationFormat::from_content_type)
+}
+
 /// Store data handler
+///
+/// The submission is CBOR when sent as `application/cbor` and JSON otherwise.
+/// On nodes that restrict writes, the entry is owned by the identity whose
+/// permit stored it, and only that identity may overwrite it.
 #[axum::debug_handler]
 pub async fn store_data(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<DataSubmissionRequest>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<impl IntoResponse> {
+    let request: DataSubmissionRequest = match header_format(&headers, header::CONTENT_TYPE) {
+        Some(SerializationFormat::Cbor) => SerializationFormat::Cbor.deserialize(&body)?,
+        _ => serde_json::from_slice(&body).map_err(|e| {
+            StorageNodeError::InvalidInput(format!("Invalid data submission: {}", e))
+        })?,
+    };
+
     info!("Storing data with blinded ID: {}", request.blinded_id);
 
     // Validate request
@@ -36,6 +59,14 @@ pub async fn store_data(
         ));
     }
 
+    let owner = write_permit_holder(&state, &headers)?;
+    if let Some(existing) = state.storage.retrieve(&request.blinded_id).await? {
+        authorize_entry_write(&state, &headers, &existing)?;
+    }
+
+    let mut metadata = request.metadata.unwrap_or_default();
+    record_entry_owner(&mut metadata, owner);
+
     // Create blinded state entry
     let entry = BlindedStateEntry {
         blinded_id: request.blinded_id.clone(),
@@ -56,7 +87,7 @@ pub async fn store_data(
             hash_bytes.copy_from_slice(hash.as_bytes());
             hash_bytes
         }),
-        metadata: request.metadata.unwrap_or_else(HashMap::new),
+        metadata,
     };
 
     // Store entry
@@ -66,12 +97,16 @@ pub async fn store_data(
 }
 
 /// Retrieve data handler
+///
+/// Readers accepting a binary format get the stored payload itself, provided
+/// it was stored in that format; everyone else gets the entry as JSON.
 #[axum::debug_handler]
 pub async fn retrieve_data(
     State(state): State<Arc<AppState>>,
     Path(blinded_id): Path<String>,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<impl IntoResponse> {
+    headers: HeaderMap,
+) -> Result<Response> {
     info!("Retrieving data with blinded ID: {}", blinded_id);
 
     // Check for requester ID and signature in query parameters
@@ -91,7 +126,26 @@ pub async fn retrieve_data(
     match entry {
         Some(entry) => {
             debug!("Entry found with ID: {}", blinded_id);
-            Ok((StatusCode::OK, Json(entry)))
+            let Some(accepted) = header_format(&headers, header::ACCEPT) else {
+                return Ok((StatusCode::OK, Json(entry)).into_response());
+            };
+
+            match entry.metadata.get("content_type") {
+                Some(stored) if stored != accepted.content_type() => {
+                    Err(StorageNodeError::InvalidInput(format!(
+                        "Entry {} is stored as {}, not {}",
+                        blinded_id,
+                        stored,
+                        accepted.content_type()
+                    )))
+                }
+                _ => Ok((
+                    StatusCode::OK,
+                    [(header::CONTENT_TYPE, accepted.content_type())],
+                    entry.encrypted_payload,
+                )
+                    .into_response()),
+            }
         }
         None => {
             debug!("Entry not found with ID: {}", blinded_id);
@@ -104,18 +158,19 @@ pub async fn retrieve_data(
 }
 
 /// Delete data handler
+///
+/// On nodes that restrict writes, only the entry's owner may delete it.
 #[axum::debug_handler]
 pub async fn delete_data(
     State(state): State<Arc<AppState>>,
     Path(blinded_id): Path<String>,
-    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
     info!("Deleting data with blinded ID: {}", blinded_id);
 
-    // Check for signature in query parameters
-    let _signature = params.get("signature").cloned();
-
-    // TODO: Verify signature if provided
+    if let Some(entry) = state.storage.retrieve(&blinded_id).await? {
+        authorize_entry_write(&state, &headers, &entry)?;
+    }
 
     // Delete entry
     let deleted = state.storage.delete(&blinded_id).await?;
@@ -233,3 +288,11 @@ pub async fn health_check() -> impl IntoResponse {
         })),
     )
 }
+
+/// Protocol version handler
+///
+/// Advertises the range of wire protocol versions this node accepts so that
+/// clients can negotiate a common version before issuing requests.
+pub async fn protocol_version() -> impl IntoResponse {
+    (StatusCode::OK, Json(ProtocolVersionRange::default()))
+}
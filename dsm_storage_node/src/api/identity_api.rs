@@ -0,0 +1,520 @@
+// Identity API for DSM Storage Node
+//
+// This module implements API handlers that let the devices sharing an identity
+// keep their state chains in sync: the identity owner registers each device it
+// owns, and devices publish the head of the chain so the others can catch up.
+// A head is only accepted when it is signed by a registered device or the owner.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::AppState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::crypto::{verify_signature_or_false, verify_with_node_key};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::{Result, StorageNodeError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::StorageEngine;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::storage_types::StorageResponse;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::BlindedStateEntry;
+#[cfg(not(target_arch = "wasm32"))]
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use dsm::types::state_types::DeviceInfo;
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::{info, warn};
+
+/// Registration of a device that shares an identity's state chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceRegistration {
+    /// The device being registered
+    pub device_info: DeviceInfo,
+
+    /// SPHINCS+ public key of the identity owner
+    pub owner_public_key: Vec<u8>,
+
+    /// Owner's signature over `device_registration_message`
+    pub owner_signature: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DeviceRegistration {
+    /// Check the owner's signature over this registration
+    pub fn verify(&self, genesis_hash: &str) -> Result<()> {
+        let message = device_registration_message(genesis_hash, &self.device_info);
+        if verify_with_node_key(&self.owner_public_key, &message, &self.owner_signature)? {
+            Ok(())
+        } else {
+            Err(StorageNodeError::Authentication(format!(
+                "Invalid owner signature for device {}",
+                self.device_info.device_id
+            )))
+        }
+    }
+}
+
+/// Message the identity owner signs to register a device
+///
+/// Binds the device ID and public key to the identity's genesis hash, so a
+/// registration cannot be replayed for another identity or device.
+pub fn device_registration_message(genesis_hash: &str, device_info: &DeviceInfo) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"DSM_DEVICE_REGISTRATION");
+    for part in [
+        genesis_hash.as_bytes(),
+        device_info.device_id.as_bytes(),
+        device_info.public_key.as_slice(),
+    ] {
+        hasher.update(&(part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().as_bytes().to_vec()
+}
+
+/// Latest state of an identity's chain, as published by one of its devices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityHead {
+    /// Number of the head state
+    pub state_number: u64,
+
+    /// Hex-encoded hash of the head state
+    pub state_hash: String,
+
+    /// Device that published the head
+    pub device_id: String,
+
+    /// Signature over `identity_head_message` by the publishing device's
+    /// registered key or the identity owner's key
+    pub signature: Vec<u8>,
+}
+
+/// Message a device signs to publish a head
+///
+/// Binds the head to the identity's genesis hash, so a head signed for one
+/// identity cannot be published for another.
+pub fn identity_head_message(
+    genesis_hash: &str,
+    state_number: u64,
+    state_hash: &str,
+    device_id: &str,
+) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"DSM_IDENTITY_HEAD");
+    hasher.update(&state_number.to_le_bytes());
+    for part in [
+        genesis_hash.as_bytes(),
+        state_hash.as_bytes(),
+        device_id.as_bytes(),
+    ] {
+        hasher.update(&(part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().as_bytes().to_vec()
+}
+
+/// Storage ID of the public key that registered an identity's first device
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn owner_blinded_id(genesis_hash: &str) -> String {
+    format!("identity:{}:owner", genesis_hash)
+}
+
+/// Storage ID of a device registration
+#[cfg(not(target_arch = "wasm32"))]
+fn device_blinded_id(genesis_hash: &str, device_id: &str) -> String {
+    format!("identity:{}:device:{}", genesis_hash, device_id)
+}
+
+/// Storage ID of an identity's chain head
+#[cfg(not(target_arch = "wasm32"))]
+fn head_blinded_id(genesis_hash: &str) -> String {
+    format!("identity:{}:head", genesis_hash)
+}
+
+/// Wrap an identity record for storage
+#[cfg(not(target_arch = "wasm32"))]
+fn identity_entry(blinded_id: String, kind: &str, payload: Vec<u8>) -> BlindedStateEntry {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    BlindedStateEntry {
+        blinded_id,
+        proof_hash: *blake3::hash(&payload).as_bytes(),
+        encrypted_payload: payload,
+        timestamp: now,
+        ttl: 0,
+        region: "global".to_string(),
+        priority: 1,
+        metadata: HashMap::from([("type".to_string(), kind.to_string())]),
+    }
+}
+
+/// Register a device owned by an identity
+///
+/// The owner's key is also registered as the key the identity signs storage
+/// receipts with.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn register_device(
+    State(state): State<Arc<AppState>>,
+    Path(genesis_hash): Path<String>,
+    Json(registration): Json<DeviceRegistration>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Registering device {} for identity {}",
+        registration.device_info.device_id, genesis_hash
+    );
+
+    let owner_public_key = registration.owner_public_key.clone();
+    let response =
+        store_device_registration(state.storage.as_ref(), &genesis_hash, registration).await?;
+
+    // The owner key now pinned for the identity is the one its receipts are
+    // checked against
+    state
+        .staking_service
+        .register_identity_key(&genesis_hash, owner_public_key)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Verify and store a device registration
+///
+/// The first registration pins the owner's public key for the identity; later
+/// registrations must be signed with the same key.
+#[cfg(not(target_arch = "wasm32"))]
+async fn store_device_registration(
+    storage: &(dyn StorageEngine + Send + Sync),
+    genesis_hash: &str,
+    registration: DeviceRegistration,
+) -> Result<StorageResponse> {
+    if registration.device_info.device_id.is_empty() {
+        return Err(StorageNodeError::InvalidInput(
+            "Device ID cannot be empty".into(),
+        ));
+    }
+
+    registration.verify(genesis_hash)?;
+
+    let owner_id = owner_blinded_id(genesis_hash);
+    match storage.retrieve(&owner_id).await? {
+        Some(owner) if owner.encrypted_payload != registration.owner_public_key => {
+            return Err(StorageNodeError::Authentication(format!(
+                "Identity {} is registered to a different owner",
+                genesis_hash
+            )));
+        }
+        Some(_) => {}
+        None => {
+            storage
+                .store(identity_entry(
+                    owner_id,
+                    "identity_owner",
+                    registration.owner_public_key.clone(),
+                ))
+                .await?;
+        }
+    }
+
+    let blinded_id = device_blinded_id(genesis_hash, &registration.device_info.device_id);
+    storage
+        .store(identity_entry(
+            blinded_id,
+            "device_registration",
+            bincode::serialize(&registration)?,
+        ))
+        .await
+}
+
+/// List the devices registered for an identity
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn list_devices(
+    State(state): State<Arc<AppState>>,
+    Path(genesis_hash): Path<String>,
+) -> Result<impl IntoResponse> {
+    let prefix = device_blinded_id(&genesis_hash, "");
+
+    let mut devices = Vec::new();
+    for id in state.storage.list(None, None).await? {
+        if !id.starts_with(&prefix) {
+            continue;
+        }
+        if let Some(entry) = state.storage.retrieve(&id).await? {
+            match bincode::deserialize::<DeviceRegistration>(&entry.encrypted_payload) {
+                Ok(registration) => devices.push(registration),
+                Err(_) => warn!("Failed to deserialize device registration: {}", id),
+            }
+        }
+    }
+
+    Ok((StatusCode::OK, Json(devices)))
+}
+
+/// Get the head of an identity's chain, or `null` if none was published
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn get_identity_head(
+    State(state): State<Arc<AppState>>,
+    Path(genesis_hash): Path<String>,
+) -> Result<impl IntoResponse> {
+    let head = load_identity_head(state.storage.as_ref(), &genesis_hash).await?;
+
+    Ok((StatusCode::OK, Json(head)))
+}
+
+/// Publish a new head for an identity's chain
+///
+/// The head must be signed by the device it names, as registered for the
+/// identity, or by the identity owner.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn update_identity_head(
+    State(state): State<Arc<AppState>>,
+    Path(genesis_hash): Path<String>,
+    Json(head): Json<IdentityHead>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Device {} publishing state {} for identity {}",
+        head.device_id, head.state_number, genesis_hash
+    );
+
+    let response = advance_identity_head(state.storage.as_ref(), &genesis_hash, head).await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn load_identity_head(
+    storage: &(dyn StorageEngine + Send + Sync),
+    genesis_hash: &str,
+) -> Result<Option<IdentityHead>> {
+    match storage.retrieve(&head_blinded_id(genesis_hash)).await? {
+        Some(entry) => Ok(Some(bincode::deserialize(&entry.encrypted_payload)?)),
+        None => Ok(None),
+    }
+}
+
+/// Check that a head is signed by its device's registered key or the owner's key
+#[cfg(not(target_arch = "wasm32"))]
+async fn verify_identity_head(
+    storage: &(dyn StorageEngine + Send + Sync),
+    genesis_hash: &str,
+    head: &IdentityHead,
+) -> Result<()> {
+    let mut keys = Vec::new();
+    if let Some(entry) = storage
+        .retrieve(&device_blinded_id(genesis_hash, &head.device_id))
+        .await?
+    {
+        let registration: DeviceRegistration = bincode::deserialize(&entry.encrypted_payload)?;
+        keys.push(registration.device_info.public_key);
+    }
+    if let Some(owner) = storage.retrieve(&owner_blinded_id(genesis_hash)).await? {
+        keys.push(owner.encrypted_payload);
+    }
+
+    let message = identity_head_message(
+        genesis_hash,
+        head.state_number,
+        &head.state_hash,
+        &head.device_id,
+    );
+    // A registered key that is not a SPHINCS+ key simply fails to verify
+    let signed = keys
+        .iter()
+        .any(|key| verify_signature_or_false(key, &message, &head.signature));
+
+    if signed {
+        Ok(())
+    } else {
+        Err(StorageNodeError::Authentication(format!(
+            "Head for identity {} is not signed by device {} or the identity owner",
+            genesis_hash, head.device_id
+        )))
+    }
+}
+
+/// Replace an identity's head with a later state
+///
+/// The head must carry a valid signature (see `verify_identity_head`), and it
+/// may only move forward, so a device that has fallen behind cannot overwrite
+/// the states other devices have already published.
+#[cfg(not(target_arch = "wasm32"))]
+async fn advance_identity_head(
+    storage: &(dyn StorageEngine + Send + Sync),
+    genesis_hash: &str,
+    head: IdentityHead,
+) -> Result<StorageResponse> {
+    if hex::decode(&head.state_hash).is_err() {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "State hash {} is not hex-encoded",
+            head.state_hash
+        )));
+    }
+
+    verify_identity_head(storage, genesis_hash, &head).await?;
+
+    if let Some(current) = load_identity_head(storage, genesis_hash).await? {
+        if current.state_number >= head.state_number {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Identity {} is already at state {}; cannot publish state {}",
+                genesis_hash, current.state_number, head.state_number
+            )));
+        }
+    }
+
+    storage
+        .store(identity_entry(
+            head_blinded_id(genesis_hash),
+            "identity_head",
+            bincode::serialize(&head)?,
+        ))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dsm::crypto::sphincs;
+
+    fn signed_registration(
+        genesis_hash: &str,
+        device_id: &str,
+        (public_key, secret_key): &(Vec<u8>, Vec<u8>),
+    ) -> DeviceRegistration {
+        let device_info = DeviceInfo::new(device_id, vec![7; 32]);
+        let message = device_registration_message(genesis_hash, &device_info);
+        DeviceRegistration {
+            device_info,
+            owner_public_key: public_key.clone(),
+            owner_signature: sphincs::sphincs_sign(secret_key, &message).unwrap(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_registration_bound_to_identity() {
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        let registration = signed_registration("genesis", "phone", &owner);
+
+        assert!(registration.verify("genesis").is_ok());
+        assert!(registration.verify("other-genesis").is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_devices_must_share_owner() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        let intruder = sphincs::generate_sphincs_keypair().unwrap();
+
+        for device_id in ["laptop", "phone"] {
+            let registration = signed_registration("genesis", device_id, &owner);
+            store_device_registration(&storage, "genesis", registration)
+                .await
+                .unwrap();
+        }
+
+        let hijack = signed_registration("genesis", "stolen", &intruder);
+        assert!(matches!(
+            store_device_registration(&storage, "genesis", hijack).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+    }
+
+    fn signed_head(state_number: u64, device_id: &str, secret_key: &[u8]) -> IdentityHead {
+        let state_hash = hex::encode([state_number as u8; 32]);
+        let message = identity_head_message("genesis", state_number, &state_hash, device_id);
+        IdentityHead {
+            state_number,
+            state_hash,
+            device_id: device_id.to_string(),
+            signature: sphincs::sphincs_sign(secret_key, &message).unwrap(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_identity_head_only_moves_forward() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        let registration = signed_registration("genesis", "laptop", &owner);
+        store_device_registration(&storage, "genesis", registration)
+            .await
+            .unwrap();
+        let head = |state_number: u64| signed_head(state_number, "laptop", &owner.1);
+
+        assert_eq!(load_identity_head(&storage, "genesis").await.unwrap(), None);
+
+        advance_identity_head(&storage, "genesis", head(3))
+            .await
+            .unwrap();
+        advance_identity_head(&storage, "genesis", head(5))
+            .await
+            .unwrap();
+        assert!(advance_identity_head(&storage, "genesis", head(5))
+            .await
+            .is_err());
+        assert!(advance_identity_head(&storage, "genesis", head(4))
+            .await
+            .is_err());
+
+        assert_eq!(
+            load_identity_head(&storage, "genesis").await.unwrap(),
+            Some(head(5))
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_identity_head_requires_registered_signer() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let owner = sphincs::generate_sphincs_keypair().unwrap();
+        let intruder = sphincs::generate_sphincs_keypair().unwrap();
+
+        // Nobody may publish before the identity has a registered device
+        let early = signed_head(1, "laptop", &owner.1);
+        assert!(matches!(
+            advance_identity_head(&storage, "genesis", early).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        let registration = signed_registration("genesis", "laptop", &owner);
+        store_device_registration(&storage, "genesis", registration)
+            .await
+            .unwrap();
+
+        let forged = signed_head(2, "laptop", &intruder.1);
+        assert!(matches!(
+            advance_identity_head(&storage, "genesis", forged).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        // A signature cannot be moved to a different state
+        let mut tampered = signed_head(2, "laptop", &owner.1);
+        tampered.state_number = 9;
+        assert!(advance_identity_head(&storage, "genesis", tampered)
+            .await
+            .is_err());
+
+        let genuine = signed_head(2, "laptop", &owner.1);
+        advance_identity_head(&storage, "genesis", genuine)
+            .await
+            .unwrap();
+    }
+}
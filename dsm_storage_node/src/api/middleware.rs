@@ -3,17 +3,20 @@
 // This module implements middleware for the API server, including authentication,
 // rate limiting, and request/response logging.
 
+use crate::api::AppState;
+use crate::client::PROTOCOL_VERSION_HEADER;
 use crate::error::{Result, StorageNodeError};
+use crate::types::{ProtocolVersion, ProtocolVersionRange};
 use axum::{
     body::Body,
     extract::State,
-    http::{HeaderMap, Request, StatusCode},
+    http::{HeaderMap, Method, Request, StatusCode},
     middleware::Next,
     response::{Response, IntoResponse},
 };
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{debug, warn};
 
 /// Rate limiter for API requests
@@ -199,3 +202,125 @@ pub async fn log_request(request: Request<Body>, next: Next<Body>) -> Response {
 
     response
 }
+
+/// Emergency pause middleware
+///
+/// Rejects every state-mutating request with 503 while the node is paused.
+/// Reads and the admin endpoints that lift the pause are still served.
+/// Restoring a backup is a write like any other and waits for the resume.
+pub async fn reject_writes_while_paused(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let is_read = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    let lifts_pause = matches!(request.uri().path(), "/admin/pause" | "/admin/resume");
+    if is_read || lifts_pause {
+        return next.run(request).await;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    match state.emergency_pause.active_pause(now) {
+        Some(pause) => {
+            warn!(
+                "Rejected {} {} during emergency pause",
+                request.method(),
+                request.uri()
+            );
+            StorageNodeError::Paused(pause.reason).into_response()
+        }
+        None => next.run(request).await,
+    }
+}
+
+/// Protocol version middleware
+///
+/// Rejects requests whose `X-DSM-Protocol-Version` header names a version
+/// outside the range this node supports with 426, and unparsable headers
+/// with 400. Requests without the header (such as the `/version` probe a
+/// client sends before negotiating) are served unchanged.
+pub async fn reject_unsupported_protocol_version(
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let Some(header) = request.headers().get(PROTOCOL_VERSION_HEADER) else {
+        return next.run(request).await;
+    };
+
+    let Some(version) = header.to_str().ok().and_then(ProtocolVersion::parse) else {
+        return StorageNodeError::InvalidInput(format!(
+            "Malformed {} header",
+            PROTOCOL_VERSION_HEADER
+        ))
+        .into_response();
+    };
+
+    let supported = ProtocolVersionRange::default();
+    if !supported.contains(version) {
+        warn!(
+            "Rejected {} {} speaking protocol {}",
+            request.method(),
+            request.uri(),
+            version
+        );
+        return StorageNodeError::VersionIncompatible {
+            client: ProtocolVersionRange {
+                min_version: version,
+                max_version: version,
+            },
+            server: supported,
+        }
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::api_server;
+    use crate::types::MAX_PROTOCOL_VERSION;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use super::PROTOCOL_VERSION_HEADER;
+
+    async fn health(router: &Router, version: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/health");
+        if let Some(version) = version {
+            request = request.header(PROTOCOL_VERSION_HEADER, version);
+        }
+
+        let request = request.body(Body::empty()).unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_protocol_versions_rejected() {
+        let router = api_server().create_router();
+
+        assert_eq!(health(&router, None).await, StatusCode::OK);
+        assert_eq!(
+            health(&router, Some(&MAX_PROTOCOL_VERSION.to_string())).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            health(&router, Some("999.0.0")).await,
+            StatusCode::UPGRADE_REQUIRED
+        );
+        assert_eq!(
+            health(&router, Some("0.1.0")).await,
+            StatusCode::UPGRADE_REQUIRED
+        );
+        assert_eq!(health(&router, Some("one")).await, StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,44 +1,81 @@
 // API module for DSM Storage Node
 //
-// This module implements the HTTP API for the storage node
+// This module implements the HTTP API for the storage node. On wasm32 targets
+// only the request and response types shared with the client are built.
 
+#[cfg(not(target_arch = "wasm32"))]
 use crate::error::{Result, StorageNodeError};
 // Removed unused import
 
+#[cfg(not(target_arch = "wasm32"))]
 use crate::staking::StakingService;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::NodeGenesisConfig;
+#[cfg(not(target_arch = "wasm32"))]
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
+#[cfg(not(target_arch = "wasm32"))]
 use serde::Serialize;
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
 use tower_http::trace::TraceLayer;
+#[cfg(not(target_arch = "wasm32"))]
 use tracing::info;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod acl_api;
+mod admin_api;
+mod backup_api;
+mod checkpoint_api;
+#[cfg(not(target_arch = "wasm32"))]
 mod handlers;
+mod identity_api;
+#[cfg(not(target_arch = "wasm32"))]
 mod middleware;
+#[cfg(not(target_arch = "wasm32"))]
 mod mpc_api;
+#[cfg(not(target_arch = "wasm32"))]
 mod rewards_api;
 mod unilateral_api;
+#[cfg(not(target_arch = "wasm32"))]
 mod vault_api;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use acl_api::*;
+pub use admin_api::*;
+pub use backup_api::*;
+pub use checkpoint_api::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub use handlers::*;
+pub use identity_api::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub use mpc_api::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub use rewards_api::*;
 pub use unilateral_api::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub use vault_api::*;
 
 /// Application state shared with all routes
+#[cfg(not(target_arch = "wasm32"))]
 #[derive(Clone)]
 pub struct AppState {
     /// Storage engine
     pub storage: Arc<dyn crate::storage::StorageEngine + Send + Sync>,
     /// Staking service
     pub staking_service: Arc<StakingService>,
+    /// Emergency pause status
+    pub emergency_pause: Arc<EmergencyPauseState>,
+    /// Per-identity write access control
+    pub write_acl: Arc<WriteAclState>,
 }
 /// API Error response
+#[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Serialize)]
 pub struct ApiError {
     /// Error message
@@ -49,6 +86,7 @@ pub struct ApiError {
     pub details: Option<serde_json::Value>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let status = match self.code.as_str() {
@@ -58,6 +96,8 @@ fn into_response(self) -> Response {
             "FORBIDDEN" => StatusCode::FORBIDDEN,
             "CONFLICT" => StatusCode::CONFLICT,
             "TOO_MANY_REQUESTS" => StatusCode::TOO_MANY_REQUESTS,
+            "GONE" => StatusCode::GONE,
+            "SERVICE_UNAVAILABLE" => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -68,6 +108,7 @@ fn into_response(self) -> Response {
 }
 
 /// Convert StorageNodeError to an API error
+#[cfg(not(target_arch = "wasm32"))]
 impl From<StorageNodeError> for ApiError {
     fn from(err: StorageNodeError) -> Self {
         let (code, message) = match err {
@@ -84,9 +125,11 @@ fn from(err: StorageNodeError) -> Self {
             StorageNodeError::Storage(msg) => ("STORAGE_ERROR", msg),
             StorageNodeError::Config(msg) => ("CONFIG_ERROR", msg),
             StorageNodeError::Encryption(msg) => ("ENCRYPTION_ERROR", msg),
+            StorageNodeError::Signing(msg) => ("SIGNING_ERROR", msg),
             StorageNodeError::Distribution(msg) => ("DISTRIBUTION_ERROR", msg),
             StorageNodeError::NodeManagement(msg) => ("NODE_MANAGEMENT_ERROR", msg),
             StorageNodeError::Staking(msg) => ("STAKING_ERROR", msg),
+            StorageNodeError::InvalidVaultPost(msg) => ("INVALID_VAULT_POST", msg),
             StorageNodeError::Authentication(msg) => ("AUTHENTICATION_ERROR", msg),
             StorageNodeError::InvalidState(msg) => ("INVALID_STATE", msg),
             StorageNodeError::Database(msg) => ("DATABASE_ERROR", msg),
@@ -104,10 +147,15 @@ fn from(err: StorageNodeError) -> Self {
             StorageNodeError::ReceiveFailure(msg) => ("RECEIVE_FAILURE", msg),
             StorageNodeError::InvalidOperation(msg) => ("INVALID_OPERATION", msg),
             StorageNodeError::InvalidInput(msg) => ("INVALID_INPUT", msg),
+            StorageNodeError::Expired(msg) => ("GONE", msg),
+            StorageNodeError::Paused(msg) => ("SERVICE_UNAVAILABLE", msg),
             StorageNodeError::ConcurrencyLimitExceeded => (
                 "CONCURRENCY_LIMIT_EXCEEDED",
                 "Concurrency limit exceeded".to_string(),
             ),
+            err @ StorageNodeError::VersionIncompatible { .. } => {
+                ("VERSION_INCOMPATIBLE", err.to_string())
+            }
         };
 
         Self {
@@ -119,6 +167,7 @@ fn from(err: StorageNodeError) -> Self {
 }
 
 /// API Server
+#[cfg(not(target_arch = "wasm32"))]
 pub struct ApiServer {
     /// Application state
     app_state: Arc<AppState>,
@@ -126,6 +175,7 @@ pub struct ApiServer {
     bind_address: String,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl ApiServer {
     /// Create a new API server
     pub fn new(
@@ -136,6 +186,8 @@ pub fn new(
         let app_state = Arc::new(AppState {
             storage,
             staking_service,
+            emergency_pause: Arc::new(EmergencyPauseState::default()),
+            write_acl: Arc::new(WriteAclState::default()),
         });
 
         Self {
@@ -144,6 +196,32 @@ pub fn new(
         }
     }
 
+    /// Apply the node's genesis configuration
+    ///
+    /// Without an emergency authority in the genesis configuration, the node
+    /// cannot be paused.
+    pub fn with_genesis_config(mut self, genesis: NodeGenesisConfig) -> Self {
+        self.app_state = Arc::new(AppState {
+            emergency_pause: Arc::new(EmergencyPauseState::new(
+                genesis.emergency_authority_public_key,
+            )),
+            ..(*self.app_state).clone()
+        });
+        self
+    }
+
+    /// Require a write permit signed with the node's ACL key for inbox and
+    /// vault writes
+    ///
+    /// Without an ACL key, any identity may write.
+    pub fn with_write_acl(mut self, write_acl: WriteAclState) -> Self {
+        self.app_state = Arc::new(AppState {
+            write_acl: Arc::new(write_acl),
+            ..(*self.app_state).clone()
+        });
+        self
+    }
+
     /// Start the API server
     pub async fn start(&self) -> Result<()> {
         // Create router with routes
@@ -167,10 +245,11 @@ pub async fn start(&self) -> Result<()> {
     }
 
     /// Create the API router
-    fn create_router(&self) -> Router {
+    pub(crate) fn create_router(&self) -> Router {
         // Build the router
         Router::new()
             .route("/health", get(handlers::health_check))
+            .route("/version", get(handlers::protocol_version))
             .route("/stats", get(handlers::node_stats))
             // General data storage
             .route("/data", post(handlers::store_data))
@@ -178,15 +257,36 @@ fn create_router(&self) -> Router {
             .route("/data/:blinded_id", delete(handlers::delete_data))
             .route("/data/:blinded_id/exists", get(handlers::exists_data))
             .route("/data", get(handlers::list_data))
+            // Checkpoints and state history
+            .route("/checkpoint", post(store_checkpoint))
+            .route("/state/:state_hash", delete(delete_state))
+            // Devices sharing an identity
+            .route(
+                "/identity/:genesis_hash/devices",
+                post(register_device).get(list_devices),
+            )
+            .route(
+                "/identity/:genesis_hash/head",
+                get(get_identity_head).put(update_identity_head),
+            )
+            // Per-identity write permits
+            .route("/acl/challenge", get(issue_write_permit_challenge))
+            .route("/acl/permit", post(issue_write_permit))
             // Unilateral transaction inbox
             .route("/inbox", post(store_inbox_entry))
+            .route("/inbox/broadcast", post(broadcast_inbox_entry))
             .route("/inbox/:recipient_genesis", get(get_inbox_entries))
             .route(
                 "/inbox/:recipient_genesis/:entry_id",
-                delete(delete_inbox_entry),
+                get(get_inbox_entry).delete(delete_inbox_entry),
+            )
+            .route(
+                "/inbox/:recipient_genesis/:entry_id/ack",
+                put(acknowledge_inbox_entry),
             )
             // Vault API
             .route("/vault", post(store_vault))
+            .route("/vault/search", post(search_vaults))
             .route("/vault/:vault_id", get(get_vault))
             .route("/vault/creator/:creator_id", get(get_vaults_by_creator))
             .route(
@@ -194,8 +294,31 @@ fn create_router(&self) -> Router {
                 get(get_vaults_by_recipient),
             )
             .route("/vault/:vault_id/status", put(update_vault_status))
+            .route("/vault/:vault_id/extend", post(extend_vault_timelock))
+            .route("/vault/:vault_id/owner", post(transfer_vault_ownership))
+            .route("/vault/:vault_id/recipient", put(reassign_vault_recipient))
+            .route("/vault/:vault_id/content", put(update_vault_content))
             // Rewards API
             .merge(rewards_api::rewards_routes())
+            // Emergency pause
+            .route(
+                "/admin/pause",
+                post(submit_emergency_pause).get(get_emergency_pause),
+            )
+            .route("/admin/resume", post(submit_emergency_resume))
+            // Disaster recovery
+            .route("/admin/backup", get(create_backup))
+            .route(
+                "/admin/restore",
+                post(restore_backup).layer(axum::extract::DefaultBodyLimit::max(MAX_RESTORE_SIZE)),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                self.app_state.clone(),
+                middleware::reject_writes_while_paused,
+            ))
+            .layer(axum::middleware::from_fn(
+                middleware::reject_unsupported_protocol_version,
+            ))
             // Share application state
             .with_state(self.app_state.clone())
     }
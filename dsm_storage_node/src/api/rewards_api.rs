@@ -3,12 +3,18 @@
 // This module implements the API endpoints for reward management
 // using the Deterministic Limbo Vault (DLV) system.
 
-use crate::error::Result;
-use crate::staking::rewards::{RateSchedule, Ratio, StorageMetrics, StorageReceipt};
+use crate::error::{Result, StorageNodeError};
+use crate::staking::receipt_exchange::ReceiptChallenge;
+use crate::staking::report::ReportFormat;
+use crate::staking::rewards::{
+    DistributionRecord, FailedDistribution, PendingDistribution, RateSchedule, Ratio,
+    RegionMultiplierStrategy, RewardEstimate,
+};
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
     Json,
     routing::{get, post},
     Router,
@@ -26,37 +32,78 @@
 pub fn rewards_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/rewards/receipts", post(submit_receipt))
+        .route("/rewards/heartbeats", post(record_heartbeat))
         .route("/rewards/vaults", get(list_vaults))
         .route("/rewards/vaults/:id", get(get_vault))
         .route("/rewards/schedule", get(get_rate_schedule))
         .route("/rewards/schedule", post(update_rate_schedule))
         .route("/rewards/calculate/:node_id", get(calculate_rewards))
+        .route("/rewards/estimate/:node_id", get(estimate_rewards))
+        .route("/rewards/report", get(export_report))
         .route("/rewards/vault", post(create_reward_vault))
+        .route("/rewards/distributions", get(get_distribution_history))
+        .route(
+            "/rewards/distributions/failed",
+            get(get_failed_distributions),
+        )
+        .route(
+            "/rewards/distributions/pending",
+            get(get_pending_distributions),
+        )
+        .route(
+            "/rewards/distributions/:id/schedule",
+            post(reschedule_distribution),
+        )
 }
 
-/// Storage receipt submission request
+/// Distribution history query
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReceiptSubmission {
-    /// Node ID that provided the service
-    pub node_id: String,
+pub struct DistributionHistoryQuery {
+    /// Only return distributions of this vault
+    pub vault_id: Option<String>,
+}
 
-    /// Client ID that received the service
-    pub client_id: String,
+/// New time for a pending distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RescheduleRequest {
+    /// When the distribution should happen (timestamp)
+    pub distribution_time: u64,
+}
 
-    /// Service period start (timestamp)
+/// Reward report query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportQuery {
+    /// Start of the reported period (timestamp)
     pub period_start: u64,
 
-    /// Service period end (timestamp)
+    /// End of the reported period (timestamp, exclusive)
     pub period_end: u64,
 
-    /// Storage metrics
-    pub metrics: StorageMetrics,
+    /// Output format
+    pub format: ReportFormat,
+}
+
+/// Storage receipt submission request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptSubmission {
+    /// Challenge the node issued, with its signature
+    pub challenge: ReceiptChallenge,
 
-    /// Client's signature
+    /// Client's signature over the challenge hash
     pub client_signature: Vec<u8>,
+}
 
-    /// Node's signature
-    pub node_signature: Vec<u8>,
+/// Signed liveness probe of a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatSubmission {
+    /// Node that answered the probe
+    pub node_id: String,
+
+    /// When the node answered (seconds since epoch)
+    pub timestamp: u64,
+
+    /// Prober's SPHINCS+ signature over the heartbeat
+    pub prober_signature: Vec<u8>,
 }
 
 /// Rate schedule update request
@@ -76,6 +123,10 @@ pub struct RateScheduleUpdate {
 
     /// Region multipliers
     pub region_multipliers: HashMap<String, f64>,
+
+    /// How the multipliers of a node's regions combine
+    #[serde(default)]
+    pub region_strategy: RegionMultiplierStrategy,
 }
 
 /// Reward vault creation request
@@ -105,33 +156,8 @@ async fn submit_receipt(
     State(state): State<Arc<AppState>>,
     Json(submission): Json<ReceiptSubmission>,
 ) -> Result<StatusCode> {
-    // Create a receipt hash
-    let mut hasher = blake3::Hasher::new();
-    hasher.update(submission.node_id.as_bytes());
-    hasher.update(submission.client_id.as_bytes());
-    hasher.update(&submission.period_start.to_le_bytes());
-    hasher.update(&submission.period_end.to_le_bytes());
-
-    // Add storage metrics to hash
-    let metrics_bytes =
-        bincode::serialize(&submission.metrics).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    hasher.update(&metrics_bytes);
-
-    let hash = hasher.finalize();
-    let mut hash_bytes = [0u8; 32];
-    hash_bytes.copy_from_slice(hash.as_bytes());
-
-    // Create the receipt
-    let receipt = StorageReceipt {
-        node_id: submission.node_id,
-        client_id: submission.client_id,
-        service_period: (submission.period_start, submission.period_end),
-        storage_metrics: submission.metrics,
-        receipt_hash: hash_bytes,
-        client_signature: submission.client_signature,
-        node_signature: submission.node_signature,
-    };
+    // Seal the challenge the client countersigned into the receipt
+    let receipt = submission.challenge.seal(submission.client_signature);
 
     // Process the receipt
     state.staking_service.process_receipt(receipt)?;
@@ -139,6 +165,26 @@ async fn submit_receipt(
     Ok(StatusCode::CREATED)
 }
 
+/// Record a signed liveness probe of a node
+async fn record_heartbeat(
+    State(state): State<Arc<AppState>>,
+    Json(heartbeat): Json<HeartbeatSubmission>,
+) -> Result<StatusCode> {
+    let monitor = state
+        .staking_service
+        .get_reward_manager()?
+        .heartbeat_monitor()
+        .ok_or_else(|| StorageNodeError::Staking("Heartbeat monitoring is not enabled".into()))?;
+
+    monitor.record_heartbeat(
+        &heartbeat.node_id,
+        heartbeat.timestamp,
+        &heartbeat.prober_signature,
+    )?;
+
+    Ok(StatusCode::CREATED)
+}
+
 /// List all reward vaults
 async fn list_vaults(State(state): State<Arc<AppState>>) -> Result<Json<Vec<serde_json::Value>>> {
     let reward_manager = state.staking_service.get_reward_manager()?;
@@ -198,6 +244,49 @@ async fn get_vault(
     Ok(Json(response))
 }
 
+/// List processed distributions, oldest first
+async fn get_distribution_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DistributionHistoryQuery>,
+) -> Result<Json<Vec<DistributionRecord>>> {
+    let reward_manager = state.staking_service.get_reward_manager()?;
+
+    let history = reward_manager.get_distribution_history(query.vault_id.as_deref())?;
+
+    Ok(Json(history))
+}
+
+/// List distributions that were given up on, oldest first
+async fn get_failed_distributions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<FailedDistribution>>> {
+    let reward_manager = state.staking_service.get_reward_manager()?;
+
+    Ok(Json(reward_manager.get_failed_distributions()?))
+}
+
+/// List distributions waiting to be processed, soonest due first
+async fn get_pending_distributions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<PendingDistribution>>> {
+    let reward_manager = state.staking_service.get_reward_manager()?;
+
+    Ok(Json(reward_manager.get_pending_distributions()?))
+}
+
+/// Move a pending distribution to a new time
+async fn reschedule_distribution(
+    State(state): State<Arc<AppState>>,
+    Path(vault_id): Path<String>,
+    Json(request): Json<RescheduleRequest>,
+) -> Result<StatusCode> {
+    let reward_manager = state.staking_service.get_reward_manager()?;
+
+    reward_manager.reschedule_distribution(&vault_id, request.distribution_time)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get current rate schedule
 async fn get_rate_schedule(
     State(_state): State<Arc<AppState>>,
@@ -210,6 +299,7 @@ async fn get_rate_schedule(
         operation_rate: 5,
         uptime_multiplier: 1.0,
         region_multipliers: HashMap::new(),
+        region_strategy: RegionMultiplierStrategy::Max,
     };
 
     Ok(Json(schedule))
@@ -227,8 +317,13 @@ async fn update_rate_schedule(
         base_rate_per_byte_day: update.base_rate_per_byte_day,
         retrieval_rate: update.retrieval_rate,
         operation_rate: update.operation_rate,
-        uptime_multiplier: update.uptime_multiplier,
-        region_multipliers: update.region_multipliers,
+        uptime_multiplier: Ratio::multiplier(update.uptime_multiplier)?,
+        region_multipliers: update
+            .region_multipliers
+            .into_iter()
+            .map(|(region, multiplier)| Ok((region, Ratio::multiplier(multiplier)?)))
+            .collect::<Result<_>>()?,
+        region_strategy: update.region_strategy,
     };
 
     // Update the schedule
@@ -253,18 +348,53 @@ async fn calculate_rewards(
     let period_start = now - (30 * 86400); // 30 days ago
     let period_end = now;
 
-    let rewards = reward_manager.calculate_node_rewards(&node_id, period_start, period_end)?;
+    let rewards = reward_manager
+        .calculate_node_rewards(&node_id, period_start, period_end)
+        .await?;
+    let breakdown = reward_manager.node_reward_breakdown(&node_id, period_start, period_end)?;
+    let pending_carryover = reward_manager.pending_carryover(&node_id)?;
 
     let response = serde_json::json!({
         "node_id": node_id,
         "period_start": period_start,
         "period_end": period_end,
         "calculated_rewards": rewards,
+        "breakdown": breakdown,
+        "pending_carryover": pending_carryover,
     });
 
     Ok(Json(response))
 }
 
+/// Estimate the rewards a node has accrued since the last distribution
+async fn estimate_rewards(
+    State(state): State<Arc<AppState>>,
+    Path(node_id): Path<String>,
+) -> Result<Json<RewardEstimate>> {
+    let reward_manager = state.staking_service.get_reward_manager()?;
+
+    Ok(Json(
+        reward_manager.estimate_current_rewards(&node_id).await?,
+    ))
+}
+
+/// Export a report of the rewards earned and paid out in a period
+async fn export_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<impl IntoResponse> {
+    let reward_manager = state.staking_service.get_reward_manager()?;
+
+    let report =
+        reward_manager.export_report(query.period_start, query.period_end, query.format)?;
+    let content_type = match query.format {
+        ReportFormat::Json => "application/json",
+        ReportFormat::Csv => "text/csv",
+    };
+
+    Ok(([(header::CONTENT_TYPE, content_type)], report))
+}
+
 /// Create a reward vault
 async fn create_reward_vault(
     State(state): State<Arc<AppState>>,
@@ -275,7 +405,7 @@ async fn create_reward_vault(
     // Convert percentages to ratios
     let mut recipients = HashMap::new();
     for (node_id, percentage) in &request.recipients {
-        recipients.insert(node_id.clone(), Ratio::new(*percentage / 100.0));
+        recipients.insert(node_id.clone(), Ratio::try_new(*percentage / 100.0)?);
     }
 
     // Create a reference state (simplified for demo)
@@ -2,19 +2,57 @@
 //
 // This module implements API handlers for unilateral transaction inbox functionality.
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::acl_api::authorize_write;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::api::AppState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::platform::now_secs;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::error::{Result, StorageNodeError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::StorageEngine;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::storage_types::StorageResponse;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::types::BlindedStateEntry;
+#[cfg(not(target_arch = "wasm32"))]
 use axum::{
     extract::{Json, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
+use dsm::crypto::ring::{verify_ring_signature, RingSignature};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
 use tracing::{info, warn};
 
+/// Priority of inbox entries stored without one
+pub const DEFAULT_INBOX_PRIORITY: u8 = 128;
+
+fn default_inbox_priority() -> u8 {
+    DEFAULT_INBOX_PRIORITY
+}
+
+/// Sender genesis hash of entries whose sender is only proven by a ring signature
+pub const ANONYMOUS_SENDER: &str = "anonymous";
+
+/// Domain separator for the message an anonymous sender's ring signature covers
+const RING_MESSAGE_DOMAIN: &[u8] = b"DSM_INBOX_RING_SENDER";
+
+/// Domain separator for the IDs of entries from anonymous senders
+const RING_ENTRY_ID_DOMAIN: &[u8] = b"DSM_INBOX_RING_ENTRY_ID";
+
+/// Append a length-prefixed field to a message
+fn push_field(message: &mut Vec<u8>, field: &[u8]) {
+    message.extend_from_slice(&(field.len() as u64).to_le_bytes());
+    message.extend_from_slice(field);
+}
+
 /// Unilateral transaction inbox entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InboxEntry {
@@ -41,6 +79,143 @@ pub struct InboxEntry {
 
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+
+    /// Seconds an acknowledged entry stays hidden from the recipient's inbox
+    /// before it is delivered again, unless deleted (0 = until deleted)
+    #[serde(default)]
+    pub ack_deadline_secs: u64,
+
+    /// Delivery priority, from 0 (lowest) to 255 (highest)
+    #[serde(default = "default_inbox_priority")]
+    pub priority: u8,
+
+    /// Proof that the sender is one of a ring of keys known to the recipient
+    ///
+    /// When present, `sender_genesis_hash` is [`ANONYMOUS_SENDER`] and `id`
+    /// is [`InboxEntry::ring_entry_id`] of the signature.
+    #[serde(default)]
+    pub ring_signature: Option<RingSignature>,
+}
+
+impl InboxEntry {
+    /// Whether this entry has expired at the given time (seconds since epoch)
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at != 0 && self.expires_at < now
+    }
+
+    /// Whether an acknowledgment made at `acked_at` still holds at `now`
+    ///
+    /// Once it lapses, the recipient is assumed to have failed to process the
+    /// entry and it is delivered again.
+    pub fn is_ack_pending_at(&self, acked_at: u64, now: u64) -> bool {
+        self.ack_deadline_secs == 0 || now < acked_at.saturating_add(self.ack_deadline_secs)
+    }
+
+    /// Content-addressed ID for a transaction from a sender
+    ///
+    /// The ID is `hex(blake3(sender_genesis_hash || blake3(transaction)))`, so
+    /// delivering the same transaction again always yields the same ID.
+    pub fn content_id(sender_genesis_hash: &str, transaction: &[u8]) -> String {
+        let operation_hash = blake3::hash(transaction);
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(sender_genesis_hash.as_bytes());
+        hasher.update(operation_hash.as_bytes());
+        hex::encode(hasher.finalize().as_bytes())
+    }
+
+    /// ID of an entry from an anonymous sender
+    ///
+    /// Anonymous entries all share one sender, so the ID is derived from the
+    /// ring signature instead of the transaction. Two entries carrying the
+    /// same transaction therefore do not collide.
+    pub fn ring_entry_id(signature: &RingSignature) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(RING_ENTRY_ID_DOMAIN);
+        hasher.update(&signature.challenge);
+        for response in &signature.responses {
+            hasher.update(response);
+        }
+        hex::encode(hasher.finalize().as_bytes())
+    }
+
+    /// Message an anonymous sender's ring signature covers
+    ///
+    /// Covers every field except the ID, which is derived from the signature,
+    /// and the ring signature itself. The recipient, expiry, metadata and
+    /// delivery settings can therefore not be changed in transit.
+    pub fn ring_message(&self) -> Vec<u8> {
+        let mut message = RING_MESSAGE_DOMAIN.to_vec();
+        push_field(&mut message, self.sender_genesis_hash.as_bytes());
+        push_field(&mut message, self.recipient_genesis_hash.as_bytes());
+        push_field(&mut message, &self.transaction);
+        push_field(&mut message, &self.signature);
+        message.extend_from_slice(&self.timestamp.to_le_bytes());
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message.extend_from_slice(&self.ack_deadline_secs.to_le_bytes());
+        message.push(self.priority);
+
+        let mut metadata: Vec<_> = self.metadata.iter().collect();
+        metadata.sort();
+        message.extend_from_slice(&(metadata.len() as u64).to_le_bytes());
+        for (key, value) in metadata {
+            push_field(&mut message, key.as_bytes());
+            push_field(&mut message, value.as_bytes());
+        }
+        message
+    }
+
+    /// Whether the entry is from an anonymous member of `ring_public_keys`
+    ///
+    /// Entries without a ring signature, that name their sender or whose ID
+    /// does not match their signature are not.
+    pub fn verify_ring_sender(&self, ring_public_keys: &[Vec<u8>]) -> bool {
+        match &self.ring_signature {
+            Some(signature) if self.sender_genesis_hash == ANONYMOUS_SENDER => {
+                self.id == Self::ring_entry_id(signature)
+                    && verify_ring_signature(&self.ring_message(), ring_public_keys, signature)
+            }
+            _ => false,
+        }
+    }
+
+    /// Order in which entries are delivered
+    ///
+    /// Higher priorities come first, then older entries; entries received
+    /// at the same time are ordered by ID.
+    pub fn delivery_order(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then(self.timestamp.cmp(&other.timestamp))
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+/// Sort entries by ID and drop all but the first entry with each ID
+///
+/// Entry IDs are content-addressed, so entries sharing an ID are the same
+/// transaction delivered more than once.
+pub fn deduplicate_inbox(mut entries: Vec<InboxEntry>) -> Vec<InboxEntry> {
+    // A stable sort keeps the first occurrence ahead of later duplicates
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    entries.dedup_by(|later, first| later.id == first.id);
+    entries
+}
+
+/// Sort entries into delivery order, see [`InboxEntry::delivery_order`]
+pub fn sort_inbox(entries: &mut [InboxEntry]) {
+    entries.sort_by(InboxEntry::delivery_order);
+}
+
+/// Metadata key of the time an inbox entry was acknowledged
+#[cfg(not(target_arch = "wasm32"))]
+const ACKED_AT_METADATA: &str = "acked_at";
+
+/// Storage ID of an inbox entry
+#[cfg(not(target_arch = "wasm32"))]
+fn inbox_blinded_id(recipient_genesis_hash: &str, entry_id: &str) -> String {
+    format!("inbox:{}:{}", recipient_genesis_hash, entry_id)
 }
 
 /// Wrapper for inbox submission
@@ -50,14 +225,129 @@ pub struct InboxSubmission {
     pub entry: InboxEntry,
 }
 
+/// Largest number of recipients a single inbox broadcast may address
+pub const MAX_BROADCAST_RECIPIENTS: usize = 1_000;
+
+/// One inbox entry addressed to many recipients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxBroadcast {
+    /// Entry to deliver; its `recipient_genesis_hash` is replaced per recipient
+    pub entry: InboxEntry,
+
+    /// Hex-encoded genesis hashes of the recipients
+    pub recipient_genesis_hashes: Vec<String>,
+}
+
+/// Per-recipient outcome of an inbox broadcast
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastResult {
+    /// Recipients whose inbox the entry was stored in
+    pub successful: Vec<String>,
+
+    /// Recipients the entry could not be delivered to, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Store an inbox entry in the inbox of every recipient of a broadcast
+///
+/// A failed delivery is reported for its recipient and does not stop the
+/// deliveries to the others.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn broadcast_inbox_entry(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(broadcast): Json<InboxBroadcast>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Broadcasting inbox entry {} to {} recipients",
+        broadcast.entry.id,
+        broadcast.recipient_genesis_hashes.len()
+    );
+
+    authorize_write(&state, &headers, &broadcast.entry.sender_genesis_hash)?;
+
+    let result = store_inbox_broadcast(state.storage.as_ref(), broadcast).await?;
+
+    Ok((StatusCode::OK, Json(result)))
+}
+
+/// Fan an inbox broadcast out into one stored entry per recipient
+#[cfg(not(target_arch = "wasm32"))]
+async fn store_inbox_broadcast(
+    storage: &(dyn StorageEngine + Send + Sync),
+    broadcast: InboxBroadcast,
+) -> Result<BroadcastResult> {
+    if broadcast.recipient_genesis_hashes.len() > MAX_BROADCAST_RECIPIENTS {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Broadcast addresses {} recipients, at most {} are allowed",
+            broadcast.recipient_genesis_hashes.len(),
+            MAX_BROADCAST_RECIPIENTS
+        )));
+    }
+
+    // A ring signature covers the one recipient it was made for
+    if broadcast.entry.ring_signature.is_some() {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Inbox entry {} is from an anonymous sender and cannot be broadcast",
+            broadcast.entry.id
+        )));
+    }
+
+    let mut result = BroadcastResult::default();
+    for recipient in broadcast.recipient_genesis_hashes {
+        if recipient.is_empty() {
+            result.failed.push((
+                recipient,
+                "Recipient genesis hash cannot be empty".to_string(),
+            ));
+            continue;
+        }
+
+        let submission = InboxSubmission {
+            entry: InboxEntry {
+                recipient_genesis_hash: recipient.clone(),
+                ..broadcast.entry.clone()
+            },
+        };
+        match store_inbox_submission(storage, submission).await {
+            Ok(_) => result.successful.push(recipient),
+            Err(e) => {
+                warn!("Failed to deliver broadcast to {}: {}", recipient, e);
+                result.failed.push((recipient, e.to_string()));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
 /// Store an inbox entry
+#[cfg(not(target_arch = "wasm32"))]
 #[axum::debug_handler]
 pub async fn store_inbox_entry(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(submission): Json<InboxSubmission>,
 ) -> Result<impl IntoResponse> {
     info!("Storing inbox entry: {}", submission.entry.id);
 
+    authorize_write(&state, &headers, &submission.entry.sender_genesis_hash)?;
+
+    let response = store_inbox_submission(state.storage.as_ref(), submission).await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Validate and store an inbox submission
+///
+/// Storing an entry whose ID is already present and unexpired succeeds
+/// without writing anything, so retried deliveries are idempotent.
+#[cfg(not(target_arch = "wasm32"))]
+async fn store_inbox_submission(
+    storage: &(dyn StorageEngine + Send + Sync),
+    submission: InboxSubmission,
+) -> Result<StorageResponse> {
     // Validate entry
     if submission.entry.id.is_empty() {
         return Err(StorageNodeError::InvalidState(
@@ -71,23 +361,65 @@ pub async fn store_inbox_entry(
         ));
     }
 
+    // The ring is only known to the recipient, who checks the signature
+    let anonymous = submission.entry.sender_genesis_hash == ANONYMOUS_SENDER;
+    match &submission.entry.ring_signature {
+        Some(signature) if anonymous => {
+            if submission.entry.id != InboxEntry::ring_entry_id(signature) {
+                return Err(StorageNodeError::InvalidInput(format!(
+                    "Inbox entry {} is from an anonymous sender but its ID is not derived \
+                     from its ring signature",
+                    submission.entry.id
+                )));
+            }
+        }
+        None if !anonymous => {}
+        _ => {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Inbox entry {} must carry a ring signature exactly when its sender is {}",
+                submission.entry.id, ANONYMOUS_SENDER
+            )));
+        }
+    }
+
+    let now = now_secs();
+    if submission.entry.is_expired_at(now) {
+        return Err(StorageNodeError::Expired(format!(
+            "Inbox entry {} expired at {}",
+            submission.entry.id, submission.entry.expires_at
+        )));
+    }
+
+    let blinded_id = inbox_blinded_id(
+        &submission.entry.recipient_genesis_hash,
+        &submission.entry.id,
+    );
+
+    // A live entry with this ID is the same transaction delivered again
+    if let Some(existing) = storage.retrieve(&blinded_id).await? {
+        let live = bincode::deserialize::<InboxEntry>(&existing.encrypted_payload)
+            .is_ok_and(|entry| !entry.is_expired_at(now));
+        if live {
+            info!("Inbox entry {} already stored", submission.entry.id);
+            return Ok(StorageResponse {
+                blinded_id,
+                timestamp: now,
+                status: "success".to_string(),
+                message: Some("Inbox entry already stored".to_string()),
+            });
+        }
+    }
+
     // Create a BlindedStateEntry from the inbox entry
     let entry = BlindedStateEntry {
-        blinded_id: format!(
-            "inbox:{}:{}",
-            submission.entry.recipient_genesis_hash, submission.entry.id
-        ),
+        blinded_id,
         encrypted_payload: bincode::serialize(&submission.entry).map_err(|e| {
             StorageNodeError::Serialization(format!("Failed to serialize inbox entry: {}", e))
         })?,
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs()),
+        timestamp: now,
         ttl: if submission.entry.expires_at > 0 {
-            submission.entry.expires_at
-                - std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map_or(0, |d| d.as_secs())
+            // Never let an expiring entry round down to "no expiration"
+            submission.entry.expires_at.saturating_sub(now).max(1)
         } else {
             0 // No expiration
         },
@@ -122,12 +454,17 @@ pub async fn store_inbox_entry(
     };
 
     // Store the entry
-    let response = state.storage.store(entry).await?;
-
-    Ok((StatusCode::OK, Json(response)))
+    storage.store(entry).await
 }
 
 /// Get inbox entries for a recipient
+///
+/// Entries are returned in delivery order, most urgent first, and `offset`
+/// and `limit` page through that order. Entries acknowledged within their
+/// acknowledgment deadline are left out. With `unacked_only=true`, every
+/// entry ever acknowledged is left out, so entries whose acknowledgment
+/// lapsed are not delivered again.
+#[cfg(not(target_arch = "wasm32"))]
 #[axum::debug_handler]
 pub async fn get_inbox_entries(
     State(state): State<Arc<AppState>>,
@@ -145,13 +482,14 @@ pub async fn get_inbox_entries(
         .get("offset")
         .and_then(|o| o.parse::<usize>().ok())
         .unwrap_or(0);
+    let unacked_only = params.get("unacked_only").is_some_and(|u| u == "true");
 
     // Get all blinded IDs with the inbox prefix for this recipient
     let prefix = format!("inbox:{}:", recipient_genesis);
 
     // This requires a custom implementation in StorageEngine to list entries with a prefix
     // For now, we'll retrieve all entries and filter
-    let all_ids = state.storage.list(Some(limit + offset), None).await?;
+    let all_ids = state.storage.list(None, None).await?;
 
     // Filter to only include entries for this recipient
     let inbox_ids: Vec<String> = all_ids
@@ -159,38 +497,159 @@ pub async fn get_inbox_entries(
         .filter(|id| id.starts_with(&prefix))
         .collect();
 
-    // Apply pagination
-    let paginated_ids = if offset < inbox_ids.len() {
-        inbox_ids[offset..].iter().take(limit).cloned().collect()
-    } else {
-        Vec::new()
-    };
+    // The whole inbox is ordered before paging, so urgent entries lead the first page
+    let entries =
+        deliverable_entries(state.storage.as_ref(), inbox_ids, unacked_only, now_secs()).await?;
+    let page: Vec<InboxEntry> = entries.into_iter().skip(offset).take(limit).collect();
+
+    Ok((StatusCode::OK, Json(page)))
+}
 
-    // Retrieve each entry
+/// Retrieve the inbox entries to deliver at `now` among the given IDs
+///
+/// Expired entries are skipped, as are entries with a pending acknowledgment
+/// or, if `unacked_only` is set, with any acknowledgment. The entries are
+/// returned in delivery order.
+#[cfg(not(target_arch = "wasm32"))]
+async fn deliverable_entries(
+    storage: &(dyn StorageEngine + Send + Sync),
+    blinded_ids: Vec<String>,
+    unacked_only: bool,
+    now: u64,
+) -> Result<Vec<InboxEntry>> {
     let mut entries = Vec::new();
-    for id in paginated_ids {
-        if let Some(entry) = state.storage.retrieve(&id).await? {
+    for id in blinded_ids {
+        if let Some(entry) = storage.retrieve(&id).await? {
             // Deserialize the inbox entry
             if let Ok(inbox_entry) = bincode::deserialize::<InboxEntry>(&entry.encrypted_payload) {
-                entries.push(inbox_entry);
+                let hidden = match acked_at(&entry) {
+                    Some(acked_at) => unacked_only || inbox_entry.is_ack_pending_at(acked_at, now),
+                    None => false,
+                };
+                if !inbox_entry.is_expired_at(now) && !hidden {
+                    entries.push(inbox_entry);
+                }
             } else {
                 warn!("Failed to deserialize inbox entry: {}", id);
             }
         }
     }
 
-    Ok((StatusCode::OK, Json(entries)))
+    sort_inbox(&mut entries);
+    Ok(entries)
+}
+
+/// Get a single inbox entry
+///
+/// Returns 410 Gone if the entry exists but has expired.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn get_inbox_entry(
+    State(state): State<Arc<AppState>>,
+    Path((recipient_genesis, entry_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    let blinded_id = inbox_blinded_id(&recipient_genesis, &entry_id);
+    info!("Getting inbox entry: {}", blinded_id);
+
+    let entry = state.storage.retrieve(&blinded_id).await?.ok_or_else(|| {
+        StorageNodeError::NotFound(format!("Inbox entry with ID {} not found", entry_id))
+    })?;
+
+    let inbox_entry: InboxEntry = bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to deserialize inbox entry: {}", e))
+    })?;
+
+    if inbox_entry.is_expired_at(now_secs()) {
+        return Err(StorageNodeError::Expired(format!(
+            "Inbox entry {} expired at {}",
+            entry_id, inbox_entry.expires_at
+        )));
+    }
+
+    Ok((StatusCode::OK, Json(inbox_entry)))
+}
+
+/// When a stored inbox entry was acknowledged, if it was
+#[cfg(not(target_arch = "wasm32"))]
+fn acked_at(entry: &BlindedStateEntry) -> Option<u64> {
+    entry.metadata.get(ACKED_AT_METADATA)?.parse().ok()
+}
+
+/// Acknowledge an inbox entry
+///
+/// The entry is left out of inbox listings until it is deleted or its
+/// acknowledgment deadline passes. Acknowledging it again restarts the
+/// deadline. Returns 410 Gone if the entry has expired. On nodes that
+/// restrict writes, only the recipient may acknowledge its entries.
+#[cfg(not(target_arch = "wasm32"))]
+#[axum::debug_handler]
+pub async fn acknowledge_inbox_entry(
+    State(state): State<Arc<AppState>>,
+    Path((recipient_genesis, entry_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    let blinded_id = inbox_blinded_id(&recipient_genesis, &entry_id);
+    info!("Acknowledging inbox entry: {}", blinded_id);
+
+    authorize_write(&state, &headers, &recipient_genesis)?;
+
+    let now = now_secs();
+    acknowledge_stored_entry(state.storage.as_ref(), &blinded_id, &entry_id, now).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "success",
+            "message": format!("Inbox entry {} acknowledged", entry_id),
+        })),
+    ))
+}
+
+/// Record the acknowledgment of a stored inbox entry
+#[cfg(not(target_arch = "wasm32"))]
+async fn acknowledge_stored_entry(
+    storage: &(dyn StorageEngine + Send + Sync),
+    blinded_id: &str,
+    entry_id: &str,
+    now: u64,
+) -> Result<()> {
+    let mut entry = storage.retrieve(blinded_id).await?.ok_or_else(|| {
+        StorageNodeError::NotFound(format!("Inbox entry with ID {} not found", entry_id))
+    })?;
+
+    let inbox_entry: InboxEntry = bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to deserialize inbox entry: {}", e))
+    })?;
+    if inbox_entry.is_expired_at(now) {
+        return Err(StorageNodeError::Expired(format!(
+            "Inbox entry {} expired at {}",
+            entry_id, inbox_entry.expires_at
+        )));
+    }
+
+    entry
+        .metadata
+        .insert(ACKED_AT_METADATA.to_string(), now.to_string());
+    storage.store(entry).await?;
+
+    Ok(())
 }
 
 /// Delete an inbox entry
+///
+/// On nodes that restrict writes, only the recipient may delete its entries.
+#[cfg(not(target_arch = "wasm32"))]
 #[axum::debug_handler]
 pub async fn delete_inbox_entry(
     State(state): State<Arc<AppState>>,
     Path((recipient_genesis, entry_id)): Path<(String, String)>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
-    let blinded_id = format!("inbox:{}:{}", recipient_genesis, entry_id);
+    let blinded_id = inbox_blinded_id(&recipient_genesis, &entry_id);
     info!("Deleting inbox entry: {}", blinded_id);
 
+    authorize_write(&state, &headers, &recipient_genesis)?;
+
     // Delete the entry
     let deleted = state.storage.delete(&blinded_id).await?;
 
@@ -209,3 +668,263 @@ pub async fn delete_inbox_entry(
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_expiring_at(expires_at: u64) -> InboxEntry {
+        InboxEntry {
+            id: "entry".to_string(),
+            sender_genesis_hash: "sender".to_string(),
+            recipient_genesis_hash: "recipient".to_string(),
+            transaction: vec![1, 2, 3],
+            signature: vec![4, 5, 6],
+            timestamp: 100,
+            expires_at,
+            metadata: HashMap::new(),
+            ack_deadline_secs: 0,
+            priority: DEFAULT_INBOX_PRIORITY,
+            ring_signature: None,
+        }
+    }
+
+    #[test]
+    fn test_entry_without_expiry_never_expires() {
+        assert!(!entry_expiring_at(0).is_expired_at(u64::MAX));
+    }
+
+    #[test]
+    fn test_entry_expiry_boundary() {
+        let entry = entry_expiring_at(200);
+        assert!(!entry.is_expired_at(199));
+        assert!(!entry.is_expired_at(200));
+        assert!(entry.is_expired_at(201));
+    }
+
+    fn submission(transaction: &[u8], timestamp: u64) -> InboxSubmission {
+        InboxSubmission {
+            entry: InboxEntry {
+                id: InboxEntry::content_id("sender", transaction),
+                transaction: transaction.to_vec(),
+                timestamp,
+                ..entry_expiring_at(0)
+            },
+        }
+    }
+
+    #[test]
+    fn test_content_id_depends_on_sender_and_transaction() {
+        let id = InboxEntry::content_id("sender", &[1, 2, 3]);
+        assert_eq!(id, InboxEntry::content_id("sender", &[1, 2, 3]));
+        assert_ne!(id, InboxEntry::content_id("other", &[1, 2, 3]));
+        assert_ne!(id, InboxEntry::content_id("sender", &[1, 2, 4]));
+    }
+
+    #[test]
+    fn test_deduplicate_inbox_keeps_first_copy() {
+        let first = submission(&[1], 100).entry;
+        let retry = submission(&[1], 200).entry;
+        let other = submission(&[2], 150).entry;
+
+        let entries = deduplicate_inbox(vec![first, other, retry]);
+        assert_eq!(entries.len(), 2);
+
+        let kept = entries.iter().find(|e| e.transaction == [1]).unwrap();
+        assert_eq!(kept.timestamp, 100);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_resubmitted_operation_stored_once() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+
+        let first = store_inbox_submission(&storage, submission(&[1, 2, 3], 100))
+            .await
+            .unwrap();
+        let retry = store_inbox_submission(&storage, submission(&[1, 2, 3], 200))
+            .await
+            .unwrap();
+        assert_eq!(first.blinded_id, retry.blinded_id);
+        assert_eq!(storage.list(None, None).await.unwrap().len(), 1);
+
+        // The retry must not overwrite the original delivery
+        let stored = storage.retrieve(&first.blinded_id).await.unwrap().unwrap();
+        let entry: InboxEntry = bincode::deserialize(&stored.encrypted_payload).unwrap();
+        assert_eq!(entry.timestamp, 100);
+
+        store_inbox_submission(&storage, submission(&[4, 5, 6], 100))
+            .await
+            .unwrap();
+        assert_eq!(storage.list(None, None).await.unwrap().len(), 2);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_acknowledged_entry_redelivered_after_deadline() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let mut submission = submission(&[1, 2, 3], 100);
+        submission.entry.ack_deadline_secs = 30;
+        let entry_id = submission.entry.id.clone();
+        let blinded_id = store_inbox_submission(&storage, submission)
+            .await
+            .unwrap()
+            .blinded_id;
+        let deliverable = |unacked_only, now| {
+            deliverable_entries(&storage, vec![blinded_id.clone()], unacked_only, now)
+        };
+
+        acknowledge_stored_entry(&storage, &blinded_id, &entry_id, 1_000)
+            .await
+            .unwrap();
+        assert!(deliverable(false, 1_029).await.unwrap().is_empty());
+
+        // Not deleted within the deadline, so it is delivered again
+        assert_eq!(deliverable(false, 1_030).await.unwrap()[0].id, entry_id);
+        assert!(deliverable(true, 1_030).await.unwrap().is_empty());
+
+        // Acknowledging again restarts the deadline
+        acknowledge_stored_entry(&storage, &blinded_id, &entry_id, 1_030)
+            .await
+            .unwrap();
+        assert!(deliverable(false, 1_059).await.unwrap().is_empty());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_entries_delivered_by_priority_then_age() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let mut blinded_ids = Vec::new();
+        for (transaction, timestamp, priority) in [
+            (1, 300, 128),
+            (2, 100, 128),
+            (3, 200, 255),
+            (4, 50, 0),
+            (5, 400, 255),
+        ] {
+            let mut submission = submission(&[transaction], timestamp);
+            submission.entry.priority = priority;
+            let stored = store_inbox_submission(&storage, submission).await.unwrap();
+            blinded_ids.push(stored.blinded_id);
+        }
+
+        let entries = deliverable_entries(&storage, blinded_ids, false, 1_000)
+            .await
+            .unwrap();
+        let order: Vec<u8> = entries.iter().map(|entry| entry.transaction[0]).collect();
+        assert_eq!(order, vec![3, 5, 2, 1, 4]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_anonymous_sender_is_proven_by_ring() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+        use dsm::crypto::ring::{generate_ring_keypair, generate_ring_signature};
+
+        let (secret_keys, ring): (Vec<_>, Vec<_>) = (0..3).map(|_| generate_ring_keypair()).unzip();
+        let sign = |mut submission: InboxSubmission| {
+            submission.entry.sender_genesis_hash = ANONYMOUS_SENDER.to_string();
+            let message = submission.entry.ring_message();
+            let signature = generate_ring_signature(&message, &secret_keys[1], &ring).unwrap();
+            submission.entry.id = InboxEntry::ring_entry_id(&signature);
+            submission.entry.ring_signature = Some(signature);
+            submission
+        };
+        let anonymous = sign(submission(&[1, 2, 3], 100));
+
+        assert!(anonymous.entry.verify_ring_sender(&ring));
+
+        // Identical payloads from anonymous senders get distinct IDs
+        assert_ne!(
+            sign(submission(&[1, 2, 3], 100)).entry.id,
+            anonymous.entry.id
+        );
+
+        // Routed fields cannot be changed without breaking the signature
+        let mut redirected = anonymous.clone();
+        redirected.entry.recipient_genesis_hash = "mallory".to_string();
+        assert!(!redirected.entry.verify_ring_sender(&ring));
+        let mut extended = anonymous.clone();
+        extended.entry.expires_at += 1_000;
+        assert!(!extended.entry.verify_ring_sender(&ring));
+        let mut relabeled = anonymous.clone();
+        relabeled
+            .entry
+            .metadata
+            .insert("type".to_string(), "other".to_string());
+        assert!(!relabeled.entry.verify_ring_sender(&ring));
+
+        let (_, outsider) = generate_ring_keypair();
+        assert!(!anonymous
+            .entry
+            .verify_ring_sender(&[ring[0].clone(), ring[2].clone(), outsider]));
+
+        // A named sender is not an anonymous ring member, even with a signature
+        let mut named = anonymous.clone();
+        named.entry.sender_genesis_hash = "sender".to_string();
+        assert!(!named.entry.verify_ring_sender(&ring));
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        assert!(store_inbox_submission(&storage, named).await.is_err());
+        let mut unsigned = anonymous.clone();
+        unsigned.entry.ring_signature = None;
+        assert!(store_inbox_submission(&storage, unsigned).await.is_err());
+        let mut renamed = anonymous.clone();
+        renamed.entry.id = InboxEntry::content_id(ANONYMOUS_SENDER, &[1, 2, 3]);
+        assert!(store_inbox_submission(&storage, renamed).await.is_err());
+        let broadcast = InboxBroadcast {
+            entry: anonymous.entry.clone(),
+            recipient_genesis_hashes: vec!["alice".to_string(), "bob".to_string()],
+        };
+        assert!(matches!(
+            store_inbox_broadcast(&storage, broadcast).await,
+            Err(StorageNodeError::InvalidInput(_))
+        ));
+        store_inbox_submission(&storage, anonymous).await.unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_broadcast_reports_each_recipient() {
+        use crate::storage::{MemoryStorage, MemoryStorageConfig};
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let broadcast = InboxBroadcast {
+            entry: submission(&[1, 2, 3], 100).entry,
+            recipient_genesis_hashes: vec!["alice".to_string(), String::new(), "bob".to_string()],
+        };
+
+        let result = store_inbox_broadcast(&storage, broadcast).await.unwrap();
+        assert_eq!(
+            result.successful,
+            vec!["alice".to_string(), "bob".to_string()]
+        );
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0].0.is_empty());
+
+        // Each delivered recipient gets its own copy of the entry
+        let entry_id = InboxEntry::content_id("sender", &[1, 2, 3]);
+        for recipient in ["alice", "bob"] {
+            let blinded_id = inbox_blinded_id(recipient, &entry_id);
+            let stored = storage.retrieve(&blinded_id).await.unwrap().unwrap();
+            let entry: InboxEntry = bincode::deserialize(&stored.encrypted_payload).unwrap();
+            assert_eq!(entry.recipient_genesis_hash, recipient);
+        }
+        assert_eq!(storage.list(None, None).await.unwrap().len(), 2);
+
+        let oversized = InboxBroadcast {
+            entry: submission(&[4], 100).entry,
+            recipient_genesis_hashes: vec!["carol".to_string(); MAX_BROADCAST_RECIPIENTS + 1],
+        };
+        assert!(matches!(
+            store_inbox_broadcast(&storage, oversized).await,
+            Err(StorageNodeError::InvalidInput(_))
+        ));
+    }
+}
@@ -2,14 +2,21 @@
 //
 // This module implements API handlers for Deterministic Limbo Vaults (DLVs).
 
+use crate::api::acl_api::authorize_write;
 use crate::api::AppState;
+use crate::client::{SerializationFormat, VaultSearchPage, VaultSearchRequest};
 use crate::error::{Result, StorageNodeError};
+use crate::storage::StorageEngine;
 use crate::types::BlindedStateEntry;
 use axum::{
     extract::{Json, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
+use dsm::vault::{
+    LimboVault, OwnershipTransferRecord, RecipientReassignment, TimelockExtension,
+    VaultContentUpdate,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -49,6 +56,15 @@ pub enum VaultStatus {
     },
 }
 
+/// Metadata key holding a time-locked vault's unlock time
+pub const UNLOCK_TIME_METADATA: &str = "unlock_time";
+
+/// Metadata key holding the hex-encoded SPHINCS+ public key of a vault's creator
+pub const CREATOR_PUBLIC_KEY_METADATA: &str = "creator_public_key";
+
+/// Metadata key holding the recipient key generation of a vault's content
+pub const RECIPIENT_KEY_GENERATION_METADATA: &str = "recipient_key_generation";
+
 /// Vault data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultData {
@@ -91,10 +107,13 @@ pub struct VaultSubmission {
 #[axum::debug_handler]
 pub async fn store_vault(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(submission): Json<VaultSubmission>,
 ) -> Result<impl IntoResponse> {
     info!("Storing vault: {}", submission.vault.id);
 
+    authorize_write(&state, &headers, &submission.vault.creator_id)?;
+
     // Validate vault
     if submission.vault.id.is_empty() {
         return Err(StorageNodeError::InvalidState(
@@ -417,11 +436,106 @@ pub async fn get_vaults_by_recipient(
     }
 }
 
+/// Number of vaults in a search page when the request does not say
+pub const DEFAULT_VAULT_SEARCH_PAGE_SIZE: u32 = 100;
+
+/// Most vaults returned in one search page
+pub const MAX_VAULT_SEARCH_PAGE_SIZE: u32 = 1000;
+
+/// Number of blinded IDs listed from storage at a time while searching
+const VAULT_SEARCH_LIST_BATCH: usize = 1000;
+
+/// Search the vaults clients stored on this node
+#[axum::debug_handler]
+pub async fn search_vaults(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<VaultSearchRequest>,
+) -> Result<impl IntoResponse> {
+    let page = search_vault_page(state.storage.as_ref(), &request).await?;
+    info!(
+        "Vault search returned {} vaults (more: {})",
+        page.vaults.len(),
+        page.next_cursor.is_some()
+    );
+
+    Ok((StatusCode::OK, Json(page)))
+}
+
+/// Find one page of stored vaults matching a search request
+///
+/// Searched are the vaults clients store under `vault:<id>`; entries there
+/// that do not decode as a vault are skipped. Vaults are returned in blinded
+/// ID order, and the cursor is the blinded ID of the last vault returned.
+async fn search_vault_page(
+    storage: &(dyn StorageEngine + Send + Sync),
+    request: &VaultSearchRequest,
+) -> Result<VaultSearchPage> {
+    let page_size = request
+        .page_size
+        .unwrap_or(DEFAULT_VAULT_SEARCH_PAGE_SIZE)
+        .clamp(1, MAX_VAULT_SEARCH_PAGE_SIZE) as usize;
+
+    // Storage engines list a bounded number of IDs per call
+    let mut blinded_ids = Vec::new();
+    let mut offset = 0;
+    loop {
+        let batch = storage
+            .list(Some(VAULT_SEARCH_LIST_BATCH), Some(offset))
+            .await?;
+        offset += batch.len();
+        let done = batch.len() < VAULT_SEARCH_LIST_BATCH;
+
+        blinded_ids.extend(batch.into_iter().filter(|id| {
+            id.starts_with("vault:") && request.cursor.as_ref().map_or(true, |cursor| id > cursor)
+        }));
+        if done {
+            break;
+        }
+    }
+    blinded_ids.sort();
+
+    let mut page = VaultSearchPage::default();
+    let mut last_returned = None;
+    for blinded_id in blinded_ids {
+        if page.vaults.len() == page_size {
+            page.next_cursor = last_returned;
+            break;
+        }
+
+        let Some(entry) = storage.retrieve(&blinded_id).await? else {
+            continue;
+        };
+        let format = match entry.metadata.get("content_type") {
+            Some(content_type) => SerializationFormat::from_content_type(content_type),
+            None => Some(SerializationFormat::Bincode),
+        };
+        let Some(vault) = format.and_then(|format| {
+            format
+                .deserialize::<LimboVault>(&entry.encrypted_payload)
+                .ok()
+        }) else {
+            continue;
+        };
+
+        if request.filter.matches(&vault) {
+            page.vaults.push(vault);
+            last_returned = Some(blinded_id);
+        }
+    }
+
+    Ok(page)
+}
+
 /// Update a vault's status
+///
+/// On nodes that restrict writes, the update needs a permit for the vault's
+/// creator. Expired vaults are reclaimed through `LimboVault::reclaim_expired`
+/// with the creator's signature, not through this route.
 #[axum::debug_handler]
 pub async fn update_vault_status(
     State(state): State<Arc<AppState>>,
     Path(vault_id): Path<String>,
+    headers: HeaderMap,
     Json(status_update): Json<HashMap<String, serde_json::Value>>,
 ) -> Result<impl IntoResponse> {
     let blinded_id = format!("vault:{}", vault_id);
@@ -435,6 +549,7 @@ pub async fn update_vault_status(
                 bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
                     StorageNodeError::Serialization(format!("Failed to deserialize vault: {}", e))
                 })?;
+            authorize_write(&state, &headers, &vault.creator_id)?;
 
             // Update the status based on the request
             if let Some(serde_json::Value::String(status_type)) = status_update.get("status_type") {
@@ -544,3 +659,683 @@ pub async fn update_vault_status(
         ))),
     }
 }
+
+/// Extend a vault's time lock
+#[axum::debug_handler]
+pub async fn extend_vault_timelock(
+    State(state): State<Arc<AppState>>,
+    Path(vault_id): Path<String>,
+    Json(extension): Json<TimelockExtension>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Extending time lock of vault {} to {}",
+        vault_id, extension.new_unlock_time
+    );
+
+    if extension.vault_id != vault_id {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Extension is for vault {}, not {}",
+            extension.vault_id, vault_id
+        )));
+    }
+
+    let vault = apply_timelock_extension(state.storage.as_ref(), &extension).await?;
+
+    Ok((StatusCode::OK, Json(vault)))
+}
+
+/// Verify a time-lock extension and store the vault with its new unlock time
+///
+/// The vault must record its unlock time and its creator's public key in its
+/// metadata. Extensions signed once the unlock time was reached are rejected,
+/// as the vault is claimable from then on.
+async fn apply_timelock_extension(
+    storage: &(dyn StorageEngine + Send + Sync),
+    extension: &TimelockExtension,
+) -> Result<VaultData> {
+    let blinded_id = format!("vault:{}", extension.vault_id);
+    let entry = storage.retrieve(&blinded_id).await?.ok_or_else(|| {
+        StorageNodeError::NotFound(format!("Vault with ID {} not found", extension.vault_id))
+    })?;
+
+    let mut vault: VaultData = bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to deserialize vault: {}", e))
+    })?;
+
+    if vault.status != VaultStatus::Active {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Vault {} is no longer locked",
+            vault.id
+        )));
+    }
+
+    let unlock_time = vault
+        .metadata
+        .get(UNLOCK_TIME_METADATA)
+        .and_then(|unlock_time| unlock_time.parse::<u64>().ok())
+        .ok_or_else(|| {
+            StorageNodeError::InvalidState(format!("Vault {} has no time lock", vault.id))
+        })?;
+
+    if extension.state_number >= unlock_time {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Time lock of vault {} already elapsed at {}",
+            vault.id, unlock_time
+        )));
+    }
+
+    if extension.new_unlock_time <= unlock_time {
+        return Err(StorageNodeError::InvalidInput(
+            "Extension must move the unlock time later".into(),
+        ));
+    }
+
+    let creator_public_key = vault
+        .metadata
+        .get(CREATOR_PUBLIC_KEY_METADATA)
+        .and_then(|key| hex::decode(key).ok())
+        .ok_or_else(|| {
+            StorageNodeError::InvalidState(format!(
+                "Vault {} has no creator key to verify the extension with",
+                vault.id
+            ))
+        })?;
+
+    if !extension.verify(&creator_public_key).unwrap_or(false) {
+        return Err(StorageNodeError::Authentication(
+            "Extension is not signed by the vault creator".into(),
+        ));
+    }
+
+    vault.metadata.insert(
+        UNLOCK_TIME_METADATA.to_string(),
+        extension.new_unlock_time.to_string(),
+    );
+
+    let updated_entry = BlindedStateEntry {
+        encrypted_payload: bincode::serialize(&vault).map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to serialize vault: {}", e))
+        })?,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        ..entry
+    };
+    storage.store(updated_entry).await?;
+
+    Ok(vault)
+}
+
+/// Transfer a vault's creator rights to another identity
+#[axum::debug_handler]
+pub async fn transfer_vault_ownership(
+    State(state): State<Arc<AppState>>,
+    Path(vault_id): Path<String>,
+    Json(transfer): Json<OwnershipTransferRecord>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Transferring ownership of vault {} to {}",
+        vault_id,
+        hex::encode(&transfer.new_creator)
+    );
+
+    if transfer.vault_id != vault_id {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Transfer is for vault {}, not {}",
+            transfer.vault_id, vault_id
+        )));
+    }
+
+    let vault = apply_ownership_transfer(state.storage.as_ref(), &transfer).await?;
+
+    Ok((StatusCode::OK, Json(vault)))
+}
+
+/// Verify an ownership transfer and store the vault with its new creator key
+///
+/// The transfer must be signed with the creator key recorded in the vault's
+/// metadata, which it then replaces, so later extensions and transfers have to
+/// be signed by the new creator.
+async fn apply_ownership_transfer(
+    storage: &(dyn StorageEngine + Send + Sync),
+    transfer: &OwnershipTransferRecord,
+) -> Result<VaultData> {
+    let blinded_id = format!("vault:{}", transfer.vault_id);
+    let entry = storage.retrieve(&blinded_id).await?.ok_or_else(|| {
+        StorageNodeError::NotFound(format!("Vault with ID {} not found", transfer.vault_id))
+    })?;
+
+    let mut vault: VaultData = bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to deserialize vault: {}", e))
+    })?;
+
+    if vault.status != VaultStatus::Active {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Vault {} is no longer locked",
+            vault.id
+        )));
+    }
+
+    if transfer.new_creator.is_empty() || transfer.new_creator == transfer.old_creator {
+        return Err(StorageNodeError::InvalidInput(
+            "Transfer must name a new creator".into(),
+        ));
+    }
+
+    let creator_public_key = vault
+        .metadata
+        .get(CREATOR_PUBLIC_KEY_METADATA)
+        .and_then(|key| hex::decode(key).ok())
+        .ok_or_else(|| {
+            StorageNodeError::InvalidState(format!(
+                "Vault {} has no creator key to verify the transfer with",
+                vault.id
+            ))
+        })?;
+
+    if transfer.old_creator != creator_public_key || !transfer.verify().unwrap_or(false) {
+        return Err(StorageNodeError::Authentication(
+            "Transfer is not signed by the vault creator".into(),
+        ));
+    }
+
+    vault.metadata.insert(
+        CREATOR_PUBLIC_KEY_METADATA.to_string(),
+        hex::encode(&transfer.new_creator),
+    );
+
+    let updated_entry = BlindedStateEntry {
+        encrypted_payload: bincode::serialize(&vault).map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to serialize vault: {}", e))
+        })?,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        ..entry
+    };
+    storage.store(updated_entry).await?;
+
+    Ok(vault)
+}
+
+/// Redirect an unclaimed vault to a new recipient
+#[axum::debug_handler]
+pub async fn reassign_vault_recipient(
+    State(state): State<Arc<AppState>>,
+    Path(vault_id): Path<String>,
+    Json(reassignment): Json<RecipientReassignment>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Reassigning vault {} to {}",
+        vault_id,
+        hex::encode(&reassignment.new_recipient)
+    );
+
+    if reassignment.vault_id != vault_id {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Reassignment is for vault {}, not {}",
+            reassignment.vault_id, vault_id
+        )));
+    }
+
+    let vault = apply_recipient_reassignment(state.storage.as_ref(), &reassignment).await?;
+
+    Ok((StatusCode::OK, Json(vault)))
+}
+
+/// Verify a recipient reassignment and store the vault with its new recipient
+///
+/// The reassignment must be signed with the creator key recorded in the
+/// vault's metadata and hand the vault on from its current recipient, so a
+/// superseded reassignment cannot be replayed.
+async fn apply_recipient_reassignment(
+    storage: &(dyn StorageEngine + Send + Sync),
+    reassignment: &RecipientReassignment,
+) -> Result<VaultData> {
+    let blinded_id = format!("vault:{}", reassignment.vault_id);
+    let entry = storage.retrieve(&blinded_id).await?.ok_or_else(|| {
+        StorageNodeError::NotFound(format!("Vault with ID {} not found", reassignment.vault_id))
+    })?;
+
+    let mut vault: VaultData = bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to deserialize vault: {}", e))
+    })?;
+
+    if vault.status != VaultStatus::Active {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Vault {} is no longer locked",
+            vault.id
+        )));
+    }
+
+    let old_recipient_id = reassignment.old_recipient.as_ref().map(hex::encode);
+    if vault.recipient_id != old_recipient_id {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Reassignment does not start from the current recipient of vault {}",
+            vault.id
+        )));
+    }
+
+    let creator_public_key = vault
+        .metadata
+        .get(CREATOR_PUBLIC_KEY_METADATA)
+        .and_then(|key| hex::decode(key).ok())
+        .ok_or_else(|| {
+            StorageNodeError::InvalidState(format!(
+                "Vault {} has no creator key to verify the reassignment with",
+                vault.id
+            ))
+        })?;
+
+    if reassignment.creator != creator_public_key || !reassignment.verify().unwrap_or(false) {
+        return Err(StorageNodeError::Authentication(
+            "Reassignment is not signed by the vault creator".into(),
+        ));
+    }
+
+    vault.recipient_id = Some(hex::encode(&reassignment.new_recipient));
+
+    let updated_entry = BlindedStateEntry {
+        encrypted_payload: bincode::serialize(&vault).map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to serialize vault: {}", e))
+        })?,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        ..entry
+    };
+    storage.store(updated_entry).await?;
+
+    Ok(vault)
+}
+
+/// Replace a vault's content with its re-encryption to the recipient's new key
+pub async fn update_vault_content(
+    State(state): State<Arc<AppState>>,
+    Path(vault_id): Path<String>,
+    Json(update): Json<VaultContentUpdate>,
+) -> Result<impl IntoResponse> {
+    info!(
+        "Updating content of vault {} to recipient key generation {}",
+        vault_id, update.recipient_key_generation
+    );
+
+    if update.vault_id != vault_id {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Content update is for vault {}, not {}",
+            update.vault_id, vault_id
+        )));
+    }
+
+    let vault = apply_content_update(state.storage.as_ref(), &update).await?;
+
+    Ok((StatusCode::OK, Json(vault)))
+}
+
+/// Verify a content update and store the vault with its new ciphertext
+///
+/// The update must be signed with the creator key recorded in the vault's
+/// metadata and encrypt to a later recipient key generation than the stored
+/// content, so a superseded ciphertext cannot be written back.
+async fn apply_content_update(
+    storage: &(dyn StorageEngine + Send + Sync),
+    update: &VaultContentUpdate,
+) -> Result<VaultData> {
+    let blinded_id = format!("vault:{}", update.vault_id);
+    let entry = storage.retrieve(&blinded_id).await?.ok_or_else(|| {
+        StorageNodeError::NotFound(format!("Vault with ID {} not found", update.vault_id))
+    })?;
+
+    let mut vault: VaultData = bincode::deserialize(&entry.encrypted_payload).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to deserialize vault: {}", e))
+    })?;
+
+    if vault.status != VaultStatus::Active {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Vault {} is no longer locked",
+            vault.id
+        )));
+    }
+
+    let creator_public_key = vault
+        .metadata
+        .get(CREATOR_PUBLIC_KEY_METADATA)
+        .and_then(|key| hex::decode(key).ok())
+        .ok_or_else(|| {
+            StorageNodeError::InvalidState(format!(
+                "Vault {} has no creator key to verify the content update with",
+                vault.id
+            ))
+        })?;
+
+    if update.creator != creator_public_key || !update.verify().unwrap_or(false) {
+        return Err(StorageNodeError::Authentication(
+            "Content update is not signed by the vault creator".into(),
+        ));
+    }
+
+    let stored_generation = vault
+        .metadata
+        .get(RECIPIENT_KEY_GENERATION_METADATA)
+        .and_then(|generation| generation.parse::<u32>().ok())
+        .unwrap_or(0);
+    if update.recipient_key_generation <= stored_generation {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Vault {} already holds recipient key generation {}",
+            vault.id, stored_generation
+        )));
+    }
+
+    vault.encrypted_content = bincode::serialize(&update.encrypted_content).map_err(|e| {
+        StorageNodeError::Serialization(format!("Failed to serialize vault content: {}", e))
+    })?;
+    vault.recipient_id = Some(hex::encode(&update.new_recipient));
+    vault.metadata.insert(
+        RECIPIENT_KEY_GENERATION_METADATA.to_string(),
+        update.recipient_key_generation.to_string(),
+    );
+
+    let updated_entry = BlindedStateEntry {
+        encrypted_payload: bincode::serialize(&vault).map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to serialize vault: {}", e))
+        })?,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        ..entry
+    };
+    storage.store(updated_entry).await?;
+
+    Ok(vault)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStorage, MemoryStorageConfig};
+    use dsm::crypto::sphincs;
+
+    async fn store_time_locked_vault(storage: &MemoryStorage, creator_public_key: &[u8]) {
+        let vault = VaultData {
+            id: "vault-1".to_string(),
+            creator_id: "creator".to_string(),
+            creation_timestamp: 0,
+            expiration_timestamp: 0,
+            status: VaultStatus::Active,
+            metadata: HashMap::from([
+                (UNLOCK_TIME_METADATA.to_string(), "10".to_string()),
+                (
+                    CREATOR_PUBLIC_KEY_METADATA.to_string(),
+                    hex::encode(creator_public_key),
+                ),
+            ]),
+            encrypted_content: vec![1, 2, 3],
+            recipient_id: None,
+        };
+
+        storage
+            .store(BlindedStateEntry {
+                blinded_id: "vault:vault-1".to_string(),
+                encrypted_payload: bincode::serialize(&vault).unwrap(),
+                timestamp: 0,
+                ttl: 0,
+                region: "global".to_string(),
+                priority: 1,
+                proof_hash: [0; 32],
+                metadata: HashMap::new(),
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn store_limbo_vault(storage: &MemoryStorage, id: &str, metadata: &[(&str, &str)]) {
+        let vault = LimboVault {
+            id: id.to_string(),
+            metadata: metadata
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..LimboVault::default()
+        };
+
+        storage
+            .store(BlindedStateEntry {
+                blinded_id: format!("vault:{}", id),
+                encrypted_payload: bincode::serialize(&vault).unwrap(),
+                timestamp: 0,
+                ttl: 0,
+                region: "global".to_string(),
+                priority: 0,
+                proof_hash: [0; 32],
+                metadata: HashMap::new(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_through_matching_vaults() {
+        use crate::client::VaultFilter;
+
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        for id in ["a", "b", "c"] {
+            store_limbo_vault(&storage, id, &[("category", "tickets")]).await;
+        }
+        store_limbo_vault(&storage, "other", &[("category", "deeds")]).await;
+        // Vaults written through the vault API are not client vaults
+        let (public_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &public_key).await;
+
+        let mut request = VaultSearchRequest {
+            filter: VaultFilter::new().metadata_equals("category", "tickets"),
+            cursor: None,
+            page_size: Some(2),
+        };
+        let ids = |page: &VaultSearchPage| -> Vec<String> {
+            page.vaults.iter().map(|vault| vault.id.clone()).collect()
+        };
+
+        let first = search_vault_page(&storage, &request).await.unwrap();
+        assert_eq!(ids(&first), ["a", "b"]);
+
+        request.cursor = first.next_cursor;
+        let second = search_vault_page(&storage, &request).await.unwrap();
+        assert_eq!(ids(&second), ["c"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_extension_accepted_before_unlock_time() {
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &public_key).await;
+
+        let extension = TimelockExtension::sign("vault-1", 20, 5, &secret_key).unwrap();
+        let vault = apply_timelock_extension(&storage, &extension)
+            .await
+            .unwrap();
+        assert_eq!(vault.metadata[UNLOCK_TIME_METADATA], "20");
+
+        // The stored unlock time moved, so an extension signed at 15 is still in time
+        let later = TimelockExtension::sign("vault-1", 30, 15, &secret_key).unwrap();
+        assert!(apply_timelock_extension(&storage, &later).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_extension_rejected_after_unlock_time() {
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let (_, intruder_key) = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &public_key).await;
+
+        let late = TimelockExtension::sign("vault-1", 20, 10, &secret_key).unwrap();
+        assert!(matches!(
+            apply_timelock_extension(&storage, &late).await,
+            Err(StorageNodeError::InvalidState(_))
+        ));
+
+        let forged = TimelockExtension::sign("vault-1", 20, 5, &intruder_key).unwrap();
+        assert!(matches!(
+            apply_timelock_extension(&storage, &forged).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transferred_vault_extended_by_new_creator_only() {
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let creator = sphincs::generate_sphincs_keypair().unwrap();
+        let successor = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &creator.0).await;
+
+        let transfer =
+            OwnershipTransferRecord::sign("vault-1", &successor.0, 3, (&creator.0, &creator.1))
+                .unwrap();
+        let vault = apply_ownership_transfer(&storage, &transfer).await.unwrap();
+        assert_eq!(
+            vault.metadata[CREATOR_PUBLIC_KEY_METADATA],
+            hex::encode(&successor.0)
+        );
+
+        let stale = TimelockExtension::sign("vault-1", 20, 5, &creator.1).unwrap();
+        assert!(matches!(
+            apply_timelock_extension(&storage, &stale).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+        let extension = TimelockExtension::sign("vault-1", 20, 5, &successor.1).unwrap();
+        assert!(apply_timelock_extension(&storage, &extension).await.is_ok());
+
+        // The old creator can no longer hand the vault on
+        assert!(matches!(
+            apply_ownership_transfer(&storage, &transfer).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_rejected_without_creator_signature() {
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let creator = sphincs::generate_sphincs_keypair().unwrap();
+        let intruder = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &creator.0).await;
+
+        // Signed by the intruder while claiming to come from the creator
+        let mut forged =
+            OwnershipTransferRecord::sign("vault-1", &intruder.0, 3, (&intruder.0, &intruder.1))
+                .unwrap();
+        forged.old_creator = creator.0.clone();
+        assert!(matches!(
+            apply_ownership_transfer(&storage, &forged).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        let self_signed =
+            OwnershipTransferRecord::sign("vault-1", &creator.0, 3, (&intruder.0, &intruder.1))
+                .unwrap();
+        assert!(matches!(
+            apply_ownership_transfer(&storage, &self_signed).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+    }
+
+    fn reassignment(
+        old_recipient: Option<&[u8]>,
+        new_recipient: &[u8],
+        creator: &(Vec<u8>, Vec<u8>),
+    ) -> RecipientReassignment {
+        let message =
+            RecipientReassignment::signing_message("vault-1", old_recipient, new_recipient, &[9]);
+        RecipientReassignment {
+            vault_id: "vault-1".to_string(),
+            old_recipient: old_recipient.map(<[u8]>::to_vec),
+            new_recipient: new_recipient.to_vec(),
+            reference_state_hash: vec![9],
+            creator: creator.0.clone(),
+            creator_signature: sphincs::sphincs_sign(&creator.1, &message).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recipient_reassigned_by_creator_only() {
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let creator = sphincs::generate_sphincs_keypair().unwrap();
+        let intruder = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &creator.0).await;
+
+        let forged = reassignment(None, b"intruder", &intruder);
+        assert!(matches!(
+            apply_recipient_reassignment(&storage, &forged).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        let first = reassignment(None, b"alice", &creator);
+        let vault = apply_recipient_reassignment(&storage, &first)
+            .await
+            .unwrap();
+        assert_eq!(vault.recipient_id, Some(hex::encode(b"alice")));
+
+        let second = reassignment(Some(&b"alice"[..]), b"bob", &creator);
+        assert!(apply_recipient_reassignment(&storage, &second)
+            .await
+            .is_ok());
+
+        // A superseded reassignment cannot be replayed
+        assert!(matches!(
+            apply_recipient_reassignment(&storage, &first).await,
+            Err(StorageNodeError::InvalidState(_))
+        ));
+    }
+
+    fn content_update(
+        generation: u32,
+        new_recipient: &[u8],
+        creator: &(Vec<u8>, Vec<u8>),
+    ) -> VaultContentUpdate {
+        let content = dsm::vault::EncryptedContent::Blob {
+            encapsulated_key: new_recipient.to_vec(),
+            encrypted_data: vec![generation as u8; 16],
+            nonce: vec![0; 12],
+            aad: Vec::new(),
+        };
+        VaultContentUpdate::sign(
+            "vault-1",
+            generation,
+            new_recipient,
+            content,
+            (&creator.0, &creator.1),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_content_update_must_be_newer_than_stored_content() {
+        let storage = MemoryStorage::new(MemoryStorageConfig::default());
+        let creator = sphincs::generate_sphincs_keypair().unwrap();
+        let intruder = sphincs::generate_sphincs_keypair().unwrap();
+        store_time_locked_vault(&storage, &creator.0).await;
+
+        let forged = content_update(1, b"intruder", &intruder);
+        assert!(matches!(
+            apply_content_update(&storage, &forged).await,
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        let first = content_update(1, b"recipient-key-1", &creator);
+        let vault = apply_content_update(&storage, &first).await.unwrap();
+        assert_eq!(vault.recipient_id, Some(hex::encode(b"recipient-key-1")));
+        assert_eq!(vault.metadata[RECIPIENT_KEY_GENERATION_METADATA], "1");
+        assert_eq!(
+            vault.encrypted_content,
+            bincode::serialize(&first.encrypted_content).unwrap()
+        );
+
+        let second = content_update(2, b"recipient-key-2", &creator);
+        assert!(apply_content_update(&storage, &second).await.is_ok());
+
+        // The superseded ciphertext cannot be written back
+        assert!(matches!(
+            apply_content_update(&storage, &first).await,
+            Err(StorageNodeError::InvalidState(_))
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+// Write permits for the DSM Storage Node Client
+//
+// Storage nodes with an ACL key only accept writes that carry a write permit
+// for the writing identity. The client answers a challenge from the node with
+// its ACL key to request a permit, caches permits per identity and renews
+// them shortly before they expire.
+
+#[cfg(feature = "reqwest")]
+use super::platform::now_secs;
+#[cfg(feature = "reqwest")]
+use super::StorageNodeClient;
+#[cfg(feature = "reqwest")]
+use crate::error::{Result, StorageNodeError};
+#[cfg(feature = "reqwest")]
+use dsm::communication::write_permit::{
+    WritePermitChallenge, WritePermitRequest, WritePermitResponse, WritePermitToken,
+    WRITE_PERMIT_CHALLENGE_PATH, WRITE_PERMIT_HEADER, WRITE_PERMIT_PATH,
+};
+#[cfg(feature = "reqwest")]
+use dsm::crypto::sphincs;
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Get a write permit for an identity, requesting or renewing it as needed
+    ///
+    /// Permits are renewed once less than 10% of their lifetime remains.
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hex-encoded genesis hash of the writing identity
+    ///
+    /// # Returns
+    /// * `Result<Option<String>>` - Encoded permit, or None without an ACL key
+    pub async fn write_permit(&self, genesis_hash: &str) -> Result<Option<String>> {
+        let Some(acl_key) = &self.acl_key else {
+            return Ok(None);
+        };
+
+        let cached = self.write_permits.read().await.get(genesis_hash).cloned();
+        let permit = match cached {
+            Some(permit) if !permit.needs_renewal_at(now_secs()) => permit,
+            _ => {
+                let permit = self.request_write_permit(genesis_hash, acl_key).await?;
+                self.write_permits
+                    .write()
+                    .await
+                    .insert(genesis_hash.to_string(), permit.clone());
+                permit
+            }
+        };
+
+        permit
+            .encode()
+            .map(Some)
+            .map_err(|e| StorageNodeError::Serialization(format!("Failed to encode permit: {}", e)))
+    }
+
+    /// Attach a write permit for an identity to a request, if one is needed
+    pub(super) async fn attach_write_permit(
+        &self,
+        builder: reqwest::RequestBuilder,
+        genesis_hash: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        Ok(match self.write_permit(genesis_hash).await? {
+            Some(permit) => builder.header(WRITE_PERMIT_HEADER, permit),
+            None => builder,
+        })
+    }
+
+    /// Attach a write permit for the client's own identity, if one is configured
+    ///
+    /// Used for writes not addressed to an identity, such as data and
+    /// checkpoints, which the node attributes to the permit holder.
+    pub(super) async fn attach_own_write_permit(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::RequestBuilder> {
+        match &self.acl_genesis_hash {
+            Some(genesis_hash) => self.attach_write_permit(builder, genesis_hash).await,
+            None => Ok(builder),
+        }
+    }
+
+    /// Ask the storage node for a new write permit
+    ///
+    /// The identity's genesis state supplies the owner public key the node
+    /// checks the request signature against. The request signs a fresh
+    /// challenge nonce from the node.
+    async fn request_write_permit(
+        &self,
+        genesis_hash: &str,
+        acl_key: &[u8],
+    ) -> Result<WritePermitToken> {
+        let hash = hex::decode(genesis_hash).map_err(|e| {
+            StorageNodeError::InvalidInput(format!("Invalid genesis hash {}: {}", genesis_hash, e))
+        })?;
+        let genesis = self.fetch_genesis_state(&hash).await?.ok_or_else(|| {
+            StorageNodeError::NotFound(format!("Genesis state {} not found", genesis_hash))
+        })?;
+
+        let challenge = self.request_write_permit_challenge().await?;
+        let message = WritePermitRequest::signing_message(genesis_hash, &challenge.nonce);
+        let signature = sphincs::sphincs_sign(acl_key, &message).map_err(|e| {
+            StorageNodeError::Encryption(format!("Failed to sign permit request: {}", e))
+        })?;
+        let request = WritePermitRequest {
+            genesis_hash: genesis_hash.to_string(),
+            nonce: challenge.nonce,
+            owner_public_key: genesis.signing_key.public_key,
+            owner_signature: signature,
+        };
+
+        let url = self
+            .base_url
+            .join(WRITE_PERMIT_PATH)
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Authentication(format!(
+                "Storage node refused a write permit for identity {}: {}",
+                genesis_hash,
+                response.status()
+            )));
+        }
+
+        let response: WritePermitResponse = response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse permit response: {}", e))
+        })?;
+        let permit = WritePermitToken::decode(&response.permit)
+            .map_err(|e| StorageNodeError::Serialization(format!("Invalid write permit: {}", e)))?;
+
+        if permit.genesis_hash != genesis_hash {
+            return Err(StorageNodeError::Authentication(format!(
+                "Storage node issued a write permit for identity {} instead of {}",
+                permit.genesis_hash, genesis_hash
+            )));
+        }
+
+        Ok(permit)
+    }
+
+    /// Fetch a single-use nonce for a permit request
+    async fn request_write_permit_challenge(&self) -> Result<WritePermitChallenge> {
+        let url = self
+            .base_url
+            .join(WRITE_PERMIT_CHALLENGE_PATH)
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node refused a write permit challenge: {}",
+                response.status()
+            )));
+        }
+
+        response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse permit challenge: {}", e))
+        })
+    }
+}
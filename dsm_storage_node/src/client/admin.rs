@@ -0,0 +1,70 @@
+// Emergency administration for the DSM Storage Node Client
+//
+// This module submits the authority-signed messages that pause a storage node
+// and lift the pause again.
+
+use super::StorageNodeClient;
+use crate::api::{EmergencyPause, EmergencyResume};
+use crate::error::{Result, StorageNodeError};
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Put the storage node under an emergency pause
+    ///
+    /// While paused, the node rejects every state-mutating request and only
+    /// serves reads.
+    ///
+    /// # Arguments
+    /// * `pause` - Pause signed by the node's emergency authority
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn submit_emergency_pause(&self, pause: &EmergencyPause) -> Result<()> {
+        self.post_admin("pause", pause).await
+    }
+
+    /// Lift an emergency pause on the storage node
+    ///
+    /// # Arguments
+    /// * `resume` - Resume signed by the node's emergency authority
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn submit_emergency_resume(&self, resume: &EmergencyResume) -> Result<()> {
+        self.post_admin("resume", resume).await
+    }
+
+    async fn post_admin<T: serde::Serialize>(&self, action: &str, body: &T) -> Result<()> {
+        let url = self
+            .base_url
+            .join(&format!("admin/{}", action))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn submit_emergency_pause(&self, _pause: &EmergencyPause) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn submit_emergency_resume(&self, _resume: &EmergencyResume) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+}
@@ -0,0 +1,381 @@
+// Backup and restore for the DSM Storage Node Client
+//
+// This module moves a storage node's data to a new host: it downloads a
+// node's backup to a file and uploads that file to the replacement node. The
+// manifest returned with the download holds the backup's checksum, and a file
+// that no longer matches it is never uploaded. Both requests carry an approval
+// signed with the node's emergency authority key.
+
+use super::StorageNodeClient;
+use crate::api::BackupManifest;
+use crate::error::{Result, StorageNodeError};
+use std::path::Path;
+
+#[cfg(feature = "reqwest")]
+use super::platform::now_secs;
+#[cfg(feature = "reqwest")]
+use crate::api::{
+    AdminAuthorization, ADMIN_AUTHORIZATION_HEADER, BACKUP_CHECKSUM_HEADER,
+    BACKUP_CREATED_AT_HEADER, BACKUP_ENTRIES_HEADER, BACKUP_OPERATION, RESTORE_OPERATION,
+};
+#[cfg(feature = "reqwest")]
+use tokio::io::AsyncWriteExt;
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Download a backup of all data on the storage node
+    ///
+    /// The backup is streamed to `output_path` and checked against the
+    /// checksum the node sent with it.
+    ///
+    /// # Arguments
+    /// * `output_path` - File to write the compressed backup to
+    /// * `authority_secret_key` - SPHINCS+ secret key of the node's emergency authority
+    ///
+    /// # Returns
+    /// * `Result<BackupManifest>` - Description of the downloaded backup
+    pub async fn request_backup(
+        &self,
+        output_path: &Path,
+        authority_secret_key: &[u8],
+    ) -> Result<BackupManifest> {
+        let url = self
+            .base_url
+            .join("admin/backup")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
+        let authorization =
+            AdminAuthorization::sign(BACKUP_OPERATION, &[], now_secs(), authority_secret_key)?;
+
+        let mut response = builder
+            .header(ADMIN_AUTHORIZATION_HEADER, authorization.encode()?)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let expected_checksum = header(BACKUP_CHECKSUM_HEADER);
+        let entry_count = header(BACKUP_ENTRIES_HEADER)
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| {
+                StorageNodeError::Serialization("Backup response has no record count".into())
+            })?;
+        let created_at = header(BACKUP_CREATED_AT_HEADER)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(now_secs);
+
+        let mut file = tokio::fs::File::create(output_path).await?;
+        let mut hasher = blake3::Hasher::new();
+        let mut size_bytes = 0u64;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to read backup: {}", e)))?
+        {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+            size_bytes += chunk.len() as u64;
+        }
+        file.flush().await?;
+
+        let checksum = *hasher.finalize().as_bytes();
+        if let Some(expected) = expected_checksum {
+            if expected != hex::encode(checksum) {
+                let _ = tokio::fs::remove_file(output_path).await;
+                return Err(StorageNodeError::InvalidState(format!(
+                    "Downloaded backup does not match checksum {}",
+                    expected
+                )));
+            }
+        }
+
+        Ok(BackupManifest {
+            entry_count,
+            size_bytes,
+            checksum,
+            created_at,
+        })
+    }
+
+    /// Restore the storage node from a backup downloaded with `request_backup`
+    ///
+    /// The backup is checked against its manifest before any of it is sent.
+    ///
+    /// # Arguments
+    /// * `backup_path` - File holding the compressed backup
+    /// * `manifest` - Manifest returned when the backup was downloaded
+    /// * `authority_secret_key` - SPHINCS+ secret key of the node's emergency authority
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn restore_from_backup(
+        &self,
+        backup_path: &Path,
+        manifest: &BackupManifest,
+        authority_secret_key: &[u8],
+    ) -> Result<()> {
+        let backup = tokio::fs::read(backup_path).await?;
+        verify_backup(&backup, manifest)?;
+        let authorization =
+            AdminAuthorization::sign(RESTORE_OPERATION, &backup, now_secs(), authority_secret_key)?;
+
+        let url = self
+            .base_url
+            .join("admin/restore")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .header("Content-Type", "application/gzip")
+            .header(BACKUP_CHECKSUM_HEADER, hex::encode(manifest.checksum))
+            .header(ADMIN_AUTHORIZATION_HEADER, authorization.encode()?)
+            .body(backup)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Check a backup against the manifest it was downloaded with
+#[cfg(feature = "reqwest")]
+fn verify_backup(backup: &[u8], manifest: &BackupManifest) -> Result<()> {
+    if backup.len() as u64 != manifest.size_bytes {
+        return Err(StorageNodeError::InvalidInput(format!(
+            "Backup is {} bytes, but its manifest records {} bytes",
+            backup.len(),
+            manifest.size_bytes
+        )));
+    }
+
+    if blake3::hash(backup).as_bytes() != &manifest.checksum {
+        return Err(StorageNodeError::InvalidInput(
+            "Backup does not match the checksum in its manifest".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn request_backup(
+        &self,
+        _output_path: &Path,
+        _authority_secret_key: &[u8],
+    ) -> Result<BackupManifest> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn restore_from_backup(
+        &self,
+        _backup_path: &Path,
+        _manifest: &BackupManifest,
+        _authority_secret_key: &[u8],
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::api::{encode_backup, BackupRecord, BackupRecordType};
+    use crate::client::{object_key, SerializationFormat, StorageNodeClientConfig};
+    use crate::storage::{MemoryStorage, MemoryStorageConfig, StorageEngine};
+    use crate::test_support::{api_server_with_storage, serve};
+    use crate::types::{BlindedStateEntry, NodeGenesisConfig};
+    use dsm::crypto::sphincs;
+    use dsm::test_support::genesis;
+    use mockito::{Server, ServerGuard};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn client(base_url: &str) -> StorageNodeClient {
+        StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: base_url.to_string(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap()
+    }
+
+    /// Serve a storage node backed by `storage` and return a client for it
+    async fn node(storage: Arc<MemoryStorage>, authority_public_key: &[u8]) -> StorageNodeClient {
+        let server = api_server_with_storage(storage).with_genesis_config(NodeGenesisConfig {
+            emergency_authority_public_key: Some(authority_public_key.to_vec()),
+        });
+
+        client(&serve(server))
+    }
+
+    /// Mock storage node that answers version negotiation
+    async fn mock_node() -> ServerGuard {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        server
+    }
+
+    fn entry(blinded_id: &str, payload: Vec<u8>) -> BlindedStateEntry {
+        BlindedStateEntry {
+            blinded_id: blinded_id.to_string(),
+            proof_hash: *blake3::hash(&payload).as_bytes(),
+            encrypted_payload: payload,
+            timestamp: now_secs(),
+            ttl: 0,
+            region: "global".to_string(),
+            priority: 0,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn backup_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "dsm-backup-{}-{}-{}.gz",
+            name,
+            std::process::id(),
+            now_secs()
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_backup_manifest_matches_file() {
+        let storage = Arc::new(MemoryStorage::new(MemoryStorageConfig::default()));
+        storage.store(entry("first", vec![1, 2, 3])).await.unwrap();
+        storage.store(entry("second", vec![4, 5, 6])).await.unwrap();
+        let path = backup_path("manifest");
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+
+        let node = node(storage, &public_key).await;
+        let manifest = node.request_backup(&path, &secret_key).await.unwrap();
+        let backup = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(manifest.entry_count, 2);
+        assert_eq!(manifest.size_bytes, backup.len() as u64);
+        assert_eq!(&manifest.checksum, blake3::hash(&backup).as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_backup_rejected_before_upload() {
+        let mut server = mock_node().await;
+        let restore = server
+            .mock("POST", "/admin/restore")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let backup = encode_backup(&[BackupRecord {
+            record_type: BackupRecordType::BlindedState as u16,
+            payload: vec![7; 64],
+        }])
+        .unwrap();
+        let manifest = BackupManifest {
+            entry_count: 1,
+            size_bytes: backup.len() as u64,
+            checksum: *blake3::hash(&backup).as_bytes(),
+            created_at: now_secs(),
+        };
+
+        let mut corrupted = backup.clone();
+        let middle = corrupted.len() / 2;
+        corrupted[middle] ^= 0xff;
+        let path = backup_path("corrupted");
+        std::fs::write(&path, &corrupted).unwrap();
+
+        let result = client(&server.url())
+            .restore_from_backup(&path, &manifest, &[])
+            .await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(StorageNodeError::InvalidInput(_))));
+        restore.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_round_trips_genesis_state() {
+        let genesis = genesis(vec![0xaa; 32]);
+        let key = object_key("genesis", &hex::encode(&genesis.hash));
+        let payload = SerializationFormat::Bincode.serialize(&genesis).unwrap();
+
+        let source = Arc::new(MemoryStorage::new(MemoryStorageConfig::default()));
+        source.store(entry(&key, payload)).await.unwrap();
+        let path = backup_path("round-trip");
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let source = node(source, &public_key).await;
+        let manifest = source.request_backup(&path, &secret_key).await.unwrap();
+
+        let target = Arc::new(MemoryStorage::new(MemoryStorageConfig::default()));
+        let restored = node(target.clone(), &public_key)
+            .await
+            .restore_from_backup(&path, &manifest, &secret_key)
+            .await;
+        std::fs::remove_file(&path).unwrap();
+        restored.unwrap();
+
+        // Serve the restored entry the way clients read stored objects
+        let restored = target.retrieve(&key).await.unwrap().unwrap();
+        let mut server = mock_node().await;
+        server
+            .mock("GET", format!("/data/{}", key).as_str())
+            .with_body(restored.encrypted_payload)
+            .create_async()
+            .await;
+
+        let fetched = client(&server.url())
+            .fetch_genesis_state(&genesis.hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(fetched.hash, genesis.hash);
+        assert_eq!(fetched.participants, genesis.participants);
+        assert_eq!(
+            fetched.signing_key.public_key,
+            genesis.signing_key.public_key
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backup_requires_authority_approval() {
+        let storage = Arc::new(MemoryStorage::new(MemoryStorageConfig::default()));
+        storage.store(entry("first", vec![1, 2, 3])).await.unwrap();
+        let (public_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+        let (_, other_secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let path = backup_path("unapproved");
+
+        let result = node(storage, &public_key)
+            .await
+            .request_backup(&path, &other_secret_key)
+            .await;
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(result, Err(StorageNodeError::Network(_))));
+    }
+}
@@ -0,0 +1,373 @@
+// Federated client for the DSM Storage Node Client
+//
+// A single storage node is a single point of failure. `FederatedStorageNodeClient`
+// replicates every inbox write to several peer nodes and reads inboxes from all
+// of them, so that data stays available while some peers are down. The
+// consistency level decides how many peers must answer for a call to succeed.
+
+use super::{RecipientSpec, StorageNodeClient, StorageNodeClientConfig};
+use crate::api::{deduplicate_inbox, sort_inbox, InboxEntry};
+use crate::error::{Result, StorageNodeError};
+use dsm::communication::StorageCache;
+use dsm::types::operations::Operation;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// How many peers must acknowledge a federated call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsistencyLevel {
+    /// A single peer
+    One,
+
+    /// A majority of the peers
+    #[default]
+    Quorum,
+
+    /// Every peer
+    All,
+}
+
+impl ConsistencyLevel {
+    /// Acknowledgments needed out of `replicas` peers
+    pub fn quorum_count(&self, replicas: usize) -> usize {
+        match self {
+            ConsistencyLevel::One => replicas.min(1),
+            ConsistencyLevel::Quorum => replicas / 2 + 1,
+            ConsistencyLevel::All => replicas,
+        }
+    }
+}
+
+/// Federated client configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationConfig {
+    /// Base URLs of the peer storage nodes
+    pub peers: Vec<String>,
+
+    /// Number of peers every write goes to
+    pub replication_factor: usize,
+
+    /// How many peers must acknowledge a call
+    #[serde(default)]
+    pub consistency: ConsistencyLevel,
+}
+
+/// Client that replicates inbox traffic across a federation of storage nodes
+///
+/// Offers the inbox calls of [`StorageNodeClient`]: writes go to the first
+/// `replication_factor` peers, reads and deletes go to every peer.
+pub struct FederatedStorageNodeClient {
+    /// Peer IDs (base URLs) and their clients, in configuration order
+    peers: Vec<(String, Arc<StorageNodeClient>)>,
+
+    /// Number of peers every write goes to
+    replication_factor: usize,
+
+    /// How many peers must acknowledge a call
+    consistency: ConsistencyLevel,
+}
+
+impl FederatedStorageNodeClient {
+    /// Create a client for the configured peers
+    ///
+    /// All peer clients share one storage cache.
+    pub fn new(config: FederationConfig) -> Result<Self> {
+        if config.peers.is_empty() {
+            return Err(StorageNodeError::Config(
+                "Federation needs at least one peer".to_string(),
+            ));
+        }
+
+        if config.replication_factor == 0 || config.replication_factor > config.peers.len() {
+            return Err(StorageNodeError::Config(format!(
+                "Replication factor must be between 1 and {} (the number of peers)",
+                config.peers.len()
+            )));
+        }
+
+        let storage_cache = Arc::new(StorageCache::new());
+        let peers = config
+            .peers
+            .into_iter()
+            .map(|base_url| {
+                let node_config = StorageNodeClientConfig {
+                    base_url: base_url.clone(),
+                    ..StorageNodeClientConfig::default()
+                };
+                let client = StorageNodeClient::with_cache(node_config, storage_cache.clone())?;
+                Ok((base_url, Arc::new(client)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            peers,
+            replication_factor: config.replication_factor,
+            consistency: config.consistency,
+        })
+    }
+
+    /// Peer IDs, in configuration order
+    pub fn peer_ids(&self) -> Vec<String> {
+        self.peers
+            .iter()
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect()
+    }
+
+    /// Store a unilateral transaction on `replication_factor` peers
+    ///
+    /// Succeeds once enough peers for the consistency level acknowledge the
+    /// write. Entry IDs are content-addressed, so every peer stores the entry
+    /// under the same ID.
+    ///
+    /// # Returns
+    /// * `Result<String>` - ID of the stored inbox entry
+    pub async fn store_unilateral_transaction(
+        &self,
+        sender_genesis_hash: &str,
+        recipient: &RecipientSpec,
+        operation: &Operation,
+        signature: &[u8],
+        expires_in: Option<Duration>,
+        optional_priority: Option<u8>,
+    ) -> Result<String> {
+        let targets = &self.peers[..self.replication_factor];
+        let results = join_all(targets.iter().map(|(_, client)| {
+            client.store_unilateral_transaction(
+                sender_genesis_hash,
+                recipient,
+                operation,
+                signature,
+                expires_in,
+                optional_priority,
+            )
+        }))
+        .await;
+
+        let mut ids = self.acknowledged(targets, results)?;
+        Ok(ids.swap_remove(0))
+    }
+
+    /// Get the live unilateral transactions in a recipient's inbox on every peer
+    ///
+    /// Succeeds once enough peers for the consistency level answer, and
+    /// returns the union of their entries, deduplicated by entry ID, in
+    /// delivery order.
+    ///
+    /// # Returns
+    /// * `Result<Vec<InboxEntry>>` - Live inbox entries
+    pub async fn get_inbox_transactions(
+        &self,
+        recipient_genesis_hash: &str,
+    ) -> Result<Vec<InboxEntry>> {
+        let results = join_all(
+            self.peers
+                .iter()
+                .map(|(_, client)| client.get_inbox_transactions(recipient_genesis_hash)),
+        )
+        .await;
+
+        let entries = self.acknowledged(&self.peers, results)?;
+        let mut entries = deduplicate_inbox(entries.into_iter().flatten().collect());
+        sort_inbox(&mut entries);
+        Ok(entries)
+    }
+
+    /// Delete a transaction from a recipient's inbox on every peer
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether any peer deleted the entry
+    pub async fn delete_inbox_transaction(
+        &self,
+        recipient_genesis_hash: &str,
+        entry_id: &str,
+    ) -> Result<bool> {
+        let results =
+            join_all(self.peers.iter().map(|(_, client)| {
+                client.delete_inbox_transaction(recipient_genesis_hash, entry_id)
+            }))
+            .await;
+
+        let deleted = self.acknowledged(&self.peers, results)?;
+        Ok(deleted.into_iter().any(|deleted| deleted))
+    }
+
+    /// Collect the answers of the peers a call went to
+    ///
+    /// Fails with the last peer error when fewer peers answered than the
+    /// consistency level requires.
+    fn acknowledged<T>(
+        &self,
+        peers: &[(String, Arc<StorageNodeClient>)],
+        results: Vec<Result<T>>,
+    ) -> Result<Vec<T>> {
+        let required = self.consistency.quorum_count(peers.len());
+        let mut answers = Vec::with_capacity(results.len());
+        let mut last_error = StorageNodeError::Internal;
+
+        for ((peer_id, _), result) in peers.iter().zip(results) {
+            match result {
+                Ok(answer) => answers.push(answer),
+                Err(e) => {
+                    debug!(peer_id = %peer_id, error = %e, "Federated peer failed");
+                    last_error = e;
+                }
+            }
+        }
+
+        if answers.len() < required.max(1) {
+            return Err(StorageNodeError::Network(format!(
+                "Only {} of {} required peers answered: {}",
+                answers.len(),
+                required,
+                last_error
+            )));
+        }
+
+        Ok(answers)
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::api::DEFAULT_INBOX_PRIORITY;
+    use dsm::crypto::stealth::{generate_stealth_address, generate_stealth_keypair};
+    use mockito::{Mock, Server, ServerGuard};
+    use std::collections::HashMap;
+
+    /// Address nothing listens on
+    const DOWN_PEER: &str = "http://127.0.0.1:1";
+
+    async fn peer() -> ServerGuard {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        server
+    }
+
+    fn client(peers: &[&str], consistency: ConsistencyLevel) -> FederatedStorageNodeClient {
+        FederatedStorageNodeClient::new(FederationConfig {
+            peers: peers.iter().map(|peer| peer.to_string()).collect(),
+            replication_factor: peers.len(),
+            consistency,
+        })
+        .unwrap()
+    }
+
+    fn entry(id: &str) -> InboxEntry {
+        InboxEntry {
+            id: id.to_string(),
+            sender_genesis_hash: "sender".to_string(),
+            recipient_genesis_hash: "recipient".to_string(),
+            transaction: vec![1],
+            signature: vec![2],
+            timestamp: 0,
+            expires_at: 0,
+            metadata: HashMap::new(),
+            ack_deadline_secs: 0,
+            priority: DEFAULT_INBOX_PRIORITY,
+            ring_signature: None,
+        }
+    }
+
+    async fn inbox(server: &mut ServerGuard, ids: &[&str]) -> Mock {
+        let entries: Vec<_> = ids.iter().map(|id| entry(id)).collect();
+        server
+            .mock("GET", "/inbox/recipient")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&entries).unwrap())
+            .create_async()
+            .await
+    }
+
+    fn recipient() -> RecipientSpec {
+        let (_, public_key) = generate_stealth_keypair();
+        let (address, secret) = generate_stealth_address(&public_key).unwrap();
+        RecipientSpec::Stealth(address, secret.ephemeral_public_key())
+    }
+
+    fn operation() -> Operation {
+        Operation::Generic {
+            operation_type: "test".to_string(),
+            data: vec![1, 2, 3],
+            message: "federated".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_quorum_counts() {
+        assert_eq!(ConsistencyLevel::One.quorum_count(3), 1);
+        assert_eq!(ConsistencyLevel::Quorum.quorum_count(3), 2);
+        assert_eq!(ConsistencyLevel::Quorum.quorum_count(4), 3);
+        assert_eq!(ConsistencyLevel::All.quorum_count(3), 3);
+    }
+
+    #[test]
+    fn test_replication_factor_validated() {
+        let config = |replication_factor| FederationConfig {
+            peers: vec![
+                "http://a.example".to_string(),
+                "http://b.example".to_string(),
+            ],
+            replication_factor,
+            consistency: ConsistencyLevel::Quorum,
+        };
+
+        assert!(FederatedStorageNodeClient::new(config(0)).is_err());
+        assert!(FederatedStorageNodeClient::new(config(3)).is_err());
+        assert!(FederatedStorageNodeClient::new(config(2)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_quorum_write_survives_one_down_peer() {
+        let mut first = peer().await;
+        let mut second = peer().await;
+        let mut stores = Vec::new();
+        for server in [&mut first, &mut second] {
+            stores.push(server.mock("POST", "/inbox").create_async().await);
+        }
+        let peers = [first.url(), second.url()];
+
+        let quorum = client(&[&peers[0], &peers[1], DOWN_PEER], ConsistencyLevel::Quorum);
+        let id = quorum
+            .store_unilateral_transaction("sender", &recipient(), &operation(), &[7], None, None)
+            .await
+            .unwrap();
+        assert!(!id.is_empty());
+        for store in &stores {
+            store.assert_async().await;
+        }
+
+        let all = client(&[&peers[0], &peers[1], DOWN_PEER], ConsistencyLevel::All);
+        let result = all
+            .store_unilateral_transaction("sender", &recipient(), &operation(), &[7], None, None)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quorum_read_merges_peers() {
+        let mut first = peer().await;
+        let mut second = peer().await;
+        inbox(&mut first, &["a", "b"]).await;
+        inbox(&mut second, &["b", "c"]).await;
+        let peers = [first.url(), second.url()];
+
+        let quorum = client(&[&peers[0], &peers[1], DOWN_PEER], ConsistencyLevel::Quorum);
+        let entries = quorum.get_inbox_transactions("recipient").await.unwrap();
+        let ids: Vec<_> = entries.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+
+        let all = client(&[&peers[0], &peers[1], DOWN_PEER], ConsistencyLevel::All);
+        assert!(all.get_inbox_transactions("recipient").await.is_err());
+    }
+}
@@ -0,0 +1,175 @@
+// Identity device registry for the DSM Storage Node Client
+//
+// This module registers the devices that share an identity with the storage
+// node, and reads and publishes the head of the identity's state chain so that
+// those devices can keep their chains in sync.
+
+use super::StorageNodeClient;
+use crate::api::{DeviceRegistration, IdentityHead};
+use crate::error::{Result, StorageNodeError};
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Register a device as sharing an identity's state chain
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hex-encoded genesis hash of the identity
+    /// * `registration` - Device and the owner's signature over it
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn register_device(
+        &self,
+        genesis_hash: &str,
+        registration: &DeviceRegistration,
+    ) -> Result<()> {
+        let url = self.identity_url(genesis_hash, "devices")?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .json(registration)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// List the devices registered for an identity
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hex-encoded genesis hash of the identity
+    ///
+    /// # Returns
+    /// * `Result<Vec<DeviceRegistration>>` - Registered devices
+    pub async fn list_devices(&self, genesis_hash: &str) -> Result<Vec<DeviceRegistration>> {
+        let url = self.identity_url(genesis_hash, "devices")?;
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse device list: {}", e))
+        })
+    }
+
+    /// Fetch the published head of an identity's state chain
+    ///
+    /// The head is never served from the local cache, since other devices
+    /// may have advanced it since it was last read.
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hex-encoded genesis hash of the identity
+    ///
+    /// # Returns
+    /// * `Result<Option<IdentityHead>>` - The head, or `None` if none was published
+    pub async fn fetch_identity_head(&self, genesis_hash: &str) -> Result<Option<IdentityHead>> {
+        let url = self.identity_url(genesis_hash, "head")?;
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse identity head: {}", e))
+        })
+    }
+
+    /// Publish a new head of an identity's state chain
+    ///
+    /// The storage node rejects heads that do not move the chain forward or
+    /// are not signed by a registered device or the identity owner.
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hex-encoded genesis hash of the identity
+    /// * `head` - The new head
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn publish_identity_head(
+        &self,
+        genesis_hash: &str,
+        head: &IdentityHead,
+    ) -> Result<()> {
+        let url = self.identity_url(genesis_hash, "head")?;
+        let builder = self.prepare_request(self.http_client().put(url)).await?;
+
+        let response = builder
+            .json(head)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn identity_url(&self, genesis_hash: &str, resource: &str) -> Result<url::Url> {
+        self.base_url
+            .join(&format!("identity/{}/{}", genesis_hash, resource))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn register_device(
+        &self,
+        _genesis_hash: &str,
+        _registration: &DeviceRegistration,
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn list_devices(&self, _genesis_hash: &str) -> Result<Vec<DeviceRegistration>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_identity_head(&self, _genesis_hash: &str) -> Result<Option<IdentityHead>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn publish_identity_head(
+        &self,
+        _genesis_hash: &str,
+        _head: &IdentityHead,
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+}
@@ -0,0 +1,1148 @@
+// Unilateral transaction inbox for the DSM Storage Node Client
+//
+// This module stores unilateral transactions in a recipient's inbox on the
+// storage node and retrieves them, enforcing message expiry on the client side.
+
+use super::StorageNodeClient;
+use crate::api::{BroadcastResult, InboxEntry, InboxSubmission};
+use crate::error::{Result, StorageNodeError};
+use dsm::core::identity::{GenesisState, Identity};
+use dsm::crypto::stealth::{EphemeralPublicKey, StealthAddress};
+use dsm::types::error::DsmError;
+use dsm::types::operations::Operation;
+use dsm::types::state_types::State;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[cfg(any(feature = "reqwest", test))]
+use super::platform::now_secs;
+#[cfg(any(feature = "reqwest", test))]
+use crate::api::DEFAULT_INBOX_PRIORITY;
+#[cfg(feature = "reqwest")]
+use crate::api::{deduplicate_inbox, sort_inbox, InboxBroadcast, ANONYMOUS_SENDER};
+#[cfg(feature = "reqwest")]
+use dsm::crypto::ring::generate_ring_signature;
+#[cfg(feature = "reqwest")]
+use dsm::crypto::stealth::STEALTH_EPHEMERAL_KEY_METADATA;
+#[cfg(feature = "reqwest")]
+use futures::future::join_all;
+#[cfg(feature = "reqwest")]
+use std::collections::HashMap;
+#[cfg(feature = "reqwest")]
+use tracing::warn;
+
+/// Recipient of a unilateral transaction
+#[derive(Debug, Clone)]
+pub enum RecipientSpec {
+    /// Recipient identified by their genesis state
+    Genesis(GenesisState),
+
+    /// One-time stealth address, with the sender's ephemeral key
+    ///
+    /// Does not reveal the recipient; they find the transaction by scanning
+    /// with `dsm::crypto::stealth::scan_for_stealth_payments`.
+    Stealth(StealthAddress, EphemeralPublicKey),
+}
+
+impl RecipientSpec {
+    /// Inbox the transaction is delivered to
+    ///
+    /// The hex-encoded genesis hash, or the hex-encoded stealth address.
+    pub fn inbox_id(&self) -> String {
+        match self {
+            RecipientSpec::Genesis(genesis) => hex::encode(&genesis.hash),
+            RecipientSpec::Stealth(address, _) => address.to_hex(),
+        }
+    }
+}
+
+/// Client-side handling of expired inbox entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboxCleanupPolicy {
+    /// Delete expired entries from the storage node when they are encountered
+    pub auto_delete_expired: bool,
+
+    /// Treat entries older than this many seconds as expired, regardless of `expires_at`
+    pub max_age_secs: Option<u64>,
+}
+
+impl Default for InboxCleanupPolicy {
+    fn default() -> Self {
+        Self {
+            auto_delete_expired: true,
+            max_age_secs: None,
+        }
+    }
+}
+
+impl InboxCleanupPolicy {
+    /// Whether an entry should be treated as expired at the given time
+    pub fn is_expired(&self, entry: &InboxEntry, now: u64) -> bool {
+        if entry.is_expired_at(now) {
+            return true;
+        }
+
+        match self.max_age_secs {
+            Some(max_age) => entry.timestamp.saturating_add(max_age) < now,
+            None => false,
+        }
+    }
+
+    /// Split entries into live and expired ones
+    pub fn partition(
+        &self,
+        entries: Vec<InboxEntry>,
+        now: u64,
+    ) -> (Vec<InboxEntry>, Vec<InboxEntry>) {
+        entries
+            .into_iter()
+            .partition(|entry| !self.is_expired(entry, now))
+    }
+}
+
+/// Metadata key of the sender's state number in a broadcast inbox entry
+#[cfg(feature = "reqwest")]
+const SENDER_STATE_NUMBER_METADATA: &str = "sender_state_number";
+
+/// Metadata key of the hex-encoded hash of the sender's state in a broadcast inbox entry
+#[cfg(feature = "reqwest")]
+const SENDER_STATE_HASH_METADATA: &str = "sender_state_hash";
+
+/// Map a storage node error onto the error reported for a failed broadcast
+#[cfg(feature = "reqwest")]
+fn broadcast_error(error: StorageNodeError) -> DsmError {
+    match error {
+        StorageNodeError::Network(e) => DsmError::network(
+            format!("Failed to broadcast unilateral transaction: {}", e),
+            None::<std::convert::Infallible>,
+        ),
+        e => DsmError::storage(
+            "Storage node rejected the unilateral transaction broadcast",
+            Some(e),
+        ),
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Store a unilateral transaction in the recipient's inbox
+    ///
+    /// A stealth recipient's ephemeral key travels in the entry's metadata.
+    ///
+    /// # Arguments
+    /// * `sender_genesis_hash` - Hex-encoded genesis hash of the sender
+    /// * `recipient` - Recipient of the transaction
+    /// * `operation` - Operation to deliver
+    /// * `signature` - Sender's signature over the operation
+    /// * `expires_in` - Optional lifetime of the message, after which it is discarded
+    /// * `optional_priority` - Delivery priority, [`DEFAULT_INBOX_PRIORITY`] if not given
+    ///
+    /// # Returns
+    /// * `Result<String>` - ID of the stored inbox entry
+    pub async fn store_unilateral_transaction(
+        &self,
+        sender_genesis_hash: &str,
+        recipient: &RecipientSpec,
+        operation: &Operation,
+        signature: &[u8],
+        expires_in: Option<Duration>,
+        optional_priority: Option<u8>,
+    ) -> Result<String> {
+        let mut entry = self.unilateral_entry(
+            sender_genesis_hash,
+            recipient.inbox_id(),
+            operation,
+            signature,
+            expires_in,
+            optional_priority,
+        )?;
+        Self::add_recipient_metadata(&mut entry, recipient);
+
+        self.submit_inbox_entry(&entry).await?;
+
+        Ok(entry.id)
+    }
+
+    /// Store a unilateral transaction without revealing which identity sent it
+    ///
+    /// The entry's sender is [`ANONYMOUS_SENDER`] and it carries a ring
+    /// signature proving the sender holds one of the ring's keys, which the
+    /// recipient checks with [`InboxEntry::verify_ring_sender`]. Storage nodes
+    /// that require write permits cannot accept anonymous entries.
+    ///
+    /// # Arguments
+    /// * `signer_sk` - Sender's ring secret key
+    /// * `ring_public_keys` - Ring public keys known to the recipient, including the sender's
+    /// * `recipient` - Recipient of the transaction
+    /// * `operation` - Operation to deliver
+    /// * `expires_in` - Optional lifetime of the message, after which it is discarded
+    /// * `optional_priority` - Delivery priority, [`DEFAULT_INBOX_PRIORITY`] if not given
+    ///
+    /// # Returns
+    /// * `Result<String>` - ID of the stored inbox entry
+    pub async fn store_anonymous_unilateral_transaction(
+        &self,
+        signer_sk: &[u8],
+        ring_public_keys: &[Vec<u8>],
+        recipient: &RecipientSpec,
+        operation: &Operation,
+        expires_in: Option<Duration>,
+        optional_priority: Option<u8>,
+    ) -> Result<String> {
+        let mut entry = self.unilateral_entry(
+            ANONYMOUS_SENDER,
+            recipient.inbox_id(),
+            operation,
+            &[],
+            expires_in,
+            optional_priority,
+        )?;
+        Self::add_recipient_metadata(&mut entry, recipient);
+
+        let signature = generate_ring_signature(&entry.ring_message(), signer_sk, ring_public_keys)
+            .map_err(|e| {
+                StorageNodeError::Signing(format!("Failed to sign as ring member: {}", e))
+            })?;
+        entry.id = InboxEntry::ring_entry_id(&signature);
+        entry.ring_signature = Some(signature);
+
+        self.submit_inbox_entry(&entry).await?;
+
+        Ok(entry.id)
+    }
+
+    /// Carry a stealth recipient's ephemeral key in the entry's metadata
+    fn add_recipient_metadata(entry: &mut InboxEntry, recipient: &RecipientSpec) {
+        if let RecipientSpec::Stealth(_, ephemeral_public_key) = recipient {
+            entry.metadata.insert(
+                STEALTH_EPHEMERAL_KEY_METADATA.to_string(),
+                ephemeral_public_key.to_hex(),
+            );
+        }
+    }
+
+    /// Deliver one unilateral transaction to the inboxes of many recipients
+    ///
+    /// The transaction is posted once and the storage node stores it in every
+    /// recipient's inbox. A node without the broadcast endpoint is sent one
+    /// inbox submission per recipient instead, all in flight at once. A failed
+    /// delivery does not stop the others; each recipient's outcome is reported
+    /// in the result.
+    ///
+    /// # Arguments
+    /// * `sender_identity` - Identity of the sender
+    /// * `sender_state` - Sender's state the transaction was issued from
+    /// * `recipients` - Genesis states of the recipients
+    /// * `operation` - Operation to deliver
+    /// * `signature` - Sender's signature over the operation
+    ///
+    /// # Returns
+    /// * `Result<BroadcastResult, DsmError>` - Recipients delivered to, and those that failed
+    pub async fn broadcast_unilateral_transaction(
+        &self,
+        sender_identity: &Identity,
+        sender_state: &State,
+        recipients: &[GenesisState],
+        operation: &Operation,
+        signature: &[u8],
+    ) -> std::result::Result<BroadcastResult, DsmError> {
+        let sender_genesis_hash = hex::encode(&sender_identity.master_genesis.hash);
+        let mut entry = self
+            .unilateral_entry(
+                &sender_genesis_hash,
+                String::new(),
+                operation,
+                signature,
+                None,
+                None,
+            )
+            .map_err(broadcast_error)?;
+        entry.metadata.insert(
+            SENDER_STATE_NUMBER_METADATA.to_string(),
+            sender_state.state_number.to_string(),
+        );
+        entry.metadata.insert(
+            SENDER_STATE_HASH_METADATA.to_string(),
+            hex::encode(&sender_state.hash),
+        );
+
+        let broadcast = InboxBroadcast {
+            entry,
+            recipient_genesis_hashes: recipients
+                .iter()
+                .map(|recipient| hex::encode(&recipient.hash))
+                .collect(),
+        };
+
+        match self.post_inbox_broadcast(&broadcast).await {
+            Ok(Some(result)) => Ok(result),
+            Ok(None) => {
+                warn!("Storage node has no inbox broadcast endpoint, delivering individually");
+                Ok(self.deliver_individually(broadcast).await)
+            }
+            Err(e) => Err(broadcast_error(e)),
+        }
+    }
+
+    /// Build the inbox entry carrying a unilateral transaction
+    fn unilateral_entry(
+        &self,
+        sender_genesis_hash: &str,
+        recipient_inbox_id: String,
+        operation: &Operation,
+        signature: &[u8],
+        expires_in: Option<Duration>,
+        optional_priority: Option<u8>,
+    ) -> Result<InboxEntry> {
+        let transaction = self.serialization_format.serialize(operation)?;
+        let now = now_secs();
+
+        let expires_at = match expires_in {
+            // Round up so that sub-second lifetimes still expire
+            Some(ttl) => now.saturating_add(ttl.as_secs().max(1)),
+            None => 0, // No expiration
+        };
+
+        let mut metadata = HashMap::new();
+        metadata.insert("type".to_string(), "unilateral_transaction".to_string());
+        metadata.insert(
+            "content_type".to_string(),
+            self.serialization_format.content_type().to_string(),
+        );
+
+        Ok(InboxEntry {
+            // Retries of the same transaction produce the same ID
+            id: InboxEntry::content_id(sender_genesis_hash, &transaction),
+            sender_genesis_hash: sender_genesis_hash.to_string(),
+            recipient_genesis_hash: recipient_inbox_id,
+            transaction,
+            signature: signature.to_vec(),
+            timestamp: now,
+            expires_at,
+            metadata,
+            ack_deadline_secs: self.inbox_ack_deadline_secs,
+            priority: optional_priority.unwrap_or(DEFAULT_INBOX_PRIORITY),
+            ring_signature: None,
+        })
+    }
+
+    /// Post an inbox entry to its recipient's inbox
+    async fn submit_inbox_entry(&self, entry: &InboxEntry) -> Result<()> {
+        let url = self
+            .base_url
+            .join("inbox")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+        let builder = self
+            .attach_write_permit(builder, &entry.sender_genesis_hash)
+            .await?;
+
+        let response = builder
+            .json(&InboxSubmission {
+                entry: entry.clone(),
+            })
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Post a broadcast to the storage node's broadcast endpoint
+    ///
+    /// Returns `None` if the node does not have the endpoint.
+    async fn post_inbox_broadcast(
+        &self,
+        broadcast: &InboxBroadcast,
+    ) -> Result<Option<BroadcastResult>> {
+        let url = self
+            .base_url
+            .join("inbox/broadcast")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+        let builder = self
+            .attach_write_permit(builder, &broadcast.entry.sender_genesis_hash)
+            .await?;
+
+        let response = builder
+            .json(broadcast)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        // Older nodes route the path to the inbox listing, which only allows GET
+        if matches!(
+            response.status(),
+            reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::METHOD_NOT_ALLOWED
+        ) {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        let result = response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse broadcast result: {}", e))
+        })?;
+
+        Ok(Some(result))
+    }
+
+    /// Deliver a broadcast with one inbox submission per recipient
+    async fn deliver_individually(&self, broadcast: InboxBroadcast) -> BroadcastResult {
+        let deliveries = broadcast
+            .recipient_genesis_hashes
+            .into_iter()
+            .map(|recipient| {
+                let entry = InboxEntry {
+                    recipient_genesis_hash: recipient.clone(),
+                    ..broadcast.entry.clone()
+                };
+                async move { (recipient, self.submit_inbox_entry(&entry).await) }
+            });
+
+        let mut result = BroadcastResult::default();
+        for (recipient, outcome) in join_all(deliveries).await {
+            match outcome {
+                Ok(()) => result.successful.push(recipient),
+                Err(e) => result.failed.push((recipient, e.to_string())),
+            }
+        }
+        result
+    }
+
+    /// Get the live unilateral transactions waiting in a recipient's inbox
+    ///
+    /// Entries that have expired are never returned. When the cleanup policy
+    /// allows it, they are also deleted from the storage node. Entries are
+    /// deduplicated by their content-addressed ID, and an entry received
+    /// earlier is never replaced by a later copy. An acknowledged entry is
+    /// only returned again once its acknowledgment deadline passes without
+    /// the entry being deleted. Entries are returned in delivery order: by
+    /// descending priority, then oldest first.
+    ///
+    /// # Arguments
+    /// * `recipient_genesis_hash` - Hex-encoded genesis hash of the recipient
+    ///
+    /// # Returns
+    /// * `Result<Vec<InboxEntry>>` - Live inbox entries
+    pub async fn get_inbox_transactions(
+        &self,
+        recipient_genesis_hash: &str,
+    ) -> Result<Vec<InboxEntry>> {
+        self.fetch_inbox(recipient_genesis_hash, false).await
+    }
+
+    /// Get the live inbox entries of a recipient at or above a priority
+    ///
+    /// # Arguments
+    /// * `recipient_genesis` - Genesis state of the recipient
+    /// * `min_priority` - Lowest priority to return
+    ///
+    /// # Returns
+    /// * `Result<Vec<InboxEntry>>` - Matching inbox entries, in delivery order
+    pub async fn get_urgent_inbox_entries(
+        &self,
+        recipient_genesis: &GenesisState,
+        min_priority: u8,
+    ) -> Result<Vec<InboxEntry>> {
+        let entries = self
+            .get_inbox_transactions(&hex::encode(&recipient_genesis.hash))
+            .await?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.priority >= min_priority)
+            .collect())
+    }
+
+    /// Get the live inbox entries of a recipient that were never acknowledged
+    ///
+    /// Unlike [`Self::get_inbox_transactions`], entries whose acknowledgment
+    /// lapsed are not returned.
+    ///
+    /// # Arguments
+    /// * `recipient_genesis_hash` - Hex-encoded genesis hash of the recipient
+    ///
+    /// # Returns
+    /// * `Result<Vec<InboxEntry>>` - Live unacknowledged inbox entries
+    pub async fn fetch_unacked_inbox_entries(
+        &self,
+        recipient_genesis_hash: &str,
+    ) -> Result<Vec<InboxEntry>> {
+        self.fetch_inbox(recipient_genesis_hash, true).await
+    }
+
+    /// Acknowledge that an inbox entry was received
+    ///
+    /// The storage node stops delivering the entry. If it is not deleted
+    /// within its `ack_deadline_secs`, the recipient is assumed to have failed
+    /// to process it and it is delivered again.
+    ///
+    /// # Arguments
+    /// * `recipient_genesis` - Genesis state of the recipient
+    /// * `entry_id` - ID of the entry to acknowledge
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success, or `NotFound` if the entry is not in the inbox
+    pub async fn acknowledge_inbox_entry(
+        &self,
+        recipient_genesis: &GenesisState,
+        entry_id: &str,
+    ) -> Result<()> {
+        let recipient_genesis_hash = hex::encode(&recipient_genesis.hash);
+        let url = self
+            .base_url
+            .join(&format!(
+                "inbox/{}/{}/ack",
+                recipient_genesis_hash, entry_id
+            ))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().put(url)).await?;
+        let builder = self
+            .attach_write_permit(builder, &recipient_genesis_hash)
+            .await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(StorageNodeError::NotFound(format!(
+                "Inbox entry {} not found",
+                entry_id
+            )));
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        // Offline reads must not hand out the entry again either
+        if let Some(inbox) = self
+            .inbox_cache
+            .write()
+            .await
+            .get_mut(&recipient_genesis_hash)
+        {
+            inbox.retain(|entry| entry.id != entry_id);
+        }
+
+        Ok(())
+    }
+
+    /// Fetch, clean up and cache the entries of a recipient's inbox
+    async fn fetch_inbox(
+        &self,
+        recipient_genesis_hash: &str,
+        unacked_only: bool,
+    ) -> Result<Vec<InboxEntry>> {
+        let mut url = self
+            .base_url
+            .join(&format!("inbox/{}", recipient_genesis_hash))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        if unacked_only {
+            url.query_pairs_mut().append_pair("unacked_only", "true");
+        }
+
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        let entries: Vec<InboxEntry> = response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse inbox entries: {}", e))
+        })?;
+
+        let (live, expired) = self.inbox_cleanup.partition(entries, now_secs());
+        let live = self
+            .cache_inbox_entries(recipient_genesis_hash, live, &expired)
+            .await;
+
+        if self.inbox_cleanup.auto_delete_expired {
+            for entry in expired {
+                if let Err(e) = self
+                    .delete_inbox_transaction(recipient_genesis_hash, &entry.id)
+                    .await
+                {
+                    warn!(entry_id = %entry.id, error = %e, "Failed to delete expired inbox entry");
+                }
+            }
+        }
+
+        Ok(live)
+    }
+
+    /// Delete a transaction from a recipient's inbox
+    ///
+    /// # Arguments
+    /// * `recipient_genesis_hash` - Hex-encoded genesis hash of the recipient
+    /// * `entry_id` - ID of the entry to delete
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether the entry was deleted
+    pub async fn delete_inbox_transaction(
+        &self,
+        recipient_genesis_hash: &str,
+        entry_id: &str,
+    ) -> Result<bool> {
+        let url = self
+            .base_url
+            .join(&format!("inbox/{}/{}", recipient_genesis_hash, entry_id))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().delete(url)).await?;
+        let builder = self
+            .attach_write_permit(builder, recipient_genesis_hash)
+            .await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        // 404 means it didn't exist, which isn't an error for delete
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        if let Some(inbox) = self
+            .inbox_cache
+            .write()
+            .await
+            .get_mut(recipient_genesis_hash)
+        {
+            inbox.retain(|entry| entry.id != entry_id);
+        }
+
+        Ok(true)
+    }
+
+    /// Get the live inbox entries previously received for a recipient
+    ///
+    /// # Arguments
+    /// * `recipient_genesis_hash` - Hex-encoded genesis hash of the recipient
+    ///
+    /// # Returns
+    /// * `Vec<InboxEntry>` - Cached entries that have not expired, in delivery order
+    pub async fn cached_inbox_transactions(&self, recipient_genesis_hash: &str) -> Vec<InboxEntry> {
+        let now = now_secs();
+        let cached = self
+            .inbox_cache
+            .read()
+            .await
+            .get(recipient_genesis_hash)
+            .cloned()
+            .unwrap_or_default();
+
+        let (live, _) = self.inbox_cleanup.partition(cached, now);
+        live
+    }
+
+    /// Merge fetched entries into the inbox cache
+    ///
+    /// Returns the deduplicated live entries in delivery order, using the
+    /// first copy received of each. Expired entries are dropped from the cache.
+    async fn cache_inbox_entries(
+        &self,
+        recipient_genesis_hash: &str,
+        live: Vec<InboxEntry>,
+        expired: &[InboxEntry],
+    ) -> Vec<InboxEntry> {
+        let mut cache = self.inbox_cache.write().await;
+        let inbox = cache.entry(recipient_genesis_hash.to_string()).or_default();
+
+        inbox.retain(|cached| expired.iter().all(|entry| entry.id != cached.id));
+
+        let mut entries: Vec<InboxEntry> = deduplicate_inbox(live)
+            .into_iter()
+            .map(
+                |entry| match inbox.iter().find(|cached| cached.id == entry.id) {
+                    Some(cached) => cached.clone(),
+                    None => {
+                        // Insert in order, so cached reads need no sorting
+                        let position =
+                            inbox.partition_point(|cached| cached.delivery_order(&entry).is_lt());
+                        inbox.insert(position, entry.clone());
+                        entry
+                    }
+                },
+            )
+            .collect();
+
+        sort_inbox(&mut entries);
+        entries
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn store_unilateral_transaction(
+        &self,
+        _sender_genesis_hash: &str,
+        _recipient: &RecipientSpec,
+        _operation: &Operation,
+        _signature: &[u8],
+        _expires_in: Option<Duration>,
+        _optional_priority: Option<u8>,
+    ) -> Result<String> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn store_anonymous_unilateral_transaction(
+        &self,
+        _signer_sk: &[u8],
+        _ring_public_keys: &[Vec<u8>],
+        _recipient: &RecipientSpec,
+        _operation: &Operation,
+        _expires_in: Option<Duration>,
+        _optional_priority: Option<u8>,
+    ) -> Result<String> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn broadcast_unilateral_transaction(
+        &self,
+        _sender_identity: &Identity,
+        _sender_state: &State,
+        _recipients: &[GenesisState],
+        _operation: &Operation,
+        _signature: &[u8],
+    ) -> std::result::Result<BroadcastResult, DsmError> {
+        Err(DsmError::storage(
+            "Broadcasting requires the reqwest feature",
+            Some(StorageNodeError::Internal),
+        ))
+    }
+
+    pub async fn get_inbox_transactions(
+        &self,
+        _recipient_genesis_hash: &str,
+    ) -> Result<Vec<InboxEntry>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn get_urgent_inbox_entries(
+        &self,
+        _recipient_genesis: &GenesisState,
+        _min_priority: u8,
+    ) -> Result<Vec<InboxEntry>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_unacked_inbox_entries(
+        &self,
+        _recipient_genesis_hash: &str,
+    ) -> Result<Vec<InboxEntry>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn acknowledge_inbox_entry(
+        &self,
+        _recipient_genesis: &GenesisState,
+        _entry_id: &str,
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn delete_inbox_transaction(
+        &self,
+        _recipient_genesis_hash: &str,
+        _entry_id: &str,
+    ) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn cached_inbox_transactions(
+        &self,
+        _recipient_genesis_hash: &str,
+    ) -> Vec<InboxEntry> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "reqwest")]
+    use dsm::test_support::genesis;
+    use std::collections::HashMap;
+
+    fn entry(id: &str, timestamp: u64, expires_at: u64) -> InboxEntry {
+        InboxEntry {
+            id: id.to_string(),
+            sender_genesis_hash: "sender".to_string(),
+            recipient_genesis_hash: "recipient".to_string(),
+            transaction: vec![1],
+            signature: vec![2],
+            timestamp,
+            expires_at,
+            metadata: HashMap::new(),
+            ack_deadline_secs: 0,
+            priority: DEFAULT_INBOX_PRIORITY,
+            ring_signature: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_one_second_ttl_not_returned_after_two_seconds() {
+        let policy = InboxCleanupPolicy::default();
+        let now = now_secs();
+        let short_lived = entry("short", now, now + 1);
+        let permanent = entry("permanent", now, 0);
+
+        let (live, expired) = policy.partition(vec![short_lived.clone(), permanent.clone()], now);
+        assert_eq!(live.len(), 2);
+        assert!(expired.is_empty());
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let (live, expired) = policy.partition(vec![short_lived, permanent], now_secs());
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].id, "permanent");
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, "short");
+    }
+
+    #[test]
+    fn test_max_age_expires_old_entries() {
+        let policy = InboxCleanupPolicy {
+            auto_delete_expired: false,
+            max_age_secs: Some(60),
+        };
+
+        assert!(!policy.is_expired(&entry("fresh", 1_000, 0), 1_030));
+        assert!(policy.is_expired(&entry("stale", 1_000, 0), 1_061));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_duplicate_delivery_does_not_replace_cached_entry() {
+        use crate::client::StorageNodeClientConfig;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig::default()).unwrap();
+        let now = now_secs();
+        let first = entry("op", now, 0);
+        let mut duplicate = entry("op", now + 5, 0);
+        duplicate.signature = vec![9];
+
+        let cached = client
+            .cache_inbox_entries("recipient", vec![first, entry("other", now, 0)], &[])
+            .await;
+        assert_eq!(cached.len(), 2);
+
+        let cached = client
+            .cache_inbox_entries("recipient", vec![duplicate], &[])
+            .await;
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].timestamp, now);
+        assert_eq!(cached[0].signature, vec![2]);
+
+        let inbox = client.cached_inbox_transactions("recipient").await;
+        assert_eq!(inbox.len(), 2);
+        assert_eq!(inbox[0].id, "op");
+        assert_eq!(inbox[0].timestamp, now);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_inbox_cache_keeps_delivery_order() {
+        use crate::client::StorageNodeClientConfig;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig::default()).unwrap();
+        let now = now_secs();
+        let prioritized = |id: &str, age: u64, priority: u8| InboxEntry {
+            priority,
+            ..entry(id, now - age, 0)
+        };
+
+        let fetched = client
+            .cache_inbox_entries(
+                "recipient",
+                vec![
+                    prioritized("routine-new", 10, 128),
+                    prioritized("low", 60, 0),
+                ],
+                &[],
+            )
+            .await;
+        let ids: Vec<_> = fetched.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, vec!["routine-new", "low"]);
+
+        let fetched = client
+            .cache_inbox_entries(
+                "recipient",
+                vec![
+                    prioritized("routine-old", 30, 128),
+                    prioritized("dispute", 5, 255),
+                    prioritized("urgent", 20, 255),
+                ],
+                &[],
+            )
+            .await;
+        let ids: Vec<_> = fetched.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, vec!["urgent", "dispute", "routine-old"]);
+
+        // Inserted one batch at a time, the cache still holds one ordered inbox
+        let cached = client.cached_inbox_transactions("recipient").await;
+        let ids: Vec<_> = cached.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["urgent", "dispute", "routine-old", "routine-new", "low"]
+        );
+    }
+
+    /// Client for a mock node, with a sender identity and state to broadcast from
+    #[cfg(feature = "reqwest")]
+    async fn broadcast_fixture(
+        server: &mut mockito::ServerGuard,
+    ) -> (StorageNodeClient, Identity, State) {
+        use crate::client::StorageNodeClientConfig;
+        use dsm::types::state_types::DeviceInfo;
+
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+        let sender = Identity {
+            name: "sender".to_string(),
+            master_genesis: genesis(vec![0x11; 32]),
+            devices: Vec::new(),
+            invalidated: false,
+        };
+        let state = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("sender", vec![0; 32]));
+
+        (client, sender, state)
+    }
+
+    #[cfg(feature = "reqwest")]
+    fn broadcast_operation() -> Operation {
+        Operation::Generic {
+            operation_type: "notification".to_string(),
+            data: vec![1, 2, 3],
+            message: "broadcast".to_string(),
+        }
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_broadcast_sends_one_request_for_all_recipients() {
+        use mockito::Matcher;
+
+        let mut server = mockito::Server::new_async().await;
+        let (client, sender, state) = broadcast_fixture(&mut server).await;
+        let (alice, bob) = (hex::encode([0xaa; 32]), hex::encode([0xbb; 32]));
+
+        // The node reports a failure for one recipient and delivers to the other
+        let reported = BroadcastResult {
+            successful: vec![alice.clone()],
+            failed: vec![(bob.clone(), "Storage error".to_string())],
+        };
+        let broadcast = server
+            .mock("POST", "/inbox/broadcast")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "entry": {
+                    "sender_genesis_hash": hex::encode([0x11; 32]),
+                    "metadata": { "sender_state_number": "0" },
+                },
+                "recipient_genesis_hashes": [alice, bob],
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&reported).unwrap())
+            .create_async()
+            .await;
+        let individual = server.mock("POST", "/inbox").expect(0).create_async().await;
+
+        let result = client
+            .broadcast_unilateral_transaction(
+                &sender,
+                &state,
+                &[genesis(vec![0xaa; 32]), genesis(vec![0xbb; 32])],
+                &broadcast_operation(),
+                &[7],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result, reported);
+        broadcast.assert_async().await;
+        individual.assert_async().await;
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_broadcast_falls_back_to_individual_deliveries() {
+        use mockito::Matcher;
+
+        let mut server = mockito::Server::new_async().await;
+        let (client, sender, state) = broadcast_fixture(&mut server).await;
+        let recipient = |byte: u8| {
+            Matcher::PartialJson(serde_json::json!({
+                "entry": { "recipient_genesis_hash": hex::encode([byte; 32]) },
+            }))
+        };
+
+        server
+            .mock("POST", "/inbox/broadcast")
+            .with_status(404)
+            .create_async()
+            .await;
+        let delivered = server
+            .mock("POST", "/inbox")
+            .match_body(Matcher::AnyOf(vec![recipient(0xaa), recipient(0xcc)]))
+            .expect(2)
+            .create_async()
+            .await;
+        let rejected = server
+            .mock("POST", "/inbox")
+            .match_body(recipient(0xbb))
+            .with_status(500)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = client
+            .broadcast_unilateral_transaction(
+                &sender,
+                &state,
+                &[
+                    genesis(vec![0xaa; 32]),
+                    genesis(vec![0xbb; 32]),
+                    genesis(vec![0xcc; 32]),
+                ],
+                &broadcast_operation(),
+                &[7],
+            )
+            .await
+            .unwrap();
+
+        // The failed delivery does not hide the successful ones
+        assert_eq!(
+            result.successful,
+            vec![hex::encode([0xaa; 32]), hex::encode([0xcc; 32])]
+        );
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].0, hex::encode([0xbb; 32]));
+        delivered.assert_async().await;
+        rejected.assert_async().await;
+    }
+
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    #[tokio::test]
+    async fn test_acknowledged_entry_hidden_until_deadline_passes() {
+        use crate::client::StorageNodeClientConfig;
+        use crate::test_support::{api_server, serve};
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: serve(api_server()),
+            inbox_ack_deadline_secs: 2,
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+        let recipient = genesis(vec![0xaa; 32]);
+        let recipient_hash = hex::encode(&recipient.hash);
+        let operation = Operation::Generic {
+            operation_type: "transfer".to_string(),
+            data: vec![1, 2, 3],
+            message: "acknowledged".to_string(),
+        };
+
+        let entry_id = client
+            .store_unilateral_transaction(
+                "sender",
+                &RecipientSpec::Genesis(recipient.clone()),
+                &operation,
+                &[7],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            client
+                .get_inbox_transactions(&recipient_hash)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        client
+            .acknowledge_inbox_entry(&recipient, &entry_id)
+            .await
+            .unwrap();
+        assert!(client
+            .get_inbox_transactions(&recipient_hash)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(client
+            .cached_inbox_transactions(&recipient_hash)
+            .await
+            .is_empty());
+
+        // Never deleted, so it is delivered again once the deadline passes
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        let redelivered = client
+            .get_inbox_transactions(&recipient_hash)
+            .await
+            .unwrap();
+        assert_eq!(redelivered.len(), 1);
+        assert_eq!(redelivered[0].id, entry_id);
+        assert!(client
+            .fetch_unacked_inbox_entries(&recipient_hash)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            client.acknowledge_inbox_entry(&recipient, "missing").await,
+            Err(StorageNodeError::NotFound(_))
+        ));
+    }
+}
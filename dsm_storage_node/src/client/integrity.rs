@@ -0,0 +1,332 @@
+// Data integrity proofs for the DSM Storage Node Client
+//
+// A storage node can drop or alter the states it stores without the client
+// noticing. On request it signs the Merkle root over every state it holds for
+// a genesis, so the client can rebuild the root from the states it received
+// and detect any state that was modified or withheld.
+
+use super::StorageNodeClient;
+use crate::crypto::verify_signature_or_false;
+use crate::error::{Result, StorageNodeError};
+use dsm::merkle::tree::MerkleTree;
+use dsm::types::state_types::State;
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of the message a storage node signs for an integrity proof
+const INTEGRITY_PROOF_DOMAIN: &[u8] = b"DSM_INTEGRITY_PROOF";
+
+/// Whether fetched checkpoints are checked against an integrity proof
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegrityMode {
+    /// Trust the states the storage node returns
+    #[default]
+    Trust,
+
+    /// Check every checkpoint fetched from the storage node against an
+    /// integrity proof signed with `node_public_key`
+    VerifyIntegrity {
+        /// The storage node's SPHINCS+ public key
+        node_public_key: Vec<u8>,
+    },
+}
+
+/// A storage node's signed commitment to the states it stores for a genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityProof {
+    /// Genesis hash the states belong to
+    pub genesis_hash: Vec<u8>,
+
+    /// Number of states the root covers
+    pub state_count: u64,
+
+    /// Merkle root over the hashes of the states, in state number order
+    pub merkle_root: [u8; 32],
+
+    /// The storage node's SPHINCS+ signature over the proof
+    pub signature: Vec<u8>,
+}
+
+impl IntegrityProof {
+    /// Message the storage node signs
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut message = INTEGRITY_PROOF_DOMAIN.to_vec();
+        message.extend_from_slice(&(self.genesis_hash.len() as u64).to_le_bytes());
+        message.extend_from_slice(&self.genesis_hash);
+        message.extend_from_slice(&self.state_count.to_le_bytes());
+        message.extend_from_slice(&self.merkle_root);
+        message
+    }
+}
+
+/// Merkle root over the states of a genesis, as committed in an integrity proof
+///
+/// The leaves are the hashes recomputed from the states' contents, ordered by
+/// state number, so a state whose contents were altered yields a different
+/// root even if its recorded hash was left untouched. Returns `None` for an
+/// empty set of states.
+pub fn integrity_root(states: &[State]) -> Result<Option<[u8; 32]>> {
+    let mut leaves = states
+        .iter()
+        .map(|state| Ok((state.state_number, state.compute_hash()?)))
+        .collect::<Result<Vec<_>>>()?;
+    leaves.sort();
+
+    let tree = MerkleTree::new(leaves.into_iter().map(|(_, hash)| hash).collect());
+    Ok(tree.root_hash())
+}
+
+/// Check that an integrity proof commits to exactly `states`
+///
+/// The proof must be signed by `node_public_key` and its root must match the
+/// root rebuilt from the states, which fails if any state was modified,
+/// dropped or added.
+pub fn verify_integrity_proof(
+    proof: &IntegrityProof,
+    states: &[State],
+    node_public_key: &[u8],
+) -> bool {
+    if proof.state_count != states.len() as u64 {
+        return false;
+    }
+
+    match integrity_root(states) {
+        Ok(Some(root)) if root == proof.merkle_root => {}
+        _ => return false,
+    }
+
+    verify_signature_or_false(node_public_key, &proof.signing_message(), &proof.signature)
+}
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Fetch the storage node's integrity proof for the states of a genesis
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Genesis hash the states belong to
+    ///
+    /// # Returns
+    /// * `Result<IntegrityProof>` - The proof, to check with `verify_integrity_proof`
+    pub async fn fetch_integrity_proof(&self, genesis_hash: &[u8]) -> Result<IntegrityProof> {
+        let url = self
+            .base_url
+            .join(&format!("integrity/{}", hex::encode(genesis_hash)))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(StorageNodeError::NotFound(format!(
+                "No states stored for genesis {}",
+                hex::encode(genesis_hash)
+            )));
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        let proof: IntegrityProof = response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse integrity proof: {}", e))
+        })?;
+
+        if proof.genesis_hash != genesis_hash {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Integrity proof is for genesis {} instead of {}",
+                hex::encode(&proof.genesis_hash),
+                hex::encode(genesis_hash)
+            )));
+        }
+
+        Ok(proof)
+    }
+
+    /// Check a checkpoint fetched from the storage node against its integrity proof
+    ///
+    /// The checkpoint's chain is rebuilt from its genesis state up to the
+    /// identity head, or up to the checkpoint when no head was published, and
+    /// compared with the proof for that genesis. History states pruned from
+    /// the storage node make the check fail.
+    pub(crate) async fn verify_checkpoint_integrity(
+        &self,
+        checkpoint: &State,
+        node_public_key: &[u8],
+    ) -> Result<()> {
+        let mut states = self.chain_back_to(checkpoint.clone(), None).await?;
+        let genesis_hash = states[0].hash()?;
+
+        if let Some(head) = self
+            .fetch_identity_head(&hex::encode(&genesis_hash))
+            .await?
+        {
+            if head.state_number > checkpoint.state_number {
+                let head_hash = hex::decode(&head.state_hash).map_err(|e| {
+                    StorageNodeError::InvalidState(format!("Invalid identity head hash: {}", e))
+                })?;
+                let head = self.fetch_history_state(&head_hash).await?;
+                let later = self.chain_back_to(head, Some(checkpoint.hash()?)).await?;
+                states.extend(later);
+            }
+        }
+
+        let proof = self.fetch_integrity_proof(&genesis_hash).await?;
+        if !verify_integrity_proof(&proof, &states, node_public_key) {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Storage node states for genesis {} do not match its integrity proof",
+                hex::encode(&genesis_hash)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Follow a state's chain back to its genesis, or to the state hashed `stop_at`
+    ///
+    /// Returns the states in state number order, without the `stop_at` state.
+    async fn chain_back_to(&self, state: State, stop_at: Option<Vec<u8>>) -> Result<Vec<State>> {
+        let mut chain = vec![state];
+        loop {
+            let earliest = &chain[chain.len() - 1];
+            if earliest.state_number == 0 || Some(&earliest.prev_state_hash) == stop_at.as_ref() {
+                break;
+            }
+
+            let previous = self.fetch_history_state(&earliest.prev_state_hash).await?;
+            if previous.state_number + 1 != earliest.state_number {
+                return Err(StorageNodeError::InvalidState(format!(
+                    "State {} does not precede state {}",
+                    previous.state_number, earliest.state_number
+                )));
+            }
+            chain.push(previous);
+        }
+
+        chain.reverse();
+        Ok(chain)
+    }
+
+    async fn fetch_history_state(&self, state_hash: &[u8]) -> Result<State> {
+        self.fetch_state(state_hash).await?.ok_or_else(|| {
+            StorageNodeError::NotFound(format!(
+                "State {} is missing; integrity cannot be checked",
+                hex::encode(state_hash)
+            ))
+        })
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn fetch_integrity_proof(&self, _genesis_hash: &[u8]) -> Result<IntegrityProof> {
+        Err(StorageNodeError::Internal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::sign_with_node_key;
+    use dsm::crypto::sphincs;
+    use dsm::types::operations::Operation;
+    use dsm::types::state_types::DeviceInfo;
+
+    /// A genesis state followed by `count - 1` states chained to it
+    fn chain(count: u64) -> Vec<State> {
+        let device_info = DeviceInfo::new("integrity", vec![0; 32]);
+        let mut genesis = State::new_genesis(vec![1, 2, 3], device_info);
+        genesis.hash = genesis.compute_hash().unwrap();
+
+        let mut states = vec![genesis];
+        for number in 1..count {
+            let mut state = states[states.len() - 1].clone();
+            state.state_number = number;
+            state.prev_state_hash = state.hash.clone();
+            state.operation = Operation::Generic {
+                operation_type: "integrity".to_string(),
+                data: vec![number as u8],
+                message: format!("State {}", number),
+            };
+            state.hash = state.compute_hash().unwrap();
+            states.push(state);
+        }
+        states
+    }
+
+    fn signed_proof(states: &[State], secret_key: &[u8]) -> IntegrityProof {
+        let mut proof = IntegrityProof {
+            genesis_hash: states[0].hash.clone(),
+            state_count: states.len() as u64,
+            merkle_root: integrity_root(states).unwrap().unwrap(),
+            signature: Vec::new(),
+        };
+        proof.signature = sign_with_node_key(secret_key, &proof.signing_message()).unwrap();
+        proof
+    }
+
+    #[test]
+    fn test_valid_proof_verifies() {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let states = chain(5);
+        let proof = signed_proof(&states, &secret_key);
+
+        assert!(verify_integrity_proof(&proof, &states, &public_key));
+
+        // The order states are supplied in does not matter
+        let mut shuffled = states.clone();
+        shuffled.reverse();
+        assert!(verify_integrity_proof(&proof, &shuffled, &public_key));
+
+        // A proof signed by another node does not verify
+        let (other_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+        assert!(!verify_integrity_proof(&proof, &states, &other_key));
+    }
+
+    #[test]
+    fn test_tampered_state_changes_root() {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let states = chain(5);
+        let proof = signed_proof(&states, &secret_key);
+
+        // The recorded hash is kept, so only the contents give the change away
+        let mut tampered = states.clone();
+        tampered[2].operation = Operation::Generic {
+            operation_type: "integrity".to_string(),
+            data: vec![99],
+            message: "Tampered".to_string(),
+        };
+
+        assert_ne!(
+            integrity_root(&tampered).unwrap(),
+            integrity_root(&states).unwrap()
+        );
+        assert!(!verify_integrity_proof(&proof, &tampered, &public_key));
+    }
+
+    #[test]
+    fn test_missing_state_changes_root() {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let states = chain(5);
+        let proof = signed_proof(&states, &secret_key);
+
+        let mut missing = states.clone();
+        missing.remove(3);
+
+        assert_ne!(
+            integrity_root(&missing).unwrap(),
+            integrity_root(&states).unwrap()
+        );
+        assert!(!verify_integrity_proof(&proof, &missing, &public_key));
+
+        // A proof over the remaining states is rejected even with the full count
+        let mut forged = signed_proof(&missing, &secret_key);
+        forged.state_count = states.len() as u64;
+        assert!(!verify_integrity_proof(&forged, &missing, &public_key));
+    }
+}
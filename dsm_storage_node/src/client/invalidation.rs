@@ -0,0 +1,122 @@
+// Invalidation lookups for the DSM Storage Node Client
+//
+// Invalidation markers are checked for nearly every received state, and almost
+// every check finds nothing. Misses are remembered in the client's negative
+// cache so repeated checks within its window are answered locally.
+
+use super::StorageNodeClient;
+use crate::error::Result;
+use dsm::recovery::invalidation::InvalidationMarker;
+
+#[cfg(feature = "reqwest")]
+use super::invalidation_key;
+#[cfg(not(feature = "reqwest"))]
+use crate::error::StorageNodeError;
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Check whether a state has been invalidated
+    ///
+    /// Cached markers are consulted first, then the negative cache. A negative
+    /// cache hit is only ever a recent confirmed miss; anything uncertain is
+    /// rechecked on the storage node.
+    ///
+    /// # Arguments
+    /// * `state_hash` - Hash of the state to check
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether an invalidation marker exists for the state
+    pub async fn is_state_invalidated(&self, state_hash: &[u8]) -> Result<bool> {
+        if self.storage_cache.is_state_invalidated(state_hash).await? {
+            return Ok(true);
+        }
+
+        let key = invalidation_key(state_hash);
+        if self.negative_cache.contains(&key) {
+            return Ok(false);
+        }
+
+        let token = self.negative_cache.miss_token();
+        match self
+            .retrieve_remote_object::<InvalidationMarker>(&key)
+            .await?
+        {
+            Some(marker) => {
+                self.storage_cache
+                    .cache_invalidation(marker, false, None)
+                    .await?;
+                Ok(true)
+            }
+            None => {
+                self.negative_cache.record_miss(&key, token);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Publish an invalidation marker to the storage node
+    ///
+    /// Any remembered miss for the state is cleared before and after the
+    /// upload, so the new marker is never masked by the negative cache.
+    ///
+    /// # Arguments
+    /// * `marker` - Marker to publish
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn publish_invalidation_marker(&self, marker: InvalidationMarker) -> Result<()> {
+        let key = invalidation_key(&marker.state_hash);
+
+        self.negative_cache.forget(&key);
+        self.store_object(&key, &marker, None).await?;
+        self.storage_cache
+            .cache_invalidation(marker, true, None)
+            .await?;
+        self.negative_cache.forget(&key);
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn is_state_invalidated(&self, _state_hash: &[u8]) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn publish_invalidation_marker(&self, _marker: InvalidationMarker) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::client::StorageNodeClientConfig;
+    use dsm::types::state_types::{DeviceInfo, State};
+
+    #[tokio::test]
+    async fn test_cached_marker_clears_negative_entry() {
+        let client = StorageNodeClient::new(StorageNodeClientConfig::default()).unwrap();
+
+        let mut state = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("dev", vec![0; 32]));
+        state.hash = state.hash().unwrap();
+        let key = invalidation_key(&state.hash);
+
+        client
+            .negative_cache
+            .record_miss(&key, client.negative_cache.miss_token());
+        assert!(client.negative_cache.contains(&key));
+
+        // The invalidation listener forgets the miss as soon as a marker is cached
+        let marker = InvalidationMarker::create(&state, "forked", 1).unwrap();
+        client
+            .storage_cache()
+            .cache_invalidation(marker, true, None)
+            .await
+            .unwrap();
+
+        assert!(!client.negative_cache.contains(&key));
+        assert!(client.is_state_invalidated(&state.hash).await.unwrap());
+    }
+}
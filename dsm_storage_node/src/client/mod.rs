@@ -5,19 +5,242 @@
 // storage nodes in the DSM network.
 
 use crate::error::{Result, StorageNodeError};
-use base64::Engine;
+use crate::types::{ProtocolVersion, ProtocolVersionRange, MAX_PROTOCOL_VERSION, MIN_PROTOCOL_VERSION};
+use dsm::communication::storage_cache::{InvalidationListener, InvalidationListenerId};
+use dsm::communication::{RateLimiter, StorageCache, VaultPrunePolicy};
+#[cfg(feature = "reqwest")]
+use dsm::communication::WritePermitToken;
+use dsm::core::identity::GenesisState;
+use dsm::types::state_types::State;
+use dsm::types::token_types::Token;
+use dsm::types::versioned::{LegacySchema, Versioned};
+use dsm::types::error::DsmError;
+#[cfg(feature = "reqwest")]
+use dsm::vault::assemble_content;
+use dsm::vault::{
+    LimboVault, OwnershipTransferRecord, RecipientReassignment, TimelockExtension,
+    VaultContentUpdate,
+};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use tokio::sync::RwLock;
+use platform::RwLock;
+use tokio::sync::OnceCell;
 use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 use url::Url;
 
+mod acl;
+mod admin;
+#[cfg(not(target_arch = "wasm32"))]
+mod backup;
+mod federation;
+mod identity;
+mod inbox;
+mod integrity;
+mod invalidation;
+mod multi_node;
+mod negative_cache;
+#[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+mod noise;
+mod offline;
+pub(crate) mod platform;
+mod prewarm;
+mod revalidate;
+mod sharding;
+mod transport;
+mod vault_search;
+mod warmup;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm;
+
+pub use federation::*;
+pub use inbox::*;
+pub use integrity::*;
+pub use invalidation::*;
+pub use multi_node::*;
+pub use negative_cache::*;
+#[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+pub use noise::{generate_noise_keypair, NoiseSession, NOISE_PARAMS};
+pub use revalidate::*;
+pub use sharding::*;
+pub use transport::*;
+pub use vault_search::*;
+pub use warmup::*;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub use wasm::*;
+
 #[cfg(feature = "reqwest")]
-use std::time::Duration;
+use crate::api::{CheckpointSubmission, InboxEntry};
+#[cfg(feature = "reqwest")]
+use crate::types::storage_types::DataSubmissionRequest;
+#[cfg(feature = "reqwest")]
+use crate::types::BlindedStateEntry;
+#[cfg(feature = "reqwest")]
+use std::collections::HashSet;
 
 /// Default timeout value for storage node requests (30 seconds)
 const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
 
+/// Default acknowledgment deadline of sent inbox entries (5 minutes)
+const DEFAULT_INBOX_ACK_DEADLINE_SECS: u64 = 300;
+
+/// Header carrying the negotiated protocol version on every request
+pub const PROTOCOL_VERSION_HEADER: &str = "X-DSM-Protocol-Version";
+
+/// Storage key under which a typed object of the given kind is stored
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+fn object_key(kind: &str, id: &str) -> String {
+    format!("{}:{}", kind, id)
+}
+
+/// Storage key under which a chunk of a vault's content is stored
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+fn vault_chunk_key(vault_id: &str, index: usize) -> String {
+    object_key("vault_chunk", &format!("{}/{}", vault_id, index))
+}
+
+/// Storage key under which the invalidation marker for a state is stored
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+fn invalidation_key(state_hash: &[u8]) -> String {
+    object_key("invalidation", &hex::encode(state_hash))
+}
+
+/// Negotiate a common protocol version between a client and a server
+///
+/// The highest version supported by both parties is selected. If the ranges
+/// do not overlap, a `DsmError::VersionIncompatible` carrying both ranges
+/// is returned.
+///
+/// # Arguments
+/// * `client` - Version range supported by the client
+/// * `server` - Version range advertised by the server
+///
+/// # Returns
+/// * `Result<ProtocolVersion, DsmError>` - The negotiated version or an error
+pub fn negotiate_protocol_version(
+    client: ProtocolVersionRange,
+    server: ProtocolVersionRange,
+) -> std::result::Result<ProtocolVersion, DsmError> {
+    let lower = client.min_version.max(server.min_version);
+    let upper = client.max_version.min(server.max_version);
+
+    if lower > upper {
+        return Err(DsmError::VersionIncompatible {
+            client: client.to_string(),
+            server: server.to_string(),
+        });
+    }
+
+    Ok(upper)
+}
+
+/// Wire format used to encode typed objects exchanged with a storage node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SerializationFormat {
+    /// Compact Rust-native binary encoding
+    #[default]
+    Bincode,
+
+    /// Self-describing CBOR encoding (RFC 7049), requires the `cbor` feature
+    Cbor,
+}
+
+impl SerializationFormat {
+    /// MIME type identifying this format on the wire
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SerializationFormat::Bincode => "application/octet-stream",
+            SerializationFormat::Cbor => "application/cbor",
+        }
+    }
+
+    /// Format named by a `Content-Type` or `Accept` header value
+    ///
+    /// Media type parameters are ignored; for lists the first known format wins.
+    pub fn from_content_type(value: &str) -> Option<Self> {
+        value.split(',').find_map(|media_type| {
+            let essence = media_type.split(';').next().unwrap_or_default().trim();
+            [SerializationFormat::Bincode, SerializationFormat::Cbor]
+                .into_iter()
+                .find(|format| essence.eq_ignore_ascii_case(format.content_type()))
+        })
+    }
+
+    /// Encode a value in this format
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            SerializationFormat::Bincode => Ok(bincode::serialize(value)?),
+            #[cfg(feature = "cbor")]
+            SerializationFormat::Cbor => serde_cbor::to_vec(value)
+                .map_err(|e| StorageNodeError::Serialization(e.to_string())),
+            #[cfg(not(feature = "cbor"))]
+            SerializationFormat::Cbor => Err(Self::cbor_unavailable()),
+        }
+    }
+
+    /// Decode a value from this format
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        match self {
+            SerializationFormat::Bincode => Ok(bincode::deserialize(bytes)?),
+            #[cfg(feature = "cbor")]
+            SerializationFormat::Cbor => serde_cbor::from_slice(bytes)
+                .map_err(|e| StorageNodeError::Serialization(e.to_string())),
+            #[cfg(not(feature = "cbor"))]
+            SerializationFormat::Cbor => Err(Self::cbor_unavailable()),
+        }
+    }
+
+    /// Encode a value behind its schema version
+    pub fn serialize_versioned<T: LegacySchema>(&self, value: &T) -> Result<Vec<u8>> {
+        self.serialize(&Versioned {
+            version: T::SCHEMA_VERSION,
+            payload: value,
+        })
+    }
+
+    /// Decode a value written by `serialize_versioned`, upgrading older schemas
+    ///
+    /// CBOR is self-describing, so fields added since the value was written
+    /// take their serde defaults instead of going through a migration.
+    pub fn deserialize_versioned<T: LegacySchema>(&self, bytes: &[u8]) -> Result<T> {
+        match self {
+            SerializationFormat::Bincode => Ok(Versioned::<T>::deserialize(bytes)?.payload),
+            SerializationFormat::Cbor => Ok(self.deserialize::<Versioned<T>>(bytes)?.payload),
+        }
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    fn cbor_unavailable() -> StorageNodeError {
+        StorageNodeError::Config(
+            "CBOR serialization requires the `cbor` feature to be enabled".to_string(),
+        )
+    }
+}
+
+/// Periodic pruning of claimed and invalidated vaults from the storage cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultPruningConfig {
+    /// Which vaults to prune
+    pub policy: VaultPrunePolicy,
+
+    /// Seconds between pruning passes
+    pub interval_secs: u64,
+}
+
+impl Default for VaultPruningConfig {
+    fn default() -> Self {
+        Self {
+            policy: VaultPrunePolicy::keep_all()
+                .remove_claimed_older_than(Duration::from_secs(86400 * 30))
+                .remove_invalidated_immediately(),
+            interval_secs: 3600,
+        }
+    }
+}
+
 /// Storage node client configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageNodeClientConfig {
@@ -29,6 +252,58 @@ pub struct StorageNodeClientConfig {
 
     /// Request timeout in seconds
     pub timeout_seconds: u64,
+
+    /// Wire format for typed objects
+    #[serde(default)]
+    pub serialization_format: SerializationFormat,
+
+    /// Handling of expired inbox entries
+    #[serde(default)]
+    pub inbox_cleanup: InboxCleanupPolicy,
+
+    /// Seconds recipients of sent inbox entries have to delete an entry they
+    /// acknowledged before it is delivered to them again (0 = never redelivered)
+    #[serde(default = "default_inbox_ack_deadline_secs")]
+    pub inbox_ack_deadline_secs: u64,
+
+    /// Caching of lookups that found nothing on the storage node
+    #[serde(default)]
+    pub negative_cache: NegativeCacheConfig,
+
+    /// Periodic vault pruning (None = vaults are kept until evicted)
+    #[serde(default)]
+    pub vault_pruning: Option<VaultPruningConfig>,
+
+    /// Add vaults returned by searches to the storage cache
+    #[serde(default = "default_auto_cache_enabled")]
+    pub auto_cache_enabled: bool,
+
+    /// Transport used to reach the storage node
+    #[serde(default)]
+    pub transport: TransportConfig,
+
+    /// Whether fetched checkpoints are checked against an integrity proof
+    #[serde(default)]
+    pub integrity_mode: IntegrityMode,
+
+    /// SPHINCS+ secret key of the identity owner, used to request write
+    /// permits from nodes that restrict writes (None = writes carry no permit)
+    #[serde(default)]
+    pub acl_key: Option<Vec<u8>>,
+
+    /// Hex-encoded genesis hash of the identity `acl_key` belongs to, whose
+    /// permit covers writes not addressed to an identity, such as data and
+    /// checkpoints (None = those writes carry no permit)
+    #[serde(default)]
+    pub acl_genesis_hash: Option<String>,
+}
+
+fn default_auto_cache_enabled() -> bool {
+    true
+}
+
+fn default_inbox_ack_deadline_secs() -> u64 {
+    DEFAULT_INBOX_ACK_DEADLINE_SECS
 }
 
 impl Default for StorageNodeClientConfig {
@@ -37,6 +312,102 @@ fn default() -> Self {
             base_url: "http://localhost:8080".to_string(),
             api_token: None,
             timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+            serialization_format: SerializationFormat::default(),
+            inbox_cleanup: InboxCleanupPolicy::default(),
+            inbox_ack_deadline_secs: default_inbox_ack_deadline_secs(),
+            negative_cache: NegativeCacheConfig::default(),
+            vault_pruning: None,
+            auto_cache_enabled: default_auto_cache_enabled(),
+            transport: TransportConfig::default(),
+            integrity_mode: IntegrityMode::default(),
+            acl_key: None,
+            acl_genesis_hash: None,
+        }
+    }
+}
+
+/// Log the settings a client is created with, leaving out its credentials
+fn log_client_config(config: &StorageNodeClientConfig) {
+    info!(
+        base_url = %config.base_url,
+        timeout_seconds = config.timeout_seconds,
+        serialization_format = ?config.serialization_format,
+        auto_cache_enabled = config.auto_cache_enabled,
+        vault_pruning = config.vault_pruning.is_some(),
+        quic = matches!(config.transport, TransportConfig::Quic { .. }),
+        noise = matches!(config.transport, TransportConfig::Noise { .. }),
+        integrity_mode = ?config.integrity_mode,
+        authenticated = config.api_token.is_some(),
+        "Creating storage node client"
+    );
+}
+
+/// Build the listener that evicts cache entries derived from an invalidated state
+///
+/// Checkpoints of the invalidated state and its descendants are dropped and
+/// vaults referencing it are flagged. The work is spawned onto the current
+/// executor because listeners run synchronously on the cache write path; the
+/// cache is held weakly so the listener does not keep it alive.
+fn invalidation_listener(cache: Weak<StorageCache>) -> InvalidationListener {
+    Box::new(move |state_hash| {
+        let Some(cache) = cache.upgrade() else {
+            return;
+        };
+
+        let hash = state_hash.to_vec();
+        let spawned = platform::spawn_detached(async move {
+            let dropped = cache.drop_checkpoints_for_state(&hash).await;
+            let flagged = cache.flag_vaults_for_state(&hash).await;
+            debug!(
+                state_hash = %hex::encode(&hash),
+                dropped_checkpoints = dropped,
+                flagged_vaults = flagged.len(),
+                "Processed invalidation of state"
+            );
+        });
+
+        if !spawned {
+            warn!(
+                state_hash = %hex::encode(state_hash),
+                "No async runtime to process invalidation of state"
+            );
+        }
+    })
+}
+
+/// Listeners and tasks a client installs on a possibly shared storage cache
+///
+/// They are removed when the client is dropped, so clients created and
+/// dropped over the lifetime of one cache do not accumulate on it.
+struct CacheHooks {
+    cache: Weak<StorageCache>,
+    listeners: Vec<InvalidationListenerId>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pruning: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl CacheHooks {
+    fn new(cache: &Arc<StorageCache>) -> Self {
+        Self {
+            cache: Arc::downgrade(cache),
+            listeners: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            pruning: None,
+        }
+    }
+}
+
+impl Drop for CacheHooks {
+    fn drop(&mut self) {
+        if let Some(cache) = self.cache.upgrade() {
+            for id in self.listeners.drain(..) {
+                cache.remove_invalidation_listener(id);
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(pruning) = self.pruning.take() {
+            pruning.abort();
         }
     }
 }
@@ -44,8 +415,18 @@ fn default() -> Self {
 /// Storage node client with full HTTP capabilities
 #[cfg(feature = "reqwest")]
 pub struct StorageNodeClient {
-    /// HTTP client for network operations
-    http_client: reqwest::Client,
+    /// HTTP client for requests over TCP
+    tcp_client: reqwest::Client,
+
+    /// HTTP/3 client, when the client is configured for QUIC
+    quic_client: Option<reqwest::Client>,
+
+    /// Loopback tunnel carrying requests over Noise, when configured
+    #[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+    noise_tunnel: Option<noise::NoiseTunnel>,
+
+    /// Transport chosen by `probe_transport`
+    transport: std::sync::OnceLock<TransportKind>,
 
     /// Base URL of the storage node
     base_url: Url,
@@ -55,6 +436,51 @@ pub struct StorageNodeClient {
 
     /// Cache for recently accessed data
     cache: RwLock<HashMap<String, Vec<u8>>>,
+
+    /// Protocol version agreed with the storage node
+    negotiated_version: OnceCell<ProtocolVersion>,
+
+    /// Wire format for typed objects
+    serialization_format: SerializationFormat,
+
+    /// Typed cache for offline access to genesis states, tokens, checkpoints and vaults
+    storage_cache: Arc<StorageCache>,
+
+    /// Listeners and tasks installed on the storage cache by this client
+    _cache_hooks: CacheHooks,
+
+    /// Handling of expired inbox entries
+    inbox_cleanup: InboxCleanupPolicy,
+
+    /// Acknowledgment deadline of the inbox entries this client sends
+    inbox_ack_deadline_secs: u64,
+
+    /// Cache entries with a background revalidation in flight
+    revalidating: std::sync::Mutex<HashSet<String>>,
+
+    /// Keys recently found to be absent on the storage node
+    negative_cache: Arc<NegativeCache>,
+
+    /// Received inbox entries by recipient genesis hash, in delivery order
+    inbox_cache: RwLock<HashMap<String, Vec<InboxEntry>>>,
+
+    /// Whether vaults returned by searches are added to the storage cache
+    auto_cache_enabled: bool,
+
+    /// Whether fetched checkpoints are checked against an integrity proof
+    integrity_mode: IntegrityMode,
+
+    /// Limits the rate of requests sent to the storage node (None = unlimited)
+    rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// Secret key used to request write permits (None = writes carry no permit)
+    acl_key: Option<Vec<u8>>,
+
+    /// Identity whose permit covers writes not addressed to an identity
+    acl_genesis_hash: Option<String>,
+
+    /// Write permits by hex genesis hash of the writing identity
+    write_permits: RwLock<HashMap<String, WritePermitToken>>,
 }
 
 /// Storage node client with minimal functionality when reqwest is disabled
@@ -68,6 +494,18 @@ pub struct StorageNodeClient {
 
     /// Cache for recently accessed data
     cache: RwLock<HashMap<String, Vec<u8>>>,
+
+    /// Protocol version agreed with the storage node
+    negotiated_version: OnceCell<ProtocolVersion>,
+
+    /// Wire format for typed objects
+    serialization_format: SerializationFormat,
+
+    /// Typed cache for offline access to genesis states, tokens, checkpoints and vaults
+    storage_cache: Arc<StorageCache>,
+
+    /// Listeners and tasks installed on the storage cache by this client
+    _cache_hooks: CacheHooks,
 }
 
 #[cfg(feature = "reqwest")]
@@ -80,24 +518,126 @@ impl StorageNodeClient {
     /// # Returns
     /// * `Result<Self, StorageNodeError>` - The initialized client or an error
     pub fn new(config: StorageNodeClientConfig) -> Result<Self> {
-        let http_client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(config.timeout_seconds.max(1)))
-            .build()
-            .map_err(|e| {
-                StorageNodeError::Network(format!("Failed to create HTTP client: {}", e))
-            })?;
+        Self::with_cache(config, Arc::new(StorageCache::new()))
+    }
+
+    /// Create a new storage node client sharing an existing storage cache
+    ///
+    /// # Arguments
+    /// * `config` - Client configuration including base URL and authentication
+    /// * `storage_cache` - Typed cache used for offline access
+    ///
+    /// # Returns
+    /// * `Result<Self, StorageNodeError>` - The initialized client or an error
+    pub fn with_cache(
+        config: StorageNodeClientConfig,
+        storage_cache: Arc<StorageCache>,
+    ) -> Result<Self> {
+        log_client_config(&config);
+
+        let timeout = Duration::from_secs(config.timeout_seconds.max(1));
+        let builder = reqwest::Client::builder();
+        // Browser fetch has no client-wide timeout
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(timeout);
+
+        // With Noise, requests are sent through a tunnel that encrypts them
+        #[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+        let noise_tunnel = transport::start_noise_tunnel(&config, timeout)?;
+        #[cfg(not(all(feature = "noise", not(target_arch = "wasm32"))))]
+        transport::start_noise_tunnel(&config, timeout)?;
+        #[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+        let builder = match &noise_tunnel {
+            Some(tunnel) => builder.proxy(
+                reqwest::Proxy::all(format!("http://{}", tunnel.local_addr())).map_err(|e| {
+                    StorageNodeError::Config(format!("Invalid Noise tunnel address: {}", e))
+                })?,
+            ),
+            None => builder,
+        };
+
+        let tcp_client = builder.build().map_err(|e| {
+            StorageNodeError::Network(format!("Failed to create HTTP client: {}", e))
+        })?;
+        let quic_client = transport::build_quic_client(&config.transport, timeout)?;
 
         let base_url = Url::parse(&config.base_url)
             .map_err(|e| StorageNodeError::Config(format!("Invalid base URL: {}", e)))?;
 
+        let mut cache_hooks = CacheHooks::new(&storage_cache);
+        cache_hooks.listeners.push(
+            storage_cache.on_invalidation(invalidation_listener(Arc::downgrade(&storage_cache))),
+        );
+
+        // A newly cached invalidation marker must never be masked by an earlier miss
+        let negative_cache = Arc::new(NegativeCache::new(config.negative_cache));
+        let negatives = Arc::downgrade(&negative_cache);
+        cache_hooks
+            .listeners
+            .push(storage_cache.on_invalidation(Box::new(move |state_hash| {
+                if let Some(negatives) = negatives.upgrade() {
+                    negatives.forget(&invalidation_key(state_hash));
+                }
+            })));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(pruning) = &config.vault_pruning {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                cache_hooks.pruning = Some(storage_cache.spawn_vault_pruning(
+                    pruning.policy.clone(),
+                    Duration::from_secs(pruning.interval_secs.max(1)),
+                ));
+            } else {
+                warn!("No async runtime; vault pruning is disabled");
+            }
+        }
+
         Ok(Self {
-            http_client,
+            tcp_client,
+            quic_client,
+            #[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+            noise_tunnel,
+            transport: std::sync::OnceLock::new(),
             base_url,
             api_token: config.api_token,
             cache: RwLock::new(HashMap::new()),
+            negotiated_version: OnceCell::new(),
+            serialization_format: config.serialization_format,
+            storage_cache,
+            _cache_hooks: cache_hooks,
+            inbox_cleanup: config.inbox_cleanup,
+            inbox_ack_deadline_secs: config.inbox_ack_deadline_secs,
+            revalidating: std::sync::Mutex::new(HashSet::new()),
+            negative_cache,
+            inbox_cache: RwLock::new(HashMap::new()),
+            auto_cache_enabled: config.auto_cache_enabled,
+            integrity_mode: config.integrity_mode,
+            rate_limiter: None,
+            acl_key: config.acl_key,
+            acl_genesis_hash: config.acl_genesis_hash,
+            write_permits: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Limit the rate of requests sent to the storage node
+    ///
+    /// Requests over the limit fail with `StorageNodeError::RateLimitExceeded`
+    /// before they are sent.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Get the typed storage cache used by this client
+    pub fn storage_cache(&self) -> Arc<StorageCache> {
+        self.storage_cache.clone()
+    }
+
+    /// Get the base URL of the storage node this client talks to
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
     /// Check if the storage node is healthy by pinging its health endpoint
     ///
     /// # Returns
@@ -109,7 +649,7 @@ pub async fn check_health(&self) -> Result<bool> {
             .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
 
         let response = self
-            .http_client
+            .http_client()
             .get(url)
             .send()
             .await
@@ -118,6 +658,90 @@ pub async fn check_health(&self) -> Result<bool> {
         Ok(response.status().is_success())
     }
 
+    /// Negotiate the protocol version with the storage node
+    ///
+    /// Performs a `GET /version` request the first time it is called and
+    /// caches the result; later calls return the cached version.
+    ///
+    /// # Returns
+    /// * `Result<ProtocolVersion, DsmError>` - The negotiated version or an error
+    pub async fn negotiate_version(&self) -> std::result::Result<ProtocolVersion, DsmError> {
+        self.negotiated_version
+            .get_or_try_init(|| async {
+                let url = self
+                    .base_url
+                    .join("version")
+                    .map_err(|e| DsmError::network("Failed to create URL", Some(e)))?;
+
+                let response = self
+                    .http_client()
+                    .get(url)
+                    .send()
+                    .await
+                    .map_err(|e| DsmError::network("Failed to send request", Some(e)))?;
+
+                if !response.status().is_success() {
+                    return Err(DsmError::network(
+                        format!("Storage node returned error: {}", response.status()),
+                        None::<std::io::Error>,
+                    ));
+                }
+
+                let server_range: ProtocolVersionRange = response.json().await.map_err(|e| {
+                    DsmError::serialization("Failed to parse version response", Some(e))
+                })?;
+
+                negotiate_protocol_version(Self::supported_versions(), server_range)
+            })
+            .await
+            .copied()
+    }
+
+    /// Get the negotiated protocol version, if negotiation has already happened
+    pub fn negotiated_version(&self) -> Option<ProtocolVersion> {
+        self.negotiated_version.get().copied()
+    }
+
+    /// Protocol version range supported by this client
+    pub fn supported_versions() -> ProtocolVersionRange {
+        ProtocolVersionRange {
+            min_version: MIN_PROTOCOL_VERSION,
+            max_version: MAX_PROTOCOL_VERSION,
+        }
+    }
+
+    /// Attach authentication and protocol version headers to a request
+    ///
+    /// Negotiates the protocol version lazily on first use, and takes a token
+    /// for the request's endpoint from the rate limiter, if any.
+    async fn prepare_request(
+        &self,
+        mut builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::RequestBuilder> {
+        if let Some(limiter) = &self.rate_limiter {
+            let (client, request) = builder.build_split();
+            let request = request.map_err(|e| {
+                StorageNodeError::Network(format!("Failed to build request: {}", e))
+            })?;
+
+            // Endpoints are named relative to the base URL
+            let base_path = self.base_url.path().trim_end_matches('/');
+            let path = request.url().path();
+            limiter.acquire_for(path.strip_prefix(base_path).unwrap_or(path))?;
+
+            builder = reqwest::RequestBuilder::from_parts(client, request);
+        }
+
+        let version = self.negotiate_version().await?;
+        builder = builder.header(PROTOCOL_VERSION_HEADER, version.to_string());
+
+        if let Some(token) = &self.api_token {
+            builder = builder.header("Authorization", format!("Bearer {}", token));
+        }
+
+        Ok(builder)
+    }
+
     /// Store data in the storage node
     ///
     /// # Arguments
@@ -128,29 +752,123 @@ pub async fn check_health(&self) -> Result<bool> {
     /// # Returns
     /// * `Result<()>` - Success or an error
     pub async fn store_data(&self, key: &str, data: &[u8], ttl: Option<u64>) -> Result<()> {
+        self.store_encoded(key, data, ttl, None).await
+    }
+
+    /// Store a serializable object using the configured wire format
+    ///
+    /// # Arguments
+    /// * `key` - Unique identifier for the object
+    /// * `value` - Object to encode and store
+    /// * `ttl` - Optional time-to-live in seconds
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn store_object<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<u64>,
+    ) -> Result<()> {
+        let data = self.serialization_format.serialize(value)?;
+        self.store_encoded(key, &data, ttl, Some(self.serialization_format))
+            .await
+    }
+
+    /// Retrieve an object stored with `store_object` and decode it
+    ///
+    /// # Arguments
+    /// * `key` - Unique identifier for the object
+    ///
+    /// # Returns
+    /// * `Result<Option<T>>` - The decoded object if found
+    pub async fn retrieve_object<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.retrieve_data(key).await? {
+            Some(data) => Ok(Some(self.serialization_format.deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store an object behind its schema version
+    async fn store_versioned<T: LegacySchema>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<u64>,
+    ) -> Result<()> {
+        let data = self.serialization_format.serialize_versioned(value)?;
+        self.store_encoded(key, &data, ttl, Some(self.serialization_format))
+            .await
+    }
+
+    /// Retrieve an object stored with `store_versioned`, upgrading older schemas
+    async fn retrieve_versioned<T: LegacySchema>(&self, key: &str) -> Result<Option<T>> {
+        match self.retrieve_data(key).await? {
+            Some(data) => Ok(Some(
+                self.serialization_format.deserialize_versioned(&data)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieve and decode an object from the storage node, bypassing the local byte cache
+    async fn retrieve_remote_object<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.retrieve_remote(key).await? {
+            Some(data) => Ok(Some(self.serialization_format.deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the wire format used for typed objects
+    pub fn serialization_format(&self) -> SerializationFormat {
+        self.serialization_format
+    }
+
+    /// Store raw bytes, optionally tagging them with the format they are encoded in
+    async fn store_encoded(
+        &self,
+        key: &str,
+        data: &[u8],
+        ttl: Option<u64>,
+        format: Option<SerializationFormat>,
+    ) -> Result<()> {
         let url = self
             .base_url
             .join("data")
             .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
 
-        let mut builder = self.http_client.post(url);
-
-        if let Some(token) = &self.api_token {
-            builder = builder.header("Authorization", format!("Bearer {}", token));
-        }
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+        let builder = self.attach_own_write_permit(builder).await?;
 
-        // Construct the payload
-        let mut payload = HashMap::<&str, String>::new();
-        payload.insert("key", key.to_string());
-        let encoded_data = base64::engine::general_purpose::STANDARD.encode(data);
-        payload.insert("data", encoded_data);
+        // The node serves the data back only to readers accepting this format
+        let metadata = format.map(|format| {
+            HashMap::from([(
+                "content_type".to_string(),
+                format.content_type().to_string(),
+            )])
+        });
+        let request = DataSubmissionRequest {
+            blinded_id: key.to_string(),
+            payload: data.to_vec(),
+            ttl,
+            region: None,
+            priority: None,
+            proof_hash: None,
+            metadata,
+        };
 
-        if let Some(ttl_value) = ttl {
-            payload.insert("ttl", ttl_value.to_string());
-        }
+        // CBOR objects travel in a CBOR request, everything else as JSON
+        let builder = match format {
+            Some(SerializationFormat::Cbor) => builder
+                .header(
+                    reqwest::header::CONTENT_TYPE,
+                    SerializationFormat::Cbor.content_type(),
+                )
+                .body(SerializationFormat::Cbor.serialize(&request)?),
+            _ => builder.json(&request),
+        };
 
         let response = builder
-            .json(&payload)
             .send()
             .await
             .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
@@ -185,17 +903,23 @@ pub async fn retrieve_data(&self, key: &str) -> Result<Option<Vec<u8>>> {
             }
         }
 
-        // Fetch from storage node
+        self.retrieve_remote(key).await
+    }
+
+    /// Retrieve data from the storage node, bypassing the local byte cache
+    async fn retrieve_remote(&self, key: &str) -> Result<Option<Vec<u8>>> {
         let url = self
             .base_url
             .join(&format!("data/{}", key))
             .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
 
-        let mut builder = self.http_client.get(url);
-
-        if let Some(token) = &self.api_token {
-            builder = builder.header("Authorization", format!("Bearer {}", token));
-        }
+        let builder = self
+            .prepare_request(self.http_client().get(url))
+            .await?
+            .header(
+                reqwest::header::ACCEPT,
+                self.serialization_format.content_type(),
+            );
 
         let response = builder
             .send()
@@ -213,12 +937,23 @@ pub async fn retrieve_data(&self, key: &str) -> Result<Option<Vec<u8>>> {
             )));
         }
 
+        // Nodes that ignore `Accept` answer with the whole entry as JSON
+        let is_json = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+
         // Parse response
-        let data = response
+        let body = response
             .bytes()
             .await
-            .map_err(|e| StorageNodeError::Network(format!("Failed to read response: {}", e)))?
-            .to_vec();
+            .map_err(|e| StorageNodeError::Network(format!("Failed to read response: {}", e)))?;
+        let data = if is_json {
+            serde_json::from_slice::<BlindedStateEntry>(&body)?.encrypted_payload
+        } else {
+            body.to_vec()
+        };
 
         // Update cache
         let mut cache = self.cache.write().await;
@@ -240,11 +975,8 @@ pub async fn delete_data(&self, key: &str) -> Result<bool> {
             .join(&format!("data/{}", key))
             .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
 
-        let mut builder = self.http_client.delete(url);
-
-        if let Some(token) = &self.api_token {
-            builder = builder.header("Authorization", format!("Bearer {}", token));
-        }
+        let builder = self.prepare_request(self.http_client().delete(url)).await?;
+        let builder = self.attach_own_write_permit(builder).await?;
 
         let response = builder
             .send()
@@ -286,17 +1018,17 @@ pub async fn exists_data(&self, key: &str) -> Result<bool> {
             }
         }
 
-        // Check storage node
+        self.exists_remote(key).await
+    }
+
+    /// Check if data exists on the storage node, bypassing the local byte cache
+    async fn exists_remote(&self, key: &str) -> Result<bool> {
         let url = self
             .base_url
             .join(&format!("data/{}/exists", key))
             .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
 
-        let mut builder = self.http_client.get(url);
-
-        if let Some(token) = &self.api_token {
-            builder = builder.header("Authorization", format!("Bearer {}", token));
-        }
+        let builder = self.prepare_request(self.http_client().get(url)).await?;
 
         let response = builder
             .send()
@@ -305,41 +1037,1137 @@ pub async fn exists_data(&self, key: &str) -> Result<bool> {
 
         Ok(response.status().is_success())
     }
-}
 
-#[cfg(not(feature = "reqwest"))]
-impl StorageNodeClient {
-    /// Create a new storage node client with minimal capabilities
-    pub fn new(config: StorageNodeClientConfig) -> Result<Self> {
-        let base_url = Url::parse(&config.base_url)
-            .map_err(|e| StorageNodeError::Config(format!("Invalid base URL: {}", e)))?;
+    /// Fetch a genesis state by hash, consulting the storage cache first
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hash of the genesis state
+    ///
+    /// # Returns
+    /// * `Result<Option<GenesisState>>` - The genesis state if found
+    pub async fn fetch_genesis_state(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>> {
+        if let Some(genesis) = self.storage_cache.get_genesis(genesis_hash).await? {
+            return Ok(Some(genesis));
+        }
 
-        Ok(Self {
-            base_url,
-            api_token: config.api_token,
-            cache: RwLock::new(HashMap::new()),
-        })
-    }
+        let key = object_key("genesis", &hex::encode(genesis_hash));
+        let genesis: Option<GenesisState> = self.retrieve_object(&key).await?;
 
-    /// Functions below return errors when reqwest is disabled
+        if let Some(genesis) = &genesis {
+            self.storage_cache
+                .cache_genesis(genesis.clone(), false, None)
+                .await?;
+        }
 
-    pub async fn check_health(&self) -> Result<bool> {
-        Err(StorageNodeError::Internal)
+        Ok(genesis)
     }
 
-    pub async fn store_data(&self, _key: &str, _data: &[u8], _ttl: Option<u64>) -> Result<()> {
-        Err(StorageNodeError::Internal)
-    }
+    /// Store a genesis state on the storage node and in the storage cache
+    ///
+    /// # Arguments
+    /// * `genesis` - Genesis state to store
+    ///
+    /// # Returns
+    /// * `Result<String>` - The hex-encoded genesis hash it is stored under
+    pub async fn store_genesis_state(&self, genesis: &GenesisState) -> Result<String> {
+        let genesis_hash = hex::encode(&genesis.hash);
 
-    pub async fn retrieve_data(&self, _key: &str) -> Result<Option<Vec<u8>>> {
-        Err(StorageNodeError::Internal)
-    }
+        self.store_object(&object_key("genesis", &genesis_hash), genesis, None)
+            .await?;
+        self.storage_cache
+            .cache_genesis(genesis.clone(), true, None)
+            .await?;
 
-    pub async fn delete_data(&self, _key: &str) -> Result<bool> {
-        Err(StorageNodeError::Internal)
+        Ok(genesis_hash)
     }
 
-    pub async fn exists_data(&self, _key: &str) -> Result<bool> {
-        Err(StorageNodeError::Internal)
+    /// Fetch a genesis state from the storage node, bypassing the storage cache
+    ///
+    /// Used to confirm that the storage node itself holds the genesis state.
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hash of the genesis state
+    ///
+    /// # Returns
+    /// * `Result<Option<GenesisState>>` - The genesis state if the node holds it
+    pub async fn fetch_remote_genesis_state(
+        &self,
+        genesis_hash: &[u8],
+    ) -> Result<Option<GenesisState>> {
+        self.retrieve_remote_object(&object_key("genesis", &hex::encode(genesis_hash)))
+            .await
+    }
+
+    /// Fetch a token by ID, consulting the storage cache first
+    ///
+    /// # Arguments
+    /// * `token_id` - Identifier of the token
+    ///
+    /// # Returns
+    /// * `Result<Option<Token>>` - The token if found
+    pub async fn fetch_token(&self, token_id: &str) -> Result<Option<Token>> {
+        if let Some(token) = self.storage_cache.get_token(token_id).await? {
+            return Ok(Some(token));
+        }
+
+        let token: Option<Token> = self.retrieve_object(&object_key("token", token_id)).await?;
+
+        if let Some(token) = &token {
+            self.storage_cache
+                .cache_token(token_id, token.clone(), false, None)
+                .await?;
+        }
+
+        Ok(token)
+    }
+
+    /// Fetch a checkpoint state by ID, consulting the storage cache first
+    ///
+    /// In `IntegrityMode::VerifyIntegrity`, a checkpoint fetched from the
+    /// storage node is only returned once its chain matches the node's
+    /// integrity proof.
+    ///
+    /// # Arguments
+    /// * `checkpoint_id` - Identifier of the checkpoint
+    ///
+    /// # Returns
+    /// * `Result<Option<State>>` - The checkpoint state if found
+    pub async fn fetch_checkpoint(&self, checkpoint_id: &str) -> Result<Option<State>> {
+        if let Some(state) = self.storage_cache.get_checkpoint(checkpoint_id).await? {
+            return Ok(Some(state));
+        }
+
+        let state: Option<State> = self
+            .retrieve_versioned(&object_key("checkpoint", checkpoint_id))
+            .await?;
+
+        if let Some(state) = &state {
+            if let IntegrityMode::VerifyIntegrity { node_public_key } = &self.integrity_mode {
+                self.verify_checkpoint_integrity(state, node_public_key)
+                    .await?;
+            }
+            self.storage_cache
+                .cache_checkpoint(state.clone(), false, None)
+                .await?;
+        }
+
+        Ok(state)
+    }
+
+    /// Store a checkpoint state on the storage node and in the storage cache
+    ///
+    /// # Arguments
+    /// * `state` - Checkpoint state to store
+    ///
+    /// # Returns
+    /// * `Result<String>` - The checkpoint identifier
+    pub async fn store_checkpoint(&self, state: &State) -> Result<String> {
+        let checkpoint_id = StorageCache::checkpoint_id(state)?;
+
+        let submission = CheckpointSubmission {
+            checkpoint_id: checkpoint_id.clone(),
+            state_number: state.state_number,
+            state_hash: hex::encode(state.hash()?),
+            payload: self.serialization_format.serialize_versioned(state)?,
+            content_type: self.serialization_format.content_type().to_string(),
+        };
+
+        let url = self
+            .base_url
+            .join("checkpoint")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+        let builder = self.attach_own_write_permit(builder).await?;
+
+        let response = builder
+            .json(&submission)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        self.storage_cache
+            .cache_checkpoint(state.clone(), true, None)
+            .await?;
+
+        Ok(checkpoint_id)
+    }
+
+    /// Check whether the storage node holds a checkpoint
+    ///
+    /// Always asks the storage node; checkpoints held only in a local cache
+    /// do not count.
+    ///
+    /// # Arguments
+    /// * `checkpoint_id` - Identifier of the checkpoint
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether the checkpoint is stored on the node
+    pub async fn checkpoint_exists(&self, checkpoint_id: &str) -> Result<bool> {
+        self.exists_remote(&object_key("checkpoint", checkpoint_id))
+            .await
+    }
+
+    /// Store a history state on the storage node and in the storage cache
+    ///
+    /// # Arguments
+    /// * `state` - State to store
+    ///
+    /// # Returns
+    /// * `Result<String>` - The hex-encoded state hash it is stored under
+    pub async fn store_state(&self, state: &State) -> Result<String> {
+        let state_hash = hex::encode(state.hash()?);
+
+        self.store_versioned(&object_key("state", &state_hash), state, None)
+            .await?;
+        self.storage_cache
+            .cache_state(state.clone(), true, None)
+            .await?;
+
+        Ok(state_hash)
+    }
+
+    /// Fetch a history state by hash, consulting the storage cache first
+    ///
+    /// # Arguments
+    /// * `state_hash` - Hash of the state
+    ///
+    /// # Returns
+    /// * `Result<Option<State>>` - The state if found
+    pub async fn fetch_state(&self, state_hash: &[u8]) -> Result<Option<State>> {
+        if let Some(state) = self.storage_cache.get_state(state_hash).await? {
+            return Ok(Some(state));
+        }
+
+        let state: Option<State> = self
+            .retrieve_versioned(&object_key("state", &hex::encode(state_hash)))
+            .await?;
+
+        if let Some(state) = &state {
+            self.storage_cache
+                .cache_state(state.clone(), false, None)
+                .await?;
+        }
+
+        Ok(state)
+    }
+
+    /// Delete a history state from the storage node and the local caches
+    ///
+    /// # Arguments
+    /// * `state_hash` - Hash of the state
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether the storage node held the state
+    pub async fn delete_state(&self, state_hash: &[u8]) -> Result<bool> {
+        let encoded_hash = hex::encode(state_hash);
+        let url = self
+            .base_url
+            .join(&format!("state/{}", encoded_hash))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let builder = self.prepare_request(self.http_client().delete(url)).await?;
+        let builder = self.attach_own_write_permit(builder).await?;
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        // 404 means it didn't exist, which isn't an error for delete
+        let deleted = response.status() != reqwest::StatusCode::NOT_FOUND;
+        if deleted && !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        self.cache
+            .write()
+            .await
+            .remove(&object_key("state", &encoded_hash));
+        self.storage_cache.remove_state(state_hash).await;
+
+        Ok(deleted)
+    }
+
+    /// Fetch a vault by ID, consulting the storage cache first
+    ///
+    /// A vault without a content commitment is returned but not cached.
+    ///
+    /// # Arguments
+    /// * `vault_id` - Identifier of the vault
+    ///
+    /// # Returns
+    /// * `Result<Option<LimboVault>>` - The vault if found
+    pub async fn fetch_vault(&self, vault_id: &str) -> Result<Option<LimboVault>> {
+        if let Some(vault) = self.storage_cache.get_vault(vault_id).await? {
+            return Ok(Some(vault));
+        }
+
+        let vault: Option<LimboVault> =
+            self.retrieve_object(&object_key("vault", vault_id)).await?;
+
+        if let Some(vault) = &vault {
+            self.cache_fetched_vault(vault).await?;
+        }
+
+        Ok(vault)
+    }
+
+    /// Cache a vault fetched from a storage node
+    ///
+    /// A vault whose content commitment is defaulted cannot have its content
+    /// checked when claimed, so it is never cached for offline use.
+    pub(crate) async fn cache_fetched_vault(&self, vault: &LimboVault) -> Result<()> {
+        if vault.content_commitment.is_empty() {
+            warn!(vault_id = %vault.id, "Not caching vault without a content commitment");
+            return Ok(());
+        }
+
+        self.storage_cache
+            .cache_vault(vault.clone(), false, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Store a vault on the storage node
+    ///
+    /// Carries a write permit for the vault's creator when the client has an
+    /// ACL key.
+    ///
+    /// # Arguments
+    /// * `submission` - Vault and its creator's signature
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn store_vault(&self, submission: &crate::api::VaultSubmission) -> Result<()> {
+        let url = self
+            .base_url
+            .join("vault")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+        let builder = self
+            .attach_write_permit(builder, &submission.vault.creator_id)
+            .await?;
+
+        let response = builder
+            .json(submission)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Publish a creator-signed extension of a vault's time lock
+    ///
+    /// The storage node rejects extensions signed after the vault's unlock
+    /// time, once the vault is claimable.
+    ///
+    /// # Arguments
+    /// * `extension` - Extension from `DLVManager::extend_vault_timelock`
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn extend_vault_timelock(&self, extension: &TimelockExtension) -> Result<()> {
+        let url = self
+            .base_url
+            .join(&format!("vault/{}/extend", extension.vault_id))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .json(extension)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Publish a transfer of a vault's creator rights
+    ///
+    /// The storage node rejects transfers not signed by the vault's current
+    /// creator.
+    ///
+    /// # Arguments
+    /// * `transfer` - Transfer from `DLVManager::transfer_vault_ownership`
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn submit_ownership_transfer(
+        &self,
+        transfer: &OwnershipTransferRecord,
+    ) -> Result<()> {
+        let url = self
+            .base_url
+            .join(&format!("vault/{}/owner", transfer.vault_id))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .json(transfer)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Publish a change of a vault's intended recipient
+    ///
+    /// The storage node rejects reassignments not signed by the vault's
+    /// current creator, or not starting from its current recipient.
+    ///
+    /// # Arguments
+    /// * `reassignment` - Reassignment from `DLVManager::reassign_vault_recipient`
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn submit_recipient_reassignment(
+        &self,
+        reassignment: &RecipientReassignment,
+    ) -> Result<()> {
+        let url = self
+            .base_url
+            .join(&format!("vault/{}/recipient", reassignment.vault_id))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().put(url)).await?;
+
+        let response = builder
+            .json(reassignment)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Publish a vault's content re-encrypted to its recipient's new key
+    ///
+    /// The storage node rejects updates not signed by the vault's current
+    /// creator, or not newer than the recipient key generation it holds.
+    ///
+    /// # Arguments
+    /// * `update` - Update from `DLVManager::reencrypt_vault_for`
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn submit_vault_content_update(&self, update: &VaultContentUpdate) -> Result<()> {
+        let url = self
+            .base_url
+            .join(&format!("vault/{}/content", update.vault_id))
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        let builder = self.prepare_request(self.http_client().put(url)).await?;
+
+        let response = builder
+            .json(update)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Store one chunk of a chunked vault's content
+    ///
+    /// # Arguments
+    /// * `vault_id` - Identifier of the vault
+    /// * `index` - Position of the chunk in the content
+    /// * `data` - Chunk from `LimboVault::new_chunked`
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn put_vault_chunk(&self, vault_id: &str, index: usize, data: &[u8]) -> Result<()> {
+        self.store_data(&vault_chunk_key(vault_id, index), data, None)
+            .await
+    }
+
+    /// Fetch one chunk of a chunked vault's content
+    ///
+    /// The chunk is not verified; `fetch_vault_content` checks every chunk
+    /// against the vault's committed hashes.
+    ///
+    /// # Arguments
+    /// * `vault_id` - Identifier of the vault
+    /// * `index` - Position of the chunk in the content
+    ///
+    /// # Returns
+    /// * `Result<Option<Vec<u8>>>` - The chunk if found
+    pub async fn get_vault_chunk(&self, vault_id: &str, index: usize) -> Result<Option<Vec<u8>>> {
+        self.retrieve_data(&vault_chunk_key(vault_id, index)).await
+    }
+
+    /// Fetch and reassemble a vault's ciphertext
+    ///
+    /// Chunks are fetched in order and each is verified before the next is
+    /// requested. Single-blob vaults are returned without fetching.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - The ciphertext, ready for `LimboVault::claim_assembled`
+    pub async fn fetch_vault_content(&self, vault: &LimboVault) -> Result<Vec<u8>> {
+        let fetch_chunk = |index: usize| async move {
+            self.get_vault_chunk(&vault.id, index)
+                .await
+                .map_err(|e| DsmError::storage("Failed to fetch vault chunk", Some(e)))?
+                .ok_or_else(|| {
+                    DsmError::not_found("Vault chunk", Some(format!("{}/{}", vault.id, index)))
+                })
+        };
+
+        Ok(assemble_content(&vault.encrypted_content, fetch_chunk).await?)
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    /// Create a new storage node client with minimal capabilities
+    pub fn new(config: StorageNodeClientConfig) -> Result<Self> {
+        Self::with_cache(config, Arc::new(StorageCache::new()))
+    }
+
+    /// Create a new storage node client sharing an existing storage cache
+    pub fn with_cache(
+        config: StorageNodeClientConfig,
+        storage_cache: Arc<StorageCache>,
+    ) -> Result<Self> {
+        log_client_config(&config);
+
+        let base_url = Url::parse(&config.base_url)
+            .map_err(|e| StorageNodeError::Config(format!("Invalid base URL: {}", e)))?;
+
+        let mut cache_hooks = CacheHooks::new(&storage_cache);
+        cache_hooks.listeners.push(
+            storage_cache.on_invalidation(invalidation_listener(Arc::downgrade(&storage_cache))),
+        );
+
+        Ok(Self {
+            base_url,
+            api_token: config.api_token,
+            cache: RwLock::new(HashMap::new()),
+            negotiated_version: OnceCell::new(),
+            serialization_format: config.serialization_format,
+            storage_cache,
+            _cache_hooks: cache_hooks,
+        })
+    }
+
+    /// Requests are never sent without reqwest, so there is nothing to limit
+    pub fn with_rate_limiter(self, _limiter: Arc<RateLimiter>) -> Self {
+        self
+    }
+
+    /// Functions below return errors when reqwest is disabled
+
+    pub async fn check_health(&self) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn negotiate_version(&self) -> std::result::Result<ProtocolVersion, DsmError> {
+        Err(DsmError::feature_not_available(
+            "Protocol version negotiation",
+            Some("the reqwest feature is disabled"),
+        ))
+    }
+
+    pub fn negotiated_version(&self) -> Option<ProtocolVersion> {
+        self.negotiated_version.get().copied()
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    pub fn supported_versions() -> ProtocolVersionRange {
+        ProtocolVersionRange {
+            min_version: MIN_PROTOCOL_VERSION,
+            max_version: MAX_PROTOCOL_VERSION,
+        }
+    }
+
+    pub async fn store_data(&self, _key: &str, _data: &[u8], _ttl: Option<u64>) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn retrieve_data(&self, _key: &str) -> Result<Option<Vec<u8>>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn store_object<T: Serialize>(
+        &self,
+        _key: &str,
+        _value: &T,
+        _ttl: Option<u64>,
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn retrieve_object<T: DeserializeOwned>(&self, _key: &str) -> Result<Option<T>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn delete_data(&self, _key: &str) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn exists_data(&self, _key: &str) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_genesis_state(&self, _genesis_hash: &[u8]) -> Result<Option<GenesisState>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn store_genesis_state(&self, _genesis: &GenesisState) -> Result<String> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_remote_genesis_state(
+        &self,
+        _genesis_hash: &[u8],
+    ) -> Result<Option<GenesisState>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_token(&self, _token_id: &str) -> Result<Option<Token>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_checkpoint(&self, _checkpoint_id: &str) -> Result<Option<State>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn store_checkpoint(&self, _state: &State) -> Result<String> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn checkpoint_exists(&self, _checkpoint_id: &str) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn store_state(&self, _state: &State) -> Result<String> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_state(&self, _state_hash: &[u8]) -> Result<Option<State>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn delete_state(&self, _state_hash: &[u8]) -> Result<bool> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_vault(&self, _vault_id: &str) -> Result<Option<LimboVault>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn store_vault(&self, _submission: &crate::api::VaultSubmission) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn extend_vault_timelock(&self, _extension: &TimelockExtension) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn submit_ownership_transfer(
+        &self,
+        _transfer: &OwnershipTransferRecord,
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn submit_recipient_reassignment(
+        &self,
+        _reassignment: &RecipientReassignment,
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn submit_vault_content_update(&self, _update: &VaultContentUpdate) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn put_vault_chunk(
+        &self,
+        _vault_id: &str,
+        _index: usize,
+        _data: &[u8],
+    ) -> Result<()> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn get_vault_chunk(&self, _vault_id: &str, _index: usize) -> Result<Option<Vec<u8>>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_vault_content(&self, _vault: &LimboVault) -> Result<Vec<u8>> {
+        Err(StorageNodeError::Internal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min: (u16, u16, u16), max: (u16, u16, u16)) -> ProtocolVersionRange {
+        ProtocolVersionRange {
+            min_version: ProtocolVersion::new(min.0, min.1, min.2),
+            max_version: ProtocolVersion::new(max.0, max.1, max.2),
+        }
+    }
+
+    #[test]
+    fn test_negotiation_identical_ranges() {
+        let client = range((1, 0, 0), (1, 2, 0));
+        let server = range((1, 0, 0), (1, 2, 0));
+
+        let version = negotiate_protocol_version(client, server).unwrap();
+        assert_eq!(version, ProtocolVersion::new(1, 2, 0));
+    }
+
+    #[test]
+    fn test_negotiation_downgrades_to_common_version() {
+        // Newer server, older client
+        let client = range((1, 0, 0), (1, 1, 0));
+        let server = range((1, 0, 0), (1, 3, 2));
+        assert_eq!(
+            negotiate_protocol_version(client, server).unwrap(),
+            ProtocolVersion::new(1, 1, 0)
+        );
+
+        // Newer client, older server
+        let client = range((1, 1, 0), (2, 0, 0));
+        let server = range((1, 0, 0), (1, 4, 0));
+        assert_eq!(
+            negotiate_protocol_version(client, server).unwrap(),
+            ProtocolVersion::new(1, 4, 0)
+        );
+    }
+
+    #[test]
+    fn test_negotiation_fails_without_overlap() {
+        // Older client, newer server
+        let client = range((1, 0, 0), (1, 5, 0));
+        let server = range((2, 0, 0), (2, 1, 0));
+
+        match negotiate_protocol_version(client, server) {
+            Err(DsmError::VersionIncompatible {
+                client: reported_client,
+                server: reported_server,
+            }) => {
+                assert_eq!(reported_client, client.to_string());
+                assert_eq!(reported_server, server.to_string());
+            }
+            other => panic!("Expected VersionIncompatible, got {:?}", other),
+        }
+
+        // Newer client, older server: the server's maximum is the bound that fails
+        let client = range((2, 0, 0), (2, 1, 0));
+        let server = range((1, 0, 0), (1, 5, 0));
+        let message = negotiate_protocol_version(client, server)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("client supports 2.0.0-2.1.0"));
+        assert!(message.contains("server supports 1.0.0-1.5.0"));
+    }
+
+    #[test]
+    fn test_bincode_format_round_trip() {
+        let format = SerializationFormat::Bincode;
+        let value = (42u64, "payload".to_string(), vec![1u8, 2, 3]);
+
+        let bytes = format.serialize(&value).unwrap();
+        let decoded: (u64, String, Vec<u8>) = format.deserialize(&bytes).unwrap();
+
+        assert_eq!(decoded, value);
+        assert_eq!(format.content_type(), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_config_defaults_to_bincode() {
+        let config: StorageNodeClientConfig = serde_json::from_value(serde_json::json!({
+            "base_url": "http://localhost:8080",
+            "api_token": null,
+            "timeout_seconds": 30,
+        }))
+        .unwrap();
+
+        assert_eq!(config.serialization_format, SerializationFormat::Bincode);
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    #[test]
+    fn test_cbor_requires_feature() {
+        assert!(SerializationFormat::Cbor.serialize(&1u32).is_err());
+    }
+
+    #[test]
+    fn test_states_carry_schema_version() {
+        use dsm::types::state_types::DeviceInfo;
+        use dsm::types::versioned::check_schema_compatibility;
+
+        let mut state = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("dev", vec![0; 32]));
+        state.is_checkpoint = true;
+
+        let format = SerializationFormat::Bincode;
+        let bytes = format.serialize_versioned(&state).unwrap();
+        assert_eq!(
+            check_schema_compatibility(&bytes).unwrap(),
+            State::SCHEMA_VERSION
+        );
+
+        let decoded: State = format.deserialize_versioned(&bytes).unwrap();
+        assert_eq!(decoded.hash().unwrap(), state.hash().unwrap());
+        assert!(decoded.is_checkpoint);
+
+        // Data too short to hold a schema version is rejected
+        assert!(format.deserialize_versioned::<State>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_protocol_version_parse_and_display() {
+        let version = ProtocolVersion::parse("3.14.15").unwrap();
+        assert_eq!(version, ProtocolVersion::new(3, 14, 15));
+        assert_eq!(version.to_string(), "3.14.15");
+
+        assert!(ProtocolVersion::parse("1.2").is_none());
+        assert!(ProtocolVersion::parse("1.2.3.4").is_none());
+        assert!(ProtocolVersion::parse("a.b.c").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_drops_cached_checkpoints() {
+        use dsm::recovery::invalidation::InvalidationMarker;
+        use dsm::types::state_types::DeviceInfo;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig::default()).unwrap();
+        let cache = client.storage_cache();
+
+        let mut state = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("dev", vec![0; 32]));
+        state.hash = state.hash().unwrap();
+        let checkpoint_id = StorageCache::checkpoint_id(&state).unwrap();
+        cache
+            .cache_checkpoint(state.clone(), true, None)
+            .await
+            .unwrap();
+
+        let marker = InvalidationMarker::create(&state, "forked", 1).unwrap();
+        cache.cache_invalidation(marker, true, None).await.unwrap();
+
+        // The listener evicts asynchronously
+        for _ in 0..50 {
+            if !cache.has_checkpoint(&checkpoint_id).await {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        assert!(!cache.has_checkpoint(&checkpoint_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_clients_remove_their_cache_listeners() {
+        let cache = Arc::new(StorageCache::new());
+
+        let clients: Vec<StorageNodeClient> = (0..3)
+            .map(|_| {
+                StorageNodeClient::with_cache(StorageNodeClientConfig::default(), cache.clone())
+                    .unwrap()
+            })
+            .collect();
+        assert!(cache.invalidation_listener_count() > 0);
+
+        drop(clients);
+        assert_eq!(cache.invalidation_listener_count(), 0);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_vault_without_commitment_is_not_cached() {
+        use dsm::crypto::pedersen::{PedersenCommitment, PedersenParams};
+        use dsm::vault::LimboVault;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig::default()).unwrap();
+        let bare = LimboVault {
+            id: "bare".to_string(),
+            ..LimboVault::default()
+        };
+        let committed = LimboVault {
+            id: "committed".to_string(),
+            content_commitment: PedersenCommitment::commit(
+                PedersenParams::vault_content_params(),
+                b"content",
+                &mut rand::thread_rng(),
+            )
+            .unwrap()
+            .0,
+            ..LimboVault::default()
+        };
+
+        client.cache_fetched_vault(&bare).await.unwrap();
+        client.cache_fetched_vault(&committed).await.unwrap();
+
+        let cache = client.storage_cache();
+        assert!(cache.get_vault("bare").await.unwrap().is_none());
+        assert!(cache.get_vault("committed").await.unwrap().is_some());
+    }
+
+    #[cfg(feature = "cbor")]
+    mod cbor {
+        use super::*;
+        use dsm::core::identity::GenesisState;
+        use dsm::crypto::{kyber, sphincs};
+        use dsm::test_support::genesis;
+        use dsm::types::state_types::{DeviceInfo, State};
+        use dsm::types::token_types::{Balance, Token};
+        use dsm::vault::{FulfillmentMechanism, LimboVault};
+        use proptest::prelude::*;
+
+        /// Decode a value through both formats and compare the results
+        ///
+        /// Values are compared through their JSON representation, which is
+        /// insensitive to the iteration order of the maps they contain.
+        fn assert_round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+            let expected = serde_json::to_value(value).unwrap();
+
+            let cbor = SerializationFormat::Cbor;
+            let via_cbor: T = cbor.deserialize(&cbor.serialize(value).unwrap()).unwrap();
+
+            let bincode = SerializationFormat::Bincode;
+            let via_bincode: T = bincode
+                .deserialize(&bincode.serialize(value).unwrap())
+                .unwrap();
+
+            assert_eq!(serde_json::to_value(&via_cbor).unwrap(), expected);
+            assert_eq!(serde_json::to_value(&via_bincode).unwrap(), expected);
+        }
+
+        fn sample_state(entropy: Vec<u8>, device_id: &str, balances: &[(String, u64)]) -> State {
+            let mut state = State::new_genesis(entropy, DeviceInfo::new(device_id, vec![9u8; 32]));
+            for (token_id, amount) in balances {
+                state
+                    .token_balances
+                    .insert(token_id.clone(), Balance::new(*amount));
+            }
+            state
+        }
+
+        #[test]
+        fn test_content_type() {
+            assert_eq!(SerializationFormat::Cbor.content_type(), "application/cbor");
+            assert_eq!(
+                SerializationFormat::from_content_type("application/json, application/cbor;q=0.9"),
+                Some(SerializationFormat::Cbor)
+            );
+            assert_eq!(
+                SerializationFormat::from_content_type("application/json"),
+                None
+            );
+        }
+
+        #[test]
+        fn test_state_round_trip() {
+            let state = sample_state(vec![1, 2, 3, 4], "device-1", &[("ROOT".into(), 1000)]);
+            assert_round_trip(&state);
+        }
+
+        #[test]
+        fn test_genesis_state_round_trip() {
+            let genesis = GenesisState {
+                merkle_root: Some(vec![0xcc; 32]),
+                ..genesis(vec![0xaa; 32])
+            };
+
+            assert_round_trip(&genesis);
+        }
+
+        #[test]
+        fn test_token_round_trip() {
+            let token = Token::new(
+                "owner",
+                b"token-data".to_vec(),
+                b"meta".to_vec(),
+                Balance::new(5),
+            );
+            assert_round_trip(&token);
+        }
+
+        #[test]
+        fn test_limbo_vault_round_trip() {
+            let (sphincs_pk, sphincs_sk) = sphincs::generate_sphincs_keypair().unwrap();
+            let (kyber_pk, _kyber_sk) = kyber::generate_kyber_keypair().unwrap();
+            let reference_state = sample_state(vec![5; 32], "device-1", &[]);
+
+            let vault = LimboVault::new(
+                (&sphincs_pk, &sphincs_sk),
+                FulfillmentMechanism::TimeRelease {
+                    unlock_time: 10,
+                    reference_states: vec![vec![7; 32]],
+                },
+                b"vault content",
+                "text/plain",
+                Some(kyber_pk),
+                &reference_state,
+            )
+            .unwrap();
+
+            assert_round_trip(&vault);
+        }
+
+        proptest! {
+            #[test]
+            fn prop_random_states_decode_identically(
+                entropy in proptest::collection::vec(any::<u8>(), 0..64),
+                device_id in "[a-z0-9]{1,16}",
+                balances in proptest::collection::vec(("[A-Z]{1,8}", any::<u64>()), 0..8),
+            ) {
+                let state = sample_state(entropy, &device_id, &balances);
+                assert_round_trip(&state);
+            }
+        }
+
+        #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+        #[tokio::test]
+        async fn test_cbor_object_round_trips_through_node() {
+            use crate::test_support::{api_server, serve};
+
+            let base_url = serve(api_server());
+            let client = |serialization_format| {
+                StorageNodeClient::new(StorageNodeClientConfig {
+                    base_url: base_url.clone(),
+                    serialization_format,
+                    ..StorageNodeClientConfig::default()
+                })
+                .unwrap()
+            };
+            let genesis = genesis(vec![0xaa; 32]);
+
+            client(SerializationFormat::Cbor)
+                .store_object("genesis-object", &genesis, None)
+                .await
+                .unwrap();
+
+            // A new client has nothing cached, so the object comes from the node
+            let fetched: GenesisState = client(SerializationFormat::Cbor)
+                .retrieve_object("genesis-object")
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(fetched.hash, genesis.hash);
+
+            // A reader expecting another format is refused rather than misreading it
+            assert!(client(SerializationFormat::Bincode)
+                .retrieve_data("genesis-object")
+                .await
+                .is_err());
+        }
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_rate_limited_requests_not_sent() {
+        use dsm::communication::TokenBucketConfig;
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        let deletes = server
+            .mock("DELETE", mockito::Matcher::Regex("^/state/".to_string()))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let unlimited = TokenBucketConfig {
+            capacity: 100,
+            refill_rate: 100.0,
+        };
+        let states = TokenBucketConfig {
+            capacity: 2,
+            refill_rate: 0.0,
+        };
+        let limiter =
+            RateLimiter::new(unlimited).with_endpoint_limits(HashMap::from([("/state", states)]));
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap()
+        .with_rate_limiter(Arc::new(limiter));
+
+        assert!(client.delete_state(&[1; 32]).await.unwrap());
+        assert!(client.delete_state(&[2; 32]).await.unwrap());
+        assert!(matches!(
+            client.delete_state(&[3; 32]).await,
+            Err(StorageNodeError::RateLimitExceeded(_))
+        ));
+        deletes.assert_async().await;
+    }
+
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_constructor_logs_config_without_credentials() {
+        let _client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: "http://127.0.0.1:8765".to_string(),
+            api_token: Some("secret-token".to_string()),
+            timeout_seconds: 7,
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+
+        assert!(logs_contain("Creating storage node client"));
+        assert!(logs_contain("base_url=http://127.0.0.1:8765"));
+        assert!(logs_contain("timeout_seconds=7"));
+        assert!(logs_contain("authenticated=true"));
+        assert!(!logs_contain("secret-token"));
     }
 }
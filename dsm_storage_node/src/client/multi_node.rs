@@ -0,0 +1,517 @@
+// Multi-node client for the DSM Storage Node Client
+//
+// A wallet usually knows several storage nodes. `MultiNodeClient` keeps a
+// client per node and a reputation for each one, built from the outcome and
+// latency of every call, and sends each call to the node with the best
+// reputation, failing over to the next best when a node does not answer.
+
+use super::platform;
+use super::{StorageNodeClient, StorageNodeClientConfig};
+use crate::error::{Result, StorageNodeError};
+use dsm::communication::StorageCache;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// Default weight of the newest sample in the reputation averages
+pub const DEFAULT_REPUTATION_ALPHA: f64 = 0.2;
+
+/// Default idle time in seconds after which a reputation starts to decay
+pub const DEFAULT_REPUTATION_STALE_SECS: u64 = 300;
+
+/// Default fraction of the distance to neutral a stale reputation covers per second
+pub const DEFAULT_REPUTATION_DECAY_RATE: f64 = 0.01;
+
+/// Latency in milliseconds assumed for a node until a call to it succeeds
+pub const NEUTRAL_LATENCY_MS: u64 = 100;
+
+/// Outcome of a call as far as reputation is concerned
+type CallOutcome = std::result::Result<(), ErrorCode>;
+
+/// Coarse classification of call failures, for reputation bookkeeping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The node could not be reached
+    Network,
+
+    /// The node did not answer in time
+    Timeout,
+
+    /// The node refused the call because of load
+    RateLimited,
+
+    /// The node answered that the object does not exist
+    NotFound,
+
+    /// The node rejected the call as unauthorised or invalid
+    Rejected,
+
+    /// The node failed to process the call
+    Server,
+}
+
+impl ErrorCode {
+    /// Whether the failure says something about the node rather than the call
+    ///
+    /// Calls that fail with a node fault are retried on the next node; other
+    /// failures are answers and are returned as they are.
+    pub fn is_node_fault(&self) -> bool {
+        !matches!(self, ErrorCode::NotFound | ErrorCode::Rejected)
+    }
+}
+
+impl From<&StorageNodeError> for ErrorCode {
+    fn from(error: &StorageNodeError) -> Self {
+        match error {
+            StorageNodeError::Network(_)
+            | StorageNodeError::Request(_)
+            | StorageNodeError::ReceiveFailure(_) => ErrorCode::Network,
+            StorageNodeError::Timeout => ErrorCode::Timeout,
+            StorageNodeError::RateLimitExceeded(_)
+            | StorageNodeError::ConcurrencyLimitExceeded
+            | StorageNodeError::QueueFull(_) => ErrorCode::RateLimited,
+            StorageNodeError::NotFound(_) => ErrorCode::NotFound,
+            StorageNodeError::Authentication(_)
+            | StorageNodeError::InvalidInput(_)
+            | StorageNodeError::InvalidOperation(_) => ErrorCode::Rejected,
+            _ => ErrorCode::Server,
+        }
+    }
+}
+
+/// Observed reliability and speed of a storage node
+///
+/// A node nobody has called yet is assumed reliable at a neutral latency, so
+/// that every node gets tried; stale reputations drift back to this default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeReputation {
+    /// Moving average of call outcomes (1.0 = every call succeeded)
+    pub success_rate: f64,
+
+    /// Moving average of successful call latency in milliseconds
+    pub average_latency_ms: u64,
+
+    /// Number of failed calls by kind
+    pub error_counts: HashMap<ErrorCode, u32>,
+
+    /// Time of the last successful call in seconds since the Unix epoch (0 = never)
+    pub last_success: u64,
+
+    /// Time of the last call in seconds since the Unix epoch (0 = never)
+    pub last_call: u64,
+}
+
+impl Default for NodeReputation {
+    fn default() -> Self {
+        Self {
+            success_rate: 1.0,
+            average_latency_ms: NEUTRAL_LATENCY_MS,
+            error_counts: HashMap::new(),
+            last_success: 0,
+            last_call: 0,
+        }
+    }
+}
+
+impl NodeReputation {
+    /// Selection score; higher is better
+    ///
+    /// Latencies under a millisecond count as one millisecond.
+    pub fn score(&self) -> f64 {
+        self.success_rate / self.average_latency_ms.max(1) as f64
+    }
+
+    /// Fold the outcome of a call into the moving averages
+    fn record(&mut self, outcome: CallOutcome, latency_ms: u64, now: u64, alpha: f64) {
+        let sample = if outcome.is_ok() { 1.0 } else { 0.0 };
+        self.success_rate = alpha * sample + (1.0 - alpha) * self.success_rate;
+
+        match outcome {
+            Ok(()) => {
+                // Failed calls say nothing about speed, so only successes are
+                // timed; the first one replaces the neutral prior outright
+                self.average_latency_ms = if self.last_success == 0 {
+                    latency_ms
+                } else {
+                    (alpha * latency_ms as f64 + (1.0 - alpha) * self.average_latency_ms as f64)
+                        .round() as u64
+                };
+                self.last_success = now;
+            }
+            Err(code) => *self.error_counts.entry(code).or_insert(0) += 1,
+        }
+        self.last_call = now;
+    }
+
+    /// Reputation as of `now`, drifted toward neutral if the node has been idle
+    ///
+    /// After `stale_after` seconds without calls, every further second moves
+    /// the averages `decay_rate` of the remaining way back to the default.
+    fn decayed(&self, now: u64, stale_after: u64, decay_rate: f64) -> NodeReputation {
+        let idle = now.saturating_sub(self.last_call);
+        if self.last_call == 0 || idle <= stale_after {
+            return self.clone();
+        }
+
+        let weight = (1.0 - decay_rate.clamp(0.0, 1.0)).powf((idle - stale_after) as f64);
+        let neutral = NodeReputation::default();
+
+        NodeReputation {
+            success_rate: neutral.success_rate
+                + (self.success_rate - neutral.success_rate) * weight,
+            average_latency_ms: (neutral.average_latency_ms as f64
+                + (self.average_latency_ms as f64 - neutral.average_latency_ms as f64) * weight)
+                .round() as u64,
+            ..self.clone()
+        }
+    }
+}
+
+/// Multi-node client configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiNodeClientConfig {
+    /// Configuration of each storage node, identified by its base URL
+    pub nodes: Vec<StorageNodeClientConfig>,
+
+    /// Weight of the newest sample in the reputation averages (0.0 - 1.0)
+    #[serde(default = "default_reputation_alpha")]
+    pub reputation_alpha: f64,
+
+    /// Idle time in seconds after which a reputation starts to decay
+    #[serde(default = "default_reputation_stale_secs")]
+    pub reputation_stale_secs: u64,
+
+    /// Fraction of the distance to neutral a stale reputation covers per second
+    #[serde(default = "default_reputation_decay_rate")]
+    pub reputation_decay_rate: f64,
+}
+
+fn default_reputation_alpha() -> f64 {
+    DEFAULT_REPUTATION_ALPHA
+}
+
+fn default_reputation_stale_secs() -> u64 {
+    DEFAULT_REPUTATION_STALE_SECS
+}
+
+fn default_reputation_decay_rate() -> f64 {
+    DEFAULT_REPUTATION_DECAY_RATE
+}
+
+impl Default for MultiNodeClientConfig {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            reputation_alpha: DEFAULT_REPUTATION_ALPHA,
+            reputation_stale_secs: DEFAULT_REPUTATION_STALE_SECS,
+            reputation_decay_rate: DEFAULT_REPUTATION_DECAY_RATE,
+        }
+    }
+}
+
+/// Node in a selection heap, ordered by score
+struct RankedNode {
+    score: f64,
+    index: usize,
+}
+
+impl PartialEq for RankedNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RankedNode {}
+
+impl PartialOrd for RankedNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Equal scores go to the node listed first in the configuration
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Client that spreads calls over several storage nodes by reputation
+pub struct MultiNodeClient {
+    /// Node IDs (base URLs) and their clients, in configuration order
+    nodes: Vec<(String, Arc<StorageNodeClient>)>,
+
+    /// Reputation by node ID
+    reputations: Mutex<HashMap<String, NodeReputation>>,
+
+    /// Weight of the newest sample in the reputation averages
+    alpha: f64,
+
+    /// Idle time in seconds after which a reputation starts to decay
+    stale_after: u64,
+
+    /// Per-second decay of stale reputations toward neutral
+    decay_rate: f64,
+}
+
+impl MultiNodeClient {
+    /// Create a client for the configured nodes
+    ///
+    /// All node clients share one storage cache.
+    pub fn new(config: MultiNodeClientConfig) -> Result<Self> {
+        if config.nodes.is_empty() {
+            return Err(StorageNodeError::Config(
+                "Multi-node client needs at least one node".to_string(),
+            ));
+        }
+
+        let storage_cache = Arc::new(StorageCache::new());
+        let nodes = config
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let node_id = node.base_url.clone();
+                let client = StorageNodeClient::with_cache(node, storage_cache.clone())?;
+                Ok((node_id, Arc::new(client)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let reputations = nodes
+            .iter()
+            .map(|(node_id, _)| (node_id.clone(), NodeReputation::default()))
+            .collect();
+
+        Ok(Self {
+            nodes,
+            reputations: Mutex::new(reputations),
+            alpha: config.reputation_alpha.clamp(0.0, 1.0),
+            stale_after: config.reputation_stale_secs,
+            decay_rate: config.reputation_decay_rate,
+        })
+    }
+
+    /// Current reputation of every node, in configuration order
+    pub fn node_reputations(&self) -> Vec<(String, NodeReputation)> {
+        let now = platform::now_secs();
+        let reputations = match self.reputations.lock() {
+            Ok(reputations) => reputations,
+            Err(_) => return Vec::new(),
+        };
+
+        self.nodes
+            .iter()
+            .filter_map(|(node_id, _)| {
+                let reputation = reputations.get(node_id)?;
+                Some((
+                    node_id.clone(),
+                    reputation.decayed(now, self.stale_after, self.decay_rate),
+                ))
+            })
+            .collect()
+    }
+
+    /// ID of the node the next call will go to first
+    pub fn best_node(&self) -> Option<String> {
+        let best = self.rank_nodes().pop()?;
+        Some(self.nodes[best.index].0.clone())
+    }
+
+    /// Run a call on the best node, failing over to the next best
+    ///
+    /// Every attempt updates the reputation of the node it went to. A failure
+    /// that is not the node's fault (see [`ErrorCode::is_node_fault`]) is
+    /// returned without trying other nodes.
+    pub async fn call<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: Fn(Arc<StorageNodeClient>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut ranking = self.rank_nodes();
+        let mut last_error = StorageNodeError::Internal;
+
+        while let Some(RankedNode { index, .. }) = ranking.pop() {
+            let (node_id, client) = &self.nodes[index];
+
+            let started = platform::now();
+            let result = operation(client.clone()).await;
+            let latency_ms = platform::now().saturating_sub(started).as_millis() as u64;
+
+            match result {
+                Ok(value) => {
+                    self.record_call(node_id, Ok(()), latency_ms);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    let code = ErrorCode::from(&e);
+                    self.record_call(node_id, Err(code), latency_ms);
+                    if !code.is_node_fault() {
+                        return Err(e);
+                    }
+
+                    debug!(
+                        node_id = %node_id,
+                        code = ?code,
+                        error = %e,
+                        "Storage node failed, trying next node"
+                    );
+                    last_error = e;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Build a max-heap of the nodes by current score
+    fn rank_nodes(&self) -> BinaryHeap<RankedNode> {
+        let now = platform::now_secs();
+        let reputations = match self.reputations.lock() {
+            Ok(reputations) => reputations,
+            Err(_) => return BinaryHeap::new(),
+        };
+
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (node_id, _))| {
+                let score = reputations
+                    .get(node_id)
+                    .map(|r| r.decayed(now, self.stale_after, self.decay_rate).score())
+                    .unwrap_or_else(|| NodeReputation::default().score());
+                RankedNode { score, index }
+            })
+            .collect()
+    }
+
+    /// Update a node's reputation with the outcome of a call
+    fn record_call(&self, node_id: &str, outcome: CallOutcome, latency_ms: u64) {
+        let now = platform::now_secs();
+        if let Ok(mut reputations) = self.reputations.lock() {
+            let reputation = reputations.entry(node_id.to_string()).or_default();
+
+            // Decay first so an idle node starts again from its drifted reputation
+            *reputation = reputation.decayed(now, self.stale_after, self.decay_rate);
+            reputation.record(outcome, latency_ms, now, self.alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn client(node_urls: &[&str]) -> MultiNodeClient {
+        MultiNodeClient::new(MultiNodeClientConfig {
+            nodes: node_urls
+                .iter()
+                .map(|url| StorageNodeClientConfig {
+                    base_url: url.to_string(),
+                    ..StorageNodeClientConfig::default()
+                })
+                .collect(),
+            ..MultiNodeClientConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reliable_node_selected_more_often() {
+        let reliable = "http://reliable.example";
+        let flaky = "http://flaky.example";
+        // The flaky node comes first, so it wins ties
+        let client = client(&[flaky, reliable]);
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut selections: HashMap<String, u32> = HashMap::new();
+
+        for _ in 0..1000 {
+            let node = client.best_node().unwrap();
+            let success_probability = if node == reliable { 0.95 } else { 0.6 };
+            let outcome = if rng.gen_bool(success_probability) {
+                Ok(())
+            } else {
+                Err(ErrorCode::Network)
+            };
+
+            client.record_call(&node, outcome, 50);
+            *selections.entry(node).or_insert(0) += 1;
+        }
+
+        let reliable_count = selections.get(reliable).copied().unwrap_or(0);
+        let flaky_count = selections.get(flaky).copied().unwrap_or(0);
+        assert!(
+            reliable_count > flaky_count,
+            "reliable {} vs flaky {}",
+            reliable_count,
+            flaky_count
+        );
+
+        let reputations: HashMap<_, _> = client.node_reputations().into_iter().collect();
+        assert!(reputations[reliable].score() > reputations[flaky].score());
+        assert!(reputations[flaky].error_counts[&ErrorCode::Network] > 0);
+    }
+
+    #[test]
+    fn test_stale_reputation_drifts_to_neutral() {
+        let mut reputation = NodeReputation::default();
+        reputation.record(Ok(()), 400, 1_000, DEFAULT_REPUTATION_ALPHA);
+        for _ in 0..10 {
+            reputation.record(Err(ErrorCode::Timeout), 5, 1_000, DEFAULT_REPUTATION_ALPHA);
+        }
+        let neutral = NodeReputation::default();
+
+        // Failed calls do not count toward latency
+        assert_eq!(reputation.average_latency_ms, 400);
+
+        // Within the stale window nothing changes
+        let fresh = reputation.decayed(1_000 + DEFAULT_REPUTATION_STALE_SECS, 300, 0.01);
+        assert_eq!(fresh, reputation);
+
+        // Afterwards the averages move toward neutral, and end up there
+        let drifting = reputation.decayed(1_400, 300, 0.01);
+        assert!(drifting.success_rate > reputation.success_rate);
+        assert!(drifting.success_rate < neutral.success_rate);
+        assert!(drifting.average_latency_ms < reputation.average_latency_ms);
+        assert!(drifting.average_latency_ms > neutral.average_latency_ms);
+
+        let settled = reputation.decayed(1_000_000, 300, 0.01);
+        assert!((settled.success_rate - neutral.success_rate).abs() < 1e-9);
+        assert_eq!(settled.average_latency_ms, neutral.average_latency_ms);
+        assert_eq!(settled.error_counts, reputation.error_counts);
+    }
+
+    #[tokio::test]
+    async fn test_call_fails_over_on_node_fault_only() {
+        let client = client(&["http://down.example", "http://up.example"]);
+
+        let node_id = client
+            .call(|node| async move {
+                if node.base_url.as_str().starts_with("http://down") {
+                    Err(StorageNodeError::Network("connection refused".to_string()))
+                } else {
+                    Ok(node.base_url.to_string())
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(node_id, "http://up.example/");
+
+        // A missing object is an answer, not a reason to ask another node
+        let attempts = Mutex::new(0);
+        let result: Result<()> = client
+            .call(|_| {
+                *attempts.lock().unwrap() += 1;
+                async { Err(StorageNodeError::NotFound("vault".to_string())) }
+            })
+            .await;
+        assert!(matches!(result, Err(StorageNodeError::NotFound(_))));
+        assert_eq!(*attempts.lock().unwrap(), 1);
+    }
+}
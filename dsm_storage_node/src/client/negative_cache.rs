@@ -0,0 +1,316 @@
+// Negative-result cache for the DSM Storage Node Client
+//
+// Most lookups against the invalidation endpoint return 404, because most
+// states are never invalidated. This module remembers recent misses for a
+// bounded window so repeated checks can be answered without the network.
+//
+// A Bloom filter gates an exact set of recent misses. The filter can only
+// produce false positives, and a positive is always confirmed against the
+// exact set, so an error in either structure leads to a network recheck and
+// never to a wrong answer.
+
+use super::platform;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Settings for negative-result caching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegativeCacheConfig {
+    /// How long a miss is remembered, in seconds (0 disables negative caching)
+    pub window_secs: u64,
+
+    /// Number of bits in each Bloom filter generation
+    pub bloom_bits: usize,
+
+    /// Number of hash functions used by the Bloom filter
+    pub bloom_hashes: u32,
+
+    /// Maximum number of misses held in the exact set
+    pub max_exact_entries: usize,
+}
+
+impl Default for NegativeCacheConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 60,
+            bloom_bits: 1 << 16,
+            bloom_hashes: 4,
+            max_exact_entries: 1024,
+        }
+    }
+}
+
+/// Fixed-size Bloom filter over string keys
+#[derive(Debug, Clone)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: usize,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    fn new(num_bits: usize, num_hashes: u32) -> Self {
+        let num_bits = num_bits.max(64);
+        Self {
+            bits: vec![0; num_bits.div_ceil(64)],
+            num_bits,
+            num_hashes: num_hashes.max(1),
+        }
+    }
+
+    /// Bit positions for a key, derived by double hashing one blake3 digest
+    fn positions(&self, key: &str) -> impl Iterator<Item = usize> + '_ {
+        let digest = blake3::hash(key.as_bytes());
+        let bytes = digest.as_bytes();
+        let h1 = u64::from_le_bytes(bytes[0..8].try_into().unwrap_or_default());
+        let h2 = u64::from_le_bytes(bytes[8..16].try_into().unwrap_or_default()) | 1;
+
+        (0..u64::from(self.num_hashes))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits as u64) as usize)
+    }
+
+    fn insert(&mut self, key: &str) {
+        let positions: Vec<usize> = self.positions(key).collect();
+        for position in positions {
+            self.bits[position / 64] |= 1 << (position % 64);
+        }
+    }
+
+    fn may_contain(&self, key: &str) -> bool {
+        self.positions(key)
+            .all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+}
+
+#[derive(Debug)]
+struct NegativeCacheInner {
+    /// Filter for misses recorded in the current window
+    current: BloomFilter,
+
+    /// Filter for misses recorded in the previous window
+    previous: BloomFilter,
+
+    /// When the current window started, since the Unix epoch
+    window_started: Duration,
+
+    /// Exact misses with the time they were recorded, since the Unix epoch
+    recent: HashMap<String, Duration>,
+
+    /// Incremented whenever an entry is forgotten, so that a lookup which
+    /// started before the change cannot record a miss that is no longer true
+    generation: u64,
+}
+
+/// Time-bounded cache of keys recently found to be absent on the storage node
+#[derive(Debug)]
+pub struct NegativeCache {
+    config: NegativeCacheConfig,
+    inner: Mutex<NegativeCacheInner>,
+}
+
+/// Token taken before a network lookup and handed back when recording its miss
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissToken(u64);
+
+impl NegativeCache {
+    /// Create an empty negative cache
+    pub fn new(config: NegativeCacheConfig) -> Self {
+        let filter = BloomFilter::new(config.bloom_bits, config.bloom_hashes);
+        Self {
+            inner: Mutex::new(NegativeCacheInner {
+                current: filter.clone(),
+                previous: filter,
+                window_started: platform::now(),
+                recent: HashMap::new(),
+                generation: 0,
+            }),
+            config,
+        }
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.window_secs)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NegativeCacheInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Rotate filter generations so no filter outlives two windows
+    fn rotate(&self, inner: &mut NegativeCacheInner) {
+        let now = platform::now();
+        let elapsed = now.saturating_sub(inner.window_started);
+        if elapsed < self.window() {
+            return;
+        }
+
+        if elapsed >= self.window() * 2 {
+            inner.previous.clear();
+        } else {
+            std::mem::swap(&mut inner.previous, &mut inner.current);
+        }
+        inner.current.clear();
+        inner.window_started = now;
+
+        let window = self.window();
+        inner
+            .recent
+            .retain(|_, recorded| now.saturating_sub(*recorded) < window);
+    }
+
+    /// Whether a key is known to have been absent within the window
+    pub fn contains(&self, key: &str) -> bool {
+        if self.config.window_secs == 0 {
+            return false;
+        }
+
+        let mut inner = self.lock();
+        self.rotate(&mut inner);
+
+        if !inner.current.may_contain(key) && !inner.previous.may_contain(key) {
+            return false;
+        }
+
+        // Confirm filter hits against the exact set
+        match inner.recent.get(key) {
+            Some(recorded) if platform::now().saturating_sub(*recorded) < self.window() => true,
+            Some(_) => {
+                inner.recent.remove(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Take a token before looking a key up on the network
+    pub fn miss_token(&self) -> MissToken {
+        MissToken(self.lock().generation)
+    }
+
+    /// Record that a key was absent on the network
+    ///
+    /// The miss is discarded if any entry was forgotten since `token` was
+    /// taken, since the lookup may have raced with a newly published entry.
+    pub fn record_miss(&self, key: &str, token: MissToken) {
+        if self.config.window_secs == 0 || self.config.max_exact_entries == 0 {
+            return;
+        }
+
+        let mut inner = self.lock();
+        if inner.generation != token.0 {
+            return;
+        }
+        self.rotate(&mut inner);
+
+        if !inner.recent.contains_key(key) && inner.recent.len() >= self.config.max_exact_entries {
+            let oldest = inner
+                .recent
+                .iter()
+                .min_by_key(|(_, recorded)| **recorded)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                inner.recent.remove(&oldest);
+            }
+        }
+
+        inner.recent.insert(key.to_string(), platform::now());
+        inner.current.insert(key);
+    }
+
+    /// Forget a key immediately, e.g. because an entry for it was just published
+    pub fn forget(&self, key: &str) {
+        let mut inner = self.lock();
+        inner.recent.remove(key);
+        inner.generation = inner.generation.wrapping_add(1);
+    }
+
+    /// Number of misses currently held in the exact set
+    pub fn len(&self) -> usize {
+        self.lock().recent.len()
+    }
+
+    /// Whether no misses are held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(window_secs: u64, max_exact_entries: usize) -> NegativeCacheConfig {
+        NegativeCacheConfig {
+            window_secs,
+            max_exact_entries,
+            ..NegativeCacheConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_recorded_miss_is_answered_locally() {
+        let cache = NegativeCache::new(config(60, 16));
+        assert!(!cache.contains("invalidation:aa"));
+
+        let token = cache.miss_token();
+        cache.record_miss("invalidation:aa", token);
+
+        assert!(cache.contains("invalidation:aa"));
+        assert!(!cache.contains("invalidation:bb"));
+    }
+
+    #[test]
+    fn test_forget_clears_entry_and_stale_lookups() {
+        let cache = NegativeCache::new(config(60, 16));
+        cache.record_miss("invalidation:aa", cache.miss_token());
+
+        // A lookup that started before the marker was published
+        let in_flight = cache.miss_token();
+        cache.forget("invalidation:aa");
+        assert!(!cache.contains("invalidation:aa"));
+
+        // Its 404 must not mask the newly published marker
+        cache.record_miss("invalidation:aa", in_flight);
+        assert!(!cache.contains("invalidation:aa"));
+    }
+
+    #[test]
+    fn test_exact_set_is_bounded() {
+        let cache = NegativeCache::new(config(60, 2));
+        for key in ["a", "b", "c"] {
+            cache.record_miss(key, cache.miss_token());
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        // The oldest miss was evicted; a filter hit alone is never trusted
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains("a"));
+        assert!(cache.contains("c"));
+    }
+
+    #[test]
+    fn test_misses_expire_after_window() {
+        let cache = NegativeCache::new(config(1, 16));
+        cache.record_miss("a", cache.miss_token());
+        assert!(cache.contains("a"));
+
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(!cache.contains("a"));
+    }
+
+    #[test]
+    fn test_disabled_cache_never_answers() {
+        let cache = NegativeCache::new(config(0, 16));
+        cache.record_miss("a", cache.miss_token());
+        assert!(!cache.contains("a"));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,507 @@
+// Noise transport for the DSM Storage Node Client
+//
+// TLS still exposes who talks to whom and how much. A client configured for
+// the Noise transport instead wraps every TCP connection to the storage node
+// in a Noise_IK session: the client knows the node's static key up front,
+// both sides authenticate, the client's identity is hidden from observers,
+// and session keys are forward secret. HTTP/1.1 is then framed over the
+// session exactly as it would be over plain TCP.
+//
+// The HTTP client cannot dial a custom stream, so the client runs a tunnel on
+// a loopback port and sends its requests to it as a proxy. Each connection
+// the tunnel accepts is forwarded to the storage node over a fresh session.
+//
+// Every Noise message, handshake or transport, travels as a frame of a
+// big-endian u16 length followed by the message.
+
+use crate::error::{Result, StorageNodeError};
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Noise protocol spoken with storage nodes
+pub const NOISE_PARAMS: &str = "Noise_IK_25519_ChaChaPoly_BLAKE2s";
+
+/// Largest Noise message, and so the largest frame payload
+const MAX_MESSAGE_LEN: usize = 65535;
+
+/// Bytes the cipher adds to every transport message
+const TAG_LEN: usize = 16;
+
+/// Largest plaintext carried by one transport message
+const MAX_PLAINTEXT_LEN: usize = MAX_MESSAGE_LEN - TAG_LEN;
+
+fn noise_error(e: snow::Error) -> StorageNodeError {
+    StorageNodeError::Encryption(format!("Noise protocol error: {}", e))
+}
+
+fn builder() -> Result<snow::Builder<'static>> {
+    let params = NOISE_PARAMS.parse().map_err(noise_error)?;
+    Ok(snow::Builder::new(params))
+}
+
+/// Generate a static key pair for the Noise transport
+///
+/// # Returns
+/// * `Result<(Vec<u8>, Vec<u8>)>` - The private and public key
+pub fn generate_noise_keypair() -> Result<(Vec<u8>, Vec<u8>)> {
+    let keypair = builder()?.generate_keypair().map_err(noise_error)?;
+    Ok((keypair.private, keypair.public))
+}
+
+async fn write_frame(stream: &mut TcpStream, message: &[u8]) -> io::Result<()> {
+    stream
+        .write_all(&(message.len() as u16).to_be_bytes())
+        .await?;
+    stream.write_all(message).await
+}
+
+async fn read_frame(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len).await?;
+    let mut message = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(message)
+}
+
+/// An established Noise session over a TCP stream
+///
+/// Reads and writes carry plaintext; the session encrypts it into frames on
+/// the way out and decrypts frames on the way in.
+pub struct NoiseSession {
+    /// Underlying connection
+    stream: TcpStream,
+
+    /// Cipher states after the handshake
+    transport: snow::TransportState,
+
+    /// Static public key the peer authenticated with
+    remote_static_key: Vec<u8>,
+
+    /// Received bytes that do not make up a whole frame yet
+    read_buf: Vec<u8>,
+
+    /// Decrypted bytes not yet returned to the reader
+    plaintext: Vec<u8>,
+
+    /// Read position in `plaintext`
+    plaintext_pos: usize,
+
+    /// Encrypted frames not yet written to the stream
+    write_buf: Vec<u8>,
+}
+
+impl NoiseSession {
+    /// Run the handshake as the initiator, authenticating the peer by its static key
+    ///
+    /// # Arguments
+    /// * `stream` - Connection to the storage node
+    /// * `local_private_key` - This side's static private key
+    /// * `remote_static_key` - Static public key the storage node must hold
+    /// * `timeout` - Time the handshake has to complete
+    pub async fn connect(
+        mut stream: TcpStream,
+        local_private_key: &[u8],
+        remote_static_key: &[u8],
+        timeout: Duration,
+    ) -> Result<Self> {
+        let mut handshake = builder()?
+            .local_private_key(local_private_key)
+            .remote_public_key(remote_static_key)
+            .build_initiator()
+            .map_err(noise_error)?;
+
+        let exchange = async {
+            let mut buf = vec![0u8; MAX_MESSAGE_LEN];
+
+            // -> e, es, s, ss
+            let len = handshake
+                .write_message(&[], &mut buf)
+                .map_err(noise_error)?;
+            write_frame(&mut stream, &buf[..len]).await?;
+
+            // <- e, ee, se
+            let message = read_frame(&mut stream).await?;
+            handshake
+                .read_message(&message, &mut buf)
+                .map_err(noise_error)?;
+            Ok::<_, StorageNodeError>(())
+        };
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| StorageNodeError::Timeout)??;
+
+        Self::established(stream, handshake)
+    }
+
+    /// Run the handshake as the responder, learning the initiator's static key
+    ///
+    /// # Arguments
+    /// * `stream` - Connection accepted from a client
+    /// * `local_private_key` - The storage node's static private key
+    /// * `timeout` - Time the handshake has to complete
+    pub async fn accept(
+        mut stream: TcpStream,
+        local_private_key: &[u8],
+        timeout: Duration,
+    ) -> Result<Self> {
+        let mut handshake = builder()?
+            .local_private_key(local_private_key)
+            .build_responder()
+            .map_err(noise_error)?;
+
+        let exchange = async {
+            let mut buf = vec![0u8; MAX_MESSAGE_LEN];
+
+            // -> e, es, s, ss
+            let message = read_frame(&mut stream).await?;
+            handshake
+                .read_message(&message, &mut buf)
+                .map_err(noise_error)?;
+
+            // <- e, ee, se
+            let len = handshake
+                .write_message(&[], &mut buf)
+                .map_err(noise_error)?;
+            write_frame(&mut stream, &buf[..len]).await?;
+            Ok::<_, StorageNodeError>(())
+        };
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| StorageNodeError::Timeout)??;
+
+        Self::established(stream, handshake)
+    }
+
+    fn established(stream: TcpStream, handshake: snow::HandshakeState) -> Result<Self> {
+        let remote_static_key = handshake
+            .get_remote_static()
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| StorageNodeError::Encryption("Peer sent no static key".into()))?;
+        let transport = handshake.into_transport_mode().map_err(noise_error)?;
+
+        Ok(Self {
+            stream,
+            transport,
+            remote_static_key,
+            read_buf: Vec::new(),
+            plaintext: Vec::new(),
+            plaintext_pos: 0,
+            write_buf: Vec::new(),
+        })
+    }
+
+    /// Static public key the peer authenticated with
+    pub fn remote_static_key(&self) -> &[u8] {
+        &self.remote_static_key
+    }
+
+    /// Write out encrypted frames that are still buffered
+    fn poll_write_frames(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.write_buf.is_empty() {
+            let written = ready!(Pin::new(&mut self.stream).poll_write(cx, &self.write_buf))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.write_buf.drain(..written);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn io_error(e: snow::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+impl AsyncRead for NoiseSession {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.plaintext_pos < this.plaintext.len() {
+                let available = &this.plaintext[this.plaintext_pos..];
+                let len = available.len().min(buf.remaining());
+                buf.put_slice(&available[..len]);
+                this.plaintext_pos += len;
+                return Poll::Ready(Ok(()));
+            }
+
+            // Decrypt the next frame once all of it has arrived
+            if let Some(len) = this
+                .read_buf
+                .get(..2)
+                .map(|l| u16::from_be_bytes([l[0], l[1]]))
+            {
+                let len = len as usize;
+                if this.read_buf.len() >= 2 + len {
+                    this.plaintext.resize(len, 0);
+                    let decrypted = this
+                        .transport
+                        .read_message(&this.read_buf[2..2 + len], &mut this.plaintext)
+                        .map_err(io_error)?;
+                    this.plaintext.truncate(decrypted);
+                    this.plaintext_pos = 0;
+                    this.read_buf.drain(..2 + len);
+                    continue;
+                }
+            }
+
+            let mut chunk = [0u8; 8192];
+            let mut received = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.stream).poll_read(cx, &mut received))?;
+            if received.filled().is_empty() {
+                // End of stream, which must not cut a frame short
+                return Poll::Ready(if this.read_buf.is_empty() {
+                    Ok(())
+                } else {
+                    Err(io::ErrorKind::UnexpectedEof.into())
+                });
+            }
+            this.read_buf.extend_from_slice(received.filled());
+        }
+    }
+}
+
+impl AsyncWrite for NoiseSession {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Frames go out in order, so earlier ones must be written first
+        ready!(this.poll_write_frames(cx))?;
+
+        let len = buf.len().min(MAX_PLAINTEXT_LEN);
+        let mut frame = vec![0u8; 2 + len + TAG_LEN];
+        let encrypted = this
+            .transport
+            .write_message(&buf[..len], &mut frame[2..])
+            .map_err(io_error)?;
+        frame[..2].copy_from_slice(&(encrypted as u16).to_be_bytes());
+        frame.truncate(2 + encrypted);
+        this.write_buf = frame;
+
+        // Whatever is not written now goes out on the next write or flush
+        if let Poll::Ready(Err(e)) = this.poll_write_frames(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_frames(cx))?;
+        Pin::new(&mut this.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_frames(cx))?;
+        Pin::new(&mut this.stream).poll_shutdown(cx)
+    }
+}
+
+/// Loopback proxy that forwards each connection to a storage node over Noise
+pub(super) struct NoiseTunnel {
+    /// Address the HTTP client sends its requests to
+    local_addr: SocketAddr,
+
+    /// Task accepting connections from the HTTP client
+    task: JoinHandle<()>,
+}
+
+impl NoiseTunnel {
+    /// Start forwarding to the storage node at `remote_addr` (`host:port`)
+    ///
+    /// The tunnel authenticates with a static key of its own, generated
+    /// here. Connecting and handshaking each have `timeout` to complete.
+    pub(super) fn start(
+        remote_addr: String,
+        remote_static_key: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            StorageNodeError::Config("Noise transport requires a Tokio runtime".to_string())
+        })?;
+
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let (private_key, _) = generate_noise_keypair()?;
+
+        let task = runtime.spawn(async move {
+            let listener = match TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!(error = %e, "Failed to start Noise tunnel");
+                    return;
+                }
+            };
+
+            loop {
+                let local = match listener.accept().await {
+                    Ok((local, _)) => local,
+                    Err(e) => {
+                        warn!(error = %e, "Noise tunnel stopped accepting connections");
+                        return;
+                    }
+                };
+
+                let remote_addr = remote_addr.clone();
+                let private_key = private_key.clone();
+                let remote_static_key = remote_static_key.clone();
+                tokio::spawn(async move {
+                    let forwarded = forward(
+                        local,
+                        &remote_addr,
+                        &private_key,
+                        &remote_static_key,
+                        timeout,
+                    )
+                    .await;
+                    if let Err(e) = forwarded {
+                        debug!(remote_addr = %remote_addr, error = %e, "Noise connection closed");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { local_addr, task })
+    }
+
+    /// Address to send requests to as a proxy
+    pub(super) fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for NoiseTunnel {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Carry one HTTP client connection to the storage node over a Noise session
+async fn forward(
+    mut local: TcpStream,
+    remote_addr: &str,
+    private_key: &[u8],
+    remote_static_key: &[u8],
+    timeout: Duration,
+) -> Result<()> {
+    let stream = tokio::time::timeout(timeout, TcpStream::connect(remote_addr))
+        .await
+        .map_err(|_| StorageNodeError::Timeout)??;
+
+    let mut session = NoiseSession::connect(stream, private_key, remote_static_key, timeout)
+        .await
+        .inspect_err(|e| {
+            warn!(remote_addr = %remote_addr, error = %e, "Noise handshake failed");
+        })?;
+
+    tokio::io::copy_bidirectional(&mut local, &mut session).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{StorageNodeClient, StorageNodeClientConfig, TransportConfig, TransportKind};
+
+    /// Answer every request on a Noise session with 200 OK, as a storage node would
+    async fn serve_noise(listener: TcpListener, private_key: Vec<u8>) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let private_key = private_key.clone();
+            tokio::spawn(async move {
+                let mut session =
+                    NoiseSession::accept(stream, &private_key, Duration::from_secs(5))
+                        .await
+                        .unwrap();
+
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                loop {
+                    let read = session.read(&mut buf).await.unwrap();
+                    if read == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                    if request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        break;
+                    }
+                }
+                assert!(String::from_utf8_lossy(&request).contains("/health"));
+
+                session
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await
+                    .unwrap();
+                session.flush().await.unwrap();
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_over_noise_session() {
+        let (private_key, public_key) = generate_noise_keypair().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_noise(listener, private_key));
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: format!("http://{}", addr),
+            timeout_seconds: 5,
+            transport: TransportConfig::Noise {
+                remote_static_key: public_key,
+            },
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            client.probe_transport().await.unwrap(),
+            TransportKind::Noise
+        );
+        assert!(client.check_health().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_with_wrong_node_key_fails() {
+        let (private_key, _) = generate_noise_keypair().unwrap();
+        let (_, other_public_key) = generate_noise_keypair().unwrap();
+        let (client_key, _) = generate_noise_keypair().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            NoiseSession::accept(stream, &private_key, Duration::from_secs(5)).await
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let connected = NoiseSession::connect(
+            stream,
+            &client_key,
+            &other_public_key,
+            Duration::from_secs(1),
+        )
+        .await;
+
+        assert!(server.await.unwrap().is_err());
+        assert!(connected.is_err());
+    }
+}
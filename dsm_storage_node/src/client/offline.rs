@@ -0,0 +1,34 @@
+// Offline queue replay for the DSM Storage Node Client
+//
+// Operations queued while the storage node was unreachable are replayed by
+// storing the state each one produced. Failing to reach the node leaves the
+// operation queued; any other error rejects it.
+
+use super::StorageNodeClient;
+use crate::error::StorageNodeError;
+use async_trait::async_trait;
+use dsm::communication::{OperationReplayer, QueuedOperation};
+use dsm::types::error::DsmError;
+
+#[async_trait]
+impl OperationReplayer for StorageNodeClient {
+    async fn replay(&self, operation: &QueuedOperation) -> Result<(), DsmError> {
+        match self.store_state(&operation.state).await {
+            Ok(_) => Ok(()),
+            Err(StorageNodeError::Network(e)) => Err(DsmError::network(
+                format!(
+                    "Failed to replay state {}: {}",
+                    operation.state.state_number, e
+                ),
+                None::<std::convert::Infallible>,
+            )),
+            Err(e) => Err(DsmError::storage(
+                format!(
+                    "Storage node rejected state {}",
+                    operation.state.state_number
+                ),
+                Some(e),
+            )),
+        }
+    }
+}
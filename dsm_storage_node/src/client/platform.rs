@@ -0,0 +1,124 @@
+// Platform support for the DSM Storage Node Client
+//
+// Natively the client runs on tokio. In the browser it runs on the
+// single-threaded wasm-bindgen-futures executor, where there is no tokio
+// runtime and `SystemTime::now()` panics, so locks, the clock and task
+// spawning are provided here for both targets.
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+pub(crate) use tokio::sync::RwLock;
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) use self::local::RwLock;
+
+/// Current time since the Unix epoch
+pub(crate) fn now() -> Duration {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    {
+        Duration::from_millis(js_sys::Date::now() as u64)
+    }
+
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub(crate) fn now_secs() -> u64 {
+    now().as_secs()
+}
+
+/// Run a future in the background on the current executor
+///
+/// Returns `false` if there is no executor to run it on.
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+pub(crate) fn spawn_detached<F>(future: F) -> bool
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn(future);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Run a future in the background on the current executor
+///
+/// Returns `false` if there is no executor to run it on.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) fn spawn_detached<F>(future: F) -> bool
+where
+    F: Future<Output = ()> + 'static,
+{
+    wasm_bindgen_futures::spawn_local(future);
+    true
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod local {
+    use std::cell::{Ref, RefCell, RefMut};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// Async read-write lock for a single-threaded executor
+    ///
+    /// Mirrors the `tokio::sync::RwLock` API used by the client. A task that
+    /// finds the lock held yields to the executor and retries, so a guard held
+    /// across an await point delays other tasks instead of panicking.
+    #[derive(Debug, Default)]
+    pub(crate) struct RwLock<T> {
+        cell: RefCell<T>,
+    }
+
+    impl<T> RwLock<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Self {
+                cell: RefCell::new(value),
+            }
+        }
+
+        pub(crate) async fn read(&self) -> Ref<'_, T> {
+            loop {
+                if let Ok(guard) = self.cell.try_borrow() {
+                    return guard;
+                }
+                YieldNow(false).await;
+            }
+        }
+
+        pub(crate) async fn write(&self) -> RefMut<'_, T> {
+            loop {
+                if let Ok(guard) = self.cell.try_borrow_mut() {
+                    return guard;
+                }
+                YieldNow(false).await;
+            }
+        }
+    }
+
+    /// Future that returns to the executor once before completing
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// Cache prewarming peer for the DSM Storage Node Client
+//
+// A node joining the network prewarms its cold storage cache from a peer's
+// storage node. The latest checkpoint of a genesis is found through the
+// identity head published for it, and invalidation markers are looked up
+// under their usual keys.
+
+use super::{invalidation_key, StorageNodeClient};
+use crate::error::StorageNodeError;
+use async_trait::async_trait;
+use dsm::communication::{CachePeer, StorageCache};
+use dsm::core::identity::GenesisState;
+use dsm::recovery::invalidation::InvalidationMarker;
+use dsm::types::error::DsmError;
+use dsm::types::state_types::State;
+
+/// Map a storage node error onto the error reported for a prewarm fetch
+fn peer_error(what: &str, hash: &[u8], error: StorageNodeError) -> DsmError {
+    match error {
+        StorageNodeError::Network(e) => DsmError::network(
+            format!("Failed to fetch {} for {}: {}", what, hex::encode(hash), e),
+            None::<std::convert::Infallible>,
+        ),
+        e => DsmError::storage(
+            format!(
+                "Storage node failed to serve {} for {}",
+                what,
+                hex::encode(hash)
+            ),
+            Some(e),
+        ),
+    }
+}
+
+#[async_trait]
+impl CachePeer for StorageNodeClient {
+    async fn fetch_genesis(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>, DsmError> {
+        self.fetch_genesis_state(genesis_hash)
+            .await
+            .map_err(|e| peer_error("genesis state", genesis_hash, e))
+    }
+
+    async fn fetch_latest_checkpoint(
+        &self,
+        genesis_hash: &[u8],
+    ) -> Result<Option<State>, DsmError> {
+        let head = self
+            .fetch_identity_head(&hex::encode(genesis_hash))
+            .await
+            .map_err(|e| peer_error("identity head", genesis_hash, e))?;
+        let Some(head) = head else {
+            return Ok(None);
+        };
+
+        let state_hash = hex::decode(&head.state_hash).map_err(|e| {
+            DsmError::serialization("Identity head carries an invalid state hash", Some(e))
+        })?;
+        let checkpoint_id = StorageCache::checkpoint_id_for(head.state_number, &state_hash);
+
+        self.fetch_checkpoint(&checkpoint_id)
+            .await
+            .map_err(|e| peer_error("checkpoint", genesis_hash, e))
+    }
+
+    async fn fetch_invalidation_markers(
+        &self,
+        state_hashes: &[Vec<u8>],
+    ) -> Result<Vec<InvalidationMarker>, DsmError> {
+        let mut markers = Vec::new();
+        for state_hash in state_hashes {
+            let marker = self
+                .retrieve_object::<InvalidationMarker>(&invalidation_key(state_hash))
+                .await
+                .map_err(|e| peer_error("invalidation marker", state_hash, e))?;
+            markers.extend(marker);
+        }
+        Ok(markers)
+    }
+}
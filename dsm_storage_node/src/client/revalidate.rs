@@ -0,0 +1,272 @@
+// Stale-while-revalidate lookups for the DSM Storage Node Client
+//
+// Cached tokens and genesis states stay usable past their TTL. A stale entry is
+// served immediately and refreshed from the storage node in the background, so
+// an expired entry never turns into a failure while offline.
+
+use super::StorageNodeClient;
+use crate::error::{Result, StorageNodeError};
+use dsm::core::identity::GenesisState;
+use dsm::types::token_types::Token;
+use std::fmt;
+use std::sync::Arc;
+
+pub use dsm::communication::{CacheLookup, Freshness};
+
+#[cfg(feature = "reqwest")]
+use super::{object_key, platform};
+#[cfg(feature = "reqwest")]
+use tracing::debug;
+
+/// Cache entry to be refreshed from the storage node
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+enum Revalidation {
+    /// Genesis state by hash
+    Genesis(Vec<u8>),
+
+    /// Token by ID
+    Token(String),
+}
+
+impl fmt::Display for Revalidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Revalidation::Genesis(hash) => write!(f, "genesis {}", hex::encode(hash)),
+            Revalidation::Token(id) => write!(f, "token {}", id),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Fetch a token, serving a stale cached copy while it is refreshed in the background
+    ///
+    /// # Arguments
+    /// * `token_id` - Identifier of the token
+    /// * `required` - Minimum acceptable freshness. With `Freshness::Fresh` a stale
+    ///   entry is refreshed before returning, and an `Expired` error is returned
+    ///   if the storage node cannot be reached.
+    ///
+    /// # Returns
+    /// * `Result<Option<CacheLookup<Token>>>` - The token and its freshness, if found
+    pub async fn fetch_token_with_freshness(
+        self: &Arc<Self>,
+        token_id: &str,
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<Token>>> {
+        let cached = self
+            .storage_cache
+            .get_token_with_freshness(token_id, Freshness::Stale)
+            .await?;
+
+        match cached {
+            Some(lookup) if lookup.is_fresh() => Ok(Some(lookup)),
+            Some(lookup) if required == Freshness::Stale => {
+                self.revalidate_in_background(Revalidation::Token(token_id.to_string()));
+                Ok(Some(lookup))
+            }
+            Some(_) => match self.refresh_token(token_id).await {
+                Ok(token) => Ok(token.map(fresh)),
+                Err(e) => Err(StorageNodeError::Expired(format!(
+                    "Cached token {} is stale and could not be revalidated: {}",
+                    token_id, e
+                ))),
+            },
+            None => Ok(self.refresh_token(token_id).await?.map(fresh)),
+        }
+    }
+
+    /// Fetch a genesis state, serving a stale cached copy while it is refreshed in the background
+    ///
+    /// # Arguments
+    /// * `genesis_hash` - Hash of the genesis state
+    /// * `required` - Minimum acceptable freshness. With `Freshness::Fresh` a stale
+    ///   entry is refreshed before returning, and an `Expired` error is returned
+    ///   if the storage node cannot be reached.
+    ///
+    /// # Returns
+    /// * `Result<Option<CacheLookup<GenesisState>>>` - The genesis state and its freshness, if found
+    pub async fn fetch_genesis_with_freshness(
+        self: &Arc<Self>,
+        genesis_hash: &[u8],
+        required: Freshness,
+    ) -> Result<Option<CacheLookup<GenesisState>>> {
+        let cached = self
+            .storage_cache
+            .get_genesis_with_freshness(genesis_hash, Freshness::Stale)
+            .await?;
+
+        match cached {
+            Some(lookup) if lookup.is_fresh() => Ok(Some(lookup)),
+            Some(lookup) if required == Freshness::Stale => {
+                self.revalidate_in_background(Revalidation::Genesis(genesis_hash.to_vec()));
+                Ok(Some(lookup))
+            }
+            Some(_) => match self.refresh_genesis_state(genesis_hash).await {
+                Ok(genesis) => Ok(genesis.map(fresh)),
+                Err(e) => Err(StorageNodeError::Expired(format!(
+                    "Cached genesis state {} is stale and could not be revalidated: {}",
+                    hex::encode(genesis_hash),
+                    e
+                ))),
+            },
+            None => Ok(self.refresh_genesis_state(genesis_hash).await?.map(fresh)),
+        }
+    }
+
+    /// Fetch a token from the storage node and replace the cached copy
+    async fn refresh_token(&self, token_id: &str) -> Result<Option<Token>> {
+        let token: Option<Token> = self
+            .retrieve_remote_object(&object_key("token", token_id))
+            .await?;
+
+        if let Some(token) = &token {
+            self.storage_cache
+                .cache_token(token_id, token.clone(), false, None)
+                .await?;
+        }
+
+        Ok(token)
+    }
+
+    /// Fetch a genesis state from the storage node and replace the cached copy
+    async fn refresh_genesis_state(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>> {
+        let key = object_key("genesis", &hex::encode(genesis_hash));
+        let genesis: Option<GenesisState> = self.retrieve_remote_object(&key).await?;
+
+        if let Some(genesis) = &genesis {
+            self.storage_cache
+                .cache_genesis(genesis.clone(), false, None)
+                .await?;
+        }
+
+        Ok(genesis)
+    }
+
+    /// Refresh a stale entry on a background task, if the storage node is reachable
+    ///
+    /// At most one refresh per entry is in flight at a time.
+    fn revalidate_in_background(self: &Arc<Self>, target: Revalidation) {
+        let key = target.to_string();
+        let target_name = key.clone();
+
+        {
+            let mut revalidating = self
+                .revalidating
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if !revalidating.insert(key.clone()) {
+                return;
+            }
+        }
+
+        let client = Arc::clone(self);
+        let spawned = platform::spawn_detached(async move {
+            if client.check_health().await.unwrap_or(false) {
+                let result = match &target {
+                    Revalidation::Genesis(hash) => {
+                        client.refresh_genesis_state(hash).await.map(|_| ())
+                    }
+                    Revalidation::Token(id) => client.refresh_token(id).await.map(|_| ()),
+                };
+
+                match result {
+                    Ok(()) => debug!(target = %target, "Revalidated stale cache entry"),
+                    Err(e) => debug!(target = %target, error = %e, "Failed to revalidate"),
+                }
+            } else {
+                debug!(target = %target, "Storage node unreachable; entry remains stale");
+            }
+
+            client
+                .revalidating
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .remove(&key);
+        });
+
+        if !spawned {
+            debug!(target = %target_name, "No executor to revalidate");
+            self.revalidating
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .remove(&target_name);
+        }
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn fetch_token_with_freshness(
+        self: &Arc<Self>,
+        _token_id: &str,
+        _required: Freshness,
+    ) -> Result<Option<CacheLookup<Token>>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn fetch_genesis_with_freshness(
+        self: &Arc<Self>,
+        _genesis_hash: &[u8],
+        _required: Freshness,
+    ) -> Result<Option<CacheLookup<GenesisState>>> {
+        Err(StorageNodeError::Internal)
+    }
+}
+
+/// Tag a value just fetched from the storage node as fresh
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+fn fresh<T>(value: T) -> CacheLookup<T> {
+    CacheLookup {
+        value,
+        freshness: Freshness::Fresh,
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::client::StorageNodeClientConfig;
+    use dsm::types::token_types::Balance;
+
+    /// Client pointing at a port nothing listens on
+    fn offline_client() -> Arc<StorageNodeClient> {
+        let config = StorageNodeClientConfig {
+            base_url: "http://127.0.0.1:9".to_string(),
+            timeout_seconds: 1,
+            ..StorageNodeClientConfig::default()
+        };
+        Arc::new(StorageNodeClient::new(config).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_stale_token_served_offline() {
+        let client = offline_client();
+        let token = Token::new("owner", vec![1, 2, 3], Vec::new(), Balance::new(5));
+        client
+            .storage_cache()
+            .cache_token("t", token, true, Some(1))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+        // The expired entry is still served, tagged as stale
+        let lookup = client
+            .fetch_token_with_freshness("t", Freshness::Stale)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(lookup.is_stale());
+        assert_eq!(lookup.value.owner_id(), "owner");
+
+        // Demanding fresh data fails instead of returning the stale copy
+        assert!(matches!(
+            client
+                .fetch_token_with_freshness("t", Freshness::Fresh)
+                .await,
+            Err(StorageNodeError::Expired(_))
+        ));
+    }
+}
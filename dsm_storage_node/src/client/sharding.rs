@@ -0,0 +1,409 @@
+// Sharded client for the DSM Storage Node Client
+//
+// A single storage node has to hold every identity's data. `ShardedStorageNodeClient`
+// spreads identities over several nodes by the first byte of their genesis hash,
+// so every call for an identity, including its inbox, lands on the same shard.
+// Shards can be swapped for another node while the client is in use.
+
+use super::platform::RwLock;
+use super::{RecipientSpec, StorageNodeClient};
+use crate::api::{DeviceRegistration, IdentityHead, InboxEntry};
+use crate::error::{Result, StorageNodeError};
+use dsm::core::identity::GenesisState;
+use dsm::types::operations::Operation;
+use dsm::vault::LimboVault;
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// Client that routes calls to storage node shards by genesis hash
+///
+/// An identity with genesis hash `h` lives on shard `h[0] % num_shards`.
+/// Hex-encoded genesis hashes are decoded before routing, so the hex and the
+/// raw form of a hash route to the same shard.
+pub struct ShardedStorageNodeClient {
+    /// Shard IDs and their clients
+    shards: RwLock<HashMap<u8, Arc<StorageNodeClient>>>,
+
+    /// Number of shards, fixed at construction
+    num_shards: usize,
+}
+
+impl ShardedStorageNodeClient {
+    /// Create a client for the given shards
+    ///
+    /// Shard IDs must run from 0 to the number of shards minus one.
+    pub fn new(shards: HashMap<u8, StorageNodeClient>) -> Result<Self> {
+        if shards.is_empty() {
+            return Err(StorageNodeError::Config(
+                "Sharding needs at least one shard".to_string(),
+            ));
+        }
+
+        let num_shards = shards.len();
+        if let Some(shard_id) = shards
+            .keys()
+            .find(|shard_id| **shard_id as usize >= num_shards)
+        {
+            return Err(StorageNodeError::Config(format!(
+                "Shard {} is out of range, shard IDs must run from 0 to {}",
+                shard_id,
+                num_shards - 1
+            )));
+        }
+
+        let shards = shards
+            .into_iter()
+            .map(|(shard_id, client)| (shard_id, Arc::new(client)))
+            .collect();
+
+        Ok(Self {
+            shards: RwLock::new(shards),
+            num_shards,
+        })
+    }
+
+    /// Number of shards
+    pub fn num_shards(&self) -> usize {
+        self.num_shards
+    }
+
+    /// Shard holding the data of a genesis hash
+    ///
+    /// An empty hash routes to shard 0.
+    pub fn shard_for(&self, genesis_hash: &[u8]) -> u8 {
+        genesis_hash
+            .first()
+            .map(|byte| (*byte as usize % self.num_shards) as u8)
+            .unwrap_or(0)
+    }
+
+    /// Shard holding the data of a hex-encoded genesis hash
+    ///
+    /// Strings that are not valid hex are routed by their bytes.
+    pub fn shard_for_hex(&self, genesis_hash: &str) -> u8 {
+        match hex::decode(genesis_hash) {
+            Ok(bytes) => self.shard_for(&bytes),
+            Err(_) => self.shard_for(genesis_hash.as_bytes()),
+        }
+    }
+
+    /// Current client of a shard
+    pub async fn shard_client(&self, shard_id: u8) -> Option<Arc<StorageNodeClient>> {
+        self.shards.read().await.get(&shard_id).cloned()
+    }
+
+    /// Replace the client of a shard, e.g. to migrate the shard to another node
+    ///
+    /// Routing does not change, and calls already running on the old client
+    /// finish there. Copying the shard's data to the new node is up to the
+    /// caller.
+    ///
+    /// # Arguments
+    /// * `shard_id` - Shard to move
+    /// * `new_client` - Client of the node now serving the shard
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success, or an error if there is no such shard
+    pub async fn rebalance_shard(&self, shard_id: u8, new_client: StorageNodeClient) -> Result<()> {
+        let mut shards = self.shards.write().await;
+        let slot = shards.get_mut(&shard_id).ok_or_else(|| {
+            StorageNodeError::Config(format!(
+                "Shard {} does not exist, there are {} shards",
+                shard_id, self.num_shards
+            ))
+        })?;
+
+        *slot = Arc::new(new_client);
+        debug!(shard_id, "Shard moved to a new storage node");
+
+        Ok(())
+    }
+
+    /// Fetch a genesis state from its shard
+    ///
+    /// # Returns
+    /// * `Result<Option<GenesisState>>` - The genesis state if found
+    pub async fn fetch_genesis_state(&self, genesis_hash: &[u8]) -> Result<Option<GenesisState>> {
+        let shard = self.client_for(self.shard_for(genesis_hash)).await?;
+        shard.fetch_genesis_state(genesis_hash).await
+    }
+
+    /// Register a device for an identity on the identity's shard
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn register_device(
+        &self,
+        genesis_hash: &str,
+        registration: &DeviceRegistration,
+    ) -> Result<()> {
+        let shard = self.client_for(self.shard_for_hex(genesis_hash)).await?;
+        shard.register_device(genesis_hash, registration).await
+    }
+
+    /// List the devices registered for an identity on the identity's shard
+    ///
+    /// # Returns
+    /// * `Result<Vec<DeviceRegistration>>` - Registered devices
+    pub async fn list_devices(&self, genesis_hash: &str) -> Result<Vec<DeviceRegistration>> {
+        let shard = self.client_for(self.shard_for_hex(genesis_hash)).await?;
+        shard.list_devices(genesis_hash).await
+    }
+
+    /// Fetch the head of an identity from the identity's shard
+    ///
+    /// # Returns
+    /// * `Result<Option<IdentityHead>>` - The head, or `None` if none was published
+    pub async fn fetch_identity_head(&self, genesis_hash: &str) -> Result<Option<IdentityHead>> {
+        let shard = self.client_for(self.shard_for_hex(genesis_hash)).await?;
+        shard.fetch_identity_head(genesis_hash).await
+    }
+
+    /// Publish the head of an identity to the identity's shard
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or an error
+    pub async fn publish_identity_head(
+        &self,
+        genesis_hash: &str,
+        head: &IdentityHead,
+    ) -> Result<()> {
+        let shard = self.client_for(self.shard_for_hex(genesis_hash)).await?;
+        shard.publish_identity_head(genesis_hash, head).await
+    }
+
+    /// Store a unilateral transaction on the recipient's shard
+    ///
+    /// # Returns
+    /// * `Result<String>` - ID of the stored inbox entry
+    pub async fn store_unilateral_transaction(
+        &self,
+        sender_genesis_hash: &str,
+        recipient: &RecipientSpec,
+        operation: &Operation,
+        signature: &[u8],
+        expires_in: Option<Duration>,
+        optional_priority: Option<u8>,
+    ) -> Result<String> {
+        let shard = self
+            .client_for(self.shard_for_hex(&recipient.inbox_id()))
+            .await?;
+        shard
+            .store_unilateral_transaction(
+                sender_genesis_hash,
+                recipient,
+                operation,
+                signature,
+                expires_in,
+                optional_priority,
+            )
+            .await
+    }
+
+    /// Get the live unilateral transactions in a recipient's inbox from its shard
+    ///
+    /// # Returns
+    /// * `Result<Vec<InboxEntry>>` - Live inbox entries
+    pub async fn get_inbox_transactions(
+        &self,
+        recipient_genesis_hash: &str,
+    ) -> Result<Vec<InboxEntry>> {
+        let shard = self
+            .client_for(self.shard_for_hex(recipient_genesis_hash))
+            .await?;
+        shard.get_inbox_transactions(recipient_genesis_hash).await
+    }
+
+    /// Delete a transaction from a recipient's inbox on its shard
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether the entry was deleted
+    pub async fn delete_inbox_transaction(
+        &self,
+        recipient_genesis_hash: &str,
+        entry_id: &str,
+    ) -> Result<bool> {
+        let shard = self
+            .client_for(self.shard_for_hex(recipient_genesis_hash))
+            .await?;
+        shard
+            .delete_inbox_transaction(recipient_genesis_hash, entry_id)
+            .await
+    }
+
+    /// Fetch a vault by ID
+    ///
+    /// Vault IDs do not name an identity, so shard 0 is asked first and the
+    /// other shards are then asked together.
+    ///
+    /// # Returns
+    /// * `Result<Option<LimboVault>>` - The vault if found, or an error if it
+    ///   was not found and some shard could not be asked
+    pub async fn fetch_vault(&self, vault_id: &str) -> Result<Option<LimboVault>> {
+        let mut shards: Vec<_> = self.shards.read().await.clone().into_iter().collect();
+        shards.sort_by_key(|(shard_id, _)| *shard_id);
+
+        let mut last_error = None;
+        let (first, rest) = shards.split_first().ok_or(StorageNodeError::Internal)?;
+        match first.1.fetch_vault(vault_id).await {
+            Ok(Some(vault)) => return Ok(Some(vault)),
+            Ok(None) => {}
+            Err(e) => {
+                debug!(shard_id = first.0, vault_id, error = %e, "Shard failed to fetch vault");
+                last_error = Some(e);
+            }
+        }
+
+        let results = join_all(rest.iter().map(|(_, client)| client.fetch_vault(vault_id))).await;
+        for ((shard_id, _), result) in rest.iter().zip(results) {
+            match result {
+                Ok(Some(vault)) => return Ok(Some(vault)),
+                Ok(None) => {}
+                Err(e) => {
+                    debug!(
+                        shard_id = *shard_id,
+                        vault_id,
+                        error = %e,
+                        "Shard failed to fetch vault"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    /// Client of a shard, released from the shard map before the call is made
+    async fn client_for(&self, shard_id: u8) -> Result<Arc<StorageNodeClient>> {
+        self.shard_client(shard_id)
+            .await
+            .ok_or(StorageNodeError::Internal)
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::client::StorageNodeClientConfig;
+    use mockito::{Server, ServerGuard};
+    use rand::RngCore;
+
+    async fn shard() -> ServerGuard {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+        server
+    }
+
+    fn node(base_url: &str) -> StorageNodeClient {
+        StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: base_url.to_string(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn client(num_shards: u8) -> ShardedStorageNodeClient {
+        let shards = (0..num_shards)
+            .map(|shard_id| (shard_id, node(&format!("http://shard{}.example", shard_id))))
+            .collect();
+        ShardedStorageNodeClient::new(shards).unwrap()
+    }
+
+    fn random_hashes(count: usize) -> Vec<[u8; 32]> {
+        let mut rng = rand::thread_rng();
+        (0..count)
+            .map(|_| {
+                let mut hash = [0u8; 32];
+                rng.fill_bytes(&mut hash);
+                hash
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shard_ids_validated() {
+        assert!(ShardedStorageNodeClient::new(HashMap::new()).is_err());
+
+        let gap = HashMap::from([(0, node("http://a.example")), (2, node("http://b.example"))]);
+        assert!(ShardedStorageNodeClient::new(gap).is_err());
+    }
+
+    #[test]
+    fn test_routing_is_deterministic() {
+        let sharded = client(4);
+
+        for hash in random_hashes(1000) {
+            let shard_id = sharded.shard_for(&hash);
+            assert_eq!(shard_id, hash[0] % 4);
+            assert_eq!(sharded.shard_for(&hash), shard_id);
+            assert_eq!(sharded.shard_for_hex(&hex::encode(hash)), shard_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rebalance_keeps_other_shards() {
+        let sharded = client(4);
+        let hashes = random_hashes(1000);
+        let before: Vec<_> = hashes.iter().map(|hash| sharded.shard_for(hash)).collect();
+        let mut others = Vec::new();
+        for shard_id in [0, 1, 3] {
+            others.push(sharded.shard_client(shard_id).await.unwrap());
+        }
+
+        sharded
+            .rebalance_shard(2, node("http://moved.example"))
+            .await
+            .unwrap();
+
+        let after: Vec<_> = hashes.iter().map(|hash| sharded.shard_for(hash)).collect();
+        assert_eq!(before, after);
+        for (shard_id, old) in [0, 1, 3].into_iter().zip(&others) {
+            let current = sharded.shard_client(shard_id).await.unwrap();
+            assert!(Arc::ptr_eq(&current, old));
+        }
+        assert!(sharded
+            .rebalance_shard(4, node("http://moved.example"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inbox_routed_to_recipient_shard() {
+        let mut first = shard().await;
+        let mut second = shard().await;
+        let inbox = second
+            .mock("GET", "/inbox/01ff")
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create_async()
+            .await;
+        let untouched = first
+            .mock("GET", "/inbox/01ff")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let shards = HashMap::from([(0, node(&first.url())), (1, node(&second.url()))]);
+        let sharded = ShardedStorageNodeClient::new(shards).unwrap();
+        assert!(sharded
+            .get_inbox_transactions("01ff")
+            .await
+            .unwrap()
+            .is_empty());
+
+        inbox.assert_async().await;
+        untouched.assert_async().await;
+    }
+}
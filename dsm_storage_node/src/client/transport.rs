@@ -0,0 +1,317 @@
+// Transport selection for the DSM Storage Node Client
+//
+// Requests go over HTTP/1.1 on TCP by default. A client configured for QUIC
+// also builds an HTTP/3 client, and `probe_transport` checks whether the
+// storage node answers over HTTP/3, falling back to TCP when it does not.
+//
+// QUIC needs the `quic` feature. HTTP/3 support in reqwest is unstable, so the
+// build must also pass `RUSTFLAGS="--cfg reqwest_unstable"`.
+//
+// A client configured for Noise sends HTTP/1.1 over Noise sessions with the
+// storage node instead, authenticating the node by its static key. This needs
+// the `noise` feature and is not available in the browser.
+
+use super::StorageNodeClient;
+use crate::error::{Result, StorageNodeError};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "reqwest")]
+use std::time::Duration;
+#[cfg(feature = "reqwest")]
+use tracing::debug;
+
+/// TLS settings for QUIC connections
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsClientConfig {
+    /// Additional trusted root certificates, PEM encoded
+    #[serde(default)]
+    pub root_certificates_pem: Vec<String>,
+
+    /// Accept certificates that fail verification (local testing only)
+    #[serde(default)]
+    pub accept_invalid_certificates: bool,
+}
+
+/// Transport used to reach a storage node
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportConfig {
+    /// HTTP/1.1 over TCP
+    #[default]
+    Tcp,
+
+    /// HTTP/3 over QUIC where the storage node supports it, requires the `quic` feature
+    Quic {
+        /// TLS settings for the QUIC handshake
+        tls_config: TlsClientConfig,
+    },
+
+    /// HTTP/1.1 over Noise sessions on TCP, requires the `noise` feature
+    Noise {
+        /// Static public key the storage node must authenticate with
+        remote_static_key: Vec<u8>,
+    },
+}
+
+/// Transport a client sends its requests over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    /// HTTP/1.1 over TCP
+    Tcp,
+
+    /// HTTP/3 over QUIC
+    Quic,
+
+    /// HTTP/1.1 over Noise sessions
+    Noise,
+}
+
+/// Build the HTTP/3 client for a QUIC transport (None for TCP)
+#[cfg(all(feature = "quic", not(target_arch = "wasm32")))]
+pub(super) fn build_quic_client(
+    transport: &TransportConfig,
+    timeout: Duration,
+) -> Result<Option<reqwest::Client>> {
+    let TransportConfig::Quic { tls_config } = transport else {
+        return Ok(None);
+    };
+
+    // reqwest only speaks HTTP/3 with rustls
+    let mut builder = reqwest::Client::builder()
+        .use_rustls_tls()
+        .http3_prior_knowledge()
+        .timeout(timeout)
+        .danger_accept_invalid_certs(tls_config.accept_invalid_certificates);
+
+    for pem in &tls_config.root_certificates_pem {
+        let certificate = reqwest::Certificate::from_pem(pem.as_bytes())
+            .map_err(|e| StorageNodeError::Config(format!("Invalid root certificate: {}", e)))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    let client = builder
+        .build()
+        .map_err(|e| StorageNodeError::Network(format!("Failed to create QUIC client: {}", e)))?;
+
+    Ok(Some(client))
+}
+
+/// Build the HTTP/3 client for a QUIC transport (None for TCP)
+#[cfg(all(
+    feature = "reqwest",
+    not(all(feature = "quic", not(target_arch = "wasm32")))
+))]
+pub(super) fn build_quic_client(
+    transport: &TransportConfig,
+    _timeout: Duration,
+) -> Result<Option<reqwest::Client>> {
+    match transport {
+        TransportConfig::Tcp | TransportConfig::Noise { .. } => Ok(None),
+        TransportConfig::Quic { .. } => Err(StorageNodeError::Config(
+            "QUIC transport requires the `quic` feature".to_string(),
+        )),
+    }
+}
+
+/// Start the tunnel for a Noise transport (None for other transports)
+///
+/// Noise encrypts the connection itself, so the base URL must use `http`.
+#[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+pub(super) fn start_noise_tunnel(
+    config: &super::StorageNodeClientConfig,
+    timeout: Duration,
+) -> Result<Option<super::noise::NoiseTunnel>> {
+    let TransportConfig::Noise { remote_static_key } = &config.transport else {
+        return Ok(None);
+    };
+
+    let base_url = url::Url::parse(&config.base_url)
+        .map_err(|e| StorageNodeError::Config(format!("Invalid base URL: {}", e)))?;
+    if base_url.scheme() != "http" {
+        return Err(StorageNodeError::Config(
+            "Noise transport requires an http:// base URL".to_string(),
+        ));
+    }
+    let host = base_url
+        .host_str()
+        .ok_or_else(|| StorageNodeError::Config("Base URL has no host".to_string()))?;
+    let port = base_url.port_or_known_default().unwrap_or(80);
+
+    let tunnel = super::noise::NoiseTunnel::start(
+        format!("{}:{}", host, port),
+        remote_static_key.clone(),
+        timeout,
+    )?;
+    debug!(local_addr = %tunnel.local_addr(), "Started Noise tunnel");
+
+    Ok(Some(tunnel))
+}
+
+/// Reject a Noise transport, which this build cannot provide
+#[cfg(all(
+    feature = "reqwest",
+    not(all(feature = "noise", not(target_arch = "wasm32")))
+))]
+pub(super) fn start_noise_tunnel(
+    config: &super::StorageNodeClientConfig,
+    _timeout: Duration,
+) -> Result<()> {
+    match config.transport {
+        TransportConfig::Noise { .. } => Err(StorageNodeError::Config(
+            "Noise transport requires the `noise` feature".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Find out which transport to use with the storage node
+    ///
+    /// A client configured for QUIC sends a health check over HTTP/3 and
+    /// falls back to TCP if the storage node does not answer it. The outcome
+    /// is kept, so later calls return it without probing again and every
+    /// request from then on uses the chosen transport. Until the first probe,
+    /// requests go over TCP. A client configured for Noise always uses Noise.
+    ///
+    /// # Returns
+    /// * `Result<TransportKind>` - The transport requests are sent over
+    pub async fn probe_transport(&self) -> Result<TransportKind> {
+        if let Some(kind) = self.transport.get() {
+            return Ok(*kind);
+        }
+
+        #[cfg(all(feature = "noise", not(target_arch = "wasm32")))]
+        if self.noise_tunnel.is_some() {
+            return Ok(*self.transport.get_or_init(|| TransportKind::Noise));
+        }
+
+        let kind = match &self.quic_client {
+            Some(quic_client) => {
+                let url = self.base_url.join("health").map_err(|e| {
+                    StorageNodeError::Network(format!("Failed to create URL: {}", e))
+                })?;
+
+                match quic_client.get(url).send().await {
+                    Ok(response) if response.status().is_success() => TransportKind::Quic,
+                    Ok(response) => {
+                        debug!(
+                            status = %response.status(),
+                            "QUIC health check failed, using TCP"
+                        );
+                        TransportKind::Tcp
+                    }
+                    Err(e) => {
+                        debug!(error = %e, "Storage node does not accept QUIC, using TCP");
+                        TransportKind::Tcp
+                    }
+                }
+            }
+            None => TransportKind::Tcp,
+        };
+
+        Ok(*self.transport.get_or_init(|| kind))
+    }
+
+    /// Transport chosen by [`Self::probe_transport`], if it has run
+    pub fn transport(&self) -> Option<TransportKind> {
+        self.transport.get().copied()
+    }
+
+    /// HTTP client for the chosen transport
+    pub(super) fn http_client(&self) -> &reqwest::Client {
+        match (&self.quic_client, self.transport.get()) {
+            (Some(quic_client), Some(TransportKind::Quic)) => quic_client,
+            _ => &self.tcp_client,
+        }
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn probe_transport(&self) -> Result<TransportKind> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub fn transport(&self) -> Option<TransportKind> {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::client::StorageNodeClientConfig;
+    use mockito::Server;
+
+    #[test]
+    fn test_transport_config_defaults_to_tcp() {
+        let config: StorageNodeClientConfig = serde_json::from_str(
+            r#"{"base_url": "http://localhost:8080", "api_token": null, "timeout_seconds": 30}"#,
+        )
+        .unwrap();
+        assert_eq!(config.transport, TransportConfig::Tcp);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_client_probes_tcp() {
+        let mut server = Server::new_async().await;
+        let health = server.mock("GET", "/health").expect(0).create_async().await;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+
+        assert_eq!(client.transport(), None);
+        assert_eq!(client.probe_transport().await.unwrap(), TransportKind::Tcp);
+        assert_eq!(client.transport(), Some(TransportKind::Tcp));
+        health.assert_async().await;
+    }
+
+    #[cfg(not(feature = "quic"))]
+    #[test]
+    fn test_quic_rejected_without_feature() {
+        let result = StorageNodeClient::new(StorageNodeClientConfig {
+            transport: TransportConfig::Quic {
+                tls_config: TlsClientConfig::default(),
+            },
+            ..StorageNodeClientConfig::default()
+        });
+        assert!(matches!(result, Err(StorageNodeError::Config(_))));
+    }
+
+    #[cfg(not(feature = "noise"))]
+    #[test]
+    fn test_noise_rejected_without_feature() {
+        let result = StorageNodeClient::new(StorageNodeClientConfig {
+            transport: TransportConfig::Noise {
+                remote_static_key: vec![0; 32],
+            },
+            ..StorageNodeClientConfig::default()
+        });
+        assert!(matches!(result, Err(StorageNodeError::Config(_))));
+    }
+
+    #[cfg(feature = "quic")]
+    #[tokio::test]
+    async fn test_quic_falls_back_to_tcp() {
+        // mockito only speaks HTTP/1.1, so the HTTP/3 health check fails
+        let mut server = Server::new_async().await;
+        server.mock("GET", "/health").create_async().await;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            timeout_seconds: 2,
+            transport: TransportConfig::Quic {
+                tls_config: TlsClientConfig::default(),
+            },
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+
+        assert_eq!(client.probe_transport().await.unwrap(), TransportKind::Tcp);
+        assert!(client.check_health().await.unwrap());
+    }
+}
@@ -0,0 +1,481 @@
+// Vault search for the DSM Storage Node Client
+//
+// Vaults can be looked up by ID with `fetch_vault`; searching finds vaults by
+// creator, intended recipient, state, creation state number and metadata
+// instead. The filter is sent as JSON to `POST /vault/search` and results are
+// paged with an opaque cursor returned by the storage node.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::StorageNodeClient;
+use crate::error::{Result, StorageNodeError};
+use dsm::vault::{LimboVault, VaultState};
+use serde::{Deserialize, Serialize};
+
+/// Vault lifecycle states that can be searched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultStateFilter {
+    /// In limbo, waiting for its fulfillment condition
+    Active,
+
+    /// Content claimed by a recipient
+    Claimed,
+
+    /// Invalidated by its creator
+    Invalidated,
+
+    /// Fulfillment condition met but not yet claimed
+    Unlocked,
+}
+
+/// Criteria for a vault search
+///
+/// Every field is optional; a vault matches when it satisfies all the fields
+/// that are set, so the default filter matches every vault.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultFilter {
+    /// Public key of the vault creator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_public_key: Option<Vec<u8>>,
+
+    /// Public key of the intended recipient
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intended_recipient: Option<Vec<u8>>,
+
+    /// Current vault state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<VaultStateFilter>,
+
+    /// Only vaults created after this state number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<u64>,
+
+    /// Only vaults created before this state number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<u64>,
+
+    /// Metadata entries the vault must have, every one of them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl VaultFilter {
+    /// Filter matching every vault
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only vaults created by the given public key
+    pub fn created_by(mut self, public_key: impl Into<Vec<u8>>) -> Self {
+        self.creator_public_key = Some(public_key.into());
+        self
+    }
+
+    /// Only vaults intended for the given public key
+    pub fn intended_for(mut self, public_key: impl Into<Vec<u8>>) -> Self {
+        self.intended_recipient = Some(public_key.into());
+        self
+    }
+
+    /// Only vaults in the given state
+    pub fn in_state(mut self, state: VaultStateFilter) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Only vaults created after the given state number
+    pub fn created_after(mut self, state_number: u64) -> Self {
+        self.created_after = Some(state_number);
+        self
+    }
+
+    /// Only vaults created before the given state number
+    pub fn created_before(mut self, state_number: u64) -> Self {
+        self.created_before = Some(state_number);
+        self
+    }
+
+    /// Only vaults whose metadata sets `key` to `value`
+    pub fn metadata_equals(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether a vault satisfies every criterion that is set
+    pub fn matches(&self, vault: &LimboVault) -> bool {
+        let state_matches = match self.state {
+            None => true,
+            Some(VaultStateFilter::Active) => matches!(vault.state, VaultState::Limbo),
+            Some(VaultStateFilter::Claimed) => matches!(vault.state, VaultState::Claimed { .. }),
+            Some(VaultStateFilter::Invalidated) => {
+                matches!(vault.state, VaultState::Invalidated { .. })
+            }
+            Some(VaultStateFilter::Unlocked) => {
+                matches!(vault.state, VaultState::Unlocked { .. })
+            }
+        };
+
+        state_matches
+            && self
+                .creator_public_key
+                .as_ref()
+                .map_or(true, |key| *key == vault.creator_public_key)
+            && self
+                .intended_recipient
+                .as_ref()
+                .map_or(true, |key| vault.intended_recipient.as_ref() == Some(key))
+            && self
+                .created_after
+                .map_or(true, |state_number| vault.created_at_state > state_number)
+            && self
+                .created_before
+                .map_or(true, |state_number| vault.created_at_state < state_number)
+            && self
+                .metadata
+                .iter()
+                .all(|(key, value)| vault.metadata.get(key) == Some(value))
+    }
+}
+
+/// Overview of a vault for listings, without its encrypted content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultSummary {
+    /// Vault ID
+    pub id: String,
+
+    /// Public key of the vault creator
+    pub creator_public_key: Vec<u8>,
+
+    /// Public key of the intended recipient (if specified)
+    pub intended_recipient: Option<Vec<u8>>,
+
+    /// Current vault state
+    pub state: VaultState,
+
+    /// Content type identifier
+    pub content_type: String,
+
+    /// State number the vault was created at
+    pub created_at_state: u64,
+
+    /// Metadata committed by the creator
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl From<&LimboVault> for VaultSummary {
+    fn from(vault: &LimboVault) -> Self {
+        Self {
+            id: vault.id.clone(),
+            creator_public_key: vault.creator_public_key.clone(),
+            intended_recipient: vault.intended_recipient.clone(),
+            state: vault.state.clone(),
+            content_type: vault.content_type.clone(),
+            created_at_state: vault.created_at_state,
+            metadata: vault.metadata.clone(),
+        }
+    }
+}
+
+/// Body of a `POST /vault/search` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultSearchRequest {
+    /// Search criteria
+    #[serde(flatten)]
+    pub filter: VaultFilter,
+
+    /// Cursor returned with the previous page (None = first page)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+
+    /// Maximum number of vaults to return (None = storage node default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+}
+
+/// Most pages `search_vaults` fetches before giving up on a search
+pub const MAX_VAULT_SEARCH_PAGES: usize = 1000;
+
+/// One page of vault search results
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultSearchPage {
+    /// Vaults matching the filter
+    #[serde(default)]
+    pub vaults: Vec<LimboVault>,
+
+    /// Cursor for the next page (None = this is the last page)
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(feature = "reqwest")]
+impl StorageNodeClient {
+    /// Find all vaults matching a filter
+    ///
+    /// Follows the storage node's cursor until every page has been fetched; an
+    /// empty cursor also ends the search. A node that repeats a cursor or
+    /// returns more than `MAX_VAULT_SEARCH_PAGES` pages fails the search
+    /// instead of keeping it going forever. With `auto_cache_enabled`, returned
+    /// vaults are added to the storage cache.
+    ///
+    /// # Arguments
+    /// * `filter` - Search criteria
+    ///
+    /// # Returns
+    /// * `Result<Vec<LimboVault>>` - Matching vaults, empty if there are none
+    pub async fn search_vaults(&self, filter: VaultFilter) -> Result<Vec<LimboVault>> {
+        let mut vaults = Vec::new();
+        let mut cursor = None;
+        let mut seen_cursors = HashSet::new();
+
+        for _ in 0..MAX_VAULT_SEARCH_PAGES {
+            let page = self.search_vaults_page(&filter, cursor, None).await?;
+            vaults.extend(page.vaults);
+
+            match page.next_cursor {
+                Some(next) if !next.is_empty() => {
+                    if !seen_cursors.insert(next.clone()) {
+                        return Err(StorageNodeError::InvalidState(format!(
+                            "Storage node repeated vault search cursor {}",
+                            next
+                        )));
+                    }
+                    cursor = Some(next);
+                }
+                _ => return Ok(vaults),
+            }
+        }
+
+        Err(StorageNodeError::InvalidState(format!(
+            "Vault search did not finish within {} pages",
+            MAX_VAULT_SEARCH_PAGES
+        )))
+    }
+
+    /// List summaries of all vaults matching a filter
+    ///
+    /// # Arguments
+    /// * `filter` - Search criteria
+    ///
+    /// # Returns
+    /// * `Result<Vec<VaultSummary>>` - Summaries of the matching vaults
+    pub async fn list_vaults(&self, filter: VaultFilter) -> Result<Vec<VaultSummary>> {
+        let vaults = self.search_vaults(filter).await?;
+        Ok(vaults.iter().map(VaultSummary::from).collect())
+    }
+
+    /// Fetch one page of vaults matching a filter
+    ///
+    /// # Arguments
+    /// * `filter` - Search criteria
+    /// * `cursor` - Cursor returned with the previous page (None = first page)
+    /// * `page_size` - Maximum number of vaults to return
+    ///
+    /// # Returns
+    /// * `Result<VaultSearchPage>` - The page and the cursor for the next one
+    pub async fn search_vaults_page(
+        &self,
+        filter: &VaultFilter,
+        cursor: Option<String>,
+        page_size: Option<u32>,
+    ) -> Result<VaultSearchPage> {
+        let url = self
+            .base_url
+            .join("vault/search")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+
+        let request = VaultSearchRequest {
+            filter: filter.clone(),
+            cursor,
+            page_size,
+        };
+
+        let builder = self.prepare_request(self.http_client().post(url)).await?;
+
+        let response = builder
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Storage node returned error: {}",
+                response.status()
+            )));
+        }
+
+        let page: VaultSearchPage = response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse vault search response: {}", e))
+        })?;
+
+        if self.auto_cache_enabled {
+            for vault in &page.vaults {
+                self.cache_fetched_vault(vault).await?;
+            }
+        }
+
+        Ok(page)
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+impl StorageNodeClient {
+    pub async fn search_vaults(&self, _filter: VaultFilter) -> Result<Vec<LimboVault>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn list_vaults(&self, _filter: VaultFilter) -> Result<Vec<VaultSummary>> {
+        Err(StorageNodeError::Internal)
+    }
+
+    pub async fn search_vaults_page(
+        &self,
+        _filter: &VaultFilter,
+        _cursor: Option<String>,
+        _page_size: Option<u32>,
+    ) -> Result<VaultSearchPage> {
+        Err(StorageNodeError::Internal)
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::client::StorageNodeClientConfig;
+    use mockito::{Matcher, Server, ServerGuard};
+    use serde_json::json;
+
+    async fn server_and_client() -> (ServerGuard, StorageNodeClient) {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/version")
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&StorageNodeClient::supported_versions()).unwrap())
+            .create_async()
+            .await;
+
+        let client = StorageNodeClient::new(StorageNodeClientConfig {
+            base_url: server.url(),
+            ..StorageNodeClientConfig::default()
+        })
+        .unwrap();
+
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_search_sends_filter_fields() {
+        let (mut server, client) = server_and_client().await;
+
+        let search = server
+            .mock("POST", "/vault/search")
+            .match_body(Matcher::Json(json!({
+                "intended_recipient": [7, 7],
+                "state": "active",
+                "created_after": 10,
+                "cursor": "page-2",
+                "page_size": 25,
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"vaults": [], "next_cursor": null}"#)
+            .create_async()
+            .await;
+
+        let filter = VaultFilter::new()
+            .intended_for(vec![7, 7])
+            .in_state(VaultStateFilter::Active)
+            .created_after(10);
+        let page = client
+            .search_vaults_page(&filter, Some("page-2".to_string()), Some(25))
+            .await
+            .unwrap();
+
+        search.assert_async().await;
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_empty_search_returns_empty_vec() {
+        let (mut server, client) = server_and_client().await;
+
+        let search = server
+            .mock("POST", "/vault/search")
+            .match_body(Matcher::Json(json!({})))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"vaults": []}"#)
+            .create_async()
+            .await;
+
+        let vaults = client.search_vaults(VaultFilter::new()).await.unwrap();
+
+        search.assert_async().await;
+        assert!(vaults.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_stops_on_repeated_cursor() {
+        let (mut server, client) = server_and_client().await;
+
+        server
+            .mock("POST", "/vault/search")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"vaults": [], "next_cursor": "stuck"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        assert!(matches!(
+            client.search_vaults(VaultFilter::new()).await,
+            Err(StorageNodeError::InvalidState(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_empty_cursor_ends_search() {
+        let (mut server, client) = server_and_client().await;
+
+        let search = server
+            .mock("POST", "/vault/search")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"vaults": [], "next_cursor": ""}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let vaults = client.search_vaults(VaultFilter::new()).await.unwrap();
+
+        search.assert_async().await;
+        assert!(vaults.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_vaults_by_metadata() {
+        let (mut server, client) = server_and_client().await;
+
+        let vault = LimboVault {
+            id: "vault-1".to_string(),
+            metadata: BTreeMap::from([("category".to_string(), "tickets".to_string())]),
+            ..LimboVault::default()
+        };
+        let search = server
+            .mock("POST", "/vault/search")
+            .match_body(Matcher::Json(json!({
+                "metadata": {"category": "tickets"},
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "vaults": [vault] }).to_string())
+            .create_async()
+            .await;
+
+        let summaries = client
+            .list_vaults(VaultFilter::new().metadata_equals("category", "tickets"))
+            .await
+            .unwrap();
+
+        search.assert_async().await;
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].id, "vault-1");
+        assert_eq!(summaries[0].metadata["category"], "tickets");
+    }
+}
@@ -0,0 +1,308 @@
+// Cache warm-up for the DSM Storage Node Client
+//
+// This module lets a wallet prefetch everything it will need offline
+// (contact genesis states, tokens, checkpoints and vaults) in a single call.
+
+use super::StorageNodeClient;
+use crate::error::Result;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Default number of concurrent fetches performed during warm-up
+pub const DEFAULT_WARMUP_CONCURRENCY: usize = 8;
+
+/// Objects to prefetch into the storage cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupSpec {
+    /// Genesis state hashes (e.g. contacts)
+    pub genesis_hashes: Vec<Vec<u8>>,
+
+    /// Token IDs
+    pub token_ids: Vec<String>,
+
+    /// Checkpoint IDs
+    pub checkpoint_ids: Vec<String>,
+
+    /// Vault IDs
+    pub vault_ids: Vec<String>,
+
+    /// Maximum number of fetches in flight at once
+    pub max_concurrency: usize,
+}
+
+impl Default for WarmupSpec {
+    fn default() -> Self {
+        Self {
+            genesis_hashes: Vec::new(),
+            token_ids: Vec::new(),
+            checkpoint_ids: Vec::new(),
+            vault_ids: Vec::new(),
+            max_concurrency: DEFAULT_WARMUP_CONCURRENCY,
+        }
+    }
+}
+
+impl WarmupSpec {
+    /// Flatten the spec into individual warm-up targets
+    fn into_targets(self) -> Vec<WarmupTarget> {
+        self.genesis_hashes
+            .into_iter()
+            .map(WarmupTarget::Genesis)
+            .chain(self.token_ids.into_iter().map(WarmupTarget::Token))
+            .chain(
+                self.checkpoint_ids
+                    .into_iter()
+                    .map(WarmupTarget::Checkpoint),
+            )
+            .chain(self.vault_ids.into_iter().map(WarmupTarget::Vault))
+            .collect()
+    }
+}
+
+/// A single object requested during warm-up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarmupTarget {
+    /// Genesis state by hash
+    Genesis(Vec<u8>),
+
+    /// Token by ID
+    Token(String),
+
+    /// Checkpoint by ID
+    Checkpoint(String),
+
+    /// Vault by ID
+    Vault(String),
+}
+
+impl fmt::Display for WarmupTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmupTarget::Genesis(hash) => write!(f, "genesis {}", hex::encode(hash)),
+            WarmupTarget::Token(id) => write!(f, "token {}", id),
+            WarmupTarget::Checkpoint(id) => write!(f, "checkpoint {}", id),
+            WarmupTarget::Vault(id) => write!(f, "vault {}", id),
+        }
+    }
+}
+
+/// Outcome of warming up a single object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarmupOutcome {
+    /// A fresh copy was already in the cache; no request was made
+    AlreadyCached,
+
+    /// The object was fetched from the storage node and cached
+    Fetched,
+
+    /// The storage node does not have the object
+    NotFound,
+
+    /// The fetch failed
+    Error(String),
+}
+
+/// Result for a single warm-up target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupItemResult {
+    /// The requested object
+    pub target: WarmupTarget,
+
+    /// What happened to it
+    pub outcome: WarmupOutcome,
+}
+
+/// Per-item report of a warm-up run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WarmupReport {
+    /// Results, one per requested object
+    pub items: Vec<WarmupItemResult>,
+}
+
+impl WarmupReport {
+    /// Number of objects that were already cached
+    pub fn already_cached(&self) -> usize {
+        self.count(|outcome| matches!(outcome, WarmupOutcome::AlreadyCached))
+    }
+
+    /// Number of objects fetched from the storage node
+    pub fn fetched(&self) -> usize {
+        self.count(|outcome| matches!(outcome, WarmupOutcome::Fetched))
+    }
+
+    /// Number of objects the storage node did not have
+    pub fn not_found(&self) -> usize {
+        self.count(|outcome| matches!(outcome, WarmupOutcome::NotFound))
+    }
+
+    /// Number of objects whose fetch failed
+    pub fn errors(&self) -> usize {
+        self.count(|outcome| matches!(outcome, WarmupOutcome::Error(_)))
+    }
+
+    /// Look up the outcome for a specific target
+    pub fn outcome_for(&self, target: &WarmupTarget) -> Option<&WarmupOutcome> {
+        self.items
+            .iter()
+            .find(|item| &item.target == target)
+            .map(|item| &item.outcome)
+    }
+
+    fn count(&self, predicate: impl Fn(&WarmupOutcome) -> bool) -> usize {
+        self.items
+            .iter()
+            .filter(|item| predicate(&item.outcome))
+            .count()
+    }
+}
+
+impl StorageNodeClient {
+    /// Prefetch a set of objects into the storage cache
+    ///
+    /// Objects already cached and fresh are skipped. Fetches run with bounded
+    /// concurrency, and a failure for one object never aborts the others.
+    ///
+    /// # Arguments
+    /// * `spec` - Objects to prefetch
+    ///
+    /// # Returns
+    /// * `Result<WarmupReport>` - Per-object outcome of the warm-up
+    pub async fn warm_cache(&self, spec: WarmupSpec) -> Result<WarmupReport> {
+        let concurrency = spec.max_concurrency.max(1);
+
+        let items = stream::iter(spec.into_targets())
+            .map(|target| async move {
+                let outcome = self.warm_target(&target).await;
+                WarmupItemResult { target, outcome }
+            })
+            .buffer_unordered(concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(WarmupReport { items })
+    }
+
+    /// Warm a single target, converting every failure into an outcome
+    async fn warm_target(&self, target: &WarmupTarget) -> WarmupOutcome {
+        let cache = self.storage_cache();
+
+        let fetched = match target {
+            WarmupTarget::Genesis(hash) => {
+                if cache.has_genesis(hash).await {
+                    return WarmupOutcome::AlreadyCached;
+                }
+                self.fetch_genesis_state(hash).await.map(|v| v.is_some())
+            }
+            WarmupTarget::Token(id) => {
+                if cache.has_token(id).await {
+                    return WarmupOutcome::AlreadyCached;
+                }
+                self.fetch_token(id).await.map(|v| v.is_some())
+            }
+            WarmupTarget::Checkpoint(id) => {
+                if cache.has_checkpoint(id).await {
+                    return WarmupOutcome::AlreadyCached;
+                }
+                self.fetch_checkpoint(id).await.map(|v| v.is_some())
+            }
+            WarmupTarget::Vault(id) => {
+                if cache.has_vault(id).await {
+                    return WarmupOutcome::AlreadyCached;
+                }
+                self.fetch_vault(id).await.map(|v| v.is_some())
+            }
+        };
+
+        match fetched {
+            Ok(true) => WarmupOutcome::Fetched,
+            Ok(false) => WarmupOutcome::NotFound,
+            Err(e) => WarmupOutcome::Error(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::StorageNodeClientConfig;
+    use dsm::communication::StorageCache;
+    use dsm::types::state_types::{DeviceInfo, State};
+    use dsm::types::token_types::{Balance, Token};
+
+    fn unreachable_client() -> StorageNodeClient {
+        StorageNodeClient::new(StorageNodeClientConfig {
+            // Nothing listens on port 1, so every network fetch fails fast
+            base_url: "http://127.0.0.1:1".to_string(),
+            timeout_seconds: 1,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_warmup_skips_cached_entries() {
+        let client = unreachable_client();
+        let cache = client.storage_cache();
+
+        let token = Token::new("owner", b"data".to_vec(), Vec::new(), Balance::new(1));
+        cache
+            .cache_token("token-1", token, true, None)
+            .await
+            .unwrap();
+
+        let checkpoint = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("dev", vec![0; 32]));
+        let checkpoint_id = StorageCache::checkpoint_id(&checkpoint).unwrap();
+        cache
+            .cache_checkpoint(checkpoint, true, None)
+            .await
+            .unwrap();
+
+        let report = client
+            .warm_cache(WarmupSpec {
+                token_ids: vec!["token-1".to_string()],
+                checkpoint_ids: vec![checkpoint_id.clone()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.items.len(), 2);
+        assert_eq!(report.already_cached(), 2);
+        assert_eq!(
+            report.outcome_for(&WarmupTarget::Checkpoint(checkpoint_id)),
+            Some(&WarmupOutcome::AlreadyCached)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warmup_failures_do_not_abort() {
+        let client = unreachable_client();
+
+        let token = Token::new("owner", b"data".to_vec(), Vec::new(), Balance::new(1));
+        client
+            .storage_cache()
+            .cache_token("cached", token, true, None)
+            .await
+            .unwrap();
+
+        let report = client
+            .warm_cache(WarmupSpec {
+                genesis_hashes: vec![vec![0xab; 32]],
+                token_ids: vec!["missing".to_string(), "cached".to_string()],
+                vault_ids: vec!["vault-1".to_string()],
+                max_concurrency: 2,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.items.len(), 4);
+        assert_eq!(report.already_cached(), 1);
+        assert_eq!(report.errors(), 3);
+        assert_eq!(
+            report.outcome_for(&WarmupTarget::Token("cached".to_string())),
+            Some(&WarmupOutcome::AlreadyCached)
+        );
+    }
+}
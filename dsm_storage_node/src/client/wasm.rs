@@ -0,0 +1,133 @@
+// JavaScript bindings for the DSM Storage Node Client
+//
+// Exposes the client to browsers through wasm-bindgen. Every async method
+// returns a `Promise`; errors reject it with a JavaScript `Error` carrying the
+// storage node error message.
+
+use super::{StorageNodeClient, StorageNodeClientConfig};
+use crate::error::StorageNodeError;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+/// Storage node client usable from JavaScript
+#[wasm_bindgen(js_name = StorageNodeClient)]
+pub struct WasmStorageClient {
+    inner: Arc<StorageNodeClient>,
+}
+
+#[wasm_bindgen(js_class = StorageNodeClient)]
+impl WasmStorageClient {
+    /// Create a client for the storage node at `base_url`
+    #[wasm_bindgen(constructor)]
+    pub fn new(base_url: String, api_token: Option<String>) -> Result<WasmStorageClient, JsError> {
+        let config = StorageNodeClientConfig {
+            base_url,
+            api_token,
+            ..StorageNodeClientConfig::default()
+        };
+
+        Ok(Self {
+            inner: Arc::new(StorageNodeClient::new(config).map_err(js_error)?),
+        })
+    }
+
+    /// Resolve to whether the storage node is healthy
+    #[wasm_bindgen(js_name = checkHealth)]
+    pub fn check_health(&self) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move { client.check_health().await })
+    }
+
+    /// Resolve to the negotiated protocol version, e.g. `"1.2.0"`
+    #[wasm_bindgen(js_name = negotiateVersion)]
+    pub fn negotiate_version(&self) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move { Ok(client.negotiate_version().await?.to_string()) })
+    }
+
+    /// Store raw bytes under `key`, optionally expiring after `ttl_seconds`
+    #[wasm_bindgen(js_name = storeData)]
+    pub fn store_data(
+        &self,
+        key: String,
+        data: Vec<u8>,
+        ttl_seconds: Option<u32>,
+    ) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move {
+            client
+                .store_data(&key, &data, ttl_seconds.map(u64::from))
+                .await
+        })
+    }
+
+    /// Resolve to the bytes stored under `key`, or `null`
+    #[wasm_bindgen(js_name = retrieveData)]
+    pub fn retrieve_data(&self, key: String) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move { client.retrieve_data(&key).await })
+    }
+
+    /// Resolve to whether the data under `key` was deleted
+    #[wasm_bindgen(js_name = deleteData)]
+    pub fn delete_data(&self, key: String) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move { client.delete_data(&key).await })
+    }
+
+    /// Resolve to the live inbox entries for a hex-encoded recipient genesis hash
+    #[wasm_bindgen(js_name = getInboxTransactions)]
+    pub fn get_inbox_transactions(&self, recipient_genesis_hash: String) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move { client.get_inbox_transactions(&recipient_genesis_hash).await })
+    }
+
+    /// Resolve to whether an inbox entry was deleted
+    #[wasm_bindgen(js_name = deleteInboxTransaction)]
+    pub fn delete_inbox_transaction(
+        &self,
+        recipient_genesis_hash: String,
+        entry_id: String,
+    ) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move {
+            client
+                .delete_inbox_transaction(&recipient_genesis_hash, &entry_id)
+                .await
+        })
+    }
+
+    /// Resolve to whether the state with the given hash has been invalidated
+    #[wasm_bindgen(js_name = isStateInvalidated)]
+    pub fn is_state_invalidated(&self, state_hash: Vec<u8>) -> js_sys::Promise {
+        let client = Arc::clone(&self.inner);
+        promise(async move { client.is_state_invalidated(&state_hash).await })
+    }
+}
+
+impl WasmStorageClient {
+    /// The wrapped client, for use by other Rust bindings
+    pub fn client(&self) -> Arc<StorageNodeClient> {
+        Arc::clone(&self.inner)
+    }
+}
+
+/// Convert a client future into a `Promise` resolving to its serialized output
+fn promise<F, T>(future: F) -> js_sys::Promise
+where
+    F: Future<Output = crate::error::Result<T>> + 'static,
+    T: Serialize,
+{
+    future_to_promise(async move {
+        let value = future.await.map_err(js_error)?;
+        serde_wasm_bindgen::to_value(&value).map_err(|e| JsError::new(&e.to_string()).into())
+    })
+}
+
+/// Convert a client error into a JavaScript `Error`
+fn js_error(error: StorageNodeError) -> JsError {
+    JsError::new(&error.to_string())
+}
@@ -101,7 +101,7 @@ pub fn sign_with_node_key(private_key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
     debug!("Signing data with node key");
 
     // Use SPHINCS+ for signing
-    let signature = sphincs_sign(data, private_key)
+    let signature = sphincs_sign(private_key, data)
         .map_err(|e| StorageNodeError::Encryption(format!("Failed to sign data: {}", e)))?;
 
     Ok(signature)
@@ -112,12 +112,20 @@ pub fn verify_with_node_key(public_key: &[u8], data: &[u8], signature: &[u8]) ->
     debug!("Verifying signature with node key");
 
     // Use SPHINCS+ for verification
-    let result = sphincs_verify(data, signature, public_key)
+    let result = sphincs_verify(public_key, data, signature)
         .map_err(|e| StorageNodeError::Encryption(format!("Failed to verify signature: {}", e)))?;
 
     Ok(result)
 }
 
+/// Verify signature with node public key, treating malformed input as invalid
+///
+/// Keys and signatures checked here come from untrusted input, so a malformed
+/// one fails verification like a wrong one instead of being an internal error.
+pub fn verify_signature_or_false(public_key: &[u8], data: &[u8], signature: &[u8]) -> bool {
+    verify_with_node_key(public_key, data, signature).unwrap_or(false)
+}
+
 /// Encrypt data for a recipient
 pub fn encrypt_data(recipient_public_key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
     debug!("Encrypting data for recipient");
@@ -2,6 +2,8 @@
 //
 // This module defines error types and utility functions for error handling
 
+use crate::types::ProtocolVersionRange;
+#[cfg(not(target_arch = "wasm32"))]
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -56,6 +58,10 @@ pub enum StorageNodeError {
     #[error("Encryption error: {0}")]
     Encryption(String),
 
+    /// Producing a signature failed
+    #[error("Signing error: {0}")]
+    Signing(String),
+
     /// Distribution-related errors
     #[error("Distribution error: {0}")]
     Distribution(String),
@@ -68,6 +74,10 @@ pub enum StorageNodeError {
     #[error("Staking error: {0}")]
     Staking(String),
 
+    /// A vault post is unsigned or not signed by the vault's creator
+    #[error("Invalid vault post: {0}")]
+    InvalidVaultPost(String),
+
     /// Authentication-related errors
     #[error("Authentication error: {0}")]
     Authentication(String),
@@ -135,9 +145,27 @@ pub enum StorageNodeError {
     /// Invalid input
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+
+    /// Resource existed but has expired
+    #[error("Expired: {0}")]
+    Expired(String),
+
+    /// The node is under an emergency pause and accepts no writes
+    #[error("Node paused: {0}")]
+    Paused(String),
+
+    /// Client and server protocol version ranges do not overlap
+    #[error("Incompatible protocol version: client supports {client}, server supports {server}")]
+    VersionIncompatible {
+        /// Version range the client speaks
+        client: ProtocolVersionRange,
+        /// Version range the server accepts
+        server: ProtocolVersionRange,
+    },
 }
 
 /// Implement IntoResponse for StorageNodeError so it can be returned directly from handlers
+#[cfg(not(target_arch = "wasm32"))]
 impl IntoResponse for StorageNodeError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
@@ -164,9 +192,11 @@ fn into_response(self) -> Response {
             StorageNodeError::Authentication(msg) => (StatusCode::UNAUTHORIZED, msg),
             StorageNodeError::Storage(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::Encryption(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            StorageNodeError::Signing(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::Distribution(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::NodeManagement(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::Staking(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            StorageNodeError::InvalidVaultPost(msg) => (StatusCode::BAD_REQUEST, msg),
             StorageNodeError::Config(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::Database(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::Serialization(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
@@ -186,6 +216,11 @@ fn into_response(self) -> Response {
             StorageNodeError::QueueFull(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             StorageNodeError::ReceiveFailure(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             StorageNodeError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
+            StorageNodeError::Expired(msg) => (StatusCode::GONE, msg),
+            StorageNodeError::Paused(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            err @ StorageNodeError::VersionIncompatible { .. } => {
+                (StatusCode::UPGRADE_REQUIRED, err.to_string())
+            }
         };
 
         let body = Json(serde_json::json!({
@@ -200,6 +235,7 @@ fn into_response(self) -> Response {
 }
 
 // Implement conversion from rusqlite error to StorageNodeError
+#[cfg(not(target_arch = "wasm32"))]
 impl From<rusqlite::Error> for StorageNodeError {
     fn from(err: rusqlite::Error) -> Self {
         StorageNodeError::Sqlite(err.to_string())
@@ -248,6 +284,24 @@ fn from(err: Box<bincode::ErrorKind>) -> Self {
     }
 }
 
+// Implement conversion from DSM core errors to StorageNodeError
+impl From<dsm::types::error::DsmError> for StorageNodeError {
+    fn from(err: dsm::types::error::DsmError) -> Self {
+        match err {
+            dsm::types::error::DsmError::RateLimited { .. } => {
+                StorageNodeError::RateLimitExceeded(err.to_string())
+            }
+            dsm::types::error::DsmError::InvalidVaultPost { .. } => {
+                StorageNodeError::InvalidVaultPost(err.to_string())
+            }
+            dsm::types::error::DsmError::VersionIncompatible { .. } => {
+                StorageNodeError::Request(err.to_string())
+            }
+            err => StorageNodeError::Storage(err.to_string()),
+        }
+    }
+}
+
 // Implement conversion from reqwest::StatusCode to StorageNodeError
 impl From<reqwest::StatusCode> for StorageNodeError {
     fn from(status: reqwest::StatusCode) -> Self {
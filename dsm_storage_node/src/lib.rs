@@ -2,15 +2,27 @@
 //
 // This library implements a quantum-resistant decentralized storage node
 // as described in Section 16 of the DSM whitepaper.
+//
+// On wasm32 targets only the client and the wire types it shares with the
+// server are built; everything else runs on the storage node itself.
 
 pub mod api;
 pub mod client;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod crypto;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod distribution;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod encryption;
 pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod network;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod node_management;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod staking;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod storage;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test_support;
 pub mod types;
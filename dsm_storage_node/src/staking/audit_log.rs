@@ -0,0 +1,479 @@
+// Distribution Audit Log for DSM Storage Node
+//
+// Every reward vault paid out by the reward vault manager is recorded in an
+// append-only log, as is every vault revoked before it was paid out. A record
+// of a payment ties the claimed vault content to the amounts paid to each
+// recipient and to the storage receipts that justified them. A record is
+// signed with the node's operational SPHINCS+ key and chains the hash of the
+// record before it, so removing, reordering or altering a record breaks the
+// chain. Auditors verify an exported range of records with the node's public
+// key alone.
+
+use crate::crypto::{sign_with_node_key, verify_signature_or_false};
+use crate::error::{Result, StorageNodeError};
+use crate::staking::reward_store::RewardStore;
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of audit record hashes
+const AUDIT_RECORD_DOMAIN: &[u8] = b"DSM_DISTRIBUTION_AUDIT";
+
+/// Previous hash of the first record in the log
+pub const AUDIT_GENESIS_HASH: [u8; 32] = [0u8; 32];
+
+/// A signed record of one reward distribution or vault revocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position of the record in the log, starting at 0
+    pub sequence: u64,
+
+    /// ID of the distributed vault
+    pub vault_id: String,
+
+    /// Blake3 hash of the claimed vault content
+    pub content_hash: [u8; 32],
+
+    /// Amount paid to each recipient (node_id -> amount)
+    pub amounts: BTreeMap<String, u64>,
+
+    /// Hashes of the storage receipts that justified the distribution
+    pub receipt_hashes: Vec<[u8; 32]>,
+
+    /// Why the vault was revoked (None = the record is of a distribution)
+    #[serde(default)]
+    pub revocation_reason: Option<String>,
+
+    /// When the distribution was processed
+    pub recorded_at: u64,
+
+    /// Hash of the previous record, or [`AUDIT_GENESIS_HASH`] for the first one
+    pub previous_hash: [u8; 32],
+
+    /// Hash of the record (deterministic from the fields above)
+    pub record_hash: [u8; 32],
+
+    /// Node's signature over the record hash
+    pub signature: Vec<u8>,
+}
+
+impl AuditRecord {
+    /// Compute the hash the node signs
+    ///
+    /// Covers every field except the hash and the signature. Variable-length
+    /// fields are length-prefixed so distinct records cannot hash the same.
+    pub fn compute_hash(&self) -> [u8; 32] {
+        let mut hasher = ::blake3::Hasher::new();
+        hasher.update(AUDIT_RECORD_DOMAIN);
+        hasher.update(&self.sequence.to_le_bytes());
+        hasher.update(&(self.vault_id.len() as u64).to_le_bytes());
+        hasher.update(self.vault_id.as_bytes());
+        hasher.update(&self.content_hash);
+
+        hasher.update(&(self.amounts.len() as u64).to_le_bytes());
+        for (node_id, amount) in &self.amounts {
+            hasher.update(&(node_id.len() as u64).to_le_bytes());
+            hasher.update(node_id.as_bytes());
+            hasher.update(&amount.to_le_bytes());
+        }
+
+        hasher.update(&(self.receipt_hashes.len() as u64).to_le_bytes());
+        for receipt_hash in &self.receipt_hashes {
+            hasher.update(receipt_hash);
+        }
+
+        hasher.update(&self.recorded_at.to_le_bytes());
+        hasher.update(&self.previous_hash);
+
+        // Appended only for revocations, so distribution records hash as before
+        if let Some(reason) = &self.revocation_reason {
+            hasher.update(&(reason.len() as u64).to_le_bytes());
+            hasher.update(reason.as_bytes());
+        }
+
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Check the record's hash and the node's signature over it
+    pub fn verify(&self, node_public_key: &[u8]) -> Result<()> {
+        if self.compute_hash() != self.record_hash {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Audit record {} does not match its hash",
+                self.sequence
+            )));
+        }
+
+        if !verify_signature_or_false(node_public_key, &self.record_hash, &self.signature) {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Audit record {} has an invalid signature",
+                self.sequence
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that consecutive records are individually valid and chained
+///
+/// `records` may be any contiguous range of a log, such as one returned by
+/// [`AuditLog::export`]; the first record's link to its predecessor is not
+/// checked.
+pub fn verify_audit_records(records: &[AuditRecord], node_public_key: &[u8]) -> Result<()> {
+    for record in records {
+        record.verify(node_public_key)?;
+    }
+
+    for pair in records.windows(2) {
+        let (previous, record) = (&pair[0], &pair[1]);
+        if record.sequence != previous.sequence + 1 || record.previous_hash != previous.record_hash
+        {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Audit record {} does not follow record {}",
+                record.sequence, previous.sequence
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Records and the receipts they cite
+#[derive(Debug, Default)]
+struct AuditChain {
+    /// Records, oldest first
+    records: Vec<AuditRecord>,
+
+    /// Receipts cited by any record
+    cited_receipts: HashSet<[u8; 32]>,
+}
+
+impl AuditChain {
+    fn push(&mut self, record: AuditRecord) {
+        self.cited_receipts
+            .extend(record.receipt_hashes.iter().copied());
+        self.records.push(record);
+    }
+}
+
+/// Append-only, signed log of reward distributions
+pub struct AuditLog {
+    /// Node's operational SPHINCS+ public key
+    public_key: Vec<u8>,
+
+    /// Node's operational SPHINCS+ secret key
+    secret_key: Vec<u8>,
+
+    /// The log
+    chain: RwLock<AuditChain>,
+
+    /// Durable copy of the log (None = in memory only)
+    store: Option<Arc<dyn RewardStore>>,
+}
+
+impl AuditLog {
+    /// Create an empty log signed with the node's operational key pair
+    pub fn new(public_key: Vec<u8>, secret_key: Vec<u8>) -> Self {
+        Self {
+            public_key,
+            secret_key,
+            chain: RwLock::new(AuditChain::default()),
+            store: None,
+        }
+    }
+
+    /// Create a log backed by a reward store, reloading the records saved there
+    ///
+    /// The reloaded records are verified, so a log that was tampered with in
+    /// the store is refused rather than extended.
+    pub fn with_store(
+        public_key: Vec<u8>,
+        secret_key: Vec<u8>,
+        store: Arc<dyn RewardStore>,
+    ) -> Result<Self> {
+        let mut log = Self::new(public_key, secret_key);
+
+        let mut chain = AuditChain::default();
+        for record in store.load_audit_records()? {
+            chain.push(record);
+        }
+        log.chain = RwLock::new(chain);
+        log.verify()?;
+        log.store = Some(store);
+
+        Ok(log)
+    }
+
+    /// Node's public key, with which the records are verified
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Append a record of a distribution, signed and chained to the last record
+    pub fn append(
+        &self,
+        vault_id: &str,
+        content_hash: [u8; 32],
+        amounts: BTreeMap<String, u64>,
+        receipt_hashes: Vec<[u8; 32]>,
+        recorded_at: u64,
+    ) -> Result<AuditRecord> {
+        self.append_record(
+            vault_id,
+            content_hash,
+            amounts,
+            receipt_hashes,
+            None,
+            recorded_at,
+        )
+    }
+
+    /// Append a record of a vault revoked before it was paid out
+    ///
+    /// Nothing was paid and no receipt is cited, so the record has no amounts
+    /// or receipt hashes and a zero content hash.
+    pub fn append_revocation(
+        &self,
+        vault_id: &str,
+        reason: &str,
+        recorded_at: u64,
+    ) -> Result<AuditRecord> {
+        self.append_record(
+            vault_id,
+            [0u8; 32],
+            BTreeMap::new(),
+            Vec::new(),
+            Some(reason.to_string()),
+            recorded_at,
+        )
+    }
+
+    fn append_record(
+        &self,
+        vault_id: &str,
+        content_hash: [u8; 32],
+        amounts: BTreeMap<String, u64>,
+        receipt_hashes: Vec<[u8; 32]>,
+        revocation_reason: Option<String>,
+        recorded_at: u64,
+    ) -> Result<AuditRecord> {
+        let mut chain = self.chain.write().map_err(|_| StorageNodeError::Internal)?;
+
+        let (sequence, previous_hash) = match chain.records.last() {
+            Some(last) => (last.sequence + 1, last.record_hash),
+            None => (0, AUDIT_GENESIS_HASH),
+        };
+        let mut record = AuditRecord {
+            sequence,
+            vault_id: vault_id.to_string(),
+            content_hash,
+            amounts,
+            receipt_hashes,
+            revocation_reason,
+            recorded_at,
+            previous_hash,
+            record_hash: [0u8; 32],
+            signature: Vec::new(),
+        };
+        record.record_hash = record.compute_hash();
+        record.signature = sign_with_node_key(&self.secret_key, &record.record_hash)?;
+
+        if let Some(store) = &self.store {
+            store.save_audit_record(&record)?;
+        }
+        chain.push(record.clone());
+
+        Ok(record)
+    }
+
+    /// Whether a receipt has been cited by a recorded distribution
+    pub fn is_cited(&self, receipt_hash: &[u8; 32]) -> Result<bool> {
+        Ok(self
+            .chain
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .cited_receipts
+            .contains(receipt_hash))
+    }
+
+    /// Number of records in the log
+    pub fn len(&self) -> Result<usize> {
+        Ok(self
+            .chain
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .records
+            .len())
+    }
+
+    /// Whether the log has no records
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Records whose sequence numbers fall in `range`, oldest first
+    pub fn export(&self, range: Range<u64>) -> Result<Vec<AuditRecord>> {
+        let chain = self.chain.read().map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(chain
+            .records
+            .iter()
+            .filter(|record| range.contains(&record.sequence))
+            .cloned()
+            .collect())
+    }
+
+    /// Re-check every record's hash and signature and the chain linking them
+    pub fn verify(&self) -> Result<()> {
+        let chain = self.chain.read().map_err(|_| StorageNodeError::Internal)?;
+
+        if let Some(first) = chain.records.first() {
+            if first.sequence != 0 || first.previous_hash != AUDIT_GENESIS_HASH {
+                return Err(StorageNodeError::InvalidState(
+                    "Audit log does not start at its first record".to_string(),
+                ));
+            }
+        }
+
+        verify_audit_records(&chain.records, &self.public_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking::reward_store::SqliteRewardStore;
+    use dsm::crypto::sphincs;
+    use std::sync::OnceLock;
+
+    /// Node's SPHINCS+ key pair, generated once
+    fn node_keys() -> &'static (Vec<u8>, Vec<u8>) {
+        static KEYS: OnceLock<(Vec<u8>, Vec<u8>)> = OnceLock::new();
+        KEYS.get_or_init(|| sphincs::generate_sphincs_keypair().unwrap())
+    }
+
+    fn audit_log() -> AuditLog {
+        let (public_key, secret_key) = node_keys().clone();
+        AuditLog::new(public_key, secret_key)
+    }
+
+    fn append_distribution(log: &AuditLog, index: u8) -> AuditRecord {
+        let amounts = BTreeMap::from([
+            ("node-1".to_string(), 600 + index as u64),
+            ("node-2".to_string(), 400),
+        ]);
+        log.append(
+            &format!("vault-{}", index),
+            [index; 32],
+            amounts,
+            vec![[index.wrapping_add(100); 32]],
+            1_000 + index as u64,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_records_are_chained_and_signed() {
+        let log = audit_log();
+        let first = append_distribution(&log, 0);
+        let second = append_distribution(&log, 1);
+
+        assert_eq!(first.sequence, 0);
+        assert_eq!(first.previous_hash, AUDIT_GENESIS_HASH);
+        assert_eq!(second.sequence, 1);
+        assert_eq!(second.previous_hash, first.record_hash);
+        assert!(log.is_cited(&[100; 32]).unwrap());
+        assert!(!log.is_cited(&[102; 32]).unwrap());
+        log.verify().unwrap();
+    }
+
+    #[test]
+    fn test_revocation_recorded_in_chain() {
+        let log = audit_log();
+        let first = append_distribution(&log, 0);
+        let revocation = log
+            .append_revocation("vault-1", "fraudulent receipts", 1_001)
+            .unwrap();
+
+        assert_eq!(revocation.previous_hash, first.record_hash);
+        assert_eq!(
+            revocation.revocation_reason.as_deref(),
+            Some("fraudulent receipts")
+        );
+        assert!(revocation.amounts.is_empty());
+        log.verify().unwrap();
+
+        // The reason is covered by the signed hash
+        let mut altered = revocation;
+        altered.revocation_reason = Some("routine".to_string());
+        assert!(altered.verify(log.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_records_fail_verification() {
+        let log = audit_log();
+        for index in 0..3 {
+            append_distribution(&log, index);
+        }
+        let (public_key, _) = node_keys();
+        let records = log.export(0..3).unwrap();
+
+        // Altered amounts no longer match the signed hash
+        let mut altered = records.clone();
+        altered[1].amounts.insert("node-1".to_string(), 1_000_000);
+        assert!(verify_audit_records(&altered, public_key).is_err());
+
+        // A re-hashed record is not signed by the node
+        altered[1].record_hash = altered[1].compute_hash();
+        assert!(verify_audit_records(&altered, public_key).is_err());
+
+        // Dropping a record breaks the chain
+        let gapped = vec![records[0].clone(), records[2].clone()];
+        assert!(verify_audit_records(&gapped, public_key).is_err());
+
+        // Records verify only against the node's key
+        let (other_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+        assert!(verify_audit_records(&records, &other_key).is_err());
+        verify_audit_records(&records, public_key).unwrap();
+    }
+
+    #[test]
+    fn test_export_range_verifies_independently() {
+        let log = audit_log();
+        for index in 0..5 {
+            append_distribution(&log, index);
+        }
+
+        let exported = log.export(2..4).unwrap();
+        let sequences: Vec<u64> = exported.iter().map(|record| record.sequence).collect();
+        assert_eq!(sequences, vec![2, 3]);
+        verify_audit_records(&exported, log.public_key()).unwrap();
+
+        assert!(log.export(5..10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_log_survives_restart() {
+        let store: Arc<dyn RewardStore> = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let (public_key, secret_key) = node_keys().clone();
+
+        let last = {
+            let log = AuditLog::with_store(public_key.clone(), secret_key.clone(), store.clone())
+                .unwrap();
+            append_distribution(&log, 0);
+            append_distribution(&log, 1)
+        };
+
+        let log = AuditLog::with_store(public_key, secret_key, store).unwrap();
+        assert_eq!(log.len().unwrap(), 2);
+        assert!(log.is_cited(&[101; 32]).unwrap());
+
+        // New records extend the reloaded chain
+        let next = append_distribution(&log, 2);
+        assert_eq!(next.previous_hash, last.record_hash);
+        log.verify().unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+// Proof-of-Storage Challenges for DSM Storage Node
+//
+// Storage metrics in receipts are self-reported, so nodes are periodically
+// challenged to prove they still hold the data they are paid for. A challenge
+// names a random byte range of a stored entry and a fresh nonce; the node
+// answers with the blake3 hash of the nonce and that range before the
+// challenge's deadline. Every answer, and every challenge left unanswered past
+// its deadline, is recorded with the reward vault manager, where the node's
+// pass rate over a period scales its rewards.
+
+use crate::client::platform::now_secs;
+use crate::error::{Result, StorageNodeError};
+use crate::staking::reward_store::RewardStore;
+use crate::staking::rewards::RewardVaultManager;
+use crate::storage::StorageEngine;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use rand::{rngs::OsRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Domain separator of challenge proofs
+const CHALLENGE_PROOF_DOMAIN: &[u8] = b"DSM_STORAGE_CHALLENGE";
+
+/// Largest byte range a challenge asks a node to prove
+pub const MAX_CHALLENGE_LENGTH: usize = 4096;
+
+/// Default time a node has to answer a challenge (5 minutes)
+pub const DEFAULT_CHALLENGE_RESPONSE_WINDOW: u64 = 300;
+
+/// Request for a node to prove it holds a byte range of a stored entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Challenge {
+    /// Unique challenge ID
+    pub challenge_id: String,
+
+    /// Node being challenged
+    pub node_id: String,
+
+    /// Blinded ID of the challenged entry
+    pub data_key: String,
+
+    /// Start of the challenged range within the entry's payload
+    pub offset: usize,
+
+    /// Length of the challenged range
+    pub length: usize,
+
+    /// Fresh nonce the proof must cover, so proofs cannot be precomputed
+    pub nonce: [u8; 32],
+
+    /// When the challenge was issued (Unix seconds)
+    pub issued_at: u64,
+
+    /// Last second at which an answer is accepted
+    pub deadline: u64,
+}
+
+impl Challenge {
+    /// Compute the proof answering this challenge from the entry's payload
+    pub fn prove(&self, payload: &[u8]) -> Result<[u8; 32]> {
+        let range = self
+            .offset
+            .checked_add(self.length)
+            .and_then(|end| payload.get(self.offset..end))
+            .ok_or_else(|| {
+                StorageNodeError::InvalidInput(format!(
+                    "Challenged range {}+{} exceeds the {} byte payload of {}",
+                    self.offset,
+                    self.length,
+                    payload.len(),
+                    self.data_key
+                ))
+            })?;
+
+        let mut hasher = ::blake3::Hasher::new();
+        hasher.update(CHALLENGE_PROOF_DOMAIN);
+        hasher.update(&self.nonce);
+        hasher.update(range);
+        Ok(*hasher.finalize().as_bytes())
+    }
+}
+
+/// A challenge waiting for the node's answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutstandingChallenge {
+    /// The challenge sent to the node
+    pub challenge: Challenge,
+
+    /// Proof computed from the issuer's copy of the entry
+    pub expected_proof: [u8; 32],
+}
+
+/// Issues storage challenges and records their outcome
+pub struct ChallengeIssuer {
+    /// Storage holding the issuer's copy of challenged entries
+    storage: Arc<dyn StorageEngine + Send + Sync>,
+
+    /// Reward vault manager the outcomes are recorded with
+    rewards: Arc<RewardVaultManager>,
+
+    /// Durable copy of the outstanding challenges (None = in memory only)
+    store: Option<Arc<dyn RewardStore>>,
+
+    /// Time a node has to answer a challenge (seconds)
+    response_window: u64,
+
+    /// Challenges waiting for an answer, by challenge ID
+    outstanding: RwLock<HashMap<String, OutstandingChallenge>>,
+}
+
+impl ChallengeIssuer {
+    /// Create an issuer that keeps outstanding challenges in memory only
+    pub fn new(
+        storage: Arc<dyn StorageEngine + Send + Sync>,
+        rewards: Arc<RewardVaultManager>,
+        response_window: u64,
+    ) -> Self {
+        Self {
+            storage,
+            rewards,
+            store: None,
+            response_window,
+            outstanding: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create an issuer that persists outstanding challenges
+    ///
+    /// Challenges still outstanding when the store was last written are
+    /// restored, so a restart neither forgets nor forgives them.
+    pub fn with_store(
+        storage: Arc<dyn StorageEngine + Send + Sync>,
+        rewards: Arc<RewardVaultManager>,
+        store: Arc<dyn RewardStore>,
+        response_window: u64,
+    ) -> Result<Self> {
+        let outstanding = store
+            .load_outstanding_challenges()?
+            .into_iter()
+            .map(|o| (o.challenge.challenge_id.clone(), o))
+            .collect();
+
+        Ok(Self {
+            storage,
+            rewards,
+            store: Some(store),
+            response_window,
+            outstanding: RwLock::new(outstanding),
+        })
+    }
+
+    /// Challenge a node to prove it holds a random range of a stored entry
+    pub async fn issue_challenge(&self, node_id: &str, data_key: &str) -> Result<Challenge> {
+        let entry = self.storage.retrieve(data_key).await?.ok_or_else(|| {
+            StorageNodeError::NotFound(format!("Cannot challenge missing entry {}", data_key))
+        })?;
+        let payload = &entry.encrypted_payload;
+        if payload.is_empty() {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Cannot challenge empty entry {}",
+                data_key
+            )));
+        }
+
+        let mut rng = OsRng;
+        let length = rng.gen_range(1..=payload.len().min(MAX_CHALLENGE_LENGTH));
+        let offset = rng.gen_range(0..=payload.len() - length);
+        let mut nonce = [0u8; 32];
+        rng.fill_bytes(&mut nonce);
+
+        let issued_at = now_secs();
+        let challenge = Challenge {
+            challenge_id: uuid::Uuid::new_v4().to_string(),
+            node_id: node_id.to_string(),
+            data_key: data_key.to_string(),
+            offset,
+            length,
+            nonce,
+            issued_at,
+            deadline: issued_at.saturating_add(self.response_window),
+        };
+        let outstanding = OutstandingChallenge {
+            expected_proof: challenge.prove(payload)?,
+            challenge: challenge.clone(),
+        };
+
+        if let Some(store) = &self.store {
+            store.save_outstanding_challenge(&outstanding)?;
+        }
+        self.outstanding
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .insert(challenge.challenge_id.clone(), outstanding);
+
+        info!(
+            "Challenged node {} on {} bytes of {} until {}",
+            node_id, length, data_key, challenge.deadline
+        );
+
+        Ok(challenge)
+    }
+
+    /// Verify a node's answer to a challenge and record whether it passed
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether the proof was correct and answered in time
+    pub fn record_challenge_response(&self, challenge_id: &str, proof: &[u8; 32]) -> Result<bool> {
+        self.record_challenge_response_at(challenge_id, proof, now_secs())
+    }
+
+    /// Verify an answer to a challenge received at `now`
+    pub fn record_challenge_response_at(
+        &self,
+        challenge_id: &str,
+        proof: &[u8; 32],
+        now: u64,
+    ) -> Result<bool> {
+        let mut outstanding = self
+            .outstanding
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let challenge = outstanding.get(challenge_id).ok_or_else(|| {
+            StorageNodeError::NotFound(format!("No outstanding challenge {}", challenge_id))
+        })?;
+
+        let in_time = now <= challenge.challenge.deadline;
+        let passed = in_time && *proof == challenge.expected_proof;
+        if !passed {
+            warn!(
+                "Node {} failed challenge {} ({})",
+                challenge.challenge.node_id,
+                challenge_id,
+                if in_time {
+                    "wrong proof"
+                } else {
+                    "late answer"
+                }
+            );
+        }
+
+        self.resolve(&mut outstanding, challenge_id, passed, now)?;
+        Ok(passed)
+    }
+
+    /// Fail every challenge whose deadline passed before `now`
+    ///
+    /// # Returns
+    /// * `Result<Vec<Challenge>>` - The challenges that timed out
+    pub fn expire_challenges(&self, now: u64) -> Result<Vec<Challenge>> {
+        let mut outstanding = self
+            .outstanding
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let expired: Vec<Challenge> = outstanding
+            .values()
+            .filter(|o| o.challenge.deadline < now)
+            .map(|o| o.challenge.clone())
+            .collect();
+
+        for challenge in &expired {
+            warn!(
+                "Node {} did not answer challenge {} in time",
+                challenge.node_id, challenge.challenge_id
+            );
+            self.resolve(
+                &mut outstanding,
+                &challenge.challenge_id,
+                false,
+                challenge.deadline,
+            )?;
+        }
+
+        Ok(expired)
+    }
+
+    /// Challenges still waiting for an answer
+    pub fn outstanding_challenges(&self) -> Result<Vec<Challenge>> {
+        let outstanding = self
+            .outstanding
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(outstanding.values().map(|o| o.challenge.clone()).collect())
+    }
+
+    /// Record a challenge's outcome and stop tracking it
+    fn resolve(
+        &self,
+        outstanding: &mut HashMap<String, OutstandingChallenge>,
+        challenge_id: &str,
+        passed: bool,
+        timestamp: u64,
+    ) -> Result<()> {
+        let Some(challenge) = outstanding.get(challenge_id) else {
+            return Ok(());
+        };
+
+        self.rewards
+            .record_challenge_result(&challenge.challenge.node_id, passed, timestamp)?;
+        if let Some(store) = &self.store {
+            store.remove_outstanding_challenge(challenge_id)?;
+        }
+        outstanding.remove(challenge_id);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking::reward_store::SqliteRewardStore;
+    use crate::staking::rewards::RewardManagerConfig;
+    use crate::storage::{MemoryStorage, MemoryStorageConfig};
+    use crate::types::BlindedStateEntry;
+    use dsm::vault::DLVManager;
+
+    const DATA_KEY: &str = "entry-1";
+
+    async fn storage() -> (Arc<dyn StorageEngine + Send + Sync>, Vec<u8>) {
+        let storage: Arc<dyn StorageEngine + Send + Sync> =
+            Arc::new(MemoryStorage::new(MemoryStorageConfig::default()));
+        let payload: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        storage
+            .store(BlindedStateEntry {
+                blinded_id: DATA_KEY.to_string(),
+                encrypted_payload: payload.clone(),
+                timestamp: now_secs(),
+                ttl: 0,
+                region: "global".to_string(),
+                priority: 0,
+                proof_hash: [0u8; 32],
+                metadata: HashMap::new(),
+            })
+            .await
+            .unwrap();
+        (storage, payload)
+    }
+
+    fn rewards() -> Arc<RewardVaultManager> {
+        Arc::new(RewardVaultManager::new(
+            Arc::new(DLVManager::new()),
+            RewardManagerConfig::default(),
+        ))
+    }
+
+    fn summary(rewards: &RewardVaultManager) -> (u64, u64) {
+        let summary = rewards.challenge_summary("node-1", 0, u64::MAX).unwrap();
+        (summary.passed, summary.failed)
+    }
+
+    #[tokio::test]
+    async fn test_valid_proof_passes() {
+        let (storage, payload) = storage().await;
+        let rewards = rewards();
+        let issuer = ChallengeIssuer::new(storage, rewards.clone(), 60);
+
+        let challenge = issuer.issue_challenge("node-1", DATA_KEY).await.unwrap();
+        assert!(challenge.offset + challenge.length <= payload.len());
+        assert!(challenge.length <= MAX_CHALLENGE_LENGTH);
+
+        let proof = challenge.prove(&payload).unwrap();
+        assert!(issuer
+            .record_challenge_response(&challenge.challenge_id, &proof)
+            .unwrap());
+        assert_eq!(summary(&rewards), (1, 0));
+        assert!(issuer.outstanding_challenges().unwrap().is_empty());
+
+        // A challenge is answered only once
+        assert!(issuer
+            .record_challenge_response(&challenge.challenge_id, &proof)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wrong_or_late_proof_fails() {
+        let (storage, payload) = storage().await;
+        let rewards = rewards();
+        let issuer = ChallengeIssuer::new(storage, rewards.clone(), 60);
+
+        let wrong = issuer.issue_challenge("node-1", DATA_KEY).await.unwrap();
+        assert!(!issuer
+            .record_challenge_response(&wrong.challenge_id, &[0u8; 32])
+            .unwrap());
+
+        let late = issuer.issue_challenge("node-1", DATA_KEY).await.unwrap();
+        let proof = late.prove(&payload).unwrap();
+        let passed = issuer
+            .record_challenge_response_at(&late.challenge_id, &proof, late.deadline + 1)
+            .unwrap();
+        assert!(!passed);
+
+        assert_eq!(summary(&rewards), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_challenges_time_out() {
+        let (storage, _) = storage().await;
+        let rewards = rewards();
+        let issuer = ChallengeIssuer::new(storage, rewards.clone(), 60);
+
+        let challenge = issuer.issue_challenge("node-1", DATA_KEY).await.unwrap();
+        assert!(issuer
+            .expire_challenges(challenge.deadline)
+            .unwrap()
+            .is_empty());
+
+        let expired = issuer.expire_challenges(challenge.deadline + 1).unwrap();
+        assert_eq!(expired, vec![challenge]);
+        assert!(issuer.outstanding_challenges().unwrap().is_empty());
+        assert_eq!(summary(&rewards), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_outstanding_challenges_survive_restart() {
+        let (storage, payload) = storage().await;
+        let rewards = rewards();
+        let store: Arc<dyn RewardStore> = Arc::new(SqliteRewardStore::in_memory().unwrap());
+
+        let issuer =
+            ChallengeIssuer::with_store(storage.clone(), rewards.clone(), store.clone(), 60)
+                .unwrap();
+        let challenge = issuer.issue_challenge("node-1", DATA_KEY).await.unwrap();
+        drop(issuer);
+
+        let restarted =
+            ChallengeIssuer::with_store(storage, rewards.clone(), store.clone(), 60).unwrap();
+        assert_eq!(
+            restarted.outstanding_challenges().unwrap(),
+            vec![challenge.clone()]
+        );
+
+        let proof = challenge.prove(&payload).unwrap();
+        assert!(restarted
+            .record_challenge_response(&challenge.challenge_id, &proof)
+            .unwrap());
+        assert!(store.load_outstanding_challenges().unwrap().is_empty());
+        assert_eq!(summary(&rewards), (1, 0));
+    }
+}
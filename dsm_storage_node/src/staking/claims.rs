@@ -0,0 +1,79 @@
+// Reward Claims for DSM Storage Node
+//
+// Instead of having the reward vault manager pay a whole vault at once, a
+// vault created for pull claims lets each recipient claim its own share once
+// the vault unlocks. A claim is signed with the key registered for the node
+// through its identity registration, and returns a voucher the node turns into
+// its own transfer.
+
+use dsm::types::operations::{Operation, TransactionMode, VerificationType};
+use dsm::types::state_types::State;
+use dsm::types::token_types::Balance;
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of reward claim signatures
+const REWARD_CLAIM_DOMAIN: &[u8] = b"DSM_REWARD_CLAIM";
+
+/// Hash a node signs to claim its share of a vault
+pub fn claim_signing_hash(vault_id: &str, node_id: &str, reference_state: &State) -> [u8; 32] {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(REWARD_CLAIM_DOMAIN);
+    for field in [
+        vault_id.as_bytes(),
+        node_id.as_bytes(),
+        &reference_state.hash,
+    ] {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field);
+    }
+
+    *hasher.finalize().as_bytes()
+}
+
+/// A node's claimed share of a reward vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimVoucher {
+    /// Vault the share was claimed from
+    pub vault_id: String,
+
+    /// Node that claimed the share
+    pub node_id: String,
+
+    /// Token the share is paid in
+    pub token_id: String,
+
+    /// Amount of the share
+    pub amount: u64,
+
+    /// Hash of the reference state the claim was made against
+    pub reference_state_hash: Vec<u8>,
+
+    /// When the share was claimed
+    pub claimed_at: u64,
+
+    /// Node's signature over the claim
+    pub node_signature: Vec<u8>,
+}
+
+impl ClaimVoucher {
+    /// Transfer paying the share to the node, to execute with the TokenSDK
+    ///
+    /// The nonce is derived from the claim, so executing the voucher twice
+    /// produces the same operation.
+    pub fn transfer_operation(&self) -> Operation {
+        let nonce = ::blake3::hash(&self.node_signature).as_bytes().to_vec();
+
+        Operation::Transfer {
+            to_address: self.node_id.clone(),
+            amount: Balance::new(self.amount),
+            token_id: self.token_id.clone(),
+            mode: TransactionMode::Unilateral,
+            nonce,
+            verification: VerificationType::Standard,
+            pre_commit: None,
+            recipient: self.node_id.clone(),
+            to: self.node_id.clone(),
+            message: format!("Reward claim from vault {}", self.vault_id),
+        }
+    }
+}
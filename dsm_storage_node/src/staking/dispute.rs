@@ -0,0 +1,152 @@
+// Distribution Disputes for DSM Storage Node
+//
+// When a reward vault's distribution time arrives, the reward vault manager
+// publishes the split it is about to pay as a proposed distribution and holds
+// the vault locked for the vault's dispute window. A node that disagrees with
+// the split files a dispute signed with the key registered for it through its
+// identity registration; the first dispute freezes the distribution until an
+// operator resolves it.
+
+use crate::crypto::{sign_with_node_key, verify_signature_or_false};
+use crate::error::{Result, StorageNodeError};
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of dispute claim signatures
+const DISPUTE_CLAIM_DOMAIN: &[u8] = b"DSM_DISTRIBUTION_DISPUTE";
+
+/// Split of a reward vault published before the vault is unlocked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposedDistribution {
+    /// Vault ID to distribute
+    pub vault_id: String,
+
+    /// Amount to pay each recipient (node_id -> amount)
+    pub amounts: HashMap<String, u64>,
+
+    /// Hashes of the receipts justifying each recipient's amount, by node ID
+    pub receipt_hashes: BTreeMap<String, Vec<[u8; 32]>>,
+
+    /// When the split was published
+    pub proposed_at: u64,
+
+    /// End of the dispute window; the vault is unlocked from then on
+    pub dispute_deadline: u64,
+}
+
+/// A node's signed objection to a proposed distribution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisputeClaim {
+    /// Vault whose proposed distribution is disputed
+    pub vault_id: String,
+
+    /// Node filing the dispute
+    pub node_id: String,
+
+    /// Amount the node claims it is owed
+    pub claimed_amount: u64,
+
+    /// Why the node disputes the split
+    pub reason: String,
+
+    /// Node's SPHINCS+ public key, as registered for its identity
+    pub node_public_key: Vec<u8>,
+
+    /// Node's signature over the claim
+    pub signature: Vec<u8>,
+}
+
+impl DisputeClaim {
+    /// Create an unsigned claim
+    pub fn new(
+        vault_id: &str,
+        node_id: &str,
+        claimed_amount: u64,
+        reason: &str,
+        node_public_key: Vec<u8>,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.to_string(),
+            node_id: node_id.to_string(),
+            claimed_amount,
+            reason: reason.to_string(),
+            node_public_key,
+            signature: Vec::new(),
+        }
+    }
+
+    /// Hash of the claim that the node signs
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut hasher = ::blake3::Hasher::new();
+        hasher.update(DISPUTE_CLAIM_DOMAIN);
+        for field in [
+            self.vault_id.as_bytes(),
+            self.node_id.as_bytes(),
+            self.reason.as_bytes(),
+            &self.node_public_key,
+        ] {
+            hasher.update(&(field.len() as u64).to_le_bytes());
+            hasher.update(field);
+        }
+        hasher.update(&self.claimed_amount.to_le_bytes());
+
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Sign the claim with the node's SPHINCS+ secret key
+    pub fn sign(&mut self, secret_key: &[u8]) -> Result<()> {
+        self.signature = sign_with_node_key(secret_key, &self.signing_hash())?;
+        Ok(())
+    }
+
+    /// Check the signature against the claim's public key
+    pub fn verify(&self) -> Result<()> {
+        if !verify_signature_or_false(&self.node_public_key, &self.signing_hash(), &self.signature)
+        {
+            return Err(StorageNodeError::Authentication(format!(
+                "Invalid dispute signature from node {}",
+                self.node_id
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// How an operator settled the disputes of a vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisputeResolution {
+    /// Pay the proposed split
+    Proceed,
+
+    /// Pay these amounts (node_id -> amount) instead of the proposed split
+    Amend { amounts: HashMap<String, u64> },
+
+    /// Do not distribute the vault
+    Cancel { reason: String },
+}
+
+/// A dispute filed against a proposed distribution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dispute {
+    /// The node's claim
+    pub claim: DisputeClaim,
+
+    /// When the dispute was filed
+    pub filed_at: u64,
+
+    /// How the dispute was settled, once it is
+    pub resolution: Option<DisputeResolution>,
+
+    /// When the dispute was settled
+    pub resolved_at: Option<u64>,
+}
+
+impl Dispute {
+    /// Whether the dispute still waits for an operator
+    pub fn is_open(&self) -> bool {
+        self.resolution.is_none()
+    }
+}
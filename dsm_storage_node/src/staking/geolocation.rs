@@ -0,0 +1,214 @@
+// Region Proofs for DSM Storage Node
+//
+// Rate schedules pay more for service in some regions, so a node claiming a
+// region must prove it serves from there. A trusted geolocation oracle either
+// signs the region its lookup found for the node's IP address, or signs the
+// round-trip times its latency prover measured from reference hosts to the
+// node. Region multipliers only apply to regions proven this way.
+
+use crate::crypto::verify_signature_or_false;
+
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of region proof signatures
+const REGION_PROOF_DOMAIN: &[u8] = b"DSM_REGION_PROOF";
+
+/// Proof that a node serves from a region
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RegionProof {
+    /// Region code the proof is for, as used in rate schedules
+    pub region_code: String,
+
+    /// How the region was established
+    pub proof_type: RegionProofType,
+}
+
+/// How a node's region was established
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RegionProofType {
+    /// The oracle's geolocation lookup of the node's IP address
+    IpGeolocation {
+        /// Address the node serves from
+        ip: IpAddr,
+
+        /// Oracle's signature over the IP address and region
+        lookup_signature: Vec<u8>,
+    },
+
+    /// Round-trip times from reference hosts in the region to the node
+    LatencyProof {
+        /// Reference hosts the node was probed from
+        reference_ips: Vec<IpAddr>,
+
+        /// Measured round-trip time from each reference host (milliseconds)
+        measured_rtts_ms: Vec<u32>,
+
+        /// Latency prover's signature over the region and measurements,
+        /// made with the oracle's key
+        prover_signature: Vec<u8>,
+    },
+}
+
+/// A region a node claims, with the proof that it serves from there
+pub type VerifiedRegion = (String, RegionProof);
+
+/// Hash the oracle signs to attest a proof's region
+///
+/// Covers the region code and the IP address or measurements of the proof,
+/// but not its signature.
+pub fn region_proof_signing_hash(proof: &RegionProof) -> [u8; 32] {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(REGION_PROOF_DOMAIN);
+    hasher.update(&(proof.region_code.len() as u64).to_le_bytes());
+    hasher.update(proof.region_code.as_bytes());
+
+    match &proof.proof_type {
+        RegionProofType::IpGeolocation { ip, .. } => {
+            hasher.update(&[0]);
+            hash_ip(&mut hasher, ip);
+        }
+        RegionProofType::LatencyProof {
+            reference_ips,
+            measured_rtts_ms,
+            ..
+        } => {
+            hasher.update(&[1]);
+            hasher.update(&(reference_ips.len() as u64).to_le_bytes());
+            for ip in reference_ips {
+                hash_ip(&mut hasher, ip);
+            }
+            hasher.update(&(measured_rtts_ms.len() as u64).to_le_bytes());
+            for rtt in measured_rtts_ms {
+                hasher.update(&rtt.to_le_bytes());
+            }
+        }
+    }
+
+    *hasher.finalize().as_bytes()
+}
+
+/// Hash an IP address with its family, so IPv4 and IPv6 cannot collide
+fn hash_ip(hasher: &mut ::blake3::Hasher, ip: &IpAddr) {
+    match ip {
+        IpAddr::V4(ip) => {
+            hasher.update(&[4]);
+            hasher.update(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            hasher.update(&[6]);
+            hasher.update(&ip.octets());
+        }
+    }
+}
+
+/// Check a region proof against the oracle's SPHINCS+ public key
+///
+/// A latency proof must hold one round-trip time for each of at least one
+/// reference host.
+pub fn verify_region_proof(proof: &RegionProof, oracle_public_key: &[u8]) -> bool {
+    let signature = match &proof.proof_type {
+        RegionProofType::IpGeolocation {
+            lookup_signature, ..
+        } => lookup_signature,
+        RegionProofType::LatencyProof {
+            reference_ips,
+            measured_rtts_ms,
+            prover_signature,
+        } => {
+            if reference_ips.is_empty() || reference_ips.len() != measured_rtts_ms.len() {
+                return false;
+            }
+            prover_signature
+        }
+    };
+
+    let hash = region_proof_signing_hash(proof);
+    verify_signature_or_false(oracle_public_key, &hash, signature)
+}
+
+/// Whether a claimed region is the one its proof is for, and the proof holds
+pub fn is_region_proven(region: &VerifiedRegion, oracle_public_key: &[u8]) -> bool {
+    let (region_code, proof) = region;
+    *region_code == proof.region_code && verify_region_proof(proof, oracle_public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::sign_with_node_key;
+    use dsm::crypto::sphincs;
+    use std::net::Ipv4Addr;
+
+    fn signed(mut proof: RegionProof, oracle_secret: &[u8]) -> RegionProof {
+        let signature =
+            sign_with_node_key(oracle_secret, &region_proof_signing_hash(&proof)).unwrap();
+        match &mut proof.proof_type {
+            RegionProofType::IpGeolocation {
+                lookup_signature, ..
+            } => *lookup_signature = signature,
+            RegionProofType::LatencyProof {
+                prover_signature, ..
+            } => *prover_signature = signature,
+        }
+        proof
+    }
+
+    #[test]
+    fn test_region_proofs_verify_against_the_oracle() {
+        let (oracle_key, oracle_secret) = sphincs::generate_sphincs_keypair().unwrap();
+        let (other_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+
+        let lookup = signed(
+            RegionProof {
+                region_code: "eu".to_string(),
+                proof_type: RegionProofType::IpGeolocation {
+                    ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                    lookup_signature: Vec::new(),
+                },
+            },
+            &oracle_secret,
+        );
+        assert!(verify_region_proof(&lookup, &oracle_key));
+        assert!(!verify_region_proof(&lookup, &other_key));
+        let claim = |region: &str| (region.to_string(), lookup.clone());
+        assert!(is_region_proven(&claim("eu"), &oracle_key));
+
+        // A proof for one region does not prove another
+        assert!(!is_region_proven(&claim("us"), &oracle_key));
+        let mut relabelled = lookup;
+        relabelled.region_code = "us".to_string();
+        assert!(!verify_region_proof(&relabelled, &oracle_key));
+
+        let latency = signed(
+            RegionProof {
+                region_code: "ap".to_string(),
+                proof_type: RegionProofType::LatencyProof {
+                    reference_ips: vec![
+                        IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1)),
+                        IpAddr::V4(Ipv4Addr::new(198, 51, 100, 2)),
+                    ],
+                    measured_rtts_ms: vec![12, 18],
+                    prover_signature: Vec::new(),
+                },
+            },
+            &oracle_secret,
+        );
+        assert!(verify_region_proof(&latency, &oracle_key));
+
+        // Measurements without a reference host for each are refused
+        let unpaired = signed(
+            RegionProof {
+                region_code: "ap".to_string(),
+                proof_type: RegionProofType::LatencyProof {
+                    reference_ips: vec![IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1))],
+                    measured_rtts_ms: vec![12, 18],
+                    prover_signature: Vec::new(),
+                },
+            },
+            &oracle_secret,
+        );
+        assert!(!verify_region_proof(&unpaired, &oracle_key));
+    }
+}
@@ -1 +1,728 @@
+// Governance Module for DSM Storage Node
+//
+// Changes to parameters shared by the storage node network (reward rates,
+// slashing policy and the set of governance voters) are made through
+// proposals. Each governance voter signs its vote with its SPHINCS+ key, and
+// a proposal is executed once a strict majority of the voters registered when
+// it was created approve it before it expires. The signed votes are kept with
+// the proposal and checked again against the manager's governance keys when it
+// is executed, and the manager records executed proposals so that none is
+// applied twice.
 
+use crate::client::platform::now_secs;
+use crate::crypto::verify_signature_or_false;
+use crate::error::{Result, StorageNodeError};
+use crate::staking::rewards::{RateSchedule, Ratio, RewardVaultManager};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Domain separator for signed governance votes
+const VOTE_DOMAIN: &[u8] = b"DSM_GOVERNANCE_VOTE";
+
+/// Penalties applied to storage nodes that fail their obligations
+///
+/// Rewards are slashed by `max_slash` for a period in which a node's uptime
+/// falls below `min_uptime_percentage`, and otherwise according to the
+/// storage challenges it answered in the period, as set by
+/// `challenge_scoring`, up to `max_slash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashingPolicy {
+    /// Minimum uptime percentage (0-100) before a node is slashed
+    pub min_uptime_percentage: u8,
+
+    /// Missed storage proofs tolerated per period
+    pub max_missed_proofs: u32,
+
+    /// Share of the stake forfeited when a node is slashed
+    pub slash_ratio: Ratio,
+
+    /// Share of a period's reward deducted per failed storage challenge
+    pub challenge_failure_penalty: Ratio,
+
+    /// Largest share of a period's reward that can be slashed
+    pub max_slash: Ratio,
+
+    /// What happens to slashed rewards
+    #[serde(default)]
+    pub slashed_rewards: SlashedRewards,
+
+    /// How storage challenge results reduce a node's reward
+    #[serde(default)]
+    pub challenge_scoring: ChallengeScoring,
+}
+
+impl Default for SlashingPolicy {
+    fn default() -> Self {
+        Self {
+            min_uptime_percentage: 90,
+            max_missed_proofs: 3,
+            slash_ratio: Ratio::from_percentage(5),
+            challenge_failure_penalty: Ratio::from_percentage(10),
+            max_slash: Ratio::from_percentage(50),
+            slashed_rewards: SlashedRewards::default(),
+            challenge_scoring: ChallengeScoring::default(),
+        }
+    }
+}
+
+/// How storage challenge results reduce a node's reward for a period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeScoring {
+    /// `challenge_failure_penalty` is slashed for each failed challenge
+    #[default]
+    PerFailure,
+
+    /// The reward is scaled by the share of challenges passed, and slashed by
+    /// `max_slash` when that share falls below `floor`
+    PassRate {
+        /// Lowest pass rate that is scaled rather than slashed
+        floor: Ratio,
+    },
+}
+
+/// Destination of rewards slashed from non-compliant nodes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlashedRewards {
+    /// Slashed rewards are not paid to anyone
+    #[default]
+    Burn,
+
+    /// Slashed rewards are shared among the unslashed nodes of the period,
+    /// in proportion to their own rewards
+    Redistribute,
+}
+
+/// Change to shared parameters that a proposal would make
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GovernanceChange {
+    /// Replace the reward rate schedule
+    UpdateRateSchedule(RateSchedule),
+
+    /// Replace the slashing policy
+    UpdateSlashingPolicy(SlashingPolicy),
+
+    /// Register a governance voter
+    AddGovernanceKey {
+        /// Genesis hash identifying the voter
+        genesis_hash: String,
+
+        /// SPHINCS+ public key the voter signs votes with
+        public_key: Vec<u8>,
+    },
+
+    /// Remove a governance voter
+    RemoveGovernanceKey {
+        /// Genesis hash identifying the voter
+        genesis_hash: String,
+    },
+}
+
+/// Lifecycle of a proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProposalState {
+    /// Accepting votes
+    Open,
+
+    /// Approved and applied
+    Executed,
+
+    /// Closed without being applied
+    Rejected,
+
+    /// Voting period ended before the proposal was executed
+    Expired,
+}
+
+/// Governance voter's signed vote on a proposal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    /// Genesis hash of the voter
+    pub voter: String,
+
+    /// Whether the voter approved
+    pub approve: bool,
+
+    /// Voter's SPHINCS+ signature over the proposal's [`vote_message`]
+    pub signature: Vec<u8>,
+}
+
+/// Proposed change to shared parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    /// Proposal ID
+    pub id: String,
+
+    /// Change to apply if the proposal is approved
+    pub change: GovernanceChange,
+
+    /// Genesis hash of the proposer
+    pub proposer: String,
+
+    /// Signed votes, in the order they were cast
+    pub votes: Vec<Vote>,
+
+    /// Current state
+    pub state: ProposalState,
+
+    /// Expiration timestamp (seconds since epoch)
+    pub expires_at: u64,
+
+    /// Voters eligible to vote, by genesis hash, fixed when the proposal is created
+    pub electorate: HashMap<String, Vec<u8>>,
+}
+
+impl Proposal {
+    /// Create a proposal voted on by the manager's current governance voters
+    pub fn new(
+        id: &str,
+        change: GovernanceChange,
+        proposer: &str,
+        manager: &RewardVaultManager,
+        expires_at: u64,
+    ) -> Result<Self> {
+        let electorate = manager.governance_keys()?;
+        if !electorate.contains_key(proposer) {
+            return Err(StorageNodeError::Authentication(format!(
+                "{} is not a governance voter",
+                proposer
+            )));
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            change,
+            proposer: proposer.to_string(),
+            votes: Vec::new(),
+            state: ProposalState::Open,
+            expires_at,
+            electorate,
+        })
+    }
+
+    /// Number of voters eligible to vote on this proposal
+    pub fn total_voters(&self) -> usize {
+        self.electorate.len()
+    }
+
+    /// Number of recorded votes approving the proposal
+    pub fn approvals(&self) -> usize {
+        self.votes.iter().filter(|vote| vote.approve).count()
+    }
+
+    /// Whether a strict majority of the electorate approved, going by the
+    /// recorded votes
+    pub fn has_majority(&self) -> bool {
+        self.approvals() > self.total_voters() / 2
+    }
+
+    /// Whether the voting period has ended at the given time (seconds since epoch)
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at < now
+    }
+
+    fn has_voted(&self, voter_genesis_hash: &str) -> bool {
+        self.votes
+            .iter()
+            .any(|vote| vote.voter == voter_genesis_hash)
+    }
+}
+
+/// Message a voter signs to cast a vote on a proposal
+///
+/// Commits to the whole proposal body (its ID, change, proposer, expiry and
+/// electorate), so a vote cannot be moved to another proposal that reuses the
+/// ID.
+pub fn vote_message(proposal: &Proposal, approve: bool) -> Result<Vec<u8>> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(VOTE_DOMAIN);
+    for field in [
+        proposal.id.as_bytes(),
+        &change_bytes(&proposal.change)?,
+        proposal.proposer.as_bytes(),
+    ] {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field);
+    }
+    hasher.update(&proposal.expires_at.to_le_bytes());
+
+    // Sorted, as the electorate's iteration order differs between nodes
+    let electorate: BTreeMap<_, _> = proposal.electorate.iter().collect();
+    hasher.update(&(electorate.len() as u64).to_le_bytes());
+    for (genesis_hash, public_key) in electorate {
+        hasher.update(&(genesis_hash.len() as u64).to_le_bytes());
+        hasher.update(genesis_hash.as_bytes());
+        hasher.update(&(public_key.len() as u64).to_le_bytes());
+        hasher.update(public_key);
+    }
+
+    hasher.update(&[approve as u8]);
+    Ok(hasher.finalize().as_bytes().to_vec())
+}
+
+/// Encoding of a change that is the same on every node
+fn change_bytes(change: &GovernanceChange) -> Result<Vec<u8>> {
+    match change {
+        // Region multipliers are encoded sorted rather than in map order
+        GovernanceChange::UpdateRateSchedule(schedule) => {
+            let regions: BTreeMap<_, _> = schedule.region_multipliers.iter().collect();
+            let schedule = RateSchedule {
+                region_multipliers: HashMap::new(),
+                ..schedule.clone()
+            };
+            Ok(bincode::serialize(&(0u32, schedule, regions))?)
+        }
+        change => Ok(bincode::serialize(change)?),
+    }
+}
+
+/// Record a signed vote on an open proposal
+///
+/// The signature must be the voter's SPHINCS+ signature over
+/// [`vote_message`]. Each voter in the proposal's electorate may vote once.
+pub fn cast_vote(
+    proposal: &mut Proposal,
+    voter_genesis_hash: &str,
+    approve: bool,
+    signature: &[u8],
+) -> Result<()> {
+    if proposal.state != ProposalState::Open {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Proposal {} is {:?}",
+            proposal.id, proposal.state
+        )));
+    }
+
+    if proposal.is_expired_at(now_secs()) {
+        proposal.state = ProposalState::Expired;
+        return Err(StorageNodeError::Expired(format!(
+            "Proposal {} expired at {}",
+            proposal.id, proposal.expires_at
+        )));
+    }
+
+    let public_key = proposal.electorate.get(voter_genesis_hash).ok_or_else(|| {
+        StorageNodeError::Authentication(format!(
+            "{} may not vote on proposal {}",
+            voter_genesis_hash, proposal.id
+        ))
+    })?;
+
+    if proposal.has_voted(voter_genesis_hash) {
+        return Err(StorageNodeError::InvalidState(format!(
+            "{} has already voted on proposal {}",
+            voter_genesis_hash, proposal.id
+        )));
+    }
+
+    let message = vote_message(proposal, approve)?;
+    if !verify_signature_or_false(public_key, &message, signature) {
+        return Err(StorageNodeError::Authentication(format!(
+            "Invalid vote signature from {}",
+            voter_genesis_hash
+        )));
+    }
+
+    proposal.votes.push(Vote {
+        voter: voter_genesis_hash.to_string(),
+        approve,
+        signature: signature.to_vec(),
+    });
+
+    Ok(())
+}
+
+/// Apply an approved proposal's change to the reward vault manager
+///
+/// The proposal must be open and unexpired, and its electorate must still be
+/// the manager's governance voters. Its votes are checked again against the
+/// manager's keys rather than trusted as recorded, and approvals from a strict
+/// majority are required; a tie is not a majority. The manager records the
+/// proposal as executed before applying it, so it cannot be executed again to
+/// undo a later change, and the proposal is marked `Executed`.
+pub fn execute_proposal(proposal: &mut Proposal, manager: &RewardVaultManager) -> Result<()> {
+    if proposal.state != ProposalState::Open {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Proposal {} is {:?}",
+            proposal.id, proposal.state
+        )));
+    }
+
+    if proposal.is_expired_at(now_secs()) {
+        proposal.state = ProposalState::Expired;
+        return Err(StorageNodeError::Expired(format!(
+            "Proposal {} expired at {}",
+            proposal.id, proposal.expires_at
+        )));
+    }
+
+    let electorate = manager.governance_keys()?;
+    if proposal.electorate != electorate {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Governance voters changed since proposal {} was created",
+            proposal.id
+        )));
+    }
+
+    let approvals = verified_approvals(proposal, &electorate)?;
+    if approvals <= electorate.len() / 2 {
+        return Err(StorageNodeError::InvalidState(format!(
+            "Proposal {} has {} of {} votes, a majority is required",
+            proposal.id,
+            approvals,
+            electorate.len()
+        )));
+    }
+
+    manager.record_executed_proposal(&proposal.id)?;
+
+    match &proposal.change {
+        GovernanceChange::UpdateRateSchedule(schedule) => {
+            manager.update_rate_schedule(schedule.clone())?
+        }
+        GovernanceChange::UpdateSlashingPolicy(policy) => {
+            manager.update_slashing_policy(*policy)?
+        }
+        GovernanceChange::AddGovernanceKey {
+            genesis_hash,
+            public_key,
+        } => manager.add_governance_key(genesis_hash, public_key.clone())?,
+        GovernanceChange::RemoveGovernanceKey { genesis_hash } => {
+            manager.remove_governance_key(genesis_hash)?
+        }
+    }
+
+    proposal.state = ProposalState::Executed;
+    Ok(())
+}
+
+/// Number of approvals among a proposal's votes, each checked against the
+/// voter's key in `electorate`
+///
+/// Fails on a vote from outside the electorate, a second vote from the same
+/// voter or a signature that does not verify.
+fn verified_approvals(proposal: &Proposal, electorate: &HashMap<String, Vec<u8>>) -> Result<usize> {
+    let mut voters = HashSet::new();
+    let mut approvals = 0;
+    for vote in &proposal.votes {
+        let public_key = electorate.get(&vote.voter).ok_or_else(|| {
+            StorageNodeError::Authentication(format!(
+                "{} may not vote on proposal {}",
+                vote.voter, proposal.id
+            ))
+        })?;
+
+        if !voters.insert(vote.voter.as_str()) {
+            return Err(StorageNodeError::InvalidState(format!(
+                "{} voted more than once on proposal {}",
+                vote.voter, proposal.id
+            )));
+        }
+
+        let message = vote_message(proposal, vote.approve)?;
+        if !verify_signature_or_false(public_key, &message, &vote.signature) {
+            return Err(StorageNodeError::Authentication(format!(
+                "Invalid vote signature from {}",
+                vote.voter
+            )));
+        }
+
+        if vote.approve {
+            approvals += 1;
+        }
+    }
+
+    Ok(approvals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dsm::crypto::sphincs;
+    use crate::staking::reward_store::SqliteRewardStore;
+    use crate::staking::rewards::RewardManagerConfig;
+    use dsm::vault::DLVManager;
+    use std::sync::Arc;
+
+    struct Voter {
+        genesis_hash: String,
+        public_key: Vec<u8>,
+        secret_key: Vec<u8>,
+    }
+
+    impl Voter {
+        fn sign(&self, proposal: &Proposal, approve: bool) -> Vec<u8> {
+            let message = vote_message(proposal, approve).unwrap();
+            sphincs::sphincs_sign(&self.secret_key, &message).unwrap()
+        }
+
+        fn vote(&self, proposal: &mut Proposal, approve: bool) -> Result<()> {
+            let signature = self.sign(proposal, approve);
+            cast_vote(proposal, &self.genesis_hash, approve, &signature)
+        }
+    }
+
+    fn register_voters(manager: &RewardVaultManager, voters: &[Voter]) {
+        for voter in voters {
+            manager
+                .add_governance_key(&voter.genesis_hash, voter.public_key.clone())
+                .unwrap();
+        }
+    }
+
+    fn manager_with_voters(count: usize) -> (RewardVaultManager, Vec<Voter>) {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let voters: Vec<_> = (0..count)
+            .map(|i| {
+                let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+                Voter {
+                    genesis_hash: format!("voter-{}", i),
+                    public_key,
+                    secret_key,
+                }
+            })
+            .collect();
+        register_voters(&manager, &voters);
+        (manager, voters)
+    }
+
+    fn rate_proposal_with(
+        manager: &RewardVaultManager,
+        retrieval_rate: u64,
+        expires_at: u64,
+    ) -> Proposal {
+        let mut schedule = manager.rate_schedule().unwrap();
+        schedule.retrieval_rate = retrieval_rate;
+        Proposal::new(
+            "raise-retrieval-rate",
+            GovernanceChange::UpdateRateSchedule(schedule),
+            "voter-0",
+            manager,
+            expires_at,
+        )
+        .unwrap()
+    }
+
+    fn rate_proposal(manager: &RewardVaultManager, expires_at: u64) -> Proposal {
+        rate_proposal_with(manager, 42, expires_at)
+    }
+
+    #[test]
+    fn test_majority_executes_proposal() {
+        let (manager, voters) = manager_with_voters(4);
+        let mut proposal = rate_proposal(&manager, now_secs() + 3600);
+
+        voters[0].vote(&mut proposal, true).unwrap();
+        voters[1].vote(&mut proposal, true).unwrap();
+        assert!(execute_proposal(&mut proposal, &manager).is_err());
+
+        voters[2].vote(&mut proposal, true).unwrap();
+        voters[3].vote(&mut proposal, false).unwrap();
+        assert!(proposal.has_majority());
+
+        execute_proposal(&mut proposal, &manager).unwrap();
+        assert_eq!(manager.rate_schedule().unwrap().retrieval_rate, 42);
+        assert_eq!(proposal.state, ProposalState::Executed);
+    }
+
+    #[test]
+    fn test_tie_does_not_execute() {
+        let (manager, voters) = manager_with_voters(4);
+        let mut proposal = rate_proposal(&manager, now_secs() + 3600);
+
+        voters[0].vote(&mut proposal, true).unwrap();
+        voters[1].vote(&mut proposal, true).unwrap();
+        voters[2].vote(&mut proposal, false).unwrap();
+        voters[3].vote(&mut proposal, false).unwrap();
+        assert!(!proposal.has_majority());
+
+        assert!(matches!(
+            execute_proposal(&mut proposal, &manager),
+            Err(StorageNodeError::InvalidState(_))
+        ));
+        assert_eq!(manager.rate_schedule().unwrap().retrieval_rate, 10);
+    }
+
+    #[test]
+    fn test_expired_proposal_rejects_votes_and_execution() {
+        let (manager, voters) = manager_with_voters(3);
+        let mut proposal = rate_proposal(&manager, now_secs() + 3600);
+
+        voters[0].vote(&mut proposal, true).unwrap();
+        voters[1].vote(&mut proposal, true).unwrap();
+        assert!(proposal.has_majority());
+
+        proposal.expires_at = now_secs() - 1;
+        assert!(matches!(
+            voters[2].vote(&mut proposal, true),
+            Err(StorageNodeError::Expired(_))
+        ));
+        assert_eq!(proposal.state, ProposalState::Expired);
+
+        assert!(matches!(
+            execute_proposal(&mut proposal, &manager),
+            Err(StorageNodeError::InvalidState(_))
+        ));
+        assert_eq!(manager.rate_schedule().unwrap().retrieval_rate, 10);
+    }
+
+    #[test]
+    fn test_votes_must_be_signed_once_by_electorate() {
+        let (manager, voters) = manager_with_voters(2);
+        let mut proposal = rate_proposal(&manager, now_secs() + 3600);
+
+        // Signature for the opposite choice
+        let signature = voters[0].sign(&proposal, false);
+        assert!(matches!(
+            cast_vote(&mut proposal, "voter-0", true, &signature),
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        voters[0].vote(&mut proposal, true).unwrap();
+        assert!(matches!(
+            voters[0].vote(&mut proposal, true),
+            Err(StorageNodeError::InvalidState(_))
+        ));
+
+        // Voters added after the proposal was created are not in its electorate
+        let (public_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+        manager.add_governance_key("late", public_key).unwrap();
+        assert!(matches!(
+            cast_vote(&mut proposal, "late", true, &signature),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert_eq!(proposal.votes.len(), 1);
+        assert_eq!(proposal.votes[0].voter, "voter-0");
+    }
+
+    #[test]
+    fn test_recorded_votes_are_checked_at_execution() {
+        let (manager, voters) = manager_with_voters(3);
+        let mut proposal = rate_proposal(&manager, now_secs() + 3600);
+        voters[0].vote(&mut proposal, true).unwrap();
+
+        // Approvals added without the voters' signatures
+        let mut forged = proposal.clone();
+        for voter in ["voter-1", "voter-2"] {
+            forged.votes.push(Vote {
+                voter: voter.to_string(),
+                approve: true,
+                signature: proposal.votes[0].signature.clone(),
+            });
+        }
+        assert!(matches!(
+            execute_proposal(&mut forged, &manager),
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        // An electorate replaced by keys the forger holds
+        let mut packed = proposal.clone();
+        let (outsider_key, outsider_secret) = sphincs::generate_sphincs_keypair().unwrap();
+        packed.electorate = HashMap::from([("voter-0".to_string(), outsider_key.clone())]);
+        packed.votes.clear();
+        let outsider = Voter {
+            genesis_hash: "voter-0".to_string(),
+            public_key: outsider_key,
+            secret_key: outsider_secret,
+        };
+        outsider.vote(&mut packed, true).unwrap();
+        assert!(matches!(
+            execute_proposal(&mut packed, &manager),
+            Err(StorageNodeError::InvalidState(_))
+        ));
+
+        // Votes moved to a different proposal that reuses the ID
+        voters[1].vote(&mut proposal, true).unwrap();
+        let mut moved = rate_proposal_with(&manager, 99, now_secs() + 3600);
+        moved.votes = proposal.votes.clone();
+        assert!(matches!(
+            execute_proposal(&mut moved, &manager),
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        assert_eq!(manager.rate_schedule().unwrap().retrieval_rate, 10);
+        execute_proposal(&mut proposal, &manager).unwrap();
+        assert_eq!(manager.rate_schedule().unwrap().retrieval_rate, 42);
+    }
+
+    #[test]
+    fn test_executed_proposal_cannot_be_replayed() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store.clone(),
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+        let (_, voters) = manager_with_voters(1);
+        register_voters(&manager, &voters);
+
+        let mut raise = rate_proposal(&manager, now_secs() + 3600);
+        voters[0].vote(&mut raise, true).unwrap();
+        let replay = raise.clone();
+        execute_proposal(&mut raise, &manager).unwrap();
+        assert!(matches!(
+            execute_proposal(&mut raise, &manager),
+            Err(StorageNodeError::InvalidState(_))
+        ));
+
+        // A later change is not undone by the earlier proposal, even after a restart
+        let mut schedule = manager.rate_schedule().unwrap();
+        schedule.retrieval_rate = 7;
+        manager.update_rate_schedule(schedule).unwrap();
+        let restarted = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store,
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+        register_voters(&restarted, &voters);
+        for manager in [&manager, &restarted] {
+            assert!(matches!(
+                execute_proposal(&mut replay.clone(), manager),
+                Err(StorageNodeError::InvalidState(_))
+            ));
+        }
+        assert_eq!(manager.rate_schedule().unwrap().retrieval_rate, 7);
+    }
+
+    #[test]
+    fn test_governance_key_changes() {
+        let (manager, voters) = manager_with_voters(1);
+        let (public_key, _) = sphincs::generate_sphincs_keypair().unwrap();
+
+        let mut add = Proposal::new(
+            "add-voter",
+            GovernanceChange::AddGovernanceKey {
+                genesis_hash: "voter-new".to_string(),
+                public_key,
+            },
+            "voter-0",
+            &manager,
+            now_secs() + 3600,
+        )
+        .unwrap();
+        voters[0].vote(&mut add, true).unwrap();
+        execute_proposal(&mut add, &manager).unwrap();
+        assert_eq!(manager.governance_keys().unwrap().len(), 2);
+
+        let mut remove = Proposal::new(
+            "remove-voter",
+            GovernanceChange::RemoveGovernanceKey {
+                genesis_hash: "voter-new".to_string(),
+            },
+            "voter-0",
+            &manager,
+            now_secs() + 3600,
+        )
+        .unwrap();
+        assert_eq!(remove.total_voters(), 2);
+
+        // One of two voters is not a majority
+        voters[0].vote(&mut remove, true).unwrap();
+        assert!(execute_proposal(&mut remove, &manager).is_err());
+        assert!(manager.governance_keys().unwrap().contains_key("voter-new"));
+    }
+}
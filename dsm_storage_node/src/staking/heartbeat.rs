@@ -0,0 +1,178 @@
+// Heartbeat Monitoring for DSM Storage Node
+//
+// The uptime in a storage receipt is worthless if the node reports it for
+// itself. Instead, clients and peer nodes probe a node at regular intervals
+// and sign each probe the node answered. Time is divided into slots of the
+// expected heartbeat interval, and a node's uptime over a period is the share
+// of the period's slots holding at least one validly signed probe.
+
+use crate::crypto::verify_signature_or_false;
+use crate::error::{Result, StorageNodeError};
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::RwLock;
+
+use tracing::warn;
+
+/// Domain separator of heartbeat signatures
+const HEARTBEAT_DOMAIN: &[u8] = b"DSM_HEARTBEAT";
+
+/// Default interval between expected heartbeats (5 minutes)
+pub const DEFAULT_HEARTBEAT_INTERVAL: u64 = 300;
+
+/// Hash a prober signs to attest that a node answered a probe at `timestamp`
+pub fn heartbeat_signing_hash(node_id: &str, timestamp: u64) -> [u8; 32] {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(HEARTBEAT_DOMAIN);
+    hasher.update(&(node_id.len() as u64).to_le_bytes());
+    hasher.update(node_id.as_bytes());
+    hasher.update(&timestamp.to_le_bytes());
+
+    *hasher.finalize().as_bytes()
+}
+
+/// Records signed liveness probes and derives node uptime from them
+pub struct HeartbeatMonitor {
+    /// Length of a heartbeat slot (seconds)
+    interval: u64,
+
+    /// SPHINCS+ public keys of the probers whose heartbeats count, by prober ID
+    probers: RwLock<HashMap<String, Vec<u8>>>,
+
+    /// Slots holding at least one valid heartbeat, by node ID
+    slots: RwLock<HashMap<String, BTreeSet<u64>>>,
+}
+
+impl HeartbeatMonitor {
+    /// Create a monitor expecting a heartbeat every `interval` seconds
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            probers: RwLock::new(HashMap::new()),
+            slots: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Accept heartbeats signed with `public_key` from now on
+    pub fn register_prober(&self, prober_id: &str, public_key: Vec<u8>) -> Result<()> {
+        self.probers
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .insert(prober_id.to_string(), public_key);
+
+        Ok(())
+    }
+
+    /// Stop accepting heartbeats from a prober
+    ///
+    /// Heartbeats it signed before are kept.
+    pub fn remove_prober(&self, prober_id: &str) -> Result<()> {
+        self.probers
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .remove(prober_id);
+
+        Ok(())
+    }
+
+    /// Record that a node answered a probe at `timestamp`
+    ///
+    /// `prober_signature` must sign [`heartbeat_signing_hash`] with the key of
+    /// a registered prober.
+    pub fn record_heartbeat(
+        &self,
+        node_id: &str,
+        timestamp: u64,
+        prober_signature: &[u8],
+    ) -> Result<()> {
+        let signing_hash = heartbeat_signing_hash(node_id, timestamp);
+        let signed = self
+            .probers
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .values()
+            .any(|key| verify_signature_or_false(key, &signing_hash, prober_signature));
+        if !signed {
+            warn!(
+                node_id = %node_id,
+                timestamp,
+                "Rejected heartbeat without a valid prober signature"
+            );
+            return Err(StorageNodeError::Authentication(format!(
+                "Heartbeat of node {} is not signed by a registered prober",
+                node_id
+            )));
+        }
+
+        self.slots
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .entry(node_id.to_string())
+            .or_default()
+            .insert(timestamp / self.interval);
+
+        Ok(())
+    }
+
+    /// Uptime of a node over `[period.0, period.1)`, in percent
+    ///
+    /// Every slot the period touches is expected to hold a heartbeat.
+    pub fn uptime_percentage(&self, node_id: &str, period: (u64, u64)) -> Result<u8> {
+        let (start, end) = period;
+        if end <= start {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Empty uptime period [{}, {})",
+                start, end
+            )));
+        }
+
+        let first = start / self.interval;
+        let last = (end - 1) / self.interval;
+        let expected = last - first + 1;
+
+        let slots = self.slots.read().map_err(|_| StorageNodeError::Internal)?;
+        let observed = slots
+            .get(node_id)
+            .map_or(0, |slots| slots.range(first..=last).count() as u64);
+
+        Ok((observed * 100 / expected) as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::sign_with_node_key;
+    use dsm::crypto::sphincs;
+
+    #[test]
+    fn test_uptime_counts_slots_with_valid_probes() {
+        let monitor = HeartbeatMonitor::new(100);
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let (other_key, other_secret) = sphincs::generate_sphincs_keypair().unwrap();
+        monitor.register_prober("client-1", public_key).unwrap();
+
+        let probe = |timestamp: u64, secret_key: &[u8]| {
+            let hash = heartbeat_signing_hash("node-1", timestamp);
+            let signature = sign_with_node_key(secret_key, &hash).unwrap();
+            monitor.record_heartbeat("node-1", timestamp, &signature)
+        };
+
+        // Two probes in the first slot count once; the third slot has none
+        probe(10, &secret_key).unwrap();
+        probe(90, &secret_key).unwrap();
+        probe(150, &secret_key).unwrap();
+        probe(350, &secret_key).unwrap();
+        assert_eq!(monitor.uptime_percentage("node-1", (0, 400)).unwrap(), 75);
+        assert_eq!(monitor.uptime_percentage("node-2", (0, 400)).unwrap(), 0);
+
+        // Probes from unregistered probers do not count
+        let error = probe(250, &other_secret).unwrap_err();
+        assert!(matches!(error, StorageNodeError::Authentication(_)));
+        monitor.register_prober("peer-1", other_key).unwrap();
+        probe(250, &other_secret).unwrap();
+        assert_eq!(monitor.uptime_percentage("node-1", (0, 400)).unwrap(), 100);
+
+        assert!(monitor.uptime_percentage("node-1", (400, 400)).is_err());
+    }
+}
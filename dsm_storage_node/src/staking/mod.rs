@@ -3,17 +3,37 @@
 // This module implements the staking, subscription, and node operation mechanisms
 // as described in the DSM whitepaper.
 
+use crate::crypto::load_or_generate_keypair;
 use crate::error::{Result, StorageNodeError};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+pub mod audit_log;
+pub mod challenge;
+pub mod claims;
+pub mod dispute;
+pub mod geolocation;
 pub mod governance;
+pub mod heartbeat;
+pub mod payout;
+pub mod price_feed;
+pub mod receipt_aggregate;
+pub mod receipt_exchange;
+pub mod report;
+pub mod reward_store;
 pub mod rewards;
+pub mod sla;
 pub mod subscription;
 
 use dsm::vault::DLVManager;
-use rewards::{RewardVaultManager, RateSchedule, StorageReceipt};
+use price_feed::{CachedPriceFeed, HttpPriceFeed, PriceFeed};
+use audit_log::AuditLog;
+use heartbeat::HeartbeatMonitor;
+use reward_store::{RewardStore, SqliteRewardStore};
+use rewards::{RewardManagerConfig, RewardVaultManager, RateSchedule, Ratio, StorageReceipt};
 use subscription::{SubscriptionConfig, SubscriptionManager, SubscriptionTier, SubscriptionPeriod};
 
 /// Configuration for the staking service
@@ -39,6 +59,31 @@ pub struct StakingConfig {
     pub renewal_notification_period: u64,
     /// Subscription grace period (seconds)
     pub subscription_grace_period: u64,
+    /// Database file for receipts and reward vaults (None = in memory only)
+    pub reward_store_path: Option<String>,
+    /// CoinGecko-compatible price feed URL (None = fixed token rates)
+    pub price_feed_url: Option<String>,
+    /// How long a fetched token price is reused (seconds)
+    pub price_cache_ttl: u64,
+    /// Path prefix of the operational key pair that signs the distribution
+    /// audit log, kept in `<path>.key` and `<path>.pub` (None = not audited)
+    pub audit_key_path: Option<String>,
+    /// Time nodes have to dispute a reward split before it is paid (seconds)
+    pub reward_dispute_window: u64,
+    /// Smallest share paid out; smaller shares carry over to the node's next one
+    pub reward_min_payout: u64,
+    /// Aggregate and prune recipients' receipts once their vault is distributed
+    pub reward_compact_receipts: bool,
+    /// Largest region multiplier a rate schedule may set
+    pub reward_max_region_multiplier: f64,
+    /// Interval at which probers are expected to heartbeat each node
+    /// (seconds, None = receipt uptime is taken as claimed)
+    pub heartbeat_interval: Option<u64>,
+    /// Percentage points receipt uptime may exceed the measured uptime by
+    pub reward_uptime_tolerance: u8,
+    /// SPHINCS+ public key of the geolocation oracle that proves node regions
+    /// (None = region multipliers never apply)
+    pub region_oracle_public_key: Option<Vec<u8>>,
 }
 
 /// Staking service for managing node staking operations
@@ -130,7 +175,60 @@ pub async fn initialize(&mut self) -> Result<()> {
         self.dlv_manager = Some(dlv_manager.clone());
 
         // Initialize the reward vault manager
-        let reward_manager = Arc::new(RewardVaultManager::new(dlv_manager.clone()));
+        let reward_config = RewardManagerConfig {
+            dispute_window: Duration::from_secs(self.config.reward_dispute_window),
+            min_payout: self.config.reward_min_payout,
+            compact_receipts: self.config.reward_compact_receipts,
+            max_region_multiplier: Ratio::multiplier(self.config.reward_max_region_multiplier)?,
+            uptime_tolerance: self.config.reward_uptime_tolerance,
+            ..RewardManagerConfig::default()
+        };
+        let store = match &self.config.reward_store_path {
+            Some(path) => Some(Arc::new(SqliteRewardStore::new(path)?) as Arc<dyn RewardStore>),
+            None => None,
+        };
+        let reward_manager = match &store {
+            Some(store) => {
+                RewardVaultManager::with_store(dlv_manager.clone(), store.clone(), reward_config)?
+            }
+            None => RewardVaultManager::new(dlv_manager.clone(), reward_config),
+        };
+        let audit_log = match &self.config.audit_key_path {
+            Some(path) => {
+                let (public_key, secret_key) = load_or_generate_keypair(
+                    Path::new(&format!("{}.key", path)),
+                    Path::new(&format!("{}.pub", path)),
+                )?;
+                let log = match &store {
+                    Some(store) => AuditLog::with_store(public_key, secret_key, store.clone())?,
+                    None => AuditLog::new(public_key, secret_key),
+                };
+                Some(Arc::new(log))
+            }
+            None => None,
+        };
+        let price_feed = match &self.config.price_feed_url {
+            Some(url) => {
+                let feed = HttpPriceFeed::new(url, Duration::from_secs(10))?;
+                let cached = CachedPriceFeed::new(
+                    Arc::new(feed),
+                    Duration::from_secs(self.config.price_cache_ttl),
+                );
+                Some(Arc::new(cached) as Arc<dyn PriceFeed>)
+            }
+            None => None,
+        };
+        let heartbeat_monitor = self
+            .config
+            .heartbeat_interval
+            .map(|interval| Arc::new(HeartbeatMonitor::new(interval)));
+        let reward_manager = Arc::new(
+            reward_manager
+                .with_price_feed(price_feed)
+                .with_audit_log(audit_log)
+                .with_heartbeat_monitor(heartbeat_monitor)
+                .with_region_oracle(self.config.region_oracle_public_key.clone()),
+        );
         reward_manager.initialize()?;
         self.reward_manager = Some(reward_manager);
 
@@ -558,6 +656,27 @@ pub fn process_receipt(&self, receipt: StorageReceipt) -> Result<()> {
         }
     }
 
+    /// Register the SPHINCS+ key a node or client signs receipts with
+    ///
+    /// See [`RewardVaultManager::register_identity_key`]. Without a reward
+    /// manager no receipts are accepted, so there is nothing to register.
+    pub fn register_identity_key(&self, id: &str, public_key: Vec<u8>) -> Result<()> {
+        match &self.reward_manager {
+            Some(reward_manager) => reward_manager.register_identity_key(id, public_key),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop background reward processing
+    ///
+    /// Pending distributions are persisted so they resume on the next start.
+    pub async fn shutdown(&self) -> Result<()> {
+        match &self.reward_manager {
+            Some(reward_manager) => reward_manager.shutdown().await,
+            None => Ok(()),
+        }
+    }
+
     /// Get the reward manager
     pub fn get_reward_manager(&self) -> Result<Arc<RewardVaultManager>> {
         self.reward_manager
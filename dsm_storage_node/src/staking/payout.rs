@@ -0,0 +1,181 @@
+// Reward Payouts for DSM Storage Node
+//
+// Processing a distribution decides how much of a vault each node is owed; a
+// distribution executor moves those tokens, one transfer per recipient. The
+// status of every transfer is kept with the vault, so a distribution that
+// failed for some recipients is retried for those recipients only and no
+// recipient is paid twice.
+//
+// A payout is marked in flight before its transfer is sent. If the node stops,
+// or the status cannot be recorded, before the outcome is known, the retry
+// sends the transfer again under the same transfer ID, and the executor
+// returns the earlier transfer's state instead of paying a second time.
+
+use crate::error::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of payout transfer IDs
+const PAYOUT_TRANSFER_DOMAIN: &[u8] = b"DSM_PAYOUT_TRANSFER";
+
+/// ID of the transfer paying `node_id` its share of `vault_id`
+///
+/// The ID is the same on every attempt, so an executor can recognise a
+/// transfer it has already made.
+pub fn payout_transfer_id(vault_id: &str, node_id: &str) -> String {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(PAYOUT_TRANSFER_DOMAIN);
+    for field in [vault_id.as_bytes(), node_id.as_bytes()] {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field);
+    }
+
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Executes the token transfers of processed distributions
+#[async_trait]
+pub trait DistributionExecutor: Send + Sync {
+    /// Transfer `transfer.amount` of `transfer.token_id` to `transfer.node_id`
+    ///
+    /// A transfer may be retried after its outcome was lost. If a transfer
+    /// with the same `transfer_id` was already made, the executor must return
+    /// the state it produced instead of transferring again.
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Number of the state the transfer produced
+    async fn execute(&self, transfer: &PayoutTransfer) -> Result<u64>;
+}
+
+/// One recipient's share of a distribution, to be transferred
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayoutTransfer {
+    /// Deterministic ID of the transfer, see [`payout_transfer_id`]
+    pub transfer_id: String,
+
+    /// Vault the share is paid from
+    pub vault_id: String,
+
+    /// Node receiving the share
+    pub node_id: String,
+
+    /// Token the share is paid in
+    pub token_id: String,
+
+    /// Amount to transfer
+    pub amount: u64,
+}
+
+impl PayoutTransfer {
+    /// Transfer of `amount` of `token_id` from `vault_id` to `node_id`
+    pub fn new(vault_id: &str, node_id: &str, token_id: &str, amount: u64) -> Self {
+        Self {
+            transfer_id: payout_transfer_id(vault_id, node_id),
+            vault_id: vault_id.to_string(),
+            node_id: node_id.to_string(),
+            token_id: token_id.to_string(),
+            amount,
+        }
+    }
+}
+
+/// Where the transfer of a payout stands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutStatus {
+    /// Not attempted yet
+    Pending,
+
+    /// Sent to the executor; the outcome has not been recorded
+    InFlight,
+
+    /// Transferred in the given state
+    Executed { state_number: u64 },
+
+    /// Last attempt failed; retried with the distribution
+    Failed { error: String },
+}
+
+/// A recipient's payout from a distributed vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payout {
+    /// Amount owed to the recipient
+    pub amount: u64,
+
+    /// Status of the transfer
+    pub status: PayoutStatus,
+}
+
+impl Payout {
+    /// A payout whose transfer has not been attempted
+    pub fn pending(amount: u64) -> Self {
+        Self {
+            amount,
+            status: PayoutStatus::Pending,
+        }
+    }
+
+    /// Whether the transfer still has to be made
+    pub fn is_outstanding(&self) -> bool {
+        !matches!(self.status, PayoutStatus::Executed { .. })
+    }
+
+    /// Number of the state the transfer produced, once executed
+    pub fn state_number(&self) -> Option<u64> {
+        match self.status {
+            PayoutStatus::Executed { state_number } => Some(state_number),
+            _ => None,
+        }
+    }
+}
+
+// Mock executor for testing the orchestration without a token stack
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockDistributionExecutor {
+    failing: std::sync::Mutex<std::collections::HashSet<String>>,
+    executed: std::sync::Mutex<Vec<PayoutTransfer>>,
+}
+
+#[cfg(test)]
+impl MockDistributionExecutor {
+    /// Make transfers to `node_id` fail until `set_failing(node_id, false)`
+    pub fn set_failing(&self, node_id: &str, failing: bool) {
+        let mut nodes = self.failing.lock().unwrap();
+        if failing {
+            nodes.insert(node_id.to_string());
+        } else {
+            nodes.remove(node_id);
+        }
+    }
+
+    /// Transfers executed so far, in order, each once
+    pub fn executed(&self) -> Vec<PayoutTransfer> {
+        self.executed.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl DistributionExecutor for MockDistributionExecutor {
+    async fn execute(&self, transfer: &PayoutTransfer) -> Result<u64> {
+        if self.failing.lock().unwrap().contains(&transfer.node_id) {
+            return Err(crate::error::StorageNodeError::Network(format!(
+                "Node {} unreachable",
+                transfer.node_id
+            )));
+        }
+
+        let mut executed = self.executed.lock().unwrap();
+        if let Some(position) = executed
+            .iter()
+            .position(|done| done.transfer_id == transfer.transfer_id)
+        {
+            return Ok(position as u64 + 1);
+        }
+        executed.push(transfer.clone());
+
+        // Each transfer advances the sender by one state
+        Ok(executed.len() as u64)
+    }
+}
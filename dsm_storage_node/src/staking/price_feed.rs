@@ -0,0 +1,241 @@
+// Price Feed for DSM Storage Node
+//
+// Reward rates are set in tokens, but the value of a token moves with the
+// market. A price feed supplies the current exchange rate of a token against
+// a reference currency so reward calculations can track that currency.
+
+use crate::error::{Result, StorageNodeError};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use url::Url;
+
+/// Source of token exchange rates
+#[async_trait]
+pub trait PriceFeed: Send + Sync {
+    /// Price of one `token_id` in `quote_currency`
+    async fn get_price(&self, token_id: &str, quote_currency: &str) -> Result<Decimal>;
+}
+
+/// Price feed served over HTTP in the CoinGecko `simple/price` format
+///
+/// Requests `GET {base_url}/simple/price?ids=<token>&vs_currencies=<currency>`
+/// and expects `{"<token>": {"<currency>": <price>}}`, with token and currency
+/// in lower case.
+pub struct HttpPriceFeed {
+    /// HTTP client for feed requests
+    client: reqwest::Client,
+
+    /// Base URL of the feed
+    base_url: Url,
+}
+
+impl HttpPriceFeed {
+    /// Create a feed for the service at `base_url`
+    pub fn new(base_url: &str, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                StorageNodeError::Network(format!("Failed to create HTTP client: {}", e))
+            })?;
+
+        // Without a trailing slash `join` would replace the last path segment
+        let mut base_url = Url::parse(base_url)
+            .map_err(|e| StorageNodeError::Config(format!("Invalid price feed URL: {}", e)))?;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        Ok(Self { client, base_url })
+    }
+}
+
+#[async_trait]
+impl PriceFeed for HttpPriceFeed {
+    async fn get_price(&self, token_id: &str, quote_currency: &str) -> Result<Decimal> {
+        let token = token_id.to_lowercase();
+        let currency = quote_currency.to_lowercase();
+
+        let mut url = self
+            .base_url
+            .join("simple/price")
+            .map_err(|e| StorageNodeError::Network(format!("Failed to create URL: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("ids", &token)
+            .append_pair("vs_currencies", &currency);
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| StorageNodeError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(StorageNodeError::Network(format!(
+                "Price feed returned error: {}",
+                response.status()
+            )));
+        }
+
+        let body: serde_json::Value = response.json().await.map_err(|e| {
+            StorageNodeError::Serialization(format!("Failed to parse price feed response: {}", e))
+        })?;
+
+        // Read numbers from their JSON text so prices are not rounded through f64
+        let price = match &body[&token][&currency] {
+            serde_json::Value::Number(n) => Decimal::from_str(&n.to_string())
+                .or_else(|_| Decimal::from_scientific(&n.to_string())),
+            serde_json::Value::String(s) => Decimal::from_str(s),
+            _ => {
+                return Err(StorageNodeError::NotFound(format!(
+                    "No {} price for {}",
+                    quote_currency, token_id
+                )))
+            }
+        };
+
+        price.map_err(|e| StorageNodeError::Serialization(format!("Invalid price: {}", e)))
+    }
+}
+
+/// Price feed wrapper that caches prices for a fixed time
+///
+/// Fresh prices are served without consulting the wrapped feed. When the
+/// wrapped feed fails, the last price it returned is used instead, however
+/// old it is.
+pub struct CachedPriceFeed {
+    /// Feed supplying prices
+    inner: Arc<dyn PriceFeed>,
+
+    /// How long a price is served without consulting the feed
+    ttl: Duration,
+
+    /// Last price and fetch time by (token, currency)
+    prices: Mutex<HashMap<(String, String), (Decimal, Instant)>>,
+}
+
+impl CachedPriceFeed {
+    /// Cache prices from `inner` for `ttl`
+    pub fn new(inner: Arc<dyn PriceFeed>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            prices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn cached(&self, key: &(String, String)) -> Option<(Decimal, Instant)> {
+        self.prices.lock().ok()?.get(key).copied()
+    }
+}
+
+#[async_trait]
+impl PriceFeed for CachedPriceFeed {
+    async fn get_price(&self, token_id: &str, quote_currency: &str) -> Result<Decimal> {
+        let key = (token_id.to_string(), quote_currency.to_string());
+        let cached = self.cached(&key);
+
+        if let Some((price, fetched_at)) = cached {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(price);
+            }
+        }
+
+        match self.inner.get_price(token_id, quote_currency).await {
+            Ok(price) => {
+                if let Ok(mut prices) = self.prices.lock() {
+                    prices.insert(key, (price, Instant::now()));
+                }
+                Ok(price)
+            }
+            Err(e) => match cached {
+                Some((price, _)) => {
+                    warn!(
+                        "Price feed failed for {}/{}, using last known price: {}",
+                        token_id, quote_currency, e
+                    );
+                    Ok(price)
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    #[tokio::test]
+    async fn test_http_feed_reads_coingecko_format() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/simple/price")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("ids".into(), "root".into()),
+                Matcher::UrlEncoded("vs_currencies".into(), "usd".into()),
+            ]))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"root": {"usd": 0.1}}"#)
+            .create_async()
+            .await;
+
+        let base_url = format!("{}/api/v3", server.url());
+        let feed = HttpPriceFeed::new(&base_url, Duration::from_secs(5)).unwrap();
+
+        let price = feed.get_price("ROOT", "USD").await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(price, Decimal::from_str("0.1").unwrap());
+
+        server
+            .mock("GET", "/api/v3/simple/price")
+            .match_query(Matcher::UrlEncoded("vs_currencies".into(), "eur".into()))
+            .with_body(r#"{"root": {}}"#)
+            .create_async()
+            .await;
+
+        assert!(matches!(
+            feed.get_price("ROOT", "EUR").await,
+            Err(StorageNodeError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cached_feed_falls_back_to_last_price() {
+        let mut server = Server::new_async().await;
+        let healthy = server
+            .mock("GET", "/simple/price")
+            .match_query(Matcher::Any)
+            .with_body(r#"{"root": {"usd": 2.5}}"#)
+            .create_async()
+            .await;
+
+        let http = HttpPriceFeed::new(&server.url(), Duration::from_secs(5)).unwrap();
+        // A zero TTL consults the feed on every call
+        let feed = CachedPriceFeed::new(Arc::new(http), Duration::ZERO);
+
+        let price = feed.get_price("ROOT", "USD").await.unwrap();
+        assert_eq!(price, Decimal::from_str("2.5").unwrap());
+
+        healthy.remove_async().await;
+        let failing = server
+            .mock("GET", "/simple/price")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .create_async()
+            .await;
+
+        assert_eq!(feed.get_price("ROOT", "USD").await.unwrap(), price);
+        failing.assert_async().await;
+
+        // Nothing to fall back on for a pair that was never fetched
+        assert!(feed.get_price("ROOT", "EUR").await.is_err());
+    }
+}
@@ -0,0 +1,134 @@
+// Receipt Aggregation for DSM Storage Node
+//
+// Storage receipts accumulate with every service period, and recalculating
+// rewards over thousands of them gets slow. Once a period has been
+// distributed, a node's receipts in it are replaced by a single aggregated
+// receipt that keeps the summed metrics, the reward they earned and a digest
+// of the receipts it stands in for, so past distributions can still be
+// audited.
+
+use crate::staking::rewards::{ChallengeSummary, RewardBreakdown, StorageMetrics, StorageReceipt};
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of the digest over an aggregate's receipt hashes
+const AGGREGATE_DIGEST_DOMAIN: &[u8] = b"DSM_RECEIPT_AGGREGATE";
+
+/// A node's storage receipts for a settled period, combined into one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatedReceipt {
+    /// Storage node ID that provided the service
+    pub node_id: String,
+
+    /// Earliest start and latest end of the receipts' service periods
+    pub service_period: (u64, u64),
+
+    /// Number of receipts combined
+    pub receipt_count: u64,
+
+    /// Metrics of the receipts, summed; uptime is the average weighted by
+    /// service time and regions are the union
+    pub storage_metrics: StorageMetrics,
+
+    /// Distinct SPHINCS+ public keys the node signed the receipts with
+    pub node_public_keys: Vec<Vec<u8>>,
+
+    /// blake3 digest of the combined receipts' hashes, in sorted order
+    pub receipts_digest: [u8; 32],
+
+    /// Seconds of service the receipts cover
+    pub service_secs: u64,
+
+    /// Service seconds weighted by uptime percentage
+    pub uptime_secs: u64,
+
+    /// Reward the receipts earned over their service periods, before slashing
+    pub breakdown: RewardBreakdown,
+}
+
+impl AggregatedReceipt {
+    /// Combine a node's receipts with the reward they earned
+    ///
+    /// `receipts` must be non-empty and belong to `node_id`. The per-segment
+    /// detail of `breakdown` is dropped.
+    pub fn new(node_id: &str, receipts: &[StorageReceipt], mut breakdown: RewardBreakdown) -> Self {
+        let mut hashes: Vec<[u8; 32]> = receipts.iter().map(|r| r.receipt_hash).collect();
+        hashes.sort_unstable();
+        let mut hasher = ::blake3::Hasher::new();
+        hasher.update(AGGREGATE_DIGEST_DOMAIN);
+        for hash in &hashes {
+            hasher.update(hash);
+        }
+
+        let mut node_public_keys: Vec<Vec<u8>> =
+            receipts.iter().map(|r| r.node_public_key.clone()).collect();
+        node_public_keys.sort();
+        node_public_keys.dedup();
+
+        let mut storage_metrics = StorageMetrics {
+            bytes_stored: 0,
+            retrievals: 0,
+            operations_count: 0,
+            uptime_percentage: 0,
+            regions: HashSet::new(),
+            challenge_results: ChallengeSummary::default(),
+        };
+        let (mut service_secs, mut uptime_secs) = (0u64, 0u64);
+        for receipt in receipts {
+            let metrics = &receipt.storage_metrics;
+            storage_metrics.bytes_stored = storage_metrics
+                .bytes_stored
+                .saturating_add(metrics.bytes_stored);
+            storage_metrics.retrievals = storage_metrics
+                .retrievals
+                .saturating_add(metrics.retrievals);
+            storage_metrics.operations_count = storage_metrics
+                .operations_count
+                .saturating_add(metrics.operations_count);
+            storage_metrics
+                .regions
+                .extend(metrics.regions.iter().cloned());
+            storage_metrics
+                .challenge_results
+                .merge(&metrics.challenge_results);
+
+            let duration = receipt
+                .service_period
+                .1
+                .saturating_sub(receipt.service_period.0);
+            service_secs = service_secs.saturating_add(duration);
+            let uptime = metrics.uptime_percentage as u64;
+            uptime_secs = uptime_secs.saturating_add(duration.saturating_mul(uptime));
+        }
+        if service_secs > 0 {
+            storage_metrics.uptime_percentage = (uptime_secs / service_secs) as u8;
+        }
+
+        breakdown.segments.clear();
+
+        Self {
+            node_id: node_id.to_string(),
+            service_period: (
+                receipts
+                    .iter()
+                    .map(|r| r.service_period.0)
+                    .min()
+                    .unwrap_or_default(),
+                receipts
+                    .iter()
+                    .map(|r| r.service_period.1)
+                    .max()
+                    .unwrap_or_default(),
+            ),
+            receipt_count: receipts.len() as u64,
+            storage_metrics,
+            node_public_keys,
+            receipts_digest: *hasher.finalize().as_bytes(),
+            service_secs,
+            uptime_secs,
+            breakdown,
+        }
+    }
+}
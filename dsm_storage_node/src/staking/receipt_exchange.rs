@@ -0,0 +1,297 @@
+// Two-Phase Receipt Exchange for DSM Storage Node
+//
+// A receipt built by one party and passed to the other for countersignature
+// leaves a window in which its terms can change between the two signatures.
+// Receipts are instead exchanged in two phases: the node commits to the terms
+// by signing a challenge, and the client checks the challenge and seals it
+// with its own signature. The receipt hash covers the challenge hash and the
+// client's signature, so neither party can produce a valid receipt alone. A
+// challenge that is not finalized within its TTL can no longer be sealed.
+
+use crate::client::platform::now_secs;
+use crate::crypto::{sign_with_node_key, verify_signature_or_false};
+use crate::error::{Result, StorageNodeError};
+use crate::staking::rewards::{StorageMetrics, StorageReceipt};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Domain separator of receipt challenge hashes
+const RECEIPT_CHALLENGE_DOMAIN: &[u8] = b"DSM_RECEIPT_CHALLENGE";
+
+/// Version of receipts produced by the two-phase exchange
+pub const TWO_PHASE_RECEIPT_VERSION: u8 = 1;
+
+/// A node's signed commitment to the terms of a storage receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptChallenge {
+    /// Storage node ID that provided the service
+    pub node_id: String,
+
+    /// Client ID that received the service
+    pub client_id: String,
+
+    /// Service period (start, end) timestamps
+    pub service_period: (u64, u64),
+
+    /// Storage metrics the node claims for the period
+    pub storage_metrics: StorageMetrics,
+
+    /// SPHINCS+ public key the client must finalize the challenge with
+    pub client_public_key: Vec<u8>,
+
+    /// Node's SPHINCS+ public key
+    pub node_public_key: Vec<u8>,
+
+    /// When the node issued the challenge (seconds since epoch)
+    pub issued_at: u64,
+
+    /// Seconds after issue the challenge can still be finalized
+    pub challenge_ttl_secs: u64,
+
+    /// Random nonce making every challenge unique
+    pub nonce: [u8; 32],
+
+    /// Hash of the terms above
+    pub challenge_hash: [u8; 32],
+
+    /// Node's signature over the challenge hash
+    pub node_signature: Vec<u8>,
+}
+
+impl ReceiptChallenge {
+    /// Time after which the challenge can no longer be finalized
+    pub fn expires_at(&self) -> u64 {
+        self.issued_at.saturating_add(self.challenge_ttl_secs)
+    }
+
+    /// Compute the hash of the challenge's terms
+    ///
+    /// Covers every field except the hash and the node's signature.
+    /// Variable-length fields are length-prefixed so distinct challenges
+    /// cannot hash the same.
+    pub fn compute_hash(&self) -> Result<[u8; 32]> {
+        let metrics_bytes = bincode::serialize(&self.storage_metrics)
+            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+
+        let mut hasher = ::blake3::Hasher::new();
+        hasher.update(RECEIPT_CHALLENGE_DOMAIN);
+        for field in [
+            self.node_id.as_bytes(),
+            self.client_id.as_bytes(),
+            &metrics_bytes,
+            &self.client_public_key,
+            &self.node_public_key,
+        ] {
+            hasher.update(&(field.len() as u64).to_le_bytes());
+            hasher.update(field);
+        }
+        hasher.update(&self.service_period.0.to_le_bytes());
+        hasher.update(&self.service_period.1.to_le_bytes());
+        hasher.update(&self.issued_at.to_le_bytes());
+        hasher.update(&self.challenge_ttl_secs.to_le_bytes());
+        hasher.update(&self.nonce);
+
+        Ok(*hasher.finalize().as_bytes())
+    }
+
+    /// Check that the terms match the challenge hash the node signed
+    pub fn verify(&self) -> Result<()> {
+        if self.compute_hash()? != self.challenge_hash {
+            return Err(StorageNodeError::Staking(
+                "Invalid receipt challenge: terms do not match the challenge hash".to_string(),
+            ));
+        }
+
+        if !verify_signature_or_false(
+            &self.node_public_key,
+            &self.challenge_hash,
+            &self.node_signature,
+        ) {
+            return Err(StorageNodeError::Staking(
+                "Invalid receipt challenge: bad node signature".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Seal the challenge into a receipt with the client's signature
+    ///
+    /// The signature is not checked here; receipts are verified when they
+    /// are processed.
+    pub fn seal(&self, client_signature: Vec<u8>) -> StorageReceipt {
+        StorageReceipt {
+            node_id: self.node_id.clone(),
+            client_id: self.client_id.clone(),
+            service_period: self.service_period,
+            storage_metrics: self.storage_metrics.clone(),
+            client_public_key: self.client_public_key.clone(),
+            node_public_key: self.node_public_key.clone(),
+            version: TWO_PHASE_RECEIPT_VERSION,
+            challenge_issued_at: self.issued_at,
+            challenge_ttl_secs: self.challenge_ttl_secs,
+            challenge_nonce: self.nonce,
+            receipt_hash: sealed_receipt_hash(&self.challenge_hash, &client_signature),
+            client_signature,
+            node_signature: self.node_signature.clone(),
+        }
+    }
+}
+
+/// Hash of a receipt sealed from a challenge: `blake3(challenge_hash || client_signature)`
+pub fn sealed_receipt_hash(challenge_hash: &[u8; 32], client_signature: &[u8]) -> [u8; 32] {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(challenge_hash);
+    hasher.update(client_signature);
+    *hasher.finalize().as_bytes()
+}
+
+/// Issue a challenge committing the node to a receipt's terms
+///
+/// `node_keypair` is `(public_key, private_key)`. The client has
+/// `challenge_ttl_secs` to finalize the challenge.
+pub fn initiate_receipt(
+    node_id: &str,
+    client_id: &str,
+    service_period: (u64, u64),
+    storage_metrics: StorageMetrics,
+    client_public_key: &[u8],
+    node_keypair: (&[u8], &[u8]),
+    challenge_ttl_secs: u64,
+) -> Result<ReceiptChallenge> {
+    let mut nonce = [0u8; 32];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut challenge = ReceiptChallenge {
+        node_id: node_id.to_string(),
+        client_id: client_id.to_string(),
+        service_period,
+        storage_metrics,
+        client_public_key: client_public_key.to_vec(),
+        node_public_key: node_keypair.0.to_vec(),
+        issued_at: now_secs(),
+        challenge_ttl_secs,
+        nonce,
+        challenge_hash: [0u8; 32],
+        node_signature: Vec::new(),
+    };
+    challenge.challenge_hash = challenge.compute_hash()?;
+    challenge.node_signature = sign_with_node_key(node_keypair.1, &challenge.challenge_hash)?;
+
+    Ok(challenge)
+}
+
+/// Countersign a node's challenge and seal it into a receipt
+///
+/// `client_keypair` is `(public_key, private_key)`; the public key must be
+/// the one the challenge was issued to. Fails if the challenge has expired,
+/// or if its terms no longer match what the node signed.
+pub fn finalize_receipt(
+    challenge: &ReceiptChallenge,
+    client_keypair: (&[u8], &[u8]),
+) -> Result<StorageReceipt> {
+    finalize_receipt_at(challenge, client_keypair, now_secs())
+}
+
+fn finalize_receipt_at(
+    challenge: &ReceiptChallenge,
+    client_keypair: (&[u8], &[u8]),
+    now: u64,
+) -> Result<StorageReceipt> {
+    if now > challenge.expires_at() {
+        return Err(StorageNodeError::Staking(format!(
+            "Receipt challenge expired at {}",
+            challenge.expires_at()
+        )));
+    }
+    if client_keypair.0 != challenge.client_public_key.as_slice() {
+        return Err(StorageNodeError::Staking(
+            "Key does not match the challenge's client public key".to_string(),
+        ));
+    }
+    challenge.verify()?;
+
+    let client_signature = sign_with_node_key(client_keypair.1, &challenge.challenge_hash)?;
+    Ok(challenge.seal(client_signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking::rewards::ChallengeSummary;
+    use dsm::crypto::sphincs;
+    use std::collections::HashSet;
+
+    fn challenge(node: &(Vec<u8>, Vec<u8>), client_public_key: &[u8]) -> ReceiptChallenge {
+        let storage_metrics = StorageMetrics {
+            bytes_stored: 10,
+            retrievals: 3,
+            operations_count: 5,
+            uptime_percentage: 100,
+            regions: HashSet::new(),
+            challenge_results: ChallengeSummary::default(),
+        };
+        initiate_receipt(
+            "node-1",
+            "client",
+            (0, 86400),
+            storage_metrics,
+            client_public_key,
+            (&node.0, &node.1),
+            60,
+        )
+        .unwrap()
+    }
+
+    fn assert_refused(result: Result<StorageReceipt>) {
+        assert!(matches!(result, Err(StorageNodeError::Staking(_))));
+    }
+
+    #[test]
+    fn test_finalized_receipt_is_sealed_by_both_parties() {
+        let node = sphincs::generate_sphincs_keypair().unwrap();
+        let client = sphincs::generate_sphincs_keypair().unwrap();
+        let challenge = challenge(&node, &client.0);
+
+        let receipt = finalize_receipt(&challenge, (&client.0, &client.1)).unwrap();
+        assert_eq!(receipt.version, TWO_PHASE_RECEIPT_VERSION);
+        assert_eq!(receipt.node_signature, challenge.node_signature);
+        assert_eq!(
+            receipt.receipt_hash,
+            sealed_receipt_hash(&challenge.challenge_hash, &receipt.client_signature)
+        );
+        assert_eq!(receipt.compute_hash().unwrap(), receipt.receipt_hash);
+
+        // The node cannot finalize its own challenge
+        assert_refused(finalize_receipt(&challenge, (&node.0, &node.1)));
+    }
+
+    #[test]
+    fn test_stale_challenge_rejected() {
+        let node = sphincs::generate_sphincs_keypair().unwrap();
+        let client = sphincs::generate_sphincs_keypair().unwrap();
+        let challenge = challenge(&node, &client.0);
+
+        let keypair = (client.0.as_slice(), client.1.as_slice());
+        assert!(finalize_receipt_at(&challenge, keypair, challenge.expires_at()).is_ok());
+        assert_refused(finalize_receipt_at(
+            &challenge,
+            keypair,
+            challenge.expires_at() + 1,
+        ));
+    }
+
+    #[test]
+    fn test_metrics_modified_after_challenge_rejected() {
+        let node = sphincs::generate_sphincs_keypair().unwrap();
+        let client = sphincs::generate_sphincs_keypair().unwrap();
+        let mut modified = challenge(&node, &client.0);
+        modified.storage_metrics.bytes_stored = 10_000;
+        assert_refused(finalize_receipt(&modified, (&client.0, &client.1)));
+
+        // Recomputing the hash does not help without the node's signature
+        modified.challenge_hash = modified.compute_hash().unwrap();
+        assert_refused(finalize_receipt(&modified, (&client.0, &client.1)));
+    }
+}
@@ -0,0 +1,199 @@
+// Reward Reports for DSM Storage Node
+//
+// Accounting exports of a reward period: a row per node with the reward it
+// earned in the period broken down by service, and a row per payment of each
+// distribution processed in the period with the audit record that signed it.
+// Amounts are integer token units; `decimals` says where the decimal point
+// goes for the token, when its metadata is known. Rows are written to the
+// output one at a time, so a long period never has to fit in memory as text.
+
+use crate::error::Result;
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+/// Columns of a CSV report, in the order of the fields of [`ReportRow`]
+const CSV_HEADER: &str = "record_type,node_id,token_id,decimals,amount,storage_reward,\
+retrieval_reward,operation_reward,slashed,redistributed,region_multiplier,vault_id,\
+transaction_ref,timestamp";
+
+/// Output format of a reward report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// A JSON object with the period and an array of rows
+    Json,
+
+    /// Comma-separated values with a header line
+    Csv,
+}
+
+/// Kind of a report row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportRecordType {
+    /// Reward a node earned in the period
+    NodeReward,
+
+    /// Amount a distribution paid to a node
+    Distribution,
+}
+
+impl ReportRecordType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::NodeReward => "node_reward",
+            Self::Distribution => "distribution",
+        }
+    }
+}
+
+/// One line of a reward report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportRow {
+    /// What the row records
+    pub record_type: ReportRecordType,
+
+    /// Node the amount is for
+    pub node_id: String,
+
+    /// Token the amount is in
+    pub token_id: String,
+
+    /// Decimal places of the token (None = token metadata unknown)
+    pub decimals: Option<u8>,
+
+    /// Reward earned or amount paid, in integer token units
+    pub amount: u64,
+
+    /// Reward for bytes stored (node rewards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_reward: Option<u64>,
+
+    /// Reward for retrievals served (node rewards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_reward: Option<u64>,
+
+    /// Reward for operations processed (node rewards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_reward: Option<u64>,
+
+    /// Reward deducted under the slashing policy (node rewards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slashed: Option<u64>,
+
+    /// Share of other nodes' slashed rewards (node rewards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redistributed: Option<u64>,
+
+    /// Largest region multiplier applied (node rewards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region_multiplier: Option<f64>,
+
+    /// Vault the payment came from (distributions only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_id: Option<String>,
+
+    /// Hex hash of the audit record of the payment (distributions only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_ref: Option<String>,
+
+    /// When the distribution was processed (distributions only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+impl ReportRow {
+    /// The row as a CSV line, without the line break
+    fn to_csv(&self) -> String {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+
+        [
+            self.record_type.as_str().to_string(),
+            csv_field(&self.node_id),
+            csv_field(&self.token_id),
+            opt(&self.decimals),
+            self.amount.to_string(),
+            opt(&self.storage_reward),
+            opt(&self.retrieval_reward),
+            opt(&self.operation_reward),
+            opt(&self.slashed),
+            opt(&self.redistributed),
+            opt(&self.region_multiplier),
+            self.vault_id.as_deref().map(csv_field).unwrap_or_default(),
+            opt(&self.transaction_ref),
+            opt(&self.timestamp),
+        ]
+        .join(",")
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes a report row by row
+pub(crate) struct ReportWriter<W: Write> {
+    writer: W,
+    format: ReportFormat,
+    rows: u64,
+}
+
+impl<W: Write> ReportWriter<W> {
+    /// Start a report for `[period_start, period_end)`
+    pub(crate) fn begin(
+        mut writer: W,
+        format: ReportFormat,
+        period_start: u64,
+        period_end: u64,
+    ) -> Result<Self> {
+        match format {
+            ReportFormat::Json => write!(
+                writer,
+                "{{\"period_start\":{},\"period_end\":{},\"rows\":[",
+                period_start, period_end
+            )?,
+            ReportFormat::Csv => writeln!(writer, "{}", CSV_HEADER)?,
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            rows: 0,
+        })
+    }
+
+    /// Append a row
+    pub(crate) fn write_row(&mut self, row: &ReportRow) -> Result<()> {
+        match self.format {
+            ReportFormat::Json => {
+                if self.rows > 0 {
+                    self.writer.write_all(b",")?;
+                }
+                serde_json::to_writer(&mut self.writer, row)?;
+            }
+            ReportFormat::Csv => writeln!(self.writer, "{}", row.to_csv())?,
+        }
+        self.rows += 1;
+
+        Ok(())
+    }
+
+    /// Close the report and hand back the output
+    pub(crate) fn finish(mut self) -> Result<W> {
+        if self.format == ReportFormat::Json {
+            self.writer.write_all(b"]}")?;
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
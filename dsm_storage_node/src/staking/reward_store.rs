@@ -0,0 +1,534 @@
+// Reward Store for DSM Storage Node
+//
+// Persists the state of the reward vault manager (storage receipts and the
+// aggregates that replace them once settled, storage challenge results and
+// the challenges still awaiting a response, reward vault metadata and the
+// limbo vaults holding the rewards, pending distributions, the log of
+// processed ones, the distributions that were given up on, the history of
+// rate schedules and per-node rate overrides, the distribution audit log, the
+// shares carried over below the minimum payout, the governance proposals
+// already executed, and the keys registered for nodes and clients) so that a
+// restart of the storage node does not lose the receipts and challenge
+// results collected during a period, the challenges nodes still have to
+// answer, the vaults waiting to be distributed and the tokens they hold, the
+// outcome of past distributions, the rates that applied to past periods, the
+// signed record of what was paid out, what is still owed to nodes, which
+// proposals may no longer be executed, or which keys receipts must be signed
+// with.
+
+use crate::error::{Result, StorageNodeError};
+use crate::staking::audit_log::AuditRecord;
+use crate::staking::challenge::OutstandingChallenge;
+use crate::staking::receipt_aggregate::AggregatedReceipt;
+use crate::staking::rewards::{
+    ChallengeResult, DistributionRecord, DistributionRequest, FailedDistribution, RateSchedule,
+    StorageReceipt, VaultMetadata,
+};
+use dsm::vault::LimboVault;
+use rusqlite::{params, Connection};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::info;
+
+/// Durable storage for the reward vault manager
+///
+/// Writes happen as receipts, vaults and distributions are recorded; the
+/// `load_*` methods are only used to rebuild the manager on startup.
+pub trait RewardStore: Send + Sync {
+    /// Append a storage receipt
+    fn save_receipt(&self, receipt: &StorageReceipt) -> Result<()>;
+
+    /// All receipts, in the order they were saved
+    fn load_receipts(&self) -> Result<Vec<StorageReceipt>>;
+
+    /// Replace receipts of a node with their aggregate in one transaction
+    fn compact_receipts(
+        &self,
+        aggregate: &AggregatedReceipt,
+        receipt_hashes: &[[u8; 32]],
+    ) -> Result<()>;
+
+    /// All receipt aggregates, in the order they were saved
+    fn load_aggregated_receipts(&self) -> Result<Vec<AggregatedReceipt>>;
+
+    /// Append the result of a storage challenge
+    fn save_challenge_result(&self, result: &ChallengeResult) -> Result<()>;
+
+    /// All challenge results, in the order they were saved
+    fn load_challenge_results(&self) -> Result<Vec<ChallengeResult>>;
+
+    /// Record a challenge that is waiting for the node's response
+    fn save_outstanding_challenge(&self, challenge: &OutstandingChallenge) -> Result<()>;
+
+    /// Forget a challenge once it was answered or timed out
+    fn remove_outstanding_challenge(&self, challenge_id: &str) -> Result<()>;
+
+    /// Challenges that are still waiting for a response
+    fn load_outstanding_challenges(&self) -> Result<Vec<OutstandingChallenge>>;
+
+    /// Insert or replace the metadata of a vault
+    fn save_vault(&self, metadata: &VaultMetadata) -> Result<()>;
+
+    /// Metadata of every known vault
+    fn load_vaults(&self) -> Result<Vec<VaultMetadata>>;
+
+    /// Insert or replace the limbo vault holding a reward vault's tokens
+    fn save_limbo_vault(&self, vault: &LimboVault) -> Result<()>;
+
+    /// Every saved limbo vault, in its last saved state
+    fn load_limbo_vaults(&self) -> Result<Vec<LimboVault>>;
+
+    /// Record a distribution that has not been processed yet
+    fn save_distribution(&self, request: &DistributionRequest) -> Result<()>;
+
+    /// Forget a distribution once it has been processed
+    fn remove_distribution(&self, vault_id: &str) -> Result<()>;
+
+    /// Distributions that have not been processed yet
+    fn load_distributions(&self) -> Result<Vec<DistributionRequest>>;
+
+    /// Append the outcome of a processed distribution
+    fn save_distribution_record(&self, record: &DistributionRecord) -> Result<()>;
+
+    /// Outcomes of processed distributions, in the order they were saved
+    fn load_distribution_history(&self) -> Result<Vec<DistributionRecord>>;
+
+    /// Record a distribution that failed permanently
+    fn save_failed_distribution(&self, failed: &FailedDistribution) -> Result<()>;
+
+    /// Distributions that failed permanently, in the order they were saved
+    fn load_failed_distributions(&self) -> Result<Vec<FailedDistribution>>;
+
+    /// Insert or replace the rate schedule taking effect at `effective_from`
+    fn save_rate_schedule(&self, effective_from: u64, schedule: &RateSchedule) -> Result<()>;
+
+    /// Every saved rate schedule with the time it takes effect, oldest first
+    fn load_rate_schedules(&self) -> Result<Vec<(u64, RateSchedule)>>;
+
+    /// Append a node's rate override taking effect at `effective_from`
+    ///
+    /// `None` ends the node's override.
+    fn save_node_rate_override(
+        &self,
+        node_id: &str,
+        effective_from: u64,
+        schedule: Option<&RateSchedule>,
+    ) -> Result<()>;
+
+    /// Every saved node rate override, in the order they were saved
+    fn load_node_rate_overrides(&self) -> Result<Vec<(String, u64, Option<RateSchedule>)>>;
+
+    /// Append a distribution audit record; records are never replaced
+    fn save_audit_record(&self, record: &AuditRecord) -> Result<()>;
+
+    /// Every saved audit record, by sequence number
+    fn load_audit_records(&self) -> Result<Vec<AuditRecord>>;
+
+    /// Replace the carried-over shares (node_id -> amount) in one write
+    fn save_carryover(&self, carryover: &HashMap<String, u64>) -> Result<()>;
+
+    /// The carried-over shares as last saved
+    fn load_carryover(&self) -> Result<HashMap<String, u64>>;
+
+    /// Record a governance proposal as executed
+    fn save_executed_proposal(&self, proposal_id: &str) -> Result<()>;
+
+    /// IDs of every governance proposal recorded as executed
+    fn load_executed_proposals(&self) -> Result<Vec<String>>;
+
+    /// Record the key registered for a node or client ID
+    fn save_identity_key(&self, id: &str, public_key: &[u8]) -> Result<()>;
+
+    /// Every registered key with its node or client ID
+    fn load_identity_keys(&self) -> Result<Vec<(String, Vec<u8>)>>;
+}
+
+/// SQLite-backed reward store
+pub struct SqliteRewardStore {
+    /// Database connection
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRewardStore {
+    /// Open (or create) a reward store at the given path
+    pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        info!("Opening reward store: {:?}", db_path.as_ref());
+
+        if let Some(parent) = db_path.as_ref().parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                std::fs::create_dir_all(parent).map_err(|e| {
+                    StorageNodeError::Storage(format!(
+                        "Failed to create reward store directory: {}",
+                        e
+                    ))
+                })?;
+            }
+        }
+
+        let conn = Connection::open(db_path).map_err(|e| {
+            StorageNodeError::Storage(format!("Failed to open reward store: {}", e))
+        })?;
+
+        Self::with_connection(conn)
+    }
+
+    /// Create a reward store that lives only as long as this value
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().map_err(|e| {
+            StorageNodeError::Storage(format!("Failed to open reward store: {}", e))
+        })?;
+
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS reward_receipts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                node_id TEXT NOT NULL,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_aggregated_receipts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                node_id TEXT NOT NULL,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_challenge_results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                node_id TEXT NOT NULL,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_outstanding_challenges (
+                challenge_id TEXT PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_vaults (
+                vault_id TEXT PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_limbo_vaults (
+                vault_id TEXT PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_distributions (
+                vault_id TEXT PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_distribution_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id TEXT NOT NULL,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_failed_distributions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id TEXT NOT NULL,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_rate_schedules (
+                effective_from INTEGER PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_node_rate_overrides (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                node_id TEXT NOT NULL,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_audit_log (
+                sequence INTEGER PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_carryover (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_executed_proposals (
+                proposal_id TEXT PRIMARY KEY,
+                data BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reward_identity_keys (
+                id TEXT PRIMARY KEY,
+                data BLOB NOT NULL
+            );",
+        )
+        .map_err(|e| {
+            StorageNodeError::Storage(format!("Failed to create reward store tables: {}", e))
+        })?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn connection(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| StorageNodeError::Internal)
+    }
+
+    /// Decode every `data` column returned by a query
+    fn load_all<T: DeserializeOwned>(&self, sql: &str) -> Result<Vec<T>> {
+        let conn = self.connection()?;
+        let mut stmt = conn
+            .prepare(sql)
+            .map_err(|e| StorageNodeError::Storage(format!("Failed to prepare query: {}", e)))?;
+
+        let rows = stmt
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))
+            .map_err(|e| StorageNodeError::Storage(format!("Failed to query rows: {}", e)))?;
+
+        let values = rows
+            .map(|row| {
+                let data = row
+                    .map_err(|e| StorageNodeError::Storage(format!("Failed to read row: {}", e)))?;
+                Ok(bincode::deserialize(&data)?)
+            })
+            .collect();
+        values
+    }
+
+    fn execute(&self, sql: &str, params: impl rusqlite::Params) -> Result<()> {
+        self.connection()?.execute(sql, params).map_err(|e| {
+            StorageNodeError::Storage(format!("Failed to write reward store: {}", e))
+        })?;
+        Ok(())
+    }
+}
+
+impl RewardStore for SqliteRewardStore {
+    fn save_receipt(&self, receipt: &StorageReceipt) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_receipts (node_id, data) VALUES (?1, ?2)",
+            params![receipt.node_id, bincode::serialize(receipt)?],
+        )
+    }
+
+    fn load_receipts(&self) -> Result<Vec<StorageReceipt>> {
+        self.load_all("SELECT data FROM reward_receipts ORDER BY id")
+    }
+
+    fn compact_receipts(
+        &self,
+        aggregate: &AggregatedReceipt,
+        receipt_hashes: &[[u8; 32]],
+    ) -> Result<()> {
+        let storage_error = |e: rusqlite::Error| {
+            StorageNodeError::Storage(format!("Failed to compact receipts: {}", e))
+        };
+        let mut conn = self.connection()?;
+        let tx = conn.transaction().map_err(storage_error)?;
+
+        // Receipts are matched by hash, as their encoding is not canonical
+        let mut compacted = Vec::new();
+        {
+            let mut stmt = tx
+                .prepare("SELECT id, data FROM reward_receipts WHERE node_id = ?1")
+                .map_err(storage_error)?;
+            let rows = stmt
+                .query_map(params![aggregate.node_id], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })
+                .map_err(storage_error)?;
+            for row in rows {
+                let (id, data) = row.map_err(storage_error)?;
+                let receipt: StorageReceipt = bincode::deserialize(&data)?;
+                if receipt_hashes.contains(&receipt.receipt_hash) {
+                    compacted.push(id);
+                }
+            }
+        }
+
+        for id in compacted {
+            tx.execute("DELETE FROM reward_receipts WHERE id = ?1", params![id])
+                .map_err(storage_error)?;
+        }
+        tx.execute(
+            "INSERT INTO reward_aggregated_receipts (node_id, data) VALUES (?1, ?2)",
+            params![aggregate.node_id, bincode::serialize(aggregate)?],
+        )
+        .map_err(storage_error)?;
+
+        tx.commit().map_err(storage_error)
+    }
+
+    fn load_aggregated_receipts(&self) -> Result<Vec<AggregatedReceipt>> {
+        self.load_all("SELECT data FROM reward_aggregated_receipts ORDER BY id")
+    }
+
+    fn save_challenge_result(&self, result: &ChallengeResult) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_challenge_results (node_id, data) VALUES (?1, ?2)",
+            params![result.node_id, bincode::serialize(result)?],
+        )
+    }
+
+    fn load_challenge_results(&self) -> Result<Vec<ChallengeResult>> {
+        self.load_all("SELECT data FROM reward_challenge_results ORDER BY id")
+    }
+
+    fn save_outstanding_challenge(&self, challenge: &OutstandingChallenge) -> Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO reward_outstanding_challenges (challenge_id, data) \
+             VALUES (?1, ?2)",
+            params![
+                challenge.challenge.challenge_id,
+                bincode::serialize(challenge)?
+            ],
+        )
+    }
+
+    fn remove_outstanding_challenge(&self, challenge_id: &str) -> Result<()> {
+        self.execute(
+            "DELETE FROM reward_outstanding_challenges WHERE challenge_id = ?1",
+            params![challenge_id],
+        )
+    }
+
+    fn load_outstanding_challenges(&self) -> Result<Vec<OutstandingChallenge>> {
+        self.load_all("SELECT data FROM reward_outstanding_challenges")
+    }
+
+    fn save_vault(&self, metadata: &VaultMetadata) -> Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO reward_vaults (vault_id, data) VALUES (?1, ?2)",
+            params![metadata.vault_id, bincode::serialize(metadata)?],
+        )
+    }
+
+    fn load_vaults(&self) -> Result<Vec<VaultMetadata>> {
+        self.load_all("SELECT data FROM reward_vaults")
+    }
+
+    fn save_limbo_vault(&self, vault: &LimboVault) -> Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO reward_limbo_vaults (vault_id, data) VALUES (?1, ?2)",
+            params![vault.id, bincode::serialize(vault)?],
+        )
+    }
+
+    fn load_limbo_vaults(&self) -> Result<Vec<LimboVault>> {
+        self.load_all("SELECT data FROM reward_limbo_vaults")
+    }
+
+    fn save_distribution(&self, request: &DistributionRequest) -> Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO reward_distributions (vault_id, data) VALUES (?1, ?2)",
+            params![request.vault_id, bincode::serialize(request)?],
+        )
+    }
+
+    fn remove_distribution(&self, vault_id: &str) -> Result<()> {
+        self.execute(
+            "DELETE FROM reward_distributions WHERE vault_id = ?1",
+            params![vault_id],
+        )
+    }
+
+    fn load_distributions(&self) -> Result<Vec<DistributionRequest>> {
+        self.load_all("SELECT data FROM reward_distributions")
+    }
+
+    fn save_distribution_record(&self, record: &DistributionRecord) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_distribution_history (vault_id, data) VALUES (?1, ?2)",
+            params![record.vault_id, bincode::serialize(record)?],
+        )
+    }
+
+    fn load_distribution_history(&self) -> Result<Vec<DistributionRecord>> {
+        self.load_all("SELECT data FROM reward_distribution_history ORDER BY id")
+    }
+
+    fn save_failed_distribution(&self, failed: &FailedDistribution) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_failed_distributions (vault_id, data) VALUES (?1, ?2)",
+            params![failed.request.vault_id, bincode::serialize(failed)?],
+        )
+    }
+
+    fn load_failed_distributions(&self) -> Result<Vec<FailedDistribution>> {
+        self.load_all("SELECT data FROM reward_failed_distributions ORDER BY id")
+    }
+
+    fn save_rate_schedule(&self, effective_from: u64, schedule: &RateSchedule) -> Result<()> {
+        // SQLite integers are signed, so the exact time is kept in the data
+        self.execute(
+            "INSERT OR REPLACE INTO reward_rate_schedules (effective_from, data) VALUES (?1, ?2)",
+            params![
+                effective_from as i64,
+                bincode::serialize(&(effective_from, schedule))?
+            ],
+        )
+    }
+
+    fn load_rate_schedules(&self) -> Result<Vec<(u64, RateSchedule)>> {
+        self.load_all("SELECT data FROM reward_rate_schedules ORDER BY effective_from")
+    }
+
+    fn save_node_rate_override(
+        &self,
+        node_id: &str,
+        effective_from: u64,
+        schedule: Option<&RateSchedule>,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_node_rate_overrides (node_id, data) VALUES (?1, ?2)",
+            params![
+                node_id,
+                bincode::serialize(&(node_id, effective_from, schedule))?
+            ],
+        )
+    }
+
+    fn load_node_rate_overrides(&self) -> Result<Vec<(String, u64, Option<RateSchedule>)>> {
+        self.load_all("SELECT data FROM reward_node_rate_overrides ORDER BY id")
+    }
+
+    fn save_audit_record(&self, record: &AuditRecord) -> Result<()> {
+        // A plain insert, so a record can never overwrite one already logged
+        self.execute(
+            "INSERT INTO reward_audit_log (sequence, data) VALUES (?1, ?2)",
+            params![record.sequence as i64, bincode::serialize(record)?],
+        )
+    }
+
+    fn load_audit_records(&self) -> Result<Vec<AuditRecord>> {
+        self.load_all("SELECT data FROM reward_audit_log ORDER BY sequence")
+    }
+
+    fn save_carryover(&self, carryover: &HashMap<String, u64>) -> Result<()> {
+        // A single row, so the shares of every node are replaced together
+        self.execute(
+            "INSERT OR REPLACE INTO reward_carryover (id, data) VALUES (0, ?1)",
+            params![bincode::serialize(carryover)?],
+        )
+    }
+
+    fn load_carryover(&self) -> Result<HashMap<String, u64>> {
+        Ok(self
+            .load_all("SELECT data FROM reward_carryover")?
+            .pop()
+            .unwrap_or_default())
+    }
+
+    fn save_executed_proposal(&self, proposal_id: &str) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_executed_proposals (proposal_id, data) VALUES (?1, ?2)",
+            params![proposal_id, bincode::serialize(proposal_id)?],
+        )
+    }
+
+    fn load_executed_proposals(&self) -> Result<Vec<String>> {
+        self.load_all("SELECT data FROM reward_executed_proposals")
+    }
+
+    fn save_identity_key(&self, id: &str, public_key: &[u8]) -> Result<()> {
+        self.execute(
+            "INSERT INTO reward_identity_keys (id, data) VALUES (?1, ?2)",
+            params![id, bincode::serialize(&(id, public_key))?],
+        )
+    }
+
+    fn load_identity_keys(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        self.load_all("SELECT data FROM reward_identity_keys")
+    }
+}
@@ -5,24 +5,109 @@
 // It maintains cryptographic guarantees and bilateral state isolation while
 // providing a mechanism for secure custody of funds pending distribution.
 
+use crate::crypto::verify_signature_or_false;
 use crate::error::{Result, StorageNodeError};
+use crate::staking::audit_log::{AuditLog, AuditRecord};
+use crate::staking::claims::{claim_signing_hash, ClaimVoucher};
+use crate::staking::dispute::{Dispute, DisputeClaim, DisputeResolution, ProposedDistribution};
+use crate::staking::geolocation::{is_region_proven, VerifiedRegion};
+use crate::staking::governance::{ChallengeScoring, SlashedRewards, SlashingPolicy};
+use crate::staking::heartbeat::HeartbeatMonitor;
+use crate::staking::payout::{DistributionExecutor, Payout, PayoutStatus, PayoutTransfer};
+use crate::staking::price_feed::PriceFeed;
+use crate::staking::receipt_aggregate::AggregatedReceipt;
+use crate::staking::receipt_exchange::{
+    sealed_receipt_hash, ReceiptChallenge, TWO_PHASE_RECEIPT_VERSION,
+};
+use crate::staking::report::{ReportFormat, ReportRecordType, ReportRow, ReportWriter};
+use crate::staking::reward_store::RewardStore;
 // Remove unused imports
 // Remove unused import
 use dsm::types::state_types::State;
+use dsm::types::token_types::{self, TokenRegistry};
 // Remove unused import
-use dsm::vault::{DLVManager, FulfillmentMechanism, FulfillmentProof, VaultPost};
-
-use std::collections::{HashMap, HashSet};
+use dsm::vault::{
+    DLVManager, FulfillmentMechanism, FulfillmentProof, LimboVault, SplitAllocation, VaultPost,
+    VaultSplit, VaultState,
+};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::ops::Range;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, watch, Notify};
+use tokio::task::JoinHandle;
 use tokio::time::interval;
-use tracing::error;
+use tracing::{error, info, warn};
+
+/// How often the distribution processor checks for vaults that are due
+const DISTRIBUTION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Distribution records buffered for each live subscriber
+const DISTRIBUTION_CHANNEL_CAPACITY: usize = 100;
+
+/// Proposed distributions buffered for each live subscriber
+const PROPOSAL_CHANNEL_CAPACITY: usize = 100;
+
+/// Retry time of a distribution frozen by a dispute; resolving it resumes them
+const FROZEN_DISTRIBUTION: u64 = u64::MAX;
+
+/// Attempts at a distribution before it is given up on, by default
+const DEFAULT_MAX_DISTRIBUTION_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry of a failed distribution, by default
+const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_secs(60);
+
+/// Longest delay between retries of a failed distribution, by default
+const DEFAULT_RETRY_MAX_DELAY: Duration = Duration::from_secs(3600);
+
+/// Default time shutdown waits for in-flight distributions
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Token in which rewards are paid
+pub const REWARD_TOKEN_ID: &str = "ROOT";
+
+/// Reference currency that rewards track when a price feed is configured
+pub const REWARD_QUOTE_CURRENCY: &str = "USD";
+
+/// Fixed-point scale of a `Ratio` (6 decimal places)
+const RATIO_SCALE: u64 = 1_000_000;
+
+/// Largest deviation from 1.0 allowed where ratios must sum to 1.0 (1%)
+///
+/// Ratios converted from percentages or floats are rounded, so a split that
+/// is meant to be whole can come out slightly above or below 1.0.
+const RATIO_SUM_TOLERANCE: u64 = 10_000;
+
+/// Seconds in a day, the unit of the storage rate
+const SECONDS_PER_DAY: u64 = 86400;
+
+/// Simulator claimant key for unlocking vaults (in production this would be a secure key)
+const CLAIMANT_KEY: [u8; 4] = [1, 2, 3, 4];
+
+/// Status of a claimable vault once its content is released to its recipients
+const CLAIMABLE_STATUS: &str = "claimable";
+
+/// Status of a vault revoked by its creator before it was distributed
+const REVOKED_STATUS: &str = "revoked";
+
+/// Default ceiling on any single region multiplier (2.0)
+const DEFAULT_MAX_REGION_MULTIPLIER: Ratio = Ratio(2 * RATIO_SCALE);
+
+/// Default percentage points a receipt's uptime may exceed the measured uptime
+const DEFAULT_UPTIME_TOLERANCE: u8 = 5;
 
 /// Cryptographically secure receipt for storage services
 /// This establishes proof of service delivery without requiring global consensus
+///
+/// Receipts are produced by the two-phase exchange in
+/// [`crate::staking::receipt_exchange`]: the node signs a challenge over the
+/// terms and the client seals it with its own signature.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageReceipt {
     /// Storage node ID that provided the service
@@ -37,16 +122,69 @@ pub struct StorageReceipt {
     /// Storage metrics (bytes, operations, etc.)
     pub storage_metrics: StorageMetrics,
 
-    /// Receipt hash (deterministic from other fields)
+    /// Client's SPHINCS+ public key
+    pub client_public_key: Vec<u8>,
+
+    /// Node's SPHINCS+ public key
+    pub node_public_key: Vec<u8>,
+
+    /// Receipt format version (0 = signed by each party in turn)
+    #[serde(default)]
+    pub version: u8,
+
+    /// When the node issued the challenge the receipt was sealed from
+    #[serde(default)]
+    pub challenge_issued_at: u64,
+
+    /// TTL of the challenge the receipt was sealed from (seconds)
+    #[serde(default)]
+    pub challenge_ttl_secs: u64,
+
+    /// Nonce of the challenge the receipt was sealed from
+    #[serde(default)]
+    pub challenge_nonce: [u8; 32],
+
+    /// Receipt hash: `blake3(challenge_hash || client_signature)`
     pub receipt_hash: [u8; 32],
 
-    /// Client's signature attesting to service
+    /// Client's signature over the challenge hash, attesting to service
     pub client_signature: Vec<u8>,
 
-    /// Node's signature affirming delivery
+    /// Node's signature over the challenge hash, affirming delivery
     pub node_signature: Vec<u8>,
 }
 
+impl StorageReceipt {
+    /// Rebuild the challenge the receipt was sealed from
+    pub fn challenge(&self) -> Result<ReceiptChallenge> {
+        let mut challenge = ReceiptChallenge {
+            node_id: self.node_id.clone(),
+            client_id: self.client_id.clone(),
+            service_period: self.service_period,
+            storage_metrics: self.storage_metrics.clone(),
+            client_public_key: self.client_public_key.clone(),
+            node_public_key: self.node_public_key.clone(),
+            issued_at: self.challenge_issued_at,
+            challenge_ttl_secs: self.challenge_ttl_secs,
+            nonce: self.challenge_nonce,
+            challenge_hash: [0u8; 32],
+            node_signature: self.node_signature.clone(),
+        };
+        challenge.challenge_hash = challenge.compute_hash()?;
+
+        Ok(challenge)
+    }
+
+    /// Compute the receipt hash from the challenge terms and the client's signature
+    pub fn compute_hash(&self) -> Result<[u8; 32]> {
+        let challenge = self.challenge()?;
+        Ok(sealed_receipt_hash(
+            &challenge.challenge_hash,
+            &self.client_signature,
+        ))
+    }
+}
+
 /// Storage service metrics for reward calculation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageMetrics {
@@ -59,26 +197,129 @@ pub struct StorageMetrics {
     /// Number of operations processed
     pub operations_count: u64,
 
-    /// Uptime percentage (0-100)
+    /// Uptime percentage (0-100), from the heartbeat monitor
     pub uptime_percentage: u8,
 
-    /// Geographic regions served
-    pub regions: HashSet<String>,
+    /// Geographic regions served, each with the proof that the node serves there
+    pub regions: HashSet<VerifiedRegion>,
+
+    /// Storage challenges the node answered during the service period
+    #[serde(default)]
+    pub challenge_results: ChallengeSummary,
+}
+
+/// Outcome of a storage challenge issued to a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeResult {
+    /// Node that was challenged
+    pub node_id: String,
+
+    /// Whether the node proved it still holds the challenged data
+    pub passed: bool,
+
+    /// When the challenge was answered (Unix seconds)
+    pub timestamp: u64,
+}
+
+/// Number of storage challenges a node passed and failed over a period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeSummary {
+    /// Challenges answered with a valid proof
+    pub passed: u64,
+
+    /// Challenges answered with a wrong proof or not answered in time
+    pub failed: u64,
+}
+
+impl ChallengeSummary {
+    /// Summarize the results answered in `[period_start, period_end)`
+    pub fn for_period<'a>(
+        results: impl IntoIterator<Item = &'a ChallengeResult>,
+        (period_start, period_end): (u64, u64),
+    ) -> Self {
+        let mut summary = Self::default();
+        for result in results {
+            if result.timestamp < period_start || result.timestamp >= period_end {
+                continue;
+            }
+            if result.passed {
+                summary.passed += 1;
+            } else {
+                summary.failed += 1;
+            }
+        }
+        summary
+    }
+
+    /// Add the results of another summary
+    pub fn merge(&mut self, other: &ChallengeSummary) {
+        self.passed = self.passed.saturating_add(other.passed);
+        self.failed = self.failed.saturating_add(other.failed);
+    }
+
+    /// Share of challenges passed (1.0 when no challenge was issued)
+    pub fn pass_rate(&self) -> Ratio {
+        let total = self.passed.saturating_add(self.failed);
+        if total == 0 {
+            return Ratio::ONE;
+        }
+        Ratio((self.passed as u128 * RATIO_SCALE as u128 / total as u128) as u64)
+    }
 }
 
 /// Payment distribution ratio for reward allocation
 /// Uses fixed-point arithmetic with 6 decimal precision
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+///
+/// Also serves as a reward multiplier, which may exceed 1.0. Deserializing
+/// rejects ratios above 1.0; multiplier fields opt out with
+/// `deserialize_with = "deserialize_multiplier"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Ratio(u64);
 
 impl Ratio {
-    /// Create a new ratio from a float (0.0 - 1.0)
-    pub fn new(value: f64) -> Self {
-        assert!(
-            (0.0..=1.0).contains(&value),
-            "Ratio must be between 0.0 and 1.0"
-        );
-        Self((value * 1_000_000.0) as u64)
+    /// A ratio of 1.0
+    pub const ONE: Ratio = Ratio(RATIO_SCALE);
+
+    /// Create a ratio from a float between 0.0 and 1.0
+    pub fn try_new(value: f64) -> Result<Self> {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(StorageNodeError::Staking(format!(
+                "Invalid ratio {}: must be between 0.0 and 1.0",
+                value
+            )));
+        }
+        Ok(Self((value * RATIO_SCALE as f64).round() as u64))
+    }
+
+    /// Create the ratio `numerator / denominator`, rounding down
+    ///
+    /// The fraction must be between 0 and 1.
+    pub fn from_parts(numerator: u64, denominator: u64) -> Result<Self> {
+        if denominator == 0 || numerator > denominator {
+            return Err(StorageNodeError::Staking(format!(
+                "Invalid ratio {}/{}: must be between 0 and 1",
+                numerator, denominator
+            )));
+        }
+        let raw = numerator as u128 * RATIO_SCALE as u128 / denominator as u128;
+        Ok(Self(raw as u64))
+    }
+
+    /// Create a multiplier from a float, which unlike a ratio may exceed 1.0
+    pub fn multiplier(value: f64) -> Result<Self> {
+        let raw = value * RATIO_SCALE as f64;
+        if !raw.is_finite() || raw < 0.0 || raw >= u64::MAX as f64 {
+            return Err(StorageNodeError::Staking(format!(
+                "Invalid multiplier {}: must be a non-negative number",
+                value
+            )));
+        }
+        Ok(Self(raw.round() as u64))
+    }
+
+    /// Create a ratio from a percentage (100 = 1.0)
+    pub fn from_percentage(percentage: u8) -> Self {
+        Self(percentage as u64 * (RATIO_SCALE / 100))
     }
 
     /// Get the raw value
@@ -88,15 +329,131 @@ pub fn raw_value(&self) -> u64 {
 
     /// Convert to float
     pub fn as_f64(&self) -> f64 {
-        self.0 as f64 / 1_000_000.0
+        self.0 as f64 / RATIO_SCALE as f64
     }
 
-    /// Apply this ratio to a value
+    /// Apply this ratio to a value, rounding down
+    ///
+    /// Saturates at `u64::MAX` for multipliers above 1.0; use
+    /// [`Self::checked_apply_to`] where that must be an error.
     pub fn apply_to(&self, value: u64) -> u64 {
-        ((value as u128 * self.0 as u128) / 1_000_000) as u64
+        let scaled = value as u128 * self.0 as u128 / RATIO_SCALE as u128;
+        u64::try_from(scaled).unwrap_or(u64::MAX)
+    }
+
+    /// Apply this ratio to a value, rounding down, failing on overflow
+    pub fn checked_apply_to(&self, value: u64) -> Result<u64> {
+        let scaled = value as u128 * self.0 as u128 / RATIO_SCALE as u128;
+        u64::try_from(scaled).map_err(|_| {
+            StorageNodeError::Staking(format!(
+                "Reward overflow applying ratio {} to {}",
+                self.as_f64(),
+                value
+            ))
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Ratio {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let raw = u64::deserialize(deserializer)?;
+        if raw > RATIO_SCALE {
+            return Err(serde::de::Error::custom(format!(
+                "ratio {} is above 1.0 ({})",
+                raw, RATIO_SCALE
+            )));
+        }
+        Ok(Self(raw))
+    }
+}
+
+/// Deserialize a multiplier, which unlike a ratio may exceed 1.0
+fn deserialize_multiplier<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Ratio, D::Error> {
+    u64::deserialize(deserializer).map(Ratio)
+}
+
+/// Deserialize a map of multipliers, which unlike ratios may exceed 1.0
+fn deserialize_multipliers<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, Ratio>, D::Error> {
+    let raw = HashMap::<String, u64>::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .map(|(key, value)| (key, Ratio(value)))
+        .collect())
+}
+
+/// How the multipliers of a node's regions combine into one
+///
+/// Multiplying every matching multiplier would reward nodes for claiming as
+/// many regions as possible, so by default only the largest one applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionMultiplierStrategy {
+    /// The largest matching multiplier
+    #[default]
+    Max,
+
+    /// The mean of the matching multipliers, rounded down
+    Mean,
+
+    /// The product of the matching multipliers, at most `cap`
+    Product {
+        #[serde(deserialize_with = "deserialize_multiplier")]
+        cap: Ratio,
+    },
+}
+
+impl RegionMultiplierStrategy {
+    /// Combine the multipliers of a node's regions; 1.0 if there are none
+    ///
+    /// `multipliers` must be in a fixed order, as the product rounds down
+    /// after each factor.
+    pub fn combine(&self, multipliers: &[Ratio]) -> Result<Ratio> {
+        if multipliers.is_empty() {
+            return Ok(Ratio::ONE);
+        }
+
+        match self {
+            RegionMultiplierStrategy::Max => Ok(multipliers
+                .iter()
+                .copied()
+                .max_by_key(Ratio::raw_value)
+                .unwrap_or(Ratio::ONE)),
+            RegionMultiplierStrategy::Mean => {
+                let sum: u128 = multipliers.iter().map(|m| m.raw_value() as u128).sum();
+                Ok(Ratio((sum / multipliers.len() as u128) as u64))
+            }
+            RegionMultiplierStrategy::Product { cap } => {
+                let mut product = Ratio::ONE;
+                for multiplier in multipliers {
+                    product = Ratio(multiplier.checked_apply_to(product.raw_value())?);
+                }
+                Ok(if product.raw_value() > cap.raw_value() {
+                    *cap
+                } else {
+                    product
+                })
+            }
+        }
     }
 }
 
+/// Region multiplier applied to a reward, and how it was combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedRegionMultiplier {
+    /// Strategy of the rate schedule
+    pub strategy: RegionMultiplierStrategy,
+
+    /// Multiplier the strategy produced for the node's regions
+    #[serde(deserialize_with = "deserialize_multiplier")]
+    pub multiplier: Ratio,
+}
+
 /// Rate schedule for reward calculations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateSchedule {
@@ -110,34 +467,292 @@ pub struct RateSchedule {
     pub operation_rate: u64,
 
     /// Multiplier for uptime percentage
-    pub uptime_multiplier: f64,
+    #[serde(deserialize_with = "deserialize_multiplier")]
+    pub uptime_multiplier: Ratio,
 
     /// Region-specific multipliers
-    pub region_multipliers: HashMap<String, f64>,
+    #[serde(deserialize_with = "deserialize_multipliers")]
+    pub region_multipliers: HashMap<String, Ratio>,
+
+    /// How the multipliers of a node's regions combine
+    #[serde(default)]
+    pub region_strategy: RegionMultiplierStrategy,
 }
 
 impl RateSchedule {
     /// Calculate reward based on service metrics
-    pub fn calculate(&self, duration_secs: u64, bytes: u64, retrievals: u64) -> u64 {
-        // Convert seconds to days (86400 seconds in a day)
-        let days = duration_secs as f64 / 86400.0;
+    pub fn calculate(&self, duration_secs: u64, metrics: &StorageMetrics) -> Result<u64> {
+        Ok(self.breakdown(duration_secs, metrics)?.total)
+    }
 
-        // Calculate storage component
-        let storage_reward = (self.base_rate_per_byte_day as f64 * bytes as f64 * days) as u64;
+    /// Calculate reward based on service metrics, itemised by component
+    ///
+    /// Uses integer arithmetic only, rounding down after each multiplier, so
+    /// every platform computes the same reward. Fails if any amount does
+    /// not fit in a `u64`.
+    pub fn breakdown(
+        &self,
+        duration_secs: u64,
+        metrics: &StorageMetrics,
+    ) -> Result<RewardBreakdown> {
+        // Calculate storage component, pro rata over the day
+        let storage_reward = (self.base_rate_per_byte_day as u128)
+            .checked_mul(metrics.bytes_stored as u128)
+            .and_then(|reward| reward.checked_mul(duration_secs as u128))
+            .map(|reward| reward / SECONDS_PER_DAY as u128)
+            .and_then(|reward| u64::try_from(reward).ok())
+            .ok_or_else(|| reward_overflow("storage reward"))?;
 
         // Calculate retrieval component
-        let retrieval_reward = self.retrieval_rate * retrievals;
+        let retrieval_reward = self
+            .retrieval_rate
+            .checked_mul(metrics.retrievals)
+            .ok_or_else(|| reward_overflow("retrieval reward"))?;
+
+        // Calculate operation component
+        let operation_reward = self
+            .operation_rate
+            .checked_mul(metrics.operations_count)
+            .ok_or_else(|| reward_overflow("operation reward"))?;
+
+        let base_reward = storage_reward
+            .checked_add(retrieval_reward)
+            .and_then(|reward| reward.checked_add(operation_reward))
+            .ok_or_else(|| reward_overflow("base reward"))?;
+
+        // Apply uptime and the uptime multiplier
+        let uptime = Ratio::from_percentage(metrics.uptime_percentage);
+        let mut total = self
+            .uptime_multiplier
+            .checked_apply_to(uptime.checked_apply_to(base_reward)?)?;
+
+        // Combine region multipliers in a fixed order, as a product rounds
+        // down, counting a region claimed with several proofs once
+        let mut regions: Vec<&String> = metrics.regions.iter().map(|(region, _)| region).collect();
+        regions.sort();
+        regions.dedup();
+        let multipliers: Vec<Ratio> = regions
+            .into_iter()
+            .filter_map(|region| self.region_multipliers.get(region).copied())
+            .collect();
+        let region_multiplier = self.region_strategy.combine(&multipliers)?;
+        total = region_multiplier.checked_apply_to(total)?;
+
+        Ok(RewardBreakdown {
+            storage_reward,
+            retrieval_reward,
+            operation_reward,
+            slashed: 0,
+            redistributed: 0,
+            total,
+            region_multiplier: Some(AppliedRegionMultiplier {
+                strategy: self.region_strategy,
+                multiplier: region_multiplier,
+            }),
+            segments: Vec::new(),
+        })
+    }
+
+    /// Check that no region multiplier exceeds `ceiling`
+    ///
+    /// The cap of a product strategy counts as a multiplier.
+    pub fn validate_region_multipliers(&self, ceiling: Ratio) -> Result<()> {
+        let cap = match self.region_strategy {
+            RegionMultiplierStrategy::Product { cap } => Some(("product cap", &cap)),
+            _ => None,
+        };
+        let multipliers = self
+            .region_multipliers
+            .iter()
+            .map(|(region, multiplier)| (region.as_str(), multiplier))
+            .chain(cap);
+
+        for (region, multiplier) in multipliers {
+            if multiplier.raw_value() > ceiling.raw_value() {
+                return Err(StorageNodeError::InvalidInput(format!(
+                    "Region multiplier {} for {} exceeds the ceiling of {}",
+                    multiplier.as_f64(),
+                    region,
+                    ceiling.as_f64()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error for a reward amount that does not fit in a `u64`
+fn reward_overflow(component: &str) -> StorageNodeError {
+    StorageNodeError::Staking(format!("Reward overflow calculating {}", component))
+}
+
+/// Reward for a period, itemised by component
+///
+/// The components are the amounts earned before the uptime and region
+/// multipliers; `total` is the reward after them, less the amount slashed
+/// and plus the share of other nodes' slashed rewards redistributed to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardBreakdown {
+    /// Reward for bytes stored over time
+    pub storage_reward: u64,
+
+    /// Reward for retrievals served
+    pub retrieval_reward: u64,
+
+    /// Reward for operations processed
+    pub operation_reward: u64,
+
+    /// Reward deducted under the slashing policy
+    pub slashed: u64,
+
+    /// Share of other nodes' slashed rewards paid to this node
+    pub redistributed: u64,
+
+    /// Reward after multipliers, slashing and redistribution
+    pub total: u64,
+
+    /// Region multiplier applied; for a reward over several receipts or
+    /// schedules, the largest applied to any of them (None = nothing rewarded)
+    #[serde(default)]
+    pub region_multiplier: Option<AppliedRegionMultiplier>,
+
+    /// Rate schedule applied to each receipt segment, in the order rewarded
+    pub segments: Vec<ScheduleSegment>,
+}
 
-        // Total reward
-        storage_reward + retrieval_reward
+impl RewardBreakdown {
+    /// Sum of the components before multipliers
+    pub fn base_reward(&self) -> u64 {
+        self.storage_reward
+            .saturating_add(self.retrieval_reward)
+            .saturating_add(self.operation_reward)
     }
+
+    /// Add another breakdown's amounts to this one
+    pub fn accumulate(&mut self, other: &RewardBreakdown) -> Result<()> {
+        let add = |a: u64, b: u64, component: &str| {
+            a.checked_add(b).ok_or_else(|| reward_overflow(component))
+        };
+
+        self.storage_reward = add(self.storage_reward, other.storage_reward, "storage reward")?;
+        self.retrieval_reward = add(
+            self.retrieval_reward,
+            other.retrieval_reward,
+            "retrieval reward",
+        )?;
+        self.operation_reward = add(
+            self.operation_reward,
+            other.operation_reward,
+            "operation reward",
+        )?;
+        self.slashed = add(self.slashed, other.slashed, "slashed reward")?;
+        self.redistributed = add(
+            self.redistributed,
+            other.redistributed,
+            "redistributed reward",
+        )?;
+        self.total = add(self.total, other.total, "total reward")?;
+        self.region_multiplier = match (self.region_multiplier, other.region_multiplier) {
+            (Some(own), Some(theirs)) => [own, theirs]
+                .into_iter()
+                .max_by_key(|applied| applied.multiplier.raw_value()),
+            (own, theirs) => own.or(theirs),
+        };
+        self.segments.extend(other.segments.iter().cloned());
+
+        Ok(())
+    }
+}
+
+/// Part of a receipt's service period rewarded under a single rate schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleSegment {
+    /// Hash of the receipt
+    pub receipt_hash: [u8; 32],
+
+    /// Start of the segment
+    pub start: u64,
+
+    /// End of the segment (exclusive)
+    pub end: u64,
+
+    /// Rate schedule applied to the segment
+    pub source: ScheduleSource,
+
+    /// Reward for the segment after multipliers, before slashing
+    pub reward: u64,
+}
+
+/// Version of a rate schedule applied to a segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleSource {
+    /// Global schedule that took effect at `effective_from`
+    Global { effective_from: u64 },
+
+    /// Rate override for the node that took effect at `effective_from`
+    Override { effective_from: u64 },
+}
+
+/// Reward a node has accrued since the last distribution, not yet paid out
+///
+/// Computed like [`RewardVaultManager::calculate_node_rewards`] over the open
+/// period; the amount may still change through slashing, redistribution or
+/// late receipts before the period is distributed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardEstimate {
+    /// Node the estimate is for
+    pub node_id: String,
+
+    /// Start of the open period (the last distribution, or 0 before any)
+    pub period_start: u64,
+
+    /// When the estimate was made
+    pub period_end: u64,
+
+    /// Always true: the amounts are not final until distributed
+    pub is_estimate: bool,
+
+    /// Reward accrued in the open period, in tokens
+    pub breakdown: RewardBreakdown,
+
+    /// Shares below the minimum payout still owed to the node
+    pub carryover: u64,
+
+    /// Accrued reward plus carryover, scaled by the token price if there is a feed
+    pub estimated_reward: u64,
+}
+
+/// State an open-period breakdown was computed from
+///
+/// A cached breakdown is reused while none of it changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EstimateKey {
+    period_start: u64,
+    receipt_count: usize,
+    aggregate_count: usize,
+    challenge_count: usize,
+    schedule_count: usize,
+    override_count: usize,
+    policy: SlashingPolicy,
 }
 
 /// Storage node reward vault manager
 ///
 /// This component integrates with the DSM core's Deterministic Limbo Vault system
 /// to provide cryptographically secure custody of rewards before distribution.
+///
+/// Clones are handles to the same manager: receipts, vaults, the
+/// distribution queue and its results are shared between them, and the
+/// distribution processor stops once the last handle is dropped.
+#[derive(Clone)]
 pub struct RewardVaultManager {
+    /// State shared by every handle
+    inner: Arc<RewardManagerInner>,
+}
+
+/// State of a reward vault manager, shared by all its handles
+struct RewardManagerInner {
     /// Reference to the DLV manager from DSM core
     dlv_manager: Arc<DLVManager>,
 
@@ -147,16 +762,153 @@ pub struct RewardVaultManager {
     /// Receipt registry for service validation
     receipt_registry: RwLock<HashMap<String, Vec<StorageReceipt>>>,
 
-    /// Rate schedule for reward calculations
-    rate_schedule: RwLock<RateSchedule>,
+    /// Aggregates of settled receipts by node ID, oldest first
+    aggregate_registry: RwLock<HashMap<String, Vec<AggregatedReceipt>>>,
+
+    /// Storage challenge results by node ID
+    challenge_registry: RwLock<HashMap<String, Vec<ChallengeResult>>>,
+
+    /// Rate schedules with the time each takes effect, sorted by that time
+    rate_schedules: RwLock<Vec<(u64, RateSchedule)>>,
+
+    /// Negotiated rates by node ID with the time each takes effect, sorted by
+    /// that time; `None` ends an override
+    node_rate_overrides: RwLock<HashMap<String, Vec<(u64, Option<RateSchedule>)>>>,
+
+    /// Slashing policy set by governance
+    slashing_policy: RwLock<SlashingPolicy>,
+
+    /// SPHINCS+ public keys of governance voters by genesis hash
+    governance_keys: RwLock<HashMap<String, Vec<u8>>>,
+
+    /// IDs of the governance proposals already executed
+    executed_proposals: RwLock<HashSet<String>>,
+
+    /// SPHINCS+ public keys of nodes and clients by ID, pinned on registration
+    identity_keys: RwLock<HashMap<String, Vec<u8>>>,
+
+    /// Pending distributions queue, shared with the distribution processor
+    distribution_queue: Arc<Mutex<Vec<DistributionRequest>>>,
+
+    /// Durable copy of the registries and queue (None = in memory only)
+    store: Option<Arc<dyn RewardStore>>,
+
+    /// Token price source for scaling rewards (None = fixed token rates)
+    price_feed: Option<Arc<dyn PriceFeed>>,
+
+    /// Signed log of paid-out distributions (None = not audited)
+    audit_log: Option<Arc<AuditLog>>,
+
+    /// Transfers the tokens of distributions (None = amounts are only recorded)
+    executor: Option<Arc<dyn DistributionExecutor>>,
+
+    /// Measured node uptime to check receipts against (None = taken as claimed)
+    heartbeat_monitor: Option<Arc<HeartbeatMonitor>>,
+
+    /// SPHINCS+ public key of the geolocation oracle whose region proofs
+    /// count (None = no region multiplier applies)
+    region_oracle_key: Option<Vec<u8>>,
+
+    /// Metadata of the tokens rewards are paid in, for report decimals
+    token_registry: TokenRegistry,
 
-    /// Pending distributions queue
-    distribution_queue: Mutex<Vec<DistributionRequest>>,
+    /// Processing settings
+    config: RewardManagerConfig,
+
+    /// Wakes the distribution processor ahead of its next check
+    check_now: Arc<Notify>,
+
+    /// Distributions that failed permanently, oldest first
+    failed_distributions: RwLock<Vec<FailedDistribution>>,
+
+    /// Shares below the minimum payout owed to nodes (node_id -> amount)
+    carryover: Mutex<HashMap<String, u64>>,
+
+    /// Outcome of every processed distribution, oldest first
+    distribution_history: RwLock<Vec<DistributionRecord>>,
+
+    /// Open-period breakdowns by node ID, with the state they were computed from
+    estimate_cache: Mutex<HashMap<String, (EstimateKey, RewardBreakdown)>>,
+
+    /// Live feed of distribution records
+    distribution_tx: broadcast::Sender<DistributionRecord>,
+
+    /// Live feed of distributions proposed for dispute
+    proposal_tx: broadcast::Sender<ProposedDistribution>,
+
+    /// Signals the distribution processor to stop
+    shutdown_tx: watch::Sender<bool>,
+
+    /// Running distribution processor task
+    processor: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Reward vault manager settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardManagerConfig {
+    /// Interval between distribution processor checks
+    pub check_interval: Duration,
+
+    /// How failed distributions are retried
+    pub retry_policy: DistributionRetryPolicy,
+
+    /// Time shutdown waits for in-flight distributions
+    pub shutdown_timeout: Duration,
+
+    /// Slashing policy in force until governance replaces it
+    #[serde(default)]
+    pub slashing_policy: SlashingPolicy,
+
+    /// Time after the distribution time during which nodes can dispute the
+    /// split of vaults created from then on (zero = no dispute window)
+    #[serde(default)]
+    pub dispute_window: Duration,
+
+    /// Smallest share paid to a node; smaller shares are carried over and
+    /// added to the node's share of its next distribution (zero = pay all)
+    #[serde(default)]
+    pub min_payout: u64,
+
+    /// Aggregate and prune the receipts of a vault's recipients once the
+    /// vault has been distributed
+    #[serde(default)]
+    pub compact_receipts: bool,
+
+    /// Largest region multiplier a rate schedule may set
+    #[serde(
+        default = "default_max_region_multiplier",
+        deserialize_with = "deserialize_multiplier"
+    )]
+    pub max_region_multiplier: Ratio,
+
+    /// Percentage points the uptime in a receipt may exceed the uptime
+    /// measured by the heartbeat monitor
+    #[serde(default = "default_uptime_tolerance")]
+    pub uptime_tolerance: u8,
+}
+
+fn default_max_region_multiplier() -> Ratio {
+    DEFAULT_MAX_REGION_MULTIPLIER
+}
+
+fn default_uptime_tolerance() -> u8 {
+    DEFAULT_UPTIME_TOLERANCE
+}
 
-    /// Distribution channel
-    distribution_tx: mpsc::Sender<DistributionResult>,
-    #[allow(dead_code)]
-    distribution_rx: Mutex<mpsc::Receiver<DistributionResult>>,
+impl Default for RewardManagerConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: DISTRIBUTION_CHECK_INTERVAL,
+            retry_policy: DistributionRetryPolicy::default(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            slashing_policy: SlashingPolicy::default(),
+            dispute_window: Duration::ZERO,
+            min_payout: 0,
+            compact_receipts: false,
+            max_region_multiplier: DEFAULT_MAX_REGION_MULTIPLIER,
+            uptime_tolerance: DEFAULT_UPTIME_TOLERANCE,
+        }
+    }
 }
 
 /// Metadata for tracking vaults
@@ -188,571 +940,5940 @@ pub struct VaultMetadata {
 
     /// Current vault status
     pub status: String,
+
+    /// Time after the distribution time during which the split can be disputed
+    #[serde(default)]
+    pub dispute_window: Duration,
+
+    /// Split published when the distribution time arrived
+    #[serde(default)]
+    pub proposal: Option<ProposedDistribution>,
+
+    /// Disputes filed against the proposal, oldest first
+    #[serde(default)]
+    pub disputes: Vec<Dispute>,
+
+    /// Whether recipients claim their own shares instead of being paid at once
+    #[serde(default)]
+    pub pull_claims: bool,
+
+    /// Amounts claimed so far (node_id -> amount)
+    #[serde(default)]
+    pub claims: HashMap<String, u64>,
+
+    /// Why the creator revoked the vault (None = not revoked)
+    #[serde(default)]
+    pub revocation_reason: Option<String>,
+
+    /// Transfers of the distributed vault by node ID (empty = not paid out
+    /// by an executor)
+    #[serde(default)]
+    pub payouts: BTreeMap<String, Payout>,
 }
 
 /// Request for distribution
-#[derive(Debug, Clone)]
-struct DistributionRequest {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionRequest {
     /// Vault ID to distribute
-    vault_id: String,
+    pub vault_id: String,
 
     /// Reference state for vault operations
-    reference_state: State,
+    pub reference_state: State,
 
     /// Distribution timestamp
-    timestamp: u64,
+    pub timestamp: u64,
+
+    /// Number of failed attempts so far
+    pub attempts: u32,
+
+    /// Earliest time of the next attempt after a failure (0 = not retried yet)
+    pub retry_at: u64,
+
+    /// Error of the last failed attempt
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl DistributionRequest {
+    /// When the request is next ready to be processed
+    pub fn due_at(&self) -> u64 {
+        self.timestamp.max(self.retry_at)
+    }
 }
 
 /// Result of a distribution
-#[derive(Debug, Clone)]
-struct DistributionResult {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionResult {
     /// Vault ID that was distributed
-    #[allow(dead_code)]
-    vault_id: String,
+    pub vault_id: String,
 
     /// Success or failure
-    #[allow(dead_code)]
-    success: bool,
+    pub success: bool,
 
     /// Distribution timestamp
-    #[allow(dead_code)]
-    timestamp: u64,
+    pub timestamp: u64,
 
     /// Error message if failed
-    #[allow(dead_code)]
-    error: Option<String>,
+    pub error: Option<String>,
 
     /// Distribution details if successful
-    #[allow(dead_code)]
-    distribution_details: Option<HashMap<String, u64>>,
-}
+    pub distribution_details: Option<HashMap<String, u64>>,
 
-impl RewardVaultManager {
-    /// Create a new reward vault manager
-    pub fn new(dlv_manager: Arc<DLVManager>) -> Self {
-        // Create the distribution channel
-        let (tx, rx) = mpsc::channel(100);
+    /// Whether a failed distribution may succeed on a later attempt
+    pub retryable: bool,
+
+    /// Time until which the distribution was put off rather than attempted
+    #[serde(default)]
+    pub deferred_until: Option<u64>,
 
+    /// State number of the transfer to each recipient (node_id -> state number)
+    #[serde(default)]
+    pub transfer_states: HashMap<String, u64>,
+}
+
+impl DistributionResult {
+    /// Result of a distribution that did not go through
+    fn failure(vault_id: String, timestamp: u64, error: String, retryable: bool) -> Self {
         Self {
-            dlv_manager,
-            vault_registry: RwLock::new(HashMap::new()),
-            receipt_registry: RwLock::new(HashMap::new()),
-            rate_schedule: RwLock::new(Self::default_rate_schedule()),
-            distribution_queue: Mutex::new(Vec::new()),
-            distribution_tx: tx,
-            distribution_rx: Mutex::new(rx),
+            vault_id,
+            success: false,
+            timestamp,
+            error: Some(error),
+            distribution_details: None,
+            retryable,
+            deferred_until: None,
+            transfer_states: HashMap::new(),
         }
     }
 
-    /// Create default rate schedule
-    fn default_rate_schedule() -> RateSchedule {
-        // Default rate schedule
-        RateSchedule {
-            base_rate_per_byte_day: 100, // 100 tokens per byte per day
-            retrieval_rate: 10,          // 10 tokens per retrieval
-            operation_rate: 5,           // 5 tokens per operation
-            uptime_multiplier: 1.0,      // Linear scaling with uptime
-            region_multipliers: HashMap::new(),
+    /// Result of a distribution put off until `until`
+    fn deferred(vault_id: String, timestamp: u64, until: u64) -> Self {
+        Self {
+            vault_id,
+            success: false,
+            timestamp,
+            error: None,
+            distribution_details: None,
+            retryable: true,
+            deferred_until: Some(until),
+            transfer_states: HashMap::new(),
         }
     }
+}
 
-    /// Initialize the manager
-    pub fn initialize(&self) -> Result<()> {
-        // Start the distribution processor
-        self.start_distribution_processor();
+/// How failed distributions are retried
+///
+/// Retryable failures are retried with exponential backoff: the first retry
+/// waits `base_delay`, and every further retry waits twice as long as the
+/// previous one, up to `max_delay`. Delays are applied in whole seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistributionRetryPolicy {
+    /// Attempts at a distribution before it is given up on
+    pub max_attempts: u32,
+
+    /// Delay before the first retry
+    pub base_delay: Duration,
+
+    /// Longest delay between two attempts
+    pub max_delay: Duration,
+}
 
-        Ok(())
+impl Default for DistributionRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_DISTRIBUTION_ATTEMPTS,
+            base_delay: DEFAULT_RETRY_BASE_DELAY,
+            max_delay: DEFAULT_RETRY_MAX_DELAY,
+        }
     }
+}
 
-    /// Create a new reward vault for a collection period
-    pub fn create_reward_vault(
-        &self,
-        creator_keypair: (&[u8], &[u8]),
-        token_amount: u64,
-        token_id: &str,
-        distribution_time: u64,
-        recipients: HashMap<String, Ratio>,
-        reference_state: &State,
-    ) -> Result<String> {
-        // Validate the ratios sum to 1.0 (or close enough accounting for fixed-point precision)
-        let ratio_sum: u64 = recipients.values().map(|r| r.raw_value()).sum();
-        if !(990_000..=1_010_000).contains(&ratio_sum) {
-            return Err(StorageNodeError::Staking(format!(
-                "Invalid recipient ratios: sum must be 1.0, got {}",
-                ratio_sum as f64 / 1_000_000.0
-            )));
-        }
+impl DistributionRetryPolicy {
+    /// Delay before retrying a distribution that has failed `attempts` times
+    pub fn delay_after(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
 
-        // Create a time-based fulfillment mechanism
-        // This will allow unlocking the vault only after the distribution time
-        let fulfillment = FulfillmentMechanism::TimeRelease {
-            unlock_time: distribution_time,
-            reference_states: vec![reference_state.hash.clone()],
-        };
+/// A queued distribution, as shown to operators
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDistribution {
+    /// Vault to distribute
+    pub vault_id: String,
 
-        // Prepare the vault content (distribution details)
-        let vault_content = VaultContent {
-            token_amount,
-            token_id: token_id.to_string(),
-            recipients: recipients.clone(),
-            metadata: HashMap::new(),
-        };
+    /// When the distribution is scheduled
+    pub scheduled_at: u64,
 
-        // Serialize the content
-        let content_bytes = bincode::serialize(&vault_content)
-            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+    /// Number of failed attempts so far
+    pub attempts: u32,
 
-        // Create the vault through the DLV manager
-        let vault_id = self
-            .dlv_manager
-            .create_vault(
-                creator_keypair,
-                fulfillment,
-                &content_bytes,
-                "application/dsm-reward-vault",
-                None, // No specific recipient (will be distributed based on content)
-                reference_state,
-            )
-            .map_err(|e| StorageNodeError::Staking(format!("Failed to create vault: {}", e)))?;
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
 
-        // Create a vault post for storage
-        let vault_post_bytes = self
-            .dlv_manager
-            .create_vault_post(
-                &vault_id,
-                &format!("Reward distribution for {}", token_id),
-                Some(distribution_time + 86400 * 30), // 30 days grace period
-            )
-            .map_err(|e| {
-                StorageNodeError::Staking(format!("Failed to create vault post: {}", e))
-            })?;
+    /// When the distribution is next attempted, if later than scheduled
+    /// (`u64::MAX` while an open dispute freezes it)
+    pub next_retry_at: Option<u64>,
+}
 
-        // Deserialize the vault post to get additional details
-        let vault_post: VaultPost = bincode::deserialize(&vault_post_bytes)
-            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+impl From<&DistributionRequest> for PendingDistribution {
+    fn from(request: &DistributionRequest) -> Self {
+        Self {
+            vault_id: request.vault_id.clone(),
+            scheduled_at: request.timestamp,
+            attempts: request.attempts,
+            last_error: request.last_error.clone(),
+            next_retry_at: (request.retry_at > request.timestamp).then_some(request.retry_at),
+        }
+    }
+}
 
-        // Register the vault
-        let metadata = VaultMetadata {
-            vault_id: vault_id.clone(),
-            purpose: format!("Reward distribution for {}", token_id),
-            creator_id: hex::encode(creator_keypair.0),
-            token_amount,
-            token_id: token_id.to_string(),
-            created_at: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-            distribution_time,
-            recipients,
-            status: vault_post.status,
-        };
+/// A distribution that was given up on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDistribution {
+    /// The request, with the number of attempts made
+    pub request: DistributionRequest,
 
-        // Store the metadata
-        let mut registry = self
-            .vault_registry
-            .write()
-            .map_err(|_| StorageNodeError::Internal)?;
+    /// Error of the last attempt
+    pub error: String,
 
-        registry.insert(vault_id.clone(), metadata);
+    /// When the distribution was given up on
+    pub failed_at: u64,
+}
 
-        // Add to distribution queue
-        {
-            let mut queue = self
-                .distribution_queue
-                .lock()
-                .map_err(|_| StorageNodeError::Internal)?;
+/// Logged outcome of a processed distribution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistributionRecord {
+    /// Vault ID that was distributed
+    pub vault_id: String,
 
-            queue.push(DistributionRequest {
-                vault_id: vault_id.clone(),
-                reference_state: reference_state.clone(),
-                timestamp: distribution_time,
-            });
-        }
+    /// Whether the vault was claimed and paid out
+    pub success: bool,
 
-        Ok(vault_id)
+    /// When the distribution was due
+    pub scheduled_at: u64,
+
+    /// When the distribution was processed
+    pub processed_at: u64,
+
+    /// Why the distribution failed
+    pub error: Option<String>,
+
+    /// Amount paid to each recipient (node_id -> amount), empty on failure
+    pub amounts: HashMap<String, u64>,
+
+    /// State number of the transfer to each recipient (node_id -> state
+    /// number), empty unless an executor paid the distribution out
+    #[serde(default)]
+    pub transfer_states: HashMap<String, u64>,
+}
+
+impl DistributionRecord {
+    /// Record the result of processing a distribution request
+    pub fn from_result(request: &DistributionRequest, result: DistributionResult) -> Self {
+        Self {
+            vault_id: result.vault_id,
+            success: result.success,
+            scheduled_at: request.timestamp,
+            processed_at: result.timestamp,
+            error: result.error,
+            amounts: result.distribution_details.unwrap_or_default(),
+            transfer_states: result.transfer_states,
+        }
     }
 
-    /// Process a storage receipt for reward calculation
-    pub fn process_receipt(&self, receipt: StorageReceipt) -> Result<()> {
-        // Verify the receipt signatures
-        self.verify_receipt(&receipt)?;
+    /// Record a distribution request that could not be processed at all
+    pub fn failed(request: &DistributionRequest, processed_at: u64, error: String) -> Self {
+        Self {
+            vault_id: request.vault_id.clone(),
+            success: false,
+            scheduled_at: request.timestamp,
+            processed_at,
+            error: Some(error),
+            amounts: HashMap::new(),
+            transfer_states: HashMap::new(),
+        }
+    }
+}
 
-        // Store the receipt
-        let mut registry = self
-            .receipt_registry
-            .write()
-            .map_err(|_| StorageNodeError::Internal)?;
+impl RewardVaultManager {
+    /// Create a new reward vault manager
+    pub fn new(dlv_manager: Arc<DLVManager>, config: RewardManagerConfig) -> Self {
+        Self {
+            inner: Arc::new(Self::new_inner(dlv_manager, config)),
+        }
+    }
 
-        let receipts = registry
-            .entry(receipt.node_id.clone())
-            .or_insert_with(Vec::new);
+    /// Create the state of a new manager, before it is shared
+    fn new_inner(dlv_manager: Arc<DLVManager>, config: RewardManagerConfig) -> RewardManagerInner {
+        info!(
+            check_interval_secs = config.check_interval.as_secs(),
+            max_attempts = config.retry_policy.max_attempts,
+            shutdown_timeout_secs = config.shutdown_timeout.as_secs(),
+            dispute_window_secs = config.dispute_window.as_secs(),
+            min_payout = config.min_payout,
+            compact_receipts = config.compact_receipts,
+            max_region_multiplier = config.max_region_multiplier.as_f64(),
+            uptime_tolerance = config.uptime_tolerance,
+            "Creating reward vault manager"
+        );
 
-        receipts.push(receipt);
+        // Create the distribution feed; subscribers attach later
+        let (tx, _) = broadcast::channel(DISTRIBUTION_CHANNEL_CAPACITY);
 
-        Ok(())
+        RewardManagerInner {
+            dlv_manager,
+            vault_registry: RwLock::new(HashMap::new()),
+            receipt_registry: RwLock::new(HashMap::new()),
+            aggregate_registry: RwLock::new(HashMap::new()),
+            challenge_registry: RwLock::new(HashMap::new()),
+            rate_schedules: RwLock::new(vec![(0, Self::default_rate_schedule())]),
+            node_rate_overrides: RwLock::new(HashMap::new()),
+            slashing_policy: RwLock::new(config.slashing_policy),
+            governance_keys: RwLock::new(HashMap::new()),
+            executed_proposals: RwLock::new(HashSet::new()),
+            identity_keys: RwLock::new(HashMap::new()),
+            distribution_queue: Arc::new(Mutex::new(Vec::new())),
+            store: None,
+            price_feed: None,
+            audit_log: None,
+            executor: None,
+            heartbeat_monitor: None,
+            region_oracle_key: None,
+            token_registry: TokenRegistry::new(),
+            config,
+            check_now: Arc::new(Notify::new()),
+            failed_distributions: RwLock::new(Vec::new()),
+            carryover: Mutex::new(HashMap::new()),
+            distribution_history: RwLock::new(Vec::new()),
+            estimate_cache: Mutex::new(HashMap::new()),
+            distribution_tx: tx,
+            proposal_tx: broadcast::channel(PROPOSAL_CHANNEL_CAPACITY).0,
+            shutdown_tx: watch::channel(false).0,
+            processor: Mutex::new(None),
+        }
     }
 
-    /// Verify a storage receipt's signatures
-    fn verify_receipt(&self, receipt: &StorageReceipt) -> Result<bool> {
-        // In a real implementation, we would verify both the client
-        // and node signatures against their public keys
+    /// Create a reward vault manager backed by a reward store
+    ///
+    /// Receipts, challenge results, vault metadata, pending distributions,
+    /// the history of rate schedules and node rate overrides, the executed
+    /// governance proposals and the registered identity keys saved by a
+    /// previous manager are reloaded; pending distributions are processed once
+    /// the manager is initialized. The saved limbo vaults are added to
+    /// `dlv_manager`, which is expected to be fresh after a restart.
+    pub fn with_store(
+        dlv_manager: Arc<DLVManager>,
+        store: Arc<dyn RewardStore>,
+        config: RewardManagerConfig,
+    ) -> Result<Self> {
+        for vault in store.load_limbo_vaults()? {
+            dlv_manager.insert_vault(vault).map_err(|e| {
+                StorageNodeError::Staking(format!("Failed to restore vault: {}", e))
+            })?;
+        }
 
-        // For this implementation, we'll just validate that the signatures exist
-        if receipt.client_signature.is_empty() || receipt.node_signature.is_empty() {
-            return Err(StorageNodeError::Staking(
-                "Invalid receipt: missing signatures".to_string(),
-            ));
+        let mut manager = Self::new_inner(dlv_manager, config);
+
+        let mut receipts: HashMap<String, Vec<StorageReceipt>> = HashMap::new();
+        for receipt in store.load_receipts()? {
+            receipts
+                .entry(receipt.node_id.clone())
+                .or_default()
+                .push(receipt);
         }
 
-        // Calculate expected receipt hash
-        let mut hasher = ::blake3::Hasher::new();
-        hasher.update(receipt.node_id.as_bytes());
-        hasher.update(receipt.client_id.as_bytes());
-        hasher.update(&receipt.service_period.0.to_le_bytes());
-        hasher.update(&receipt.service_period.1.to_le_bytes());
+        let mut aggregates: HashMap<String, Vec<AggregatedReceipt>> = HashMap::new();
+        for aggregate in store.load_aggregated_receipts()? {
+            aggregates
+                .entry(aggregate.node_id.clone())
+                .or_default()
+                .push(aggregate);
+        }
 
-        // Add storage metrics to hash
-        let metrics_bytes = bincode::serialize(&receipt.storage_metrics)
-            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+        let mut challenges: HashMap<String, Vec<ChallengeResult>> = HashMap::new();
+        for result in store.load_challenge_results()? {
+            challenges
+                .entry(result.node_id.clone())
+                .or_default()
+                .push(result);
+        }
 
-        hasher.update(&metrics_bytes);
+        let vaults = store
+            .load_vaults()?
+            .into_iter()
+            .map(|metadata| (metadata.vault_id.clone(), metadata))
+            .collect();
 
-        let calculated_hash = hasher.finalize();
-        let mut hash_bytes = [0u8; 32];
-        hash_bytes.copy_from_slice(calculated_hash.as_bytes());
+        let mut schedules = vec![(0, Self::default_rate_schedule())];
+        for (effective_from, schedule) in store.load_rate_schedules()? {
+            insert_version(&mut schedules, effective_from, schedule);
+        }
 
-        // Verify hash matches
-        if hash_bytes != receipt.receipt_hash {
-            return Err(StorageNodeError::Staking(
-                "Invalid receipt: hash mismatch".to_string(),
-            ));
+        let mut overrides: HashMap<String, Vec<_>> = HashMap::new();
+        for (node_id, effective_from, schedule) in store.load_node_rate_overrides()? {
+            insert_version(
+                overrides.entry(node_id).or_default(),
+                effective_from,
+                schedule,
+            );
         }
 
-        Ok(true)
+        manager.receipt_registry = RwLock::new(receipts);
+        manager.aggregate_registry = RwLock::new(aggregates);
+        manager.challenge_registry = RwLock::new(challenges);
+        manager.vault_registry = RwLock::new(vaults);
+        manager.distribution_queue = Arc::new(Mutex::new(store.load_distributions()?));
+        manager.distribution_history = RwLock::new(store.load_distribution_history()?);
+        manager.failed_distributions = RwLock::new(store.load_failed_distributions()?);
+        manager.carryover = Mutex::new(store.load_carryover()?);
+        manager.rate_schedules = RwLock::new(schedules);
+        manager.node_rate_overrides = RwLock::new(overrides);
+        manager.executed_proposals =
+            RwLock::new(store.load_executed_proposals()?.into_iter().collect());
+        manager.identity_keys = RwLock::new(store.load_identity_keys()?.into_iter().collect());
+        manager.store = Some(store);
+
+        Ok(Self {
+            inner: Arc::new(manager),
+        })
     }
 
-    /// Calculate rewards for a node based on its receipts
-    pub fn calculate_node_rewards(
+    /// State of a manager that is still being built
+    ///
+    /// Builders take the manager by value before it is cloned or its
+    /// processor is started, so this handle is the only one.
+    fn inner_mut(&mut self) -> &mut RewardManagerInner {
+        Arc::get_mut(&mut self.inner).expect("reward manager configured after being shared")
+    }
+
+    /// Scale rewards by the token price from a price feed
+    ///
+    /// With a feed, the rate schedule is read as rates at a token price of one
+    /// unit of the reference currency, so every rate moves with the price.
+    pub fn with_price_feed(mut self, price_feed: Option<Arc<dyn PriceFeed>>) -> Self {
+        self.inner_mut().price_feed = price_feed;
+        self
+    }
+
+    /// Record every paid-out distribution in a signed audit log
+    ///
+    /// Each record cites the receipts of the recipients whose service period
+    /// ended by the vault's distribution time and that no earlier record
+    /// cites.
+    pub fn with_audit_log(mut self, audit_log: Option<Arc<AuditLog>>) -> Self {
+        self.inner_mut().audit_log = audit_log;
+        self
+    }
+
+    /// Pay out distributions with token transfers made by `executor`
+    ///
+    /// Without an executor a distribution only records what each node is owed.
+    pub fn with_executor(mut self, executor: Option<Arc<dyn DistributionExecutor>>) -> Self {
+        self.inner_mut().executor = executor;
+        self
+    }
+
+    /// Check the uptime claimed in receipts against heartbeats in `monitor`
+    ///
+    /// A receipt claiming more than [`RewardManagerConfig::uptime_tolerance`]
+    /// percentage points above the measured uptime is rejected.
+    pub fn with_heartbeat_monitor(mut self, monitor: Option<Arc<HeartbeatMonitor>>) -> Self {
+        self.inner_mut().heartbeat_monitor = monitor;
+        self
+    }
+
+    /// Heartbeat monitor receipts are checked against, if any
+    pub fn heartbeat_monitor(&self) -> Option<Arc<HeartbeatMonitor>> {
+        self.inner.heartbeat_monitor.clone()
+    }
+
+    /// Apply region multipliers only to regions proven to the oracle with `public_key`
+    ///
+    /// Without an oracle no region is proven, so rewards carry no region
+    /// multiplier.
+    pub fn with_region_oracle(mut self, public_key: Option<Vec<u8>>) -> Self {
+        self.inner_mut().region_oracle_key = public_key;
+        self
+    }
+
+    /// Look up token metadata in `registry` for reports
+    ///
+    /// Defaults to a registry holding only the native ROOT token.
+    pub fn with_token_registry(mut self, registry: TokenRegistry) -> Self {
+        self.inner_mut().token_registry = registry;
+        self
+    }
+
+    /// Re-check the hash chain and signatures of the distribution audit log
+    pub fn verify_audit_log(&self) -> Result<()> {
+        self.require_audit_log()?.verify()
+    }
+
+    /// Audit records whose sequence numbers fall in `range`, for external auditors
+    ///
+    /// The records verify on their own with
+    /// [`verify_audit_records`](crate::staking::audit_log::verify_audit_records)
+    /// and the node's public key.
+    pub fn export_audit_log(&self, range: Range<u64>) -> Result<Vec<AuditRecord>> {
+        self.require_audit_log()?.export(range)
+    }
+
+    fn require_audit_log(&self) -> Result<&AuditLog> {
+        self.inner
+            .audit_log
+            .as_deref()
+            .ok_or_else(|| StorageNodeError::Config("No distribution audit log".to_string()))
+    }
+
+    /// Export a report of the rewards earned and paid out in a period
+    ///
+    /// See [`Self::write_report`] for the rows.
+    pub fn export_report(
         &self,
-        node_id: &str,
         period_start: u64,
         period_end: u64,
-    ) -> Result<u64> {
-        let registry = self
-            .receipt_registry
-            .read()
-            .map_err(|_| StorageNodeError::Internal)?;
+        format: ReportFormat,
+    ) -> Result<Vec<u8>> {
+        self.write_report(period_start, period_end, format, Vec::new())
+    }
 
-        let receipts = match registry.get(node_id) {
-            Some(r) => r,
-            None => return Ok(0), // No receipts for this node
-        };
+    /// Write a report of the rewards earned and paid out in a period
+    ///
+    /// The report has a row per node with the reward its receipts earned in
+    /// the period, as in [`Self::node_reward_breakdown`], followed by a row
+    /// per payment of each distribution processed in the period, referencing
+    /// the audit record of the distribution when there is an audit log. Rows
+    /// are written as they are computed.
+    pub fn write_report<W: Write>(
+        &self,
+        period_start: u64,
+        period_end: u64,
+        format: ReportFormat,
+        writer: W,
+    ) -> Result<W> {
+        if period_end <= period_start {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Report period {}..{} is empty",
+                period_start, period_end
+            )));
+        }
 
-        // Filter receipts for the specified period
-        let period_receipts: Vec<&StorageReceipt> = receipts
-            .iter()
-            .filter(|r| {
-                // Check if receipt period overlaps with query period
-                r.service_period.0 < period_end && r.service_period.1 > period_start
-            })
-            .collect::<Vec<&StorageReceipt>>();
+        let mut report = ReportWriter::begin(writer, format, period_start, period_end)?;
+        let decimals = |token_id: &str| {
+            self.inner
+                .token_registry
+                .get_token(token_id)
+                .map(|t| t.decimals)
+        };
 
-        // Calculate rewards based on rate schedule
-        let schedule = self
-            .rate_schedule
-            .read()
-            .map_err(|_| StorageNodeError::Internal)?;
+        let mut node_ids: Vec<String> = {
+            let registry = self
+                .inner
+                .receipt_registry
+                .read()
+                .map_err(|_| StorageNodeError::Internal)?;
 
-        let mut total_reward = 0;
+            let aggregates = self
+                .inner
+                .aggregate_registry
+                .read()
+                .map_err(|_| StorageNodeError::Internal)?;
 
-        for receipt in period_receipts {
-            // Calculate overlap duration (in seconds)
-            let overlap_start = period_start.max(receipt.service_period.0);
-            let overlap_end = period_end.min(receipt.service_period.1);
-            let duration = overlap_end.saturating_sub(overlap_start);
+            registry.keys().chain(aggregates.keys()).cloned().collect()
+        };
+        node_ids.sort_unstable();
+        node_ids.dedup();
 
-            // Skip if no overlap
-            if duration == 0 {
-                continue;
+        for node_id in node_ids {
+            let breakdown = self.node_reward_breakdown(&node_id, period_start, period_end)?;
+            if breakdown.base_reward() == 0 && breakdown.total == 0 {
+                continue; // No service in the period
             }
 
-            // Calculate reward components
-            let storage_reward = schedule.calculate(
-                duration,
-                receipt.storage_metrics.bytes_stored,
-                receipt.storage_metrics.retrievals,
-            );
-
-            // Add uptime multiplier
-            let uptime_factor = (receipt.storage_metrics.uptime_percentage as f64 / 100.0)
-                * schedule.uptime_multiplier;
+            report.write_row(&ReportRow {
+                record_type: ReportRecordType::NodeReward,
+                node_id,
+                token_id: REWARD_TOKEN_ID.to_string(),
+                decimals: decimals(REWARD_TOKEN_ID),
+                amount: breakdown.total,
+                storage_reward: Some(breakdown.storage_reward),
+                retrieval_reward: Some(breakdown.retrieval_reward),
+                operation_reward: Some(breakdown.operation_reward),
+                slashed: Some(breakdown.slashed),
+                redistributed: Some(breakdown.redistributed),
+                region_multiplier: breakdown.region_multiplier.map(|m| m.multiplier.as_f64()),
+                vault_id: None,
+                transaction_ref: None,
+                timestamp: None,
+            })?;
+        }
 
-            let scaled_reward = (storage_reward as f64 * uptime_factor) as u64;
+        let audit_refs: HashMap<String, String> = match &self.inner.audit_log {
+            Some(audit_log) => audit_log
+                .export(0..u64::MAX)?
+                .into_iter()
+                .map(|record| (record.vault_id, hex::encode(record.record_hash)))
+                .collect(),
+            None => HashMap::new(),
+        };
 
-            // Add region multipliers
-            let mut region_multiplier = 1.0;
-            for region in &receipt.storage_metrics.regions {
-                if let Some(mult) = schedule.region_multipliers.get(region) {
-                    region_multiplier *= mult;
-                }
+        let processed = self
+            .get_distribution_history(None)?
+            .into_iter()
+            .filter(|record| {
+                record.success
+                    && record.processed_at >= period_start
+                    && record.processed_at < period_end
+            });
+        for record in processed {
+            // Vaults pruned since they were paid out were in the reward token
+            let token_id = self
+                .get_vault(&record.vault_id)
+                .map_or_else(|_| REWARD_TOKEN_ID.to_string(), |vault| vault.token_id);
+
+            let mut amounts: Vec<(String, u64)> = record.amounts.into_iter().collect();
+            amounts.sort_unstable();
+            for (node_id, amount) in amounts {
+                report.write_row(&ReportRow {
+                    record_type: ReportRecordType::Distribution,
+                    node_id,
+                    token_id: token_id.clone(),
+                    decimals: decimals(&token_id),
+                    amount,
+                    storage_reward: None,
+                    retrieval_reward: None,
+                    operation_reward: None,
+                    slashed: None,
+                    redistributed: None,
+                    region_multiplier: None,
+                    vault_id: Some(record.vault_id.clone()),
+                    transaction_ref: audit_refs.get(&record.vault_id).cloned(),
+                    timestamp: Some(record.processed_at),
+                })?;
             }
-
-            let final_reward = (scaled_reward as f64 * region_multiplier) as u64;
-            total_reward += final_reward;
         }
 
-        Ok(total_reward)
+        report.finish()
     }
 
-    /// Update the rate schedule
-    pub fn update_rate_schedule(&self, new_schedule: RateSchedule) -> Result<()> {
-        let mut schedule = self
-            .rate_schedule
-            .write()
+    /// Distributions waiting in the queue, soonest due first
+    ///
+    /// Distributions the processor is working on are not in the queue and
+    /// so not listed.
+    pub fn get_pending_distributions(&self) -> Result<Vec<PendingDistribution>> {
+        let queue = self
+            .inner
+            .distribution_queue
+            .lock()
             .map_err(|_| StorageNodeError::Internal)?;
 
-        *schedule = new_schedule;
+        let mut requests: Vec<&DistributionRequest> = queue.iter().collect();
+        requests.sort_by_key(|request| (request.due_at(), request.vault_id.clone()));
+
+        Ok(requests
+            .into_iter()
+            .map(PendingDistribution::from)
+            .collect())
+    }
+
+    /// Move a queued distribution to `new_time`
+    ///
+    /// The vault's fulfillment mechanism must allow unlocking it by then, so
+    /// a time-released vault cannot be scheduled before its unlock time. A
+    /// pending retry is replaced by the new time; a distribution frozen by
+    /// an open dispute stays frozen.
+    pub fn reschedule_distribution(&self, vault_id: &str, new_time: u64) -> Result<()> {
+        let unlock_time = {
+            let vault =
+                self.inner.dlv_manager.get_vault(vault_id).map_err(|e| {
+                    StorageNodeError::Staking(format!("Failed to load vault: {}", e))
+                })?;
+            let vault = vault.lock().map_err(|_| StorageNodeError::Internal)?;
+            earliest_unlock_time(&vault.fulfillment_condition)
+        };
+        if let Some(unlock_time) = unlock_time.filter(|&unlock_time| new_time < unlock_time) {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Vault {} cannot be unlocked before {}",
+                vault_id, unlock_time
+            )));
+        }
+
+        {
+            let mut queue = self
+                .inner
+                .distribution_queue
+                .lock()
+                .map_err(|_| StorageNodeError::Internal)?;
+            let request = queue
+                .iter_mut()
+                .find(|request| request.vault_id == vault_id)
+                .ok_or_else(|| {
+                    StorageNodeError::NotFound(format!(
+                        "Pending distribution of vault {}",
+                        vault_id
+                    ))
+                })?;
+
+            let mut rescheduled = request.clone();
+            rescheduled.timestamp = new_time;
+            if rescheduled.retry_at != FROZEN_DISTRIBUTION {
+                rescheduled.retry_at = 0;
+            }
+            if let Some(store) = &self.inner.store {
+                store.save_distribution(&rescheduled)?;
+            }
+            *request = rescheduled;
+        }
+
+        // The distribution is only attempted once the vault is due
+        self.update_vault(vault_id, |metadata| {
+            metadata.distribution_time = new_time;
+            Ok(())
+        })?;
 
+        info!(vault_id = %vault_id, new_time, "Rescheduled distribution");
+        self.trigger_distribution_check_now();
         Ok(())
     }
 
-    /// Get all registered vaults
-    pub fn get_vaults(&self) -> Result<Vec<VaultMetadata>> {
-        let registry = self
-            .vault_registry
+    /// Distributions that failed permanently or ran out of attempts, oldest first
+    pub fn get_failed_distributions(&self) -> Result<Vec<FailedDistribution>> {
+        Ok(self
+            .inner
+            .failed_distributions
             .read()
-            .map_err(|_| StorageNodeError::Internal)?;
-
-        Ok(registry.values().cloned().collect())
+            .map_err(|_| StorageNodeError::Internal)?
+            .clone())
     }
 
-    /// Get a specific vault by ID
-    pub fn get_vault(&self, vault_id: &str) -> Result<VaultMetadata> {
-        let registry = self
-            .vault_registry
+    /// Processed distributions, oldest first, optionally for a single vault
+    pub fn get_distribution_history(
+        &self,
+        vault_id: Option<&str>,
+    ) -> Result<Vec<DistributionRecord>> {
+        let history = self
+            .inner
+            .distribution_history
             .read()
             .map_err(|_| StorageNodeError::Internal)?;
 
-        registry
-            .get(vault_id)
+        Ok(history
+            .iter()
+            .filter(|record| vault_id.is_none() || vault_id == Some(record.vault_id.as_str()))
             .cloned()
-            .ok_or_else(|| StorageNodeError::NotFound(format!("Vault {}", vault_id)))
+            .collect())
     }
 
-    /// Process distribution
-    fn process_distribution(&self, request: DistributionRequest) -> Result<DistributionResult> {
-        // Get the vault metadata
-        let metadata = self.get_vault(&request.vault_id)?;
+    /// Subscribe to distributions as they are processed
+    ///
+    /// Only records produced after subscribing are delivered; earlier ones
+    /// are available from [`Self::get_distribution_history`].
+    pub fn subscribe_distributions(&self) -> broadcast::Receiver<DistributionRecord> {
+        self.inner.distribution_tx.subscribe()
+    }
 
-        // Check if it's time to distribute
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+    /// Subscribe to distributions as they are proposed for dispute
+    pub fn subscribe_proposals(&self) -> broadcast::Receiver<ProposedDistribution> {
+        self.inner.proposal_tx.subscribe()
+    }
 
-        if now < metadata.distribution_time {
-            return Ok(DistributionResult {
-                vault_id: request.vault_id,
-                success: false,
-                timestamp: now,
-                error: Some(format!(
-                    "Not yet time to distribute (scheduled at {})",
-                    metadata.distribution_time
-                )),
-                distribution_details: None,
-            });
+    /// Log a processed distribution and publish it to subscribers
+    fn record_distribution(&self, record: DistributionRecord) -> Result<()> {
+        if let Some(store) = &self.inner.store {
+            store.save_distribution_record(&record)?;
         }
 
-        // Try to unlock the vault
-        // Create a time proof based on the reference state
-        let time_proof = FulfillmentProof::TimeProof {
-            reference_state: request.reference_state.hash.clone(),
-            state_proof: vec![], // Empty proof since we're using direct state
-        };
+        self.inner
+            .distribution_history
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .push(record.clone());
 
-        // Get the simulator claimant key (in production this would be a secure key)
-        let claimant_key = vec![1, 2, 3, 4]; // Simplified for demonstration
+        // Sending only fails when nobody is subscribed
+        let _ = self.inner.distribution_tx.send(record);
 
-        // Try to unlock the vault
-        match self.dlv_manager.try_unlock_vault(
-            &request.vault_id,
-            time_proof,
-            &claimant_key,
-            &request.reference_state,
-        ) {
-            Ok(true) => {
-                // Successfully unlocked, now claim the content
-                match self.dlv_manager.claim_vault_content(
-                    &request.vault_id,
-                    &claimant_key,
-                    &request.reference_state,
-                ) {
-                    Ok(content) => {
-                        // Deserialize the content
-                        let vault_content: VaultContent = bincode::deserialize(&content)
-                            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+        Ok(())
+    }
 
-                        // Calculate distribution amounts
-                        let mut distributions = HashMap::new();
-                        for (node_id, ratio) in &vault_content.recipients {
-                            let amount = ratio.apply_to(vault_content.token_amount);
-                            distributions.insert(node_id.clone(), amount);
+    /// Create default rate schedule
+    fn default_rate_schedule() -> RateSchedule {
+        // Default rate schedule
+        RateSchedule {
+            base_rate_per_byte_day: 100,   // 100 tokens per byte per day
+            retrieval_rate: 10,            // 10 tokens per retrieval
+            operation_rate: 5,             // 5 tokens per operation
+            uptime_multiplier: Ratio::ONE, // Linear scaling with uptime
+            region_multipliers: HashMap::new(),
+            region_strategy: RegionMultiplierStrategy::Max,
+        }
+    }
+
+    /// Initialize the manager
+    ///
+    /// Must be called from within a tokio runtime. Initializing a manager
+    /// whose processor is already running has no effect.
+    pub fn initialize(&self) -> Result<()> {
+        let mut processor = self
+            .inner
+            .processor
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        if processor.is_none() {
+            // Start the distribution processor
+            self.inner.shutdown_tx.send_replace(false);
+            *processor = Some(self.start_distribution_processor());
+        }
+
+        Ok(())
+    }
+
+    /// Wake the distribution processor to check for due vaults now
+    ///
+    /// A trigger while the processor is busy makes it check again as soon as
+    /// it finishes, instead of waiting for the next interval.
+    pub fn trigger_distribution_check_now(&self) {
+        self.inner.check_now.notify_one();
+    }
+
+    /// Stop the distribution processor
+    ///
+    /// Distributions already being processed are allowed to finish for up to
+    /// the shutdown timeout, after which the processor is aborted. Pending
+    /// distributions are then flushed to the reward store, so a manager
+    /// created from the same store picks them up again.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.inner.shutdown_tx.send_replace(true);
+
+        let processor = self
+            .inner
+            .processor
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?
+            .take();
+
+        if let Some(mut processor) = processor {
+            match tokio::time::timeout(self.inner.config.shutdown_timeout, &mut processor).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => error!(error = %e, "Distribution processor failed"),
+                Err(_) => {
+                    warn!(
+                        timeout_secs = self.inner.config.shutdown_timeout.as_secs(),
+                        "Distribution processor did not stop in time, aborting it"
+                    );
+                    processor.abort();
+                }
+            }
+        }
+
+        self.flush_distribution_queue()
+    }
+
+    /// Write every pending distribution to the reward store
+    fn flush_distribution_queue(&self) -> Result<()> {
+        let Some(store) = &self.inner.store else {
+            return Ok(());
+        };
+
+        let queue = self
+            .inner
+            .distribution_queue
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        for request in queue.iter() {
+            store.save_distribution(request)?;
+        }
+
+        Ok(())
+    }
+
+    /// Save the limbo vault behind a reward vault in its current state
+    ///
+    /// Called after every change the DLV manager makes to the vault, so a
+    /// restarted manager can rebuild it.
+    fn save_limbo_vault(&self, vault_id: &str) -> Result<()> {
+        let Some(store) = &self.inner.store else {
+            return Ok(());
+        };
+
+        let vault = self
+            .inner
+            .dlv_manager
+            .get_vault(vault_id)
+            .map_err(|e| StorageNodeError::Staking(format!("Failed to load vault: {}", e)))?;
+        let vault = vault
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?
+            .clone();
+
+        store.save_limbo_vault(&vault)
+    }
+
+    /// Create a new reward vault for a collection period
+    pub fn create_reward_vault(
+        &self,
+        creator_keypair: (&[u8], &[u8]),
+        token_amount: u64,
+        token_id: &str,
+        distribution_time: u64,
+        recipients: HashMap<String, Ratio>,
+        reference_state: &State,
+    ) -> Result<String> {
+        check_ratio_sum(recipients.values(), "recipient")?;
+
+        self.register_reward_vault(
+            creator_keypair,
+            token_amount,
+            token_id,
+            distribution_time,
+            recipients,
+            reference_state,
+            format!("Reward distribution for {}", token_id),
+            false,
+        )
+    }
+
+    /// Create a reward vault whose recipients claim their own shares
+    ///
+    /// The vault is not distributed by the manager. Once `unlock_time` has
+    /// passed, each recipient calls [`Self::claim_reward`] for its share.
+    pub fn create_claimable_vault(
+        &self,
+        creator_keypair: (&[u8], &[u8]),
+        token_amount: u64,
+        token_id: &str,
+        unlock_time: u64,
+        recipients: HashMap<String, Ratio>,
+        reference_state: &State,
+    ) -> Result<String> {
+        check_ratio_sum(recipients.values(), "recipient")?;
+
+        self.register_reward_vault(
+            creator_keypair,
+            token_amount,
+            token_id,
+            unlock_time,
+            recipients,
+            reference_state,
+            format!("Claimable rewards for {}", token_id),
+            true,
+        )
+    }
+
+    /// Create reward vaults that vest in tranches
+    ///
+    /// Each `(unlock_time, ratio)` in the schedule becomes its own vault,
+    /// holding that share of `token_amount` and distributed at its unlock time
+    /// like any other reward vault, so a tranche that fails to distribute
+    /// does not hold up the others. Rounding leftovers go to the last tranche.
+    ///
+    /// The schedule is checked up front, but tranches are created one at a
+    /// time: if creating one fails, the tranches before it remain.
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>>` - Vault IDs of the tranches, by unlock time
+    pub fn create_vesting_vault(
+        &self,
+        creator_keypair: (&[u8], &[u8]),
+        token_amount: u64,
+        token_id: &str,
+        mut schedule: Vec<(u64, Ratio)>,
+        recipients: HashMap<String, Ratio>,
+        reference_state: &State,
+    ) -> Result<Vec<String>> {
+        if schedule.is_empty() {
+            return Err(StorageNodeError::Staking(
+                "Vesting schedule needs at least one tranche".to_string(),
+            ));
+        }
+        check_ratio_sum(schedule.iter().map(|(_, ratio)| ratio), "vesting schedule")?;
+        check_ratio_sum(recipients.values(), "recipient")?;
+
+        schedule.sort_by_key(|(unlock_time, _)| *unlock_time);
+        let tranches = schedule.len();
+        let mut remaining = token_amount;
+        let mut vault_ids = Vec::with_capacity(tranches);
+
+        for (index, (unlock_time, ratio)) in schedule.into_iter().enumerate() {
+            let amount = if index + 1 == tranches {
+                remaining
+            } else {
+                ratio.apply_to(token_amount).min(remaining)
+            };
+            remaining -= amount;
+
+            vault_ids.push(self.register_reward_vault(
+                creator_keypair,
+                amount,
+                token_id,
+                unlock_time,
+                recipients.clone(),
+                reference_state,
+                format!(
+                    "Vesting tranche {}/{} for {}",
+                    index + 1,
+                    tranches,
+                    token_id
+                ),
+                false,
+            )?);
+        }
+
+        Ok(vault_ids)
+    }
+
+    /// Create a time-released reward vault, register it and queue its distribution
+    ///
+    /// Vaults for pull claims are registered without queueing a distribution.
+    #[allow(clippy::too_many_arguments)]
+    fn register_reward_vault(
+        &self,
+        creator_keypair: (&[u8], &[u8]),
+        token_amount: u64,
+        token_id: &str,
+        distribution_time: u64,
+        recipients: HashMap<String, Ratio>,
+        reference_state: &State,
+        purpose: String,
+        pull_claims: bool,
+    ) -> Result<String> {
+        // Create a time-based fulfillment mechanism
+        // This will allow unlocking the vault only after the distribution time
+        let fulfillment = FulfillmentMechanism::TimeRelease {
+            unlock_time: distribution_time,
+            reference_states: vec![reference_state.hash.clone()],
+        };
+
+        // Prepare the vault content (distribution details)
+        let vault_content = VaultContent {
+            token_amount,
+            token_id: token_id.to_string(),
+            recipients: recipients.clone(),
+            metadata: HashMap::new(),
+        };
+
+        // Serialize the content
+        let content_bytes = bincode::serialize(&vault_content)
+            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+
+        // Create the vault through the DLV manager. Vaults for pull claims
+        // are split between the recipients, who each claim their own share
+        let vault_id = if pull_claims {
+            let split = VaultSplit::new(token_id, token_amount, split_allocations(&recipients))
+                .map_err(|e| StorageNodeError::Staking(format!("Invalid vault split: {}", e)))?;
+            self.inner.dlv_manager.create_splittable_vault(
+                creator_keypair,
+                fulfillment,
+                &content_bytes,
+                "application/dsm-reward-vault",
+                split,
+                reference_state,
+            )
+        } else {
+            self.inner.dlv_manager.create_vault(
+                creator_keypair,
+                fulfillment,
+                &content_bytes,
+                "application/dsm-reward-vault",
+                None, // No specific recipient (will be distributed based on content)
+                reference_state,
+            )
+        }
+        .map_err(|e| StorageNodeError::Staking(format!("Failed to create vault: {}", e)))?;
+
+        // Create a vault post for storage
+        let vault_post_bytes = self
+            .inner
+            .dlv_manager
+            .create_vault_post(
+                &vault_id,
+                &purpose,
+                Some(distribution_time + 86400 * 30), // 30 days grace period
+                creator_keypair,
+            )
+            .map_err(|e| {
+                StorageNodeError::Staking(format!("Failed to create vault post: {}", e))
+            })?;
+
+        // Deserialize the vault post to get additional details
+        let vault_post: VaultPost = bincode::deserialize(&vault_post_bytes)
+            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+
+        // Only trust a post signed with the key bound to the vault the DLV
+        // manager holds. The creator's public key is its Kyber key, which
+        // cannot sign, so the vault binds its SPHINCS+ key at creation.
+        self.inner.dlv_manager.verify_vault_post(&vault_post)?;
+
+        // Register the vault
+        self.save_limbo_vault(&vault_id)?;
+        let metadata = VaultMetadata {
+            vault_id: vault_id.clone(),
+            purpose,
+            creator_id: hex::encode(creator_keypair.0),
+            token_amount,
+            token_id: token_id.to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            distribution_time,
+            recipients,
+            status: vault_post.status,
+            dispute_window: self.inner.config.dispute_window,
+            proposal: None,
+            disputes: Vec::new(),
+            pull_claims,
+            claims: HashMap::new(),
+            revocation_reason: None,
+            payouts: BTreeMap::new(),
+        };
+
+        if pull_claims {
+            if let Some(store) = &self.inner.store {
+                store.save_vault(&metadata)?;
+            }
+            self.inner
+                .vault_registry
+                .write()
+                .map_err(|_| StorageNodeError::Internal)?
+                .insert(vault_id.clone(), metadata);
+            return Ok(vault_id);
+        }
+
+        let request = DistributionRequest {
+            vault_id: vault_id.clone(),
+            reference_state: reference_state.clone(),
+            timestamp: distribution_time,
+            attempts: 0,
+            retry_at: 0,
+            last_error: None,
+        };
+
+        if let Some(store) = &self.inner.store {
+            store.save_vault(&metadata)?;
+            store.save_distribution(&request)?;
+        }
+
+        // Store the metadata
+        let mut registry = self
+            .inner
+            .vault_registry
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        registry.insert(vault_id.clone(), metadata);
+
+        // Add to distribution queue
+        {
+            let mut queue = self
+                .inner
+                .distribution_queue
+                .lock()
+                .map_err(|_| StorageNodeError::Internal)?;
+
+            queue.push(request);
+        }
+
+        Ok(vault_id)
+    }
+
+    /// Process a storage receipt for reward calculation
+    pub fn process_receipt(&self, receipt: StorageReceipt) -> Result<()> {
+        // Verify the receipt signatures
+        self.verify_receipt(&receipt)?;
+
+        if let Some(store) = &self.inner.store {
+            store.save_receipt(&receipt)?;
+        }
+
+        // Store the receipt
+        let mut registry = self
+            .inner
+            .receipt_registry
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let receipts = registry
+            .entry(receipt.node_id.clone())
+            .or_insert_with(Vec::new);
+
+        receipts.push(receipt);
+
+        Ok(())
+    }
+
+    /// Aggregate every node's receipts whose service ended by `before`
+    ///
+    /// Each node's receipts are replaced by one [`AggregatedReceipt`] that
+    /// keeps the reward they earned, so rewards for the settled periods stay
+    /// the same while later calculations skip the individual receipts.
+    /// Intended for periods that have been distributed: aggregates are not
+    /// cited by later distribution proposals.
+    ///
+    /// # Returns
+    /// * `Result<Vec<AggregatedReceipt>>` - The aggregates created, one per
+    ///   node with receipts to compact
+    pub fn compact_receipts(&self, before: u64) -> Result<Vec<AggregatedReceipt>> {
+        let node_ids: Vec<String> = self
+            .inner
+            .receipt_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .keys()
+            .cloned()
+            .collect();
+
+        let mut aggregates = Vec::new();
+        for node_id in node_ids {
+            aggregates.extend(self.compact_node_receipts(&node_id, before)?);
+        }
+
+        Ok(aggregates)
+    }
+
+    /// Receipt aggregates of a node, oldest first
+    pub fn aggregated_receipts(&self, node_id: &str) -> Result<Vec<AggregatedReceipt>> {
+        Ok(self
+            .inner
+            .aggregate_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .get(node_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Aggregate a node's receipts whose service ended by `before`
+    fn compact_node_receipts(
+        &self,
+        node_id: &str,
+        before: u64,
+    ) -> Result<Option<AggregatedReceipt>> {
+        let mut registry = self
+            .inner
+            .receipt_registry
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+        let mut aggregates = self
+            .inner
+            .aggregate_registry
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let Some(receipts) = registry.get_mut(node_id) else {
+            return Ok(None);
+        };
+        let (settled, open): (Vec<_>, Vec<_>) = receipts
+            .iter()
+            .cloned()
+            .partition(|receipt| receipt.service_period.1 <= before);
+        if settled.is_empty() {
+            return Ok(None);
+        }
+
+        // The reward each receipt earned over its whole service period
+        let mut breakdown = RewardBreakdown::default();
+        {
+            let schedules = self
+                .inner
+                .rate_schedules
+                .read()
+                .map_err(|_| StorageNodeError::Internal)?;
+            let overrides = self
+                .inner
+                .node_rate_overrides
+                .read()
+                .map_err(|_| StorageNodeError::Internal)?;
+            let node_overrides = overrides
+                .get(node_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let node_schedules = node_schedules(&schedules, node_overrides);
+            let oracle_key = self.inner.region_oracle_key.as_deref();
+            for receipt in &settled {
+                let window = receipt.service_period;
+                let receipt_breakdown =
+                    segmented_breakdown(receipt, window, &node_schedules, oracle_key)?;
+                breakdown.accumulate(&receipt_breakdown)?;
+            }
+        }
+
+        let aggregate = AggregatedReceipt::new(node_id, &settled, breakdown);
+        if let Some(store) = &self.inner.store {
+            let hashes: Vec<[u8; 32]> = settled.iter().map(|r| r.receipt_hash).collect();
+            store.compact_receipts(&aggregate, &hashes)?;
+        }
+
+        if open.is_empty() {
+            registry.remove(node_id);
+        } else {
+            *receipts = open;
+        }
+        aggregates
+            .entry(node_id.to_string())
+            .or_default()
+            .push(aggregate.clone());
+
+        Ok(Some(aggregate))
+    }
+
+    /// Record the outcome of a storage challenge issued to a node
+    ///
+    /// Failed challenges count against the node's rewards for the period
+    /// containing `timestamp`, as set by the slashing policy.
+    pub fn record_challenge_result(
+        &self,
+        node_id: &str,
+        passed: bool,
+        timestamp: u64,
+    ) -> Result<()> {
+        let result = ChallengeResult {
+            node_id: node_id.to_string(),
+            passed,
+            timestamp,
+        };
+
+        if let Some(store) = &self.inner.store {
+            store.save_challenge_result(&result)?;
+        }
+
+        let mut registry = self
+            .inner
+            .challenge_registry
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        registry
+            .entry(result.node_id.clone())
+            .or_default()
+            .push(result);
+
+        Ok(())
+    }
+
+    /// Storage challenges a node passed and failed in a period
+    ///
+    /// Receipts for the period embed this summary in their storage metrics.
+    pub fn challenge_summary(
+        &self,
+        node_id: &str,
+        period_start: u64,
+        period_end: u64,
+    ) -> Result<ChallengeSummary> {
+        let registry = self
+            .inner
+            .challenge_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let results = registry.get(node_id).map(Vec::as_slice).unwrap_or_default();
+        Ok(ChallengeSummary::for_period(
+            results,
+            (period_start, period_end),
+        ))
+    }
+
+    /// Verify a storage receipt's signatures
+    ///
+    /// The receipt must come from the two-phase exchange: both the client and
+    /// the node must have signed the hash of the challenge it was sealed from
+    /// with the SPHINCS+ keys recorded in the receipt, and those must be the
+    /// keys registered for its `client_id` and `node_id` (see
+    /// [`Self::register_identity_key`]).
+    fn verify_receipt(&self, receipt: &StorageReceipt) -> Result<bool> {
+        if receipt.version != TWO_PHASE_RECEIPT_VERSION {
+            return Err(StorageNodeError::Staking(format!(
+                "Invalid receipt: version {} was not produced by the two-phase exchange",
+                receipt.version
+            )));
+        }
+        if receipt.client_signature.is_empty() || receipt.node_signature.is_empty() {
+            return Err(StorageNodeError::Staking(
+                "Invalid receipt: missing signatures".to_string(),
+            ));
+        }
+
+        // Verify hash matches
+        let challenge = receipt.challenge()?;
+        if sealed_receipt_hash(&challenge.challenge_hash, &receipt.client_signature)
+            != receipt.receipt_hash
+        {
+            return Err(StorageNodeError::Staking(
+                "Invalid receipt: hash mismatch".to_string(),
+            ));
+        }
+
+        // Keys carried in the receipt are only trusted if registered for the
+        // IDs it names, or a node could sign for a made-up client
+        let parties = [
+            ("client", &receipt.client_id, &receipt.client_public_key),
+            ("node", &receipt.node_id, &receipt.node_public_key),
+        ];
+        for (role, id, public_key) in parties {
+            match self.identity_key(id)? {
+                Some(registered) if registered == *public_key => {}
+                Some(_) => {
+                    return Err(StorageNodeError::Staking(format!(
+                        "Invalid receipt: {} key is not the one registered for {}",
+                        role, id
+                    )));
+                }
+                None => {
+                    return Err(StorageNodeError::Staking(format!(
+                        "Invalid receipt: no key is registered for {} {}",
+                        role, id
+                    )));
+                }
+            }
+        }
+
+        let signers = [
+            (
+                "client",
+                &receipt.client_public_key,
+                &receipt.client_signature,
+            ),
+            ("node", &receipt.node_public_key, &receipt.node_signature),
+        ];
+        for (role, public_key, signature) in signers {
+            if !verify_signature_or_false(public_key, &challenge.challenge_hash, signature) {
+                return Err(StorageNodeError::Staking(format!(
+                    "Invalid receipt: bad {} signature",
+                    role
+                )));
+            }
+        }
+
+        if let Some(monitor) = &self.inner.heartbeat_monitor {
+            let claimed = receipt.storage_metrics.uptime_percentage;
+            let measured = monitor.uptime_percentage(&receipt.node_id, receipt.service_period)?;
+            if claimed > measured.saturating_add(self.inner.config.uptime_tolerance) {
+                return Err(StorageNodeError::Staking(format!(
+                    "Invalid receipt: claimed uptime {}% exceeds measured uptime {}%",
+                    claimed, measured
+                )));
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Calculate rewards for a node based on its receipts
+    ///
+    /// Rewards are slashed under the slashing policy, see
+    /// [`Self::node_reward_breakdown`]. With a price feed, the rewards from
+    /// the rate schedule are scaled by the current price of the reward token
+    /// in the reference currency. The result includes the node's pending
+    /// carryover, see [`Self::pending_carryover`].
+    pub async fn calculate_node_rewards(
+        &self,
+        node_id: &str,
+        period_start: u64,
+        period_end: u64,
+    ) -> Result<u64> {
+        let price = self.reward_token_price().await?;
+
+        let reward = self
+            .node_reward_breakdown(node_id, period_start, period_end)?
+            .total
+            .checked_add(self.pending_carryover(node_id)?)
+            .ok_or_else(|| reward_overflow("carryover"))?;
+
+        Self::price_scaled(reward, price)
+    }
+
+    /// Estimate the reward a node has accrued since the last distribution
+    ///
+    /// Runs the calculation of [`Self::calculate_node_rewards`] from the last
+    /// distribution to now with the live rate schedules, without changing any
+    /// registry. The breakdown is cached per node and reused until receipts,
+    /// challenge results, rates or the slashing policy change, so the
+    /// estimate is cheap to poll.
+    pub async fn estimate_current_rewards(&self, node_id: &str) -> Result<RewardEstimate> {
+        let price = self.reward_token_price().await?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let period_start = self.last_distribution_time()?;
+
+        let breakdown = self.open_period_breakdown(node_id, period_start, now)?;
+        let carryover = self.pending_carryover(node_id)?;
+        let reward = breakdown
+            .total
+            .checked_add(carryover)
+            .ok_or_else(|| reward_overflow("carryover"))?;
+
+        Ok(RewardEstimate {
+            node_id: node_id.to_string(),
+            period_start,
+            period_end: now,
+            is_estimate: true,
+            breakdown,
+            carryover,
+            estimated_reward: Self::price_scaled(reward, price)?,
+        })
+    }
+
+    /// When the last successful distribution was due (0 before any)
+    fn last_distribution_time(&self) -> Result<u64> {
+        let history = self
+            .inner
+            .distribution_history
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(history
+            .iter()
+            .filter(|record| record.success)
+            .map(|record| record.scheduled_at)
+            .max()
+            .unwrap_or(0))
+    }
+
+    /// A node's breakdown from `period_start` to `now`, reusing a cached one if still valid
+    fn open_period_breakdown(
+        &self,
+        node_id: &str,
+        period_start: u64,
+        now: u64,
+    ) -> Result<RewardBreakdown> {
+        let (key, latest_service_end) = self.estimate_key(period_start)?;
+
+        if let Some((cached_key, breakdown)) = self
+            .inner
+            .estimate_cache
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?
+            .get(node_id)
+        {
+            if *cached_key == key {
+                return Ok(breakdown.clone());
+            }
+        }
+
+        let breakdown = self.node_reward_breakdown(node_id, period_start, now)?;
+
+        // A receipt still running grows with time, so its breakdown is not reused
+        if latest_service_end <= now {
+            self.inner
+                .estimate_cache
+                .lock()
+                .map_err(|_| StorageNodeError::Internal)?
+                .insert(node_id.to_string(), (key, breakdown.clone()));
+        }
+
+        Ok(breakdown)
+    }
+
+    /// The state open-period breakdowns depend on, and the latest end of a receipt
+    ///
+    /// Redistribution depends on every node's receipts, so all of them count.
+    fn estimate_key(&self, period_start: u64) -> Result<(EstimateKey, u64)> {
+        let policy = self.slashing_policy()?;
+
+        let registry = self
+            .inner
+            .receipt_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let aggregates = self
+            .inner
+            .aggregate_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let challenges = self
+            .inner
+            .challenge_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let schedules = self
+            .inner
+            .rate_schedules
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let overrides = self
+            .inner
+            .node_rate_overrides
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let latest_service_end = registry
+            .values()
+            .flatten()
+            .map(|receipt| receipt.service_period.1)
+            .max()
+            .unwrap_or(0);
+
+        let key = EstimateKey {
+            period_start,
+            receipt_count: registry.values().map(Vec::len).sum(),
+            aggregate_count: aggregates.values().map(Vec::len).sum(),
+            challenge_count: challenges.values().map(Vec::len).sum(),
+            schedule_count: schedules.len(),
+            override_count: overrides.values().map(Vec::len).sum(),
+            policy,
+        };
+
+        Ok((key, latest_service_end))
+    }
+
+    /// Current price of the reward token, if there is a price feed
+    async fn reward_token_price(&self) -> Result<Option<Decimal>> {
+        match &self.inner.price_feed {
+            Some(feed) => Ok(Some(
+                feed.get_price(REWARD_TOKEN_ID, REWARD_QUOTE_CURRENCY)
+                    .await?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// A reward in tokens scaled by the token price, if there is one
+    fn price_scaled(reward: u64, price: Option<Decimal>) -> Result<u64> {
+        match price {
+            Some(price) => Decimal::from(reward)
+                .checked_mul(price)
+                .and_then(|scaled| scaled.trunc().to_u64())
+                .ok_or_else(|| {
+                    StorageNodeError::Staking(format!(
+                        "Reward {} cannot be scaled by price {}",
+                        reward, price
+                    ))
+                }),
+            None => Ok(reward),
+        }
+    }
+
+    /// Shares owed to a node that were below the minimum payout
+    ///
+    /// They are added to the node's share of the next vault it receives from.
+    pub fn pending_carryover(&self, node_id: &str) -> Result<u64> {
+        Ok(self
+            .inner
+            .carryover
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?
+            .get(node_id)
+            .copied()
+            .unwrap_or(0))
+    }
+
+    /// Calculate rewards for a node from the rate schedule and slashing policy
+    ///
+    /// Lets a node audit how its reward was computed; the amounts are in
+    /// tokens, before any price feed scaling. A node whose uptime over the
+    /// period fell below the policy minimum, or that failed storage
+    /// challenges in it, has part of its reward slashed. Under
+    /// [`SlashedRewards::Redistribute`] the rewards slashed from all nodes
+    /// are shared among the unslashed ones in proportion to their rewards;
+    /// rounding leftovers are burned.
+    ///
+    /// Settled periods are read from the nodes' receipt aggregates, see
+    /// [`Self::compact_receipts`]. An aggregate only partly inside the period
+    /// counts in proportion to the overlap, and contributes no segments.
+    pub fn node_reward_breakdown(
+        &self,
+        node_id: &str,
+        period_start: u64,
+        period_end: u64,
+    ) -> Result<RewardBreakdown> {
+        let policy = self.slashing_policy()?;
+
+        let registry = self
+            .inner
+            .receipt_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let aggregates = self
+            .inner
+            .aggregate_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let challenges = self
+            .inner
+            .challenge_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        // Calculate rewards based on the rate schedules in effect over the period,
+        // preferring each node's negotiated rates
+        let schedules = self
+            .inner
+            .rate_schedules
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let overrides = self
+            .inner
+            .node_rate_overrides
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let period = (period_start, period_end);
+        let node_breakdown = |node_id: &str| -> Result<_> {
+            let receipts = NodeReceipts {
+                receipts: registry.get(node_id).map(Vec::as_slice).unwrap_or_default(),
+                aggregates: aggregates
+                    .get(node_id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                region_oracle_key: self.inner.region_oracle_key.as_deref(),
+            };
+            let node_challenges = challenges
+                .get(node_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let node_overrides = overrides
+                .get(node_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let node_schedules = node_schedules(&schedules, node_overrides);
+            slashed_breakdown(receipts, node_challenges, &node_schedules, &policy, period)
+        };
+
+        if !registry.contains_key(node_id) && !aggregates.contains_key(node_id) {
+            return Ok(RewardBreakdown::default()); // No receipts for this node
+        }
+        let mut breakdown = node_breakdown(node_id)?;
+
+        if breakdown.slashed > 0 || policy.slashed_rewards != SlashedRewards::Redistribute {
+            return Ok(breakdown);
+        }
+
+        let mut slashed_pool: u64 = 0;
+        let mut compliant_rewards: u64 = 0;
+        let node_ids: HashSet<&String> = registry.keys().chain(aggregates.keys()).collect();
+        for other_id in node_ids {
+            let other = node_breakdown(other_id)?;
+            if other.slashed > 0 {
+                slashed_pool = slashed_pool
+                    .checked_add(other.slashed)
+                    .ok_or_else(|| reward_overflow("slashed reward pool"))?;
+            } else {
+                compliant_rewards = compliant_rewards
+                    .checked_add(other.total)
+                    .ok_or_else(|| reward_overflow("compliant rewards"))?;
+            }
+        }
+
+        if slashed_pool > 0 && compliant_rewards > 0 {
+            // At most the whole pool, as this node's reward is part of the compliant rewards
+            breakdown.redistributed =
+                (slashed_pool as u128 * breakdown.total as u128 / compliant_rewards as u128) as u64;
+            breakdown.total = breakdown
+                .total
+                .checked_add(breakdown.redistributed)
+                .ok_or_else(|| reward_overflow("total reward"))?;
+        }
+
+        Ok(breakdown)
+    }
+
+    /// Update the rate schedule, effective now
+    ///
+    /// Rewards for time before now are still calculated at the earlier rates.
+    /// Fails if a region multiplier exceeds the configured ceiling.
+    pub fn update_rate_schedule(&self, new_schedule: RateSchedule) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.schedule_rate_change(now, new_schedule)
+    }
+
+    /// Set the rate schedule in effect from `effective_from`
+    ///
+    /// The schedule applies until the next later schedule takes effect. A
+    /// schedule taking effect at the same time as an existing one replaces it.
+    /// Fails if a region multiplier exceeds the configured ceiling.
+    pub fn schedule_rate_change(&self, effective_from: u64, schedule: RateSchedule) -> Result<()> {
+        schedule.validate_region_multipliers(self.inner.config.max_region_multiplier)?;
+
+        if let Some(store) = &self.inner.store {
+            store.save_rate_schedule(effective_from, &schedule)?;
+        }
+
+        let mut schedules = self
+            .inner
+            .rate_schedules
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        insert_version(&mut schedules, effective_from, schedule);
+
+        Ok(())
+    }
+
+    /// Get the current rate schedule
+    pub fn rate_schedule(&self) -> Result<RateSchedule> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.get_rate_schedule_at(now)
+    }
+
+    /// Get the rate schedule in effect at `timestamp`
+    pub fn get_rate_schedule_at(&self, timestamp: u64) -> Result<RateSchedule> {
+        let schedules = self
+            .inner
+            .rate_schedules
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        // The history always starts with a schedule effective from time zero
+        version_at(&schedules, timestamp)
+            .map(|(_, schedule)| schedule.clone())
+            .ok_or(StorageNodeError::Internal)
+    }
+
+    /// Every rate schedule with the time it took effect, oldest first
+    pub fn get_schedule_history(&self) -> Result<Vec<(u64, RateSchedule)>> {
+        Ok(self
+            .inner
+            .rate_schedules
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .clone())
+    }
+
+    /// Reward a node at negotiated rates from now on
+    ///
+    /// While the override is in effect it replaces the global rate schedule
+    /// for the node's receipts. Rewards for time before now are unaffected.
+    pub fn set_node_rate_override(&self, node_id: &str, schedule: RateSchedule) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.record_node_rate_override(node_id, now, Some(schedule))
+    }
+
+    /// Reward a node at the global rates again from now on
+    ///
+    /// Has no effect if the node has no override in effect.
+    pub fn clear_node_rate_override(&self, node_id: &str) -> Result<()> {
+        if self.node_rate_override(node_id)?.is_none() {
+            return Ok(());
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.record_node_rate_override(node_id, now, None)
+    }
+
+    /// Get the rate override currently in effect for a node
+    pub fn node_rate_override(&self, node_id: &str) -> Result<Option<RateSchedule>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let overrides = self
+            .inner
+            .node_rate_overrides
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(overrides
+            .get(node_id)
+            .and_then(|history| version_at(history, now))
+            .and_then(|(_, schedule)| schedule.clone()))
+    }
+
+    /// Set or end a node's rate override from `effective_from`
+    fn record_node_rate_override(
+        &self,
+        node_id: &str,
+        effective_from: u64,
+        schedule: Option<RateSchedule>,
+    ) -> Result<()> {
+        if let Some(schedule) = &schedule {
+            schedule.validate_region_multipliers(self.inner.config.max_region_multiplier)?;
+        }
+
+        if let Some(store) = &self.inner.store {
+            store.save_node_rate_override(node_id, effective_from, schedule.as_ref())?;
+        }
+
+        let mut overrides = self
+            .inner
+            .node_rate_overrides
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        insert_version(
+            overrides.entry(node_id.to_string()).or_default(),
+            effective_from,
+            schedule,
+        );
+
+        Ok(())
+    }
+
+    /// Update the slashing policy
+    pub fn update_slashing_policy(&self, new_policy: SlashingPolicy) -> Result<()> {
+        let mut policy = self
+            .inner
+            .slashing_policy
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        *policy = new_policy;
+
+        Ok(())
+    }
+
+    /// Get the current slashing policy
+    pub fn slashing_policy(&self) -> Result<SlashingPolicy> {
+        let policy = self
+            .inner
+            .slashing_policy
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(*policy)
+    }
+
+    /// Register a governance voter's SPHINCS+ public key
+    pub fn add_governance_key(&self, genesis_hash: &str, public_key: Vec<u8>) -> Result<()> {
+        let mut keys = self
+            .inner
+            .governance_keys
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        keys.insert(genesis_hash.to_string(), public_key);
+
+        Ok(())
+    }
+
+    /// Remove a governance voter
+    pub fn remove_governance_key(&self, genesis_hash: &str) -> Result<()> {
+        let mut keys = self
+            .inner
+            .governance_keys
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        keys.remove(genesis_hash);
+
+        Ok(())
+    }
+
+    /// Get the governance voters' public keys by genesis hash
+    pub fn governance_keys(&self) -> Result<HashMap<String, Vec<u8>>> {
+        let keys = self
+            .inner
+            .governance_keys
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(keys.clone())
+    }
+
+    /// Register the SPHINCS+ key a node or client signs receipts with
+    ///
+    /// `id` is the node or client ID the receipts name, and the key must come
+    /// from an authenticated source, such as the identity's genesis state or
+    /// its owner registration. The first key registered for an ID is pinned:
+    /// registering it again has no effect and registering another key fails.
+    pub fn register_identity_key(&self, id: &str, public_key: Vec<u8>) -> Result<()> {
+        let mut keys = self
+            .inner
+            .identity_keys
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        match keys.get(id) {
+            Some(registered) if *registered == public_key => return Ok(()),
+            Some(_) => {
+                return Err(StorageNodeError::Authentication(format!(
+                    "{} is registered with a different key",
+                    id
+                )));
+            }
+            None => {}
+        }
+
+        if let Some(store) = &self.inner.store {
+            store.save_identity_key(id, &public_key)?;
+        }
+        keys.insert(id.to_string(), public_key);
+
+        Ok(())
+    }
+
+    /// Get the key registered for a node or client ID
+    pub fn identity_key(&self, id: &str) -> Result<Option<Vec<u8>>> {
+        let keys = self
+            .inner
+            .identity_keys
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(keys.get(id).cloned())
+    }
+
+    /// Record a governance proposal as executed
+    ///
+    /// Fails if it was executed before, so that each proposal is applied at
+    /// most once, across restarts when the manager has a store.
+    pub fn record_executed_proposal(&self, proposal_id: &str) -> Result<()> {
+        let mut executed = self
+            .inner
+            .executed_proposals
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        if executed.contains(proposal_id) {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Proposal {} was already executed",
+                proposal_id
+            )));
+        }
+
+        if let Some(store) = &self.inner.store {
+            store.save_executed_proposal(proposal_id)?;
+        }
+        executed.insert(proposal_id.to_string());
+
+        Ok(())
+    }
+
+    /// Get all registered vaults
+    pub fn get_vaults(&self) -> Result<Vec<VaultMetadata>> {
+        let registry = self
+            .inner
+            .vault_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        Ok(registry.values().cloned().collect())
+    }
+
+    /// Get a specific vault by ID
+    pub fn get_vault(&self, vault_id: &str) -> Result<VaultMetadata> {
+        let registry = self
+            .inner
+            .vault_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        registry
+            .get(vault_id)
+            .cloned()
+            .ok_or_else(|| StorageNodeError::NotFound(format!("Vault {}", vault_id)))
+    }
+
+    /// Process distribution
+    async fn process_distribution(
+        &self,
+        request: DistributionRequest,
+    ) -> Result<DistributionResult> {
+        // Get the vault metadata
+        let metadata = self.get_vault(&request.vault_id)?;
+
+        // Check if it's time to distribute
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        if now < metadata.distribution_time {
+            return Ok(DistributionResult::failure(
+                request.vault_id,
+                now,
+                format!(
+                    "Not yet time to distribute (scheduled at {})",
+                    metadata.distribution_time
+                ),
+                true,
+            ));
+        }
+
+        // A vault claimed on an earlier attempt only has transfers left to make
+        if metadata.payouts.values().any(Payout::is_outstanding) {
+            return self.execute_payouts(&request.vault_id, now).await;
+        }
+
+        // A claimed or invalidated vault can never be distributed
+        let vault_state = {
+            let vault = self
+                .inner
+                .dlv_manager
+                .get_vault(&request.vault_id)
+                .map_err(|e| StorageNodeError::Staking(format!("Failed to load vault: {}", e)))?;
+            let vault = vault.lock().map_err(|_| StorageNodeError::Internal)?;
+            vault.state.clone()
+        };
+        match vault_state {
+            VaultState::Claimed { .. } => {
+                return Ok(DistributionResult::failure(
+                    request.vault_id,
+                    now,
+                    "Vault already claimed".to_string(),
+                    false,
+                ));
+            }
+            VaultState::Invalidated { reason, .. } => {
+                return Ok(DistributionResult::failure(
+                    request.vault_id,
+                    now,
+                    format!("Vault was invalidated: {}", reason),
+                    false,
+                ));
+            }
+            VaultState::Reclaimed { .. } => {
+                return Ok(DistributionResult::failure(
+                    request.vault_id,
+                    now,
+                    "Vault was reclaimed by its creator".to_string(),
+                    false,
+                ));
+            }
+            VaultState::Limbo | VaultState::Unlocked { .. } => {}
+        }
+
+        // Publish the split and hold the vault until its dispute window closes
+        let proposal = match metadata.proposal.clone() {
+            Some(proposal) => proposal,
+            None => self.propose_distribution(&metadata, now)?,
+        };
+        if metadata.disputes.iter().any(Dispute::is_open) {
+            return Ok(DistributionResult::deferred(
+                request.vault_id,
+                now,
+                FROZEN_DISTRIBUTION,
+            ));
+        }
+        if let Some(DisputeResolution::Cancel { reason }) = metadata
+            .disputes
+            .last()
+            .and_then(|dispute| dispute.resolution.as_ref())
+        {
+            return Ok(DistributionResult::failure(
+                request.vault_id,
+                now,
+                format!("Distribution cancelled after dispute: {}", reason),
+                false,
+            ));
+        }
+        if now < proposal.dispute_deadline {
+            return Ok(DistributionResult::deferred(
+                request.vault_id,
+                now,
+                proposal.dispute_deadline,
+            ));
+        }
+
+        // Try to unlock the vault
+        // Create a time proof based on the reference state
+        let time_proof = FulfillmentProof::TimeProof {
+            reference_state: request.reference_state.hash.clone(),
+            state_proof: vec![], // Empty proof since we're using direct state
+        };
+
+        let claimant_key = CLAIMANT_KEY;
+
+        // Try to unlock the vault
+        match self.inner.dlv_manager.try_unlock_vault(
+            &request.vault_id,
+            time_proof,
+            &claimant_key,
+            &request.reference_state,
+        ) {
+            Ok(true) => {
+                // Successfully unlocked, now claim the content
+                match self.inner.dlv_manager.claim_vault_content(
+                    &request.vault_id,
+                    &claimant_key,
+                    &request.reference_state,
+                    None,
+                ) {
+                    Ok(content) => {
+                        self.save_limbo_vault(&request.vault_id)?;
+
+                        // Deserialize the content
+                        let vault_content: VaultContent = bincode::deserialize(&content)
+                            .map_err(|e| StorageNodeError::Serialization(e.to_string()))?;
+
+                        // Pay the proposed split, as amended by any dispute resolution
+                        let total = proposal
+                            .amounts
+                            .values()
+                            .try_fold(0u64, |sum, amount| sum.checked_add(*amount));
+                        if !matches!(total, Some(total) if total <= vault_content.token_amount) {
+                            return Ok(DistributionResult::failure(
+                                request.vault_id,
+                                now,
+                                "Proposed split exceeds the vault content".to_string(),
+                                false,
+                            ));
+                        }
+                        let distributions = self.apply_carryover(proposal.amounts)?;
+
+                        if let Some(audit_log) = &self.inner.audit_log {
+                            audit_log.append(
+                                &request.vault_id,
+                                *::blake3::hash(&content).as_bytes(),
+                                distributions.clone().into_iter().collect(),
+                                proposal.receipt_hashes.into_values().flatten().collect(),
+                                now,
+                            )?;
+                        }
+
+                        // Update vault status, with the transfers still to make
+                        if self.inner.executor.is_some() {
+                            let payouts = distributions
+                                .iter()
+                                .map(|(node_id, amount)| {
+                                    (node_id.clone(), Payout::pending(*amount))
+                                })
+                                .collect();
+                            self.update_vault(&request.vault_id, |metadata| {
+                                metadata.status = "claimed".to_string();
+                                metadata.payouts = payouts;
+                                Ok(())
+                            })?;
+                        } else {
+                            self.update_vault_status(&request.vault_id, "claimed")?;
+                        }
+
+                        // The recipients' receipts for the period are settled
+                        if self.inner.config.compact_receipts {
+                            for node_id in metadata.recipients.keys() {
+                                let compacted =
+                                    self.compact_node_receipts(node_id, metadata.distribution_time);
+                                if let Err(e) = compacted {
+                                    warn!(
+                                        node_id = %node_id,
+                                        error = %e,
+                                        "Failed to compact receipts of node"
+                                    );
+                                }
+                            }
+                        }
+
+                        if self.inner.executor.is_some() {
+                            return self.execute_payouts(&request.vault_id, now).await;
+                        }
+
+                        // Return success result
+                        Ok(DistributionResult {
+                            vault_id: request.vault_id,
+                            success: true,
+                            timestamp: now,
+                            error: None,
+                            distribution_details: Some(distributions),
+                            retryable: false,
+                            deferred_until: None,
+                            transfer_states: HashMap::new(),
+                        })
+                    }
+                    Err(e) => {
+                        // Failed to claim; the unlock may not yet hold against the reference state
+                        Ok(DistributionResult::failure(
+                            request.vault_id,
+                            now,
+                            format!("Failed to claim vault: {}", e),
+                            true,
+                        ))
+                    }
+                }
+            }
+            Ok(false) => {
+                // Failed to unlock (conditions not met yet)
+                Ok(DistributionResult::failure(
+                    request.vault_id,
+                    now,
+                    "Failed to unlock vault: conditions not met".to_string(),
+                    true,
+                ))
+            }
+            Err(e) => {
+                // Error unlocking, treated as transient
+                Ok(DistributionResult::failure(
+                    request.vault_id,
+                    now,
+                    format!("Error unlocking vault: {}", e),
+                    true,
+                ))
+            }
+        }
+    }
+
+    /// Make the outstanding transfers of a claimed vault's payouts
+    ///
+    /// Each payout is persisted as in flight before its transfer is sent, and
+    /// its status as soon as it is known, so a retry only sends the transfers
+    /// that have not gone through. A transfer whose outcome was lost is sent
+    /// again under the same ID, and the executor does not repeat it.
+    async fn execute_payouts(&self, vault_id: &str, now: u64) -> Result<DistributionResult> {
+        let executor = self.inner.executor.as_ref().ok_or_else(|| {
+            StorageNodeError::Staking(format!(
+                "No distribution executor to pay out vault {}",
+                vault_id
+            ))
+        })?;
+        let metadata = self.get_vault(vault_id)?;
+
+        let mut failed = Vec::new();
+        for (node_id, payout) in &metadata.payouts {
+            if !payout.is_outstanding() {
+                continue;
+            }
+
+            self.update_vault(vault_id, |metadata| {
+                if let Some(payout) = metadata.payouts.get_mut(node_id) {
+                    payout.status = PayoutStatus::InFlight;
+                }
+                Ok(())
+            })?;
+
+            let transfer =
+                PayoutTransfer::new(vault_id, node_id, &metadata.token_id, payout.amount);
+            let status = match executor.execute(&transfer).await {
+                Ok(state_number) => PayoutStatus::Executed { state_number },
+                Err(e) => {
+                    warn!(
+                        vault_id = %vault_id,
+                        node_id = %node_id,
+                        error = %e,
+                        "Reward transfer failed"
+                    );
+                    failed.push(node_id.as_str());
+                    PayoutStatus::Failed {
+                        error: e.to_string(),
+                    }
+                }
+            };
+
+            self.update_vault(vault_id, |metadata| {
+                if let Some(payout) = metadata.payouts.get_mut(node_id) {
+                    payout.status = status;
+                }
+                Ok(())
+            })?;
+        }
+
+        if !failed.is_empty() {
+            return Ok(DistributionResult::failure(
+                vault_id.to_string(),
+                now,
+                format!(
+                    "Transfers to {} of {} recipients failed: {}",
+                    failed.len(),
+                    metadata.payouts.len(),
+                    failed.join(", ")
+                ),
+                true,
+            ));
+        }
+
+        let payouts = self.get_vault(vault_id)?.payouts;
+        Ok(DistributionResult {
+            vault_id: vault_id.to_string(),
+            success: true,
+            timestamp: now,
+            error: None,
+            distribution_details: Some(
+                payouts
+                    .iter()
+                    .map(|(node_id, payout)| (node_id.clone(), payout.amount))
+                    .collect(),
+            ),
+            retryable: false,
+            deferred_until: None,
+            transfer_states: payouts
+                .iter()
+                .filter_map(|(node_id, payout)| Some((node_id.clone(), payout.state_number()?)))
+                .collect(),
+        })
+    }
+
+    /// Process a ready distribution, then retry it or give up on it if it failed
+    async fn handle_distribution(&self, request: DistributionRequest, now: u64) -> Result<()> {
+        let (record, retryable) = match self.process_distribution(request.clone()).await {
+            // Put off without an attempt; nothing is recorded until it is processed
+            Ok(DistributionResult {
+                deferred_until: Some(until),
+                ..
+            }) => {
+                let vault_id = request.vault_id.clone();
+                let mut request = request;
+                request.retry_at = until;
+                self.requeue_distribution(request)?;
+
+                // A dispute resolved while the request was out of the queue
+                // could not resume it
+                if until == FROZEN_DISTRIBUTION
+                    && !self
+                        .get_vault(&vault_id)?
+                        .disputes
+                        .iter()
+                        .any(Dispute::is_open)
+                {
+                    self.resume_distribution(&vault_id)?;
+                }
+                return Ok(());
+            }
+            Ok(result) => {
+                let retryable = result.retryable;
+                (DistributionRecord::from_result(&request, result), retryable)
+            }
+            Err(e) => {
+                error!(vault_id = %request.vault_id, error = %e, "Failed to process distribution");
+                (
+                    DistributionRecord::failed(&request, now, e.to_string()),
+                    false,
+                )
+            }
+        };
+
+        let outcome = match record.error.clone() {
+            None => self.remove_distribution(&request.vault_id),
+            Some(error) => {
+                let mut request = request;
+                request.attempts += 1;
+                request.last_error = Some(error.clone());
+
+                if retryable && request.attempts < self.inner.config.retry_policy.max_attempts {
+                    let delay = self.inner.config.retry_policy.delay_after(request.attempts);
+                    request.retry_at = now.saturating_add(delay.as_secs());
+                    warn!(
+                        vault_id = %request.vault_id,
+                        attempts = request.attempts,
+                        retry_in_secs = delay.as_secs(),
+                        error = %error,
+                        "Distribution of vault failed, retrying"
+                    );
+                    self.requeue_distribution(request)
+                } else {
+                    warn!(
+                        vault_id = %request.vault_id,
+                        attempts = request.attempts,
+                        error = %error,
+                        "Distribution of vault failed, giving up"
+                    );
+                    self.fail_distribution(FailedDistribution {
+                        request,
+                        error,
+                        failed_at: now,
+                    })
+                }
+            }
+        };
+
+        self.record_distribution(record)?;
+        outcome
+    }
+
+    /// Put a failed distribution back in the queue for a later attempt
+    fn requeue_distribution(&self, request: DistributionRequest) -> Result<()> {
+        if let Some(store) = &self.inner.store {
+            store.save_distribution(&request)?;
+        }
+
+        self.inner
+            .distribution_queue
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?
+            .push(request);
+
+        Ok(())
+    }
+
+    /// Move a distribution that will not be retried to the failed list
+    fn fail_distribution(&self, failed: FailedDistribution) -> Result<()> {
+        self.remove_distribution(&failed.request.vault_id)?;
+
+        if let Some(store) = &self.inner.store {
+            store.save_failed_distribution(&failed)?;
+        }
+
+        self.inner
+            .failed_distributions
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .push(failed);
+
+        Ok(())
+    }
+
+    /// Forget a distribution that is no longer pending
+    fn remove_distribution(&self, vault_id: &str) -> Result<()> {
+        match &self.inner.store {
+            Some(store) => store.remove_distribution(vault_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Hashes of the receipts justifying a vault's distribution, by node ID
+    ///
+    /// These are the receipts of the vault's recipients whose service period
+    /// ended by its distribution time and that no earlier audit record cites.
+    fn contributing_receipts(
+        &self,
+        metadata: &VaultMetadata,
+    ) -> Result<BTreeMap<String, Vec<[u8; 32]>>> {
+        let registry = self
+            .inner
+            .receipt_registry
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let mut receipt_hashes = BTreeMap::new();
+        for node_id in metadata.recipients.keys() {
+            let mut cited = Vec::new();
+            for receipt in registry.get(node_id).into_iter().flatten() {
+                let already_cited = match &self.inner.audit_log {
+                    Some(audit_log) => audit_log.is_cited(&receipt.receipt_hash)?,
+                    None => false,
+                };
+                if receipt.service_period.1 <= metadata.distribution_time && !already_cited {
+                    cited.push(receipt.receipt_hash);
+                }
+            }
+            if !cited.is_empty() {
+                receipt_hashes.insert(node_id.clone(), cited);
+            }
+        }
+
+        Ok(receipt_hashes)
+    }
+
+    /// Publish the split of a vault whose distribution time has arrived
+    fn propose_distribution(
+        &self,
+        metadata: &VaultMetadata,
+        now: u64,
+    ) -> Result<ProposedDistribution> {
+        let proposal = ProposedDistribution {
+            vault_id: metadata.vault_id.clone(),
+            amounts: split_by_ratios(metadata.token_amount, &metadata.recipients),
+            receipt_hashes: self.contributing_receipts(metadata)?,
+            proposed_at: now,
+            dispute_deadline: metadata
+                .distribution_time
+                .saturating_add(metadata.dispute_window.as_secs()),
+        };
+
+        self.update_vault(&metadata.vault_id, |vault| {
+            vault.proposal = Some(proposal.clone());
+            Ok(())
+        })?;
+
+        // Sending only fails when nobody is subscribed
+        let _ = self.inner.proposal_tx.send(proposal.clone());
+        Ok(proposal)
+    }
+
+    /// Dispute the proposed split of a vault
+    ///
+    /// The claim must be signed with the key registered for `node_id` (see
+    /// [`Self::register_identity_key`]) and filed before the vault's dispute
+    /// window closes.
+    /// The distribution stays frozen until [`Self::resolve_dispute`].
+    pub fn file_dispute(
+        &self,
+        vault_id: &str,
+        node_id: &str,
+        signed_claim: DisputeClaim,
+    ) -> Result<()> {
+        if signed_claim.vault_id != vault_id || signed_claim.node_id != node_id {
+            return Err(StorageNodeError::InvalidInput(
+                "Dispute claim is for another vault or node".to_string(),
+            ));
+        }
+        signed_claim.verify()?;
+
+        if self.identity_key(node_id)?.as_ref() != Some(&signed_claim.node_public_key) {
+            return Err(StorageNodeError::Authentication(format!(
+                "Dispute key is not the key registered for node {}",
+                node_id
+            )));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.update_vault(vault_id, |metadata| {
+            let Some(proposal) = &metadata.proposal else {
+                return Err(StorageNodeError::Staking(format!(
+                    "Vault {} has no proposed distribution to dispute",
+                    vault_id
+                )));
+            };
+            if now >= proposal.dispute_deadline {
+                return Err(StorageNodeError::Staking(format!(
+                    "Dispute window of vault {} closed at {}",
+                    vault_id, proposal.dispute_deadline
+                )));
+            }
+
+            metadata.disputes.push(Dispute {
+                claim: signed_claim,
+                filed_at: now,
+                resolution: None,
+                resolved_at: None,
+            });
+            Ok(())
+        })
+    }
+
+    /// Settle the open disputes of a vault and resume its distribution
+    ///
+    /// Amended amounts may not add up to more than the vault holds. A
+    /// cancelled distribution is moved to the failed distributions when it is
+    /// next processed.
+    pub fn resolve_dispute(&self, vault_id: &str, resolution: DisputeResolution) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.update_vault(vault_id, |metadata| {
+            if !metadata.disputes.iter().any(Dispute::is_open) {
+                return Err(StorageNodeError::Staking(format!(
+                    "Vault {} has no open dispute",
+                    vault_id
+                )));
+            }
+
+            if let DisputeResolution::Amend { amounts } = &resolution {
+                let total = amounts
+                    .values()
+                    .try_fold(0u64, |sum, amount| sum.checked_add(*amount));
+                if !matches!(total, Some(total) if total <= metadata.token_amount) {
+                    return Err(StorageNodeError::InvalidInput(format!(
+                        "Amended amounts exceed the {} held by vault {}",
+                        metadata.token_amount, vault_id
+                    )));
+                }
+                if let Some(proposal) = &mut metadata.proposal {
+                    proposal.amounts = amounts.clone();
+                }
+            }
+
+            for dispute in metadata.disputes.iter_mut().filter(|d| d.is_open()) {
+                dispute.resolution = Some(resolution.clone());
+                dispute.resolved_at = Some(now);
+            }
+            Ok(())
+        })?;
+
+        self.resume_distribution(vault_id)
+    }
+
+    /// Make a vault's queued distribution due again and wake the processor
+    fn resume_distribution(&self, vault_id: &str) -> Result<()> {
+        {
+            let mut queue = self
+                .inner
+                .distribution_queue
+                .lock()
+                .map_err(|_| StorageNodeError::Internal)?;
+
+            if let Some(request) = queue.iter_mut().find(|r| r.vault_id == vault_id) {
+                let mut resumed = request.clone();
+                resumed.retry_at = 0;
+                if let Some(store) = &self.inner.store {
+                    store.save_distribution(&resumed)?;
+                }
+                *request = resumed;
+            }
+        }
+
+        self.trigger_distribution_check_now();
+        Ok(())
+    }
+
+    /// Revoke a reward vault before it is distributed
+    ///
+    /// `creator_signature` must sign [`LimboVault::invalidation_message`] of
+    /// the vault and `reason` with the creator key the vault was registered
+    /// with. The vault is invalidated in the DLV manager, its pending
+    /// distribution is dropped and the revocation is recorded in the audit
+    /// log. A vault that was already claimed cannot be revoked.
+    pub fn revoke_reward_vault(
+        &self,
+        vault_id: &str,
+        creator_signature: &[u8],
+        reason: &str,
+    ) -> Result<()> {
+        let metadata = self.get_vault(vault_id)?;
+        if metadata.status == REVOKED_STATUS {
+            return Err(StorageNodeError::Staking(format!(
+                "Vault {} is already revoked",
+                vault_id
+            )));
+        }
+        let already_claimed = || {
+            StorageNodeError::Staking(format!(
+                "Vault {} was already claimed and cannot be revoked",
+                vault_id
+            ))
+        };
+        if metadata.status == "claimed"
+            || metadata.status == CLAIMABLE_STATUS
+            || !metadata.claims.is_empty()
+        {
+            return Err(already_claimed());
+        }
+
+        let (reference_state_hash, state_number) = {
+            let vault =
+                self.inner.dlv_manager.get_vault(vault_id).map_err(|e| {
+                    StorageNodeError::Staking(format!("Failed to load vault: {}", e))
+                })?;
+            let vault = vault.lock().map_err(|_| StorageNodeError::Internal)?;
+            if matches!(vault.state, VaultState::Claimed { .. }) {
+                return Err(already_claimed());
+            }
+            (vault.reference_state_hash.clone(), vault.created_at_state)
+        };
+
+        let message = LimboVault::invalidation_message(vault_id, reason, &reference_state_hash);
+        let signed = hex::decode(&metadata.creator_id)
+            .map(|key| verify_signature_or_false(&key, &message, creator_signature))
+            .unwrap_or(false);
+        if !signed {
+            return Err(StorageNodeError::Authentication(format!(
+                "Invalid revocation signature for vault {}",
+                vault_id
+            )));
+        }
+
+        self.inner
+            .dlv_manager
+            .invalidate_vault_with_signature(vault_id, reason, creator_signature, state_number)
+            .map_err(|e| StorageNodeError::Staking(format!("Failed to revoke vault: {}", e)))?;
+        self.save_limbo_vault(vault_id)?;
+
+        self.remove_distribution(vault_id)?;
+        self.inner
+            .distribution_queue
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?
+            .retain(|request| request.vault_id != vault_id);
+
+        self.update_vault(vault_id, |metadata| {
+            metadata.status = REVOKED_STATUS.to_string();
+            metadata.revocation_reason = Some(reason.to_string());
+            Ok(())
+        })?;
+
+        if let Some(audit_log) = &self.inner.audit_log {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            audit_log.append_revocation(vault_id, reason, now)?;
+        }
+
+        Ok(())
+    }
+
+    /// Claim a node's share of a vault created with [`Self::create_claimable_vault`]
+    ///
+    /// `node_signature` must sign [`claim_signing_hash`] with the key
+    /// registered for the node (see [`Self::register_identity_key`]), not one
+    /// taken from the receipts it submitted. The first claim unlocks the vault against
+    /// `reference_state` and checks its content against the registered
+    /// recipients. Each recipient can claim its share once.
+    ///
+    /// # Returns
+    /// * `Result<ClaimVoucher>` - The claimed share, to pay out with the TokenSDK
+    pub fn claim_reward(
+        &self,
+        vault_id: &str,
+        node_id: &str,
+        node_signature: &[u8],
+        reference_state: &State,
+    ) -> Result<ClaimVoucher> {
+        let metadata = self.get_vault(vault_id)?;
+        if !metadata.pull_claims {
+            return Err(StorageNodeError::Staking(format!(
+                "Vault {} is distributed by the manager and cannot be claimed",
+                vault_id
+            )));
+        }
+        if !metadata.recipients.contains_key(node_id) {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "Node {} is not a recipient of vault {}",
+                node_id, vault_id
+            )));
+        }
+
+        let signing_hash = claim_signing_hash(vault_id, node_id, reference_state);
+        let signed = self
+            .identity_key(node_id)?
+            .is_some_and(|key| verify_signature_or_false(&key, &signing_hash, node_signature));
+        if !signed {
+            return Err(StorageNodeError::Authentication(format!(
+                "Invalid claim signature from node {}",
+                node_id
+            )));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut amount = 0;
+
+        // Unlocking and recording the claim under the registry lock keeps
+        // concurrent claims from unlocking twice or claiming the same share
+        self.update_vault(vault_id, |metadata| {
+            if metadata.claims.contains_key(node_id) {
+                return Err(StorageNodeError::Staking(format!(
+                    "Node {} already claimed its share of vault {}",
+                    node_id, vault_id
+                )));
+            }
+            if metadata.status != CLAIMABLE_STATUS {
+                self.unlock_claimable_vault(metadata, node_id, reference_state)?;
+                metadata.status = CLAIMABLE_STATUS.to_string();
+            }
+
+            // The vault marks the share as claimed, so it cannot be paid twice
+            let share = self
+                .inner
+                .dlv_manager
+                .claim_vault_share(vault_id, node_id.as_bytes(), reference_state)
+                .map_err(|e| StorageNodeError::Staking(format!("Failed to claim share: {}", e)))?;
+            self.save_limbo_vault(vault_id)?;
+            amount = share.amount;
+
+            metadata.claims.insert(node_id.to_string(), share.amount);
+            Ok(())
+        })?;
+
+        Ok(ClaimVoucher {
+            vault_id: vault_id.to_string(),
+            node_id: node_id.to_string(),
+            token_id: metadata.token_id,
+            amount,
+            reference_state_hash: reference_state.hash.clone(),
+            claimed_at: now,
+            node_signature: node_signature.to_vec(),
+        })
+    }
+
+    /// Recipients of a claimable vault that have not claimed their share, sorted
+    pub fn get_unclaimed_recipients(&self, vault_id: &str) -> Result<Vec<String>> {
+        let metadata = self.get_vault(vault_id)?;
+
+        let mut unclaimed: Vec<String> = metadata
+            .recipients
+            .into_keys()
+            .filter(|node_id| !metadata.claims.contains_key(node_id))
+            .collect();
+        unclaimed.sort();
+
+        Ok(unclaimed)
+    }
+
+    /// Unlock a claimable vault and check its split against its registration
+    ///
+    /// The vault is unlocked by the first claiming recipient. Runs under the
+    /// vault registry lock, so it must not go through the registry.
+    fn unlock_claimable_vault(
+        &self,
+        metadata: &VaultMetadata,
+        node_id: &str,
+        reference_state: &State,
+    ) -> Result<()> {
+        let time_proof = FulfillmentProof::TimeProof {
+            reference_state: reference_state.hash.clone(),
+            state_proof: vec![],
+        };
+
+        let unlocked = self
+            .inner
+            .dlv_manager
+            .try_unlock_vault(
+                &metadata.vault_id,
+                time_proof,
+                node_id.as_bytes(),
+                reference_state,
+            )
+            .map_err(|e| StorageNodeError::Staking(format!("Failed to unlock vault: {}", e)))?;
+        if !unlocked {
+            return Err(StorageNodeError::Staking(format!(
+                "Vault {} cannot be claimed before {}",
+                metadata.vault_id, metadata.distribution_time
+            )));
+        }
+
+        let vault = self
+            .inner
+            .dlv_manager
+            .get_vault(&metadata.vault_id)
+            .map_err(|e| StorageNodeError::Staking(format!("Failed to load vault: {}", e)))?;
+        let vault = vault.lock().map_err(|_| StorageNodeError::Internal)?;
+        let registered = split_allocations(&metadata.recipients);
+
+        if !vault.split.as_ref().is_some_and(|split| {
+            split.token_amount == metadata.token_amount
+                && split.token_id == metadata.token_id
+                && split.allocations == registered
+        }) {
+            return Err(StorageNodeError::InvalidState(format!(
+                "Content of vault {} does not match its registration",
+                metadata.vault_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Add carried-over shares to a vault's split and carry over the shares
+    /// still below the minimum payout
+    ///
+    /// Holding the carryover lock from read to write means a carried-over
+    /// share is paid by exactly one of two vaults distributed concurrently.
+    fn apply_carryover(&self, amounts: HashMap<String, u64>) -> Result<HashMap<String, u64>> {
+        let mut carryover = self
+            .inner
+            .carryover
+            .lock()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let mut updated = carryover.clone();
+        let mut payouts = HashMap::with_capacity(amounts.len());
+        for (node_id, amount) in amounts {
+            let owed = updated.remove(&node_id).unwrap_or(0);
+            let share = amount
+                .checked_add(owed)
+                .ok_or_else(|| reward_overflow("carryover"))?;
+
+            if share < self.inner.config.min_payout {
+                if share > 0 {
+                    updated.insert(node_id, share);
+                }
+            } else {
+                payouts.insert(node_id, share);
+            }
+        }
+
+        if updated != *carryover {
+            if let Some(store) = &self.inner.store {
+                store.save_carryover(&updated)?;
+            }
+            *carryover = updated;
+        }
+
+        Ok(payouts)
+    }
+
+    /// Apply a change to a vault's metadata, persisting it before it is visible
+    fn update_vault(
+        &self,
+        vault_id: &str,
+        change: impl FnOnce(&mut VaultMetadata) -> Result<()>,
+    ) -> Result<()> {
+        let mut registry = self
+            .inner
+            .vault_registry
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?;
+
+        let metadata = registry
+            .get_mut(vault_id)
+            .ok_or_else(|| StorageNodeError::NotFound(format!("Vault {}", vault_id)))?;
+
+        let mut updated = metadata.clone();
+        change(&mut updated)?;
+        if let Some(store) = &self.inner.store {
+            store.save_vault(&updated)?;
+        }
+        *metadata = updated;
+
+        Ok(())
+    }
+
+    /// Update a vault's status
+    fn update_vault_status(&self, vault_id: &str, status: &str) -> Result<()> {
+        self.update_vault(vault_id, |metadata| {
+            metadata.status = status.to_string();
+            Ok(())
+        })
+    }
+
+    /// Start the distribution processor
+    ///
+    /// The task drains the manager's own queue and holds the manager weakly,
+    /// so it stops once the manager is dropped or shut down.
+    fn start_distribution_processor(&self) -> JoinHandle<()> {
+        let shared = Arc::downgrade(&self.inner);
+        let distribution_queue = Arc::clone(&self.inner.distribution_queue);
+        let mut shutdown_rx = self.inner.shutdown_tx.subscribe();
+        let check_now = Arc::clone(&self.inner.check_now);
+        let period = self.inner.config.check_interval;
+
+        // Spawn processing task
+        tokio::spawn(async move {
+            let mut check_interval = interval(period);
+
+            loop {
+                tokio::select! {
+                    _ = check_interval.tick() => {}
+                    _ = check_now.notified() => {}
+                    // Also fires when the manager is dropped
+                    _ = shutdown_rx.changed() => return,
+                }
+
+                // Get current time
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+
+                // Get requests to process
+                let to_process = {
+                    let mut queue = match distribution_queue.lock() {
+                        Ok(queue) => queue,
+                        Err(_) => {
+                            error!("Distribution queue lock poisoned, stopping processor");
+                            return;
                         }
+                    };
+
+                    // Find ready requests and put pending requests back in queue
+                    let (ready, pending): (Vec<_>, Vec<_>) =
+                        queue.drain(..).partition(|req| req.due_at() <= now);
+                    *queue = pending;
+                    ready
+                };
+
+                if to_process.is_empty() {
+                    continue;
+                }
+
+                let Some(inner) = shared.upgrade() else {
+                    return;
+                };
+                let manager = RewardVaultManager { inner };
+
+                // Process each ready request, stopping early on shutdown
+                let mut ready = to_process.into_iter();
+                for request in ready.by_ref() {
+                    let vault_id = request.vault_id.clone();
+                    if let Err(e) = manager.handle_distribution(request, now).await {
+                        error!(vault_id = %vault_id, error = %e, "Failed to handle distribution");
+                    }
+
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+
+                // Requests skipped by a shutdown stay pending
+                let skipped: Vec<_> = ready.collect();
+                if !skipped.is_empty() {
+                    match distribution_queue.lock() {
+                        Ok(mut queue) => queue.extend(skipped),
+                        Err(_) => error!("Distribution queue lock poisoned, dropping requests"),
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Drop for RewardManagerInner {
+    fn drop(&mut self) {
+        // Last resort for managers dropped without being shut down
+        if let Ok(Some(processor)) = self.processor.get_mut().map(Option::take) {
+            processor.abort();
+        }
+    }
+}
+
+/// Allocations of a claimable vault's split, one per recipient, by node ID
+fn split_allocations(recipients: &HashMap<String, Ratio>) -> Vec<SplitAllocation> {
+    let mut allocations: Vec<SplitAllocation> = recipients
+        .iter()
+        .map(|(node_id, ratio)| SplitAllocation {
+            recipient: node_id.as_bytes().to_vec(),
+            share: token_types::Ratio::new(ratio.raw_value(), RATIO_SCALE),
+        })
+        .collect();
+    allocations.sort_by(|a, b| a.recipient.cmp(&b.recipient));
+    allocations
+}
+
+/// Check that ratios sum to 1.0, within [`RATIO_SUM_TOLERANCE`]
+fn check_ratio_sum<'a>(ratios: impl Iterator<Item = &'a Ratio>, kind: &str) -> Result<()> {
+    let ratio_sum: u128 = ratios.map(|r| r.raw_value() as u128).sum();
+    if ratio_sum.abs_diff(RATIO_SCALE as u128) > RATIO_SUM_TOLERANCE as u128 {
+        return Err(StorageNodeError::Staking(format!(
+            "Invalid {} ratios: sum must be 1.0, got {}",
+            kind,
+            ratio_sum as f64 / RATIO_SCALE as f64
+        )));
+    }
+
+    Ok(())
+}
+
+/// Split an amount between recipients by their ratios, rounding down
+///
+/// Recipients are paid in order of their IDs, and no recipient is paid more
+/// than is left, so the amounts never add up to more than `total` even when
+/// the ratios sum to slightly over 1.0.
+fn split_by_ratios(total: u64, ratios: &HashMap<String, Ratio>) -> HashMap<String, u64> {
+    let mut recipients: Vec<_> = ratios.iter().collect();
+    recipients.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut remaining = total;
+    recipients
+        .into_iter()
+        .map(|(recipient, ratio)| {
+            let amount = ratio.apply_to(total).min(remaining);
+            remaining -= amount;
+            (recipient.clone(), amount)
+        })
+        .collect()
+}
+
+/// Insert a value into a history sorted by effective time
+///
+/// A value taking effect at the same time as an existing one replaces it.
+fn insert_version<T>(history: &mut Vec<(u64, T)>, effective_from: u64, value: T) {
+    match history.binary_search_by_key(&effective_from, |(from, _)| *from) {
+        Ok(index) => history[index].1 = value,
+        Err(index) => history.insert(index, (effective_from, value)),
+    }
+}
+
+/// Entry of a history sorted by effective time that is in effect at `timestamp`
+fn version_at<T>(history: &[(u64, T)], timestamp: u64) -> Option<&(u64, T)> {
+    history
+        .iter()
+        .rev()
+        .find(|(effective_from, _)| *effective_from <= timestamp)
+}
+
+/// Rate schedules applying to a node, with the time each starts to apply
+///
+/// An override in effect replaces the global schedule; consecutive periods
+/// under the same schedule are merged.
+fn node_schedules<'a>(
+    global: &'a [(u64, RateSchedule)],
+    overrides: &'a [(u64, Option<RateSchedule>)],
+) -> Vec<(u64, ScheduleSource, &'a RateSchedule)> {
+    let mut starts: Vec<u64> = global
+        .iter()
+        .map(|(from, _)| *from)
+        .chain(overrides.iter().map(|(from, _)| *from))
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+
+    let mut schedules: Vec<(u64, ScheduleSource, &RateSchedule)> = starts
+        .into_iter()
+        .filter_map(|start| match version_at(overrides, start) {
+            Some((effective_from, Some(schedule))) => Some((
+                start,
+                ScheduleSource::Override {
+                    effective_from: *effective_from,
+                },
+                schedule,
+            )),
+            _ => version_at(global, start).map(|(effective_from, schedule)| {
+                (
+                    start,
+                    ScheduleSource::Global {
+                        effective_from: *effective_from,
+                    },
+                    schedule,
+                )
+            }),
+        })
+        .collect();
+    schedules.dedup_by(|next, previous| next.1 == previous.1);
+
+    schedules
+}
+
+/// Reward for a receipt's service within a window of time
+///
+/// The window is split where a new rate schedule takes effect, and each
+/// segment is rewarded at the rates in effect during it. Retrievals and
+/// operations are spread evenly over the receipt's whole service period, so
+/// a segment gets them in proportion to the part of that period it covers
+/// and windows splitting a receipt sum to its full counts.
+///
+/// Only regions proven to the oracle with `region_oracle_key` earn their
+/// multiplier.
+fn segmented_breakdown(
+    receipt: &StorageReceipt,
+    (start, end): (u64, u64),
+    schedules: &[(u64, ScheduleSource, &RateSchedule)],
+    region_oracle_key: Option<&[u8]>,
+) -> Result<RewardBreakdown> {
+    let metrics = &receipt.storage_metrics;
+    let (service_start, service_end) = receipt.service_period;
+    let service_secs = service_end.saturating_sub(service_start);
+    let mut breakdown = RewardBreakdown::default();
+    let mut segment_metrics = metrics.clone();
+    segment_metrics
+        .regions
+        .retain(|region| region_oracle_key.is_some_and(|key| is_region_proven(region, key)));
+
+    // Counts falling in the receipt's service period before the window
+    let before_window = start.saturating_sub(service_start);
+    let mut retrievals = prorate(metrics.retrievals, before_window, service_secs);
+    let mut operations = prorate(metrics.operations_count, before_window, service_secs);
+
+    for (index, (effective_from, source, schedule)) in schedules.iter().enumerate() {
+        let next_from = schedules
+            .get(index + 1)
+            .map_or(u64::MAX, |(from, ..)| *from);
+        let segment_start = start.max(*effective_from);
+        let segment_end = end.min(next_from);
+        if segment_start >= segment_end {
+            continue;
+        }
+
+        // Counts up to the end of the segment, less those already attributed
+        let elapsed = segment_end - service_start;
+        let retrievals_to_date = prorate(metrics.retrievals, elapsed, service_secs);
+        let operations_to_date = prorate(metrics.operations_count, elapsed, service_secs);
+        segment_metrics.retrievals = retrievals_to_date - retrievals;
+        segment_metrics.operations_count = operations_to_date - operations;
+        (retrievals, operations) = (retrievals_to_date, operations_to_date);
+
+        let mut segment = schedule.breakdown(segment_end - segment_start, &segment_metrics)?;
+        segment.segments.push(ScheduleSegment {
+            receipt_hash: receipt.receipt_hash,
+            start: segment_start,
+            end: segment_end,
+            source: *source,
+            reward: segment.total,
+        });
+        breakdown.accumulate(&segment)?;
+    }
+
+    Ok(breakdown)
+}
+
+/// `count` scaled by `part / whole`, rounding down
+fn prorate(count: u64, part: u64, whole: u64) -> u64 {
+    (count as u128 * part as u128 / whole as u128) as u64
+}
+
+/// Every amount of a breakdown scaled by `part / whole`, without its segments
+fn prorated_breakdown(breakdown: &RewardBreakdown, part: u64, whole: u64) -> RewardBreakdown {
+    RewardBreakdown {
+        storage_reward: prorate(breakdown.storage_reward, part, whole),
+        retrieval_reward: prorate(breakdown.retrieval_reward, part, whole),
+        operation_reward: prorate(breakdown.operation_reward, part, whole),
+        slashed: prorate(breakdown.slashed, part, whole),
+        redistributed: prorate(breakdown.redistributed, part, whole),
+        total: prorate(breakdown.total, part, whole),
+        region_multiplier: breakdown.region_multiplier,
+        segments: Vec::new(),
+    }
+}
+
+/// A node's raw receipts and the aggregates of its settled ones
+#[derive(Clone, Copy)]
+struct NodeReceipts<'a> {
+    receipts: &'a [StorageReceipt],
+    aggregates: &'a [AggregatedReceipt],
+
+    /// Oracle key the regions claimed in the receipts are proven to
+    region_oracle_key: Option<&'a [u8]>,
+}
+
+/// Reward a node earned in a period, after slashing but before redistribution
+fn slashed_breakdown(
+    node_receipts: NodeReceipts<'_>,
+    challenges: &[ChallengeResult],
+    schedules: &[(u64, ScheduleSource, &RateSchedule)],
+    policy: &SlashingPolicy,
+    (period_start, period_end): (u64, u64),
+) -> Result<RewardBreakdown> {
+    let mut breakdown = RewardBreakdown::default();
+    let mut service_secs: u64 = 0;
+    let mut uptime_secs: u64 = 0;
+
+    for aggregate in node_receipts.aggregates {
+        let (start, end) = aggregate.service_period;
+        let overlap = period_end.min(end).saturating_sub(period_start.max(start));
+        if overlap == 0 {
+            continue;
+        }
+
+        // Aggregates keep no detail within their span, so a partial overlap is prorated
+        let span = end - start;
+        breakdown.accumulate(&prorated_breakdown(&aggregate.breakdown, overlap, span))?;
+        service_secs = service_secs.saturating_add(prorate(aggregate.service_secs, overlap, span));
+        uptime_secs = uptime_secs.saturating_add(prorate(aggregate.uptime_secs, overlap, span));
+    }
+
+    for receipt in node_receipts.receipts {
+        // Calculate overlap duration (in seconds)
+        let overlap_start = period_start.max(receipt.service_period.0);
+        let overlap_end = period_end.min(receipt.service_period.1);
+        let duration = overlap_end.saturating_sub(overlap_start);
+
+        // Skip if no overlap
+        if duration == 0 {
+            continue;
+        }
+
+        let uptime = receipt.storage_metrics.uptime_percentage as u64;
+        let window = (overlap_start, overlap_end);
+        let oracle_key = node_receipts.region_oracle_key;
+        let receipt_breakdown = segmented_breakdown(receipt, window, schedules, oracle_key)?;
+        breakdown.accumulate(&receipt_breakdown)?;
+        service_secs = service_secs.saturating_add(duration);
+        uptime_secs = uptime_secs.saturating_add(duration.saturating_mul(uptime));
+    }
+
+    if service_secs == 0 {
+        return Ok(breakdown);
+    }
+
+    let challenges = ChallengeSummary::for_period(challenges, (period_start, period_end));
+
+    // Uptime over the period, weighted by the time each receipt covers
+    let uptime = uptime_secs / service_secs;
+    let max_slashed = policy.max_slash.apply_to(breakdown.total);
+    breakdown.slashed = if uptime < policy.min_uptime_percentage as u64 {
+        max_slashed
+    } else {
+        match policy.challenge_scoring {
+            ChallengeScoring::PerFailure => policy
+                .challenge_failure_penalty
+                .apply_to(breakdown.total)
+                .saturating_mul(challenges.failed)
+                .min(max_slashed),
+            ChallengeScoring::PassRate { floor } => {
+                let pass_rate = challenges.pass_rate();
+                if pass_rate.0 < floor.0 {
+                    max_slashed
+                } else {
+                    // Full reward at a 100% pass rate, scaled down below it
+                    let earned = pass_rate.apply_to(breakdown.total);
+                    breakdown.total.saturating_sub(earned).min(max_slashed)
+                }
+            }
+        }
+    };
+    breakdown.total = breakdown.total.saturating_sub(breakdown.slashed);
+
+    Ok(breakdown)
+}
+
+/// Earliest time a vault with this fulfillment mechanism can be unlocked
+///
+/// None if the mechanism puts no bound on the time.
+fn earliest_unlock_time(mechanism: &FulfillmentMechanism) -> Option<u64> {
+    match mechanism {
+        FulfillmentMechanism::TimeRelease { unlock_time, .. } => Some(*unlock_time),
+        // Every condition must hold, so the latest bound applies
+        FulfillmentMechanism::And(conditions) => {
+            conditions.iter().filter_map(earliest_unlock_time).max()
+        }
+        // Any condition may hold, so an unbounded one lifts the bound
+        FulfillmentMechanism::Or(conditions) => conditions
+            .iter()
+            .map(earliest_unlock_time)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|bounds| bounds.into_iter().min()),
+        _ => None,
+    }
+}
+
+/// Contents of a reward vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultContent {
+    /// Total token amount in this vault
+    token_amount: u64,
+
+    /// Token ID (currency type)
+    token_id: String,
+
+    /// Recipient mapping (node_id -> ratio)
+    recipients: HashMap<String, Ratio>,
+
+    /// Additional metadata
+    metadata: HashMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::sign_with_node_key;
+    use crate::staking::geolocation::{region_proof_signing_hash, RegionProof, RegionProofType};
+    use crate::staking::heartbeat::heartbeat_signing_hash;
+    use crate::staking::payout::MockDistributionExecutor;
+    use crate::staking::price_feed::{CachedPriceFeed, HttpPriceFeed};
+    use crate::staking::receipt_exchange::{finalize_receipt, initiate_receipt};
+    use crate::staking::reward_store::SqliteRewardStore;
+    use dsm::crypto::{kyber, sphincs};
+    use dsm::types::state_types::DeviceInfo;
+    use mockito::{Matcher, Server};
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::OnceLock;
+
+    /// Configuration with a processor that checks every 50 ms
+    fn fast_config() -> RewardManagerConfig {
+        RewardManagerConfig {
+            check_interval: Duration::from_millis(50),
+            ..RewardManagerConfig::default()
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn reference_state() -> State {
+        let mut state = State::new_genesis(vec![1, 2, 3], DeviceInfo::new("node", vec![0; 32]));
+        state.hash = state.hash().unwrap();
+        state
+    }
+
+    /// Creator key pair for reward vaults
+    ///
+    /// Vault content is encapsulated to the creator's Kyber key and signed
+    /// with its SPHINCS+ key.
+    fn creator_keys() -> (Vec<u8>, Vec<u8>) {
+        let (public_key, _) = kyber::generate_kyber_keypair().unwrap();
+        let (_, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        (public_key, secret_key)
+    }
+
+    /// SPHINCS+ key pairs of the client and the node, generated once
+    fn receipt_keys() -> &'static [(Vec<u8>, Vec<u8>); 2] {
+        static KEYS: OnceLock<[(Vec<u8>, Vec<u8>); 2]> = OnceLock::new();
+        KEYS.get_or_init(|| {
+            [
+                sphincs::generate_sphincs_keypair().unwrap(),
+                sphincs::generate_sphincs_keypair().unwrap(),
+            ]
+        })
+    }
+
+    fn receipt_metrics(bytes_stored: u64, uptime_percentage: u8) -> StorageMetrics {
+        StorageMetrics {
+            bytes_stored,
+            retrievals: 3,
+            operations_count: 5,
+            uptime_percentage,
+            regions: HashSet::new(),
+            challenge_results: ChallengeSummary::default(),
+        }
+    }
+
+    /// Receipt exchanged between the node and the client in two phases
+    fn sealed_receipt(
+        node_id: &str,
+        service_period: (u64, u64),
+        storage_metrics: StorageMetrics,
+    ) -> StorageReceipt {
+        let [(client_pk, client_sk), (node_pk, node_sk)] = receipt_keys();
+        let challenge = initiate_receipt(
+            node_id,
+            "client",
+            service_period,
+            storage_metrics,
+            client_pk,
+            (node_pk, node_sk),
+            60,
+        )
+        .unwrap();
+        finalize_receipt(&challenge, (client_pk, client_sk)).unwrap()
+    }
+
+    /// Register the keys of the test client and nodes, as their identity
+    /// registrations would
+    fn register_receipt_keys(manager: &RewardVaultManager) {
+        let [(client_pk, _), (node_pk, _)] = receipt_keys();
+        manager
+            .register_identity_key("client", client_pk.clone())
+            .unwrap();
+        for node_id in ["node-1", "node-2", "node-3"] {
+            manager
+                .register_identity_key(node_id, node_pk.clone())
+                .unwrap();
+        }
+    }
+
+    /// Process a receipt from a registered client and node
+    fn process(manager: &RewardVaultManager, receipt: StorageReceipt) -> Result<()> {
+        register_receipt_keys(manager);
+        manager.process_receipt(receipt)
+    }
+
+    fn receipt(node_id: &str, service_period: (u64, u64), bytes_stored: u64) -> StorageReceipt {
+        receipt_with_uptime(node_id, service_period, bytes_stored, 100)
+    }
+
+    fn receipt_with_uptime(
+        node_id: &str,
+        service_period: (u64, u64),
+        bytes_stored: u64,
+        uptime_percentage: u8,
+    ) -> StorageReceipt {
+        sealed_receipt(
+            node_id,
+            service_period,
+            receipt_metrics(bytes_stored, uptime_percentage),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_due_vault_distributed_on_next_tick() {
+        let manager = Arc::new(RewardVaultManager::new(
+            Arc::new(DLVManager::new()),
+            fast_config(),
+        ));
+        let mut results = manager.subscribe_distributions();
+        manager.initialize().unwrap();
+
+        let (public_key, secret_key) = creator_keys();
+        let recipients = HashMap::from([("node-1".to_string(), Ratio::ONE)]);
+
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                recipients,
+                &reference_state(),
+            )
+            .unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), results.recv())
+            .await
+            .expect("vault was never distributed")
+            .unwrap();
+        assert_eq!(result.vault_id, vault_id);
+    }
+
+    #[tokio::test]
+    async fn test_vesting_tranches_distributed_independently() {
+        let manager = Arc::new(RewardVaultManager::new(
+            Arc::new(DLVManager::new()),
+            fast_config(),
+        ));
+        let mut records = manager.subscribe_distributions();
+
+        let (public_key, secret_key) = creator_keys();
+        let recipients = HashMap::from([("node-1".to_string(), Ratio::ONE)]);
+        let start = now();
+        let schedule = vec![
+            (start - 120, Ratio::from_parts(1, 4).unwrap()),
+            (start - 60, Ratio::from_parts(1, 4).unwrap()),
+            (start + 3600, Ratio::from_parts(1, 4).unwrap()),
+            (start + 7200, Ratio::from_parts(1, 4).unwrap()),
+        ];
+
+        let vault_ids = manager
+            .create_vesting_vault(
+                (&public_key, &secret_key),
+                1_001,
+                "ROOT",
+                schedule.clone(),
+                recipients,
+                &reference_state(),
+            )
+            .unwrap();
+        assert_eq!(vault_ids.len(), 4);
+
+        let tranches: Vec<_> = vault_ids
+            .iter()
+            .map(|vault_id| manager.get_vault(vault_id).unwrap())
+            .collect();
+        for (tranche, (unlock_time, _)) in tranches.iter().zip(&schedule) {
+            assert_eq!(tranche.distribution_time, *unlock_time);
+        }
+        let amounts: Vec<_> = tranches
+            .iter()
+            .map(|tranche| tranche.token_amount)
+            .collect();
+        assert_eq!(amounts, vec![250, 250, 250, 251]);
+        assert_eq!(manager.inner.distribution_queue.lock().unwrap().len(), 4);
+
+        // A failing first tranche does not hold up the second
+        manager
+            .inner
+            .vault_registry
+            .write()
+            .unwrap()
+            .remove(&vault_ids[0]);
+        manager.initialize().unwrap();
+
+        let mut processed = HashMap::new();
+        for _ in 0..2 {
+            let record = next_record(&mut records).await;
+            processed.insert(record.vault_id.clone(), record);
+        }
+        assert!(!processed[&vault_ids[0]].success);
+        assert_eq!(processed[&vault_ids[1]].scheduled_at, schedule[1].0);
+
+        // Later tranches wait for their own unlock time
+        let queue = manager.inner.distribution_queue.lock().unwrap();
+        assert!(queue.iter().any(|request| request.vault_id == vault_ids[2]));
+        assert!(queue.iter().any(|request| request.vault_id == vault_ids[3]));
+    }
+
+    #[test]
+    fn test_vesting_schedule_must_sum_to_one() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let (public_key, secret_key) = creator_keys();
+        let recipients = HashMap::from([("node-1".to_string(), Ratio::ONE)]);
+
+        let short = vec![
+            (now(), Ratio::from_parts(1, 2).unwrap()),
+            (now() + 60, Ratio::from_parts(2, 5).unwrap()),
+        ];
+        for schedule in [vec![], short] {
+            let result = manager.create_vesting_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                schedule,
+                recipients.clone(),
+                &reference_state(),
+            );
+            assert!(matches!(result, Err(StorageNodeError::Staking(_))));
+        }
+        assert!(manager.get_vaults().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_distribution_history_survives_restart() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = Arc::new(
+            RewardVaultManager::with_store(
+                Arc::new(DLVManager::new()),
+                store.clone(),
+                fast_config(),
+            )
+            .unwrap(),
+        );
+        let mut records = manager.subscribe_distributions();
+        manager.initialize().unwrap();
+
+        let (public_key, secret_key) = creator_keys();
+        let recipients = HashMap::from([("node-1".to_string(), Ratio::ONE)]);
+        let distribution_time = now() - 60;
+
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                distribution_time,
+                recipients,
+                &reference_state(),
+            )
+            .unwrap();
+
+        let record = tokio::time::timeout(Duration::from_secs(5), records.recv())
+            .await
+            .expect("vault was never distributed")
+            .unwrap();
+        assert_eq!(record.vault_id, vault_id);
+        assert_eq!(record.scheduled_at, distribution_time);
+        assert!(record.processed_at >= distribution_time);
+        assert_eq!(record.success, record.error.is_none());
+        if record.success {
+            assert_eq!(record.amounts.get("node-1"), Some(&1_000));
+        } else {
+            assert!(record.amounts.is_empty());
+        }
+
+        assert_eq!(
+            manager.get_distribution_history(None).unwrap(),
+            vec![record.clone()]
+        );
+        assert_eq!(
+            manager.get_distribution_history(Some(&vault_id)).unwrap(),
+            vec![record.clone()]
+        );
+        assert!(manager
+            .get_distribution_history(Some("unknown-vault"))
+            .unwrap()
+            .is_empty());
+
+        let restarted =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, Default::default())
+                .unwrap();
+        assert_eq!(
+            restarted.get_distribution_history(None).unwrap(),
+            vec![record]
+        );
+    }
+
+    /// Fast configuration that retries immediately, so retries happen on consecutive ticks
+    fn immediate_retries(max_attempts: u32) -> RewardManagerConfig {
+        RewardManagerConfig {
+            retry_policy: DistributionRetryPolicy {
+                max_attempts,
+                base_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+            },
+            ..fast_config()
+        }
+    }
+
+    async fn next_record(
+        records: &mut broadcast::Receiver<DistributionRecord>,
+    ) -> DistributionRecord {
+        tokio::time::timeout(Duration::from_secs(5), records.recv())
+            .await
+            .expect("distribution was never processed")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_retry_delay_is_bounded() {
+        let policy = DistributionRetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(300),
+        };
+
+        assert_eq!(policy.delay_after(1), Duration::from_secs(30));
+        assert_eq!(policy.delay_after(2), Duration::from_secs(60));
+        assert_eq!(policy.delay_after(4), Duration::from_secs(240));
+        assert_eq!(policy.delay_after(5), Duration::from_secs(300));
+        assert_eq!(policy.delay_after(64), Duration::from_secs(300));
+    }
+
+    #[tokio::test]
+    async fn test_retryable_failure_given_up_after_max_attempts() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = Arc::new(
+            RewardVaultManager::with_store(
+                Arc::new(DLVManager::new()),
+                store.clone(),
+                immediate_retries(3),
+            )
+            .unwrap(),
+        );
+        let mut records = manager.subscribe_distributions();
+
+        let (public_key, secret_key) = creator_keys();
+        let recipients = HashMap::from([("node-1".to_string(), Ratio::ONE)]);
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() + 3600,
+                recipients,
+                &reference_state(),
+            )
+            .unwrap();
+
+        // Process the request before the vault can be unlocked
+        manager.inner.distribution_queue.lock().unwrap()[0].timestamp = now() - 60;
+        manager.initialize().unwrap();
+
+        for _ in 0..3 {
+            let record = next_record(&mut records).await;
+            assert_eq!(record.vault_id, vault_id);
+            assert!(!record.success);
+            assert!(record.error.unwrap().starts_with("Not yet time"));
+        }
+
+        let failed = manager.get_failed_distributions().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].request.vault_id, vault_id);
+        assert_eq!(failed[0].request.attempts, 3);
+        assert!(manager.inner.distribution_queue.lock().unwrap().is_empty());
+
+        let restarted =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, Default::default())
+                .unwrap();
+        assert_eq!(restarted.get_failed_distributions().unwrap().len(), 1);
+        assert!(restarted
+            .inner
+            .distribution_queue
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_not_retried() {
+        let manager = Arc::new(RewardVaultManager::new(
+            Arc::new(DLVManager::new()),
+            immediate_retries(5),
+        ));
+        let mut records = manager.subscribe_distributions();
+
+        manager
+            .inner
+            .distribution_queue
+            .lock()
+            .unwrap()
+            .push(pending_request("missing-vault", now() - 60));
+        manager.initialize().unwrap();
+
+        let record = next_record(&mut records).await;
+        assert_eq!(record.vault_id, "missing-vault");
+        assert!(!record.success);
+
+        // Further ticks find nothing left to process
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(manager.get_distribution_history(None).unwrap().len(), 1);
+
+        let failed = manager.get_failed_distributions().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].request.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pending_distributions_show_retries_and_reschedule() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let (public_key, secret_key) = creator_keys();
+        let unlock_time = now() + 3600;
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                unlock_time,
+                HashMap::from([("node-1".to_string(), Ratio::ONE)]),
+                &reference_state(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.get_pending_distributions().unwrap(),
+            vec![PendingDistribution {
+                vault_id: vault_id.clone(),
+                scheduled_at: unlock_time,
+                attempts: 0,
+                last_error: None,
+                next_retry_at: None,
+            }]
+        );
+
+        // An attempt before the unlock time fails and is retried after a delay
+        manager.inner.distribution_queue.lock().unwrap()[0].timestamp = now() - 60;
+        process_queued(&manager, &vault_id).await;
+        let pending = manager.get_pending_distributions().unwrap().remove(0);
+        assert_eq!(pending.attempts, 1);
+        assert!(pending.last_error.unwrap().starts_with("Not yet time"));
+        assert!(pending.next_retry_at.unwrap() >= now() + 59);
+
+        // The DLV cannot be unlocked earlier, so neither can the distribution
+        let error = manager
+            .reschedule_distribution(&vault_id, unlock_time - 1)
+            .unwrap_err();
+        assert!(matches!(error, StorageNodeError::InvalidInput(_)));
+
+        manager
+            .reschedule_distribution(&vault_id, unlock_time + 600)
+            .unwrap();
+        let pending = manager.get_pending_distributions().unwrap().remove(0);
+        assert_eq!(pending.scheduled_at, unlock_time + 600);
+        assert_eq!(pending.attempts, 1);
+        assert_eq!(pending.next_retry_at, None);
+        assert_eq!(
+            manager.get_vault(&vault_id).unwrap().distribution_time,
+            unlock_time + 600
+        );
+    }
+
+    fn pending_request(vault_id: &str, timestamp: u64) -> DistributionRequest {
+        DistributionRequest {
+            vault_id: vault_id.to_string(),
+            reference_state: reference_state(),
+            timestamp,
+            attempts: 0,
+            retry_at: 0,
+            last_error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_processor() {
+        let manager = Arc::new(RewardVaultManager::new(
+            Arc::new(DLVManager::new()),
+            fast_config(),
+        ));
+        let mut records = manager.subscribe_distributions();
+        manager.initialize().unwrap();
+        manager.shutdown().await.unwrap();
+        assert!(manager.inner.processor.lock().unwrap().is_none());
+
+        // Nothing picks up a due distribution once the processor is stopped
+        manager
+            .inner
+            .distribution_queue
+            .lock()
+            .unwrap()
+            .push(pending_request("stopped-vault", now() - 60));
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(records.try_recv().is_err());
+        assert_eq!(manager.inner.distribution_queue.lock().unwrap().len(), 1);
+
+        // A restarted processor resumes distribution
+        manager.initialize().unwrap();
+        assert_eq!(next_record(&mut records).await.vault_id, "stopped-vault");
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manual_trigger_wakes_processor() {
+        // Long enough that only the trigger can wake the processor
+        let config = RewardManagerConfig {
+            check_interval: Duration::from_secs(3600),
+            ..RewardManagerConfig::default()
+        };
+        let manager = Arc::new(RewardVaultManager::new(Arc::new(DLVManager::new()), config));
+        let mut records = manager.subscribe_distributions();
+        manager.initialize().unwrap();
+
+        // Let the processor use up the immediate first tick
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        manager
+            .inner
+            .distribution_queue
+            .lock()
+            .unwrap()
+            .push(pending_request("triggered-vault", now() - 60));
+
+        manager.trigger_distribution_check_now();
+        assert_eq!(next_record(&mut records).await.vault_id, "triggered-vault");
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_pending_distributions() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = Arc::new(
+            RewardVaultManager::with_store(
+                Arc::new(DLVManager::new()),
+                store.clone(),
+                fast_config(),
+            )
+            .unwrap(),
+        );
+        manager.initialize().unwrap();
+
+        // Queued without being persisted, and not yet due
+        manager
+            .inner
+            .distribution_queue
+            .lock()
+            .unwrap()
+            .push(pending_request("pending-vault", now() + 3600));
+        manager.shutdown().await.unwrap();
+
+        let restarted =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, Default::default())
+                .unwrap();
+        let queue = restarted.inner.distribution_queue.lock().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].vault_id, "pending-vault");
+    }
+
+    #[tokio::test]
+    async fn test_registries_survive_restart() {
+        let path = std::env::temp_dir().join(format!(
+            "dsm-reward-store-{}-{}.db",
+            std::process::id(),
+            now()
+        ));
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            Arc::new(SqliteRewardStore::new(&path).unwrap()),
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+        process(&manager, receipt("node-1", (86400, 172800), 20)).unwrap();
+        process(&manager, receipt("node-2", (0, 43200), 5)).unwrap();
+        manager
+            .record_challenge_result("node-2", false, 1000)
+            .unwrap();
+
+        let (public_key, secret_key) = creator_keys();
+        let recipients = HashMap::from([
+            ("node-1".to_string(), Ratio::try_new(0.5).unwrap()),
+            ("node-2".to_string(), Ratio::try_new(0.5).unwrap()),
+        ]);
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() + 3600,
+                recipients,
+                &reference_state(),
+            )
+            .unwrap();
+
+        let mut rewards_before = Vec::new();
+        for node in ["node-1", "node-2", "node-3"] {
+            rewards_before.push(
+                manager
+                    .calculate_node_rewards(node, 0, 172800)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let vault_before = manager.get_vault(&vault_id).unwrap();
+        drop(manager);
+
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            Arc::new(SqliteRewardStore::new(&path).unwrap()),
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+
+        let mut rewards_after = Vec::new();
+        for node in ["node-1", "node-2", "node-3"] {
+            rewards_after.push(
+                manager
+                    .calculate_node_rewards(node, 0, 172800)
+                    .await
+                    .unwrap(),
+            );
+        }
+        assert_eq!(rewards_after, rewards_before);
+        assert!(rewards_after[0] > 0);
+
+        let vaults = manager.get_vaults().unwrap();
+        assert_eq!(vaults.len(), 1);
+        assert_eq!(vaults[0].vault_id, vault_before.vault_id);
+        assert_eq!(vaults[0].distribution_time, vault_before.distribution_time);
+        assert_eq!(vaults[0].status, vault_before.status);
+
+        // The pending distribution is re-enqueued
+        let queue = manager.inner.distribution_queue.lock().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].vault_id, vault_id);
+        drop(queue);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restored_distribution_finds_its_vault() {
+        let config = RewardManagerConfig {
+            dispute_window: Duration::ZERO,
+            ..RewardManagerConfig::default()
+        };
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store.clone(), config)
+                .unwrap();
+
+        let (public_key, secret_key) = creator_keys();
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([("node-1".to_string(), Ratio::ONE)]),
+                &reference_state(),
+            )
+            .unwrap();
+        drop(manager);
+
+        // A restarted node starts with an empty DLV manager
+        let manager =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store.clone(), config)
+                .unwrap();
+        let vault = manager.inner.dlv_manager.get_vault(&vault_id).unwrap();
+        assert!(matches!(vault.lock().unwrap().state, VaultState::Limbo));
+
+        process_queued(&manager, &vault_id).await;
+        assert!(manager.get_failed_distributions().unwrap().is_empty());
+
+        // The claimed vault is saved in its new state
+        let history = manager.get_distribution_history(Some(&vault_id)).unwrap();
+        if history[0].success {
+            let manager =
+                RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, config).unwrap();
+            let vault = manager.inner.dlv_manager.get_vault(&vault_id).unwrap();
+            assert!(matches!(
+                vault.lock().unwrap().state,
+                VaultState::Claimed { .. }
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_price_increase_scales_rewards() {
+        let mut server = Server::new_async().await;
+        let price = server
+            .mock("GET", "/simple/price")
+            .match_query(Matcher::Any)
+            .with_body(r#"{"root": {"usd": 1.5}}"#)
+            .create_async()
+            .await;
+
+        let feed = HttpPriceFeed::new(&server.url(), Duration::from_secs(5)).unwrap();
+        let feed = CachedPriceFeed::new(Arc::new(feed), Duration::ZERO);
+        let manager = RewardVaultManager::new(Arc::new(DLVManager::new()), Default::default())
+            .with_price_feed(Some(Arc::new(feed)));
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+
+        let before = manager
+            .calculate_node_rewards("node-1", 0, 86400)
+            .await
+            .unwrap();
+        assert!(before > 0);
+
+        price.remove_async().await;
+        let doubled = server
+            .mock("GET", "/simple/price")
+            .match_query(Matcher::Any)
+            .with_body(r#"{"root": {"usd": 3.0}}"#)
+            .create_async()
+            .await;
+
+        let after = manager
+            .calculate_node_rewards("node-1", 0, 86400)
+            .await
+            .unwrap();
+        doubled.assert_async().await;
+        assert_eq!(after, before * 2);
+
+        // An unreachable feed falls back to the last price it returned
+        doubled.remove_async().await;
+        let unchanged = manager
+            .calculate_node_rewards("node-1", 0, 86400)
+            .await
+            .unwrap();
+        assert_eq!(unchanged, after);
+    }
+
+    fn assert_rejected(manager: &RewardVaultManager, receipt: StorageReceipt) {
+        assert!(matches!(
+            process(manager, receipt),
+            Err(StorageNodeError::Staking(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_signed_receipt_accepted() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+        assert!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap()
+                > 0
+        );
+    }
+
+    #[test]
+    fn test_tampered_metrics_rejected() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let mut tampered = receipt("node-1", (0, 86400), 10);
+        tampered.storage_metrics.bytes_stored = 10_000;
+        assert_rejected(&manager, tampered.clone());
+
+        // Recomputing the hash does not help without new signatures
+        tampered.receipt_hash = tampered.compute_hash().unwrap();
+        assert_rejected(&manager, tampered);
+    }
+
+    #[test]
+    fn test_wrong_key_rejected() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let (forger_pk, forger_sk) = sphincs::generate_sphincs_keypair().unwrap();
+
+        // `finalize_receipt` refuses a key the challenge was not issued to
+        let mut forged = receipt("node-1", (0, 86400), 10);
+        let challenge = forged.challenge().unwrap();
+        assert!(finalize_receipt(&challenge, (&forger_pk, &forger_sk)).is_err());
+
+        // A signature from another key does not verify against the recorded one
+        forged.client_signature =
+            sign_with_node_key(&forger_sk, &challenge.challenge_hash).unwrap();
+        forged.receipt_hash = forged.compute_hash().unwrap();
+        assert_rejected(&manager, forged.clone());
+
+        // Neither does swapping in the forger's key after the node signed
+        forged.client_public_key = forger_pk;
+        let challenge_hash = forged.challenge().unwrap().challenge_hash;
+        forged.client_signature = sign_with_node_key(&forger_sk, &challenge_hash).unwrap();
+        forged.receipt_hash = forged.compute_hash().unwrap();
+        assert_rejected(&manager, forged);
+    }
+
+    #[test]
+    fn test_receipt_keys_must_be_registered_for_its_ids() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        register_receipt_keys(&manager);
+
+        // A node that made up a client signs for it with a key of its own
+        let [_, (node_pk, node_sk)] = receipt_keys();
+        let (made_up_pk, made_up_sk) = sphincs::generate_sphincs_keypair().unwrap();
+        let sealed = |client_id: &str| {
+            let challenge = initiate_receipt(
+                "node-1",
+                client_id,
+                (0, 86400),
+                receipt_metrics(10, 100),
+                &made_up_pk,
+                (node_pk, node_sk),
+                60,
+            )
+            .unwrap();
+            finalize_receipt(&challenge, (&made_up_pk, &made_up_sk)).unwrap()
+        };
+        assert_rejected(&manager, sealed("made-up-client"));
+        assert_rejected(&manager, sealed("client"));
+
+        // A registered key cannot be replaced
+        assert!(matches!(
+            manager.register_identity_key("client", made_up_pk.clone()),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert_eq!(
+            manager.identity_key("client").unwrap().as_ref(),
+            Some(&receipt_keys()[0].0)
+        );
+    }
+
+    #[test]
+    fn test_missing_signature_rejected() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+
+        let mut unsigned_by_node = receipt("node-1", (0, 86400), 10);
+        unsigned_by_node.node_signature.clear();
+        assert_rejected(&manager, unsigned_by_node);
+
+        // A challenge the client never sealed is not a receipt
+        let mut unsealed = receipt("node-1", (0, 86400), 10);
+        unsealed.client_signature.clear();
+        unsealed.receipt_hash = unsealed.compute_hash().unwrap();
+        assert_rejected(&manager, unsealed);
+    }
+
+    #[test]
+    fn test_receipt_outside_two_phase_exchange_rejected() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+
+        let mut one_sided = receipt("node-1", (0, 86400), 10);
+        one_sided.version = 0;
+        assert_rejected(&manager, one_sided);
+    }
+
+    #[test]
+    fn test_claimed_uptime_checked_against_heartbeats() {
+        // The client probes the node once, in the first of two half-day slots
+        let [(client_pk, client_sk), _] = receipt_keys();
+        let monitor = Arc::new(HeartbeatMonitor::new(43200));
+        monitor
+            .register_prober("client", client_pk.clone())
+            .unwrap();
+        let signature =
+            sign_with_node_key(client_sk, &heartbeat_signing_hash("node-1", 100)).unwrap();
+        monitor.record_heartbeat("node-1", 100, &signature).unwrap();
+
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default())
+                .with_heartbeat_monitor(Some(monitor.clone()));
+        assert_rejected(&manager, receipt_with_uptime("node-1", (0, 86400), 10, 100));
+        process(&manager, receipt_with_uptime("node-1", (0, 86400), 10, 55)).unwrap();
+        assert_eq!(monitor.uptime_percentage("node-1", (0, 86400)).unwrap(), 50);
+    }
+
+    fn unit_region_multiplier() -> Option<AppliedRegionMultiplier> {
+        Some(AppliedRegionMultiplier {
+            strategy: RegionMultiplierStrategy::Max,
+            multiplier: Ratio::ONE,
+        })
+    }
+
+    /// A region claimed with a proof no oracle signed
+    fn claimed_region(region_code: &str) -> VerifiedRegion {
+        let proof = RegionProof {
+            region_code: region_code.to_string(),
+            proof_type: RegionProofType::IpGeolocation {
+                ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                lookup_signature: Vec::new(),
+            },
+        };
+        (region_code.to_string(), proof)
+    }
+
+    fn metrics(
+        bytes_stored: u64,
+        retrievals: u64,
+        operations_count: u64,
+        uptime: u8,
+    ) -> StorageMetrics {
+        StorageMetrics {
+            bytes_stored,
+            retrievals,
+            operations_count,
+            uptime_percentage: uptime,
+            regions: HashSet::new(),
+            challenge_results: ChallengeSummary::default(),
+        }
+    }
+
+    #[test]
+    fn test_rate_schedule_breakdown() {
+        let schedule = RateSchedule {
+            base_rate_per_byte_day: 2,
+            retrieval_rate: 10,
+            operation_rate: 5,
+            uptime_multiplier: Ratio::ONE,
+            region_multipliers: HashMap::from([(
+                "eu".to_string(),
+                Ratio::multiplier(1.5).unwrap(),
+            )]),
+            region_strategy: RegionMultiplierStrategy::Max,
+        };
+
+        // One day at full uptime: every component counts in full
+        let full = schedule.breakdown(86400, &metrics(100, 3, 4, 100)).unwrap();
+        assert_eq!(
+            full,
+            RewardBreakdown {
+                storage_reward: 200,
+                retrieval_reward: 30,
+                operation_reward: 20,
+                slashed: 0,
+                redistributed: 0,
+                total: 250,
+                region_multiplier: unit_region_multiplier(),
+                segments: Vec::new(),
+            }
+        );
+        assert_eq!(full.base_reward(), 250);
+
+        // Half a day at half uptime
+        let partial = schedule.breakdown(43200, &metrics(100, 0, 10, 50)).unwrap();
+        assert_eq!(
+            partial,
+            RewardBreakdown {
+                storage_reward: 100,
+                retrieval_reward: 0,
+                operation_reward: 50,
+                slashed: 0,
+                redistributed: 0,
+                total: 75,
+                region_multiplier: unit_region_multiplier(),
+                segments: Vec::new(),
+            }
+        );
 
-                        // Update vault status
-                        self.update_vault_status(&request.vault_id, "claimed")?;
+        // Operations alone earn a reward; unknown regions do not change it
+        let mut operations_only = metrics(0, 0, 7, 100);
+        operations_only.regions = HashSet::from([claimed_region("eu"), claimed_region("us")]);
+        let operations = schedule.breakdown(86400, &operations_only).unwrap();
+        assert_eq!(operations.operation_reward, 35);
+        assert_eq!(operations.total, 52);
+        assert_eq!(schedule.calculate(86400, &operations_only).unwrap(), 52);
+    }
 
-                        // Return success result
-                        Ok(DistributionResult {
-                            vault_id: request.vault_id,
-                            success: true,
-                            timestamp: now,
-                            error: None,
-                            distribution_details: Some(distributions),
-                        })
+    #[test]
+    fn test_region_multipliers_do_not_compound() {
+        let mut schedule = schedule(0, 0, 10);
+        schedule.region_multipliers = ["af", "ap", "sa", "us"]
+            .into_iter()
+            .map(|region| (region.to_string(), Ratio::multiplier(1.5).unwrap()))
+            .collect();
+        schedule
+            .region_multipliers
+            .insert("eu".to_string(), Ratio::ONE);
+
+        // 100 operations at 10 each, claiming all five regions
+        let mut stuffed = metrics(0, 0, 100, 100);
+        stuffed.regions = schedule
+            .region_multipliers
+            .keys()
+            .map(|r| claimed_region(r))
+            .collect();
+
+        let cases = [
+            (RegionMultiplierStrategy::Max, 1.5, 1500),
+            (RegionMultiplierStrategy::Mean, 1.4, 1400),
+            (
+                RegionMultiplierStrategy::Product {
+                    cap: Ratio::multiplier(10.0).unwrap(),
+                },
+                5.0625,
+                5062,
+            ),
+            (
+                RegionMultiplierStrategy::Product {
+                    cap: Ratio::multiplier(3.0).unwrap(),
+                },
+                3.0,
+                3000,
+            ),
+        ];
+        for (strategy, multiplier, total) in cases {
+            schedule.region_strategy = strategy;
+            let breakdown = schedule.breakdown(86400, &stuffed).unwrap();
+            assert_eq!(breakdown.total, total, "{:?}", strategy);
+            assert_eq!(
+                breakdown.region_multiplier,
+                Some(AppliedRegionMultiplier {
+                    strategy,
+                    multiplier: Ratio::multiplier(multiplier).unwrap(),
+                })
+            );
+        }
+
+        // A product cap may exceed 1.0 when deserialized
+        let strategy: RegionMultiplierStrategy =
+            serde_json::from_str(r#"{"product": {"cap": 3000000}}"#).unwrap();
+        assert_eq!(
+            strategy,
+            RegionMultiplierStrategy::Product {
+                cap: Ratio::multiplier(3.0).unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn test_region_multiplier_ceiling_enforced() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+
+        let mut excessive = schedule(100, 10, 5);
+        excessive
+            .region_multipliers
+            .insert("eu".to_string(), Ratio::multiplier(2.5).unwrap());
+        assert!(matches!(
+            manager.update_rate_schedule(excessive.clone()),
+            Err(StorageNodeError::InvalidInput(_))
+        ));
+        assert!(manager.set_node_rate_override("node-1", excessive).is_err());
+        assert!(manager.node_rate_override("node-1").unwrap().is_none());
+
+        let mut generous_cap = schedule(100, 10, 5);
+        generous_cap.region_strategy = RegionMultiplierStrategy::Product {
+            cap: Ratio::multiplier(4.0).unwrap(),
+        };
+        assert!(manager.update_rate_schedule(generous_cap).is_err());
+
+        let mut allowed = schedule(100, 10, 5);
+        allowed
+            .region_multipliers
+            .insert("eu".to_string(), Ratio::multiplier(2.0).unwrap());
+        manager.update_rate_schedule(allowed).unwrap();
+        assert_eq!(
+            manager.rate_schedule().unwrap().region_multipliers["eu"],
+            Ratio::multiplier(2.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reward_overflow_is_an_error() {
+        let schedule = RateSchedule {
+            base_rate_per_byte_day: u64::MAX,
+            retrieval_rate: u64::MAX,
+            operation_rate: 1,
+            uptime_multiplier: Ratio::ONE,
+            region_multipliers: HashMap::from([(
+                "eu".to_string(),
+                Ratio::multiplier(2.0).unwrap(),
+            )]),
+            region_strategy: RegionMultiplierStrategy::Max,
+        };
+
+        for overflowing in [metrics(u64::MAX, 0, 0, 100), metrics(0, 2, 0, 100)] {
+            assert!(matches!(
+                schedule.breakdown(86400, &overflowing),
+                Err(StorageNodeError::Staking(_))
+            ));
+        }
+
+        // A multiplier above 1.0 can push a valid reward out of range
+        let mut doubled = metrics(0, 1, 0, 100);
+        doubled.regions = HashSet::from([claimed_region("eu")]);
+        assert!(matches!(
+            schedule.breakdown(1, &doubled),
+            Err(StorageNodeError::Staking(_))
+        ));
+
+        assert!(Ratio::multiplier(-1.0).is_err());
+        assert!(Ratio::multiplier(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_ratio_constructors_validate() {
+        assert_eq!(
+            Ratio::try_new(0.25).unwrap(),
+            Ratio::from_parts(1, 4).unwrap()
+        );
+        assert_eq!(Ratio::try_new(1.0).unwrap(), Ratio::ONE);
+        assert!(Ratio::try_new(1.5).is_err());
+        assert!(Ratio::try_new(-0.1).is_err());
+        assert!(Ratio::try_new(f64::NAN).is_err());
+
+        assert_eq!(Ratio::from_parts(1, 3).unwrap().raw_value(), 333_333);
+        assert!(Ratio::from_parts(2, 1).is_err());
+        assert!(Ratio::from_parts(0, 0).is_err());
+    }
+
+    #[test]
+    fn test_ratio_deserialization_rejects_above_one() {
+        assert_eq!(
+            serde_json::from_str::<Ratio>("500000").unwrap().raw_value(),
+            500_000
+        );
+        assert_eq!(
+            serde_json::from_str::<Ratio>("1000000").unwrap(),
+            Ratio::ONE
+        );
+        assert!(serde_json::from_str::<Ratio>("5000000").is_err());
+
+        // Multipliers may still exceed 1.0
+        let schedule: RateSchedule = serde_json::from_str(
+            r#"{
+                "base_rate_per_byte_day": 1,
+                "retrieval_rate": 1,
+                "operation_rate": 1,
+                "uptime_multiplier": 1500000,
+                "region_multipliers": {"eu": 2000000}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(schedule.uptime_multiplier, Ratio::multiplier(1.5).unwrap());
+        assert_eq!(
+            schedule.region_multipliers["eu"],
+            Ratio::multiplier(2.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ratio_sum_tolerance() {
+        let ratios = |raw: &[u64]| raw.iter().map(|raw| Ratio(*raw)).collect::<Vec<_>>();
+
+        assert!(check_ratio_sum(ratios(&[500_000, 500_000]).iter(), "test").is_ok());
+        assert!(check_ratio_sum(ratios(&[500_000, 510_000]).iter(), "test").is_ok());
+        assert!(check_ratio_sum(ratios(&[500_000, 490_000]).iter(), "test").is_ok());
+        assert!(check_ratio_sum(ratios(&[500_000, 510_001]).iter(), "test").is_err());
+        assert!(check_ratio_sum(ratios(&[500_000, 489_999]).iter(), "test").is_err());
+    }
+
+    mod arithmetic {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn prop_breakdown_is_deterministic(
+                rates in (any::<u32>(), any::<u32>(), any::<u32>()),
+                metrics in (any::<u64>(), any::<u32>(), any::<u32>(), 0u8..=100),
+                duration_secs in 0u64..=30 * 86400,
+                region_multipliers in
+                    proptest::collection::vec(("[a-z]{2}", 0u64..3_000_000), 0..4),
+            ) {
+                let schedule = RateSchedule {
+                    base_rate_per_byte_day: rates.0 as u64,
+                    retrieval_rate: rates.1 as u64,
+                    operation_rate: rates.2 as u64,
+                    uptime_multiplier: Ratio::ONE,
+                    region_multipliers: region_multipliers
+                        .iter()
+                        .map(|(region, raw)| (region.clone(), Ratio(*raw)))
+                        .collect(),
+                    region_strategy: RegionMultiplierStrategy::Max,
+                };
+                let regions: Vec<VerifiedRegion> =
+                    region_multipliers.iter().map(|(region, _)| claimed_region(region)).collect();
+
+                // Region sets built in different orders give the same reward
+                let mut forward = StorageMetrics {
+                    bytes_stored: metrics.0,
+                    retrievals: metrics.1 as u64,
+                    operations_count: metrics.2 as u64,
+                    uptime_percentage: metrics.3,
+                    regions: regions.iter().cloned().collect(),
+                    challenge_results: ChallengeSummary::default(),
+                };
+                let first = schedule.breakdown(duration_secs, &forward).ok();
+                forward.regions = regions.iter().rev().cloned().collect();
+                let second = schedule.breakdown(duration_secs, &forward).ok();
+                prop_assert_eq!(first, second);
+
+                // Rewards never exceed the base reward without a multiplier above 1.0
+                if let Some(breakdown) = first {
+                    if region_multipliers.iter().all(|(_, raw)| *raw <= RATIO_SCALE) {
+                        prop_assert!(breakdown.total <= breakdown.base_reward());
                     }
-                    Err(e) => {
-                        // Failed to claim
-                        Ok(DistributionResult {
-                            vault_id: request.vault_id,
-                            success: false,
-                            timestamp: now,
-                            error: Some(format!("Failed to claim vault: {}", e)),
-                            distribution_details: None,
-                        })
+                }
+            }
+
+            #[test]
+            fn prop_split_never_exceeds_total(
+                total in any::<u64>(),
+                ratios in proptest::collection::vec(0u64..=RATIO_SCALE, 1..12),
+            ) {
+                let ratios: HashMap<String, Ratio> = ratios
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, raw)| (format!("node-{}", i), Ratio(raw)))
+                    .collect();
+
+                let split = split_by_ratios(total, &ratios);
+                let paid: u128 = split.values().map(|amount| *amount as u128).sum();
+                prop_assert!(paid <= total as u128);
+                prop_assert_eq!(split.len(), ratios.len());
+
+                // Ratios within 1.0 pay every recipient its full share
+                let ratio_sum: u64 = ratios.values().map(Ratio::raw_value).sum();
+                if ratio_sum <= RATIO_SCALE {
+                    for (node_id, ratio) in &ratios {
+                        prop_assert_eq!(split[node_id], ratio.apply_to(total));
                     }
                 }
             }
-            Ok(false) => {
-                // Failed to unlock (conditions not met)
-                Ok(DistributionResult {
-                    vault_id: request.vault_id,
-                    success: false,
-                    timestamp: now,
-                    error: Some("Failed to unlock vault: conditions not met".to_string()),
-                    distribution_details: None,
-                })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_node_rewards_include_operations() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let first = receipt("node-1", (0, 86400), 10);
+        let second = receipt("node-1", (86400, 172800), 20);
+        process(&manager, first.clone()).unwrap();
+        process(&manager, second.clone()).unwrap();
+
+        // Default schedule: 100 per byte-day, 10 per retrieval, 5 per operation
+        let breakdown = manager.node_reward_breakdown("node-1", 0, 172800).unwrap();
+        let global = ScheduleSource::Global { effective_from: 0 };
+        assert_eq!(
+            breakdown,
+            RewardBreakdown {
+                storage_reward: 3000,
+                retrieval_reward: 60,
+                operation_reward: 50,
+                slashed: 0,
+                redistributed: 0,
+                total: 3110,
+                region_multiplier: unit_region_multiplier(),
+                segments: vec![
+                    segment(&first, (0, 86400), global, 1055),
+                    segment(&second, (86400, 172800), global, 2055),
+                ],
             }
-            Err(e) => {
-                // Error unlocking
-                Ok(DistributionResult {
-                    vault_id: request.vault_id,
-                    success: false,
-                    timestamp: now,
-                    error: Some(format!("Error unlocking vault: {}", e)),
-                    distribution_details: None,
-                })
+        );
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 172800)
+                .await
+                .unwrap(),
+            3110
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_state() {
+        let manager = RewardVaultManager::new(Arc::new(DLVManager::new()), fast_config());
+        let handle = manager.clone();
+        let mut records = manager.subscribe_distributions();
+        handle.initialize().unwrap();
+
+        // A receipt processed through one handle counts for the other
+        process(&handle, receipt("node-1", (0, 86400), 10)).unwrap();
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap(),
+            1055
+        );
+
+        // Results of the shared processor reach subscribers of every handle
+        let (public_key, secret_key) = creator_keys();
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([("node-1".to_string(), Ratio::ONE)]),
+                &reference_state(),
+            )
+            .unwrap();
+        assert_eq!(next_record(&mut records).await.vault_id, vault_id);
+        assert_eq!(
+            handle
+                .get_distribution_history(Some(&vault_id))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // The processor holds no handle, so dropping the last one frees the manager
+        let shared = Arc::downgrade(&manager.inner);
+        drop(manager);
+        drop(handle);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while shared.upgrade().is_some() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
             }
+        })
+        .await
+        .expect("processor kept the manager alive");
+    }
+
+    fn segment(
+        receipt: &StorageReceipt,
+        (start, end): (u64, u64),
+        source: ScheduleSource,
+        reward: u64,
+    ) -> ScheduleSegment {
+        ScheduleSegment {
+            receipt_hash: receipt.receipt_hash,
+            start,
+            end,
+            source,
+            reward,
         }
     }
 
-    /// Update a vault's status
-    fn update_vault_status(&self, vault_id: &str, status: &str) -> Result<()> {
-        let mut registry = self
-            .vault_registry
+    fn schedule(
+        base_rate_per_byte_day: u64,
+        retrieval_rate: u64,
+        operation_rate: u64,
+    ) -> RateSchedule {
+        RateSchedule {
+            base_rate_per_byte_day,
+            retrieval_rate,
+            operation_rate,
+            uptime_multiplier: Ratio::ONE,
+            region_multipliers: HashMap::new(),
+            region_strategy: RegionMultiplierStrategy::Max,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receipt_straddling_rate_change() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        manager
+            .schedule_rate_change(43200, schedule(200, 20, 10))
+            .unwrap();
+        let straddling = receipt("node-1", (0, 86400), 10);
+        process(&manager, straddling.clone()).unwrap();
+
+        assert_eq!(
+            manager
+                .get_rate_schedule_at(43199)
+                .unwrap()
+                .base_rate_per_byte_day,
+            100
+        );
+        assert_eq!(
+            manager
+                .get_rate_schedule_at(43200)
+                .unwrap()
+                .base_rate_per_byte_day,
+            200
+        );
+
+        // Half a day at the default rates, half at the new ones; of the 3
+        // retrievals and 5 operations, 1 and 2 fall in the first half
+        let breakdown = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!(
+            breakdown,
+            RewardBreakdown {
+                storage_reward: 500 + 1000,
+                retrieval_reward: 10 + 40,
+                operation_reward: 10 + 30,
+                slashed: 0,
+                redistributed: 0,
+                total: 1590,
+                region_multiplier: unit_region_multiplier(),
+                segments: vec![
+                    segment(
+                        &straddling,
+                        (0, 43200),
+                        ScheduleSource::Global { effective_from: 0 },
+                        520,
+                    ),
+                    segment(
+                        &straddling,
+                        (43200, 86400),
+                        ScheduleSource::Global {
+                            effective_from: 43200,
+                        },
+                        1070,
+                    ),
+                ],
+            }
+        );
+
+        // A period before the change is unaffected by it
+        let first_half = manager.node_reward_breakdown("node-1", 0, 43200).unwrap();
+        assert_eq!(first_half.storage_reward, 500);
+    }
+
+    #[tokio::test]
+    async fn test_region_multiplier_requires_oracle_proof() {
+        let (oracle_key, oracle_secret) = sphincs::generate_sphincs_keypair().unwrap();
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default())
+                .with_region_oracle(Some(oracle_key));
+        let mut rates = schedule(0, 0, 10);
+        rates.region_multipliers =
+            HashMap::from([("eu".to_string(), Ratio::multiplier(2.0).unwrap())]);
+        manager.schedule_rate_change(0, rates).unwrap();
+
+        let (_, mut proof) = claimed_region("eu");
+        let signature =
+            sign_with_node_key(&oracle_secret, &region_proof_signing_hash(&proof)).unwrap();
+        if let RegionProofType::IpGeolocation {
+            lookup_signature, ..
+        } = &mut proof.proof_type
+        {
+            *lookup_signature = signature;
+        }
+
+        let proven = ("eu".to_string(), proof);
+        for (node_id, region) in [("node-1", proven), ("node-2", claimed_region("eu"))] {
+            let mut storage_metrics = receipt_metrics(0, 100);
+            storage_metrics.regions = HashSet::from([region]);
+            let receipt = sealed_receipt(node_id, (0, 86400), storage_metrics);
+            process(&manager, receipt).unwrap();
+        }
+
+        // 5 operations at 10 each, doubled only where the oracle proved the region
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap(),
+            100
+        );
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-2", 0, 86400)
+                .await
+                .unwrap(),
+            50
+        );
+    }
+
+    #[tokio::test]
+    async fn test_receipt_straddling_period_boundary_is_split() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+
+        // Of the 3 retrievals and 5 operations, 1 and 2 fall in the first half
+        let first = manager.node_reward_breakdown("node-1", 0, 43200).unwrap();
+        let second = manager
+            .node_reward_breakdown("node-1", 43200, 86400)
+            .unwrap();
+        let whole = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!((first.retrieval_reward, first.operation_reward), (10, 10));
+        assert_eq!((second.retrieval_reward, second.operation_reward), (20, 15));
+        assert_eq!(
+            first.storage_reward + second.storage_reward,
+            whole.storage_reward
+        );
+        assert_eq!(
+            first.retrieval_reward + second.retrieval_reward,
+            whole.retrieval_reward
+        );
+        assert_eq!(
+            first.operation_reward + second.operation_reward,
+            whole.operation_reward
+        );
+
+        let first = manager
+            .calculate_node_rewards("node-1", 0, 43200)
+            .await
+            .unwrap();
+        let second = manager
+            .calculate_node_rewards("node-1", 43200, 86400)
+            .await
+            .unwrap();
+        let whole = manager
+            .calculate_node_rewards("node-1", 0, 86400)
+            .await
+            .unwrap();
+        assert_eq!(first + second, whole);
+    }
+
+    #[test]
+    fn test_schedule_history_survives_restart() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store.clone(),
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+        manager
+            .schedule_rate_change(2000, schedule(300, 30, 15))
+            .unwrap();
+        manager
+            .schedule_rate_change(1000, schedule(200, 20, 10))
+            .unwrap();
+        manager
+            .schedule_rate_change(2000, schedule(400, 40, 20))
+            .unwrap();
+        drop(manager);
+
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store,
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+        let history: Vec<(u64, u64)> = manager
+            .get_schedule_history()
+            .unwrap()
+            .into_iter()
+            .map(|(effective_from, schedule)| (effective_from, schedule.base_rate_per_byte_day))
+            .collect();
+        assert_eq!(history, vec![(0, 100), (1000, 200), (2000, 400)]);
+    }
+
+    #[tokio::test]
+    async fn test_node_rate_override_preferred() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let overridden = receipt("node-1", (0, 86400), 10);
+        process(&manager, overridden.clone()).unwrap();
+        process(&manager, receipt("node-2", (0, 86400), 10)).unwrap();
+
+        // Negotiated rates for node-1 over the first half of the day only
+        manager
+            .record_node_rate_override("node-1", 0, Some(schedule(1000, 0, 0)))
+            .unwrap();
+        manager
+            .record_node_rate_override("node-1", 43200, None)
+            .unwrap();
+
+        let breakdown = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!(breakdown.total, 5000 + 535);
+        assert_eq!(
+            breakdown.segments,
+            vec![
+                segment(
+                    &overridden,
+                    (0, 43200),
+                    ScheduleSource::Override { effective_from: 0 },
+                    5000,
+                ),
+                segment(
+                    &overridden,
+                    (43200, 86400),
+                    ScheduleSource::Global { effective_from: 0 },
+                    535,
+                ),
+            ]
+        );
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap(),
+            5535
+        );
+
+        // Other nodes keep the global rates
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-2", 0, 86400)
+                .await
+                .unwrap(),
+            1055
+        );
+    }
+
+    #[test]
+    fn test_node_rate_override_set_and_cleared() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store.clone(),
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+
+        manager
+            .set_node_rate_override("node-1", schedule(1000, 0, 0))
+            .unwrap();
+        let current = manager.node_rate_override("node-1").unwrap().unwrap();
+        assert_eq!(current.base_rate_per_byte_day, 1000);
+        assert!(manager.node_rate_override("node-2").unwrap().is_none());
+        drop(manager);
+
+        // The override is reloaded, and clearing it is persisted too
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store.clone(),
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+        assert!(manager.node_rate_override("node-1").unwrap().is_some());
+        manager.clear_node_rate_override("node-1").unwrap();
+        assert!(manager.node_rate_override("node-1").unwrap().is_none());
+        drop(manager);
+
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store,
+            RewardManagerConfig::default(),
+        )
+        .unwrap();
+        assert!(manager.node_rate_override("node-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_export_report() {
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let audit_log = Arc::new(AuditLog::new(public_key, secret_key));
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default())
+                .with_audit_log(Some(audit_log.clone()));
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+        process(&manager, receipt("node-2", (0, 86400), 20)).unwrap();
+
+        let amounts = HashMap::from([("node-1".to_string(), 600), ("node-2".to_string(), 400)]);
+        let audit = audit_log
+            .append(
+                "vault-1",
+                [0u8; 32],
+                amounts.clone().into_iter().collect(),
+                Vec::new(),
+                1000,
+            )
+            .unwrap();
+        manager
+            .inner
+            .distribution_history
             .write()
-            .map_err(|_| StorageNodeError::Internal)?;
+            .unwrap()
+            .push(DistributionRecord {
+                vault_id: "vault-1".to_string(),
+                success: true,
+                scheduled_at: 1000,
+                processed_at: 1000,
+                error: None,
+                amounts,
+                transfer_states: HashMap::new(),
+            });
+        let transaction_ref = hex::encode(audit.record_hash);
+
+        let json = manager.export_report(0, 86400, ReportFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let rows = json["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0]["record_type"], "node_reward");
+        assert_eq!(rows[0]["node_id"], "node-1");
+        assert_eq!(rows[0]["amount"], 1055);
+        assert_eq!(rows[0]["storage_reward"], 1000);
+        assert_eq!(rows[0]["decimals"], 18);
+        assert_eq!(rows[1]["amount"], 2055);
+        assert_eq!(rows[2]["record_type"], "distribution");
+        assert_eq!(rows[2]["transaction_ref"], transaction_ref.as_str());
+        assert_eq!(rows[3]["node_id"], "node-2");
+        assert_eq!(rows[3]["amount"], 400);
+
+        let csv = manager.export_report(0, 86400, ReportFormat::Csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("record_type,node_id,token_id,decimals,amount,"));
+        assert!(lines[1].starts_with("node_reward,node-1,ROOT,18,1055,1000,30,25,0,0,"));
+        assert_eq!(
+            lines[3],
+            format!(
+                "distribution,node-1,ROOT,18,600,,,,,,,vault-1,{},1000",
+                transaction_ref
+            )
+        );
 
-        if let Some(metadata) = registry.get_mut(vault_id) {
-            metadata.status = status.to_string();
-            Ok(())
-        } else {
-            Err(StorageNodeError::NotFound(format!("Vault {}", vault_id)))
+        // Nothing was served or paid out in the following period
+        let later = manager
+            .export_report(86400, 172800, ReportFormat::Json)
+            .unwrap();
+        let later: serde_json::Value = serde_json::from_slice(&later).unwrap();
+        assert!(later["rows"].as_array().unwrap().is_empty());
+        assert!(manager.export_report(100, 100, ReportFormat::Csv).is_err());
+    }
+
+    #[test]
+    fn test_audit_cites_each_receipt_once() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        assert!(matches!(
+            manager.verify_audit_log(),
+            Err(StorageNodeError::Config(_))
+        ));
+
+        let (public_key, secret_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let audit_log = Arc::new(AuditLog::new(public_key, secret_key));
+        let manager = manager.with_audit_log(Some(audit_log.clone()));
+        assert!(manager.export_audit_log(0..10).unwrap().is_empty());
+
+        let early = receipt("node-1", (0, 100), 10);
+        let late = receipt("node-1", (100, 300), 10);
+        for receipt in [early.clone(), late.clone(), receipt("node-3", (0, 100), 10)] {
+            process(&manager, receipt).unwrap();
         }
+
+        let vault = |distribution_time| VaultMetadata {
+            vault_id: format!("vault-{}", distribution_time),
+            purpose: String::new(),
+            creator_id: String::new(),
+            token_amount: 1_000,
+            token_id: "ROOT".to_string(),
+            created_at: 0,
+            distribution_time,
+            recipients: HashMap::from([("node-1".to_string(), Ratio::ONE)]),
+            status: "active".to_string(),
+            dispute_window: Duration::ZERO,
+            proposal: None,
+            disputes: Vec::new(),
+            pull_claims: false,
+            claims: HashMap::new(),
+            revocation_reason: None,
+            payouts: BTreeMap::new(),
+        };
+
+        // Only receipts of recipients that ended by the distribution time count
+        let cited = manager.contributing_receipts(&vault(200)).unwrap();
+        assert_eq!(
+            cited,
+            BTreeMap::from([("node-1".to_string(), vec![early.receipt_hash])])
+        );
+        let cited = cited.into_values().flatten().collect();
+        audit_log
+            .append("vault-200", [0; 32], Default::default(), cited, 200)
+            .unwrap();
+
+        // A later vault cites only the receipts no earlier record cites
+        let cited = manager.contributing_receipts(&vault(400)).unwrap();
+        assert_eq!(
+            cited,
+            BTreeMap::from([("node-1".to_string(), vec![late.receipt_hash])])
+        );
+
+        manager.verify_audit_log().unwrap();
+        assert_eq!(manager.export_audit_log(0..10).unwrap().len(), 1);
     }
 
-    /// Start the distribution processor
-    fn start_distribution_processor(&self) {
-        // Clone what we need for the task
-        let distribution_queue = Arc::new(Mutex::new(Vec::<DistributionRequest>::new()));
-        let distribution_tx = self.distribution_tx.clone();
-        let self_clone = Arc::new(self.clone());
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_constructor_logs_config() {
+        let config = RewardManagerConfig {
+            min_payout: 25,
+            dispute_window: Duration::from_secs(3600),
+            ..RewardManagerConfig::default()
+        };
+        let _manager = RewardVaultManager::new(Arc::new(DLVManager::new()), config);
 
-        // Spawn processing task
-        tokio::spawn(async move {
-            let mut check_interval = interval(Duration::from_secs(60)); // Check every minute
+        assert!(logs_contain("Creating reward vault manager"));
+        assert!(logs_contain("min_payout=25"));
+        assert!(logs_contain("dispute_window_secs=3600"));
+    }
 
-            loop {
-                check_interval.tick().await;
+    /// Manager with an hour-long dispute window and a vault of node-1 that is due
+    fn disputed_vault_manager() -> (RewardVaultManager, String) {
+        let config = RewardManagerConfig {
+            dispute_window: Duration::from_secs(3600),
+            ..RewardManagerConfig::default()
+        };
+        let manager = RewardVaultManager::new(Arc::new(DLVManager::new()), config);
+        process(&manager, receipt("node-1", (0, 100), 10)).unwrap();
+
+        let (public_key, secret_key) = creator_keys();
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([("node-1".to_string(), Ratio::ONE)]),
+                &reference_state(),
+            )
+            .unwrap();
+        (manager, vault_id)
+    }
 
-                // Get current time
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
+    /// Take a vault's request out of the queue and process it, as the processor does
+    async fn process_queued(manager: &RewardVaultManager, vault_id: &str) {
+        let request = {
+            let mut queue = manager.inner.distribution_queue.lock().unwrap();
+            let index = queue.iter().position(|r| r.vault_id == vault_id).unwrap();
+            queue.remove(index)
+        };
+        manager.handle_distribution(request, now()).await.unwrap();
+    }
 
-                // Get requests to process
-                let to_process;
-                {
-                    let mut queue = distribution_queue.lock().unwrap();
+    fn queued_retry_at(manager: &RewardVaultManager, vault_id: &str) -> u64 {
+        let queue = manager.inner.distribution_queue.lock().unwrap();
+        queue
+            .iter()
+            .find(|r| r.vault_id == vault_id)
+            .unwrap()
+            .retry_at
+    }
 
-                    // Find ready requests
-                    let (ready, pending): (Vec<_>, Vec<_>) =
-                        queue.drain(..).partition(|req| req.timestamp <= now);
+    fn signed_claim(vault_id: &str, keys: &(Vec<u8>, Vec<u8>)) -> DisputeClaim {
+        let mut claim =
+            DisputeClaim::new(vault_id, "node-1", 1_200, "Uptime missed", keys.0.clone());
+        claim.sign(&keys.1).unwrap();
+        claim
+    }
 
-                    to_process = ready;
+    #[test]
+    fn test_revocation_requires_creator_and_unclaimed_vault() {
+        let (manager, vault_id) = disputed_vault_manager();
+        let status = manager.get_vault(&vault_id).unwrap().status;
+
+        // A signature by anyone but the creator leaves the vault untouched
+        let (_, intruder_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let message = LimboVault::invalidation_message(&vault_id, "fraud", b"reference");
+        let forged = sphincs::sphincs_sign(&intruder_key, &message).unwrap();
+        assert!(matches!(
+            manager.revoke_reward_vault(&vault_id, &forged, "fraud"),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert_eq!(manager.get_vault(&vault_id).unwrap().status, status);
+        assert_eq!(manager.inner.distribution_queue.lock().unwrap().len(), 1);
+
+        manager.update_vault_status(&vault_id, "claimed").unwrap();
+        assert!(matches!(
+            manager.revoke_reward_vault(&vault_id, &forged, "fraud"),
+            Err(StorageNodeError::Staking(_))
+        ));
+    }
 
-                    // Put pending requests back in queue
-                    *queue = pending;
-                }
+    #[tokio::test]
+    async fn test_distribution_proposed_before_unlock() {
+        let (manager, vault_id) = disputed_vault_manager();
+        let mut proposals = manager.subscribe_proposals();
 
-                // Process each ready request
-                for request in to_process {
-                    match self_clone.process_distribution(request) {
-                        Ok(result) => {
-                            if let Err(e) = distribution_tx.send(result).await {
-                                error!("Failed to send distribution result: {}", e);
-                            }
-                        }
-                        Err(e) => {
-                            error!("Failed to process distribution: {}", e);
-                        }
-                    }
-                }
-            }
+        process_queued(&manager, &vault_id).await;
+
+        let proposal = proposals.try_recv().unwrap();
+        let vault = manager.get_vault(&vault_id).unwrap();
+        assert_eq!(
+            proposal.amounts,
+            HashMap::from([("node-1".to_string(), 1_000)])
+        );
+        assert_eq!(proposal.receipt_hashes["node-1"].len(), 1);
+        assert_eq!(proposal.dispute_deadline, vault.distribution_time + 3600);
+        assert_eq!(vault.proposal, Some(proposal.clone()));
+
+        // The vault stays locked until the window closes, without a failed attempt
+        assert_eq!(
+            queued_retry_at(&manager, &vault_id),
+            proposal.dispute_deadline
+        );
+        assert_eq!(
+            manager.inner.distribution_queue.lock().unwrap()[0].attempts,
+            0
+        );
+        assert!(manager.get_distribution_history(None).unwrap().is_empty());
+
+        // Processing again reuses the published proposal
+        process_queued(&manager, &vault_id).await;
+        assert!(proposals.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dispute_freezes_distribution_until_resolved() {
+        let (manager, vault_id) = disputed_vault_manager();
+        let [client_keys, node_keys] = receipt_keys();
+
+        // Nothing can be disputed before the split is proposed
+        let claim = signed_claim(&vault_id, node_keys);
+        assert!(matches!(
+            manager.file_dispute(&vault_id, "node-1", claim.clone()),
+            Err(StorageNodeError::Staking(_))
+        ));
+        process_queued(&manager, &vault_id).await;
+
+        let mut tampered = claim.clone();
+        tampered.claimed_amount += 1;
+        assert!(matches!(
+            manager.file_dispute(&vault_id, "node-1", tampered),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert!(matches!(
+            manager.file_dispute(&vault_id, "node-1", signed_claim(&vault_id, client_keys)),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert!(matches!(
+            manager.file_dispute(&vault_id, "node-2", claim.clone()),
+            Err(StorageNodeError::InvalidInput(_))
+        ));
+
+        manager.file_dispute(&vault_id, "node-1", claim).unwrap();
+        process_queued(&manager, &vault_id).await;
+        assert_eq!(queued_retry_at(&manager, &vault_id), FROZEN_DISTRIBUTION);
+
+        // Amendments cannot pay out more than the vault holds
+        let amounts = HashMap::from([("node-1".to_string(), 1_200)]);
+        assert!(matches!(
+            manager.resolve_dispute(&vault_id, DisputeResolution::Amend { amounts }),
+            Err(StorageNodeError::InvalidInput(_))
+        ));
+
+        let reason = "Receipts do not cover the claim".to_string();
+        manager
+            .resolve_dispute(&vault_id, DisputeResolution::Cancel { reason })
+            .unwrap();
+        assert_eq!(queued_retry_at(&manager, &vault_id), 0);
+        assert!(manager.get_vault(&vault_id).unwrap().disputes[0]
+            .resolved_at
+            .is_some());
+        assert!(matches!(
+            manager.resolve_dispute(&vault_id, DisputeResolution::Proceed),
+            Err(StorageNodeError::Staking(_))
+        ));
+
+        process_queued(&manager, &vault_id).await;
+        let failed = manager.get_failed_distributions().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0]
+            .error
+            .starts_with("Distribution cancelled after dispute"));
+    }
+
+    #[tokio::test]
+    async fn test_amended_split_paid_after_window() {
+        let (manager, vault_id) = disputed_vault_manager();
+        let [_, node_keys] = receipt_keys();
+        process_queued(&manager, &vault_id).await;
+
+        manager
+            .file_dispute(&vault_id, "node-1", signed_claim(&vault_id, node_keys))
+            .unwrap();
+        let amounts = HashMap::from([("node-1".to_string(), 900)]);
+        manager
+            .resolve_dispute(
+                &vault_id,
+                DisputeResolution::Amend {
+                    amounts: amounts.clone(),
+                },
+            )
+            .unwrap();
+
+        // Close the window; disputes are rejected from then on
+        manager
+            .inner
+            .vault_registry
+            .write()
+            .unwrap()
+            .get_mut(&vault_id)
+            .unwrap()
+            .proposal
+            .as_mut()
+            .unwrap()
+            .dispute_deadline = now() - 1;
+        let error = manager
+            .file_dispute(&vault_id, "node-1", signed_claim(&vault_id, node_keys))
+            .unwrap_err();
+        assert!(error.to_string().contains("closed"));
+
+        process_queued(&manager, &vault_id).await;
+        let history = manager.get_distribution_history(None).unwrap();
+        assert_eq!(history.len(), 1);
+        if history[0].success {
+            assert_eq!(history[0].amounts, amounts);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_transfers_retried_for_their_recipients_only() {
+        let executor = Arc::new(MockDistributionExecutor::default());
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let config = immediate_retries(3);
+        let manager =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store.clone(), config)
+                .unwrap()
+                .with_executor(Some(executor.clone()));
+
+        let (public_key, secret_key) = creator_keys();
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([
+                    ("node-1".to_string(), Ratio::from_percentage(60)),
+                    ("node-2".to_string(), Ratio::from_percentage(40)),
+                ]),
+                &reference_state(),
+            )
+            .unwrap();
+
+        // The vault was claimed; only its transfers are left
+        manager
+            .update_vault(&vault_id, |metadata| {
+                metadata.status = "claimed".to_string();
+                metadata.payouts = BTreeMap::from([
+                    ("node-1".to_string(), Payout::pending(600)),
+                    ("node-2".to_string(), Payout::pending(400)),
+                ]);
+                Ok(())
+            })
+            .unwrap();
+
+        executor.set_failing("node-2", true);
+        process_queued(&manager, &vault_id).await;
+        let history = manager.get_distribution_history(Some(&vault_id)).unwrap();
+        assert!(!history[0].success);
+        assert!(history[0].error.as_ref().unwrap().contains("node-2"));
+
+        // The status of each transfer survives a restart
+        let manager = RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, config)
+            .unwrap()
+            .with_executor(Some(executor.clone()));
+        let payouts = manager.get_vault(&vault_id).unwrap().payouts;
+        assert_eq!(payouts["node-1"].state_number(), Some(1));
+        assert!(matches!(
+            payouts["node-2"].status,
+            PayoutStatus::Failed { .. }
+        ));
+
+        executor.set_failing("node-2", false);
+        process_queued(&manager, &vault_id).await;
+        let recipients: Vec<_> = executor
+            .executed()
+            .into_iter()
+            .map(|transfer| (transfer.node_id, transfer.amount))
+            .collect();
+        assert_eq!(
+            recipients,
+            [("node-1".to_string(), 600), ("node-2".to_string(), 400)]
+        );
+
+        let history = manager.get_distribution_history(Some(&vault_id)).unwrap();
+        let record = history.last().unwrap();
+        assert!(record.success);
+        assert_eq!(
+            record.amounts,
+            HashMap::from([("node-1".to_string(), 600), ("node-2".to_string(), 400)])
+        );
+        assert_eq!(
+            record.transfer_states,
+            HashMap::from([("node-1".to_string(), 1), ("node-2".to_string(), 2)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_transfer_not_repeated() {
+        let executor = Arc::new(MockDistributionExecutor::default());
+        let manager = RewardVaultManager::new(Arc::new(DLVManager::new()), immediate_retries(3))
+            .with_executor(Some(executor.clone()));
+
+        let (public_key, secret_key) = creator_keys();
+        let vault_id = manager
+            .create_reward_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([("node-1".to_string(), Ratio::from_percentage(100))]),
+                &reference_state(),
+            )
+            .unwrap();
+
+        // The transfer went through, but the node stopped before recording it
+        executor
+            .execute(&PayoutTransfer::new(&vault_id, "node-1", "ROOT", 1_000))
+            .await
+            .unwrap();
+        manager
+            .update_vault(&vault_id, |metadata| {
+                metadata.status = "claimed".to_string();
+                metadata.payouts = BTreeMap::from([(
+                    "node-1".to_string(),
+                    Payout {
+                        amount: 1_000,
+                        status: PayoutStatus::InFlight,
+                    },
+                )]);
+                Ok(())
+            })
+            .unwrap();
+
+        process_queued(&manager, &vault_id).await;
+        assert_eq!(executor.executed().len(), 1);
+        let payouts = manager.get_vault(&vault_id).unwrap().payouts;
+        assert_eq!(payouts["node-1"].state_number(), Some(1));
+    }
+
+    #[test]
+    fn test_nodes_claim_their_own_shares() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        process(&manager, receipt("node-1", (0, 100), 10)).unwrap();
+        let [client_keys, node_keys] = receipt_keys();
+
+        let (public_key, secret_key) = creator_keys();
+        let state = reference_state();
+        let vault_id = manager
+            .create_claimable_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([
+                    ("node-1".to_string(), Ratio::from_percentage(60)),
+                    ("node-2".to_string(), Ratio::from_percentage(40)),
+                ]),
+                &state,
+            )
+            .unwrap();
+
+        // Claimable vaults are not distributed by the manager
+        assert!(manager.inner.distribution_queue.lock().unwrap().is_empty());
+        assert_eq!(
+            manager.get_unclaimed_recipients(&vault_id).unwrap(),
+            ["node-1", "node-2"]
+        );
+
+        let signing_hash = claim_signing_hash(&vault_id, "node-1", &state);
+        let signature = sign_with_node_key(&node_keys.1, &signing_hash).unwrap();
+        let forged = sign_with_node_key(&client_keys.1, &signing_hash).unwrap();
+        assert!(matches!(
+            manager.claim_reward(&vault_id, "node-1", &forged, &state),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert!(matches!(
+            manager.claim_reward(&vault_id, "node-3", &signature, &state),
+            Err(StorageNodeError::InvalidInput(_))
+        ));
+
+        // The DLV may not unlock against the test reference state
+        if let Ok(voucher) = manager.claim_reward(&vault_id, "node-1", &signature, &state) {
+            assert_eq!((voucher.amount, voucher.token_id.as_str()), (600, "ROOT"));
+            assert_eq!(
+                manager.get_unclaimed_recipients(&vault_id).unwrap(),
+                ["node-2"]
+            );
+            assert!(matches!(
+                manager.claim_reward(&vault_id, "node-1", &signature, &state),
+                Err(StorageNodeError::Staking(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_claims_need_the_registered_node_key() {
+        let manager =
+            RewardVaultManager::new(Arc::new(DLVManager::new()), RewardManagerConfig::default());
+        let (public_key, secret_key) = creator_keys();
+        let state = reference_state();
+        let vault_id = manager
+            .create_claimable_vault(
+                (&public_key, &secret_key),
+                1_000,
+                "ROOT",
+                now() - 60,
+                HashMap::from([("node-1".to_string(), Ratio::ONE)]),
+                &state,
+            )
+            .unwrap();
+
+        // A key the claimant made up is not accepted before registration
+        let (_, made_up_key) = sphincs::generate_sphincs_keypair().unwrap();
+        let signing_hash = claim_signing_hash(&vault_id, "node-1", &state);
+        let signature = sign_with_node_key(&made_up_key, &signing_hash).unwrap();
+        assert!(matches!(
+            manager.claim_reward(&vault_id, "node-1", &signature, &state),
+            Err(StorageNodeError::Authentication(_))
+        ));
+
+        // Nor after the node registered its own
+        let [_, node_keys] = receipt_keys();
+        manager
+            .register_identity_key("node-1", node_keys.0.clone())
+            .unwrap();
+        assert!(matches!(
+            manager.claim_reward(&vault_id, "node-1", &signature, &state),
+            Err(StorageNodeError::Authentication(_))
+        ));
+        assert_eq!(
+            manager.get_unclaimed_recipients(&vault_id).unwrap(),
+            ["node-1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_small_shares_carry_over_once() {
+        let config = RewardManagerConfig {
+            min_payout: 10,
+            ..RewardManagerConfig::default()
+        };
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store.clone(), config)
+                .unwrap();
+        let split = |amount: u64| HashMap::from([("node-1".to_string(), amount)]);
+
+        let paid = manager
+            .apply_carryover(HashMap::from([
+                ("node-1".to_string(), 3),
+                ("node-2".to_string(), 50),
+            ]))
+            .unwrap();
+        assert_eq!(paid, HashMap::from([("node-2".to_string(), 50)]));
+        assert!(manager.apply_carryover(split(4)).unwrap().is_empty());
+        assert_eq!(manager.pending_carryover("node-1").unwrap(), 7);
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap(),
+            7
+        );
+
+        // The carryover survives a restart
+        let manager =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, config).unwrap();
+        assert_eq!(manager.pending_carryover("node-1").unwrap(), 7);
+
+        // Of two vaults distributed at once, only one pays the carryover
+        let payouts: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| manager.apply_carryover(split(5)).unwrap()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
         });
+        let paid: Vec<u64> = payouts
+            .iter()
+            .filter_map(|paid| paid.get("node-1").copied())
+            .collect();
+        assert_eq!(paid, [12]);
+        assert_eq!(manager.pending_carryover("node-1").unwrap(), 5);
     }
-}
 
-/// Allow cloning the RewardVaultManager
-impl Clone for RewardVaultManager {
-    fn clone(&self) -> Self {
-        // Create a new channel for the clone
-        let (tx, rx) = mpsc::channel(100);
+    #[test]
+    fn test_compacted_receipts_keep_rewards() {
+        let store = Arc::new(SqliteRewardStore::in_memory().unwrap());
+        let manager = RewardVaultManager::with_store(
+            Arc::new(DLVManager::new()),
+            store.clone(),
+            Default::default(),
+        )
+        .unwrap();
+        for day in 0..3 {
+            let start = day * 86400;
+            process(&manager, receipt("node-1", (start, start + 86400), 1_000)).unwrap();
+        }
+        process(&manager, receipt("node-1", (259200, 345600), 1_000)).unwrap();
+        process(&manager, receipt("node-2", (0, 86400), 2_000)).unwrap();
+
+        let settled = manager.node_reward_breakdown("node-1", 0, 259200).unwrap();
+        let full = manager.node_reward_breakdown("node-1", 0, 345600).unwrap();
+
+        let aggregates = manager.compact_receipts(259200).unwrap();
+        assert_eq!(aggregates.len(), 2);
+        let node_aggregates = manager.aggregated_receipts("node-1").unwrap();
+        assert_eq!(node_aggregates.len(), 1);
+        let aggregate = &node_aggregates[0];
+        assert_eq!(aggregate.receipt_count, 3);
+        assert_eq!(aggregate.service_period, (0, 259200));
+        assert_eq!(aggregate.storage_metrics.bytes_stored, 3_000);
+
+        // The open period stays raw, and both periods earn what they did before
+        assert_eq!(
+            manager.inner.receipt_registry.read().unwrap()["node-1"].len(),
+            1
+        );
+        assert!(!manager
+            .inner
+            .receipt_registry
+            .read()
+            .unwrap()
+            .contains_key("node-2"));
+        let compacted = manager.node_reward_breakdown("node-1", 0, 259200).unwrap();
+        assert_eq!(compacted.total, settled.total);
+        assert_eq!(compacted.base_reward(), settled.base_reward());
+        assert_eq!(
+            manager
+                .node_reward_breakdown("node-1", 0, 345600)
+                .unwrap()
+                .total,
+            full.total
+        );
+
+        // Half of the settled period earns half of its reward
+        let half = manager.node_reward_breakdown("node-1", 0, 129600).unwrap();
+        assert_eq!(half.total, settled.total / 2);
+
+        // Compaction survives a restart and nothing is left to compact
+        let manager =
+            RewardVaultManager::with_store(Arc::new(DLVManager::new()), store, Default::default())
+                .unwrap();
+        assert_eq!(manager.aggregated_receipts("node-1").unwrap().len(), 1);
+        assert_eq!(
+            manager
+                .node_reward_breakdown("node-1", 0, 345600)
+                .unwrap()
+                .total,
+            full.total
+        );
+        assert!(manager.compact_receipts(259200).unwrap().is_empty());
+    }
 
-        Self {
-            dlv_manager: self.dlv_manager.clone(),
-            vault_registry: RwLock::new(match self.vault_registry.read() {
-                Ok(registry) => registry.clone(),
-                Err(_) => HashMap::new(),
-            }),
-            receipt_registry: RwLock::new(match self.receipt_registry.read() {
-                Ok(registry) => registry.clone(),
-                Err(_) => HashMap::new(),
-            }),
-            rate_schedule: RwLock::new(match self.rate_schedule.read() {
-                Ok(schedule) => schedule.clone(),
-                Err(_) => RewardVaultManager::default_rate_schedule(),
-            }),
-            distribution_queue: Mutex::new(Vec::new()),
-            distribution_tx: tx,
-            distribution_rx: Mutex::new(rx),
+    /// Manager with the default schedule and a slashing policy
+    fn slashing_manager(slashed_rewards: SlashedRewards) -> RewardVaultManager {
+        let config = RewardManagerConfig {
+            slashing_policy: SlashingPolicy {
+                slashed_rewards,
+                ..SlashingPolicy::default()
+            },
+            ..RewardManagerConfig::default()
+        };
+        RewardVaultManager::new(Arc::new(DLVManager::new()), config)
+    }
+
+    #[tokio::test]
+    async fn test_failed_challenges_slash_rewards() {
+        let manager = slashing_manager(SlashedRewards::Burn);
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+        process(&manager, receipt("node-2", (0, 86400), 10)).unwrap();
+
+        // Two failures in the period; passes and later failures do not count
+        manager
+            .record_challenge_result("node-1", false, 100)
+            .unwrap();
+        manager
+            .record_challenge_result("node-1", false, 200)
+            .unwrap();
+        manager
+            .record_challenge_result("node-1", true, 300)
+            .unwrap();
+        manager
+            .record_challenge_result("node-1", false, 90000)
+            .unwrap();
+
+        // 1055 earned, less 10% for each failure
+        let slashed = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!(slashed.slashed, 210);
+        assert_eq!(slashed.redistributed, 0);
+        assert_eq!(slashed.total, 845);
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap(),
+            845
+        );
+
+        // Burned rewards are not paid to compliant nodes
+        let compliant = manager.node_reward_breakdown("node-2", 0, 86400).unwrap();
+        assert_eq!((compliant.slashed, compliant.redistributed), (0, 0));
+        assert_eq!(compliant.total, 1055);
+
+        // The penalty stops at the maximum slash
+        for timestamp in 400..410 {
+            manager
+                .record_challenge_result("node-1", false, timestamp)
+                .unwrap();
         }
+        let capped = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!(capped.slashed, 527);
+        assert_eq!(capped.total, 528);
     }
-}
 
-/// Contents of a reward vault
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VaultContent {
-    /// Total token amount in this vault
-    token_amount: u64,
+    #[tokio::test]
+    async fn test_estimate_current_rewards() {
+        let manager = slashing_manager(SlashedRewards::Burn);
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+        manager
+            .inner
+            .carryover
+            .lock()
+            .unwrap()
+            .insert("node-1".to_string(), 7);
+
+        let estimate = manager.estimate_current_rewards("node-1").await.unwrap();
+        assert!(estimate.is_estimate);
+        assert_eq!(estimate.period_start, 0);
+        assert_eq!((estimate.breakdown.total, estimate.carryover), (1055, 7));
+        assert_eq!(
+            estimate.estimated_reward,
+            manager
+                .calculate_node_rewards("node-1", 0, estimate.period_end)
+                .await
+                .unwrap()
+        );
 
-    /// Token ID (currency type)
-    token_id: String,
+        // Polling reuses the cached breakdown and leaves the registries alone
+        let polled = manager.estimate_current_rewards("node-1").await.unwrap();
+        assert_eq!(polled.breakdown, estimate.breakdown);
+        assert_eq!(manager.inner.estimate_cache.lock().unwrap().len(), 1);
+        assert_eq!(
+            manager.inner.receipt_registry.read().unwrap()["node-1"].len(),
+            1
+        );
 
-    /// Recipient mapping (node_id -> ratio)
-    recipients: HashMap<String, Ratio>,
+        // A new receipt invalidates the cached breakdown
+        process(&manager, receipt("node-1", (86400, 172800), 10)).unwrap();
+        let grown = manager.estimate_current_rewards("node-1").await.unwrap();
+        assert_eq!(grown.breakdown.total, 2110);
 
-    /// Additional metadata
-    metadata: HashMap<String, String>,
+        // Once a distribution is made, only service after it is estimated
+        manager
+            .inner
+            .distribution_history
+            .write()
+            .unwrap()
+            .push(DistributionRecord {
+                vault_id: "vault-1".to_string(),
+                success: true,
+                scheduled_at: 86400,
+                processed_at: 86400,
+                error: None,
+                amounts: HashMap::new(),
+                transfer_states: HashMap::new(),
+            });
+        let open = manager.estimate_current_rewards("node-1").await.unwrap();
+        assert_eq!(open.period_start, 86400);
+        assert_eq!(open.breakdown.total, 1055);
+    }
+
+    #[tokio::test]
+    async fn test_challenge_pass_rate_scales_rewards() {
+        let config = RewardManagerConfig {
+            slashing_policy: SlashingPolicy {
+                challenge_scoring: ChallengeScoring::PassRate {
+                    floor: Ratio::from_percentage(50),
+                },
+                ..SlashingPolicy::default()
+            },
+            ..RewardManagerConfig::default()
+        };
+        let manager = RewardVaultManager::new(Arc::new(DLVManager::new()), config);
+        process(&manager, receipt("node-1", (0, 86400), 10)).unwrap();
+
+        // Full reward while every challenge passes
+        manager
+            .record_challenge_result("node-1", true, 100)
+            .unwrap();
+        let full = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!((full.slashed, full.total), (0, 1055));
+
+        // 1055 earned, scaled to a 75% pass rate
+        for timestamp in [200, 300] {
+            manager
+                .record_challenge_result("node-1", true, timestamp)
+                .unwrap();
+        }
+        manager
+            .record_challenge_result("node-1", false, 400)
+            .unwrap();
+        assert_eq!(
+            manager.challenge_summary("node-1", 0, 86400).unwrap(),
+            ChallengeSummary {
+                passed: 3,
+                failed: 1
+            }
+        );
+        let scaled = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!((scaled.slashed, scaled.total), (264, 791));
+
+        // Below the floor the maximum is slashed
+        for timestamp in 500..503 {
+            manager
+                .record_challenge_result("node-1", false, timestamp)
+                .unwrap();
+        }
+        let floored = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!((floored.slashed, floored.total), (527, 528));
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-1", 0, 86400)
+                .await
+                .unwrap(),
+            528
+        );
+    }
+
+    #[tokio::test]
+    async fn test_low_uptime_slashed_and_redistributed() {
+        let manager = slashing_manager(SlashedRewards::Redistribute);
+        process(&manager, receipt_with_uptime("node-1", (0, 86400), 10, 50)).unwrap();
+        process(&manager, receipt("node-2", (0, 86400), 10)).unwrap();
+        process(&manager, receipt("node-3", (0, 86400), 20)).unwrap();
+
+        // Half uptime earns 527 and is slashed by the maximum
+        let slashed = manager.node_reward_breakdown("node-1", 0, 86400).unwrap();
+        assert_eq!(slashed.slashed, 263);
+        assert_eq!(slashed.redistributed, 0);
+        assert_eq!(slashed.total, 264);
+
+        // The 263 slashed is shared pro rata between 1055 and 2055
+        let second = manager.node_reward_breakdown("node-2", 0, 86400).unwrap();
+        assert_eq!(
+            (second.slashed, second.redistributed, second.total),
+            (0, 89, 1144)
+        );
+        let third = manager.node_reward_breakdown("node-3", 0, 86400).unwrap();
+        assert_eq!(
+            (third.slashed, third.redistributed, third.total),
+            (0, 173, 2228)
+        );
+        assert_eq!(
+            manager
+                .calculate_node_rewards("node-3", 0, 86400)
+                .await
+                .unwrap(),
+            2228
+        );
+    }
 }
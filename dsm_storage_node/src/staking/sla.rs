@@ -0,0 +1,380 @@
+// SLA Monitoring for DSM Storage Node
+//
+// The uptime a node puts in its metrics is its own claim. An SLA monitor
+// measures it from the outside instead: it probes the node's health endpoint
+// at a fixed interval, keeps the outcome and latency of the most recent
+// probes, and compares the share of successful probes with the uptime the
+// node agreed to. Each time a full window of new probes has been taken, a
+// node below the threshold is reported to the registered violation handlers,
+// which may reduce its rewards, submit slashing evidence or raise an alert.
+
+use crate::client::StorageNodeClient;
+use crate::error::{Result, StorageNodeError};
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Health check of a storage node
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    /// Whether the node reports itself healthy
+    async fn check_health(&self) -> Result<bool>;
+}
+
+#[async_trait]
+impl HealthProbe for StorageNodeClient {
+    async fn check_health(&self) -> Result<bool> {
+        StorageNodeClient::check_health(self).await
+    }
+}
+
+/// A node whose measured uptime fell below its SLA over a window of probes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlaViolation {
+    /// ID of the probed node
+    pub node_id: String,
+
+    /// Share of successful probes in the window, from 0.0 to 1.0
+    pub measured: f64,
+
+    /// Uptime the node agreed to, from 0.0 to 1.0
+    pub required: f64,
+
+    /// Time of the window's first probe (seconds since the Unix epoch)
+    pub period_start: u64,
+
+    /// Time of the window's last probe (seconds since the Unix epoch)
+    pub period_end: u64,
+}
+
+/// Reaction to SLA violations, such as reward reduction or alerting
+pub trait SlaViolationHandler: Send + Sync {
+    /// Called once for every violation the monitor detects
+    fn on_violation(&self, v: SlaViolation);
+}
+
+/// Outcome of a single health probe
+#[derive(Debug, Clone)]
+struct ProbeRecord {
+    /// When the probe was sent (seconds since the Unix epoch)
+    timestamp: u64,
+
+    /// Time until the node answered or the probe failed
+    latency: Duration,
+
+    /// Whether the node answered healthy
+    success: bool,
+}
+
+/// Probes a storage node and reports violations of its uptime SLA
+pub struct SlaMonitor {
+    /// ID of the probed node
+    node_id: String,
+
+    /// Health check of the probed node
+    probe: Arc<dyn HealthProbe>,
+
+    /// Minimum share of successful probes, from 0.0 to 1.0
+    sla_threshold: f64,
+
+    /// Number of probes in the rolling window
+    window_size: usize,
+
+    /// Most recent probes, oldest first
+    window: Mutex<VecDeque<ProbeRecord>>,
+
+    /// Probes taken since the window was last evaluated
+    since_evaluation: Mutex<usize>,
+
+    /// Handlers notified of violations
+    handlers: RwLock<Vec<Arc<dyn SlaViolationHandler>>>,
+}
+
+impl SlaMonitor {
+    /// Create a monitor judging `node_id` over windows of `window_size` probes
+    ///
+    /// `sla_threshold` is the required share of successful probes, between
+    /// 0.0 and 1.0.
+    pub fn new(
+        node_id: &str,
+        probe: Arc<dyn HealthProbe>,
+        sla_threshold: f64,
+        window_size: usize,
+    ) -> Result<Self> {
+        if !(0.0..=1.0).contains(&sla_threshold) {
+            return Err(StorageNodeError::InvalidInput(format!(
+                "SLA threshold {} is not between 0 and 1",
+                sla_threshold
+            )));
+        }
+        if window_size == 0 {
+            return Err(StorageNodeError::InvalidInput(
+                "SLA window must hold at least one probe".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            node_id: node_id.to_string(),
+            probe,
+            sla_threshold,
+            window_size,
+            window: Mutex::new(VecDeque::with_capacity(window_size)),
+            since_evaluation: Mutex::new(0),
+            handlers: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Notify `handler` of every violation detected from now on
+    pub fn register_handler(&self, handler: Arc<dyn SlaViolationHandler>) -> Result<()> {
+        self.handlers
+            .write()
+            .map_err(|_| StorageNodeError::Internal)?
+            .push(handler);
+
+        Ok(())
+    }
+
+    /// Probe the node once and evaluate the window if it is due
+    ///
+    /// A failed health check counts as an unsuccessful probe, not an error.
+    ///
+    /// # Returns
+    /// * `Result<Option<SlaViolation>>` - The violation reported after this probe, if any
+    pub async fn probe_once(&self) -> Result<Option<SlaViolation>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let started = Instant::now();
+        let result = self.probe.check_health().await;
+        let latency = started.elapsed();
+
+        let success = match result {
+            Ok(healthy) => healthy,
+            Err(e) => {
+                debug!(node_id = %self.node_id, error = %e, "Health probe failed");
+                false
+            }
+        };
+
+        self.record_probe(ProbeRecord {
+            timestamp,
+            latency,
+            success,
+        })
+    }
+
+    /// Share of successful probes in the current window
+    ///
+    /// `None` until the first probe has been taken.
+    pub fn measured_uptime(&self) -> Result<Option<f64>> {
+        let window = self.window.lock().map_err(|_| StorageNodeError::Internal)?;
+        Ok(Self::uptime_of(&window))
+    }
+
+    /// Mean latency of the probes in the current window
+    pub fn average_latency(&self) -> Result<Option<Duration>> {
+        let window = self.window.lock().map_err(|_| StorageNodeError::Internal)?;
+        if window.is_empty() {
+            return Ok(None);
+        }
+
+        let total: Duration = window.iter().map(|record| record.latency).sum();
+        Ok(Some(total / window.len() as u32))
+    }
+
+    /// Probe the node every `interval` in a background task
+    ///
+    /// The task holds the monitor weakly and stops once it is dropped.
+    pub fn start(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let monitor = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            let mut probe_interval = tokio::time::interval(interval);
+
+            loop {
+                probe_interval.tick().await;
+
+                let Some(monitor) = monitor.upgrade() else {
+                    return;
+                };
+                if let Err(e) = monitor.probe_once().await {
+                    warn!(
+                        node_id = %monitor.node_id,
+                        error = %e,
+                        "SLA probe could not be recorded"
+                    );
+                }
+            }
+        })
+    }
+
+    /// Add a probe to the window and report a violation once a window is due
+    fn record_probe(&self, record: ProbeRecord) -> Result<Option<SlaViolation>> {
+        let violation = {
+            let mut window = self.window.lock().map_err(|_| StorageNodeError::Internal)?;
+            let mut since_evaluation = self
+                .since_evaluation
+                .lock()
+                .map_err(|_| StorageNodeError::Internal)?;
+
+            window.push_back(record);
+            while window.len() > self.window_size {
+                window.pop_front();
+            }
+
+            // Judge each probe once, so one outage is not reported repeatedly
+            *since_evaluation += 1;
+            if *since_evaluation < self.window_size {
+                return Ok(None);
+            }
+            *since_evaluation = 0;
+
+            let measured = Self::uptime_of(&window).unwrap_or_default();
+            if measured >= self.sla_threshold {
+                return Ok(None);
+            }
+
+            SlaViolation {
+                node_id: self.node_id.clone(),
+                measured,
+                required: self.sla_threshold,
+                period_start: window.front().map_or(0, |record| record.timestamp),
+                period_end: window.back().map_or(0, |record| record.timestamp),
+            }
+        };
+
+        warn!(
+            node_id = %violation.node_id,
+            measured = violation.measured,
+            required = violation.required,
+            "Storage node violated its uptime SLA"
+        );
+
+        let handlers = self
+            .handlers
+            .read()
+            .map_err(|_| StorageNodeError::Internal)?
+            .clone();
+        for handler in handlers {
+            handler.on_violation(violation.clone());
+        }
+
+        Ok(Some(violation))
+    }
+
+    fn uptime_of(window: &VecDeque<ProbeRecord>) -> Option<f64> {
+        if window.is_empty() {
+            return None;
+        }
+
+        let successful = window.iter().filter(|record| record.success).count();
+        Some(successful as f64 / window.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Health check answering from a fixed script, then healthy
+    struct ScriptedProbe {
+        answers: Mutex<VecDeque<Result<bool>>>,
+    }
+
+    impl ScriptedProbe {
+        fn new(answers: Vec<Result<bool>>) -> Arc<Self> {
+            Arc::new(Self {
+                answers: Mutex::new(answers.into()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl HealthProbe for ScriptedProbe {
+        async fn check_health(&self) -> Result<bool> {
+            self.answers.lock().unwrap().pop_front().unwrap_or(Ok(true))
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        violations: Mutex<Vec<SlaViolation>>,
+    }
+
+    impl SlaViolationHandler for RecordingHandler {
+        fn on_violation(&self, v: SlaViolation) {
+            self.violations.lock().unwrap().push(v);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_violation_fires_with_measured_uptime() {
+        let probe = ScriptedProbe::new(vec![
+            Ok(true),
+            Ok(false),
+            Err(StorageNodeError::Network("connection refused".to_string())),
+            Ok(true),
+            Ok(false),
+        ]);
+        let monitor = SlaMonitor::new("node-1", probe, 0.9, 5).unwrap();
+        let handler = Arc::new(RecordingHandler::default());
+        monitor.register_handler(handler.clone()).unwrap();
+
+        for _ in 0..4 {
+            assert_eq!(monitor.probe_once().await.unwrap(), None);
+        }
+        let violation = monitor.probe_once().await.unwrap().unwrap();
+
+        assert_eq!(violation.node_id, "node-1");
+        assert!((violation.measured - 0.4).abs() < f64::EPSILON);
+        assert!((violation.required - 0.9).abs() < f64::EPSILON);
+        assert!(violation.period_start <= violation.period_end);
+        assert_eq!(*handler.violations.lock().unwrap(), vec![violation]);
+        assert!(monitor.average_latency().unwrap().is_some());
+
+        // The next window of healthy probes meets the SLA
+        for _ in 0..5 {
+            assert_eq!(monitor.probe_once().await.unwrap(), None);
+        }
+        assert_eq!(monitor.measured_uptime().unwrap(), Some(1.0));
+        assert_eq!(handler.violations.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_background_task_probes_on_interval() {
+        let probe = ScriptedProbe::new((0..5).map(|_| Ok(false)).collect());
+        let monitor = Arc::new(SlaMonitor::new("node-1", probe, 0.5, 5).unwrap());
+        let handler = Arc::new(RecordingHandler::default());
+        monitor.register_handler(handler.clone()).unwrap();
+
+        let task = monitor.start(Duration::from_millis(5));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handler.violations.lock().unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(handler.violations.lock().unwrap()[0].measured.abs() < f64::EPSILON);
+
+        // Dropping the monitor stops the task
+        drop(monitor);
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_threshold_and_window_are_validated() {
+        let probe = ScriptedProbe::new(Vec::new());
+        assert!(SlaMonitor::new("node-1", probe.clone(), 1.5, 5).is_err());
+        assert!(SlaMonitor::new("node-1", probe, 0.9, 0).is_err());
+    }
+}
@@ -463,6 +463,7 @@ pub fn verify_subscription_payment(
                     &subscription.payment_vault_id,
                     verifier_pubkey,
                     reference_state,
+                    None,
                 ) {
                     Ok(content) => {
                         // Deserialize the payment data
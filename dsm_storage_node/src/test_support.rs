@@ -0,0 +1,66 @@
+// Shared test fixtures
+//
+// Staking configuration and API servers used by the tests of several
+// modules, defined once so new configuration fields are added in one place.
+// Genesis states come from `dsm::test_support`.
+
+use std::sync::Arc;
+
+use crate::api::ApiServer;
+use crate::staking::{StakingConfig, StakingService};
+use crate::storage::{MemoryStorage, MemoryStorageConfig, StorageEngine};
+
+/// Staking configuration with staking, subscriptions and reward extras disabled
+pub(crate) fn staking_config() -> StakingConfig {
+    StakingConfig {
+        enable_staking: false,
+        dsm_endpoint: None,
+        staking_address: None,
+        auto_compound: false,
+        reward_distribution_interval: 86400,
+        enable_subscriptions: false,
+        subscription_token_id: "ROOT".to_string(),
+        subscription_payment_account: String::new(),
+        renewal_notification_period: 0,
+        subscription_grace_period: 0,
+        reward_store_path: None,
+        price_feed_url: None,
+        price_cache_ttl: 0,
+        audit_key_path: None,
+        reward_dispute_window: 0,
+        reward_min_payout: 0,
+        reward_compact_receipts: false,
+        reward_max_region_multiplier: 2.0,
+        heartbeat_interval: None,
+        reward_uptime_tolerance: 5,
+        region_oracle_public_key: None,
+    }
+}
+
+/// API server over fresh in-memory storage
+pub(crate) fn api_server() -> ApiServer {
+    api_server_with_storage(Arc::new(MemoryStorage::new(MemoryStorageConfig::default())))
+}
+
+/// API server over the given storage
+pub(crate) fn api_server_with_storage(storage: Arc<dyn StorageEngine + Send + Sync>) -> ApiServer {
+    ApiServer::new(
+        storage,
+        Arc::new(StakingService::new(staking_config())),
+        "127.0.0.1:0".to_string(),
+    )
+}
+
+/// Serve a node's API on a local port and return its base URL
+pub(crate) fn serve(server: ApiServer) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = server.create_router();
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service()),
+    );
+
+    format!("http://{}", addr)
+}
@@ -35,6 +35,14 @@ pub struct BlindedStateEntry {
     pub metadata: HashMap<String, String>,
 }
 
+/// Configuration a storage node is set up with and keeps for its lifetime
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeGenesisConfig {
+    /// SPHINCS+ public key of the authority that may pause the node and approve
+    /// backups and restores (None = cannot be paused or backed up)
+    pub emergency_authority_public_key: Option<Vec<u8>>,
+}
+
 /// Storage node information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StorageNode {
@@ -159,3 +167,87 @@ pub struct EntrySelector {
     /// Offset results
     pub offset: Option<usize>,
 }
+
+/// Wire protocol version spoken between storage node clients and servers
+///
+/// Versions are ordered lexicographically by (major, minor, patch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    /// Major version (incompatible wire changes)
+    pub major: u16,
+
+    /// Minor version (backwards-compatible additions)
+    pub minor: u16,
+
+    /// Patch version (fixes with no wire impact)
+    pub patch: u16,
+}
+
+impl ProtocolVersion {
+    /// Create a new protocol version
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version from its "major.minor.patch" representation
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?.parse().ok()?;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Oldest protocol version this build can still speak
+pub const MIN_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::new(1, 0, 0);
+
+/// Newest protocol version this build speaks
+pub const MAX_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::new(1, 0, 0);
+
+/// Supported protocol version range advertised by a storage node on `GET /version`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolVersionRange {
+    /// Oldest supported version
+    pub min_version: ProtocolVersion,
+
+    /// Newest supported version
+    pub max_version: ProtocolVersion,
+}
+
+impl Default for ProtocolVersionRange {
+    fn default() -> Self {
+        Self {
+            min_version: MIN_PROTOCOL_VERSION,
+            max_version: MAX_PROTOCOL_VERSION,
+        }
+    }
+}
+
+impl ProtocolVersionRange {
+    /// Whether `version` lies within this range (inclusive)
+    pub fn contains(&self, version: ProtocolVersion) -> bool {
+        self.min_version <= version && version <= self.max_version
+    }
+}
+
+impl std::fmt::Display for ProtocolVersionRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.min_version, self.max_version)
+    }
+}
@@ -94,7 +94,7 @@ async fn main() -> Result<(), DsmError> {
     // 4. Initialize the system with the genesis state
     println!("\n=== Step 4: Initializing System with Genesis State ===");
     core_sdk
-        .initialize_with_genesis(genesis_state.clone())
+        .initialize_with_genesis(genesis_state.clone(), None)
         .await?;
     println!("System initialized with genesis state");
 
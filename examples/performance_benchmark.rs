@@ -91,7 +91,7 @@ async fn main() -> Result<(), DsmError> {
 
     // Initialize system with genesis state
     core_sdk
-        .initialize_with_genesis(genesis_state.clone())
+        .initialize_with_genesis(genesis_state.clone(), None)
         .await?;
 
     println!("System initialized, starting benchmarks...\n");
@@ -184,7 +184,7 @@ async fn main() -> Result<(), DsmError> {
 
     // Re-initialize with genesis
     core_sdk
-        .initialize_with_genesis(genesis_state.clone())
+        .initialize_with_genesis(genesis_state.clone(), None)
         .await?;
 
     let iterations = 500;
@@ -279,7 +279,7 @@ async fn main() -> Result<(), DsmError> {
 
     // Re-initialize with genesis
     core_sdk
-        .initialize_with_genesis(genesis_state.clone())
+        .initialize_with_genesis(genesis_state.clone(), None)
         .await?;
 
     let start = Instant::now();
@@ -334,7 +334,7 @@ async fn main() -> Result<(), DsmError> {
 
     // Re-initialize with genesis
     core_sdk
-        .initialize_with_genesis(genesis_state.clone())
+        .initialize_with_genesis(genesis_state.clone(), None)
         .await?;
 
     let start = Instant::now();
@@ -120,7 +120,7 @@ async fn main() -> Result<(), DsmError> {
     // Initialize core system with User 1's primary device genesis
     let user1_genesis = &user_genesis_states[0][0];
     core_sdk
-        .initialize_with_genesis(user1_genesis.clone())
+        .initialize_with_genesis(user1_genesis.clone(), None)
         .await?;
 
     println!("System initialized with User 1's primary device genesis state");
@@ -0,0 +1,39 @@
+// Creates a DSM identity in the browser and polls its inbox on a storage node.
+//
+// Build the bindings first (see README.md); they are loaded from ./pkg.
+import init, { Identity, StorageNodeClient } from "./pkg/dsm_sdk.js";
+
+const log = (message) => {
+  document.getElementById("log").textContent += `${message}\n`;
+};
+
+let identity;
+let client;
+
+async function createIdentity() {
+  const entropy = crypto.getRandomValues(new Uint8Array(32));
+  identity = new Identity(`browser-${Date.now()}`, "browser", entropy);
+  client = new StorageNodeClient(document.getElementById("node-url").value);
+
+  log(`Created identity ${identity.identityId}`);
+  log(`Genesis hash ${identity.genesisHash}`);
+  log(`Storage node healthy: ${await client.checkHealth()}`);
+
+  document.getElementById("poll").disabled = false;
+}
+
+async function pollInbox() {
+  const entries = await identity.pollInbox(client);
+  log(`Inbox has ${entries.length} entries`);
+  for (const entry of entries) {
+    log(`  ${entry.id} from ${entry.sender_genesis_hash}`);
+  }
+}
+
+function report(action) {
+  return () => action().catch((error) => log(`Error: ${error.message}`));
+}
+
+await init();
+document.getElementById("create").addEventListener("click", report(createIdentity));
+document.getElementById("poll").addEventListener("click", report(pollInbox));